@@ -1,6 +1,6 @@
 use crate::ApiResult;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
 /// Summary of a cached asset
@@ -10,16 +10,44 @@ pub struct CachedAssetSummary {
     pub path: String,
     pub size_bytes: u64,
     pub stub_count: usize,
+    /// Packages the asset's stubs belong to
+    #[serde(default)]
+    pub package_count: usize,
     pub version: u32,
     pub created_at: u64,
 }
 
+impl CachedAssetSummary {
+    /// Whether `coordinate` names this asset: a prefix of its hash, a part
+    /// of its path, or a `group:artifact[:version]` Maven coordinate of a JAR
+    /// in a Maven or Gradle repository.
+    pub fn matches(&self, coordinate: &str) -> bool {
+        if self.hash.starts_with(coordinate) {
+            return true;
+        }
+        let path = self.path.replace('\\', "/");
+        let parts: Vec<&str> = coordinate.split(':').collect();
+        let artifact = match parts.as_slice() {
+            [_, artifact] => format!("/{}/", artifact),
+            [_, artifact, version] => format!("/{}/{}/", artifact, version),
+            _ => return path.contains(coordinate),
+        };
+        // Both keep the artifact and version as directories; Gradle keeps the
+        // group as one directory, Maven splits it
+        path.contains(&format!("/{}{}", parts[0], artifact))
+            || path.contains(&format!("/{}{}", parts[0].replace('.', "/"), artifact))
+    }
+}
+
 /// Detailed inspection result for a cached asset
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheInspectResult {
     pub summary: CachedAssetSummary,
     pub metadata_distribution: HashMap<String, usize>,
     pub sample_entries: Vec<String>,
+    /// Stubs per package
+    #[serde(default)]
+    pub packages: BTreeMap<String, usize>,
 }
 
 /// Statistics for the global stub cache
@@ -38,9 +66,48 @@ pub trait StubCacheManager: Send + Sync {
     /// Scan all cached assets returning their summaries
     fn scan_assets(&self) -> Vec<CachedAssetSummary>;
 
-    /// Inspect a specific cached asset by hash (full or prefix)
-    fn inspect_asset(&self, hash_prefix: &str) -> Option<CacheInspectResult>;
+    /// Inspect the first cached asset `coordinate` matches, see
+    /// [`CachedAssetSummary::matches`]
+    fn inspect_asset(&self, coordinate: &str) -> Option<CacheInspectResult>;
+
+    /// Remove the cached assets with the given hashes, returning those removed
+    fn remove_assets(&self, hashes: &[String]) -> ApiResult<Vec<CachedAssetSummary>>;
 
     /// Clear all cached data
     fn clear(&self) -> ApiResult<()>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(path: &str) -> CachedAssetSummary {
+        CachedAssetSummary {
+            hash: "0123456789abcdef".to_string(),
+            path: path.to_string(),
+            size_bytes: 0,
+            stub_count: 0,
+            package_count: 0,
+            version: 1,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn assets_match_hashes_paths_and_coordinates() {
+        let gradle = asset(
+            "/home/u/.gradle/caches/modules-2/files-2.1/com.google.guava/guava/31.1-jre/ab12/guava-31.1-jre.jar",
+        );
+        let maven =
+            asset("/home/u/.m2/repository/com/google/guava/guava/31.1-jre/guava-31.1-jre.jar");
+        for asset in [&gradle, &maven] {
+            assert!(asset.matches("0123"));
+            assert!(asset.matches("guava-31.1"));
+            assert!(asset.matches("com.google.guava:guava"));
+            assert!(asset.matches("com.google.guava:guava:31.1-jre"));
+            assert!(!asset.matches("com.google.guava:guava:31.1"));
+            assert!(!asset.matches("com.google.guava:failureaccess"));
+        }
+        assert!(!asset("/jdk/lib/modules").matches("com.google.guava:guava"));
+    }
+}
@@ -11,6 +11,8 @@ pub enum ApiError {
     InvalidArgument(String),
     #[error("Internal error: {0}")]
     Internal(String),
+    #[error("Cancelled")]
+    Cancelled,
 }
 
 pub type ApiResult<T> = std::result::Result<T, ApiError>;
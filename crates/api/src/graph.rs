@@ -1,21 +1,228 @@
 use crate::ApiResult;
-pub use crate::models::graph::{GraphQuery, QueryResult};
+pub use crate::models::graph::{
+    ExportFormat, ExportSummary, ExternalApiReport, GraphDelta, GraphQuery, NodeNote, QueryResult,
+    SafeDeleteReport,
+};
+use crate::models::symbol::{ReferenceSite, TypeUsageFilter};
+pub use crate::models::validate::{QueryIssue, QueryValidation};
 use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct GraphStats {
     pub node_count: usize,
     pub edge_count: usize,
+    /// Source roots detected (or configured) for the project.
+    #[serde(default)]
+    pub source_roots: Vec<SourceRoot>,
+    /// Toolchains whose standard libraries are indexed, e.g. the JDK.
+    #[serde(default)]
+    pub toolchains: Vec<Toolchain>,
+}
+
+/// An installed toolchain selected for the project.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Toolchain {
+    /// Language the toolchain belongs to, e.g. `java`.
+    pub language: String,
+    pub version: Option<String>,
+    pub home: PathBuf,
+}
+
+/// How a source root was determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceRootOrigin {
+    /// Conventional layout under a module declared by build metadata.
+    BuildMetadata,
+    /// Inferred from package declarations matching the directory layout.
+    PackageLayout,
+    /// Explicitly listed in `naviscope.toml`.
+    Config,
+    /// Annotation processor output under a module declared by build metadata.
+    Generated,
+}
+
+/// A directory from which package paths are resolved.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SourceRoot {
+    pub path: PathBuf,
+    pub origin: SourceRootOrigin,
+}
+
+type ProgressFn = dyn Fn(u64, Option<u64>) + Send + Sync;
+
+/// Cooperative cancellation and progress reporting for a running query.
+///
+/// Clones share state: the caller keeps one handle to cancel while the engine
+/// checks another between traversal steps.
+#[derive(Clone, Default)]
+pub struct QueryControl {
+    cancelled: Arc<AtomicBool>,
+    progress: Option<Arc<ProgressFn>>,
+}
+
+impl QueryControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Receive `(done, total)` as the query advances.
+    pub fn with_progress(
+        mut self,
+        progress: impl Fn(u64, Option<u64>) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn report(&self, done: u64, total: Option<u64>) {
+        if let Some(progress) = &self.progress {
+            progress(done, total);
+        }
+    }
+}
+
+impl std::fmt::Debug for QueryControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueryControl")
+            .field("cancelled", &self.is_cancelled())
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
 }
 
 #[async_trait]
 pub trait GraphService: Send + Sync {
     async fn query(&self, query: &GraphQuery) -> ApiResult<QueryResult>;
+
+    /// Like `query`, but stops with `ApiError::Cancelled` once `control` is
+    /// cancelled and reports progress through it.
+    async fn query_with_control(
+        &self,
+        query: &GraphQuery,
+        control: &QueryControl,
+    ) -> ApiResult<QueryResult> {
+        if control.is_cancelled() {
+            return Err(crate::ApiError::Cancelled);
+        }
+        self.query(query).await
+    }
     async fn get_stats(&self) -> ApiResult<GraphStats>;
 
+    /// Check a query in its JSON form without running it. Engines may also
+    /// check it against the index, e.g. that the symbols it names exist.
+    async fn validate_query(&self, query: &serde_json::Value) -> ApiResult<QueryValidation> {
+        Ok(crate::models::validate_query(query))
+    }
+
+    /// Identifier of the current graph snapshot. It increases every time an
+    /// index update publishes a new snapshot.
+    async fn graph_epoch(&self) -> ApiResult<u64>;
+
+    /// Wait until an index update commits a snapshot other than `epoch` and
+    /// return its epoch; `None` if the engine does not report commits.
+    async fn wait_for_commit(&self, _epoch: u64) -> ApiResult<Option<u64>> {
+        Ok(None)
+    }
+
     /// Get a fully hydrated display node by its FQN.
     async fn get_node_display(
         &self,
         fqn: &str,
     ) -> ApiResult<Option<crate::models::DisplayGraphNode>>;
+
+    /// Check whether the symbol `fqn` can be removed: remaining references
+    /// outside tests, overriding methods and reflective-use hints.
+    async fn safe_delete(&self, fqn: &str) -> ApiResult<SafeDeleteReport>;
+
+    /// References to the symbol `fqn`, tagged by whether they only name it as a type.
+    async fn references(
+        &self,
+        fqn: &str,
+        type_usages: TypeUsageFilter,
+    ) -> ApiResult<Vec<ReferenceSite>>;
+
+    /// Notes attached to the node `fqn`, oldest first
+    async fn notes(&self, _fqn: &str) -> ApiResult<Vec<NodeNote>> {
+        Ok(Vec::new())
+    }
+
+    /// Attach a note and tags to the node `fqn`. They are stored next to the
+    /// index and outlive rebuilds.
+    async fn annotate(&self, _fqn: &str, _text: &str, _tags: &[String]) -> ApiResult<NodeNote> {
+        Err(crate::ApiError::Internal(
+            "this engine does not store notes".to_string(),
+        ))
+    }
+
+    /// Remove the note `id` from `fqn`; `false` if there was none.
+    async fn remove_note(&self, _fqn: &str, _id: u64) -> ApiResult<bool> {
+        Ok(false)
+    }
+
+    /// Save the current graph as the snapshot `name` and return its file.
+    async fn save_snapshot(&self, name: &str) -> ApiResult<PathBuf>;
+
+    /// Changes from a saved snapshot to the current graph. `reference` is a
+    /// snapshot name, the path of a snapshot file or a git revision.
+    async fn diff(&self, reference: &str) -> ApiResult<GraphDelta>;
+
+    /// Index the git revision `rev` of the project, without checking it out,
+    /// into the snapshot named after its commit, and return its file.
+    async fn index_revision(&self, _rev: &str) -> ApiResult<PathBuf> {
+        Err(crate::ApiError::Internal(
+            "this engine does not index revisions".to_string(),
+        ))
+    }
+
+    /// Changes from the snapshot or git revision `old` to `new`. Revisions
+    /// without a snapshot are indexed first.
+    async fn diff_between(&self, _old: &str, _new: &str) -> ApiResult<GraphDelta> {
+        Err(crate::ApiError::Internal(
+            "this engine does not compare snapshots".to_string(),
+        ))
+    }
+
+    /// Write the whole graph in `format` to files in `out_dir`.
+    async fn export_graph(
+        &self,
+        _format: ExportFormat,
+        _out_dir: &Path,
+    ) -> ApiResult<ExportSummary> {
+        Err(crate::ApiError::Internal(
+            "this engine does not export graphs".to_string(),
+        ))
+    }
+
+    /// Write the whole index to the single, portable file `path`, which
+    /// another machine can open read-only like an index.
+    async fn export_bundle(&self, _path: &Path) -> ApiResult<ExportSummary> {
+        Err(crate::ApiError::Internal(
+            "this engine does not export bundles".to_string(),
+        ))
+    }
+
+    /// External symbols the project refers to, grouped by library and
+    /// package with reference counts. `artifact` keeps only libraries whose
+    /// name contains it; `top` caps the symbols listed per package.
+    async fn external_api_usage(
+        &self,
+        _artifact: Option<&str>,
+        _top: usize,
+    ) -> ApiResult<ExternalApiReport> {
+        Err(crate::ApiError::Internal(
+            "this engine does not track external assets".to_string(),
+        ))
+    }
 }
@@ -9,8 +9,12 @@ pub mod semantic;
 // Re-export commonly used types
 pub use cache::{CacheInspectResult, CacheStats, CachedAssetSummary, StubCacheManager};
 pub use error::{ApiError, ApiResult};
-pub use graph::GraphService;
-pub use lifecycle::{EngineLifecycle, EngineWatchHandle};
+pub use graph::{GraphService, QueryControl};
+pub use lifecycle::{
+    BuildResume, ChangeSubscription, ChangeSummary, DocumentEvent, EngineLifecycle,
+    EngineWatchHandle, EpochProgress, HostSettings, IndexControl, IndexPhase, IndexProgress,
+    IndexWarning, ProgressSubscription, WarningSeverity,
+};
 pub use models::*;
 pub use navigation::NavigationService;
 pub use semantic::{CallHierarchyAnalyzer, ReferenceAnalyzer, SymbolInfoProvider, SymbolNavigator};
@@ -1,15 +1,484 @@
 use crate::ApiResult;
+use crate::models::graph::{NodeSource, ResolutionStatus};
+use crate::models::{DetailLimits, EdgeType, NodeKind};
 use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Time hosts give `EngineLifecycle::shutdown` when they stop
+pub const SHUTDOWN_DEADLINE: Duration = Duration::from_secs(10);
 
 pub trait EngineWatchHandle: Send + Sync {
     fn stop(&self);
 }
 
+/// Stage of an indexing run, in execution order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexPhase {
+    /// Walking the tree and reading changed files
+    Scanning,
+    /// Parsing sources and collecting declarations
+    Parsing,
+    /// Resolving references and lowering them to graph operations
+    Resolving,
+    /// Applying the new graph and persisting it
+    Committing,
+}
+
+impl IndexPhase {
+    pub fn label(&self) -> &'static str {
+        match self {
+            IndexPhase::Scanning => "Scanning",
+            IndexPhase::Parsing => "Parsing",
+            IndexPhase::Resolving => "Resolving",
+            IndexPhase::Committing => "Committing",
+        }
+    }
+}
+
+/// How much of a file a warning says was lost
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningSeverity {
+    /// Indexed in full, but worth knowing
+    Info,
+    /// Some constructs were skipped
+    Warning,
+    /// Nothing of the file made it into the index
+    Error,
+}
+
+/// A problem met while indexing a file that did not stop the run, e.g. a
+/// declaration dropped because of a syntax error
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct IndexWarning {
+    pub path: String,
+    pub phase: IndexPhase,
+    pub severity: WarningSeverity,
+    pub message: String,
+}
+
+/// Position within the current phase of an indexing run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct IndexProgress {
+    pub phase: IndexPhase,
+    pub done: usize,
+    pub total: usize,
+}
+
+impl IndexProgress {
+    /// Completion of the current phase, 0-100
+    pub fn percentage(&self) -> u32 {
+        if self.total == 0 {
+            return 0;
+        }
+        (self.done.min(self.total) * 100 / self.total) as u32
+    }
+
+    /// Completion of the whole run, 0-100, giving each phase a share of the
+    /// range so that it only moves forward
+    pub fn overall_percentage(&self) -> u32 {
+        let (start, end) = match self.phase {
+            IndexPhase::Scanning => (0, 10),
+            IndexPhase::Parsing => (10, 50),
+            IndexPhase::Resolving => (50, 90),
+            IndexPhase::Committing => (90, 100),
+        };
+        start + (end - start) * self.percentage() / 100
+    }
+}
+
+/// Progress of the update building on a committed snapshot
+///
+/// An update is known by the epoch of the snapshot committed when it
+/// started, so it can be asked about before it commits anything. Full builds
+/// commit in batches and keep the epoch they started from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EpochProgress {
+    /// Epoch of the snapshot the update started from
+    pub epoch: u64,
+    /// Files handed to the update
+    pub submitted: usize,
+    /// Files whose results are in a committed snapshot
+    pub committed: usize,
+    /// Position within the phase running now
+    pub current: IndexProgress,
+    /// Completion of the whole update, 0-100. Unlike
+    /// `IndexProgress::overall_percentage` it counts committed batches and
+    /// never goes back.
+    pub percent: u32,
+    /// Committed, failed or cancelled
+    pub finished: bool,
+}
+
+type IndexProgressFn = dyn Fn(IndexProgress) + Send + Sync;
+
+/// Cooperative cancellation and progress reporting for an indexing run.
+///
+/// Clones share state, like `QueryControl`.
+#[derive(Clone, Default)]
+pub struct IndexControl {
+    cancelled: Arc<AtomicBool>,
+    progress: Option<Arc<IndexProgressFn>>,
+}
+
+impl IndexControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Receive progress as files move through the phases.
+    pub fn with_progress(
+        mut self,
+        progress: impl Fn(IndexProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn report(&self, phase: IndexPhase, done: usize, total: usize) {
+        if let Some(progress) = &self.progress {
+            progress(IndexProgress { phase, done, total });
+        }
+    }
+}
+
+impl std::fmt::Debug for IndexControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IndexControl")
+            .field("cancelled", &self.is_cancelled())
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+/// An interrupted full build that the next rebuild will resume
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BuildResume {
+    /// Files already committed to the partial index
+    pub committed_files: usize,
+    /// Epoch of the last committed batch
+    pub epoch: u64,
+}
+
+/// Project settings that concern the process hosting the engine
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct HostSettings {
+    /// Fixed port for the MCP server, if configured
+    pub mcp_port: Option<u16>,
+    /// Address the MCP server binds to, if configured
+    #[serde(default)]
+    pub mcp_host: Option<std::net::IpAddr>,
+    /// Bearer token MCP clients must present, if configured
+    #[serde(default)]
+    pub mcp_token: Option<String>,
+    /// Argument defaults per MCP tool, keyed by tool name
+    #[serde(default)]
+    pub mcp_tools: BTreeMap<String, ToolDefaults>,
+    /// Size limits of node details in LSP hovers, [`DetailLimits::LSP`] if unset
+    #[serde(default)]
+    pub lsp_detail: Option<DetailLimits>,
+    /// Size limits of node details in MCP responses, [`DetailLimits::MCP`] if unset
+    #[serde(default)]
+    pub mcp_detail: Option<DetailLimits>,
+}
+
+/// Defaults for an MCP tool's arguments, used when a request leaves them unset.
+/// Tools ignore the arguments they do not take.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ToolDefaults {
+    /// Node kinds to return
+    pub kind: Option<Vec<NodeKind>>,
+    /// Relationship types to follow
+    pub edge_type: Option<Vec<EdgeType>>,
+    /// Node sources to return
+    pub sources: Option<Vec<NodeSource>>,
+    /// Resolution statuses to return
+    pub statuses: Option<Vec<ResolutionStatus>>,
+    /// Maximum number of results
+    pub limit: Option<usize>,
+}
+
+/// A tool call of an MCP client, kept in the project's audit log
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AuditEntry {
+    /// Seconds since the Unix epoch
+    pub at: u64,
+    /// Name the client gave when it connected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    pub tool: String,
+    /// Arguments of the call, as sent
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub arguments: serde_json::Value,
+    /// Why the call failed; unset when it succeeded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Batching of file watcher updates, adapted to their latency
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct WatchFlow {
+    /// Maximum number of files applied per update
+    pub limit: usize,
+    /// Configured bounds of `limit`
+    pub min: usize,
+    pub max: usize,
+    /// Update latency the limit is adapted to
+    pub target_latency_ms: u64,
+    /// Changed files waiting for an update
+    pub backlog: usize,
+    /// Files in the latest update
+    pub last_batch: usize,
+    pub last_latency_ms: u64,
+    /// Recent changes of `limit`, oldest first
+    pub decisions: Vec<FlowDecision>,
+}
+
+/// A change of the watcher's batch limit
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FlowDecision {
+    pub from: usize,
+    pub to: usize,
+    pub reason: FlowReason,
+    /// Latency of the update that triggered the change
+    pub latency_ms: u64,
+    /// Files waiting after that update
+    pub backlog: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlowReason {
+    /// The update exceeded the target latency
+    Slow,
+    /// Files were waiting and updates stayed well below the target
+    Backlog,
+}
+
+/// Counters of the engine's index updates since it started
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RuntimeMetrics {
+    /// Files handed to index updates
+    pub runnable: u64,
+    /// File updates held back because the document had unsaved edits
+    pub deferred: u64,
+    /// Snapshots committed
+    pub committed: u64,
+    /// Time to execute an index update, a full build or an incremental one
+    pub execute_latency: LatencyHistogram,
+}
+
+impl RuntimeMetrics {
+    /// The metrics in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        use std::fmt::Write;
+
+        let mut out = String::new();
+        let counters = [
+            (
+                "naviscope_runnable_files_total",
+                "Files handed to index updates",
+                self.runnable,
+            ),
+            (
+                "naviscope_deferred_files_total",
+                "File updates held back by unsaved edits",
+                self.deferred,
+            ),
+            (
+                "naviscope_commits_total",
+                "Index snapshots committed",
+                self.committed,
+            ),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+
+        let name = "naviscope_execute_duration_seconds";
+        let latency = &self.execute_latency;
+        let _ = writeln!(out, "# HELP {} Time to execute an index update", name);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        for bucket in &latency.buckets {
+            let le = bucket.le_ms as f64 / 1000.0;
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, le, bucket.count);
+        }
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, latency.count);
+        let _ = writeln!(out, "{}_sum {}", name, latency.sum_ms as f64 / 1000.0);
+        let _ = writeln!(out, "{}_count {}", name, latency.count);
+        out
+    }
+}
+
+/// Cumulative latency histogram
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LatencyHistogram {
+    /// Observations at or below each bound, by increasing bound
+    pub buckets: Vec<LatencyBucket>,
+    /// All observations, including those above the last bound
+    pub count: u64,
+    pub sum_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LatencyBucket {
+    pub le_ms: u64,
+    pub count: u64,
+}
+
+/// What one committed update changed
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ChangeSummary {
+    /// Epoch of the committed graph
+    pub epoch: u64,
+    /// Indexed files whose content or declarations changed, sorted
+    pub files: Vec<String>,
+    /// FQNs of symbols declared by the update
+    pub added: Vec<String>,
+    /// FQNs of symbols that no longer exist
+    pub removed: Vec<String>,
+}
+
+/// Editor event for a document open in a client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentEvent {
+    /// Opened with the content of the file on disk
+    Opened { version: i32 },
+    /// Edited in the editor; the file on disk no longer matches
+    Changed { version: i32 },
+    /// Written to disk
+    Saved,
+    /// Closed, discarding unsaved edits
+    Closed,
+}
+
+/// Committed updates of an engine, from the moment of subscribing
+#[async_trait]
+pub trait ChangeSubscription: Send {
+    /// Wait for the next update that changes indexed files. Updates that
+    /// happen between calls are merged into one summary.
+    async fn next(&mut self) -> ApiResult<ChangeSummary>;
+}
+
+/// Progress of the updates of an engine, from the moment of subscribing
+#[async_trait]
+pub trait ProgressSubscription: Send {
+    /// Wait for the progress to change. Changes that happen between calls
+    /// are skipped; the latest one is returned.
+    async fn next(&mut self) -> ApiResult<EpochProgress>;
+}
+
 #[async_trait]
 pub trait EngineLifecycle: Send + Sync {
     /// Rebuild the index from scratch
     async fn rebuild(&self) -> ApiResult<()>;
 
+    /// Like `rebuild`, but reports phase progress through `control` and stops
+    /// with `ApiError::Cancelled` once it is cancelled.
+    async fn rebuild_with_control(&self, control: &IndexControl) -> ApiResult<()> {
+        if control.is_cancelled() {
+            return Err(crate::ApiError::Cancelled);
+        }
+        self.rebuild().await
+    }
+
+    /// Re-index `files`; paths that no longer exist are removed from the index
+    async fn update_files(&self, files: Vec<PathBuf>) -> ApiResult<()>;
+
+    /// Track the editor state of `path`. Updates of a document with unsaved
+    /// edits are held back and applied once it is saved or closed.
+    async fn document_event(&self, _path: &Path, _event: DocumentEvent) -> ApiResult<()> {
+        Ok(())
+    }
+
+    /// Interrupted full build that `rebuild` would resume, if any
+    async fn pending_resume(&self) -> ApiResult<Option<BuildResume>> {
+        Ok(None)
+    }
+
+    /// Re-read the project configuration (`naviscope.toml`)
+    async fn reload_config(&self) -> ApiResult<()>;
+
+    /// Settings for the hosting server, taken from the project configuration
+    async fn host_settings(&self) -> ApiResult<HostSettings> {
+        Ok(HostSettings::default())
+    }
+
+    /// Batching state of the file watcher and its recent adaptations
+    async fn watch_flow(&self) -> ApiResult<WatchFlow> {
+        Ok(WatchFlow::default())
+    }
+
+    /// Counters and latencies of the index updates since the engine started
+    async fn runtime_metrics(&self) -> ApiResult<RuntimeMetrics> {
+        Ok(RuntimeMetrics::default())
+    }
+
+    /// Warnings of the indexed files, as left by the runs that last
+    /// indexed each of them
+    async fn index_warnings(&self) -> ApiResult<Vec<IndexWarning>> {
+        Ok(Vec::new())
+    }
+
+    /// Append `entry` to the project's audit log
+    async fn record_audit(&self, _entry: AuditEntry) -> ApiResult<()> {
+        Ok(())
+    }
+
+    /// Entries of the audit log, oldest first
+    async fn audit_log(&self) -> ApiResult<Vec<AuditEntry>> {
+        Ok(Vec::new())
+    }
+
+    /// Follow the updates committed from now on, e.g. by the file watcher
+    async fn subscribe_changes(&self) -> ApiResult<Box<dyn ChangeSubscription>> {
+        Err(crate::ApiError::Internal(
+            "this engine does not report changes".to_string(),
+        ))
+    }
+
+    /// Progress of the update that started from the snapshot of `epoch`.
+    /// `None` if there is none, or a later update has started since.
+    async fn epoch_progress(&self, _epoch: u64) -> ApiResult<Option<EpochProgress>> {
+        Ok(None)
+    }
+
+    /// Follow the progress of the updates run from now on
+    async fn subscribe_progress(&self) -> ApiResult<Box<dyn ProgressSubscription>> {
+        Err(crate::ApiError::Internal(
+            "this engine does not report progress".to_string(),
+        ))
+    }
+
     /// Load the index from disk
     async fn load(&self) -> ApiResult<bool>;
 
@@ -24,4 +493,11 @@ pub trait EngineLifecycle: Send + Sync {
 
     /// Clear the index for the current project
     async fn clear_index(&self) -> ApiResult<()>;
+
+    /// Stop taking index updates, let the running one commit and write the
+    /// index to disk, giving up after `deadline`. Updates asked for later
+    /// fail with `ApiError::Cancelled`.
+    async fn shutdown(&self, _deadline: Duration) -> ApiResult<()> {
+        Ok(())
+    }
 }
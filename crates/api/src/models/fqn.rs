@@ -14,3 +14,30 @@ pub trait FqnReader {
     fn resolve_node(&self, id: FqnId) -> Option<FqnNode>;
     fn resolve_atom(&self, atom: Symbol) -> &str;
 }
+
+/// Separator between the language tag and the FQN of a symbol ID
+pub const SYMBOL_ID_SEPARATOR: char = ':';
+
+/// Language-tagged symbol ID, e.g. `java:com.app.Foo#bar(int)`.
+///
+/// FQNs follow each language's naming convention and may collide across
+/// languages; the tag keeps IDs unique and stable for other tools.
+pub fn symbol_id(lang: &str, fqn: &str) -> String {
+    format!("{lang}{SYMBOL_ID_SEPARATOR}{fqn}")
+}
+
+/// Split a symbol ID into its language tag and FQN.
+///
+/// Returns `None` for strings without a well-formed tag (lowercase ASCII
+/// letters, digits and `-`). Plain FQNs may still parse, e.g. Gradle's
+/// `project:app`, so callers resolve the whole string first.
+pub fn parse_symbol_id(id: &str) -> Option<(&str, &str)> {
+    let (lang, fqn) = id.split_once(SYMBOL_ID_SEPARATOR)?;
+    let well_formed = lang
+        .bytes()
+        .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-');
+    if lang.is_empty() || fqn.is_empty() || !well_formed {
+        return None;
+    }
+    Some((lang, fqn))
+}
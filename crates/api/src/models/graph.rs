@@ -22,6 +22,9 @@ pub enum NodeKind {
     Dependency,
     Task,
     Plugin,
+    // Source files
+    /// An indexed file, declaring its top-level symbols
+    File,
     // Extension
     Custom(#[schemars(with = "String")] String),
 }
@@ -43,6 +46,7 @@ impl From<&str> for NodeKind {
             "dependency" => NodeKind::Dependency,
             "task" => NodeKind::Task,
             "plugin" => NodeKind::Plugin,
+            "file" => NodeKind::File,
             _ => NodeKind::Custom(s.to_string()),
         }
     }
@@ -65,6 +69,7 @@ impl ToString for NodeKind {
             NodeKind::Dependency => "dependency".to_string(),
             NodeKind::Task => "task".to_string(),
             NodeKind::Plugin => "plugin".to_string(),
+            NodeKind::File => "file".to_string(),
             NodeKind::Custom(s) => s.to_string(),
         }
     }
@@ -82,16 +87,70 @@ pub enum EdgeType {
     DecoratedBy,
     // Build system relationships
     UsesDependency,
+    // Module system relationships
+    /// A module reads another module (`requires`)
+    Requires,
+    /// A module exports one of its packages (`exports`)
+    Exports,
+    // Naming relationships
+    /// The source is another name for the target: a re-export, a type alias
+    /// or a static import. Navigation resolves through it to the target.
+    AliasOf,
+    // Code generation relationships
+    /// The source was generated by an annotation processor from the target,
+    /// e.g. `QOrder` from the `@Entity` class `Order`
+    GeneratedFrom,
+    // File relationships
+    /// A file declares a top-level symbol: a type, or a build element not
+    /// nested in another element of the same file
+    Declares,
+    // Cross-language relationships
+    /// The source reaches the target across a language boundary, e.g. an
+    /// HTTP call to the handler of its route, see `CrossLanguageResolver`
+    CrossLanguage,
+    // Dependency injection relationships
+    /// The source has the target injected by a container: the declared type
+    /// of an injected field or constructor parameter, and the beans that can
+    /// be injected there
+    Injects,
+    /// The source is a bean that is injected where the target is expected,
+    /// e.g. a `@Service` class and the interfaces it implements
+    BeanOf,
+    // Web relationships
+    /// A route node is served by the target handler method
+    HandledBy,
+    // Persistence relationships
+    /// The source is stored in the target: an entity class in its table, a
+    /// persistent field in its column
+    MapsTo,
+    // Test relationships
+    /// A test method exercises the target class, see `GraphQuery::TestsFor`
+    Tests,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, JsonSchema)]
 pub struct GraphEdge {
     pub edge_type: EdgeType,
+    /// The source refers to the target although its module cannot read the
+    /// target's module, or the target's package is not exported to it
+    #[serde(default)]
+    pub inaccessible: bool,
 }
 
 impl GraphEdge {
     pub fn new(edge_type: EdgeType) -> Self {
-        Self { edge_type }
+        Self {
+            edge_type,
+            inaccessible: false,
+        }
+    }
+
+    /// Edge for a reference that module visibility rules forbid
+    pub fn inaccessible(edge_type: EdgeType) -> Self {
+        Self {
+            edge_type,
+            inaccessible: true,
+        }
     }
 }
 
@@ -142,6 +201,10 @@ pub enum ResolutionStatus {
     /// Full details known from source code or complete parsing
     #[default]
     Resolved,
+    /// Declared in a source file with syntax errors; only the declarations
+    /// that parsed cleanly were kept, so members may be missing
+    #[serde(rename = "partial_parse")]
+    PartialParse,
 }
 
 #[derive(Debug, Clone)]
@@ -218,6 +281,9 @@ pub struct DisplaySymbolLocation {
 #[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
 pub struct DisplayGraphNode {
     pub id: String,
+    /// Language-tagged ID (`java:com.app.Foo#bar(int)`), see [`super::fqn::symbol_id`]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub symbol_id: String,
     pub name: String,
     pub kind: NodeKind,
     pub lang: String,
@@ -225,6 +291,9 @@ pub struct DisplayGraphNode {
     pub source: NodeSource,
     #[serde(default)]
     pub status: ResolutionStatus,
+    /// Declared in a generated source file, see [`crate::graph::SourceRootOrigin::Generated`]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub generated: bool,
     pub location: Option<DisplaySymbolLocation>,
 
     // Rendering fields
@@ -249,8 +318,17 @@ pub enum GraphQuery {
         kind: Vec<NodeKind>,
         #[serde(default)]
         sources: Vec<NodeSource>,
+        /// Resolution statuses to keep, e.g. `stubbed` for library stubs
+        #[serde(default)]
+        statuses: Vec<ResolutionStatus>,
         #[serde(default)]
         modifiers: Vec<String>,
+        /// Group matching nodes and return per-group counts instead of nodes
+        #[serde(default)]
+        group_by: Option<GroupBy>,
+        /// Return only the number of matching nodes
+        #[serde(default)]
+        count: bool,
     },
 
     /// Search for symbols
@@ -260,12 +338,48 @@ pub enum GraphQuery {
         kind: Vec<NodeKind>,
         #[serde(default)]
         sources: Vec<NodeSource>,
+        /// Resolution statuses to keep, e.g. `stubbed` for library stubs
+        #[serde(default)]
+        statuses: Vec<ResolutionStatus>,
+        /// Modifiers every node must have, e.g. `vulnerable` for dependencies
+        /// with a known advisory
+        #[serde(default)]
+        modifiers: Vec<String>,
+        /// Maximum number of nodes returned; ignored when aggregating
+        #[serde(default = "default_limit")]
+        limit: usize,
+        #[serde(default)]
+        group_by: Option<GroupBy>,
+        #[serde(default)]
+        count: bool,
+    },
+
+    /// Fuzzy symbol search: `pattern` is matched against simple names, or
+    /// against FQNs when it contains a `.`, as an in-order abbreviation that
+    /// favours camel humps (`UsrCtl` finds `UserController`). Nodes are
+    /// ranked best match first.
+    Fuzzy {
+        pattern: String,
+        #[serde(default)]
+        kind: Vec<NodeKind>,
+        #[serde(default)]
+        sources: Vec<NodeSource>,
+        /// Resolution statuses to keep, e.g. `stubbed` for library stubs
+        #[serde(default)]
+        statuses: Vec<ResolutionStatus>,
+        /// Maximum number of nodes returned
         #[serde(default = "default_limit")]
         limit: usize,
     },
 
     /// Inspect node details (Source & Metadata)
-    Cat { fqn: String },
+    Cat {
+        fqn: String,
+        /// Shorten long signatures and summarize members beyond these limits;
+        /// full details when null
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limits: Option<DetailLimits>,
+    },
 
     /// Find dependencies (outgoing) or dependents (incoming)
     Deps {
@@ -276,13 +390,229 @@ pub enum GraphQuery {
         rev: bool,
         #[serde(default)]
         edge_types: Vec<EdgeType>,
+        #[serde(default)]
+        sources: Vec<NodeSource>,
+        #[serde(default)]
+        statuses: Vec<ResolutionStatus>,
+        #[serde(default)]
+        group_by: Option<GroupBy>,
+        #[serde(default)]
+        count: bool,
+    },
+
+    /// Transitive dependents: everything that may break if `fqn` changes.
+    /// Returns the affected classes and methods with per-module counts.
+    Impact {
+        fqn: String,
+        /// Maximum number of incoming edges followed from `fqn`
+        #[serde(default = "default_impact_depth")]
+        max_depth: usize,
+        /// Edge types followed; all but `Contains` and `Declares` if empty
+        #[serde(default)]
+        edge_types: Vec<EdgeType>,
+    },
+
+    /// Dependency cycles between project classes, or packages when
+    /// `packages` is set. Members count as their enclosing class. Nodes are
+    /// ordered by cycle, largest first, with one group per cycle.
+    Cycles {
+        /// Only consider nodes inside this package, module or class
+        #[serde(default)]
+        scope_fqn: Option<String>,
+        /// Edge types followed; all but `Contains` if empty
+        #[serde(default)]
+        edge_types: Vec<EdgeType>,
+        /// Report cycles between packages instead of classes
+        #[serde(default)]
+        packages: bool,
+    },
+
+    /// Shortest chain of edges leading from `from` to `to`. Nodes are
+    /// returned in chain order, each followed by the edge to the next one;
+    /// the result is empty when `to` is not reachable.
+    Path {
+        from: String,
+        to: String,
+        /// Edge types followed; all if empty
+        #[serde(default)]
+        edge_types: Vec<EdgeType>,
+        /// Maximum number of edges in the chain
+        #[serde(default = "default_path_depth")]
+        max_depth: usize,
     },
+
+    /// Graph metrics: counts per node kind and edge type, fan-in/fan-out
+    /// distributions, largest classes and most-referenced symbols. The
+    /// result carries them in `metrics`.
+    Stats {
+        /// Length of the ranked lists
+        #[serde(default = "default_stats_top")]
+        top: usize,
+    },
+
+    /// Full-text search: lines of indexed source files containing
+    /// `pattern` literally. Hits are returned in `hits`, and their enclosing
+    /// nodes in `nodes`.
+    Text {
+        pattern: String,
+        #[serde(default)]
+        ignore_case: bool,
+        /// Maximum number of hits returned
+        #[serde(default = "default_limit")]
+        limit: usize,
+    },
+
+    /// Groups of project methods whose normalized token streams are nearly
+    /// the same, e.g. copies with renamed variables. Nodes are ordered by
+    /// group, largest methods first, with one group per set of clones.
+    Duplicates {
+        /// Methods with fewer tokens are ignored
+        #[serde(default = "default_min_tokens")]
+        min_tokens: usize,
+    },
+
+    /// Public project types and methods nothing is known to use: no edge
+    /// reaches them from outside, no other file mentions their name and
+    /// they override nothing. Entry points, such as tests, `main` and
+    /// framework callbacks, and test sources are left out. Members of an
+    /// unused type are reported through the type. Nodes are ordered by FQN.
+    Dead {
+        /// Only report symbols inside this package, module or class
+        #[serde(default)]
+        scope_fqn: Option<String>,
+        /// Entry points besides the built-in and configured ones:
+        /// `@Name` for an annotation, a plain name for a method
+        #[serde(default)]
+        entry_points: Vec<String>,
+    },
+
+    /// Test methods exercising `fqn`: a class, the class of a member, or
+    /// every class in a package or module. Nodes are the test methods, by
+    /// FQN, and `edges` their `Tests` edges to the classes they exercise.
+    TestsFor { fqn: String },
 }
 
 fn default_limit() -> usize {
     20
 }
 
+fn default_impact_depth() -> usize {
+    5
+}
+
+fn default_path_depth() -> usize {
+    10
+}
+
+fn default_stats_top() -> usize {
+    10
+}
+
+fn default_min_tokens() -> usize {
+    50
+}
+
+/// Grouping key for aggregated queries
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    /// Node kind (class, method, ...)
+    Kind,
+    /// Nearest enclosing package
+    Package,
+    /// Nearest enclosing module
+    Module,
+}
+
+/// Number of matching nodes sharing a group key
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QueryGroup {
+    pub key: String,
+    pub count: usize,
+}
+
+/// Summary of per-node counts
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Distribution {
+    pub mean: f64,
+    pub median: usize,
+    pub p90: usize,
+    pub max: usize,
+}
+
+impl Distribution {
+    pub fn of(mut values: Vec<usize>) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        values.sort_unstable();
+        let at = |quantile: usize| values[(values.len() - 1) * quantile / 100];
+        Self {
+            mean: values.iter().sum::<usize>() as f64 / values.len() as f64,
+            median: at(50),
+            p90: at(90),
+            max: values[values.len() - 1],
+        }
+    }
+}
+
+/// A symbol ranked by a count
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RankedSymbol {
+    pub fqn: String,
+    pub kind: NodeKind,
+    pub count: usize,
+}
+
+/// Structural metrics of the graph, see `GraphQuery::Stats`.
+///
+/// Fan-in, fan-out and rankings cover project types and callables and
+/// ignore `Contains` edges; class size is the number of direct members.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GraphMetrics {
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub nodes_by_kind: Vec<QueryGroup>,
+    pub edges_by_type: Vec<QueryGroup>,
+    pub fan_in: Distribution,
+    pub fan_out: Distribution,
+    pub largest_classes: Vec<RankedSymbol>,
+    pub most_referenced: Vec<RankedSymbol>,
+    /// Edges whose target could not be resolved to a known symbol
+    pub unresolved_edges: usize,
+    /// Project types whose file changed in the most commits, set when git
+    /// history is enabled
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hot_spots: Vec<RankedSymbol>,
+}
+
+/// A line matching a `GraphQuery::Text` pattern
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TextHit {
+    pub path: String,
+    /// Zero-based line and byte column of the match
+    pub line: usize,
+    pub column: usize,
+    /// The matching line, trimmed
+    pub text: String,
+    /// FQN of the innermost node enclosing the match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+}
+
+/// A note attached to a node through `annotate`, kept across sessions
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NodeNote {
+    /// Identifies the note among those of its node
+    pub id: u64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Seconds since the Unix epoch
+    pub created_at: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryResultEdge {
     #[serde(with = "super::util::serde_arc_str")]
@@ -292,14 +622,373 @@ pub struct QueryResultEdge {
     pub data: GraphEdge,
 }
 
+/// Last change of the lines of a node, from git history
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NodeHistory {
+    /// Hash of the last commit changing the node
+    pub commit: String,
+    pub author: String,
+    /// Seconds since the Unix epoch
+    pub committed_at: u64,
+    /// Whole days since `committed_at`
+    pub age_days: u64,
+    /// Commits that changed the node
+    pub commits: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct QueryResult {
     pub nodes: Vec<DisplayGraphNode>,
     pub edges: Vec<QueryResultEdge>,
+    /// Total number of matches, set for aggregated queries and for those
+    /// that matched more than the result budget
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    /// Per-group counts, set when `group_by` is requested
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<QueryGroup>,
+    /// Set by `GraphQuery::Stats`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<GraphMetrics>,
+    /// Set by `GraphQuery::Text`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hits: Vec<TextHit>,
+    /// Set by `GraphQuery::Cat`: notes attached to the node
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<NodeNote>,
+    /// Set by `GraphQuery::Cat` when git history is enabled: the last
+    /// change of the node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<NodeHistory>,
+    /// Set by `GraphQuery::Cat` with limits: members of the node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub members: Option<MemberSummary>,
+    /// Set by `GraphQuery::Cat` with limits when a signature was shortened
+    /// or members were left out; the query without limits has them all
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub abridged: bool,
+    /// Set when the query ran out of time or matched more than the result
+    /// budget: `nodes`, `edges` and `hits` are only part of the answer
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
 }
 
 impl QueryResult {
     pub fn new(nodes: Vec<DisplayGraphNode>, edges: Vec<QueryResultEdge>) -> Self {
-        Self { nodes, edges }
+        Self {
+            nodes,
+            edges,
+            ..Default::default()
+        }
+    }
+
+    /// Result carrying only aggregate counts
+    pub fn aggregated(total: usize, groups: Vec<QueryGroup>) -> Self {
+        Self {
+            total: Some(total),
+            groups,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_aggregated(&self) -> bool {
+        self.total.is_some()
+    }
+}
+
+/// Size limits of the node details shown to a client, so that huge
+/// generated classes keep hovers and tool responses readable
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+pub struct DetailLimits {
+    /// Characters of a signature before its generic arguments are elided
+    pub signature_chars: usize,
+    /// Members listed before the others are only counted
+    pub members: usize,
+}
+
+impl DetailLimits {
+    /// Editor hovers, read at a glance
+    pub const LSP: Self = Self {
+        signature_chars: 120,
+        members: 20,
+    };
+    /// Agents, which can take more but pay for every token
+    pub const MCP: Self = Self {
+        signature_chars: 400,
+        members: 100,
+    };
+    /// Everything, when a client asks for the full details
+    pub const UNLIMITED: Self = Self {
+        signature_chars: usize::MAX,
+        members: usize::MAX,
+    };
+
+    /// `signature` shortened to `signature_chars`, or `None` when it fits.
+    ///
+    /// Generic arguments are elided innermost first, so that
+    /// `Map<String, List<Foo>>` becomes `Map<String, List<…>>`, then
+    /// `Map<…>`; a signature still too long is cut.
+    pub fn abridge(&self, signature: &str) -> Option<String> {
+        let fits = |text: &str| text.chars().count() <= self.signature_chars;
+        if fits(signature) {
+            return None;
+        }
+        let mut text = signature.to_string();
+        while !fits(&text) {
+            match collapse_deepest_generics(&text) {
+                Some(shorter) => text = shorter,
+                None => break,
+            }
+        }
+        if !fits(&text) {
+            text = text
+                .chars()
+                .take(self.signature_chars.saturating_sub(1))
+                .collect();
+            text.push('…');
+        }
+        Some(text)
+    }
+}
+
+/// `text` with the arguments of its most deeply nested generics replaced by
+/// `…`, or `None` when all of them already are. The `>` of `->` is not a
+/// bracket.
+fn collapse_deepest_generics(text: &str) -> Option<String> {
+    // Byte range of the arguments and nesting depth of each pair of brackets
+    let mut groups = Vec::new();
+    let mut open = Vec::new();
+    let mut previous = None;
+    for (i, c) in text.char_indices() {
+        match c {
+            '<' => open.push(i + 1),
+            '>' if previous != Some('-') => {
+                if let Some(start) = open.pop() {
+                    groups.push((start, i, open.len()));
+                }
+            }
+            _ => {}
+        }
+        previous = Some(c);
+    }
+    groups.retain(|&(start, end, _)| &text[start..end] != "…");
+    let deepest = groups.iter().map(|&(_, _, depth)| depth).max()?;
+
+    let mut collapsed = text.to_string();
+    let mut deepest_groups: Vec<_> = groups.iter().filter(|g| g.2 == deepest).collect();
+    deepest_groups.sort_by_key(|&&(start, _, _)| std::cmp::Reverse(start));
+    for &(start, end, _) in deepest_groups {
+        collapsed.replace_range(start..end, "…");
+    }
+    Some(collapsed)
+}
+
+/// Members of a node, listed up to [`DetailLimits::members`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemberSummary {
+    pub total: usize,
+    /// Members per kind, most frequent first
+    pub kinds: Vec<QueryGroup>,
+    /// The first members, in declaration order
+    pub listed: Vec<DisplayGraphNode>,
+}
+
+/// Whether a symbol can be removed without breaking other code
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SafeDeleteVerdict {
+    /// No usages were found outside the symbol itself and tests
+    Safe,
+    /// Only heuristic hints were found; a human should confirm
+    Review,
+    /// Other code depends on the symbol
+    Unsafe,
+}
+
+/// Why a usage blocks (or may block) a deletion
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageKind {
+    /// A graph edge from another symbol
+    Reference,
+    /// A method overriding this one, or overridden by it
+    Override,
+    /// A sign that the symbol may be reached reflectively or by a framework
+    Reflection,
+    /// A file mentioning the symbol's name without a resolved edge
+    TextMatch,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+pub struct BlockingUsage {
+    pub kind: UsageKind,
+    /// FQN of the using symbol, or the file path for text matches
+    pub from: String,
+    pub detail: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<DisplaySymbolLocation>,
+}
+
+/// Result of a safe delete analysis
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+pub struct SafeDeleteReport {
+    pub fqn: String,
+    pub verdict: SafeDeleteVerdict,
+    pub usages: Vec<BlockingUsage>,
+}
+
+/// A node present in only one side of a diff, or changed between them
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+pub struct NodeChange {
+    pub fqn: String,
+    pub kind: NodeKind,
+    /// File declaring the node, on the side it was taken from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// An edge present in only one side of a diff
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+pub struct EdgeChange {
+    pub from: String,
+    pub to: String,
+    pub edge_type: EdgeType,
+}
+
+/// Differences between two graphs, from the old one to the new one
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, JsonSchema)]
+pub struct GraphDelta {
+    pub added_nodes: Vec<NodeChange>,
+    pub removed_nodes: Vec<NodeChange>,
+    /// Nodes whose kind, file, extent or resolution status changed
+    pub modified_nodes: Vec<NodeChange>,
+    pub added_edges: Vec<EdgeChange>,
+    pub removed_edges: Vec<EdgeChange>,
+}
+
+impl GraphDelta {
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.modified_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+}
+
+/// File format of a graph export
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExportFormat {
+    /// `nodes.csv` and `relationships.csv` for `neo4j-admin database import`
+    Neo4jCsv,
+    /// `dump.lsif`: definitions, references and hovers of project symbols
+    Lsif,
+}
+
+impl ExportFormat {
+    pub const ALL: &'static [ExportFormat] = &[ExportFormat::Neo4jCsv, ExportFormat::Lsif];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Neo4jCsv => "neo4j-csv",
+            ExportFormat::Lsif => "lsif",
+        }
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "neo4j" | "neo4j-csv" => Ok(ExportFormat::Neo4jCsv),
+            "lsif" => Ok(ExportFormat::Lsif),
+            _ => {
+                let names: Vec<_> = Self::ALL.iter().map(|f| f.name()).collect();
+                Err(format!(
+                    "unknown export format '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                ))
+            }
+        }
+    }
+}
+
+/// Files written by a graph export
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, JsonSchema)]
+pub struct ExportSummary {
+    pub files: Vec<std::path::PathBuf>,
+    /// Nodes written; for LSIF, the symbols with a definition
+    pub nodes: usize,
+    /// Edges written; for LSIF, the references to those symbols
+    pub edges: usize,
+}
+
+/// Project use of one package of an external library
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExternalPackageUsage {
+    pub package: String,
+    /// Edges from project symbols into the package
+    pub references: usize,
+    /// Symbols of the package the project refers to, most used first
+    pub symbols: Vec<RankedSymbol>,
+}
+
+/// Project use of one external library
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExternalArtifactUsage {
+    /// `group:artifact:version`, the JDK, the jar name, or `unresolved` for
+    /// symbols found in no scanned asset
+    pub artifact: String,
+    pub references: usize,
+    /// Distinct symbols of the library the project refers to
+    pub symbols: usize,
+    /// Project files referring to the library
+    pub files: usize,
+    pub packages: Vec<ExternalPackageUsage>,
+}
+
+/// External API the project uses, by library and package, most used first
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExternalApiReport {
+    pub artifacts: Vec<ExternalArtifactUsage>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures_lose_their_innermost_generics_first() {
+        let limits = |signature_chars| DetailLimits {
+            signature_chars,
+            members: 0,
+        };
+        let signature = "load(keys: Map<String, List<Foo<Bar>>>) -> Set<Baz>";
+        assert_eq!(limits(100).abridge(signature), None);
+        assert_eq!(
+            limits(50).abridge(signature).as_deref(),
+            Some("load(keys: Map<String, List<Foo<…>>>) -> Set<Baz>")
+        );
+        assert_eq!(
+            limits(45).abridge(signature).as_deref(),
+            Some("load(keys: Map<String, List<…>>) -> Set<Baz>")
+        );
+        assert_eq!(
+            limits(30).abridge(signature).as_deref(),
+            Some("load(keys: Map<…>) -> Set<…>")
+        );
+        assert_eq!(
+            limits(12).abridge(signature).as_deref(),
+            Some("load(keys: …")
+        );
     }
 }
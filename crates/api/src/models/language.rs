@@ -15,6 +15,8 @@ impl Language {
     pub const TYPESCRIPT: Language = Language(Cow::Borrowed("typescript"));
     pub const PYTHON: Language = Language(Cow::Borrowed("python"));
     pub const GO: Language = Language(Cow::Borrowed("go"));
+    pub const GROOVY: Language = Language(Cow::Borrowed("groovy"));
+    pub const SQL: Language = Language(Cow::Borrowed("sql"));
     pub const BUILDFILE: Language = Language(Cow::Borrowed("buildfile"));
     pub const UNKNOWN: Language = Language(Cow::Borrowed("unknown"));
 
@@ -33,6 +35,8 @@ impl Language {
             "ts" => Some(Self::TYPESCRIPT),
             "py" => Some(Self::PYTHON),
             "go" => Some(Self::GO),
+            "groovy" => Some(Self::GROOVY),
+            "sql" => Some(Self::SQL),
             "gradle" | "gradle.kts" => Some(Self::new("gradle")),
             "pom.xml" => Some(Self::new("maven")),
             ext => Some(Self::new(ext.to_string())),
@@ -3,8 +3,10 @@ pub mod graph;
 pub mod language;
 pub mod symbol;
 pub mod util;
+pub mod validate;
 
 pub use fqn::*;
 pub use graph::*;
 pub use language::*;
 pub use symbol::*;
+pub use validate::{QueryIssue, QueryValidation, validate_query};
@@ -120,7 +120,7 @@ impl Range {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SymbolIntent {
     Type,
     Method,
@@ -142,7 +142,7 @@ pub fn matches_intent(node_kind: &NodeKind, intent: SymbolIntent) -> bool {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SymbolResolution {
     Local(Range, Option<String>), // Range of declaration, and optional type name
     Precise(String, SymbolIntent),
@@ -159,9 +159,125 @@ impl SymbolResolution {
     }
 }
 
+/// The symbol at a source position, as resolved by `SymbolNavigator::resolve_at`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ResolvedSymbol {
+    /// FQN of the symbol; `None` for local variables and parameters
+    pub fqn: Option<String>,
+    /// Declaration of a local symbol in the same file
+    pub local_declaration: Option<Range>,
+    /// Declared type of a local symbol, if known
+    pub type_name: Option<String>,
+    /// Indexed node for `fqn`, if the index has one
+    pub node: Option<super::graph::DisplayGraphNode>,
+}
+
+impl std::fmt::Display for SymbolResolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SymbolResolution::Local(range, type_name) => {
+                write!(
+                    f,
+                    "local declared at {}:{}",
+                    range.start_line, range.start_col
+                )?;
+                if let Some(type_name) = type_name {
+                    write!(f, " ({})", type_name)?;
+                }
+                Ok(())
+            }
+            SymbolResolution::Precise(fqn, intent) => write!(f, "precise {:?} {}", intent, fqn),
+            SymbolResolution::Global(fqn) => write!(f, "global {}", fqn),
+        }
+    }
+}
+
+/// Step-by-step account of how the symbol at a position was resolved.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ResolutionTrace {
+    /// Source text of the syntax node at the position
+    pub symbol: String,
+    /// Parser node kind at the position (e.g. `identifier`)
+    pub syntax_kind: String,
+    /// Enclosing scopes, innermost first
+    pub scopes: Vec<String>,
+    /// Resolution stages in the order they were tried
+    pub steps: Vec<ResolutionStep>,
+    /// Final resolution, if any stage produced one
+    pub resolution: Option<String>,
+    /// Index nodes considered for the resolved FQN
+    pub candidates: Vec<ResolutionCandidate>,
+    /// FQN of the node navigation goes to
+    pub chosen: Option<String>,
+}
+
+impl ResolutionTrace {
+    pub fn step(&mut self, stage: &str, matched: bool, detail: impl Into<String>) {
+        self.steps.push(ResolutionStep {
+            stage: stage.to_string(),
+            matched,
+            detail: detail.into(),
+        });
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ResolutionStep {
+    pub stage: String,
+    /// Whether this stage produced the resolution
+    pub matched: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ResolutionCandidate {
+    pub fqn: String,
+    pub kind: NodeKind,
+    pub accepted: bool,
+    /// Why the candidate was rejected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Overloads applicable at a call site.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SignatureHelp {
+    pub signatures: Vec<SignatureInfo>,
+    /// Index into `signatures` of the overload the arguments resolve to
+    pub active_signature: usize,
+    /// Zero-based index of the argument under the cursor
+    pub active_parameter: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SignatureInfo {
+    /// Full label, e.g. `put(key: K, value: V) -> V`
+    pub label: String,
+    /// Parameter labels, each a substring of `label`
+    pub parameters: Vec<String>,
+    #[serde(default)]
+    pub documentation: Option<String>,
+}
+
+/// Collapsible region of a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FoldingRange {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub kind: FoldingKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FoldingKind {
+    Comment,
+    Imports,
+    Region,
+}
+
 // --- New Core API Types ---
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PositionContext {
     pub uri: String,
     pub line: u32,
@@ -169,7 +285,7 @@ pub struct PositionContext {
     pub content: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SymbolQuery {
     pub resolution: SymbolResolution,
     pub language: Language,
@@ -204,20 +320,64 @@ impl InternedLocation {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReferenceQuery {
     pub resolution: SymbolResolution,
     pub language: Language,
     pub include_declaration: bool,
+    pub type_usages: TypeUsageFilter,
+}
+
+/// How a reference site uses its target.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    /// Calls, instantiations, member access and other expression uses
+    #[default]
+    Usage,
+    /// The symbol names a type: field, parameter, return, local variable,
+    /// `throws`, bound or type argument
+    TypeUsage,
+}
+
+/// Which reference kinds a reference query returns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeUsageFilter {
+    /// Both usages and type usages
+    #[default]
+    Include,
+    /// Leave out type usages
+    Exclude,
+    /// Only type usages
+    Only,
+}
+
+impl TypeUsageFilter {
+    pub fn accepts(&self, kind: ReferenceKind) -> bool {
+        match self {
+            TypeUsageFilter::Include => true,
+            TypeUsageFilter::Exclude => kind != ReferenceKind::TypeUsage,
+            TypeUsageFilter::Only => kind == ReferenceKind::TypeUsage,
+        }
+    }
+}
+
+/// A reference location tagged with how it uses the target.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReferenceSite {
+    #[serde(flatten)]
+    pub location: SymbolLocation,
+    pub kind: ReferenceKind,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallHierarchyIncomingCall {
     pub from: DisplayGraphNode,
     pub from_ranges: Vec<Range>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallHierarchyOutgoingCall {
     pub to: DisplayGraphNode,
     pub from_ranges: Vec<Range>,
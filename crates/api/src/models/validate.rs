@@ -0,0 +1,298 @@
+//! Dry-run validation of graph queries
+//!
+//! A malformed query fails with a terse deserialization error, or worse, runs
+//! with a misspelled filter silently ignored. `validate_query` checks a query
+//! in its JSON form against the schema of `GraphQuery` without running it and
+//! reports every problem with the closest valid alternative, so a client can
+//! fix the query in one round trip.
+
+use super::graph::GraphQuery;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// A problem found in a query
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryIssue {
+    /// Field the problem concerns, e.g. `kind[1]`; empty for the whole query
+    pub field: String,
+    pub message: String,
+    /// Closest valid value for the field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+/// Outcome of validating a query
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryValidation {
+    pub valid: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<QueryIssue>,
+    /// The query as the engine reads it, with defaults filled in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<GraphQuery>,
+}
+
+impl QueryValidation {
+    /// Record a problem, marking the query invalid.
+    pub fn push(&mut self, field: &str, message: String, suggestion: Option<String>) {
+        self.valid = false;
+        self.issues.push(QueryIssue {
+            field: field.to_string(),
+            message,
+            suggestion,
+        });
+    }
+}
+
+/// Check `query` against the schema of `GraphQuery`.
+pub fn validate_query(query: &Value) -> QueryValidation {
+    let schema = schemars::schema_for!(GraphQuery);
+    let root = schema.as_value();
+    let mut validation = QueryValidation {
+        valid: true,
+        ..Default::default()
+    };
+
+    let Some(object) = query.as_object() else {
+        validation.push(
+            "",
+            "a query is a JSON object with a `command` field".to_string(),
+            None,
+        );
+        return validation;
+    };
+    let variants = root["oneOf"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+    let commands: Vec<&str> = variants
+        .iter()
+        .filter_map(|v| v["properties"]["command"]["const"].as_str())
+        .collect();
+    let command = object.get("command").and_then(Value::as_str);
+    let variant = command.and_then(|command| {
+        variants
+            .iter()
+            .find(|v| v["properties"]["command"]["const"] == command)
+    });
+    match (command, variant) {
+        (_, Some(variant)) => check_fields(root, variant, object, &mut validation),
+        (Some(command), None) => {
+            let suggestion = closest(command, &commands);
+            validation.push(
+                "command",
+                unknown(format!("command `{}`", command), &commands, suggestion),
+                suggestion.map(str::to_string),
+            );
+        }
+        (None, None) => validation.push(
+            "command",
+            format!("missing `command`, one of: {}", commands.join(", ")),
+            None,
+        ),
+    }
+
+    if validation.valid {
+        match serde_json::from_value::<GraphQuery>(query.clone()) {
+            Ok(parsed) => validation.query = Some(parsed),
+            Err(e) => validation.push("", e.to_string(), None),
+        }
+    }
+    validation
+}
+
+fn check_fields(
+    root: &Value,
+    variant: &Value,
+    object: &Map<String, Value>,
+    validation: &mut QueryValidation,
+) {
+    let empty = Map::new();
+    let properties = variant["properties"].as_object().unwrap_or(&empty);
+    let known: Vec<&str> = properties.keys().map(String::as_str).collect();
+    for (field, value) in object {
+        let Some(property) = properties.get(field) else {
+            // Serde would ignore the field and run the query without it
+            let suggestion = closest(field, &known);
+            validation.push(
+                field,
+                unknown(format!("field `{}`", field), &known, suggestion),
+                suggestion.map(str::to_string),
+            );
+            continue;
+        };
+        if field == "command" {
+            continue;
+        }
+        let mut vocabulary = Vec::new();
+        collect_vocabulary(root, property, &mut vocabulary);
+        let is_list = property["type"] == "array";
+        match value {
+            Value::String(s) if is_list => {
+                let item = closest(s, &vocabulary).unwrap_or(s);
+                validation.push(
+                    field,
+                    format!("`{}` takes a list, e.g. [\"{}\"]", field, item),
+                    Some(format!("[\"{}\"]", item)),
+                );
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    check_value(&format!("{}[{}]", field, i), item, &vocabulary, validation);
+                }
+            }
+            _ => check_value(field, value, &vocabulary, validation),
+        }
+    }
+    for required in variant["required"].as_array().into_iter().flatten() {
+        if let Some(required) = required.as_str()
+            && !object.contains_key(required)
+        {
+            validation.push(
+                required,
+                format!("missing required field `{}`", required),
+                None,
+            );
+        }
+    }
+}
+
+fn check_value(field: &str, value: &Value, vocabulary: &[&str], validation: &mut QueryValidation) {
+    let Value::String(s) = value else {
+        return;
+    };
+    if vocabulary.is_empty() || vocabulary.contains(&s.as_str()) {
+        return;
+    }
+    let suggestion = closest(s, vocabulary);
+    validation.push(
+        field,
+        unknown(format!("`{}` in `{}`", s, field), vocabulary, suggestion),
+        suggestion.map(str::to_string),
+    );
+}
+
+fn unknown(subject: String, expected: &[&str], suggestion: Option<&str>) -> String {
+    match suggestion {
+        Some(suggestion) => format!("unknown {}, did you mean `{}`?", subject, suggestion),
+        None => format!(
+            "unknown {}, expected one of: {}",
+            subject,
+            expected.join(", ")
+        ),
+    }
+}
+
+/// String values a schema accepts, following `$ref`s into the definitions
+fn collect_vocabulary<'a>(root: &'a Value, schema: &'a Value, out: &mut Vec<&'a str>) {
+    if !schema.is_object() {
+        return;
+    }
+    if let Some(name) = schema["$ref"]
+        .as_str()
+        .and_then(|r| r.strip_prefix("#/$defs/"))
+    {
+        collect_vocabulary(root, &root["$defs"][name], out);
+    }
+    out.extend(schema["const"].as_str());
+    for value in schema["enum"].as_array().into_iter().flatten() {
+        out.extend(value.as_str());
+    }
+    for key in ["oneOf", "anyOf"] {
+        for alternative in schema[key].as_array().into_iter().flatten() {
+            collect_vocabulary(root, alternative, out);
+        }
+    }
+    collect_vocabulary(root, &schema["items"], out);
+}
+
+/// The candidate closest to `input`: a case-insensitive match, or one within
+/// a few edits
+fn closest<'a>(input: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let lower = input.to_lowercase();
+    if let Some(exact) = candidates.iter().find(|c| c.to_lowercase() == lower) {
+        return Some(exact);
+    }
+    let max_distance = (input.chars().count() / 3).max(2);
+    candidates
+        .iter()
+        .map(|c| (edit_distance(&lower, &c.to_lowercase()), *c))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, c)| c)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn suggestions(query: Value) -> Vec<(String, Option<String>)> {
+        validate_query(&query)
+            .issues
+            .into_iter()
+            .map(|i| (i.field, i.suggestion))
+            .collect()
+    }
+
+    #[test]
+    fn valid_query_is_parsed_with_defaults() {
+        let validation = validate_query(&json!({"command": "find", "pattern": "Order"}));
+        assert!(validation.valid);
+        assert!(matches!(
+            validation.query,
+            Some(GraphQuery::Find { limit: 20, .. })
+        ));
+    }
+
+    #[test]
+    fn misspellings_get_suggestions() {
+        assert_eq!(
+            suggestions(json!({"command": "finf", "pattern": "Order"})),
+            [("command".to_string(), Some("find".to_string()))]
+        );
+        assert_eq!(
+            suggestions(json!({
+                "command": "find",
+                "pattern": "Order",
+                "kinds": ["class"],
+            })),
+            [("kinds".to_string(), Some("kind".to_string()))]
+        );
+        assert_eq!(
+            suggestions(json!({
+                "command": "deps",
+                "fqn": "a.B",
+                "edge_types": ["implements", "Calls"],
+            })),
+            [
+                ("edge_types[0]".to_string(), Some("Implements".to_string())),
+                ("edge_types[1]".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn shape_errors_are_reported_per_field() {
+        assert_eq!(
+            suggestions(json!({"command": "find", "kind": "Class"})),
+            [
+                ("kind".to_string(), Some("[\"class\"]".to_string())),
+                ("pattern".to_string(), None),
+            ]
+        );
+        assert!(!validate_query(&json!("ls")).valid);
+    }
+}
@@ -3,7 +3,7 @@ use async_trait::async_trait;
 
 /// Result of resolving a user-provided path to a node FQN.
 /// This is specific to CLI-style navigation with fuzzy matching and relative paths.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum ResolveResult {
     /// Exactly one node found
     Found(String),
@@ -60,6 +60,10 @@ pub trait NavigationService: Send + Sync {
         current_context: Option<&str>,
     ) -> ApiResult<ResolveResult>;
 
+    /// `fqn` if it still exists, otherwise its nearest surviving ancestor,
+    /// falling back to the project root. `None` if the graph has neither.
+    async fn nearest_existing(&self, fqn: &str) -> ApiResult<Option<String>>;
+
     /// Get completion candidates for a prefix.
     async fn get_completion_candidates(&self, prefix: &str, limit: usize)
     -> ApiResult<Vec<String>>;
@@ -1,7 +1,8 @@
 use crate::ApiResult;
 use crate::models::{
-    CallHierarchyIncomingCall, CallHierarchyOutgoingCall, DisplayGraphNode, PositionContext,
-    ReferenceQuery, SymbolLocation, SymbolQuery, SymbolResolution,
+    CallHierarchyIncomingCall, CallHierarchyOutgoingCall, DisplayGraphNode, FoldingRange,
+    PositionContext, Range, ReferenceQuery, ReferenceSite, ResolutionTrace, ResolvedSymbol,
+    SignatureHelp, SymbolLocation, SymbolQuery, SymbolResolution,
 };
 use async_trait::async_trait;
 
@@ -17,6 +18,36 @@ pub trait SymbolNavigator: Send + Sync {
     async fn resolve_symbol_at(&self, ctx: &PositionContext)
     -> ApiResult<Option<SymbolResolution>>;
 
+    /// Resolve the symbol at `line`/`col` of the file at `path`, as saved on disk,
+    /// together with its indexed node.
+    ///
+    /// Positions are 0-based; `col` counts UTF-16 code units, like LSP positions.
+    async fn resolve_at(
+        &self,
+        path: &std::path::Path,
+        line: u32,
+        col: u32,
+    ) -> ApiResult<Option<ResolvedSymbol>>;
+
+    /// Resolve many `(line, col)` positions of one file, like `resolve_at`,
+    /// parsing the file and reading the graph once. Results follow the order
+    /// of `positions`.
+    async fn resolve_batch(
+        &self,
+        path: &std::path::Path,
+        positions: &[(u32, u32)],
+    ) -> ApiResult<Vec<Option<ResolvedSymbol>>> {
+        let mut resolved = Vec::with_capacity(positions.len());
+        for &(line, col) in positions {
+            resolved.push(self.resolve_at(path, line, col).await?);
+        }
+        Ok(resolved)
+    }
+
+    /// Replay resolution at a position, recording every stage and candidate.
+    async fn explain_resolution(&self, ctx: &PositionContext)
+    -> ApiResult<Option<ResolutionTrace>>;
+
     /// Find all definition locations for a given symbol query.
     async fn find_definitions(&self, query: &SymbolQuery) -> ApiResult<Vec<SymbolLocation>>;
 
@@ -28,13 +59,31 @@ pub trait SymbolNavigator: Send + Sync {
 
     /// Find occurrences of a symbol for document highlighting.
     async fn find_highlights(&self, ctx: &PositionContext) -> ApiResult<Vec<crate::models::Range>>;
+
+    /// Range of the local variable or parameter at a position, if it can be
+    /// renamed. Symbols in the graph are not renamed this way.
+    async fn prepare_rename(&self, ctx: &PositionContext) -> ApiResult<Option<Range>>;
+
+    /// Occurrences to replace, all in the document of `ctx`, to rename the
+    /// local variable or parameter at a position to `new_name`.
+    ///
+    /// Fails with `InvalidArgument` if there is no local there, `new_name` is
+    /// not an identifier, or the rename would clash with another declaration
+    /// or capture an existing reference.
+    async fn rename_local(&self, ctx: &PositionContext, new_name: &str) -> ApiResult<Vec<Range>>;
 }
 
 /// Reference analysis: find all usages of a symbol.
 #[async_trait]
 pub trait ReferenceAnalyzer: Send + Sync {
+    /// Find all reference sites for a given reference query, tagged by kind.
+    async fn find_reference_sites(&self, query: &ReferenceQuery) -> ApiResult<Vec<ReferenceSite>>;
+
     /// Find all reference locations for a given reference query.
-    async fn find_references(&self, query: &ReferenceQuery) -> ApiResult<Vec<SymbolLocation>>;
+    async fn find_references(&self, query: &ReferenceQuery) -> ApiResult<Vec<SymbolLocation>> {
+        let sites = self.find_reference_sites(query).await?;
+        Ok(sites.into_iter().map(|site| site.location).collect())
+    }
 }
 
 /// Call hierarchy analysis: incoming and outgoing calls.
@@ -53,12 +102,35 @@ pub trait SymbolInfoProvider: Send + Sync {
     /// Get detailed information about a symbol by its FQN.
     async fn get_symbol_info(&self, fqn: &str) -> ApiResult<Option<DisplayGraphNode>>;
 
+    /// Get the signatures of the call whose argument list contains the position.
+    async fn get_signature_help(&self, ctx: &PositionContext) -> ApiResult<Option<SignatureHelp>>;
+
     /// Get all symbols defined in a specific document.
     async fn get_document_symbols(&self, uri: &str) -> ApiResult<Vec<DisplayGraphNode>>;
 
+    /// Get the collapsible regions of a document.
+    ///
+    /// `content` is the editor buffer; the file on disk is read when it is `None`.
+    async fn get_folding_ranges(
+        &self,
+        uri: &str,
+        content: Option<String>,
+    ) -> ApiResult<Vec<FoldingRange>>;
+
+    /// Get the enclosing syntax ranges at a position, innermost first.
+    async fn get_selection_ranges(&self, ctx: &PositionContext) -> ApiResult<Vec<Range>>;
+
     /// Get the language of a specific document.
     async fn get_language_for_document(
         &self,
         uri: &str,
     ) -> ApiResult<Option<crate::models::Language>>;
+
+    /// Content of the file at `path`, as the engine reads it for its own
+    /// requests, so that snippets agree with the results they illustrate.
+    async fn read_source(&self, path: &std::path::Path) -> ApiResult<std::sync::Arc<str>> {
+        std::fs::read_to_string(path).map(Into::into).map_err(|e| {
+            crate::ApiError::NotFound(format!("Cannot read {}: {}", path.display(), e))
+        })
+    }
 }
@@ -0,0 +1,146 @@
+use naviscope_api::models::{GraphQuery, NodeKind};
+use naviscope_runtime::java_audit::{self, AuditReport, ClassAudit};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Classes listed individually in the text report
+const SHOWN_CLASSES: usize = 20;
+
+pub async fn run(
+    path: PathBuf,
+    module: Option<PathBuf>,
+    classes: Option<PathBuf>,
+    sample: usize,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let engine = naviscope_runtime::build_default_engine(path.clone());
+
+    if !engine.load().await? {
+        println!(
+            "No index found for {}. Run `naviscope index` first.",
+            path.display()
+        );
+        return Ok(());
+    }
+
+    let module_dir = path.join(module.unwrap_or_default());
+    let Some(classes_dir) = classes.or_else(|| find_classes_dir(&module_dir)) else {
+        return Err(format!(
+            "No compiled classes under {} (looked in {}). Build the module first or pass --classes.",
+            module_dir.display(),
+            java_audit::CLASS_DIRS.join(", ")
+        )
+        .into());
+    };
+
+    let candidates: Vec<(PathBuf, String)> = java_audit::class_files(&classes_dir)
+        .into_iter()
+        .filter_map(|file| {
+            let relative = file.strip_prefix(&classes_dir).ok()?;
+            let fqn = java_audit::class_fqn(relative)?;
+            Some((file, fqn))
+        })
+        .collect();
+
+    let mut audits = Vec::new();
+    for (file, fqn) in java_audit::sample(&candidates, sample) {
+        let shape = match java_audit::read_class(fqn, std::fs::read(file)?) {
+            Ok(Some(shape)) => shape,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!("Skipping {}: {}", file.display(), e);
+                continue;
+            }
+        };
+        let indexed = indexed_members(engine.as_ref(), fqn).await?;
+        audits.push(java_audit::compare(&shape, indexed.as_ref()));
+    }
+    let report = AuditReport::new(candidates.len(), audits);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&classes_dir, &report);
+    }
+    Ok(())
+}
+
+fn find_classes_dir(module_dir: &Path) -> Option<PathBuf> {
+    java_audit::CLASS_DIRS
+        .iter()
+        .map(|dir| module_dir.join(dir))
+        .find(|dir| dir.is_dir())
+}
+
+/// FQNs of the fields, methods and constructors indexed under `fqn`, or
+/// `None` if the class is not indexed
+async fn indexed_members(
+    engine: &dyn naviscope_api::NaviscopeEngine,
+    fqn: &str,
+) -> Result<Option<BTreeSet<String>>, Box<dyn std::error::Error>> {
+    if engine.get_node_display(fqn).await?.is_none() {
+        return Ok(None);
+    }
+    let query = GraphQuery::Ls {
+        fqn: Some(fqn.to_string()),
+        kind: vec![NodeKind::Field, NodeKind::Method, NodeKind::Constructor],
+        sources: vec![],
+        statuses: vec![],
+        modifiers: vec![],
+        group_by: None,
+        count: false,
+    };
+    let result = engine.query(&query).await?;
+    Ok(Some(result.nodes.into_iter().map(|n| n.id).collect()))
+}
+
+fn print_report(classes_dir: &Path, report: &AuditReport) {
+    println!("Classes: {}", classes_dir.display());
+    println!(
+        "Sampled {} of {} classes, {} not indexed",
+        report.classes_sampled, report.classes_total, report.classes_missing
+    );
+    println!(
+        "Members: {} expected, {} matched, {} missing, {} mismatched signatures, {} extra",
+        report.members_expected,
+        report.members_matched,
+        report.members_missing,
+        report.members_mismatched,
+        report.members_extra
+    );
+    println!("Accuracy: {:.1}%", report.accuracy());
+
+    let mut gaps: Vec<&ClassAudit> = report
+        .classes
+        .iter()
+        .filter(|c| !c.indexed || c.matched < c.expected || !c.extra.is_empty())
+        .collect();
+    gaps.sort_by_key(|c| std::cmp::Reverse(c.expected - c.matched + c.extra.len()));
+    for class in gaps.iter().take(SHOWN_CLASSES) {
+        println!();
+        if !class.indexed {
+            println!("{} (not indexed)", class.fqn);
+            continue;
+        }
+        println!(
+            "{} ({}/{} matched)",
+            class.fqn, class.matched, class.expected
+        );
+        for member in &class.missing {
+            println!("  - {}", member);
+        }
+        for (expected, indexed) in &class.mismatched {
+            println!("  ~ {} indexed as {}", expected, indexed);
+        }
+        for member in &class.extra {
+            println!("  + {}", member);
+        }
+    }
+    if gaps.len() > SHOWN_CLASSES {
+        println!();
+        println!(
+            "... {} more classes with gaps (use --json for all)",
+            gaps.len() - SHOWN_CLASSES
+        );
+    }
+}
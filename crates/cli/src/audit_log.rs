@@ -0,0 +1,78 @@
+use naviscope_api::lifecycle::AuditEntry;
+use std::path::PathBuf;
+use tabled::{Table, Tabled, settings::Style};
+
+#[derive(Tabled)]
+struct EntryRow {
+    when: String,
+    client: String,
+    tool: String,
+    arguments: String,
+    result: String,
+}
+
+pub async fn run(
+    path: PathBuf,
+    tool: Option<String>,
+    client: Option<String>,
+    limit: usize,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let engine = naviscope_runtime::build_default_engine(path.clone());
+    let mut entries: Vec<AuditEntry> = engine
+        .audit_log()
+        .await?
+        .into_iter()
+        .filter(|e| tool.as_ref().is_none_or(|t| &e.tool == t))
+        .filter(|e| client.as_ref().is_none_or(|c| e.client.as_ref() == Some(c)))
+        .collect();
+    // The most recent ones
+    if limit > 0 && entries.len() > limit {
+        entries.drain(..entries.len() - limit);
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("No tool calls recorded for {}.", path.display());
+        return Ok(());
+    }
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let rows: Vec<EntryRow> = entries
+        .into_iter()
+        .map(|e| EntryRow {
+            when: age(now.saturating_sub(e.at)),
+            client: e.client.unwrap_or_else(|| "-".to_string()),
+            tool: e.tool,
+            arguments: if e.arguments.is_null() {
+                String::new()
+            } else {
+                e.arguments.to_string()
+            },
+            result: match e.error {
+                Some(error) => format!("error: {}", error),
+                None => format!("ok ({} ms)", e.duration_ms),
+            },
+        })
+        .collect();
+    println!("{}", Table::new(&rows).with(Style::psql()));
+    Ok(())
+}
+
+fn age(seconds: u64) -> String {
+    if seconds < 60 {
+        format!("{}s ago", seconds)
+    } else if seconds < 3600 {
+        format!("{}m ago", seconds / 60)
+    } else if seconds < 86400 {
+        format!("{}h ago", seconds / 3600)
+    } else {
+        format!("{}d ago", seconds / 86400)
+    }
+}
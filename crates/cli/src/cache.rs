@@ -6,6 +6,7 @@ pub enum CacheCommands {
     /// Show cache statistics
     Stats,
     /// List cached assets
+    #[command(visible_alias = "ls")]
     List {
         /// Sort by size or date
         #[arg(long, value_parser = ["size", "date"])]
@@ -16,8 +17,19 @@ pub enum CacheCommands {
     },
     /// Inspect a specific cached asset
     Inspect {
-        /// Asset hash (full or prefix)
-        hash: String,
+        /// Asset hash (full or prefix), part of its path, or a Maven
+        /// coordinate (group:artifact[:version])
+        coordinate: String,
+    },
+    /// Remove cached assets, so their stubs are generated again
+    #[command(visible_alias = "rm")]
+    Remove {
+        /// Asset hash (full or prefix), part of its path, or a Maven
+        /// coordinate (group:artifact[:version])
+        coordinate: String,
+        /// Remove every matching asset instead of requiring a single match
+        #[arg(long)]
+        all: bool,
     },
     /// Clear the cache
     Clear,
@@ -33,6 +45,8 @@ struct AssetRow {
     size: String,
     #[tabled(rename = "Stubs")]
     stubs: usize,
+    #[tabled(rename = "Packages")]
+    packages: usize,
     #[tabled(rename = "Ver")]
     version: u32,
     #[tabled(rename = "Age")]
@@ -99,6 +113,7 @@ pub async fn run(cmd: CacheCommands) -> Result<(), Box<dyn std::error::Error>> {
                         path: a.path,
                         size: size_str,
                         stubs: a.stub_count,
+                        packages: a.package_count,
                         version: a.version,
                         age: age_str,
                     }
@@ -111,13 +126,19 @@ pub async fn run(cmd: CacheCommands) -> Result<(), Box<dyn std::error::Error>> {
                 println!("{}", Table::new(rows));
             }
         }
-        CacheCommands::Inspect { hash } => {
-            if let Some(result) = cache.inspect_asset(&hash) {
+        CacheCommands::Inspect { coordinate } => {
+            if let Some(result) = cache.inspect_asset(&coordinate) {
                 println!("Asset Summary:");
-                println!("  Path:    {}", result.summary.path);
-                println!("  Hash:    {}", result.summary.hash);
-                println!("  Version: {}", result.summary.version);
-                println!("  Stubs:   {}", result.summary.stub_count);
+                println!("  Path:     {}", result.summary.path);
+                println!("  Hash:     {}", result.summary.hash);
+                println!("  Version:  {}", result.summary.version);
+                println!("  Stubs:    {}", result.summary.stub_count);
+                println!("  Packages: {}", result.summary.package_count);
+
+                println!("\nStubs per Package:");
+                for (package, count) in &result.packages {
+                    println!("  {}: {}", package, count);
+                }
 
                 println!("\nMetadata Distribution:");
                 for (type_tag, count) in result.metadata_distribution {
@@ -129,7 +150,28 @@ pub async fn run(cmd: CacheCommands) -> Result<(), Box<dyn std::error::Error>> {
                     println!("  {}. {}", i + 1, entry);
                 }
             } else {
-                println!("Asset not found with hash prefix: {}", hash);
+                println!("No cached asset matches: {}", coordinate);
+            }
+        }
+        CacheCommands::Remove { coordinate, all } => {
+            let matched: Vec<_> = cache
+                .scan_assets()
+                .into_iter()
+                .filter(|a| a.matches(&coordinate))
+                .collect();
+            if matched.is_empty() {
+                println!("No cached asset matches: {}", coordinate);
+            } else if matched.len() > 1 && !all {
+                println!("{} cached assets match {}:", matched.len(), coordinate);
+                for asset in &matched {
+                    println!("  {}  {}", asset.hash, asset.path);
+                }
+                println!("Narrow the coordinate or pass --all to remove them all.");
+            } else {
+                let hashes: Vec<String> = matched.into_iter().map(|a| a.hash).collect();
+                for asset in cache.remove_assets(&hashes)? {
+                    println!("Removed {} ({} stubs)", asset.path, asset.stub_count);
+                }
             }
         }
         CacheCommands::Clear => {
@@ -0,0 +1,82 @@
+use naviscope_api::models::{EdgeChange, GraphDelta, NodeChange};
+use std::path::PathBuf;
+
+pub async fn run(
+    path: PathBuf,
+    reference: String,
+    new: Option<String>,
+    save: bool,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let engine = naviscope_runtime::build_default_engine(path.clone());
+
+    // Two snapshots or revisions do not need the index
+    if let Some(new) = new {
+        let delta = engine.diff_between(&reference, &new).await?;
+        return print(&format!("from {} to {}", reference, new), &delta, json);
+    }
+
+    if !engine.load().await? {
+        println!(
+            "No index found for {}. Run `naviscope index` first.",
+            path.display()
+        );
+        return Ok(());
+    }
+
+    if save {
+        let file = engine.save_snapshot(&reference).await?;
+        println!("Saved snapshot '{}' to {}", reference, file.display());
+        return Ok(());
+    }
+
+    let delta = engine.diff(&reference).await?;
+    print(&format!("since {}", reference), &delta, json)
+}
+
+fn print(span: &str, delta: &GraphDelta, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(delta)?);
+    } else {
+        print_summary(span, delta);
+    }
+    Ok(())
+}
+
+/// `span` tells what was compared: `since main`, `from v1 to v2`
+fn print_summary(span: &str, delta: &GraphDelta) {
+    if delta.is_empty() {
+        println!("No changes {}", span);
+        return;
+    }
+
+    println!(
+        "Changes {}: {} added, {} removed, {} modified nodes; {} added, {} removed edges",
+        span,
+        delta.added_nodes.len(),
+        delta.removed_nodes.len(),
+        delta.modified_nodes.len(),
+        delta.added_edges.len(),
+        delta.removed_edges.len()
+    );
+    print_nodes("+", &delta.added_nodes);
+    print_nodes("-", &delta.removed_nodes);
+    print_nodes("~", &delta.modified_nodes);
+    print_edges("+", &delta.added_edges);
+    print_edges("-", &delta.removed_edges);
+}
+
+fn print_nodes(marker: &str, nodes: &[NodeChange]) {
+    for node in nodes {
+        println!("{} {:?} {}", marker, node.kind, node.fqn);
+    }
+}
+
+fn print_edges(marker: &str, edges: &[EdgeChange]) {
+    for edge in edges {
+        println!(
+            "{} {} -[{:?}]-> {}",
+            marker, edge.from, edge.edge_type, edge.to
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use naviscope_api::models::graph::ExportFormat;
+use std::path::PathBuf;
+
+pub async fn run(
+    path: PathBuf,
+    format: ExportFormat,
+    out_dir: PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let engine = naviscope_runtime::build_default_engine(path.clone());
+
+    if !engine.load().await? {
+        println!(
+            "No index found for {}. Run `naviscope index` first.",
+            path.display()
+        );
+        return Ok(());
+    }
+
+    let summary = engine.export_graph(format, &out_dir).await?;
+    match format {
+        ExportFormat::Neo4jCsv => println!(
+            "Exported {} nodes and {} edges as {}:",
+            summary.nodes, summary.edges, format
+        ),
+        ExportFormat::Lsif => println!(
+            "Exported {} symbols and {} references as {}:",
+            summary.nodes, summary.edges, format
+        ),
+    }
+    for file in &summary.files {
+        println!("  {}", file.display());
+    }
+    if format == ExportFormat::Neo4jCsv {
+        println!(
+            "Import with: neo4j-admin database import full --nodes={} --relationships={} <database>",
+            out_dir.join("nodes.csv").display(),
+            out_dir.join("relationships.csv").display()
+        );
+    }
+    Ok(())
+}
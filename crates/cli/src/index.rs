@@ -1,13 +1,40 @@
+use naviscope_api::NaviscopeEngine;
+use std::io::IsTerminal;
 use std::path::PathBuf;
+use tokio::task::JoinHandle;
 use tracing::info;
 
-pub async fn run(path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn run(
+    path: PathBuf,
+    at: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
     let engine = naviscope_runtime::build_default_engine(path.clone());
 
+    if let Some(rev) = at {
+        info!("Indexing revision {} of {}...", rev, path.display());
+        let file = engine.index_revision(&rev).await?;
+        info!("Saved snapshot of {} to {}", rev, file.display());
+        return Ok(());
+    }
+
     info!("Indexing project at: {}...", path.display());
 
+    if let Ok(Some(resume)) = engine.pending_resume().await {
+        info!(
+            "Resuming interrupted build: {} files already indexed (epoch {})",
+            resume.committed_files, resume.epoch
+        );
+    }
+
     // Run async build
-    engine.rebuild().await?;
+    let progress = show_progress(engine.as_ref()).await;
+    let rebuilt = engine.rebuild().await;
+    if let Some(progress) = progress {
+        progress.abort();
+        eprintln!();
+    }
+    rebuilt?;
 
     let stats = engine.get_stats().await?;
 
@@ -20,7 +47,10 @@ pub async fn run(path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
         fqn: None,
         kind: vec![],
         sources: vec![],
+        statuses: vec![],
         modifiers: vec![],
+        group_by: None,
+        count: false,
     };
     if let Ok(res) = engine.query(&query).await {
         for node in res.nodes.iter().take(10) {
@@ -28,5 +58,41 @@ pub async fn run(path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    if let Some(output) = output {
+        let summary = engine.export_bundle(&output).await?;
+        info!(
+            "Wrote bundle {} ({} nodes, {} edges)",
+            output.display(),
+            summary.nodes,
+            summary.edges
+        );
+    }
+
     Ok(())
 }
+
+/// Keep a line on stderr showing how far the running update got, if stderr
+/// is a terminal
+async fn show_progress(engine: &dyn NaviscopeEngine) -> Option<JoinHandle<()>> {
+    if !std::io::stderr().is_terminal() {
+        return None;
+    }
+    let mut updates = engine.subscribe_progress().await.ok()?;
+    Some(tokio::spawn(async move {
+        let mut last = None;
+        while let Ok(progress) = updates.next().await {
+            let key = (progress.percent, progress.current.phase);
+            if last == Some(key) {
+                continue;
+            }
+            last = Some(key);
+            eprint!(
+                "\r{:>3}% {} ({}/{} files committed)\x1b[K",
+                progress.percent,
+                progress.current.phase.label(),
+                progress.committed,
+                progress.submitted
+            );
+        }
+    }))
+}
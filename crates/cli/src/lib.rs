@@ -1,11 +1,23 @@
+mod audit;
+mod audit_log;
 mod cache;
 mod clear;
+mod completions;
+mod diff;
+mod export;
 mod index;
+mod man;
+mod query;
+mod serve_index;
 mod shell;
+mod stats;
+mod tui;
 mod watch;
 
 use clap::{Parser, Subcommand};
+use std::net::IpAddr;
 use std::path::PathBuf;
+use std::process::ExitCode;
 
 #[derive(Parser)]
 #[command(
@@ -26,32 +38,137 @@ pub enum Commands {
     /// Index a project directory into a Code Knowledge Graph
     #[command(
         long_about = "Analyzes the project structure and source code to build a persistent index. \
-                            By default, the index is stored in ~/.naviscope/indices/."
+                            By default, the index is stored in ~/.naviscope/indices/. With --at, \
+                            the files of a git revision are read from the repository, without a \
+                            checkout, and indexed into a snapshot named after its commit for \
+                            `naviscope diff`; the index itself is left as it is. With --output, \
+                            the finished index is also written to a single portable bundle \
+                            that `naviscope query --index` and `naviscope shell --index` open \
+                            on any machine."
     )]
     Index {
         /// Path to the project root directory to index
         #[arg(value_name = "PROJECT_PATH")]
         path: PathBuf,
+        /// Index this git revision (commit, branch or tag) into a snapshot
+        #[arg(long, value_name = "REV")]
+        at: Option<String>,
+        /// Also write the index to this bundle file
+        #[arg(long, value_name = "FILE", conflicts_with = "at")]
+        output: Option<PathBuf>,
+    },
+    /// Run one structured query and print the result as JSON
+    #[command(
+        long_about = "Runs a query given in its JSON form against the project's index and \
+                            prints the result as JSON, e.g. for \
+                            '{\"command\": \"find\", \"pattern\": \"Order\"}'. The query \
+                            is validated first; problems are listed on stderr with the closest \
+                            valid value. With --index, a prebuilt index or bundle file is queried \
+                            read-only instead, and with --remote the index served by \
+                            `naviscope serve-index` at the given address."
+    )]
+    Query {
+        /// Query as a JSON object with a `command` field
+        #[arg(value_name = "QUERY")]
+        query: String,
+        /// Path to the project root. Defaults to current directory.
+        #[arg(long, value_name = "PROJECT_PATH")]
+        path: Option<PathBuf>,
+        /// Query this prebuilt index or bundle file read-only
+        #[arg(long, value_name = "FILE")]
+        index: Option<PathBuf>,
+        /// Query the index server at this address (host:port)
+        #[arg(long, value_name = "ADDR", conflicts_with = "index")]
+        remote: Option<String>,
     },
     /// Start an interactive shell to query the code knowledge graph
     #[command(
         long_about = "Starts an interactive shell where you can execute structured queries \
-                            against the index using both JSON DSL and shorthand commands."
+                            against the index using both JSON DSL and shorthand commands. \
+                            With --script or -c, the commands are run without a prompt, one per \
+                            line or separated by ';', stopping at the first failure. The exit \
+                            status is 0 when all succeed, 1 when a command fails or a program \
+                            its output is piped to exits non-zero, and 2 when a command cannot \
+                            be parsed. With --index, a prebuilt index or bundle is queried \
+                            read-only instead: it is neither refreshed nor watched and nothing \
+                            is written next to it, so parallel CI jobs can share one artifact. \
+                            With --remote, the index served by `naviscope serve-index` is \
+                            queried, and kept up to date by the server."
     )]
     Shell {
         /// Path to the project root (used to locate the default index). Defaults to current directory.
         #[arg(value_name = "PROJECT_PATH")]
         path: Option<PathBuf>,
+        /// Query this prebuilt index or bundle file read-only
+        #[arg(long, value_name = "FILE")]
+        index: Option<PathBuf>,
+        /// Query the index server at this address (host:port)
+        #[arg(long, value_name = "ADDR", conflicts_with = "index")]
+        remote: Option<String>,
+        /// Run the commands in this file instead of starting a prompt
+        #[arg(long, value_name = "FILE", conflicts_with = "commands")]
+        script: Option<PathBuf>,
+        /// Run these commands, separated by ';', instead of starting a prompt
+        #[arg(short = 'c', value_name = "COMMANDS")]
+        commands: Option<String>,
+    },
+    /// Explore the code knowledge graph in a full-screen terminal UI
+    #[command(
+        long_about = "Opens a full-screen explorer with the containment tree, the details of the \
+                            selected node and its outgoing and incoming edges. Move with the \
+                            arrow keys or j/k, switch panes with Tab, press Enter on an edge to \
+                            jump to the node at its other end and Backspace to go back. `/` \
+                            searches symbols as you type; q quits."
+    )]
+    Tui {
+        /// Path to the project root. Defaults to current directory.
+        #[arg(value_name = "PROJECT_PATH")]
+        path: Option<PathBuf>,
     },
     /// Watch for file changes and update the index automatically
     #[command(
         long_about = "Starts a file watcher that monitors the project directory for changes. \
-                            When a change is detected, the index is automatically updated."
+                            When a change is detected, the index is automatically updated. \
+                            With --emit-json, a JSON summary of each update (changed files, \
+                            added and removed symbols) is printed to stdout, one per line. \
+                            With --exec, a command is run after each update with that summary \
+                            on its stdin."
     )]
     Watch {
         /// Path to the project root directory to watch
         #[arg(value_name = "PROJECT_PATH")]
         path: PathBuf,
+        /// Run this shell command after each update
+        #[arg(long, value_name = "CMD")]
+        exec: Option<String>,
+        /// Print a JSON summary of each update
+        #[arg(long)]
+        emit_json: bool,
+    },
+    /// Serve a project's index to other machines
+    #[command(
+        name = "serve-index",
+        long_about = "Indexes the project, keeps the index up to date and serves it over JSON-RPC on \
+                            a WebSocket at ws://HOST:PORT/rpc, so that a huge project is indexed \
+                            once on a machine that can afford it. `naviscope query`, `shell`, \
+                            `mcp` and `lsp` connect to it with --remote HOST:PORT. Clients \
+                            present the token in NAVISCOPE_REMOTE_TOKEN; without --token or that \
+                            variable, a fresh token is generated and printed. Paths in requests \
+                            and results are those of the serving machine."
+    )]
+    ServeIndex {
+        /// Path to the project root directory to serve
+        #[arg(value_name = "PROJECT_PATH")]
+        path: PathBuf,
+        /// Address to listen on; other hosts can connect unless it is a loopback address
+        #[arg(long, default_value = "127.0.0.1")]
+        host: IpAddr,
+        /// Port to listen on
+        #[arg(long, default_value_t = 7860)]
+        port: u16,
+        /// Token clients must present, instead of NAVISCOPE_REMOTE_TOKEN
+        #[arg(long)]
+        token: Option<String>,
     },
     /// Clear built indices
     #[command(
@@ -63,31 +180,221 @@ pub enum Commands {
         #[arg(value_name = "PROJECT_PATH")]
         path: Option<PathBuf>,
     },
+    /// Show statistics for a project's index
+    #[command(
+        long_about = "Loads the project's index and prints node/edge counts together with the \
+                            source roots detected from build metadata, package declarations or \
+                            naviscope.toml, followed by graph metrics: counts per node kind and \
+                            edge type, fan-in/fan-out distributions, the largest classes, the \
+                            most-referenced symbols and the number of unresolved edges. \
+                            With `--warnings`, lists what the parsers and resolvers skipped \
+                            instead, e.g. declarations inside syntax errors. With `--runtime`, \
+                            prints the engine's update counters (files run, files held back by \
+                            unsaved edits, commits) and update latencies; pair it with \
+                            `--remote` to inspect a running `naviscope serve-index`."
+    )]
+    Stats {
+        /// Path to the project root. Defaults to current directory.
+        #[arg(value_name = "PROJECT_PATH")]
+        path: Option<PathBuf>,
+        /// Number of entries in the ranked lists
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+        /// Print the statistics as JSON
+        #[arg(long)]
+        json: bool,
+        /// List the warnings recorded while indexing
+        #[arg(long)]
+        warnings: bool,
+        /// Print the runtime metrics of the engine
+        #[arg(long, conflicts_with = "warnings")]
+        runtime: bool,
+        /// Read from the index server at this address (host:port)
+        #[arg(long, value_name = "ADDR")]
+        remote: Option<String>,
+    },
+    /// Compare the index with a saved snapshot
+    #[command(
+        long_about = "Lists the nodes and edges added, removed or modified since the snapshot REF \
+                            was saved, e.g. to summarize the impact of a pull request. Save a \
+                            snapshot with `--save` before the change; REF may also be the path \
+                            of a snapshot file or a git revision. With NEW, compares REF with NEW \
+                            instead of the index. Revisions without a snapshot are indexed first, \
+                            as by `naviscope index --at`."
+    )]
+    Diff {
+        /// Snapshot name, path of a snapshot file, or git revision
+        #[arg(value_name = "REF")]
+        reference: String,
+        /// Compare with this snapshot or git revision instead of the index
+        #[arg(value_name = "NEW", conflicts_with = "save")]
+        new: Option<String>,
+        /// Path to the project root. Defaults to current directory.
+        #[arg(long, value_name = "PROJECT_PATH")]
+        path: Option<PathBuf>,
+        /// Save the current index as snapshot REF instead of comparing
+        #[arg(long)]
+        save: bool,
+        /// Print the changes as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Export the graph for other tools
+    #[command(
+        long_about = "Writes the whole index to files in OUT_DIR. The `neo4j-csv` format produces \
+                            nodes.csv and relationships.csv for `neo4j-admin database import`: nodes \
+                            are labelled by kind and keyed by symbol ID, relationships are typed by \
+                            edge type (e.g. INHERITS_FROM), ready for Cypher analytics. The `lsif` \
+                            format produces dump.lsif with the definition, hover and references of \
+                            every project type and member, for code hosts that import LSIF."
+    )]
+    Export {
+        /// Directory to write the files to
+        #[arg(value_name = "OUT_DIR")]
+        out_dir: PathBuf,
+        /// Output format
+        #[arg(long, default_value = "neo4j-csv")]
+        format: naviscope_api::models::graph::ExportFormat,
+        /// Path to the project root. Defaults to current directory.
+        #[arg(long, value_name = "PROJECT_PATH")]
+        path: Option<PathBuf>,
+    },
+    /// Measure Java index accuracy against compiled classes
+    #[command(
+        name = "audit-java",
+        long_about = "Reads a sample of the module's compiled classes (build/classes/java/main or \
+                            target/classes) and compares the fields, methods and constructors they \
+                            declare with the indexed nodes. Reports missing classes and members, \
+                            members indexed with a different signature and indexed members the \
+                            bytecode does not declare, with the share matched as an accuracy metric. \
+                            Build the module first; the sample is stable between runs."
+    )]
+    AuditJava {
+        /// Path to the project root. Defaults to current directory.
+        #[arg(long, value_name = "PROJECT_PATH")]
+        path: Option<PathBuf>,
+        /// Module directory, relative to the project root
+        #[arg(long, value_name = "MODULE")]
+        module: Option<PathBuf>,
+        /// Directory of compiled classes, instead of the module's build output
+        #[arg(long, value_name = "DIR")]
+        classes: Option<PathBuf>,
+        /// Number of classes to compare
+        #[arg(long, default_value_t = 200)]
+        sample: usize,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show the tool calls MCP clients made on a project
+    #[command(
+        name = "audit-log",
+        long_about = "Prints the project's audit log: every tool an MCP client called, with the \
+                            client's name, the arguments, when it was called and whether it \
+                            succeeded. The log is appended to next to the index and never \
+                            rewritten; the most recent calls are listed last."
+    )]
+    AuditLog {
+        /// Path to the project root. Defaults to current directory.
+        #[arg(value_name = "PROJECT_PATH")]
+        path: Option<PathBuf>,
+        /// Only list calls of this tool
+        #[arg(long)]
+        tool: Option<String>,
+        /// Only list calls of this client
+        #[arg(long)]
+        client: Option<String>,
+        /// Number of most recent calls to list, 0 for all
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+        /// Print the entries as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Start the Model Context Protocol (MCP) server
+    #[command(
+        long_about = "Serves MCP over stdio. By default it forwards to the engine of the LSP server \
+                            running for the project, waiting for one to start. With `--standalone` \
+                            it indexes the project itself and keeps the index up to date, for \
+                            agents without an editor attached. With `--http` one WebSocket server \
+                            indexes and serves the project and every `--project`, each at \
+                            `/projects/<name>/mcp`; `naviscope mcp --path <project>` then \
+                            connects to that project's endpoint. With `--remote` it serves \
+                            the index of a `naviscope serve-index` server."
+    )]
     Mcp {
         /// Path to the project root directory
         #[arg(long, value_name = "PROJECT_PATH")]
         path: Option<PathBuf>,
+        /// Build and watch an index of our own instead of connecting to an LSP server
+        #[arg(long)]
+        standalone: bool,
+        /// Serve over WebSocket instead of stdio, indexing the projects ourselves
+        #[arg(long, conflicts_with = "standalone")]
+        http: bool,
+        /// Another project served by the same HTTP server (repeatable)
+        #[arg(long = "project", value_name = "PROJECT_PATH", requires = "http")]
+        projects: Vec<PathBuf>,
+        /// Serve the index of the index server at this address (host:port)
+        #[arg(long, value_name = "ADDR", conflicts_with_all = ["standalone", "http"])]
+        remote: Option<String>,
     },
     /// Start the Language Server Protocol (LSP) server
-    Lsp,
+    Lsp {
+        /// Answer from the index server at this address (host:port)
+        #[arg(long, value_name = "ADDR")]
+        remote: Option<String>,
+    },
     /// Manage global stub cache
     Cache {
         #[command(subcommand)]
         command: cache::CacheCommands,
     },
+    /// Generate shell completion scripts
+    #[command(
+        long_about = "Prints a completion script for the given shell to stdout, e.g. \
+                            `naviscope completions bash > /etc/bash_completion.d/naviscope`."
+    )]
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum, value_name = "SHELL")]
+        shell: clap_complete::Shell,
+    },
+    /// Generate man pages
+    #[command(
+        long_about = "Renders the naviscope man page, including the node kinds, edge types and \
+                            shell commands understood by structured queries. Prints to stdout \
+                            unless an output directory is given, in which case one page per \
+                            subcommand is written as well."
+    )]
+    Man {
+        /// Directory to write the pages to
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+    },
 }
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
+pub fn run() -> Result<ExitCode, Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
     // Initialize logging based on command
     let (component, to_stderr) = match &cli.command {
-        Commands::Lsp => ("lsp", false),
+        Commands::Lsp { .. } => ("lsp", false),
+        Commands::Mcp { http: true, .. } => ("mcp", true),
         Commands::Mcp { .. } => ("mcp", false),
         Commands::Shell { .. } => ("cli", false),
+        Commands::Tui { .. } => ("cli", false),
         Commands::Cache { .. } => ("cli", false),
         Commands::Clear { .. } => ("cli", false),
+        Commands::Stats { .. } => ("cli", false),
+        Commands::Query { .. } => ("cli", false),
+        Commands::ServeIndex { .. } => ("cli", true),
+        Commands::Diff { .. } => ("cli", false),
+        Commands::Export { .. } => ("cli", false),
+        Commands::AuditJava { .. } => ("cli", false),
+        Commands::AuditLog { .. } => ("cli", false),
+        Commands::Completions { .. } => ("cli", false),
+        Commands::Man { .. } => ("cli", false),
         _ => ("cli", true),
     };
     let _guard = naviscope_runtime::init_logging(component, to_stderr);
@@ -95,30 +402,201 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
     let rt = tokio::runtime::Runtime::new()?;
 
     match cli.command {
-        Commands::Index { path } => rt.block_on(index::run(path.canonicalize()?)),
-        Commands::Shell { path } => {
-            rt.block_on(shell::run(path.map(|p| p.canonicalize()).transpose()?))
+        Commands::Index { path, at, output } => {
+            rt.block_on(index::run(path.canonicalize()?, at, output))
+        }
+        Commands::Query {
+            query,
+            path,
+            index,
+            remote,
+        } => {
+            let project_path = match path {
+                Some(p) => p.canonicalize()?,
+                None => std::env::current_dir()?.canonicalize()?,
+            };
+            rt.block_on(query::run(project_path, index, remote, query))
         }
-        Commands::Watch { path } => rt.block_on(watch::run(path.canonicalize()?)),
+        Commands::Shell {
+            path,
+            index,
+            remote,
+            script,
+            commands,
+        } => {
+            let path = path.map(|p| p.canonicalize()).transpose()?;
+            return rt.block_on(shell::run(path, index, remote, script, commands));
+        }
+        Commands::Tui { path } => {
+            rt.block_on(tui::run(path.map(|p| p.canonicalize()).transpose()?))
+        }
+        Commands::Watch {
+            path,
+            exec,
+            emit_json,
+        } => rt.block_on(watch::run(path.canonicalize()?, exec, emit_json)),
+        Commands::ServeIndex {
+            path,
+            host,
+            port,
+            token,
+        } => rt.block_on(serve_index::run(path.canonicalize()?, host, port, token)),
         Commands::Clear { path } => {
             rt.block_on(clear::run(path.map(|p| p.canonicalize()).transpose()?))
         }
-        Commands::Mcp { path } => {
+        Commands::Stats {
+            path,
+            top,
+            json,
+            warnings,
+            runtime,
+            remote,
+        } => {
+            let project_path = match path {
+                Some(p) => p.canonicalize()?,
+                None => std::env::current_dir()?.canonicalize()?,
+            };
+            rt.block_on(stats::run(
+                project_path,
+                top,
+                json,
+                warnings,
+                runtime,
+                remote,
+            ))
+        }
+        Commands::Diff {
+            reference,
+            new,
+            path,
+            save,
+            json,
+        } => {
+            let project_path = match path {
+                Some(p) => p.canonicalize()?,
+                None => std::env::current_dir()?.canonicalize()?,
+            };
+            rt.block_on(diff::run(project_path, reference, new, save, json))
+        }
+        Commands::Export {
+            out_dir,
+            format,
+            path,
+        } => {
             let project_path = match path {
                 Some(p) => p.canonicalize()?,
                 None => std::env::current_dir()?.canonicalize()?,
             };
+            rt.block_on(export::run(project_path, format, out_dir))
+        }
+        Commands::AuditJava {
+            path,
+            module,
+            classes,
+            sample,
+            json,
+        } => {
+            let project_path = match path {
+                Some(p) => p.canonicalize()?,
+                None => std::env::current_dir()?.canonicalize()?,
+            };
+            rt.block_on(audit::run(project_path, module, classes, sample, json))
+        }
+        Commands::AuditLog {
+            path,
+            tool,
+            client,
+            limit,
+            json,
+        } => {
+            let project_path = match path {
+                Some(p) => p.canonicalize()?,
+                None => std::env::current_dir()?.canonicalize()?,
+            };
+            rt.block_on(audit_log::run(project_path, tool, client, limit, json))
+        }
+        Commands::Mcp {
+            path,
+            standalone,
+            http,
+            projects,
+            remote,
+        } => {
+            if let Some(addr) = remote {
+                rt.block_on(async {
+                    let engine = naviscope_runtime::connect_remote(&addr);
+                    engine.load().await?;
+                    let engine = std::sync::Arc::new(tokio::sync::RwLock::new(Some(engine)));
+                    naviscope_mcp::stdio::run_stdio_server(engine, None).await
+                })?;
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let project_path = match path {
+                Some(p) => p.canonicalize()?,
+                None => std::env::current_dir()?.canonicalize()?,
+            };
+
+            if http {
+                let mut roots = vec![project_path];
+                for project in projects {
+                    roots.push(project.canonicalize()?);
+                }
+                rt.block_on(async {
+                    let engines: Vec<_> = roots
+                        .into_iter()
+                        .map(|root| {
+                            let engine = naviscope_runtime::build_engine(
+                                root.clone(),
+                                naviscope_runtime::Profile::Mcp,
+                            );
+                            (root, engine)
+                        })
+                        .collect();
+                    let settings = engines[0].1.host_settings().await.unwrap_or_default();
+                    let access = naviscope_mcp::http::HttpAccess::from_settings(&settings);
+
+                    let cancel_token = tokio_util::sync::CancellationToken::new();
+                    tokio::spawn({
+                        let cancel_token = cancel_token.clone();
+                        async move {
+                            let _ = tokio::signal::ctrl_c().await;
+                            cancel_token.cancel();
+                        }
+                    });
+                    naviscope_mcp::http::run_projects_server(engines, access, cancel_token).await
+                })?;
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            if standalone {
+                rt.block_on(async {
+                    let engine = naviscope_runtime::build_engine(
+                        project_path,
+                        naviscope_runtime::Profile::Mcp,
+                    );
+                    naviscope_mcp::stdio::run_standalone_server(engine).await
+                })?;
+                return Ok(ExitCode::SUCCESS);
+            }
 
             // Connect to LSP via proxy mode (waits for LSP if not started)
             rt.block_on(async { naviscope_mcp::proxy::run_mcp_proxy(&project_path).await })?;
             Ok(())
         }
-        Commands::Lsp => {
+        Commands::Lsp { remote } => {
             rt.block_on(async {
-                naviscope_lsp::run_server(naviscope_runtime::build_default_engine).await
+                naviscope_lsp::run_server(move |path| match &remote {
+                    Some(addr) => naviscope_runtime::connect_remote(addr),
+                    None => naviscope_runtime::build_engine(path, naviscope_runtime::Profile::Lsp),
+                })
+                .await
             })?;
             Ok(())
         }
         Commands::Cache { command } => rt.block_on(cache::run(command)),
-    }
+        Commands::Completions { shell } => completions::run(shell),
+        Commands::Man { out_dir } => man::run(out_dir),
+    }?;
+    Ok(ExitCode::SUCCESS)
 }
@@ -0,0 +1,64 @@
+use clap::CommandFactory;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+pub fn run(out_dir: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = crate::Cli::command();
+
+    let Some(dir) = out_dir else {
+        render_page(&cmd, &mut io::stdout().lock())?;
+        return Ok(());
+    };
+
+    std::fs::create_dir_all(&dir)?;
+    let mut file = std::fs::File::create(dir.join("naviscope.1"))?;
+    render_page(&cmd, &mut file)?;
+    for sub in cmd.get_subcommands() {
+        let name = format!("naviscope-{}", sub.get_name());
+        let mut file = std::fs::File::create(dir.join(format!("{name}.1")))?;
+        clap_mangen::Man::new(sub.clone().name(name)).render(&mut file)?;
+    }
+    println!("Wrote man pages to {}", dir.display());
+    Ok(())
+}
+
+/// Top-level page followed by the shell query vocabulary.
+fn render_page(cmd: &clap::Command, out: &mut dyn Write) -> io::Result<()> {
+    clap_mangen::Man::new(cmd.clone()).render(out)?;
+
+    writeln!(out, ".SH \"QUERY VOCABULARY\"")?;
+    writeln!(
+        out,
+        "Commands and filter values accepted by \\fBnaviscope shell\\fR."
+    )?;
+    for sub in crate::shell::shell_commands() {
+        writeln!(out, ".TP")?;
+        writeln!(out, "\\fB{}\\fR", sub.get_name())?;
+        if let Some(about) = sub.get_about() {
+            writeln!(out, "{about}")?;
+        }
+    }
+    for (label, values) in crate::shell::query_vocabulary() {
+        writeln!(out, ".TP")?;
+        writeln!(out, "\\fB{label}\\fR")?;
+        writeln!(out, "{}", values.join(", "))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_lists_shell_commands_and_query_vocabulary() {
+        let mut page = Vec::new();
+        render_page(&crate::Cli::command(), &mut page).unwrap();
+        let page = String::from_utf8(page).unwrap();
+
+        let vocabulary = page.find(".SH \"QUERY VOCABULARY\"").unwrap();
+        assert!(page[..vocabulary].contains("naviscope"));
+        assert!(page[vocabulary..].contains("\\fBls\\fR"));
+        assert!(page[vocabulary..].contains("\\fBnode kinds\\fR"));
+    }
+}
@@ -0,0 +1,53 @@
+use naviscope_api::models::GraphQuery;
+use std::path::PathBuf;
+
+pub async fn run(
+    path: PathBuf,
+    index: Option<PathBuf>,
+    remote: Option<String>,
+    query: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let engine = match (index, remote) {
+        (Some(index), _) => naviscope_runtime::open_readonly(path.clone(), index).await?,
+        (None, Some(addr)) => naviscope_runtime::connect_remote(&addr),
+        (None, None) => {
+            let engine = naviscope_runtime::build_default_engine(path.clone());
+            if !engine.load().await? {
+                return Err(format!(
+                    "No index found for {}. Run `naviscope index` first.",
+                    path.display()
+                )
+                .into());
+            }
+            engine
+        }
+    };
+
+    let value: serde_json::Value = serde_json::from_str(&query)?;
+    let validation = engine.validate_query(&value).await?;
+    if !validation.valid {
+        for issue in &validation.issues {
+            let field = if issue.field.is_empty() {
+                "query"
+            } else {
+                &issue.field
+            };
+            match &issue.suggestion {
+                Some(suggestion) => eprintln!(
+                    "{}: {} (did you mean `{}`?)",
+                    field, issue.message, suggestion
+                ),
+                None => eprintln!("{}: {}", field, issue.message),
+            }
+        }
+        return Err("Invalid query".into());
+    }
+    let query: GraphQuery = match validation.query {
+        Some(query) => query,
+        None => serde_json::from_value(value)?,
+    };
+
+    let result = engine.query(&query).await?;
+    println!("{}", serde_json::to_string_pretty(&result)?);
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use naviscope_api::lifecycle::SHUTDOWN_DEADLINE;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+pub async fn run(
+    path: PathBuf,
+    host: IpAddr,
+    port: u16,
+    token: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let engine = naviscope_runtime::build_engine(path.clone(), naviscope_runtime::Profile::Mcp);
+
+    info!("Indexing project at: {}...", path.display());
+    engine.rebuild().await?;
+    let stats = engine.get_stats().await?;
+    info!(
+        "Indexing complete. Nodes: {}, Edges: {}",
+        stats.node_count, stats.edge_count
+    );
+    let watch_handle = engine.start_watch().await?;
+
+    let token = token
+        .or_else(|| std::env::var(naviscope_runtime::REMOTE_TOKEN_ENV).ok())
+        .filter(|token| !token.is_empty());
+    let token = match token {
+        Some(token) => token,
+        None => {
+            let token = naviscope_mcp::http::generate_token();
+            eprintln!(
+                "Clients must set {}={}",
+                naviscope_runtime::REMOTE_TOKEN_ENV,
+                token
+            );
+            token
+        }
+    };
+
+    let listener = tokio::net::TcpListener::bind(SocketAddr::new(host, port)).await?;
+    eprintln!("Serving the index on {}", listener.local_addr()?);
+    info!("Press Ctrl+C to stop.");
+
+    let cancel_token = CancellationToken::new();
+    tokio::spawn({
+        let cancel_token = cancel_token.clone();
+        async move {
+            let _ = tokio::signal::ctrl_c().await;
+            cancel_token.cancel();
+        }
+    });
+    let result = naviscope_remote::serve(engine.clone(), listener, token, cancel_token).await;
+    watch_handle.stop();
+    if let Err(e) = engine.shutdown(SHUTDOWN_DEADLINE).await {
+        warn!("Engine shutdown incomplete: {}", e);
+    }
+    result?;
+    Ok(())
+}
@@ -1,6 +1,11 @@
-use super::view::{ShellNodeView, ShellNodeViewShort, get_kind_weight};
+use super::output::{OutputFormat, OutputTarget};
+use super::view::{
+    ShellGroupView, ShellNodeView, ShellNodeViewShort, describe_history, get_kind_weight,
+    is_container, render_chain,
+};
 use clap::{Parser, ValueEnum};
-use naviscope_api::models::{EdgeType, GraphQuery, NodeKind, NodeSource, QueryResult};
+use naviscope_api::models::graph::ResolutionStatus;
+use naviscope_api::models::{EdgeType, GraphQuery, GroupBy, NodeKind, NodeSource, QueryResult};
 use shlex;
 use tabled::{Table, settings::Style};
 
@@ -23,6 +28,10 @@ pub enum CliNodeKind {
     Dependency,
     Task,
     Plugin,
+    File,
+    Route,
+    Table,
+    Column,
     Other,
 }
 
@@ -34,6 +43,26 @@ pub enum CliNodeSource {
     Builtin,
 }
 
+#[derive(Clone, Debug, ValueEnum)]
+#[clap(rename_all = "kebab-case")]
+pub enum CliResolutionStatus {
+    Unresolved,
+    Stubbed,
+    Resolved,
+    PartialParse,
+}
+
+impl From<CliResolutionStatus> for ResolutionStatus {
+    fn from(status: CliResolutionStatus) -> Self {
+        match status {
+            CliResolutionStatus::Unresolved => ResolutionStatus::Unresolved,
+            CliResolutionStatus::Stubbed => ResolutionStatus::Stubbed,
+            CliResolutionStatus::Resolved => ResolutionStatus::Resolved,
+            CliResolutionStatus::PartialParse => ResolutionStatus::PartialParse,
+        }
+    }
+}
+
 impl From<CliNodeSource> for NodeSource {
     fn from(source: CliNodeSource) -> Self {
         match source {
@@ -60,11 +89,33 @@ impl From<CliNodeKind> for NodeKind {
             CliNodeKind::Dependency => NodeKind::Dependency,
             CliNodeKind::Task => NodeKind::Task,
             CliNodeKind::Plugin => NodeKind::Plugin,
+            CliNodeKind::File => NodeKind::File,
+            CliNodeKind::Route => NodeKind::Custom("route".to_string()),
+            CliNodeKind::Table => NodeKind::Custom("table".to_string()),
+            CliNodeKind::Column => NodeKind::Custom("column".to_string()),
             CliNodeKind::Other => NodeKind::Custom("other".to_string()),
         }
     }
 }
 
+#[derive(Clone, Debug, ValueEnum)]
+#[clap(rename_all = "lowercase")]
+pub enum CliGroupBy {
+    Kind,
+    Package,
+    Module,
+}
+
+impl From<CliGroupBy> for GroupBy {
+    fn from(group_by: CliGroupBy) -> Self {
+        match group_by {
+            CliGroupBy::Kind => GroupBy::Kind,
+            CliGroupBy::Package => GroupBy::Package,
+            CliGroupBy::Module => GroupBy::Module,
+        }
+    }
+}
+
 #[derive(Clone, Debug, ValueEnum)]
 pub enum CliEdgeType {
     Contains,
@@ -73,6 +124,17 @@ pub enum CliEdgeType {
     TypedAs,
     DecoratedBy,
     UsesDependency,
+    Requires,
+    Exports,
+    AliasOf,
+    GeneratedFrom,
+    Declares,
+    CrossLanguage,
+    Injects,
+    BeanOf,
+    HandledBy,
+    MapsTo,
+    Tests,
 }
 
 impl From<CliEdgeType> for EdgeType {
@@ -84,6 +146,17 @@ impl From<CliEdgeType> for EdgeType {
             CliEdgeType::TypedAs => EdgeType::TypedAs,
             CliEdgeType::DecoratedBy => EdgeType::DecoratedBy,
             CliEdgeType::UsesDependency => EdgeType::UsesDependency,
+            CliEdgeType::Requires => EdgeType::Requires,
+            CliEdgeType::Exports => EdgeType::Exports,
+            CliEdgeType::AliasOf => EdgeType::AliasOf,
+            CliEdgeType::GeneratedFrom => EdgeType::GeneratedFrom,
+            CliEdgeType::Declares => EdgeType::Declares,
+            CliEdgeType::CrossLanguage => EdgeType::CrossLanguage,
+            CliEdgeType::Injects => EdgeType::Injects,
+            CliEdgeType::BeanOf => EdgeType::BeanOf,
+            CliEdgeType::HandledBy => EdgeType::HandledBy,
+            CliEdgeType::MapsTo => EdgeType::MapsTo,
+            CliEdgeType::Tests => EdgeType::Tests,
         }
     }
 }
@@ -94,7 +167,7 @@ impl From<CliEdgeType> for EdgeType {
 pub enum ShellCommand {
     /// List members or structure
     Ls {
-        /// Target node FQN (optional, defaults to current node)
+        /// Target node FQN or `file:<path>` (optional, defaults to current node)
         fqn: Option<String>,
         /// Filter by node kind (e.g. class, interface, method)
         #[arg(long, value_delimiter = ',')]
@@ -102,6 +175,9 @@ pub enum ShellCommand {
         /// Filter by node source (e.g. project, external, builtin)
         #[arg(long, value_delimiter = ',')]
         source: Vec<CliNodeSource>,
+        /// Filter by resolution status (e.g. resolved, stubbed)
+        #[arg(long, value_delimiter = ',')]
+        status: Vec<CliResolutionStatus>,
         /// Filter by modifiers (e.g. public, static)
         #[arg(long, value_delimiter = ',')]
         modifiers: Vec<String>,
@@ -111,6 +187,12 @@ pub enum ShellCommand {
         /// Show all nodes (including external and builtins)
         #[arg(short, long)]
         all: bool,
+        /// Show counts per group instead of nodes
+        #[arg(long)]
+        group_by: Option<CliGroupBy>,
+        /// Show only the number of matching nodes
+        #[arg(long)]
+        count: bool,
     },
     /// Change current node context (internal shell command)
     Cd {
@@ -131,9 +213,24 @@ pub enum ShellCommand {
         /// Filter by node source
         #[arg(long, value_delimiter = ',')]
         source: Vec<CliNodeSource>,
+        /// Filter by resolution status (e.g. resolved, stubbed)
+        #[arg(long, value_delimiter = ',')]
+        status: Vec<CliResolutionStatus>,
+        /// Filter by modifiers (e.g. public, vulnerable, license:MIT)
+        #[arg(long, value_delimiter = ',')]
+        modifiers: Vec<String>,
         /// Limit number of results
         #[arg(long, default_value_t = DEFAULT_SEARCH_LIMIT)]
         limit: usize,
+        /// Show counts per group instead of nodes
+        #[arg(long)]
+        group_by: Option<CliGroupBy>,
+        /// Show only the number of matching nodes
+        #[arg(long)]
+        count: bool,
+        /// Match the pattern as an abbreviation (e.g. `UsrCtl`) and rank results
+        #[arg(long, conflicts_with_all = ["group_by", "count"])]
+        fuzzy: bool,
     },
     /// Inspect node details
     Cat {
@@ -150,6 +247,94 @@ pub enum ShellCommand {
         /// Filter by edge types (e.g. TypedAs, InheritsFrom)
         #[arg(long, value_delimiter = ',')]
         edge_types: Vec<CliEdgeType>,
+        /// Filter related nodes by source
+        #[arg(long, value_delimiter = ',')]
+        source: Vec<CliNodeSource>,
+        /// Filter related nodes by resolution status
+        #[arg(long, value_delimiter = ',')]
+        status: Vec<CliResolutionStatus>,
+        /// Show counts per group instead of nodes
+        #[arg(long)]
+        group_by: Option<CliGroupBy>,
+        /// Show only the number of matching nodes
+        #[arg(long)]
+        count: bool,
+    },
+    /// Find dependency cycles between classes or packages
+    Cycles {
+        /// Scope FQN (optional, defaults to current node)
+        scope: Option<String>,
+        /// Filter by edge types (e.g. TypedAs, InheritsFrom)
+        #[arg(long, value_delimiter = ',')]
+        edge_types: Vec<CliEdgeType>,
+        /// Report cycles between packages instead of classes
+        #[arg(long)]
+        packages: bool,
+    },
+    /// Show the shortest chain of edges leading from one node to another
+    #[command(visible_alias = "find-path")]
+    Path {
+        /// Start node FQN
+        from: String,
+        /// End node FQN
+        to: String,
+        /// Follow only these edge types (e.g. Contains,TypedAs)
+        #[arg(long, value_delimiter = ',')]
+        edges: Vec<CliEdgeType>,
+        /// Maximum number of edges in the chain
+        #[arg(long, default_value_t = 10)]
+        max_depth: usize,
+    },
+    /// List groups of near-duplicate methods
+    Duplicates {
+        /// Ignore methods with fewer normalized tokens
+        #[arg(long, default_value_t = 50)]
+        min_tokens: usize,
+    },
+    /// List public types and methods nothing is known to use
+    Dead {
+        /// Scope FQN (optional, defaults to current node)
+        scope: Option<String>,
+        /// More entry points: `@Annotation` or method name (e.g. @Scheduled,run)
+        #[arg(long, value_delimiter = ',')]
+        entry_points: Vec<String>,
+    },
+    /// List the test methods exercising a class
+    #[command(visible_alias = "tests_for")]
+    TestsFor {
+        /// Class, member or package FQN (optional, defaults to current node)
+        fqn: Option<String>,
+    },
+    /// Check whether a symbol can be deleted safely
+    #[command(visible_alias = "safe_delete")]
+    SafeDelete {
+        /// Target node FQN (optional, defaults to current node)
+        fqn: Option<String>,
+    },
+    /// Show the containment hierarchy as an indented tree
+    Tree {
+        /// Root node FQN (optional, defaults to current node)
+        fqn: Option<String>,
+        /// Levels below the root to show
+        #[arg(short, long, default_value_t = 2)]
+        depth: usize,
+        /// Show only nodes of these kinds, with the containers leading to them
+        #[arg(long, value_delimiter = ',')]
+        kind: Vec<CliNodeKind>,
+        /// Show all nodes (including external and builtins)
+        #[arg(short, long)]
+        all: bool,
+    },
+    /// Resolve the symbol at a source position
+    What {
+        /// Position as path:line:col (1-based)
+        location: String,
+    },
+    /// Wait until the file changes seen so far are indexed
+    Sync {
+        /// Seconds to wait at most
+        #[arg(long, default_value_t = 60)]
+        timeout: u64,
     },
 }
 
@@ -167,15 +352,122 @@ impl ShellCommand {
     }
 }
 
-pub fn parse_shell_command(
-    input: &str,
-) -> Result<Option<ShellCommand>, Box<dyn std::error::Error>> {
+/// Values accepted by the query filters, taken from the clap enums above.
+pub fn query_vocabulary() -> Vec<(&'static str, Vec<String>)> {
+    fn names<T: ValueEnum>() -> Vec<String> {
+        T::value_variants()
+            .iter()
+            .filter_map(|v| v.to_possible_value())
+            .map(|v| v.get_name().to_string())
+            .collect()
+    }
+
+    vec![
+        ("node kinds", names::<CliNodeKind>()),
+        ("node sources", names::<CliNodeSource>()),
+        ("resolution statuses", names::<CliResolutionStatus>()),
+        ("edge types", names::<CliEdgeType>()),
+        ("group by", names::<CliGroupBy>()),
+    ]
+}
+
+/// Split a script into command lines, one per line or `;`, skipping blank
+/// lines and `#` comments. Separators inside quotes are kept.
+pub fn split_commands(script: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    for line in script.lines() {
+        let mut current = String::new();
+        let mut quote = None;
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match (quote, c) {
+                (Some('"'), '\\') => {
+                    current.push(c);
+                    current.extend(chars.next());
+                }
+                (Some(q), c) if c == q => {
+                    quote = None;
+                    current.push(c);
+                }
+                (Some(_), c) => current.push(c),
+                (None, '\'' | '"') => {
+                    quote = Some(c);
+                    current.push(c);
+                }
+                (None, '#') if current.trim().is_empty() => break,
+                (None, ';') => commands.push(std::mem::take(&mut current)),
+                (None, c) => current.push(c),
+            }
+        }
+        commands.push(current);
+    }
+    commands
+        .into_iter()
+        .map(|command| command.trim().to_string())
+        .filter(|command| !command.is_empty())
+        .collect()
+}
+
+/// A parsed command line: the command and where its output goes
+pub struct ShellLine {
+    pub command: ShellCommand,
+    pub format: OutputFormat,
+    pub target: OutputTarget,
+}
+
+pub fn parse_shell_command(input: &str) -> Result<Option<ShellLine>, Box<dyn std::error::Error>> {
     // Use shlex to split arguments while respecting quotes
-    let args = shlex::split(input).ok_or("Invalid quoting")?;
+    let mut args = shlex::split(input).ok_or("Invalid quoting")?;
+
+    let mut target = OutputTarget::Stdout;
+    if let Some(pipe) = args.iter().position(|a| a == "|") {
+        let rest: Vec<String> = args.drain(pipe..).skip(1).collect();
+        if rest.is_empty() {
+            return Err("Missing command after '|'".into());
+        }
+        // Later `|` stay unquoted so that the system shell runs the pipeline
+        let stages = rest
+            .split(|a| a == "|")
+            .map(|stage| shlex::try_join(stage.iter().map(String::as_str)))
+            .collect::<Result<Vec<_>, _>>()?;
+        target = OutputTarget::Pipe(stages.join(" | "));
+    }
+    if let Some(redirect) = args.iter().position(|a| a.starts_with('>')) {
+        if target != OutputTarget::Stdout {
+            return Err("Output can be redirected or piped, not both".into());
+        }
+        let mut rest = args.drain(redirect..);
+        let operator = rest.next().unwrap_or_default();
+        let append = operator.starts_with(">>");
+        let attached = operator.trim_start_matches('>');
+        let path = if attached.is_empty() {
+            rest.next().ok_or("Missing file after '>'")?
+        } else {
+            attached.to_string()
+        };
+        if rest.next().is_some() {
+            return Err("Redirection must come last".into());
+        }
+        target = OutputTarget::File {
+            path: path.into(),
+            append,
+        };
+    }
+    let format = match args.iter().position(|a| a == "--json") {
+        Some(json) => {
+            args.remove(json);
+            OutputFormat::Json
+        }
+        None => OutputFormat::Table,
+    };
 
     // Parse using Clap
     match ShellCommand::try_parse_from(args) {
-        Ok(c) => Ok(Some(c)),
+        Ok(command) => Ok(Some(ShellLine {
+            command,
+            format,
+            target,
+        })),
         Err(e) => {
             // Handle help/version display without returning an error
             if e.kind() == ErrorKind::DisplayHelp || e.kind() == ErrorKind::DisplayVersion {
@@ -187,6 +479,10 @@ pub fn parse_shell_command(
     }
 }
 
+fn statuses(status: &[CliResolutionStatus]) -> Vec<ResolutionStatus> {
+    status.iter().map(|s| s.clone().into()).collect()
+}
+
 impl ShellCommand {
     pub fn to_graph_query(
         &self,
@@ -197,8 +493,11 @@ impl ShellCommand {
                 fqn,
                 kind,
                 source,
+                status,
                 modifiers,
                 all,
+                group_by,
+                count,
                 ..
             } => {
                 let target_fqn = fqn.clone().or_else(|| current_node.clone());
@@ -217,14 +516,40 @@ impl ShellCommand {
                     fqn: target_fqn,
                     kind: kind.iter().map(|k| k.clone().into()).collect(),
                     sources,
+                    statuses: statuses(status),
                     modifiers: modifiers.clone(),
+                    group_by: group_by.clone().map(Into::into),
+                    count: *count,
                 })
             }
             ShellCommand::Find {
                 pattern,
                 kind,
                 source,
+                status,
+                limit,
+                fuzzy: true,
+                ..
+            } => Ok(GraphQuery::Fuzzy {
+                pattern: pattern.clone(),
+                kind: kind.iter().map(|k| k.clone().into()).collect(),
+                sources: source
+                    .iter()
+                    .map(|s| s.clone().into())
+                    .collect::<Vec<NodeSource>>(),
+                statuses: statuses(status),
+                limit: *limit,
+            }),
+            ShellCommand::Find {
+                pattern,
+                kind,
+                source,
+                status,
+                modifiers,
                 limit,
+                group_by,
+                count,
+                fuzzy: false,
             } => Ok(GraphQuery::Find {
                 pattern: pattern.clone(),
                 kind: kind.iter().map(|k| k.clone().into()).collect(),
@@ -232,15 +557,24 @@ impl ShellCommand {
                     .iter()
                     .map(|s| s.clone().into())
                     .collect::<Vec<NodeSource>>(),
+                statuses: statuses(status),
+                modifiers: modifiers.clone(),
                 limit: *limit,
+                group_by: group_by.clone().map(Into::into),
+                count: *count,
             }),
             ShellCommand::Cat { target } => Ok(GraphQuery::Cat {
                 fqn: target.clone(),
+                limits: None,
             }),
             ShellCommand::Deps {
                 fqn,
                 rev,
                 edge_types,
+                source,
+                status,
+                group_by,
+                count,
             } => {
                 let target_fqn = fqn
                     .clone()
@@ -250,9 +584,56 @@ impl ShellCommand {
                     fqn: target_fqn,
                     rev: *rev,
                     edge_types: edge_types.iter().map(|e| e.clone().into()).collect(),
+                    sources: source.iter().map(|s| s.clone().into()).collect(),
+                    statuses: statuses(status),
+                    group_by: group_by.clone().map(Into::into),
+                    count: *count,
                 })
             }
-            ShellCommand::Cd { .. } | ShellCommand::Pwd | ShellCommand::Clear => {
+            ShellCommand::Cycles {
+                scope,
+                edge_types,
+                packages,
+            } => Ok(GraphQuery::Cycles {
+                scope_fqn: scope.clone().or_else(|| current_node.clone()),
+                edge_types: edge_types.iter().map(|e| e.clone().into()).collect(),
+                packages: *packages,
+            }),
+            ShellCommand::Duplicates { min_tokens } => Ok(GraphQuery::Duplicates {
+                min_tokens: *min_tokens,
+            }),
+            ShellCommand::Dead {
+                scope,
+                entry_points,
+            } => Ok(GraphQuery::Dead {
+                scope_fqn: scope.clone().or_else(|| current_node.clone()),
+                entry_points: entry_points.clone(),
+            }),
+            ShellCommand::TestsFor { fqn } => {
+                let target_fqn = fqn
+                    .clone()
+                    .or_else(|| current_node.clone())
+                    .ok_or("No FQN provided and no current context")?;
+                Ok(GraphQuery::TestsFor { fqn: target_fqn })
+            }
+            ShellCommand::Path {
+                from,
+                to,
+                edges,
+                max_depth,
+            } => Ok(GraphQuery::Path {
+                from: from.clone(),
+                to: to.clone(),
+                edge_types: edges.iter().map(|e| e.clone().into()).collect(),
+                max_depth: *max_depth,
+            }),
+            ShellCommand::Cd { .. }
+            | ShellCommand::Pwd
+            | ShellCommand::Clear
+            | ShellCommand::SafeDelete { .. }
+            | ShellCommand::Tree { .. }
+            | ShellCommand::What { .. }
+            | ShellCommand::Sync { .. } => {
                 Err("Internal shell command should be handled by ReplServer".into())
             }
         }
@@ -262,7 +643,53 @@ impl ShellCommand {
         &self,
         result: QueryResult,
         _context: &super::context::ShellContext,
+        format: OutputFormat,
     ) -> Result<String, Box<dyn std::error::Error>> {
+        if format == OutputFormat::Json {
+            return Ok(serde_json::to_string_pretty(&result)?);
+        }
+        if result.truncated {
+            let mut partial = result;
+            partial.truncated = false;
+            // The total of a listing counts what the budget left out
+            let note = match partial.total {
+                Some(total) if !partial.nodes.is_empty() => {
+                    partial.total = None;
+                    format!(
+                        "(truncated: {} of {} results, narrow the query or raise [query] max_results)",
+                        partial.nodes.len(),
+                        total
+                    )
+                }
+                _ => "(truncated: the query ran out of time, raise [query] timeout_ms for all results)"
+                    .to_string(),
+            };
+            return Ok(format!(
+                "{}\n{}",
+                self.render(partial, _context, format)?,
+                note
+            ));
+        }
+        if let Some(total) = result.total {
+            if result.groups.is_empty() {
+                return Ok(format!("{} matching nodes", total));
+            }
+            let views: Vec<ShellGroupView> = result
+                .groups
+                .iter()
+                .map(|g| ShellGroupView {
+                    group: g.key.clone(),
+                    count: g.count,
+                })
+                .collect();
+            return Ok(format!(
+                "{}\n{} matching nodes in {} groups",
+                Table::new(&views).with(Style::psql()),
+                total,
+                result.groups.len()
+            ));
+        }
+
         if result.nodes.is_empty() {
             return Ok("NO RECORDS FOUND".to_string());
         }
@@ -274,7 +701,7 @@ impl ShellCommand {
                     .iter()
                     .map(|node| ShellNodeViewShort {
                         kind: node.kind.to_string(),
-                        name: if is_container(node.kind.clone()) {
+                        name: if is_container(&node.kind) {
                             format!("{}/", node.name)
                         } else {
                             node.name.to_string()
@@ -294,8 +721,56 @@ impl ShellCommand {
 
                 Ok(Table::new(&views).with(Style::psql()).to_string())
             }
+            ShellCommand::Cycles { .. } => {
+                let mut lines = Vec::new();
+                let mut members = result.nodes.iter();
+                for (i, group) in result.groups.iter().enumerate() {
+                    let cycle: Vec<&str> = members
+                        .by_ref()
+                        .take(group.count)
+                        .map(|node| node.id.as_str())
+                        .collect();
+                    lines.push(format!(
+                        "cycle {} ({} nodes): {}",
+                        i + 1,
+                        group.count,
+                        cycle.join(" <-> ")
+                    ));
+                }
+                Ok(lines.join("\n"))
+            }
+            ShellCommand::Duplicates { .. } => {
+                let mut lines = Vec::new();
+                let mut members = result.nodes.iter();
+                for (i, group) in result.groups.iter().enumerate() {
+                    lines.push(format!("clone group {} ({} methods):", i + 1, group.count));
+                    for node in members.by_ref().take(group.count) {
+                        let at = node
+                            .location
+                            .as_ref()
+                            .map(|loc| format!("  {}:{}", loc.path, loc.range.start_line + 1))
+                            .unwrap_or_default();
+                        lines.push(format!("  {}{}", node.id, at));
+                    }
+                }
+                Ok(lines.join("\n"))
+            }
+            ShellCommand::Path { .. } => Ok(render_chain(&result.nodes, &result.edges)),
             ShellCommand::Cat { .. } if result.nodes.len() == 1 => {
-                Ok(serde_json::to_string_pretty(&result.nodes[0])?)
+                let mut out = serde_json::to_string_pretty(&result.nodes[0])?;
+                if let Some(history) = &result.history {
+                    out.push_str(&format!("\n{}", describe_history(history)));
+                }
+                for note in &result.notes {
+                    out.push_str(&format!("\nnote {}:", note.id));
+                    for tag in &note.tags {
+                        out.push_str(&format!(" #{}", tag));
+                    }
+                    if !note.text.is_empty() {
+                        out.push_str(&format!(" {}", note.text));
+                    }
+                }
+                Ok(out)
             }
             _ => {
                 // Default detailed table view for Find, Deps, and Ls -l
@@ -338,14 +813,79 @@ impl ShellCommand {
     }
 }
 
-fn is_container(kind: NodeKind) -> bool {
-    matches!(
-        kind,
-        NodeKind::Class
-            | NodeKind::Interface
-            | NodeKind::Enum
-            | NodeKind::Annotation
-            | NodeKind::Module
-            | NodeKind::Package
-    )
+/// Split `path:line:col` into its parts, keeping any `:` inside the path.
+pub fn parse_location(location: &str) -> Result<(String, u32, u32), String> {
+    let invalid = || format!("Expected path:line:col, got '{}'", location);
+    let mut parts = location.rsplitn(3, ':');
+    let col = parts.next().and_then(|c| c.parse::<u32>().ok());
+    let line = parts.next().and_then(|l| l.parse::<u32>().ok());
+    let path = parts.next().filter(|p| !p.is_empty());
+    match (path, line, col) {
+        (Some(path), Some(line), Some(col)) if line > 0 && col > 0 => {
+            Ok((path.to_string(), line, col))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> ShellLine {
+        parse_shell_command(input).unwrap().unwrap()
+    }
+
+    #[test]
+    fn output_options_are_taken_off_the_command() {
+        let line = parse("ls --json --kind class > 'out file.json'");
+        assert!(matches!(line.command, ShellCommand::Ls { ref kind, .. } if kind.len() == 1));
+        assert_eq!(line.format, OutputFormat::Json);
+        assert_eq!(
+            line.target,
+            OutputTarget::File {
+                path: "out file.json".into(),
+                append: false
+            }
+        );
+
+        let line = parse("ls >>out.txt");
+        assert_eq!(line.format, OutputFormat::Table);
+        assert_eq!(
+            line.target,
+            OutputTarget::File {
+                path: "out.txt".into(),
+                append: true
+            }
+        );
+
+        let line = parse("ls -l | grep 'a b' | wc -l");
+        assert_eq!(
+            line.target,
+            OutputTarget::Pipe("grep 'a b' | wc -l".to_string())
+        );
+    }
+
+    #[test]
+    fn misplaced_output_options_are_rejected() {
+        assert!(parse_shell_command("ls |").is_err());
+        assert!(parse_shell_command("ls >").is_err());
+        assert!(parse_shell_command("ls > a b").is_err());
+        assert!(parse_shell_command("ls > a | cat").is_err());
+    }
+
+    #[test]
+    fn scripts_split_on_lines_and_unquoted_semicolons() {
+        let script =
+            "# setup\ncd com.example; ls -l\n\n  find 'a;b' # no comment\ngrep \"x\\\";y\"; ";
+        assert_eq!(
+            split_commands(script),
+            vec![
+                "cd com.example",
+                "ls -l",
+                "find 'a;b' # no comment",
+                "grep \"x\\\";y\"",
+            ]
+        );
+    }
 }
@@ -43,11 +43,23 @@ impl<'a> Completer for NaviscopeCompleter<'a> {
                 .collect();
         }
 
-        // 2. Argument completion (for cd, ls, cat, deps)
+        // 2. Argument completion (for cd, ls, tree, cat, deps, cycles, dead, tests-for, path)
         let parts: Vec<&str> = trimmed.split_whitespace().collect();
         if parts.len() >= 1 {
             let cmd = parts[0];
-            if matches!(cmd, "cd" | "ls" | "cat" | "deps") {
+            if matches!(
+                cmd,
+                "cd" | "ls"
+                    | "tree"
+                    | "cat"
+                    | "deps"
+                    | "cycles"
+                    | "dead"
+                    | "tests-for"
+                    | "tests_for"
+                    | "path"
+                    | "find-path"
+            ) {
                 // Determine the partial FQN being typed
                 let last_word = if line.ends_with(' ') {
                     ""
@@ -103,7 +115,10 @@ impl<'a> Completer for NaviscopeCompleter<'a> {
                     fqn: parent_fqn.clone(),
                     kind: vec![],
                     sources: vec![],
+                    statuses: vec![],
                     modifiers: vec![],
+                    group_by: None,
+                    count: false,
                 };
 
                 if let Ok(result) = self.context.execute_query(&query) {
@@ -1,14 +1,34 @@
 use naviscope_api::NaviscopeEngine;
 use naviscope_api::graph::GraphService;
-use naviscope_api::models::{GraphQuery, QueryResult};
+use naviscope_api::models::{GraphQuery, QueryResult, ResolvedSymbol, SafeDeleteReport};
 use naviscope_api::navigation::NavigationService;
+use naviscope_api::semantic::SymbolNavigator;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// How often `sync` checks for changes left without a commit, e.g. by a
+/// failed update
+const SYNC_POLL: Duration = Duration::from_millis(250);
 
 #[derive(Clone)]
 pub struct ShellContext {
     pub engine: Arc<dyn NaviscopeEngine>,
     pub rt_handle: tokio::runtime::Handle,
     pub current_node: Arc<RwLock<Option<String>>>,
+    /// Files changed by updates committed since the last command ran
+    updated_files: Arc<AtomicUsize>,
+}
+
+/// Whether the index matches the files on disk, as shown in the prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    /// The watcher has changes it has not committed yet
+    Reindexing,
+    /// Updates were committed since the last command, so its output may be
+    /// out of date
+    Updated,
+    Fresh,
 }
 
 // Re-export ResolveResult from API
@@ -24,6 +44,71 @@ impl ShellContext {
             engine,
             rt_handle,
             current_node,
+            updated_files: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    fn block_on<F: std::future::Future>(&self, future: F) -> F::Output {
+        if tokio::runtime::Handle::try_current().is_ok() {
+            tokio::task::block_in_place(|| self.rt_handle.block_on(future))
+        } else {
+            self.rt_handle.block_on(future)
+        }
+    }
+
+    /// Count the files of the updates the engine commits from now on.
+    /// Engines that do not report changes are left alone.
+    pub async fn follow_changes(&self) {
+        let Ok(mut changes) = self.engine.subscribe_changes().await else {
+            return;
+        };
+        let updated_files = self.updated_files.clone();
+        self.rt_handle.spawn(async move {
+            while let Ok(summary) = changes.next().await {
+                updated_files.fetch_add(summary.files.len().max(1), Ordering::Relaxed);
+            }
+        });
+    }
+
+    /// Take the updates committed so far as seen, e.g. by the next command
+    pub fn acknowledge_updates(&self) {
+        self.updated_files.store(0, Ordering::Relaxed);
+    }
+
+    pub fn freshness(&self) -> Freshness {
+        let backlog = self
+            .block_on(self.engine.watch_flow())
+            .map_or(0, |flow| flow.backlog);
+        if backlog > 0 {
+            Freshness::Reindexing
+        } else if self.updated_files.load(Ordering::Relaxed) > 0 {
+            Freshness::Updated
+        } else {
+            Freshness::Fresh
+        }
+    }
+
+    /// Wait until the file changes seen by the watcher are committed, for at
+    /// most `timeout`. Returns the epoch of the graph then current.
+    pub fn sync(&self, timeout: Duration) -> Result<u64, Box<dyn std::error::Error>> {
+        let engine = &self.engine;
+        let synced = async {
+            loop {
+                // Read before the backlog, so a commit in between is not waited for
+                let epoch = engine.graph_epoch().await?;
+                if engine.watch_flow().await?.backlog == 0 {
+                    return Ok::<_, naviscope_api::ApiError>(epoch);
+                }
+                let _ = tokio::time::timeout(SYNC_POLL, engine.wait_for_commit(epoch)).await;
+            }
+        };
+        match self.block_on(async { tokio::time::timeout(timeout, synced).await }) {
+            Ok(epoch) => Ok(epoch?),
+            Err(_) => Err(format!(
+                "Index still updating after {}s; try again later",
+                timeout.as_secs()
+            )
+            .into()),
         }
     }
 
@@ -49,6 +134,79 @@ impl ShellContext {
         Ok(result?)
     }
 
+    /// Helper to run a safe delete analysis synchronously
+    pub fn safe_delete(&self, fqn: &str) -> Result<SafeDeleteReport, Box<dyn std::error::Error>> {
+        let service: &dyn GraphService = self.engine.as_ref();
+        let result = if tokio::runtime::Handle::try_current().is_ok() {
+            tokio::task::block_in_place(|| self.rt_handle.block_on(service.safe_delete(fqn)))
+        } else {
+            self.rt_handle.block_on(service.safe_delete(fqn))
+        };
+        Ok(result?)
+    }
+
+    /// Helper to resolve the symbol at a 0-based position synchronously
+    pub fn resolve_at(
+        &self,
+        path: &std::path::Path,
+        line: u32,
+        col: u32,
+    ) -> Result<Option<ResolvedSymbol>, Box<dyn std::error::Error>> {
+        let navigator: &dyn SymbolNavigator = self.engine.as_ref();
+        let result = if tokio::runtime::Handle::try_current().is_ok() {
+            tokio::task::block_in_place(|| {
+                self.rt_handle
+                    .block_on(navigator.resolve_at(path, line, col))
+            })
+        } else {
+            self.rt_handle
+                .block_on(navigator.resolve_at(path, line, col))
+        };
+        Ok(result?)
+    }
+
+    /// Identifier of the graph snapshot the next command will see
+    pub fn graph_epoch(&self) -> Result<u64, Box<dyn std::error::Error>> {
+        let service: &dyn GraphService = self.engine.as_ref();
+        let result = if tokio::runtime::Handle::try_current().is_ok() {
+            tokio::task::block_in_place(|| self.rt_handle.block_on(service.graph_epoch()))
+        } else {
+            self.rt_handle.block_on(service.graph_epoch())
+        };
+        Ok(result?)
+    }
+
+    /// Check the current node against the latest snapshot.
+    ///
+    /// If a watch update removed it, moves to the nearest surviving ancestor
+    /// and returns a notice for the user.
+    pub fn revalidate_current(&self) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let Some(current) = self.current_fqn() else {
+            return Ok(None);
+        };
+        let nav_service: &dyn NavigationService = self.engine.as_ref();
+        let nearest = if tokio::runtime::Handle::try_current().is_ok() {
+            tokio::task::block_in_place(|| {
+                self.rt_handle
+                    .block_on(nav_service.nearest_existing(&current))
+            })
+        } else {
+            self.rt_handle
+                .block_on(nav_service.nearest_existing(&current))
+        }?;
+
+        if nearest.as_deref() == Some(current.as_str()) {
+            return Ok(None);
+        }
+        let notice = format!(
+            "Note: {} no longer exists; moved to {}",
+            current,
+            nearest.as_deref().unwrap_or("/")
+        );
+        self.set_current_fqn(nearest);
+        Ok(Some(notice))
+    }
+
     /// Resolves a user input path using the NavigationService API.
     pub fn resolve_node(&self, target: &str) -> Result<ResolveResult, Box<dyn std::error::Error>> {
         let nav_service: &dyn NavigationService = self.engine.as_ref();
@@ -66,3 +224,32 @@ impl ShellContext {
         Ok(result?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn committed_updates_mark_the_index_updated_until_acknowledged() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("A.java"), "class A {}").unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let _guard = rt.enter();
+        let engine = naviscope_runtime::build_default_engine(dir.path().to_path_buf());
+        let context = ShellContext::new(engine.clone(), rt.handle().clone(), Default::default());
+        assert_eq!(context.freshness(), Freshness::Fresh);
+
+        rt.block_on(context.follow_changes());
+        rt.block_on(engine.rebuild()).unwrap();
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while context.freshness() != Freshness::Updated {
+            assert!(std::time::Instant::now() < deadline, "update not seen");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        context.acknowledge_updates();
+        assert_eq!(context.freshness(), Freshness::Fresh);
+        let epoch = context.sync(Duration::from_secs(5)).unwrap();
+        assert_eq!(epoch, rt.block_on(engine.graph_epoch()).unwrap());
+    }
+}
@@ -1,12 +1,16 @@
-use super::command::ShellCommand;
+use super::command::{ShellCommand, parse_location};
 use super::context::{ResolveResult, ShellContext};
-use naviscope_api::models::GraphQuery;
+use super::output::OutputFormat;
+use super::view::{ShellTreeNode, ShellUsageView, get_kind_weight, is_container, render_tree};
+use naviscope_api::models::{GraphQuery, NodeKind, NodeSource, SafeDeleteVerdict};
+use tabled::{Table, settings::Style};
 
 pub trait CommandHandler {
     fn handle(
         &self,
         cmd: &ShellCommand,
         context: &mut ShellContext,
+        format: OutputFormat,
     ) -> Result<String, Box<dyn std::error::Error>>;
 }
 
@@ -16,6 +20,7 @@ impl CommandHandler for CdHandler {
         &self,
         cmd: &ShellCommand,
         context: &mut ShellContext,
+        _format: OutputFormat,
     ) -> Result<String, Box<dyn std::error::Error>> {
         if let ShellCommand::Cd { path } = cmd {
             match context.resolve_node(path)? {
@@ -45,6 +50,7 @@ impl CommandHandler for CatHandler {
         &self,
         cmd: &ShellCommand,
         context: &mut ShellContext,
+        format: OutputFormat,
     ) -> Result<String, Box<dyn std::error::Error>> {
         if let ShellCommand::Cat { target } = cmd {
             // First resolve the target to a concrete FQN
@@ -57,13 +63,10 @@ impl CommandHandler for CatHandler {
                         msg.push_str(&format!("  - {}\n", c));
                     }
                     msg.push_str("\nPlease specify the full name.");
-                    return Ok(msg);
+                    return Err(msg.into());
                 }
                 ResolveResult::NotFound => {
-                    return Ok(format!(
-                        "Error: Target '{}' not found in current context.",
-                        target
-                    ));
+                    return Err(format!("Target '{}' not found in current context.", target).into());
                 }
             };
 
@@ -71,10 +74,10 @@ impl CommandHandler for CatHandler {
                 return Err("Cannot cat root.".into());
             }
 
-            let query = GraphQuery::Cat { fqn };
+            let query = GraphQuery::Cat { fqn, limits: None };
             let result = context.execute_query(&query)?;
 
-            cmd.render(result, context)
+            cmd.render(result, context, format)
         } else {
             Ok(String::new())
         }
@@ -87,6 +90,7 @@ impl CommandHandler for GenericQueryHandler {
         &self,
         cmd: &ShellCommand,
         context: &mut ShellContext,
+        format: OutputFormat,
     ) -> Result<String, Box<dyn std::error::Error>> {
         let current_node = context.current_fqn();
 
@@ -97,9 +101,12 @@ impl CommandHandler for GenericQueryHandler {
                 fqn: Some(target),
                 kind,
                 source,
+                status,
                 modifiers,
                 long,
                 all,
+                group_by,
+                count,
             } => {
                 resolved_target_fqn = match context.resolve_node(target) {
                     Ok(ResolveResult::Found(f)) => Some(f),
@@ -109,15 +116,22 @@ impl CommandHandler for GenericQueryHandler {
                     fqn: resolved_target_fqn.clone(),
                     kind: kind.clone(),
                     source: source.clone(),
+                    status: status.clone(),
                     modifiers: modifiers.clone(),
                     long: *long,
                     all: *all,
+                    group_by: group_by.clone(),
+                    count: *count,
                 }
             }
             ShellCommand::Deps {
                 fqn: Some(target),
                 rev,
                 edge_types,
+                source,
+                status,
+                group_by,
+                count,
             } => {
                 resolved_target_fqn = match context.resolve_node(target) {
                     Ok(ResolveResult::Found(f)) => Some(f),
@@ -127,6 +141,55 @@ impl CommandHandler for GenericQueryHandler {
                     fqn: resolved_target_fqn.clone(),
                     rev: *rev,
                     edge_types: edge_types.clone(),
+                    source: source.clone(),
+                    status: status.clone(),
+                    group_by: group_by.clone(),
+                    count: *count,
+                }
+            }
+            ShellCommand::Cycles {
+                scope: Some(target),
+                edge_types,
+                packages,
+            } => ShellCommand::Cycles {
+                scope: match context.resolve_node(target) {
+                    Ok(ResolveResult::Found(f)) => Some(f),
+                    _ => Some(target.clone()),
+                },
+                edge_types: edge_types.clone(),
+                packages: *packages,
+            },
+            ShellCommand::Dead {
+                scope: Some(target),
+                entry_points,
+            } => ShellCommand::Dead {
+                scope: match context.resolve_node(target) {
+                    Ok(ResolveResult::Found(f)) => Some(f),
+                    _ => Some(target.clone()),
+                },
+                entry_points: entry_points.clone(),
+            },
+            ShellCommand::TestsFor { fqn: Some(target) } => ShellCommand::TestsFor {
+                fqn: match context.resolve_node(target) {
+                    Ok(ResolveResult::Found(f)) => Some(f),
+                    _ => Some(target.clone()),
+                },
+            },
+            ShellCommand::Path {
+                from,
+                to,
+                edges,
+                max_depth,
+            } => {
+                let resolve = |target: &String| match context.resolve_node(target) {
+                    Ok(ResolveResult::Found(f)) => f,
+                    _ => target.clone(),
+                };
+                ShellCommand::Path {
+                    from: resolve(from),
+                    to: resolve(to),
+                    edges: edges.clone(),
+                    max_depth: *max_depth,
                 }
             }
             _ => cmd.clone(),
@@ -135,11 +198,36 @@ impl CommandHandler for GenericQueryHandler {
         let query = resolved_cmd.to_graph_query(&current_node)?;
         let result = context.execute_query(&query)?;
 
-        if result.nodes.is_empty() {
+        if format == OutputFormat::Table && result.nodes.is_empty() && !result.is_aggregated() {
+            if matches!(resolved_cmd, ShellCommand::Cycles { .. }) {
+                return Ok("No cycles found".to_string());
+            }
+            if matches!(resolved_cmd, ShellCommand::Duplicates { .. }) {
+                return Ok("No duplicated methods found".to_string());
+            }
+            if matches!(resolved_cmd, ShellCommand::Dead { .. }) {
+                return Ok("No unused symbols found".to_string());
+            }
+            if matches!(resolved_cmd, ShellCommand::TestsFor { .. }) {
+                return Ok("No tests found".to_string());
+            }
+            if let ShellCommand::Path {
+                from,
+                to,
+                max_depth,
+                ..
+            } = &resolved_cmd
+            {
+                return Ok(format!(
+                    "No path from '{}' to '{}' within {} edges",
+                    from, to, max_depth
+                ));
+            }
             if let Some(target) = resolved_target_fqn {
                 // Check if node itself exists in the graph
                 let check_query = naviscope_api::models::GraphQuery::Cat {
                     fqn: target.clone(),
+                    limits: None,
                 };
                 if let Ok(res) = context.execute_query(&check_query) {
                     if !res.nodes.is_empty() {
@@ -153,7 +241,206 @@ impl CommandHandler for GenericQueryHandler {
             return Ok("NO RECORDS FOUND".to_string());
         }
 
-        resolved_cmd.render(result, context)
+        resolved_cmd.render(result, context, format)
+    }
+}
+
+pub struct SafeDeleteHandler;
+impl CommandHandler for SafeDeleteHandler {
+    fn handle(
+        &self,
+        cmd: &ShellCommand,
+        context: &mut ShellContext,
+        format: OutputFormat,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let ShellCommand::SafeDelete { fqn } = cmd else {
+            return Ok(String::new());
+        };
+        let target = fqn
+            .clone()
+            .or_else(|| context.current_fqn())
+            .ok_or("No FQN provided and no current context")?;
+        let fqn = match context.resolve_node(&target)? {
+            ResolveResult::Found(f) if !f.is_empty() => f,
+            ResolveResult::Ambiguous(candidates) => {
+                let mut msg = format!("Ambiguous match for '{}'. Available options:\n\n", target);
+                for c in candidates {
+                    msg.push_str(&format!("  - {}\n", c));
+                }
+                return Ok(msg);
+            }
+            _ => return Err(format!("Node '{}' not found.", target).into()),
+        };
+
+        let report = context.safe_delete(&fqn)?;
+        if format == OutputFormat::Json {
+            return Ok(serde_json::to_string_pretty(&report)?);
+        }
+        let verdict = match report.verdict {
+            SafeDeleteVerdict::Safe => "SAFE: no usages outside the symbol and tests",
+            SafeDeleteVerdict::Review => "REVIEW: only heuristic hints found",
+            SafeDeleteVerdict::Unsafe => "UNSAFE: other code depends on this symbol",
+        };
+        if report.usages.is_empty() {
+            return Ok(verdict.to_string());
+        }
+
+        let views: Vec<ShellUsageView> = report
+            .usages
+            .iter()
+            .map(|u| ShellUsageView {
+                kind: format!("{:?}", u.kind),
+                from: u.from.clone(),
+                detail: u.detail.clone(),
+                location: u
+                    .location
+                    .as_ref()
+                    .map(|l| format!("{}:{}", l.path, l.range.start_line + 1))
+                    .unwrap_or_else(|| "-".to_string()),
+            })
+            .collect();
+        Ok(format!(
+            "{}\n{}",
+            verdict,
+            Table::new(&views).with(Style::psql())
+        ))
+    }
+}
+
+pub struct TreeHandler;
+impl CommandHandler for TreeHandler {
+    fn handle(
+        &self,
+        cmd: &ShellCommand,
+        context: &mut ShellContext,
+        format: OutputFormat,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let ShellCommand::Tree {
+            fqn,
+            depth,
+            kind,
+            all,
+        } = cmd
+        else {
+            return Ok(String::new());
+        };
+        let root = match fqn.clone().or_else(|| context.current_fqn()) {
+            Some(target) => match context.resolve_node(&target)? {
+                ResolveResult::Found(f) => f,
+                ResolveResult::Ambiguous(candidates) => {
+                    let mut msg =
+                        format!("Ambiguous match for '{}'. Available options:\n\n", target);
+                    for c in candidates {
+                        msg.push_str(&format!("  - {}\n", c));
+                    }
+                    return Ok(msg);
+                }
+                ResolveResult::NotFound => {
+                    return Err(format!("Node '{}' not found.", target).into());
+                }
+            },
+            None => String::new(),
+        };
+
+        let kinds: Vec<NodeKind> = kind.iter().map(|k| k.clone().into()).collect();
+        let sources = if *all {
+            vec![]
+        } else {
+            vec![NodeSource::Project]
+        };
+        let start = (!root.is_empty()).then(|| root.clone());
+        let children = tree_children(context, start, &kinds, &sources, *depth)?;
+        if format == OutputFormat::Json {
+            return Ok(serde_json::to_string_pretty(&children)?);
+        }
+        if children.is_empty() {
+            return Ok("NO RECORDS FOUND".to_string());
+        }
+        let root = if root.is_empty() { "/" } else { root.as_str() };
+        Ok(render_tree(root, &children))
+    }
+}
+
+/// Containment subtree below `fqn` (the root modules when `None`), `depth`
+/// levels deep. With `kinds`, only nodes of those kinds and the containers
+/// leading to them are kept.
+fn tree_children(
+    context: &ShellContext,
+    fqn: Option<String>,
+    kinds: &[NodeKind],
+    sources: &[NodeSource],
+    depth: usize,
+) -> Result<Vec<ShellTreeNode>, Box<dyn std::error::Error>> {
+    if depth == 0 {
+        return Ok(Vec::new());
+    }
+    let query = GraphQuery::Ls {
+        fqn,
+        kind: vec![],
+        sources: sources.to_vec(),
+        statuses: vec![],
+        modifiers: vec![],
+        group_by: None,
+        count: false,
+    };
+    let mut nodes = context.execute_query(&query)?.nodes;
+    nodes.sort_by(|a, b| {
+        get_kind_weight(&a.kind.to_string())
+            .cmp(&get_kind_weight(&b.kind.to_string()))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let mut children = Vec::new();
+    for node in nodes {
+        let grandchildren = if is_container(&node.kind) {
+            tree_children(context, Some(node.id.clone()), kinds, sources, depth - 1)?
+        } else {
+            Vec::new()
+        };
+        if kinds.is_empty() || kinds.contains(&node.kind) || !grandchildren.is_empty() {
+            children.push(ShellTreeNode {
+                node,
+                children: grandchildren,
+            });
+        }
+    }
+    Ok(children)
+}
+
+pub struct WhatHandler;
+impl CommandHandler for WhatHandler {
+    fn handle(
+        &self,
+        cmd: &ShellCommand,
+        context: &mut ShellContext,
+        format: OutputFormat,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let ShellCommand::What { location } = cmd else {
+            return Ok(String::new());
+        };
+        let (path, line, col) = parse_location(location)?;
+        let path = std::fs::canonicalize(&path).map_err(|e| format!("{}: {}", path, e))?;
+
+        let resolved = context.resolve_at(&path, line - 1, col - 1)?;
+        if format == OutputFormat::Json {
+            return Ok(serde_json::to_string_pretty(&resolved)?);
+        }
+        let Some(resolved) = resolved else {
+            return Ok("No symbol at this position.".to_string());
+        };
+        if let Some(range) = resolved.local_declaration {
+            let mut msg = format!("Local symbol declared at line {}", range.start_line + 1);
+            if let Some(type_name) = resolved.type_name {
+                msg.push_str(&format!(" with type {}", type_name));
+            }
+            return Ok(msg);
+        }
+
+        let fqn = resolved.fqn.unwrap_or_default();
+        match resolved.node {
+            Some(node) => Ok(format!("{}\n{}", fqn, serde_json::to_string_pretty(&node)?)),
+            None => Ok(format!("{} (not in the index)", fqn)),
+        }
     }
 }
 
@@ -163,8 +450,32 @@ impl CommandHandler for PwdHandler {
         &self,
         _cmd: &ShellCommand,
         context: &mut ShellContext,
+        format: OutputFormat,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        match format {
+            OutputFormat::Json => Ok(serde_json::to_string(&context.current_fqn())?),
+            OutputFormat::Table => Ok(context.current_fqn().unwrap_or("/".to_string())),
+        }
+    }
+}
+
+pub struct SyncHandler;
+impl CommandHandler for SyncHandler {
+    fn handle(
+        &self,
+        cmd: &ShellCommand,
+        context: &mut ShellContext,
+        format: OutputFormat,
     ) -> Result<String, Box<dyn std::error::Error>> {
-        Ok(context.current_fqn().unwrap_or("/".to_string()))
+        let ShellCommand::Sync { timeout } = cmd else {
+            return Ok(String::new());
+        };
+        let epoch = context.sync(std::time::Duration::from_secs(*timeout))?;
+        context.acknowledge_updates();
+        match format {
+            OutputFormat::Json => Ok(serde_json::json!({ "epoch": epoch }).to_string()),
+            OutputFormat::Table => Ok(format!("Index is up to date (epoch {})", epoch)),
+        }
     }
 }
 
@@ -174,6 +485,7 @@ impl CommandHandler for ClearHandler {
         &self,
         _cmd: &ShellCommand,
         _context: &mut ShellContext,
+        _format: OutputFormat,
     ) -> Result<String, Box<dyn std::error::Error>> {
         // Clear is handled by the Reedline loop mostly, but we can return a marker if needed.
         // For now, simple print or empty string. The loop handles `line_editor.clear_screen()`.
@@ -185,6 +497,10 @@ pub fn get_handler(cmd: &ShellCommand) -> Box<dyn CommandHandler> {
     match cmd {
         ShellCommand::Cd { .. } => Box::new(CdHandler),
         ShellCommand::Cat { .. } => Box::new(CatHandler),
+        ShellCommand::SafeDelete { .. } => Box::new(SafeDeleteHandler),
+        ShellCommand::Tree { .. } => Box::new(TreeHandler),
+        ShellCommand::What { .. } => Box::new(WhatHandler),
+        ShellCommand::Sync { .. } => Box::new(SyncHandler),
         ShellCommand::Pwd => Box::new(PwdHandler),
         ShellCommand::Clear => Box::new(ClearHandler),
         _ => Box::new(GenericQueryHandler),
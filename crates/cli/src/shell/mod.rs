@@ -3,6 +3,7 @@ mod completer;
 mod context;
 mod handlers;
 mod highlighter;
+mod output;
 mod prompt;
 mod view;
 
@@ -11,26 +12,131 @@ use reedline::{
     Reedline, ReedlineEvent, ReedlineMenu, Signal, default_emacs_keybindings,
 };
 use std::path::PathBuf;
+use std::process::ExitCode;
 use std::sync::{Arc, RwLock};
 use tracing::{error, info};
 
-use self::command::{ShellCommand, parse_shell_command};
+use self::command::{ShellCommand, parse_shell_command, split_commands};
 use self::completer::NaviscopeCompleter;
-use self::context::ShellContext;
 use self::highlighter::NaviscopeHighlighter;
 use self::prompt::DefaultPrompt;
 
+pub(crate) use self::command::query_vocabulary;
+pub(crate) use self::context::ShellContext;
+pub(crate) use self::output::system_shell;
+pub(crate) use self::view::{describe_history, get_kind_weight, is_container};
+
 // Shell configuration constants
 const SHELL_HISTORY_SIZE: usize = 500;
 
+/// Batch exit status when a command fails, or a program its output is
+/// piped to exits non-zero
+const EXIT_FAILED: u8 = 1;
+/// Batch exit status when a command line cannot be parsed
+const EXIT_USAGE: u8 = 2;
+
+/// Why a command line did not run to completion
+enum LineError {
+    Parse(String),
+    Failed(String),
+}
+
+impl LineError {
+    fn exit_code(&self) -> ExitCode {
+        match self {
+            LineError::Parse(_) => ExitCode::from(EXIT_USAGE),
+            LineError::Failed(_) => ExitCode::from(EXIT_FAILED),
+        }
+    }
+}
+
+impl std::fmt::Display for LineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LineError::Parse(e) | LineError::Failed(e) => f.write_str(e),
+        }
+    }
+}
+
+/// Parse and run one command line and write its output. Returns the command
+/// that ran; `None` for help.
+fn execute(context: &mut ShellContext, input: &str) -> Result<Option<ShellCommand>, LineError> {
+    let line = match parse_shell_command(input) {
+        Ok(Some(line)) => line,
+        Ok(None) => return Ok(None), // Help or handled by Clap
+        Err(e) => return Err(LineError::Parse(e.to_string())),
+    };
+    // A watch update may have removed the node we are in
+    match context.revalidate_current() {
+        Ok(Some(notice)) => println!("{}", notice),
+        Ok(None) => {}
+        Err(e) => eprintln!("Error: {}", e),
+    }
+    context.acknowledge_updates();
+    let cmd = line.command;
+    let handler = self::handlers::get_handler(&cmd);
+    let output = handler
+        .handle(&cmd, context, line.format)
+        .map_err(|e| LineError::Failed(e.to_string()))?;
+    line.target
+        .write(&output)
+        .map_err(|e| LineError::Failed(e.to_string()))?;
+    Ok(Some(cmd))
+}
+
+/// Subcommands understood by the interactive shell
+pub(crate) fn shell_commands() -> Vec<clap::Command> {
+    use clap::CommandFactory;
+    ShellCommand::command().get_subcommands().cloned().collect()
+}
+
+/// Where the shell's index comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IndexSource {
+    /// Loaded, refreshed and watched by the shell
+    Local,
+    /// A prebuilt index that is neither refreshed nor watched
+    ReadOnly,
+    /// An index server, which keeps its index up to date itself
+    Remote,
+}
+
 pub struct ReplServer {
     context: ShellContext,
     project_path: PathBuf,
+    source: IndexSource,
 }
 
 impl ReplServer {
     pub fn new(project_path: PathBuf) -> Self {
         let engine = naviscope_runtime::build_default_engine(project_path.clone());
+        Self::with_engine(engine, project_path, IndexSource::Local)
+    }
+
+    /// Serve the prebuilt index at `index_path` read-only
+    pub async fn open_readonly(
+        project_path: PathBuf,
+        index_path: PathBuf,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let engine = naviscope_runtime::open_readonly(project_path.clone(), index_path).await?;
+        Ok(Self::with_engine(
+            engine,
+            project_path,
+            IndexSource::ReadOnly,
+        ))
+    }
+
+    /// Query the index server at `addr`
+    pub fn connect_remote(project_path: PathBuf, addr: &str) -> Self {
+        let engine = naviscope_runtime::connect_remote(addr);
+        Self::with_engine(engine, project_path, IndexSource::Remote)
+    }
+
+    fn with_engine(
+        engine: Arc<dyn naviscope_api::NaviscopeEngine>,
+        project_path: PathBuf,
+        source: IndexSource,
+    ) -> Self {
         let current_node = Arc::new(RwLock::new(None));
 
         // ShellContext will get resolver from engine
@@ -39,23 +145,35 @@ impl ReplServer {
         Self {
             context,
             project_path,
+            source,
         }
     }
 
+    pub(crate) fn context(&self) -> &ShellContext {
+        &self.context
+    }
+
     pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
         println!("Project: {:?}", self.project_path);
 
-        self.initialize_index().await?;
+        self.initialize_index(true).await?;
+
+        // Subscribe before watching so the prompt sees every update
+        self.context.follow_changes().await;
 
         // Start watcher (spawns background task on the runtime)
-        let watch_handle = match self.context.engine.start_watch().await {
-            Ok(handle) => {
-                info!("File watcher started.");
-                Some(handle)
-            }
-            Err(e) => {
-                error!("Failed to start file watcher: {}", e);
-                None
+        let watch_handle = if self.source != IndexSource::Local {
+            None
+        } else {
+            match self.context.engine.start_watch().await {
+                Ok(handle) => {
+                    info!("File watcher started.");
+                    Some(handle)
+                }
+                Err(e) => {
+                    error!("Failed to start file watcher: {}", e);
+                    None
+                }
             }
         };
 
@@ -69,23 +187,76 @@ impl ReplServer {
         run_result
     }
 
-    async fn initialize_index(&self) -> Result<(), Box<dyn std::error::Error>> {
+    /// Run the commands of `script` without a prompt, stopping at the first
+    /// that fails. Progress goes to stderr so stdout holds only results.
+    pub async fn run_batch(&self, script: &str) -> Result<ExitCode, Box<dyn std::error::Error>> {
+        self.initialize_index(false).await?;
+
+        let mut context = self.context.clone();
+        for input in split_commands(script) {
+            if input == "exit" || input == "quit" {
+                break;
+            }
+            if let Err(e) = execute(&mut context, &input) {
+                eprintln!("Error in '{}': {}", input, e);
+                return Ok(e.exit_code());
+            }
+        }
+        Ok(ExitCode::SUCCESS)
+    }
+
+    /// Load and refresh the index. Status lines go to stdout when
+    /// `interactive`, to stderr otherwise.
+    pub(crate) async fn initialize_index(
+        &self,
+        interactive: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         let engine = &self.context.engine;
         let start = std::time::Instant::now();
+        let status = |message: String| {
+            if interactive {
+                println!("{}", message);
+            } else {
+                eprintln!("{}", message);
+            }
+        };
+
+        // Opened and loaded already, and never synchronized
+        if self.source == IndexSource::ReadOnly {
+            let stats = engine.get_stats().await.unwrap_or_default();
+            status(format!(
+                "Index opened read-only. Nodes: {}, Edges: {}",
+                stats.node_count, stats.edge_count
+            ));
+            self.select_project().await;
+            return Ok(());
+        }
+
+        // Kept up to date by the server
+        if self.source == IndexSource::Remote {
+            engine.load().await?;
+            let stats = engine.get_stats().await?;
+            status(format!(
+                "Connected to the index server. Nodes: {}, Edges: {}",
+                stats.node_count, stats.edge_count
+            ));
+            self.select_project().await;
+            return Ok(());
+        }
 
         // Load index (blocking on async)
         match engine.load().await {
             Ok(true) => {
                 let stats = engine.get_stats().await.unwrap_or_default();
-                println!(
+                status(format!(
                     "Index loaded from disk in {:?}. Nodes: {}, Edges: {}",
                     start.elapsed(),
                     stats.node_count,
                     stats.edge_count
-                );
+                ));
             }
             Ok(false) => {
-                println!("No existing index found or it was stale. Rebuilding...");
+                status("No existing index found or it was stale. Rebuilding...".to_string());
                 // If load returns false, we should verify/rebuild.
                 // But refresh() below will handle it anyway.
             }
@@ -99,32 +270,37 @@ impl ReplServer {
         let sync_start = std::time::Instant::now();
         if let Err(e) = engine.refresh().await {
             error!("Synchronization failed: {}", e);
-            println!("Warning: Index synchronization failed: {}", e);
+            status(format!("Warning: Index synchronization failed: {}", e));
         } else {
             let stats = engine.get_stats().await.unwrap_or_default();
-            println!(
+            status(format!(
                 "Index synchronized in {:?}. Total nodes: {}",
                 sync_start.elapsed(),
                 stats.node_count
-            );
-
-            // Auto-set context to Project node if it exists
+            ));
+            self.select_project().await;
+        }
+        Ok(())
+    }
 
-            let query = naviscope_api::models::GraphQuery::Ls {
-                fqn: None,
-                kind: vec![naviscope_api::models::NodeKind::Project],
-                sources: vec![],
-                modifiers: vec![],
-            };
+    /// Auto-set context to Project node if it exists
+    async fn select_project(&self) {
+        let query = naviscope_api::models::GraphQuery::Ls {
+            fqn: None,
+            kind: vec![naviscope_api::models::NodeKind::Project],
+            sources: vec![],
+            statuses: vec![],
+            modifiers: vec![],
+            group_by: None,
+            count: false,
+        };
 
-            if let Ok(res) = engine.query(&query).await {
-                if res.nodes.len() == 1 {
-                    let fqn = res.nodes[0].id.to_string();
-                    self.context.set_current_fqn(Some(fqn));
-                }
-            }
+        if let Ok(res) = self.context.engine.query(&query).await
+            && res.nodes.len() == 1
+        {
+            let fqn = res.nodes[0].id.to_string();
+            self.context.set_current_fqn(Some(fqn));
         }
-        Ok(())
     }
 
     // Manual start_watcher removed - handled by EngineHandle::watch()
@@ -187,7 +363,11 @@ impl ReplServer {
 
         loop {
             let curr = context.current_fqn();
-            let prompt = DefaultPrompt::new(curr.clone());
+            let prompt = DefaultPrompt::new(
+                curr.clone(),
+                context.graph_epoch().ok(),
+                context.freshness(),
+            );
             let sig = line_editor.read_line(&prompt);
 
             match sig {
@@ -200,23 +380,11 @@ impl ReplServer {
                         break;
                     }
 
-                    match parse_shell_command(trimmed) {
-                        Ok(Some(cmd)) => {
-                            let handler = self::handlers::get_handler(&cmd);
-
-                            match handler.handle(&cmd, &mut context) {
-                                Ok(output) => {
-                                    if !output.is_empty() {
-                                        println!("{}", output);
-                                    }
-                                    if matches!(cmd, ShellCommand::Clear) {
-                                        let _ = line_editor.clear_screen();
-                                    }
-                                }
-                                Err(e) => eprintln!("Error: {}", e),
-                            }
+                    match execute(&mut context, trimmed) {
+                        Ok(Some(ShellCommand::Clear)) => {
+                            let _ = line_editor.clear_screen();
                         }
-                        Ok(None) => {} // Help or handled by Clap
+                        Ok(_) => {}
                         Err(e) => eprintln!("Error: {}", e),
                     }
                 }
@@ -231,11 +399,50 @@ impl ReplServer {
     }
 }
 
-pub async fn run(path: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+/// Start the shell; with a `script` file or `commands`, run them in batch
+/// mode and return the batch exit status.
+pub async fn run(
+    path: Option<PathBuf>,
+    index: Option<PathBuf>,
+    remote: Option<String>,
+    script: Option<PathBuf>,
+    commands: Option<String>,
+) -> Result<ExitCode, Box<dyn std::error::Error>> {
     let project_path = match path {
         Some(p) => p,
         None => std::env::current_dir()?.canonicalize()?,
     };
-    let server = ReplServer::new(project_path);
-    server.run().await
+    let batch = match (script, commands) {
+        (Some(script), _) => Some(
+            std::fs::read_to_string(&script)
+                .map_err(|e| format!("Cannot read {}: {}", script.display(), e))?,
+        ),
+        (None, commands) => commands,
+    };
+    let server = match (index, remote) {
+        (Some(index), _) => ReplServer::open_readonly(project_path, index).await?,
+        (None, Some(addr)) => ReplServer::connect_remote(project_path, &addr),
+        (None, None) => ReplServer::new(project_path),
+    };
+    match batch {
+        Some(script) => server.run_batch(&script).await,
+        None => server.run().await.map(|()| ExitCode::SUCCESS),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn batch_stops_at_cat_of_a_missing_node() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("A.java"), "class A {}").unwrap();
+        let server = ReplServer::new(dir.path().to_path_buf());
+
+        let status = server.run_batch("cat A; pwd").await.unwrap();
+        assert_eq!(status, ExitCode::SUCCESS);
+        let status = server.run_batch("cat com.nope; pwd").await.unwrap();
+        assert_eq!(status, ExitCode::from(EXIT_FAILED));
+    }
 }
@@ -0,0 +1,202 @@
+//! Where shell command output goes
+//!
+//! A command line may end in `> file`, `>> file` or `| command` to send its
+//! output to a file or another program instead of the terminal, and `--json`
+//! switches that one command to JSON, so results can feed `jq` and friends.
+//!
+//! Output that does not fit on the terminal goes through a pager: `$PAGER`,
+//! or `less -FRX` when it is unset. An empty `PAGER` prints inline. Colors
+//! are kept only for the terminal and the pager.
+
+use std::borrow::Cow;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// How a command renders its result
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+}
+
+/// Pager used when `$PAGER` is unset; `-F` quits at once on short input
+const DEFAULT_PAGER: &str = "less -FRX";
+
+/// Destination of a command's output
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum OutputTarget {
+    #[default]
+    Stdout,
+    /// `> path` truncates the file, `>> path` appends to it
+    File { path: PathBuf, append: bool },
+    /// `| command`, run by the system shell with the output on its stdin
+    Pipe(String),
+}
+
+impl OutputTarget {
+    pub fn write(&self, output: &str) -> std::io::Result<()> {
+        match self {
+            OutputTarget::Stdout => {
+                if output.is_empty() {
+                    return Ok(());
+                }
+                if let Some(pager) = pager_for(output) {
+                    match pipe_to(&pager, output) {
+                        Ok(()) => return Ok(()),
+                        // Fall back to printing when the pager cannot start
+                        Err(e) => eprintln!("Cannot run pager '{}': {}", pager, e),
+                    }
+                }
+                if std::io::stdout().is_terminal() {
+                    println!("{}", output);
+                } else {
+                    println!("{}", plain(output));
+                }
+                Ok(())
+            }
+            OutputTarget::File { path, append } => {
+                let mut file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .write(true)
+                    .append(*append)
+                    .truncate(!*append)
+                    .open(path)?;
+                writeln!(file, "{}", plain(output))
+            }
+            OutputTarget::Pipe(command) => pipe_to(command, &plain(output)),
+        }
+    }
+}
+
+/// `output` without ANSI escape sequences
+pub(super) fn plain(output: &str) -> Cow<'_, str> {
+    if !output.contains('\x1b') {
+        return Cow::Borrowed(output);
+    }
+    let mut plain = String::with_capacity(output.len());
+    let mut chars = output.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.clone().next() == Some('[') {
+            // Skip to the final byte of the sequence
+            chars.next();
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    Cow::Owned(plain)
+}
+
+/// Run `command` with `output` on its stdin and wait for it.
+fn pipe_to(command: &str, output: &str) -> std::io::Result<()> {
+    let mut child = system_shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The command may exit without reading everything (`head`, `q` in less)
+        match writeln!(stdin, "{}", output) {
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e),
+            _ => {}
+        }
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!(
+            "'{}' exited with {}",
+            command, status
+        )));
+    }
+    Ok(())
+}
+
+/// The pager to show `output` with, if it is taller than the terminal
+fn pager_for(output: &str) -> Option<String> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    let (_, rows) = crossterm::terminal::size().ok()?;
+    // Leave room for the prompt
+    if output.lines().count() < usize::from(rows).saturating_sub(1) {
+        return None;
+    }
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    let pager = pager.trim();
+    (!pager.is_empty()).then(|| pager.to_string())
+}
+
+/// Run `command` through `sh -c` (`cmd /C` on Windows)
+pub(crate) fn system_shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_are_truncated_or_appended_to() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.txt");
+        let file = |append| OutputTarget::File {
+            path: path.clone(),
+            append,
+        };
+
+        file(false).write("a").unwrap();
+        file(true).write("b").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a\nb\n");
+        file(false).write("c").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "c\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pipes_feed_the_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("piped.txt");
+        let command = format!("sort > '{}'", path.display());
+
+        OutputTarget::Pipe(command).write("b\na").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a\nb\n");
+    }
+
+    #[test]
+    fn colors_are_stripped_outside_the_terminal() {
+        assert_eq!(
+            plain("\x1b[1;33mA\x1b[0m/ \x1b[90mclass\x1b[0m"),
+            "A/ class"
+        );
+        assert!(matches!(plain("no colors"), Cow::Borrowed("no colors")));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.txt");
+        let file = OutputTarget::File {
+            path: path.clone(),
+            append: false,
+        };
+        file.write("\x1b[32mgreen\x1b[0m").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "green\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failing_pipe_commands_are_reported() {
+        assert!(OutputTarget::Pipe("exit 3".to_string()).write("a").is_err());
+    }
+}
@@ -1,14 +1,21 @@
+use super::context::Freshness;
 use nu_ansi_term::Color;
 use reedline::{Prompt, PromptEditMode, PromptHistorySearch};
 use std::borrow::Cow;
 
 pub struct DefaultPrompt {
     current_node: Option<String>,
+    epoch: Option<u64>,
+    freshness: Freshness,
 }
 
 impl DefaultPrompt {
-    pub fn new(current_node: Option<String>) -> Self {
-        Self { current_node }
+    pub fn new(current_node: Option<String>, epoch: Option<u64>, freshness: Freshness) -> Self {
+        Self {
+            current_node,
+            epoch,
+            freshness,
+        }
     }
 }
 
@@ -33,7 +40,19 @@ impl Prompt for DefaultPrompt {
     }
 
     fn render_prompt_right(&self) -> Cow<'_, str> {
-        Cow::Borrowed("")
+        let freshness = match self.freshness {
+            Freshness::Reindexing => Color::Yellow.paint("[reindexing…]"),
+            Freshness::Updated => Color::Cyan.paint("[updated]"),
+            Freshness::Fresh => Color::Green.paint("[fresh]"),
+        };
+        match self.epoch {
+            Some(epoch) => Cow::Owned(format!(
+                "{} {}",
+                freshness,
+                Color::DarkGray.paint(format!("epoch {}", epoch))
+            )),
+            None => Cow::Owned(freshness.to_string()),
+        }
     }
 
     fn render_prompt_indicator(&self, _edit_mode: PromptEditMode) -> Cow<'_, str> {
@@ -76,3 +95,23 @@ fn shorten_fqn(fqn: &str) -> String {
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn right(epoch: Option<u64>, freshness: Freshness) -> String {
+        let prompt = DefaultPrompt::new(None, epoch, freshness);
+        super::super::output::plain(&prompt.render_prompt_right()).into_owned()
+    }
+
+    #[test]
+    fn right_prompt_shows_freshness_before_the_epoch() {
+        assert_eq!(right(Some(3), Freshness::Updated), "[updated] epoch 3");
+        assert_eq!(
+            right(Some(4), Freshness::Reindexing),
+            "[reindexing…] epoch 4"
+        );
+        assert_eq!(right(None, Freshness::Fresh), "[fresh]");
+    }
+}
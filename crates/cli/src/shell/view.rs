@@ -1,4 +1,6 @@
-use naviscope_api::models::{DisplayGraphNode, NodeKind};
+use naviscope_api::models::{DisplayGraphNode, NodeHistory, NodeKind, QueryResultEdge};
+use nu_ansi_term::{Color, Style};
+use serde::Serialize;
 use tabled::Tabled;
 
 /// A terminal-optimized view of a GraphNode (Detailed)
@@ -12,6 +14,31 @@ pub struct ShellNodeView {
     pub fqn: String,
 }
 
+/// A row of an aggregated query result
+#[derive(Tabled)]
+pub struct ShellGroupView {
+    pub group: String,
+    pub count: usize,
+}
+
+/// A usage blocking a safe delete
+#[derive(Tabled)]
+pub struct ShellUsageView {
+    pub kind: String,
+    pub from: String,
+    pub detail: String,
+    pub location: String,
+}
+
+/// A node with the part of its containment subtree shown by `tree`
+#[derive(Serialize)]
+pub struct ShellTreeNode {
+    #[serde(flatten)]
+    pub node: DisplayGraphNode,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ShellTreeNode>,
+}
+
 /// A short view of a GraphNode
 #[derive(Tabled)]
 pub struct ShellNodeViewShort {
@@ -92,6 +119,24 @@ pub fn shorten_fqn(fqn: &str) -> String {
     result
 }
 
+/// `changed 12 days ago by ann in 3f2a1b9 (5 commits)`
+pub fn describe_history(history: &NodeHistory) -> String {
+    let age = match history.age_days {
+        0 => "today".to_string(),
+        1 => "1 day ago".to_string(),
+        days => format!("{} days ago", days),
+    };
+    let commit: String = history.commit.chars().take(7).collect();
+    format!(
+        "changed {} by {} in {} ({} commit{})",
+        age,
+        history.author,
+        commit,
+        history.commits,
+        if history.commits == 1 { "" } else { "s" }
+    )
+}
+
 pub fn get_kind_weight(kind: &str) -> i32 {
     match kind.to_lowercase().as_str() {
         "package" => 1,
@@ -105,3 +150,131 @@ pub fn get_kind_weight(kind: &str) -> i32 {
         _ => 99,
     }
 }
+
+pub fn is_container(kind: &NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Class
+            | NodeKind::Interface
+            | NodeKind::Enum
+            | NodeKind::Annotation
+            | NodeKind::Module
+            | NodeKind::Package
+    )
+}
+
+/// Style of a node name in tree views
+pub fn kind_style(kind: &NodeKind) -> Style {
+    match kind {
+        NodeKind::Project | NodeKind::Module => Color::LightBlue.bold(),
+        NodeKind::Package => Color::Blue.normal(),
+        NodeKind::Class => Color::Yellow.normal(),
+        NodeKind::Interface => Color::LightCyan.normal(),
+        NodeKind::Enum => Color::LightPurple.normal(),
+        NodeKind::Annotation => Color::Purple.normal(),
+        NodeKind::Method | NodeKind::Constructor => Color::LightGreen.normal(),
+        NodeKind::Field => Color::Cyan.normal(),
+        _ => Style::new(),
+    }
+}
+
+/// Render `children` below `root` as an indented tree, one node per line
+pub fn render_tree(root: &str, children: &[ShellTreeNode]) -> String {
+    let mut out = Color::Yellow.bold().paint(root).to_string();
+    render_branches(children, "", &mut out);
+    out
+}
+
+fn render_branches(children: &[ShellTreeNode], prefix: &str, out: &mut String) {
+    for (i, child) in children.iter().enumerate() {
+        let (branch, indent) = if i + 1 == children.len() {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        let node = &child.node;
+        let name = if is_container(&node.kind) {
+            format!("{}/", node.name)
+        } else {
+            node.name.clone()
+        };
+        out.push_str(&format!(
+            "\n{}{}{} {}",
+            prefix,
+            branch,
+            kind_style(&node.kind).paint(name),
+            Color::DarkGray.paint(node.kind.to_string())
+        ));
+        render_branches(&child.children, &format!("{}{}", prefix, indent), out);
+    }
+}
+
+/// Render a chain of nodes, each indented below the previous one and
+/// reached through the edge type shown before it
+pub fn render_chain(nodes: &[DisplayGraphNode], edges: &[QueryResultEdge]) -> String {
+    let mut out = String::new();
+    let mut prefix = String::new();
+    for (i, node) in nodes.iter().enumerate() {
+        let line = format!(
+            "{} {}",
+            kind_style(&node.kind).paint(&node.id),
+            Color::DarkGray.paint(node.kind.to_string())
+        );
+        match i.checked_sub(1).and_then(|i| edges.get(i)) {
+            Some(edge) => {
+                out.push_str(&format!(
+                    "\n{}└─{:?}─> {}",
+                    prefix, edge.data.edge_type, line
+                ));
+                prefix.push_str("   ");
+            }
+            None => out.push_str(&line),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree_node(name: &str, kind: &str, children: Vec<ShellTreeNode>) -> ShellTreeNode {
+        let node = serde_json::json!({ "id": name, "name": name, "kind": kind, "lang": "java" });
+        ShellTreeNode {
+            node: serde_json::from_value(node).unwrap(),
+            children,
+        }
+    }
+
+    #[test]
+    fn tree_draws_branches_and_marks_containers() {
+        let children = vec![
+            tree_node(
+                "A",
+                "class",
+                vec![
+                    tree_node("run()", "method", vec![]),
+                    tree_node("x", "field", vec![]),
+                ],
+            ),
+            tree_node("b", "package", vec![tree_node("B", "interface", vec![])]),
+        ];
+
+        let tree = render_tree("com.example", &children);
+        let lines: Vec<_> = super::super::output::plain(&tree)
+            .lines()
+            .map(str::to_string)
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "com.example",
+                "├── A/ class",
+                "│   ├── run() method",
+                "│   └── x field",
+                "└── b/ package",
+                "    └── B/ interface",
+            ]
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use naviscope_api::lifecycle::{IndexWarning, RuntimeMetrics};
+use naviscope_api::models::{Distribution, GraphQuery, QueryGroup, RankedSymbol};
+use std::path::{Path, PathBuf};
+use tabled::{Table, Tabled, settings::Style};
+
+#[derive(Tabled)]
+struct CountRow {
+    name: String,
+    count: usize,
+}
+
+#[derive(Tabled)]
+struct DistributionRow {
+    metric: &'static str,
+    mean: String,
+    median: usize,
+    p90: usize,
+    max: usize,
+}
+
+#[derive(Tabled)]
+struct RankedRow {
+    fqn: String,
+    kind: String,
+    count: usize,
+}
+
+#[derive(Tabled)]
+struct LatencyRow {
+    #[tabled(rename = "at most")]
+    bound: String,
+    updates: u64,
+}
+
+#[derive(Tabled)]
+struct WarningRow {
+    path: String,
+    phase: &'static str,
+    severity: String,
+    message: String,
+}
+
+pub async fn run(
+    path: PathBuf,
+    top: usize,
+    json: bool,
+    warnings: bool,
+    runtime: bool,
+    remote: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let engine = match remote {
+        Some(addr) => naviscope_runtime::connect_remote(&addr),
+        None => {
+            let engine = naviscope_runtime::build_default_engine(path.clone());
+            if !engine.load().await? {
+                println!(
+                    "No index found for {}. Run `naviscope index` first.",
+                    path.display()
+                );
+            }
+            engine
+        }
+    };
+
+    if warnings {
+        return print_warnings(&path, engine.index_warnings().await?, json);
+    }
+    if runtime {
+        return print_runtime(engine.runtime_metrics().await?, json);
+    }
+
+    let stats = engine.get_stats().await?;
+    let metrics = engine
+        .query(&GraphQuery::Stats { top })
+        .await?
+        .metrics
+        .unwrap_or_default();
+
+    if json {
+        let report = serde_json::json!({
+            "project": path,
+            "source_roots": stats.source_roots,
+            "toolchains": stats.toolchains,
+            "metrics": metrics,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!("Project: {}", path.display());
+    println!("Nodes:   {}", stats.node_count);
+    println!("Edges:   {}", stats.edge_count);
+    println!("Unresolved edges: {}", metrics.unresolved_edges);
+
+    println!("Source Roots:");
+    if stats.source_roots.is_empty() {
+        println!("  (none detected)");
+    }
+    for root in &stats.source_roots {
+        let display = root.path.strip_prefix(&path).unwrap_or(&root.path);
+        println!("  {} ({:?})", display.display(), root.origin);
+    }
+
+    println!("Toolchains:");
+    if stats.toolchains.is_empty() {
+        println!("  (none found)");
+    }
+    for toolchain in &stats.toolchains {
+        println!(
+            "  {} {} ({})",
+            toolchain.language,
+            toolchain.version.as_deref().unwrap_or("(unknown version)"),
+            toolchain.home.display()
+        );
+    }
+
+    print_table("Nodes by kind", counts(&metrics.nodes_by_kind));
+    print_table("Edges by type", counts(&metrics.edges_by_type));
+    print_table(
+        "Fan-in / fan-out (project types and methods)",
+        vec![
+            distribution("fan-in", &metrics.fan_in),
+            distribution("fan-out", &metrics.fan_out),
+        ],
+    );
+    print_table(
+        "Largest classes (members)",
+        ranked(&metrics.largest_classes),
+    );
+    print_table("Most referenced symbols", ranked(&metrics.most_referenced));
+    print_table("Hot spots (commits)", ranked(&metrics.hot_spots));
+
+    Ok(())
+}
+
+fn print_warnings(
+    path: &Path,
+    warnings: Vec<IndexWarning>,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(&warnings)?);
+        return Ok(());
+    }
+    if warnings.is_empty() {
+        println!("No warnings recorded for {}.", path.display());
+        return Ok(());
+    }
+
+    let rows: Vec<WarningRow> = warnings
+        .into_iter()
+        .map(|w| WarningRow {
+            path: Path::new(&w.path)
+                .strip_prefix(path)
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|_| w.path.clone()),
+            phase: w.phase.label(),
+            severity: format!("{:?}", w.severity).to_lowercase(),
+            message: w.message,
+        })
+        .collect();
+    println!("{} warnings:", rows.len());
+    println!("{}", Table::new(&rows).with(Style::psql()));
+    Ok(())
+}
+
+fn print_runtime(metrics: RuntimeMetrics, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(&metrics)?);
+        return Ok(());
+    }
+
+    let latency = &metrics.execute_latency;
+    println!("Files handed to updates: {}", metrics.runnable);
+    println!("Files held back:         {}", metrics.deferred);
+    println!("Snapshots committed:     {}", metrics.committed);
+    println!("Updates executed:        {}", latency.count);
+    if let Some(mean) = latency.sum_ms.checked_div(latency.count) {
+        println!("Mean update latency:     {} ms", mean);
+    }
+
+    let mut rows: Vec<LatencyRow> = latency
+        .buckets
+        .iter()
+        .map(|b| LatencyRow {
+            bound: format!("{} ms", b.le_ms),
+            updates: b.count,
+        })
+        .collect();
+    if latency.count > 0 {
+        rows.push(LatencyRow {
+            bound: "any".to_string(),
+            updates: latency.count,
+        });
+        print_table("Update latency", rows);
+    }
+    Ok(())
+}
+
+fn print_table<T: Tabled>(title: &str, rows: Vec<T>) {
+    if rows.is_empty() {
+        return;
+    }
+    println!("\n{}:", title);
+    println!("{}", Table::new(&rows).with(Style::psql()));
+}
+
+fn counts(groups: &[QueryGroup]) -> Vec<CountRow> {
+    groups
+        .iter()
+        .map(|g| CountRow {
+            name: g.key.clone(),
+            count: g.count,
+        })
+        .collect()
+}
+
+fn distribution(metric: &'static str, d: &Distribution) -> DistributionRow {
+    DistributionRow {
+        metric,
+        mean: format!("{:.1}", d.mean),
+        median: d.median,
+        p90: d.p90,
+        max: d.max,
+    }
+}
+
+fn ranked(symbols: &[RankedSymbol]) -> Vec<RankedRow> {
+    symbols
+        .iter()
+        .map(|s| RankedRow {
+            fqn: s.fqn.clone(),
+            kind: s.kind.to_string(),
+            count: s.count,
+        })
+        .collect()
+}
@@ -0,0 +1,578 @@
+use crate::shell::{ShellContext, get_kind_weight, is_container};
+use naviscope_api::models::{
+    DisplayGraphNode, EdgeType, GraphQuery, NodeHistory, NodeNote, NodeSource,
+};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::ListState;
+
+type AppResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+/// Maximum number of search results listed
+const SEARCH_LIMIT: usize = 50;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Tree,
+    Outgoing,
+    Incoming,
+}
+
+/// A visible line of the containment tree
+pub struct TreeRow {
+    pub node: DisplayGraphNode,
+    pub depth: usize,
+    pub expanded: bool,
+}
+
+/// An edge of the selected node and the node at its other end
+pub struct EdgeRow {
+    pub edge_type: EdgeType,
+    pub node: DisplayGraphNode,
+}
+
+/// The node shown in the details pane, with its edges
+pub struct Selection {
+    pub node: DisplayGraphNode,
+    pub notes: Vec<NodeNote>,
+    pub history: Option<NodeHistory>,
+    pub outgoing: Vec<EdgeRow>,
+    pub incoming: Vec<EdgeRow>,
+}
+
+#[derive(Default)]
+pub struct Search {
+    pub pattern: String,
+    pub results: Vec<DisplayGraphNode>,
+    pub state: ListState,
+}
+
+pub struct App {
+    context: ShellContext,
+    pub rows: Vec<TreeRow>,
+    pub tree: ListState,
+    pub outgoing: ListState,
+    pub incoming: ListState,
+    pub focus: Focus,
+    pub selection: Option<Selection>,
+    pub search: Option<Search>,
+    /// Nodes navigated away from, most recent last
+    history: Vec<String>,
+    /// Shown in the status line until the next key
+    pub message: Option<String>,
+    pub quit: bool,
+}
+
+impl App {
+    pub fn new(context: ShellContext) -> AppResult<Self> {
+        let mut app = Self {
+            context,
+            rows: Vec::new(),
+            tree: ListState::default(),
+            outgoing: ListState::default(),
+            incoming: ListState::default(),
+            focus: Focus::Tree,
+            selection: None,
+            search: None,
+            history: Vec::new(),
+            message: None,
+            quit: false,
+        };
+        app.rows = app
+            .children(None)?
+            .into_iter()
+            .map(|node| TreeRow {
+                node,
+                depth: 0,
+                expanded: false,
+            })
+            .collect();
+        if !app.rows.is_empty() {
+            app.select_row(0)?;
+        }
+        Ok(app)
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        self.message = None;
+        if let Err(e) = self.dispatch(key) {
+            self.message = Some(e.to_string());
+        }
+    }
+
+    fn dispatch(&mut self, key: KeyEvent) -> AppResult<()> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return Ok(());
+        }
+        if self.search.is_some() {
+            return self.search_key(key);
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('/') => self.search = Some(Search::default()),
+            KeyCode::Tab => self.focus = self.next_focus(true),
+            KeyCode::BackTab => self.focus = self.next_focus(false),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1)?,
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1)?,
+            KeyCode::PageUp => self.move_selection(-10)?,
+            KeyCode::PageDown => self.move_selection(10)?,
+            KeyCode::Backspace => self.back()?,
+            KeyCode::Right | KeyCode::Char('l') if self.focus == Focus::Tree => {
+                if let Some(i) = self.tree.selected() {
+                    self.expand(i)?;
+                }
+            }
+            KeyCode::Left | KeyCode::Char('h') if self.focus == Focus::Tree => self.collapse()?,
+            KeyCode::Enter => match self.focus {
+                Focus::Tree => {
+                    if let Some(i) = self.tree.selected() {
+                        if self.rows[i].expanded {
+                            self.collapse()?;
+                        } else {
+                            self.expand(i)?;
+                        }
+                    }
+                }
+                Focus::Outgoing | Focus::Incoming => self.follow_edge()?,
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn search_key(&mut self, key: KeyEvent) -> AppResult<()> {
+        let Some(search) = self.search.as_mut() else {
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc => self.search = None,
+            KeyCode::Up => search.state.select_previous(),
+            KeyCode::Down => search.state.select_next(),
+            KeyCode::Enter => {
+                let target = search
+                    .state
+                    .selected()
+                    .and_then(|i| search.results.get(i))
+                    .map(|node| node.id.clone());
+                self.search = None;
+                if let Some(fqn) = target {
+                    self.jump(&fqn)?;
+                }
+            }
+            KeyCode::Backspace => {
+                search.pattern.pop();
+                self.update_search()?;
+            }
+            KeyCode::Char(c) => {
+                search.pattern.push(c);
+                self.update_search()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Rerun the search for the pattern typed so far
+    fn update_search(&mut self) -> AppResult<()> {
+        let Some(pattern) = self.search.as_ref().map(|s| s.pattern.clone()) else {
+            return Ok(());
+        };
+        let results = if pattern.is_empty() {
+            Vec::new()
+        } else {
+            let query = GraphQuery::Fuzzy {
+                pattern,
+                kind: vec![],
+                sources: vec![],
+                statuses: vec![],
+                limit: SEARCH_LIMIT,
+            };
+            self.context.execute_query(&query)?.nodes
+        };
+        if let Some(search) = self.search.as_mut() {
+            search.state.select((!results.is_empty()).then_some(0));
+            search.results = results;
+        }
+        Ok(())
+    }
+
+    fn next_focus(&self, forward: bool) -> Focus {
+        let order = [Focus::Tree, Focus::Outgoing, Focus::Incoming];
+        let i = order.iter().position(|f| *f == self.focus).unwrap_or(0);
+        let step = if forward { 1 } else { order.len() - 1 };
+        order[(i + step) % order.len()]
+    }
+
+    fn move_selection(&mut self, delta: isize) -> AppResult<()> {
+        let (state, len) = match self.focus {
+            Focus::Tree => (&mut self.tree, self.rows.len()),
+            Focus::Outgoing => (
+                &mut self.outgoing,
+                self.selection.as_ref().map_or(0, |s| s.outgoing.len()),
+            ),
+            Focus::Incoming => (
+                &mut self.incoming,
+                self.selection.as_ref().map_or(0, |s| s.incoming.len()),
+            ),
+        };
+        if len == 0 {
+            return Ok(());
+        }
+        let current = state.selected().unwrap_or(0) as isize;
+        let next = (current + delta).clamp(0, len as isize - 1) as usize;
+        if self.focus == Focus::Tree {
+            if self.tree.selected() != Some(next) {
+                self.select_row(next)?;
+            }
+        } else {
+            state.select(Some(next));
+        }
+        Ok(())
+    }
+
+    /// Project nodes directly contained in `fqn`, or the root modules
+    fn children(&self, fqn: Option<String>) -> AppResult<Vec<DisplayGraphNode>> {
+        let query = GraphQuery::Ls {
+            fqn,
+            kind: vec![],
+            sources: vec![NodeSource::Project],
+            statuses: vec![],
+            modifiers: vec![],
+            group_by: None,
+            count: false,
+        };
+        let mut nodes = self.context.execute_query(&query)?.nodes;
+        nodes.sort_by(|a, b| {
+            get_kind_weight(&a.kind.to_string())
+                .cmp(&get_kind_weight(&b.kind.to_string()))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(nodes)
+    }
+
+    fn expand(&mut self, index: usize) -> AppResult<()> {
+        let row = &self.rows[index];
+        if row.expanded || !is_container(&row.node.kind) {
+            return Ok(());
+        }
+        let depth = row.depth + 1;
+        let children = self.children(Some(row.node.id.clone()))?;
+        self.rows[index].expanded = true;
+        self.rows.splice(
+            index + 1..index + 1,
+            children.into_iter().map(|node| TreeRow {
+                node,
+                depth,
+                expanded: false,
+            }),
+        );
+        Ok(())
+    }
+
+    /// Collapse the selected row, or select its parent when it is not expanded
+    fn collapse(&mut self) -> AppResult<()> {
+        let Some(index) = self.tree.selected() else {
+            return Ok(());
+        };
+        let depth = self.rows[index].depth;
+        if self.rows[index].expanded {
+            let end = self.rows[index + 1..]
+                .iter()
+                .position(|row| row.depth <= depth)
+                .map_or(self.rows.len(), |n| index + 1 + n);
+            self.rows.drain(index + 1..end);
+            self.rows[index].expanded = false;
+        } else if let Some(parent) = self.rows[..index].iter().rposition(|r| r.depth < depth) {
+            self.select_row(parent)?;
+        }
+        Ok(())
+    }
+
+    fn select_row(&mut self, index: usize) -> AppResult<()> {
+        self.tree.select(Some(index));
+        let fqn = self.rows[index].node.id.clone();
+        self.inspect(&fqn)
+    }
+
+    /// Load the details and edges of `fqn` into the right-hand panes
+    fn inspect(&mut self, fqn: &str) -> AppResult<()> {
+        let result = self.context.execute_query(&GraphQuery::Cat {
+            fqn: fqn.to_string(),
+            limits: None,
+        })?;
+        let Some(node) = result.nodes.into_iter().next() else {
+            return Err(format!("Node '{}' not found.", fqn).into());
+        };
+        let selection = Selection {
+            node,
+            notes: result.notes,
+            history: result.history,
+            outgoing: self.edges(fqn, false)?,
+            incoming: self.edges(fqn, true)?,
+        };
+        self.outgoing
+            .select((!selection.outgoing.is_empty()).then_some(0));
+        self.incoming
+            .select((!selection.incoming.is_empty()).then_some(0));
+        self.selection = Some(selection);
+        Ok(())
+    }
+
+    /// Edges of `fqn` other than containment, which the tree already shows
+    fn edges(&self, fqn: &str, rev: bool) -> AppResult<Vec<EdgeRow>> {
+        let query = GraphQuery::Deps {
+            fqn: fqn.to_string(),
+            rev,
+            edge_types: vec![],
+            sources: vec![],
+            statuses: vec![],
+            group_by: None,
+            count: false,
+        };
+        let result = self.context.execute_query(&query)?;
+        let mut rows: Vec<EdgeRow> = result
+            .edges
+            .iter()
+            .filter(|e| e.data.edge_type != EdgeType::Contains)
+            .filter_map(|e| {
+                let other = if rev { &e.from } else { &e.to };
+                let node = result.nodes.iter().find(|n| n.id == other.as_ref())?;
+                Some(EdgeRow {
+                    edge_type: e.data.edge_type.clone(),
+                    node: node.clone(),
+                })
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            format!("{:?}", a.edge_type)
+                .cmp(&format!("{:?}", b.edge_type))
+                .then_with(|| a.node.id.cmp(&b.node.id))
+        });
+        Ok(rows)
+    }
+
+    fn follow_edge(&mut self) -> AppResult<()> {
+        let target = self.selection.as_ref().and_then(|s| match self.focus {
+            Focus::Outgoing => self.outgoing.selected().and_then(|i| s.outgoing.get(i)),
+            Focus::Incoming => self.incoming.selected().and_then(|i| s.incoming.get(i)),
+            Focus::Tree => None,
+        });
+        let Some(fqn) = target.map(|row| row.node.id.clone()) else {
+            return Ok(());
+        };
+        self.jump(&fqn)
+    }
+
+    /// Show `fqn`, remembering the current node for `back`
+    fn jump(&mut self, fqn: &str) -> AppResult<()> {
+        if let Some(current) = &self.selection {
+            self.history.push(current.node.id.clone());
+        }
+        self.show(fqn)
+    }
+
+    fn back(&mut self) -> AppResult<()> {
+        match self.history.pop() {
+            Some(fqn) => self.show(&fqn),
+            None => Ok(()),
+        }
+    }
+
+    /// Select `fqn` in the tree, expanding its ancestors. Nodes outside the
+    /// project tree, such as library types, are only inspected.
+    fn show(&mut self, fqn: &str) -> AppResult<()> {
+        let mut ancestors = Vec::new();
+        let mut current = fqn.to_string();
+        while let Some(parent) = self.parent(&current)? {
+            ancestors.push(parent.clone());
+            current = parent;
+        }
+        for ancestor in ancestors.iter().rev() {
+            if let Some(i) = self.row_of(ancestor) {
+                self.expand(i)?;
+            }
+        }
+
+        self.focus = Focus::Tree;
+        match self.row_of(fqn) {
+            Some(i) => self.select_row(i),
+            None => {
+                self.tree.select(None);
+                self.inspect(fqn)
+            }
+        }
+    }
+
+    fn parent(&self, fqn: &str) -> AppResult<Option<String>> {
+        let query = GraphQuery::Deps {
+            fqn: fqn.to_string(),
+            rev: true,
+            edge_types: vec![EdgeType::Contains],
+            sources: vec![],
+            statuses: vec![],
+            group_by: None,
+            count: false,
+        };
+        let result = self.context.execute_query(&query)?;
+        Ok(result.nodes.into_iter().next().map(|node| node.id))
+    }
+
+    fn row_of(&self, fqn: &str) -> Option<usize> {
+        self.rows.iter().position(|row| row.node.id == fqn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Explorer over packages `com.app` and `com.app.sub` with classes `A`
+    /// (declaring `run()` and implementing `B`), interface `B` and `sub.C`
+    fn sample_app() -> (App, tempfile::TempDir, tokio::runtime::Runtime) {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in [
+            (
+                "com/app/A.java",
+                "package com.app;\npublic class A implements B {\n    public void run() {}\n}\n",
+            ),
+            (
+                "com/app/B.java",
+                "package com.app;\npublic interface B {}\n",
+            ),
+            (
+                "com/app/sub/C.java",
+                "package com.app.sub;\npublic class C {}\n",
+            ),
+        ] {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let engine = {
+            let _guard = rt.enter();
+            naviscope_runtime::build_default_engine(dir.path().to_path_buf())
+        };
+        rt.block_on(engine.rebuild()).unwrap();
+        let context = ShellContext::new(engine, rt.handle().clone(), Default::default());
+        (App::new(context).unwrap(), dir, rt)
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_key(KeyEvent::from(code));
+        assert_eq!(app.message, None);
+    }
+
+    fn selected(app: &App) -> &str {
+        &app.selection.as_ref().unwrap().node.id
+    }
+
+    fn tree(app: &App) -> Vec<&str> {
+        app.rows.iter().map(|row| row.node.id.as_str()).collect()
+    }
+
+    #[test]
+    fn selection_moves_across_tree_and_edge_panes() {
+        let (mut app, _dir, _rt) = sample_app();
+        assert_eq!(tree(&app), ["com.app", "com.app.sub"]);
+        assert_eq!(selected(&app), "com.app");
+
+        press(&mut app, KeyCode::Down);
+        assert_eq!(selected(&app), "com.app.sub");
+        // Moves stop at the ends of the list
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.tree.selected(), Some(1));
+        press(&mut app, KeyCode::PageUp);
+        assert_eq!(selected(&app), "com.app");
+
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(
+            tree(&app),
+            ["com.app", "com.app.A", "com.app.B", "com.app.sub"]
+        );
+        press(&mut app, KeyCode::Down);
+        assert_eq!(selected(&app), "com.app.A");
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.rows[2].node.id, "com.app.A#run()");
+        assert_eq!(app.rows[2].depth, 2);
+
+        // Edge panes move their own selection without changing the node
+        press(&mut app, KeyCode::Tab);
+        assert!(app.focus == Focus::Outgoing);
+        assert_eq!(app.outgoing.selected(), Some(0));
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.outgoing.selected(), Some(0));
+        assert_eq!(selected(&app), "com.app.A");
+        press(&mut app, KeyCode::Tab);
+        assert!(app.focus == Focus::Incoming);
+        press(&mut app, KeyCode::Tab);
+        assert!(app.focus == Focus::Tree);
+        press(&mut app, KeyCode::BackTab);
+        assert!(app.focus == Focus::Incoming);
+
+        // Left collapses the selected node, then selects its parent
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Left);
+        assert_eq!(
+            tree(&app),
+            ["com.app", "com.app.A", "com.app.B", "com.app.sub"]
+        );
+        press(&mut app, KeyCode::Left);
+        assert_eq!(selected(&app), "com.app");
+    }
+
+    #[test]
+    fn following_an_edge_selects_the_connected_node() {
+        let (mut app, _dir, _rt) = sample_app();
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Down);
+        let edges = &app.selection.as_ref().unwrap().outgoing;
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].edge_type, EdgeType::Implements);
+
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(selected(&app), "com.app.B");
+        assert!(app.focus == Focus::Tree);
+        assert_eq!(app.tree.selected(), Some(2));
+        let incoming = &app.selection.as_ref().unwrap().incoming;
+        assert!(incoming.iter().any(|edge| edge.node.id == "com.app.A"));
+
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!(selected(&app), "com.app.A");
+        assert_eq!(app.tree.selected(), Some(1));
+    }
+
+    #[test]
+    fn search_results_follow_the_query() {
+        let (mut app, _dir, _rt) = sample_app();
+        let results = |app: &App| -> Vec<String> {
+            let search = app.search.as_ref().unwrap();
+            search.results.iter().map(|node| node.id.clone()).collect()
+        };
+
+        press(&mut app, KeyCode::Char('/'));
+        assert!(results(&app).is_empty());
+        press(&mut app, KeyCode::Char('a'));
+        let broad = results(&app);
+        assert!(broad.contains(&"com.app.A".to_string()));
+        press(&mut app, KeyCode::Char('p'));
+        let narrow = results(&app);
+        assert_eq!(narrow, ["com.app", "com.app.sub"]);
+        assert!(narrow.iter().all(|fqn| broad.contains(fqn)));
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!(results(&app), broad);
+        press(&mut app, KeyCode::Char('p'));
+
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        assert!(app.search.is_none());
+        assert_eq!(selected(&app), "com.app.sub");
+
+        press(&mut app, KeyCode::Char('/'));
+        press(&mut app, KeyCode::Char('x'));
+        press(&mut app, KeyCode::Esc);
+        assert!(app.search.is_none());
+        assert!(!app.quit);
+    }
+}
@@ -0,0 +1,45 @@
+//! Full-screen graph explorer
+//!
+//! The containment tree on the left, the selected node's details and its
+//! outgoing and incoming edges on the right. Following an edge selects the
+//! node at its other end, so the graph can be walked with the keyboard
+//! alone; `/` searches symbols as you type.
+
+mod app;
+mod ui;
+
+use self::app::App;
+use crate::shell::ReplServer;
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use std::path::PathBuf;
+
+pub async fn run(path: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    let project_path = match path {
+        Some(p) => p,
+        None => std::env::current_dir()?.canonicalize()?,
+    };
+    let server = ReplServer::new(project_path);
+    server.initialize_index(true).await?;
+    let mut app = App::new(server.context().clone())?;
+
+    let mut terminal = ratatui::init();
+    let result = explore(&mut terminal, &mut app);
+    ratatui::restore();
+    result
+}
+
+fn explore(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+) -> Result<(), Box<dyn std::error::Error>> {
+    while !app.quit {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            app.handle_key(key);
+        }
+    }
+    Ok(())
+}
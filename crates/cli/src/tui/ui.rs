@@ -0,0 +1,222 @@
+use super::app::{App, EdgeRow, Focus, Search, Selection};
+use naviscope_api::models::{DisplayGraphNode, NodeKind};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Wrap};
+
+const HELP: &str =
+    "/ search  tab pane  ↑↓ move  ←→ collapse/expand  enter follow  backspace back  q quit";
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [main, status] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+    let [tree, right] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Fill(1)]).areas(main);
+    let [details, edges] =
+        Layout::vertical([Constraint::Percentage(45), Constraint::Fill(1)]).areas(right);
+    let [outgoing, incoming] =
+        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(edges);
+
+    let items: Vec<ListItem> = app.rows.iter().map(tree_item).collect();
+    let block = pane("Tree", app.focus == Focus::Tree);
+    render_list(frame, tree, items, block, &mut app.tree);
+
+    frame.render_widget(
+        Paragraph::new(
+            app.selection
+                .as_ref()
+                .map(details_lines)
+                .unwrap_or_default(),
+        )
+        .block(Block::bordered().title(" Details "))
+        .wrap(Wrap { trim: false }),
+        details,
+    );
+
+    let (out_rows, in_rows) = match &app.selection {
+        Some(s) => (s.outgoing.as_slice(), s.incoming.as_slice()),
+        None => (&[][..], &[][..]),
+    };
+    let block = pane(
+        &format!("Outgoing ({})", out_rows.len()),
+        app.focus == Focus::Outgoing,
+    );
+    render_list(
+        frame,
+        outgoing,
+        out_rows.iter().map(edge_item).collect(),
+        block,
+        &mut app.outgoing,
+    );
+    let block = pane(
+        &format!("Incoming ({})", in_rows.len()),
+        app.focus == Focus::Incoming,
+    );
+    render_list(
+        frame,
+        incoming,
+        in_rows.iter().map(edge_item).collect(),
+        block,
+        &mut app.incoming,
+    );
+
+    let status_line = match &app.message {
+        Some(message) => Line::styled(message.as_str(), Style::new().fg(Color::Red)),
+        None => Line::styled(HELP, Style::new().add_modifier(Modifier::DIM)),
+    };
+    frame.render_widget(Paragraph::new(status_line), status);
+
+    if let Some(search) = app.search.as_mut() {
+        draw_search(frame, main, search);
+    }
+}
+
+fn pane(title: &str, focused: bool) -> Block<'static> {
+    let block = Block::bordered().title(format!(" {} ", title));
+    if focused {
+        block.border_style(Style::new().fg(Color::Yellow))
+    } else {
+        block
+    }
+}
+
+fn render_list(
+    frame: &mut Frame,
+    area: Rect,
+    items: Vec<ListItem>,
+    block: Block,
+    state: &mut ListState,
+) {
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(list, area, state);
+}
+
+fn draw_search(frame: &mut Frame, area: Rect, search: &mut Search) {
+    let [area] = Layout::horizontal([Constraint::Percentage(70)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Percentage(70)])
+        .flex(Flex::Center)
+        .areas(area);
+    frame.render_widget(Clear, area);
+
+    let items: Vec<ListItem> = search
+        .results
+        .iter()
+        .map(|node| {
+            ListItem::new(Line::from(vec![
+                Span::styled(node.name.clone(), kind_style(&node.kind)),
+                Span::styled(
+                    format!("  {} {}", node.kind.to_string(), node.id),
+                    Style::new().add_modifier(Modifier::DIM),
+                ),
+            ]))
+        })
+        .collect();
+    let block = pane(&format!("Search: {}▏", search.pattern), true);
+    render_list(frame, area, items, block, &mut search.state);
+}
+
+fn tree_item(row: &super::app::TreeRow) -> ListItem<'static> {
+    let marker = if !crate::shell::is_container(&row.node.kind) {
+        "  "
+    } else if row.expanded {
+        "▾ "
+    } else {
+        "▸ "
+    };
+    ListItem::new(Line::from(vec![
+        Span::raw(format!("{}{}", "  ".repeat(row.depth), marker)),
+        Span::styled(row.node.name.clone(), kind_style(&row.node.kind)),
+        Span::styled(
+            format!(" {}", row.node.kind.to_string()),
+            Style::new().add_modifier(Modifier::DIM),
+        ),
+    ]))
+}
+
+fn edge_item(row: &EdgeRow) -> ListItem<'static> {
+    ListItem::new(Line::from(vec![
+        Span::styled(
+            format!("{:?} ", row.edge_type),
+            Style::new().add_modifier(Modifier::DIM),
+        ),
+        Span::styled(row.node.id.clone(), kind_style(&row.node.kind)),
+    ]))
+}
+
+fn details_lines(selection: &Selection) -> Vec<Line<'static>> {
+    let node = &selection.node;
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(
+                node.name.clone(),
+                kind_style(&node.kind).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!("  {} ({:?})", node.kind.to_string(), node.source)),
+        ]),
+        field("fqn", node.id.clone()),
+    ];
+    if let Some(location) = location(node) {
+        lines.push(field("at", location));
+    }
+    if let Some(signature) = &node.signature {
+        lines.push(field("signature", signature.clone()));
+    }
+    if !node.modifiers.is_empty() {
+        lines.push(field("modifiers", node.modifiers.join(" ")));
+    }
+    if let Some(detail) = &node.detail {
+        lines.push(Line::raw(""));
+        lines.extend(detail.lines().map(|line| Line::raw(line.to_string())));
+    }
+    if let Some(history) = &selection.history {
+        lines.push(field("history", crate::shell::describe_history(history)));
+    }
+    for note in &selection.notes {
+        let tags: String = note.tags.iter().map(|t| format!(" #{}", t)).collect();
+        lines.push(field(
+            &format!("note {}", note.id),
+            format!("{}{}", note.text, tags),
+        ));
+    }
+    lines
+}
+
+fn field(name: &str, value: String) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(
+            format!("{}: ", name),
+            Style::new().add_modifier(Modifier::DIM),
+        ),
+        Span::raw(value),
+    ])
+}
+
+fn location(node: &DisplayGraphNode) -> Option<String> {
+    node.location
+        .as_ref()
+        .map(|loc| format!("{}:{}", loc.path, loc.range.start_line + 1))
+}
+
+/// Same colors as the shell's `tree` command
+fn kind_style(kind: &NodeKind) -> Style {
+    let style = Style::new();
+    match kind {
+        NodeKind::Project | NodeKind::Module => {
+            style.fg(Color::LightBlue).add_modifier(Modifier::BOLD)
+        }
+        NodeKind::Package => style.fg(Color::Blue),
+        NodeKind::Class => style.fg(Color::Yellow),
+        NodeKind::Interface => style.fg(Color::LightCyan),
+        NodeKind::Enum => style.fg(Color::LightMagenta),
+        NodeKind::Annotation => style.fg(Color::Magenta),
+        NodeKind::Method | NodeKind::Constructor => style.fg(Color::LightGreen),
+        NodeKind::Field => style.fg(Color::Cyan),
+        _ => style,
+    }
+}
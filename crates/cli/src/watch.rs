@@ -1,22 +1,89 @@
-use std::path::PathBuf;
-use tracing::info;
+use crate::shell::system_shell;
+use naviscope_api::lifecycle::{ChangeSummary, SHUTDOWN_DEADLINE};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tracing::{info, warn};
 
-pub async fn run(path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn run(
+    path: PathBuf,
+    exec: Option<String>,
+    emit_json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     let engine = naviscope_runtime::build_default_engine(path.clone());
 
     info!("Initializing: Indexing project at: {}...", path.display());
     engine.rebuild().await?;
     info!("Initial indexing complete.");
 
+    // Subscribe before watching so no update is missed
+    let mut changes = if exec.is_some() || emit_json {
+        Some(engine.subscribe_changes().await?)
+    } else {
+        None
+    };
+
     // Start background watcher via trait
     let watch_handle = engine.start_watch().await?;
     info!("File watcher started. Ready for changes.");
     info!("Press Ctrl+C to stop.");
 
     // Keep the main thread alive
-    tokio::signal::ctrl_c().await?;
+    match changes.as_mut() {
+        Some(changes) => loop {
+            tokio::select! {
+                result = tokio::signal::ctrl_c() => {
+                    result?;
+                    break;
+                }
+                summary = changes.next() => {
+                    let summary = summary?;
+                    let json = serde_json::to_string(&summary)?;
+                    if emit_json {
+                        println!("{}", json);
+                    }
+                    if let Some(command) = &exec {
+                        run_hook(command, &summary, &json, &path).await;
+                    }
+                }
+            }
+        },
+        None => tokio::signal::ctrl_c().await?,
+    }
     watch_handle.stop();
+    if let Err(e) = engine.shutdown(SHUTDOWN_DEADLINE).await {
+        warn!("Engine shutdown incomplete: {}", e);
+    }
     info!("Watcher stopped.");
 
     Ok(())
 }
+
+/// Run the `--exec` command in the project root with the summary on its
+/// stdin. A failing command is reported and watching goes on.
+async fn run_hook(command: &str, summary: &ChangeSummary, json: &str, root: &Path) {
+    let mut shell = system_shell(command);
+    shell
+        .current_dir(root)
+        .env("NAVISCOPE_EPOCH", summary.epoch.to_string())
+        .stdin(Stdio::piped());
+    let json = json.to_string();
+    let result = tokio::task::spawn_blocking(move || -> std::io::Result<_> {
+        let mut child = shell.spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // The command need not read its input
+            match writeln!(stdin, "{}", json) {
+                Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e),
+                _ => {}
+            }
+        }
+        child.wait()
+    })
+    .await;
+    match result {
+        Ok(Ok(status)) if !status.success() => warn!("'{}' exited with {}", command, status),
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => warn!("Failed to run '{}': {}", command, e),
+        Err(e) => warn!("Failed to run '{}': {}", command, e),
+    }
+}
@@ -0,0 +1,154 @@
+//! Vulnerabilities and licenses of dependencies
+//!
+//! Dependency nodes carry the coordinates of a library, nothing about its
+//! security or legal standing. `DependencyAdvisor`s know about those: the
+//! offline advisor reads them from OSV advisories and SPDX documents listed
+//! in `[advisories] files`, other advisors are registered with the engine.
+//! Their advice is attached when a dependency node is rendered, to the
+//! detail for reading and to the modifiers for filtering: `vulnerable`, the
+//! advisory ids and `license:<id>`.
+
+mod offline;
+
+pub use offline::OfflineAdvisor;
+
+use crate::model::DisplayGraphNode;
+use naviscope_plugin::{DependencyAdvice, DependencyAdvisor};
+use std::sync::Arc;
+
+/// Modifier of a dependency some advisory is against
+pub const VULNERABLE_MODIFIER: &str = "vulnerable";
+
+/// Registered advisors
+#[derive(Default)]
+pub struct Advisories {
+    advisors: Vec<Arc<dyn DependencyAdvisor>>,
+}
+
+impl Advisories {
+    pub fn new(advisors: Vec<Arc<dyn DependencyAdvisor>>) -> Self {
+        Self { advisors }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.advisors.is_empty()
+    }
+
+    /// What all advisors know about the library at `coordinates`
+    pub fn advise(&self, coordinates: &str) -> DependencyAdvice {
+        let mut advice = DependencyAdvice::default();
+        for advisor in &self.advisors {
+            if let Some(more) = advisor.advise(coordinates) {
+                advice.merge(more);
+            }
+        }
+        advice
+    }
+
+    /// Attach the advice on a rendered dependency node. Its coordinates are
+    /// the detail build plugins render for dependencies, or its name.
+    pub fn enrich(&self, display: &mut DisplayGraphNode) {
+        if self.is_empty() {
+            return;
+        }
+        let coordinates = display.detail.as_deref().unwrap_or(&display.name);
+        let advice = self.advise(coordinates);
+        if advice.is_empty() {
+            return;
+        }
+
+        let mut notes = Vec::new();
+        if !advice.vulnerabilities.is_empty() {
+            notes.push(format!("vulnerable: {}", advice.vulnerabilities.join(", ")));
+            display.modifiers.push(VULNERABLE_MODIFIER.to_string());
+            display
+                .modifiers
+                .extend(advice.vulnerabilities.iter().cloned());
+        }
+        if !advice.licenses.is_empty() {
+            notes.push(format!("license: {}", advice.licenses.join(", ")));
+            display.modifiers.extend(
+                advice
+                    .licenses
+                    .iter()
+                    .map(|license| format!("license:{}", license)),
+            );
+        }
+        let notes = notes.join("; ");
+        display.detail = Some(match display.detail.take() {
+            Some(detail) => format!("{} ({})", detail, notes),
+            None => notes,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use naviscope_api::models::graph::{NodeKind, NodeSource, ResolutionStatus};
+
+    struct FixedAdvisor(DependencyAdvice);
+
+    impl DependencyAdvisor for FixedAdvisor {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        fn advise(&self, coordinates: &str) -> Option<DependencyAdvice> {
+            (coordinates == "org.example:lib:1.0").then(|| self.0.clone())
+        }
+    }
+
+    fn dependency(detail: &str) -> DisplayGraphNode {
+        DisplayGraphNode {
+            id: format!("dep:{}", detail),
+            symbol_id: String::new(),
+            name: "lib".to_string(),
+            kind: NodeKind::Dependency,
+            lang: "gradle".to_string(),
+            source: NodeSource::External,
+            status: ResolutionStatus::Resolved,
+            generated: false,
+            location: None,
+            detail: Some(detail.to_string()),
+            signature: None,
+            modifiers: vec!["implementation".to_string()],
+            children: None,
+        }
+    }
+
+    #[test]
+    fn advice_is_merged_into_detail_and_modifiers() {
+        let advisories = Advisories::new(vec![
+            Arc::new(FixedAdvisor(DependencyAdvice {
+                vulnerabilities: vec!["CVE-2024-0001".to_string()],
+                licenses: vec![],
+            })),
+            Arc::new(FixedAdvisor(DependencyAdvice {
+                vulnerabilities: vec!["CVE-2024-0001".to_string()],
+                licenses: vec!["Apache-2.0".to_string()],
+            })),
+        ]);
+
+        let mut node = dependency("org.example:lib:1.0");
+        advisories.enrich(&mut node);
+        assert_eq!(
+            node.detail.as_deref(),
+            Some("org.example:lib:1.0 (vulnerable: CVE-2024-0001; license: Apache-2.0)")
+        );
+        assert_eq!(
+            node.modifiers,
+            [
+                "implementation",
+                VULNERABLE_MODIFIER,
+                "CVE-2024-0001",
+                "license:Apache-2.0"
+            ]
+        );
+
+        let mut unknown = dependency("org.example:lib:2.0");
+        advisories.enrich(&mut unknown);
+        assert_eq!(unknown.detail.as_deref(), Some("org.example:lib:2.0"));
+        assert_eq!(unknown.modifiers, ["implementation"]);
+    }
+}
@@ -0,0 +1,423 @@
+//! Advice read from OSV advisories and SPDX documents on disk
+//!
+//! OSV files hold a single advisory, an array of them, or the `vulns` of a
+//! query result; a directory stands for all the JSON files below it, as in
+//! an export of the OSV database. Only `Maven` packages are read. SPDX JSON
+//! documents give the concluded or declared license of their packages, which
+//! are matched by their Maven purl, or by a `group:artifact` name.
+
+use super::DependencyAdvisor;
+use crate::error::{NaviscopeError, Result};
+use naviscope_plugin::DependencyAdvice;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Versions of a package an advisory is against
+struct Affected {
+    ids: Vec<String>,
+    versions: Vec<String>,
+    /// Events of each `ECOSYSTEM` or `SEMVER` range, by ascending version
+    ranges: Vec<Vec<(Event, String)>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Event {
+    Introduced,
+    Fixed,
+    LastAffected,
+}
+
+/// A license of some or all versions of a package
+struct License {
+    version: Option<String>,
+    license: String,
+}
+
+/// Advisories and licenses by `group:artifact`
+#[derive(Default)]
+pub struct OfflineAdvisor {
+    affected: HashMap<String, Vec<Affected>>,
+    licenses: HashMap<String, Vec<License>>,
+}
+
+impl OfflineAdvisor {
+    /// Read the OSV and SPDX files or directories at `paths`. A file that is
+    /// neither is an error; a directory's other files are skipped.
+    pub fn load(paths: &[PathBuf]) -> Result<Self> {
+        let mut advisor = Self::default();
+        for path in paths {
+            if path.is_dir() {
+                for entry in walkdir::WalkDir::new(path)
+                    .into_iter()
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+                {
+                    if let Err(e) = advisor.read_file(entry.path()) {
+                        tracing::debug!("Skipping {}: {}", entry.path().display(), e);
+                    }
+                }
+            } else {
+                advisor.read_file(path)?;
+            }
+        }
+        Ok(advisor)
+    }
+
+    fn read_file(&mut self, path: &Path) -> Result<()> {
+        let text = std::fs::read_to_string(path)?;
+        let json: Value = serde_json::from_str(&text)
+            .map_err(|e| NaviscopeError::Parsing(format!("{}: {}", path.display(), e)))?;
+        self.read(&json)
+            .map_err(|e| NaviscopeError::Parsing(format!("{}: {}", path.display(), e)))
+    }
+
+    fn read(&mut self, json: &Value) -> std::result::Result<(), String> {
+        if let Some(packages) = json.get("spdxVersion").and(json.get("packages")) {
+            for package in packages.as_array().into_iter().flatten() {
+                self.read_spdx_package(package);
+            }
+            return Ok(());
+        }
+        let advisories = match json {
+            Value::Array(advisories) => advisories.as_slice(),
+            _ => match json.get("vulns") {
+                Some(Value::Array(advisories)) => advisories.as_slice(),
+                _ if json.get("affected").is_some() => std::slice::from_ref(json),
+                _ => return Err("neither an OSV advisory nor an SPDX document".to_string()),
+            },
+        };
+        for advisory in advisories {
+            self.read_osv_advisory(advisory);
+        }
+        Ok(())
+    }
+
+    fn read_osv_advisory(&mut self, advisory: &Value) {
+        let Some(id) = advisory.get("id").and_then(Value::as_str) else {
+            return;
+        };
+        // CVE aliases are the names most tools and people use
+        let mut ids: Vec<String> = strings(advisory.get("aliases"))
+            .filter(|alias| alias.starts_with("CVE-"))
+            .map(str::to_string)
+            .collect();
+        if ids.is_empty() {
+            ids.push(id.to_string());
+        }
+
+        for affected in advisory
+            .get("affected")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let package = affected.get("package");
+            let ecosystem = package
+                .and_then(|p| p.get("ecosystem"))
+                .and_then(Value::as_str);
+            let name = package.and_then(|p| p.get("name")).and_then(Value::as_str);
+            let (Some("Maven"), Some(name)) = (ecosystem, name) else {
+                continue;
+            };
+            let ranges = affected
+                .get("ranges")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter(|range| {
+                    matches!(
+                        range.get("type").and_then(Value::as_str),
+                        Some("ECOSYSTEM" | "SEMVER")
+                    )
+                })
+                .map(|range| {
+                    let mut events: Vec<(Event, String)> = range
+                        .get("events")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|event| {
+                            [
+                                ("introduced", Event::Introduced),
+                                ("fixed", Event::Fixed),
+                                ("last_affected", Event::LastAffected),
+                            ]
+                            .into_iter()
+                            .find_map(|(key, kind)| {
+                                Some((kind, event.get(key)?.as_str()?.to_string()))
+                            })
+                        })
+                        .collect();
+                    events.sort_by(|(_, a), (_, b)| compare_versions(a, b));
+                    events
+                })
+                .collect();
+            self.affected
+                .entry(name.to_string())
+                .or_default()
+                .push(Affected {
+                    ids: ids.clone(),
+                    versions: strings(affected.get("versions"))
+                        .map(str::to_string)
+                        .collect(),
+                    ranges,
+                });
+        }
+    }
+
+    fn read_spdx_package(&mut self, package: &Value) {
+        let license = ["licenseConcluded", "licenseDeclared"]
+            .into_iter()
+            .filter_map(|key| package.get(key).and_then(Value::as_str))
+            .find(|license| !matches!(*license, "NOASSERTION" | "NONE" | ""));
+        let Some(license) = license else {
+            return;
+        };
+        let purl = package
+            .get("externalRefs")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|r| r.get("referenceType").and_then(Value::as_str) == Some("purl"))
+            .find_map(|r| maven_purl(r.get("referenceLocator")?.as_str()?));
+        let (name, version) = match purl {
+            Some(purl) => purl,
+            None => {
+                let Some(name) = package
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .filter(|name| name.contains(':'))
+                else {
+                    return;
+                };
+                let version = package.get("versionInfo").and_then(Value::as_str);
+                (name.to_string(), version.map(str::to_string))
+            }
+        };
+        self.licenses.entry(name).or_default().push(License {
+            version,
+            license: license.to_string(),
+        });
+    }
+}
+
+impl DependencyAdvisor for OfflineAdvisor {
+    fn name(&self) -> &str {
+        "offline"
+    }
+
+    fn advise(&self, coordinates: &str) -> Option<DependencyAdvice> {
+        let (name, version) = split_coordinates(coordinates);
+        let mut advice = DependencyAdvice::default();
+        if let Some(version) = version {
+            for affected in self.affected.get(name).into_iter().flatten() {
+                if affected.affects(version) {
+                    advice.merge(DependencyAdvice {
+                        vulnerabilities: affected.ids.clone(),
+                        licenses: Vec::new(),
+                    });
+                }
+            }
+        }
+        for license in self.licenses.get(name).into_iter().flatten() {
+            let applies = match (&license.version, version) {
+                (Some(licensed), Some(version)) => licensed == version,
+                _ => true,
+            };
+            if applies {
+                advice.merge(DependencyAdvice {
+                    vulnerabilities: Vec::new(),
+                    licenses: vec![license.license.clone()],
+                });
+            }
+        }
+        (!advice.is_empty()).then_some(advice)
+    }
+}
+
+impl Affected {
+    fn affects(&self, version: &str) -> bool {
+        if self.versions.iter().any(|v| v == version) {
+            return true;
+        }
+        self.ranges.iter().any(|events| {
+            let mut affected = false;
+            for (event, bound) in events {
+                let order = compare_versions(version, bound);
+                match event {
+                    Event::Introduced if bound == "0" || order != Ordering::Less => affected = true,
+                    Event::Fixed if order != Ordering::Less => affected = false,
+                    Event::LastAffected if order == Ordering::Greater => affected = false,
+                    _ => {}
+                }
+            }
+            affected
+        })
+    }
+}
+
+fn strings(value: Option<&Value>) -> impl Iterator<Item = &str> {
+    value
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+}
+
+/// `group:artifact` and version of `pkg:maven/group/artifact@version`
+fn maven_purl(purl: &str) -> Option<(String, Option<String>)> {
+    let rest = purl.strip_prefix("pkg:maven/")?;
+    let rest = rest.split(['?', '#']).next().unwrap_or(rest);
+    let (path, version) = match rest.split_once('@') {
+        Some((path, version)) => (path, Some(version.to_string())),
+        None => (rest, None),
+    };
+    let (group, artifact) = path.rsplit_once('/')?;
+    Some((format!("{}:{}", group, artifact), version))
+}
+
+/// `group:artifact` and version of `group:artifact:version`
+fn split_coordinates(coordinates: &str) -> (&str, Option<&str>) {
+    if coordinates.matches(':').count() >= 2
+        && let Some((name, version)) = coordinates.rsplit_once(':')
+    {
+        return (name, Some(version));
+    }
+    (coordinates, None)
+}
+
+/// Maven-like ordering: numeric segments compare as numbers and come after
+/// qualifiers, so `1.0-beta9` < `1.0-beta10` < `1.0` = `1.0.0` < `1.10`
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    // `beta10` is the qualifier `beta` and the number 10
+    let segments = |v: &str| -> Vec<String> {
+        let mut segments: Vec<String> = Vec::new();
+        let mut last: Option<char> = None;
+        for c in v.to_lowercase().chars() {
+            if matches!(c, '.' | '-' | '_' | '+') {
+                last = None;
+                continue;
+            }
+            match (last, segments.last_mut()) {
+                (Some(l), Some(segment)) if l.is_ascii_digit() == c.is_ascii_digit() => {
+                    segment.push(c)
+                }
+                _ => segments.push(c.to_string()),
+            }
+            last = Some(c);
+        }
+        segments
+    };
+    let (a, b) = (segments(a), segments(b));
+    for i in 0..a.len().max(b.len()) {
+        let order = match (a.get(i), b.get(i)) {
+            (Some(x), Some(y)) => match (x.parse::<u64>(), y.parse::<u64>()) {
+                (Ok(x), Ok(y)) => x.cmp(&y),
+                (Ok(_), Err(_)) => Ordering::Greater,
+                (Err(_), Ok(_)) => Ordering::Less,
+                (Err(_), Err(_)) => x.cmp(y),
+            },
+            // A release is newer than its qualified versions, older than its
+            // patch versions
+            (Some(x), None) => match x.parse::<u64>() {
+                Ok(0) => Ordering::Equal,
+                Ok(_) => Ordering::Greater,
+                Err(_) => Ordering::Less,
+            },
+            (None, Some(y)) => match y.parse::<u64>() {
+                Ok(0) => Ordering::Equal,
+                Ok(_) => Ordering::Less,
+                Err(_) => Ordering::Greater,
+            },
+            (None, None) => Ordering::Equal,
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OSV: &str = r#"[{
+        "id": "GHSA-jfh8-c2jp-5v3q",
+        "aliases": ["CVE-2021-44228"],
+        "affected": [{
+            "package": {"ecosystem": "Maven", "name": "org.apache.logging.log4j:log4j-core"},
+            "ranges": [{"type": "ECOSYSTEM", "events": [
+                {"introduced": "2.0-beta9"}, {"fixed": "2.15.0"}
+            ]}]
+        }]
+    }, {
+        "id": "GHSA-0000-0000-0000",
+        "affected": [{
+            "package": {"ecosystem": "npm", "name": "org.apache.logging.log4j:log4j-core"},
+            "versions": ["2.16.0"]
+        }]
+    }]"#;
+
+    const SPDX: &str = r#"{
+        "spdxVersion": "SPDX-2.3",
+        "packages": [{
+            "name": "log4j-core",
+            "versionInfo": "2.14.1",
+            "licenseConcluded": "NOASSERTION",
+            "licenseDeclared": "Apache-2.0",
+            "externalRefs": [{
+                "referenceType": "purl",
+                "referenceLocator": "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1"
+            }]
+        }]
+    }"#;
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.0-beta", "1.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0", "1.0.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.9", "1.10"), Ordering::Less);
+        assert_eq!(compare_versions("2.0-beta9", "2.0-beta10"), Ordering::Less);
+        assert_eq!(compare_versions("2.15.0", "2.15"), Ordering::Equal);
+        assert_eq!(compare_versions("2.15.1", "2.15"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0", "1.0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn advisories_and_licenses_are_read_from_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let osv = dir.path().join("osv");
+        std::fs::create_dir(&osv).unwrap();
+        std::fs::write(osv.join("log4j.json"), OSV).unwrap();
+        std::fs::write(osv.join("README.md"), "not an advisory").unwrap();
+        let spdx = dir.path().join("sbom.spdx.json");
+        std::fs::write(&spdx, SPDX).unwrap();
+
+        let advisor = OfflineAdvisor::load(&[osv, spdx]).unwrap();
+        let advice = advisor
+            .advise("org.apache.logging.log4j:log4j-core:2.14.1")
+            .unwrap();
+        assert_eq!(advice.vulnerabilities, ["CVE-2021-44228"]);
+        assert_eq!(advice.licenses, ["Apache-2.0"]);
+
+        // Fixed, and not the licensed version
+        assert!(
+            advisor
+                .advise("org.apache.logging.log4j:log4j-core:2.16.0")
+                .is_none()
+        );
+        // Without a version, only the license is known
+        let advice = advisor
+            .advise("org.apache.logging.log4j:log4j-core")
+            .unwrap();
+        assert!(advice.vulnerabilities.is_empty());
+        assert_eq!(advice.licenses, ["Apache-2.0"]);
+
+        let broken = dir.path().join("broken.json");
+        std::fs::write(&broken, "{\"name\": 1}").unwrap();
+        assert!(OfflineAdvisor::load(&[broken]).is_err());
+    }
+}
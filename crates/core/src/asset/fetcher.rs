@@ -0,0 +1,189 @@
+//! On-demand download of source artifacts from remote Maven repositories.
+//!
+//! Used when no local `-sources.jar` sits next to a dependency. Downloaded
+//! artifacts are stored under the stub cache directory using the standard
+//! Maven repository layout, so each artifact is fetched at most once.
+
+use naviscope_plugin::{AssetSource, BoxError};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{debug, info};
+
+pub const MAVEN_CENTRAL: &str = "https://repo1.maven.org/maven2";
+
+/// Upper bound for one download, so a stalled repository cannot block navigation
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Downloads `-sources.jar` artifacts for Maven coordinates.
+pub struct SourceFetcher {
+    repositories: Vec<String>,
+    cache_dir: PathBuf,
+    agent: ureq::Agent,
+    /// Relative artifact paths that no repository could provide
+    misses: Mutex<HashSet<PathBuf>>,
+}
+
+impl SourceFetcher {
+    pub fn new(repositories: Vec<String>, cache_dir: PathBuf) -> Self {
+        Self {
+            repositories,
+            cache_dir,
+            agent: ureq::Agent::config_builder()
+                .timeout_global(Some(DOWNLOAD_TIMEOUT))
+                .build()
+                .into(),
+            misses: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Default download location inside the global stub cache
+    pub fn default_cache_dir() -> PathBuf {
+        crate::cache::GlobalStubCache::default_location().join("sources")
+    }
+
+    /// Return the local path of the source artifact for `source`, downloading it if needed.
+    ///
+    /// Only Gradle and Maven assets carry coordinates; other sources yield `None`.
+    pub fn fetch(&self, source: &AssetSource) -> Option<PathBuf> {
+        let rel = source_artifact_path(source)?;
+        let target = self.cache_dir.join(&rel);
+        if target.exists() {
+            return Some(target);
+        }
+        if self.misses.lock().ok()?.contains(&rel) {
+            return None;
+        }
+
+        let rel_url = rel.to_string_lossy().replace('\\', "/");
+        for repo in &self.repositories {
+            let url = format!("{}/{}", repo.trim_end_matches('/'), rel_url);
+            match self.download(&url, &target) {
+                Ok(()) => {
+                    info!("Downloaded source artifact {}", url);
+                    return Some(target);
+                }
+                Err(e) => debug!("Source artifact not available at {}: {}", url, e),
+            }
+        }
+
+        if let Ok(mut misses) = self.misses.lock() {
+            misses.insert(rel);
+        }
+        None
+    }
+
+    fn download(&self, url: &str, target: &Path) -> Result<(), BoxError> {
+        let response = self.agent.get(url).call()?;
+
+        let parent = target.parent().unwrap_or_else(|| Path::new("."));
+        fs::create_dir_all(parent)?;
+        // Write to a temporary file of this fetch first, so readers never see
+        // a partial jar and concurrent fetches of the same artifact do not
+        // write into each other's. It is removed if the download fails.
+        let mut partial = tempfile::Builder::new()
+            .suffix(".jar.part")
+            .tempfile_in(parent)?;
+        io::copy(
+            &mut response.into_body().into_reader(),
+            partial.as_file_mut(),
+        )?;
+        partial.persist(target)?;
+        Ok(())
+    }
+}
+
+/// Maven repository layout path of the `-sources.jar` for an asset source.
+fn source_artifact_path(source: &AssetSource) -> Option<PathBuf> {
+    let (group, artifact, version) = match source {
+        AssetSource::Gradle {
+            group,
+            artifact,
+            version,
+        }
+        | AssetSource::Maven {
+            group,
+            artifact,
+            version,
+        } => (group, artifact, version),
+        _ => return None,
+    };
+
+    let mut path: PathBuf = group.split('.').collect();
+    path.push(artifact);
+    path.push(version);
+    path.push(format!("{}-{}-sources.jar", artifact, version));
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_artifact_path_follows_maven_layout() {
+        let source = AssetSource::Gradle {
+            group: "io.netty".to_string(),
+            artifact: "netty-common".to_string(),
+            version: "4.1.100.Final".to_string(),
+        };
+        assert_eq!(
+            source_artifact_path(&source),
+            Some(PathBuf::from(
+                "io/netty/netty-common/4.1.100.Final/netty-common-4.1.100.Final-sources.jar"
+            ))
+        );
+    }
+
+    #[test]
+    fn source_artifact_path_requires_coordinates() {
+        assert_eq!(source_artifact_path(&AssetSource::Unknown), None);
+    }
+
+    #[test]
+    fn fetch_reuses_cached_artifact() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = AssetSource::Maven {
+            group: "com.example".to_string(),
+            artifact: "lib".to_string(),
+            version: "1.0".to_string(),
+        };
+        let cached = dir.path().join("com/example/lib/1.0/lib-1.0-sources.jar");
+        fs::create_dir_all(cached.parent().unwrap()).unwrap();
+        fs::write(&cached, b"jar").unwrap();
+
+        // No repositories: the result must come from the cache without network access
+        let fetcher = SourceFetcher::new(vec![], dir.path().to_path_buf());
+        assert_eq!(fetcher.fetch(&source), Some(cached));
+    }
+
+    #[test]
+    fn interrupted_download_leaves_no_partial_file() {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let repo = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.read(&mut [0; 1024]);
+            // Promise more bytes than are sent, then hang up
+            let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\njar");
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let source = AssetSource::Maven {
+            group: "com.example".to_string(),
+            artifact: "lib".to_string(),
+            version: "1.0".to_string(),
+        };
+        let fetcher = SourceFetcher::new(vec![repo], dir.path().to_path_buf());
+        assert_eq!(fetcher.fetch(&source), None);
+
+        let artifact_dir = dir.path().join("com/example/lib/1.0");
+        assert!(artifact_dir.is_dir());
+        assert_eq!(fs::read_dir(&artifact_dir).unwrap().count(), 0);
+    }
+}
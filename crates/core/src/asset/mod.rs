@@ -4,6 +4,7 @@
 //! - Discovering assets (JARs, JDK modules) from various sources
 //! - Building route tables (FQN prefix -> asset paths)
 //! - Managing stub requests and generation
+//! - Optionally downloading missing source artifacts (see [`fetcher`])
 //!
 //! ## Architecture
 //!
@@ -26,10 +27,12 @@
 //! - `naviscope-java::JdkDiscoverer` - JDK asset discovery
 //! - `naviscope-gradle::GradleCacheDiscoverer` - Gradle cache discovery
 
+pub mod fetcher;
 pub mod registry;
 pub mod scanner;
 pub mod service;
 
+pub use fetcher::SourceFetcher;
 pub use registry::InMemoryRouteRegistry;
 pub use scanner::AssetScanner;
 pub use service::AssetStubService;
@@ -6,6 +6,8 @@
 //! 3. Results are registered in the route registry
 
 use naviscope_plugin::{AssetDiscoverer, AssetEntry, AssetIndexer, AssetRouteRegistry};
+use std::collections::HashSet;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tracing::{debug, info, warn};
 
@@ -106,6 +108,72 @@ impl AssetScanner {
         result
     }
 
+    /// Directories to watch for newly fetched assets, across all discoverers
+    pub fn watch_roots(&self) -> Vec<PathBuf> {
+        let mut roots: Vec<PathBuf> = self
+            .discoverers
+            .iter()
+            .flat_map(|d| d.watch_roots())
+            .collect();
+        roots.sort();
+        roots.dedup();
+        roots
+    }
+
+    /// Re-discover the assets of watched discoverers and register only the
+    /// ones not yet in `registry`; returns the newly indexed entries.
+    ///
+    /// Discovery only walks directories, so this is far cheaper than a full
+    /// scan: existing assets are not re-indexed.
+    pub fn scan_new(&self, registry: &dyn AssetRouteRegistry) -> (ScanResult, Vec<AssetEntry>) {
+        let mut result = ScanResult::default();
+        let mut added = Vec::new();
+        let start = std::time::Instant::now();
+        let mut known: HashSet<PathBuf> = registry
+            .all_routes()
+            .into_values()
+            .flatten()
+            .map(|e| e.path)
+            .collect();
+
+        for discoverer in self
+            .discoverers
+            .iter()
+            .filter(|d| !d.watch_roots().is_empty())
+        {
+            for entry in discoverer.discover() {
+                if !known.insert(entry.path.clone()) {
+                    continue;
+                }
+                result.total_assets += 1;
+                let Some(indexer) = self.find_indexer(&entry) else {
+                    result.skipped_assets += 1;
+                    continue;
+                };
+                match self.index_and_register(&entry, indexer.as_ref(), registry) {
+                    Ok(prefix_count) => {
+                        result.indexed_assets += 1;
+                        result.total_prefixes += prefix_count;
+                        added.push(entry);
+                    }
+                    Err(e) => {
+                        warn!("Failed to index {:?}: {}", entry.path, e);
+                        result.failed_assets += 1;
+                    }
+                }
+            }
+        }
+
+        result.duration = start.elapsed();
+        if result.total_assets > 0 {
+            info!(
+                "Registered {} new assets ({} prefixes) in {:?}",
+                result.indexed_assets, result.total_prefixes, result.duration
+            );
+        }
+        (result, added)
+    }
+
     /// Find an indexer that can handle the given asset
     fn find_indexer(&self, entry: &AssetEntry) -> Option<Arc<dyn AssetIndexer>> {
         self.indexers
@@ -160,13 +228,36 @@ mod tests {
     use super::*;
     use crate::asset::registry::InMemoryRouteRegistry;
     use naviscope_plugin::{AssetSource, BoxError};
-    use std::path::{Path, PathBuf};
+    use std::path::Path;
 
     /// Mock discoverer for testing
     struct MockDiscoverer {
         assets: Vec<AssetEntry>,
     }
 
+    /// Mock discoverer whose assets appear over time under a watched root
+    struct WatchedDiscoverer {
+        assets: Arc<std::sync::Mutex<Vec<AssetEntry>>>,
+    }
+
+    impl AssetDiscoverer for WatchedDiscoverer {
+        fn discover(&self) -> Box<dyn Iterator<Item = AssetEntry> + Send + '_> {
+            Box::new(self.assets.lock().unwrap().clone().into_iter())
+        }
+
+        fn name(&self) -> &str {
+            "Watched Discoverer"
+        }
+
+        fn source_type(&self) -> &str {
+            "mock"
+        }
+
+        fn watch_roots(&self) -> Vec<PathBuf> {
+            vec![PathBuf::from("/cache")]
+        }
+    }
+
     impl AssetDiscoverer for MockDiscoverer {
         fn discover(&self) -> Box<dyn Iterator<Item = AssetEntry> + Send + '_> {
             Box::new(self.assets.iter().cloned())
@@ -245,4 +336,34 @@ mod tests {
         assert_eq!(result.skipped_assets, 1);
         assert_eq!(result.indexed_assets, 0);
     }
+
+    #[test]
+    fn test_scan_new_indexes_only_new_assets() {
+        let jar = |name: &str| AssetEntry::new(PathBuf::from(name), AssetSource::Unknown);
+        let cache = Arc::new(std::sync::Mutex::new(vec![jar("/cache/a.jar")]));
+
+        let scanner = AssetScanner::new()
+            .add_discoverer(Box::new(WatchedDiscoverer {
+                assets: cache.clone(),
+            }))
+            .add_discoverer(Box::new(MockDiscoverer {
+                assets: vec![jar("/jdk/rt.jar")],
+            }))
+            .add_indexer(Arc::new(MockIndexer {
+                prefixes_per_asset: vec!["com.example".to_string()],
+            }));
+        assert_eq!(scanner.watch_roots(), vec![PathBuf::from("/cache")]);
+
+        let registry = InMemoryRouteRegistry::new();
+        scanner.scan(&registry);
+        let (result, added) = scanner.scan_new(&registry);
+        assert_eq!(result.total_assets, 0);
+        assert!(added.is_empty());
+
+        cache.lock().unwrap().push(jar("/cache/b.jar"));
+        let (result, added) = scanner.scan_new(&registry);
+        assert_eq!(result.indexed_assets, 1);
+        assert_eq!(added[0].path, PathBuf::from("/cache/b.jar"));
+        assert_eq!(registry.lookup("com.example.Foo").unwrap().len(), 3);
+    }
 }
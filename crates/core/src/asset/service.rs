@@ -5,6 +5,7 @@
 //! - Stub request handling
 //! - Background scanning
 
+use crate::asset::fetcher::SourceFetcher;
 use crate::asset::registry::InMemoryRouteRegistry;
 use crate::asset::scanner::{AssetScanner, ScanResult};
 use naviscope_plugin::{
@@ -33,6 +34,9 @@ pub struct AssetStubService {
 
     /// Source locators (from language/build plugins)
     source_locators: Vec<Arc<dyn AssetSourceLocator>>,
+
+    /// Remote source fetcher used when no local source artifact exists
+    fetcher: Option<Arc<SourceFetcher>>,
 }
 
 impl AssetStubService {
@@ -53,6 +57,7 @@ impl AssetStubService {
             generators,
             source_map: Arc::new(RwLock::new(HashMap::new())),
             source_locators,
+            fetcher: None,
         }
     }
 
@@ -74,9 +79,16 @@ impl AssetStubService {
             generators,
             source_map: Arc::new(RwLock::new(HashMap::new())),
             source_locators,
+            fetcher: None,
         }
     }
 
+    /// Enable on-demand download of missing source artifacts
+    pub fn with_source_fetcher(mut self, fetcher: Arc<SourceFetcher>) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
     /// Get a reference to the registry
     pub fn registry(&self) -> Arc<InMemoryRouteRegistry> {
         self.registry.clone()
@@ -89,6 +101,33 @@ impl AssetStubService {
         result
     }
 
+    /// Directories whose changes may bring new assets (build tool caches)
+    pub fn watch_roots(&self) -> Vec<PathBuf> {
+        self.scanner.watch_roots()
+    }
+
+    /// Register assets fetched since the last scan without rescanning the
+    /// known ones, and locate sources for them (blocks until complete).
+    pub fn scan_new_assets(&self) -> ScanResult {
+        let (result, added) = self.scanner.scan_new(self.registry.as_ref());
+        if added.is_empty() {
+            return result;
+        }
+        let located: Vec<_> = added
+            .iter()
+            .filter_map(|entry| {
+                self.source_locators
+                    .iter()
+                    .find_map(|locator| locator.locate_source(entry))
+                    .map(|source| (entry.path.clone(), source))
+            })
+            .collect();
+        if let Ok(mut guard) = self.source_map.try_write() {
+            guard.extend(located);
+        }
+        result
+    }
+
     /// Start a background scan task
     pub fn spawn_scan(&self) -> JoinHandle<ScanResult> {
         let registry = self.registry.clone();
@@ -116,6 +155,14 @@ impl AssetStubService {
         self.registry.lookup(fqn)
     }
 
+    /// Name of the asset declaring an FQN, see `AssetEntry::display_name`
+    pub fn artifact_name(&self, fqn: &str) -> Option<String> {
+        self.registry
+            .lookup(fqn)?
+            .first()
+            .map(AssetEntry::display_name)
+    }
+
     /// Lookup asset paths for an FQN (legacy compatibility)
     pub fn lookup_paths(&self, fqn: &str) -> Option<Vec<PathBuf>> {
         self.registry
@@ -131,6 +178,22 @@ impl AssetStubService {
             .and_then(|map| map.get(binary_path).cloned())
     }
 
+    /// Resolve the source asset for a binary asset, downloading it when a fetcher
+    /// is configured and no local source artifact was found.
+    ///
+    /// Downloads block the calling thread; call from a blocking context.
+    pub fn resolve_source(&self, entry: &AssetEntry) -> Option<PathBuf> {
+        if let Some(source) = self.lookup_source(&entry.path) {
+            return Some(source);
+        }
+
+        let source = self.fetcher.as_ref()?.fetch(&entry.source)?;
+        if let Ok(mut map) = self.source_map.try_write() {
+            map.insert(entry.path.clone(), source.clone());
+        }
+        Some(source)
+    }
+
     /// Get a snapshot of all routes (for serialization or passing to resolver)
     pub fn routes_snapshot(&self) -> HashMap<String, Vec<PathBuf>> {
         self.registry
@@ -204,6 +267,7 @@ pub struct AssetStubServiceBuilder {
     generators: Vec<Arc<dyn StubGenerator>>,
     source_locators: Vec<Arc<dyn AssetSourceLocator>>,
     registry: Option<Arc<InMemoryRouteRegistry>>,
+    fetcher: Option<Arc<SourceFetcher>>,
 }
 
 impl AssetStubServiceBuilder {
@@ -214,6 +278,7 @@ impl AssetStubServiceBuilder {
             generators: Vec::new(),
             source_locators: Vec::new(),
             registry: None,
+            fetcher: None,
         }
     }
 
@@ -242,6 +307,11 @@ impl AssetStubServiceBuilder {
         self
     }
 
+    pub fn with_source_fetcher(mut self, fetcher: Arc<SourceFetcher>) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
     pub fn build(self) -> AssetStubService {
         let service = if let Some(registry) = self.registry {
             AssetStubService::with_registry(
@@ -260,7 +330,10 @@ impl AssetStubServiceBuilder {
             )
         };
 
-        service
+        match self.fetcher {
+            Some(fetcher) => service.with_source_fetcher(fetcher),
+            None => service,
+        }
     }
 }
 
@@ -0,0 +1,171 @@
+//! Cross-process claim records
+//!
+//! A claim is a small file created with `create_new`, so exactly one process
+//! can hold it at a time. The file is removed when the guard drops. Claims left
+//! behind by a crashed process are considered stale after a timeout and may be
+//! taken over: the record is first renamed aside, so of the processes that
+//! find it stale only one removes it.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+
+static NEXT_ASIDE: AtomicU64 = AtomicU64::new(0);
+
+/// Held claim; the record is removed on drop
+#[derive(Debug)]
+pub struct ClaimGuard {
+    path: PathBuf,
+}
+
+impl Drop for ClaimGuard {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Try to create the claim record at `path`.
+///
+/// Returns `Ok(None)` when another live process holds the claim.
+pub fn try_claim(path: &Path, stale_after: Duration) -> io::Result<Option<ClaimGuard>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Second attempt only happens after removing a stale record
+    for _ in 0..2 {
+        match OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(mut file) => {
+                let _ = writeln!(file, "{}", std::process::id());
+                return Ok(Some(ClaimGuard {
+                    path: path.to_path_buf(),
+                }));
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                if !is_stale(path, stale_after) || !take_over(path, stale_after)? {
+                    return Ok(None);
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(None)
+}
+
+/// Remove the stale record at `path`, unless it turns out to be live.
+///
+/// The record is renamed aside first, so of the processes that found it
+/// stale only one moves it. Returns `false` if a claim created since the
+/// staleness check was moved, which is put back.
+fn take_over(path: &Path, stale_after: Duration) -> io::Result<bool> {
+    let aside = path.with_extension(format!(
+        "stale.{}.{}",
+        std::process::id(),
+        NEXT_ASIDE.fetch_add(1, Ordering::Relaxed)
+    ));
+    match fs::rename(path, &aside) {
+        Ok(()) => {}
+        // Another process moved it first; the retry races its claim
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(true),
+        Err(e) => return Err(e),
+    }
+    if !is_stale(&aside, stale_after) {
+        // Linking fails rather than replace a claim created meanwhile
+        let _ = fs::hard_link(&aside, path);
+        let _ = fs::remove_file(&aside);
+        return Ok(false);
+    }
+    tracing::debug!("Taking over stale claim {:?}", path);
+    fs::remove_file(&aside)?;
+    Ok(true)
+}
+
+/// Whether a live claim record exists at `path`.
+pub fn is_held(path: &Path, stale_after: Duration) -> bool {
+    path.exists() && !is_stale(path, stale_after)
+}
+
+fn is_stale(path: &Path, stale_after: Duration) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_none_or(|age| age > stale_after)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STALE: Duration = Duration::from_secs(30);
+
+    #[test]
+    fn claim_is_exclusive_until_released() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("claims/a.claim");
+
+        let guard = try_claim(&path, STALE).unwrap().expect("first claim");
+        assert!(is_held(&path, STALE));
+        assert!(try_claim(&path, STALE).unwrap().is_none());
+
+        drop(guard);
+        assert!(!is_held(&path, STALE));
+        assert!(try_claim(&path, STALE).unwrap().is_some());
+    }
+
+    #[test]
+    fn stale_claim_is_taken_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.claim");
+        fs::write(&path, "0\n").unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+
+        assert!(!is_held(&path, Duration::ZERO));
+        assert!(try_claim(&path, Duration::ZERO).unwrap().is_some());
+    }
+
+    #[test]
+    fn takeover_leaves_a_live_claim_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.claim");
+        let guard = try_claim(&path, STALE).unwrap().expect("first claim");
+
+        // As by a process that found the record stale before it was claimed
+        assert!(!take_over(&path, STALE).unwrap());
+        assert!(is_held(&path, STALE));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+        drop(guard);
+    }
+
+    #[test]
+    fn stale_claim_is_taken_over_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.claim");
+        for _ in 0..20 {
+            let file = fs::File::create(&path).unwrap();
+            file.set_modified(SystemTime::now() - 2 * STALE).unwrap();
+            drop(file);
+
+            let barrier = std::sync::Barrier::new(8);
+            let claims = std::thread::scope(|scope| {
+                let handles: Vec<_> = (0..8)
+                    .map(|_| {
+                        scope.spawn(|| {
+                            barrier.wait();
+                            try_claim(&path, STALE).unwrap()
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .filter_map(|handle| handle.join().unwrap())
+                    .collect::<Vec<_>>()
+            });
+            assert_eq!(claims.len(), 1);
+            drop(claims);
+            assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+        }
+    }
+}
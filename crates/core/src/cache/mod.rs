@@ -1,5 +1,6 @@
 //! Caching subsystem
 
+pub mod claim;
 pub mod stub_cache;
 
-pub use stub_cache::{AssetKey, GlobalStubCache};
+pub use stub_cache::{AssetKey, GlobalStubCache, StubClaim};
@@ -5,15 +5,25 @@
 
 use naviscope_plugin::IndexNode;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
-use std::time::SystemTime;
+use std::time::{Duration, Instant, SystemTime};
 use xxhash_rust::xxh3::xxh3_64;
 
+use super::claim::{self, ClaimGuard};
+
 // Note: We use the metadata registry from naviscope_plugin
 
+/// Claims older than this are assumed to belong to a crashed process
+const CLAIM_STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// How long a cache write waits for another process to finish writing the same file
+const WRITE_LOCK_WAIT: Duration = Duration::from_secs(2);
+
+const CLAIM_POLL_INTERVAL: Duration = Duration::from_millis(25);
+
 /// Key identifying an external asset
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct AssetKey {
@@ -128,6 +138,17 @@ impl StubCacheFile {
     }
 }
 
+/// Outcome of claiming a stub for generation
+#[derive(Debug)]
+pub enum StubClaim {
+    /// This process generates the stub; the claim is released on drop
+    Acquired(ClaimGuard),
+    /// Another process is generating the stub
+    Busy,
+    /// Claims cannot be recorded (e.g. read-only cache directory)
+    Unavailable,
+}
+
 /// Global stub cache manager
 pub struct GlobalStubCache {
     base_dir: PathBuf,
@@ -146,8 +167,13 @@ impl StubCacheManager for GlobalStubCache {
         self.scan_assets()
     }
 
-    fn inspect_asset(&self, hash_prefix: &str) -> Option<CacheInspectResult> {
-        self.inspect_asset(hash_prefix)
+    fn inspect_asset(&self, coordinate: &str) -> Option<CacheInspectResult> {
+        self.inspect_asset(coordinate)
+    }
+
+    fn remove_assets(&self, hashes: &[String]) -> ApiResult<Vec<CachedAssetSummary>> {
+        self.remove_assets(hashes)
+            .map_err(|e| ApiError::Internal(e.to_string()))
     }
 
     fn clear(&self) -> ApiResult<()> {
@@ -165,6 +191,16 @@ impl GlobalStubCache {
         }
     }
 
+    /// Directory holding source documents rendered from binary assets
+    pub fn synthetic_source_dir(&self) -> PathBuf {
+        self.base_dir.join("decompiled")
+    }
+
+    /// Directory holding source documents read from source artifacts
+    pub fn extracted_source_dir(&self) -> PathBuf {
+        self.base_dir.join("extracted")
+    }
+
     /// Get the default global cache location
     pub fn default_location() -> PathBuf {
         let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
@@ -181,6 +217,64 @@ impl GlobalStubCache {
         self.base_dir.join(format!("{:016x}.stubs", asset_hash))
     }
 
+    /// Claim record path for a single stub key
+    fn claim_path(&self, asset: &AssetKey, fqn: &str) -> PathBuf {
+        self.base_dir.join("claims").join(format!(
+            "{:016x}-{:016x}.claim",
+            asset.hash(),
+            xxh3_64(fqn.as_bytes())
+        ))
+    }
+
+    /// Claim generation of the stub `fqn` from `asset`.
+    ///
+    /// Claims are shared between processes using the same cache directory, so
+    /// only one of them generates a given stub at a time.
+    pub fn claim(&self, asset: &AssetKey, fqn: &str) -> StubClaim {
+        match claim::try_claim(&self.claim_path(asset, fqn), CLAIM_STALE_AFTER) {
+            Ok(Some(guard)) => StubClaim::Acquired(guard),
+            Ok(None) => StubClaim::Busy,
+            Err(e) => {
+                tracing::debug!("Cannot record stub claim for {}: {}", fqn, e);
+                StubClaim::Unavailable
+            }
+        }
+    }
+
+    /// Wait until another process releases its claim on `fqn`, then return the stub
+    /// it stored. Returns `None` if the stub is still missing after `timeout`.
+    pub fn wait_for(&self, asset: &AssetKey, fqn: &str, timeout: Duration) -> Option<IndexNode> {
+        let claim_path = self.claim_path(asset, fqn);
+        let deadline = Instant::now() + timeout;
+
+        while claim::is_held(&claim_path, CLAIM_STALE_AFTER) && Instant::now() < deadline {
+            std::thread::sleep(CLAIM_POLL_INTERVAL);
+        }
+
+        self.reload(asset);
+        self.lookup(asset, fqn)
+    }
+
+    /// Merge entries written by other processes into the in-memory cache
+    fn reload(&self, asset: &AssetKey) {
+        let hash = asset.hash();
+        let Some(on_disk) = self.read_cache_file(hash) else {
+            return;
+        };
+        let cache = self.get_or_create_cache(asset);
+        let mut cache = cache.write().unwrap();
+        for (fqn, entry) in on_disk.entries {
+            cache.entries.entry(fqn).or_insert(entry);
+        }
+    }
+
+    fn read_cache_file(&self, hash: u64) -> Option<StubCacheFile> {
+        let bytes = fs::read(self.cache_path(hash)).ok()?;
+        rmp_serde::from_slice::<StubCacheFile>(&bytes)
+            .ok()
+            .filter(|file| file.asset_hash == hash)
+    }
+
     /// Load or create cache for an asset
     fn get_or_create_cache(&self, asset: &AssetKey) -> Arc<RwLock<StubCacheFile>> {
         let hash = asset.hash();
@@ -194,18 +288,9 @@ impl GlobalStubCache {
         }
 
         // Try to load from disk
-        let cache_path = self.cache_path(hash);
-        let cache = if cache_path.exists() {
-            match fs::read(&cache_path) {
-                Ok(bytes) => match rmp_serde::from_slice::<StubCacheFile>(&bytes) {
-                    Ok(file) if file.asset_hash == hash => file,
-                    _ => StubCacheFile::new(asset),
-                },
-                Err(_) => StubCacheFile::new(asset),
-            }
-        } else {
-            StubCacheFile::new(asset)
-        };
+        let cache = self
+            .read_cache_file(hash)
+            .unwrap_or_else(|| StubCacheFile::new(asset));
 
         let cache = Arc::new(RwLock::new(cache));
 
@@ -227,7 +312,10 @@ impl GlobalStubCache {
     }
 
     /// Store a stub in the cache
-    pub fn store(&self, asset: &AssetKey, stub: &IndexNode) {
+    ///
+    /// The stub is kept in memory even when persisting it fails, e.g. because
+    /// another process held the file too long; storing again retries the write.
+    pub fn store(&self, asset: &AssetKey, stub: &IndexNode) -> std::io::Result<()> {
         let fqn = match &stub.id {
             naviscope_api::models::symbol::NodeId::Flat(s) => s.clone(),
             naviscope_api::models::symbol::NodeId::Structured(s) => format!("{:?}", s),
@@ -240,22 +328,51 @@ impl GlobalStubCache {
         }
 
         // Persist to disk
-        self.save_cache(asset);
+        self.save_cache(asset)
     }
 
     /// Save cache to disk
-    fn save_cache(&self, asset: &AssetKey) {
+    ///
+    /// Writers in other processes are serialized through a per-file claim, and
+    /// entries they stored meanwhile are merged in before the file is replaced.
+    fn save_cache(&self, asset: &AssetKey) -> std::io::Result<()> {
         let hash = asset.hash();
-        let loaded = self.loaded.read().unwrap();
+        let cache_path = self.cache_path(hash);
+        let lock_path = cache_path.with_extension("stubs.lock");
+
+        let deadline = Instant::now() + WRITE_LOCK_WAIT;
+        let _guard = loop {
+            match claim::try_claim(&lock_path, CLAIM_STALE_AFTER) {
+                Ok(Some(guard)) => break Some(guard),
+                Ok(None) if Instant::now() < deadline => std::thread::sleep(CLAIM_POLL_INTERVAL),
+                Ok(None) => {
+                    tracing::warn!("Timed out waiting to write stub cache {:?}", cache_path);
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::TimedOut,
+                        format!(
+                            "stub cache {} is held by another writer",
+                            cache_path.display()
+                        ),
+                    ));
+                }
+                // Unable to lock (e.g. read-only directory): fall back to a plain write
+                Err(_) => break None,
+            }
+        };
 
+        self.reload(asset);
+
+        let loaded = self.loaded.read().unwrap();
         if let Some(cache) = loaded.get(&hash) {
             let cache = cache.read().unwrap();
-            let cache_path = self.cache_path(hash);
 
-            if let Ok(bytes) = rmp_serde::to_vec(&*cache) {
-                let _ = fs::write(cache_path, bytes);
-            }
+            let bytes = rmp_serde::to_vec(&*cache).map_err(std::io::Error::other)?;
+            // Replace atomically so concurrent readers never see a partial file
+            let partial = cache_path.with_extension("stubs.part");
+            fs::write(&partial, bytes)?;
+            fs::rename(&partial, &cache_path)?;
         }
+        Ok(())
     }
 
     /// Clear all cached data
@@ -302,11 +419,13 @@ impl GlobalStubCache {
                         // Optimization: Define a Header-only struct for rmp-serde if needed.
                         if let Ok(bytes) = fs::read(&path) {
                             if let Ok(file) = rmp_serde::from_slice::<StubCacheFile>(&bytes) {
+                                let package_count = packages(&file).len();
                                 summaries.push(CachedAssetSummary {
                                     hash: format!("{:016x}", file.asset_hash),
                                     path: file.asset_path,
                                     size_bytes: metadata.len(),
                                     stub_count: file.entries.len(),
+                                    package_count,
                                     version: file.version,
                                     created_at: file.created_at,
                                 });
@@ -320,10 +439,10 @@ impl GlobalStubCache {
         summaries
     }
 
-    /// Inspect a specific cached asset by hash (full or prefix)
-    pub fn inspect_asset(&self, hash_prefix: &str) -> Option<CacheInspectResult> {
+    /// Inspect the first cached asset `coordinate` matches
+    pub fn inspect_asset(&self, coordinate: &str) -> Option<CacheInspectResult> {
         let summaries = self.scan_assets();
-        let target = summaries.iter().find(|s| s.hash.starts_with(hash_prefix))?;
+        let target = summaries.iter().find(|s| s.matches(coordinate))?;
 
         // Convert hex string back to u64 for file path lookup
         let hash = u64::from_str_radix(&target.hash, 16).ok()?;
@@ -341,17 +460,20 @@ impl GlobalStubCache {
                     }
                 }
 
+                let packages = packages(&file);
                 return Some(CacheInspectResult {
                     summary: CachedAssetSummary {
                         hash: target.hash.clone(),
                         path: file.asset_path,
                         size_bytes: target.size_bytes,
                         stub_count: file.entries.len(),
+                        package_count: packages.len(),
                         version: file.version,
                         created_at: file.created_at,
                     },
                     metadata_distribution: distro,
                     sample_entries: samples,
+                    packages,
                 });
             }
         }
@@ -359,6 +481,23 @@ impl GlobalStubCache {
         None
     }
 
+    /// Remove the cached assets with the given hashes, returning those removed
+    pub fn remove_assets(&self, hashes: &[String]) -> std::io::Result<Vec<CachedAssetSummary>> {
+        let mut removed = Vec::new();
+        for summary in self.scan_assets() {
+            if !hashes.contains(&summary.hash) {
+                continue;
+            }
+            let Ok(hash) = u64::from_str_radix(&summary.hash, 16) else {
+                continue;
+            };
+            self.loaded.write().unwrap().remove(&hash);
+            fs::remove_file(self.cache_path(hash))?;
+            removed.push(summary);
+        }
+        Ok(removed)
+    }
+
     /// Get cache statistics
     pub fn stats(&self) -> CacheStats {
         let summaries = self.scan_assets();
@@ -372,3 +511,21 @@ impl GlobalStubCache {
         }
     }
 }
+
+/// Stubs per package of `file`. The package of a stub is the leading
+/// lowercase segments of its type's name, e.g. `java.util` for
+/// `java.util.Map.Entry`.
+fn packages(file: &StubCacheFile) -> BTreeMap<String, usize> {
+    let mut packages = BTreeMap::new();
+    for fqn in file.entries.keys() {
+        let type_name = fqn.split('#').next().unwrap_or(fqn);
+        let package: Vec<&str> = type_name
+            .split('.')
+            .take_while(|segment| segment.starts_with(|c: char| c.is_lowercase()))
+            .collect();
+        if !package.is_empty() {
+            *packages.entry(package.join(".")).or_insert(0) += 1;
+        }
+    }
+    packages
+}
@@ -0,0 +1,649 @@
+//! Project-level configuration loaded from `naviscope.toml`.
+
+use crate::error::{NaviscopeError, Result};
+use crate::features::query::QueryBudget;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use naviscope_api::lifecycle::ToolDefaults;
+use naviscope_api::models::DetailLimits;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const CONFIG_FILE_NAME: &str = "naviscope.toml";
+
+/// Settings read from `<project_root>/naviscope.toml`. Missing sections use defaults.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ProjectConfig {
+    pub sources: SourcesConfig,
+    pub assets: AssetsConfig,
+    pub index: IndexConfig,
+    pub plugins: PluginsConfig,
+    pub jdk: JdkConfig,
+    pub memory: MemoryConfig,
+    pub mcp: McpConfig,
+    pub profiles: ProfilesConfig,
+    pub detail: DetailConfig,
+    pub query: QueryConfig,
+    pub advisories: AdvisoriesConfig,
+    pub git: GitConfig,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SourcesConfig {
+    /// Source roots relative to the project root. When set, they replace detected roots.
+    pub roots: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AssetsConfig {
+    /// Download missing `-sources.jar` artifacts from `repositories` on demand.
+    pub download_sources: bool,
+    /// Remote Maven repositories, tried in order.
+    pub repositories: Vec<String>,
+}
+
+impl Default for AssetsConfig {
+    fn default() -> Self {
+        Self {
+            download_sources: false,
+            repositories: vec![crate::asset::fetcher::MAVEN_CENTRAL.to_string()],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct IndexConfig {
+    /// Directory holding the index file. Relative paths are resolved against the
+    /// project root; by default indices live under `~/.naviscope/indices`.
+    pub dir: Option<PathBuf>,
+    /// Gitignore-style globs, relative to the project root, excluded from indexing.
+    pub ignore: Vec<String>,
+    /// Where to stream committed node changes as NDJSON: a file path (relative
+    /// to the project root), `tcp://host:port` or `unix:///path/to/socket`.
+    pub op_log: Option<String>,
+    /// Threads scanning, parsing and resolving sources at most, whatever the
+    /// profile asks for.
+    pub max_parallelism: Option<usize>,
+}
+
+impl IndexConfig {
+    /// Matcher for `ignore`, anchored at `project_root`.
+    pub fn ignore_matcher(&self, project_root: &Path) -> Result<Gitignore> {
+        let mut builder = GitignoreBuilder::new(project_root);
+        for glob in &self.ignore {
+            builder
+                .add_line(None, glob)
+                .map_err(|e| NaviscopeError::Parsing(format!("index.ignore: {}", e)))?;
+        }
+        builder
+            .build()
+            .map_err(|e| NaviscopeError::Parsing(format!("index.ignore: {}", e)))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PluginsConfig {
+    /// Language and build tool plugins to load, by name (e.g. `java`, `gradle`).
+    /// All registered plugins are loaded when unset.
+    pub enabled: Option<Vec<String>>,
+}
+
+impl PluginsConfig {
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.enabled
+            .as_ref()
+            .is_none_or(|enabled| enabled.iter().any(|n| n.eq_ignore_ascii_case(name)))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct JdkConfig {
+    /// JDK installation used for the standard library, instead of auto-detection.
+    pub home: Option<PathBuf>,
+    /// Java version to pick among the installed JDKs, instead of the one the
+    /// build declares (e.g. `"17"`).
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct MemoryConfig {
+    /// Parsed files kept in memory while collecting declarations.
+    pub collect_cache_limit: Option<usize>,
+    /// Parsed files kept in memory while resolving references.
+    pub analyze_cache_limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct McpConfig {
+    /// Port for the MCP server started alongside the LSP server; a free port is
+    /// picked when unset.
+    pub port: Option<u16>,
+    /// Address the MCP server binds to; loopback when unset.
+    pub host: Option<IpAddr>,
+    /// Bearer token MCP clients must present; a token is generated for each
+    /// server and recorded in its session file when unset.
+    pub token: Option<String>,
+    /// Argument defaults per tool (`[mcp.tools.find]`, ...), overridden by
+    /// the arguments of each request.
+    pub tools: BTreeMap<String, ToolDefaults>,
+}
+
+/// Size limits of the node details shown by hovers (`[detail.lsp]`) and MCP
+/// tools (`[detail.mcp]`)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DetailConfig {
+    pub lsp: DetailLimitsConfig,
+    pub mcp: DetailLimitsConfig,
+}
+
+/// Unset limits keep the client's built-in ones.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DetailLimitsConfig {
+    /// Characters of a signature before its generic arguments are elided.
+    pub signature_chars: Option<usize>,
+    /// Members listed before the others are only counted.
+    pub members: Option<usize>,
+}
+
+impl DetailLimitsConfig {
+    pub fn over(&self, defaults: DetailLimits) -> DetailLimits {
+        DetailLimits {
+            signature_chars: self.signature_chars.unwrap_or(defaults.signature_chars),
+            members: self.members.unwrap_or(defaults.members),
+        }
+    }
+}
+
+/// Limits of graph queries, so that a query such as the dependents of
+/// `java.lang.Object` returns part of its answer instead of stalling the
+/// client. 0 lifts a limit.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct QueryConfig {
+    /// Time after which a query stops with what it found.
+    pub timeout_ms: Option<u64>,
+    /// Nodes, edges or text hits returned by a query at most.
+    pub max_results: Option<usize>,
+    /// Entry points of the `dead` query besides tests, `main` and framework
+    /// callbacks: `@Name` for an annotation, a plain name for a method.
+    pub entry_points: Vec<String>,
+}
+
+impl QueryConfig {
+    pub fn budget(&self) -> QueryBudget {
+        QueryBudget {
+            timeout: Some(self.timeout_ms.unwrap_or(10_000))
+                .filter(|ms| *ms > 0)
+                .map(Duration::from_millis),
+            max_results: Some(self.max_results.unwrap_or(2_000)).filter(|max| *max > 0),
+        }
+    }
+}
+
+/// Offline vulnerability and license data attached to dependency nodes
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AdvisoriesConfig {
+    /// OSV advisories (files, or directories of them) and SPDX JSON
+    /// documents, relative to the project root.
+    pub files: Vec<PathBuf>,
+}
+
+/// Git integration
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct GitConfig {
+    /// Show the last change of a node in `cat` and rank hot spots by churn
+    /// in `stats`, running `git log` in the project's repository.
+    pub history: bool,
+}
+
+/// Component an engine is built for; selects its `[profiles.*]` section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Profile {
+    /// Batch commands such as `naviscope index`: use the whole machine
+    #[default]
+    Cli,
+    /// Engine behind an editor: index in the background with fewer threads
+    Lsp,
+    /// Engine serving an MCP server on its own
+    Mcp,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ProfilesConfig {
+    pub cli: ProfileConfig,
+    pub lsp: ProfileConfig,
+    pub mcp: ProfileConfig,
+}
+
+/// Indexing tuning for one component. Unset cache limits fall back to `[memory]`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ProfileConfig {
+    /// Threads scanning, parsing and resolving sources.
+    pub threads: Option<usize>,
+    /// Parsed files kept in memory while collecting declarations.
+    pub collect_cache_limit: Option<usize>,
+    /// Parsed files kept in memory while resolving references.
+    pub analyze_cache_limit: Option<usize>,
+    /// Generate stubs for referenced library types while indexing, rather than
+    /// only when a request needs them.
+    pub prefetch_stubs: Option<bool>,
+    /// Library stub batches a build generates at once.
+    pub stub_bulk_slots: Option<usize>,
+    /// Stubs generated at once for requests waiting on a single symbol
+    /// (hovers, goto, agent queries), on top of the bulk slots.
+    pub stub_interactive_slots: Option<usize>,
+    /// Bounds of the number of changed files the watcher applies per update.
+    pub watch_batch_min: Option<usize>,
+    pub watch_batch_max: Option<usize>,
+    /// Update latency the watcher's batch size is adapted to.
+    pub watch_target_latency_ms: Option<u64>,
+}
+
+/// Effective indexing settings of a profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tuning {
+    pub threads: usize,
+    pub collect_cache_limit: usize,
+    pub analyze_cache_limit: usize,
+    pub prefetch_stubs: bool,
+    pub stub_bulk_slots: usize,
+    pub stub_interactive_slots: usize,
+    pub watch_batch_min: usize,
+    pub watch_batch_max: usize,
+    pub watch_target_latency: Duration,
+}
+
+impl ProjectConfig {
+    /// Settings for `profile`: its section, then `[memory]`, then built-in defaults.
+    pub fn tuning(&self, profile: Profile) -> Tuning {
+        let cores = std::thread::available_parallelism().map_or(4, usize::from);
+        let (section, default_threads) = match profile {
+            Profile::Cli => (&self.profiles.cli, cores),
+            Profile::Lsp => (&self.profiles.lsp, (cores / 2).max(1)),
+            Profile::Mcp => (&self.profiles.mcp, (cores / 2).max(1)),
+        };
+        let positive = |v: Option<usize>| v.filter(|v| *v > 0);
+        Tuning {
+            threads: positive(section.threads)
+                .unwrap_or(default_threads)
+                .min(positive(self.index.max_parallelism).unwrap_or(usize::MAX)),
+            collect_cache_limit: positive(section.collect_cache_limit)
+                .or(positive(self.memory.collect_cache_limit))
+                .unwrap_or(512),
+            analyze_cache_limit: positive(section.analyze_cache_limit)
+                .or(positive(self.memory.analyze_cache_limit))
+                .unwrap_or(512),
+            prefetch_stubs: section.prefetch_stubs.unwrap_or(true),
+            stub_bulk_slots: positive(section.stub_bulk_slots).unwrap_or(1),
+            stub_interactive_slots: positive(section.stub_interactive_slots).unwrap_or(2),
+            watch_batch_min: positive(section.watch_batch_min).unwrap_or(16),
+            watch_batch_max: positive(section.watch_batch_max).unwrap_or(1024),
+            watch_target_latency: Duration::from_millis(
+                section
+                    .watch_target_latency_ms
+                    .filter(|v| *v > 0)
+                    .unwrap_or(500),
+            ),
+        }
+    }
+
+    /// Load the configuration for a project, falling back to defaults when no file exists.
+    pub fn load(project_root: &Path) -> Result<Self> {
+        let path = project_root.join(CONFIG_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        Self::parse(&content)
+            .map_err(|e| NaviscopeError::Parsing(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn parse(content: &str) -> std::result::Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    /// Sections that differ from `other` and only take effect when the engine
+    /// is rebuilt; the others apply to the next indexing run.
+    pub fn restart_required(&self, other: &Self) -> Vec<&'static str> {
+        let mut sections = Vec::new();
+        if self.assets != other.assets {
+            sections.push("assets");
+        }
+        if self.index.dir != other.index.dir {
+            sections.push("index.dir");
+        }
+        if self.index.op_log != other.index.op_log {
+            sections.push("index.op_log");
+        }
+        if self.plugins != other.plugins {
+            sections.push("plugins");
+        }
+        if self.jdk != other.jdk {
+            sections.push("jdk");
+        }
+        if self.memory != other.memory {
+            sections.push("memory");
+        }
+        if self.mcp.port != other.mcp.port {
+            sections.push("mcp.port");
+        }
+        if self.mcp.host != other.mcp.host {
+            sections.push("mcp.host");
+        }
+        if self.mcp.token != other.mcp.token {
+            sections.push("mcp.token");
+        }
+        if self.profiles != other.profiles {
+            sections.push("profiles");
+        }
+        if self.advisories != other.advisories {
+            sections.push("advisories");
+        }
+        if self.git != other.git {
+            sections.push("git");
+        }
+        sections
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use naviscope_api::models::graph::{NodeSource, ResolutionStatus};
+    use naviscope_api::models::{EdgeType, NodeKind};
+
+    #[test]
+    fn parse_empty_config_uses_defaults() {
+        let config = ProjectConfig::parse("").unwrap();
+        assert!(config.sources.roots.is_empty());
+        assert!(!config.assets.download_sources);
+    }
+
+    #[test]
+    fn parse_asset_download_settings() {
+        let config = ProjectConfig::parse(
+            r#"
+[assets]
+download_sources = true
+repositories = ["https://repo.example.com/maven2"]
+"#,
+        )
+        .unwrap();
+        assert!(config.assets.download_sources);
+        assert_eq!(
+            config.assets.repositories,
+            vec!["https://repo.example.com/maven2".to_string()]
+        );
+    }
+
+    #[test]
+    fn parse_source_roots() {
+        let config = ProjectConfig::parse(
+            r#"
+[sources]
+roots = ["src", "generated/java"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.sources.roots,
+            vec![PathBuf::from("src"), PathBuf::from("generated/java")]
+        );
+    }
+
+    #[test]
+    fn parse_engine_settings() {
+        let config = ProjectConfig::parse(
+            r#"
+[index]
+dir = ".naviscope"
+ignore = ["generated/**"]
+
+[plugins]
+enabled = ["java"]
+
+[jdk]
+home = "/opt/jdk-21"
+version = "21"
+
+[memory]
+analyze_cache_limit = 128
+
+[mcp]
+port = 7411
+host = "0.0.0.0"
+token = "s3cret"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.index.dir, Some(PathBuf::from(".naviscope")));
+        assert!(config.plugins.is_enabled("Java"));
+        assert!(!config.plugins.is_enabled("gradle"));
+        assert_eq!(config.jdk.home, Some(PathBuf::from("/opt/jdk-21")));
+        assert_eq!(config.jdk.version.as_deref(), Some("21"));
+        assert_eq!(config.memory.collect_cache_limit, None);
+        assert_eq!(config.memory.analyze_cache_limit, Some(128));
+        assert_eq!(config.mcp.port, Some(7411));
+        assert_eq!(config.mcp.host, Some(IpAddr::from([0, 0, 0, 0])));
+        assert_eq!(config.mcp.token.as_deref(), Some("s3cret"));
+        assert!(ProjectConfig::default().plugins.is_enabled("gradle"));
+        assert_eq!(
+            config.restart_required(&ProjectConfig::default()),
+            vec![
+                "index.dir",
+                "plugins",
+                "jdk",
+                "memory",
+                "mcp.port",
+                "mcp.host",
+                "mcp.token"
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_mcp_tool_defaults() {
+        let config = ProjectConfig::parse(
+            r#"
+[mcp.tools.find]
+kind = ["class", "interface", "method"]
+sources = ["Project"]
+statuses = ["resolved", "partial_parse"]
+limit = 50
+
+[mcp.tools.deps]
+edge_type = ["InheritsFrom", "Implements"]
+"#,
+        )
+        .unwrap();
+        let find = &config.mcp.tools["find"];
+        assert_eq!(
+            find.kind,
+            Some(vec![NodeKind::Class, NodeKind::Interface, NodeKind::Method])
+        );
+        assert_eq!(find.limit, Some(50));
+        assert_eq!(find.sources, Some(vec![NodeSource::Project]));
+        assert_eq!(
+            find.statuses,
+            Some(vec![
+                ResolutionStatus::Resolved,
+                ResolutionStatus::PartialParse
+            ])
+        );
+        assert_eq!(
+            config.mcp.tools["deps"].edge_type,
+            Some(vec![EdgeType::InheritsFrom, EdgeType::Implements])
+        );
+        // Read per request, so no restart is needed
+        assert!(
+            config
+                .restart_required(&ProjectConfig::default())
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn detail_limits_override_the_client_defaults() {
+        let config = ProjectConfig::parse(
+            r#"
+[detail.lsp]
+members = 5
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.detail.lsp.over(DetailLimits::LSP),
+            DetailLimits {
+                signature_chars: DetailLimits::LSP.signature_chars,
+                members: 5,
+            }
+        );
+        assert_eq!(config.detail.mcp.over(DetailLimits::MCP), DetailLimits::MCP);
+        assert!(
+            config
+                .restart_required(&ProjectConfig::default())
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn query_budget_has_defaults_and_zero_lifts_a_limit() {
+        let budget = ProjectConfig::default().query.budget();
+        assert_eq!(budget.timeout, Some(Duration::from_secs(10)));
+        assert_eq!(budget.max_results, Some(2_000));
+
+        let config = ProjectConfig::parse(
+            r#"
+[query]
+timeout_ms = 0
+max_results = 300
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.query.budget(),
+            QueryBudget {
+                timeout: None,
+                max_results: Some(300),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_advisories() {
+        let config = ProjectConfig::parse(
+            r#"
+[advisories]
+files = ["osv", "build/sbom.spdx.json"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.advisories.files,
+            vec![PathBuf::from("osv"), PathBuf::from("build/sbom.spdx.json")]
+        );
+    }
+
+    #[test]
+    fn parse_git() {
+        assert!(!ProjectConfig::parse("").unwrap().git.history);
+        let config = ProjectConfig::parse("[git]\nhistory = true\n").unwrap();
+        assert!(config.git.history);
+    }
+
+    #[test]
+    fn profile_tuning_falls_back_to_memory_and_defaults() {
+        let config = ProjectConfig::parse(
+            r#"
+[memory]
+collect_cache_limit = 256
+
+[profiles.lsp]
+threads = 1
+analyze_cache_limit = 64
+prefetch_stubs = false
+stub_interactive_slots = 4
+watch_batch_max = 128
+watch_target_latency_ms = 250
+"#,
+        )
+        .unwrap();
+        let lsp = config.tuning(Profile::Lsp);
+        assert_eq!(lsp.threads, 1);
+        assert_eq!(lsp.collect_cache_limit, 256);
+        assert_eq!(lsp.analyze_cache_limit, 64);
+        assert!(!lsp.prefetch_stubs);
+        assert_eq!((lsp.stub_bulk_slots, lsp.stub_interactive_slots), (1, 4));
+        assert_eq!(lsp.watch_batch_min, 16);
+        assert_eq!(lsp.watch_batch_max, 128);
+        assert_eq!(lsp.watch_target_latency, Duration::from_millis(250));
+
+        let cli = config.tuning(Profile::Cli);
+        assert_eq!(cli.analyze_cache_limit, 512);
+        assert!(cli.prefetch_stubs);
+        assert!(cli.threads >= config.tuning(Profile::Mcp).threads);
+    }
+
+    #[test]
+    fn max_parallelism_caps_the_threads_of_every_profile() {
+        let config = ProjectConfig::parse(
+            r#"
+[index]
+max_parallelism = 2
+
+[profiles.lsp]
+threads = 1
+
+[profiles.cli]
+threads = 8
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.tuning(Profile::Lsp).threads, 1);
+        assert_eq!(config.tuning(Profile::Cli).threads, 2);
+        assert!(config.tuning(Profile::Mcp).threads <= 2);
+    }
+
+    #[test]
+    fn ignore_matcher_matches_globs_under_root() {
+        let root = Path::new("/ws");
+        let index = IndexConfig {
+            ignore: vec!["generated/**".to_string(), "*.gen.java".to_string()],
+            ..Default::default()
+        };
+        let matcher = index.ignore_matcher(root).unwrap();
+        let ignored = |p: &str| {
+            matcher
+                .matched_path_or_any_parents(Path::new(p), false)
+                .is_ignore()
+        };
+        assert!(ignored("/ws/generated/src/A.java"));
+        assert!(ignored("/ws/src/B.gen.java"));
+        assert!(!ignored("/ws/src/B.java"));
+    }
+
+    #[test]
+    fn load_missing_file_returns_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = ProjectConfig::load(dir.path()).unwrap();
+        assert!(config.sources.roots.is_empty());
+    }
+}
@@ -12,6 +12,10 @@ pub enum NaviscopeError {
     Internal(String),
     #[error("Plugin error: {0}")]
     Plugin(String),
+    #[error("Cancelled")]
+    Cancelled,
+    #[error("The index at {} is opened read-only", .0.display())]
+    ReadOnly(std::path::PathBuf),
     #[error("Unknown error")]
     Unknown,
 }
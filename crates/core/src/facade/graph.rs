@@ -1,47 +1,486 @@
 use super::EngineHandle;
 use crate::error::NaviscopeError;
+use crate::features::CodeGraphLike;
+use crate::features::export::{LSIF_FILE, LsifSymbol, LsifWriter};
+use crate::features::external_usage::ExternalUsageAnalyzer;
 use crate::features::query::QueryEngine;
+use crate::features::safe_delete::{self, SafeDeleteAnalyzer};
 use async_trait::async_trait;
+use naviscope_api::graph::GraphService as _;
+use naviscope_api::semantic::ReferenceAnalyzer;
 use naviscope_api::{ApiError, ApiResult, graph, models};
+use std::collections::HashSet;
 
 #[async_trait]
 impl graph::GraphService for EngineHandle {
     async fn query(&self, query: &models::GraphQuery) -> ApiResult<models::QueryResult> {
-        let graph = self.graph().await;
+        self.query_with_control(query, &graph::QueryControl::default())
+            .await
+    }
+
+    async fn query_with_control(
+        &self,
+        query: &models::GraphQuery,
+        control: &graph::QueryControl,
+    ) -> ApiResult<models::QueryResult> {
+        // Lookups of one symbol need only the shards around it
+        let graph = match query {
+            models::GraphQuery::Cat { fqn, .. } | models::GraphQuery::Deps { fqn, .. } => {
+                self.engine.snapshot_for(std::slice::from_ref(fqn)).await
+            }
+            _ => self.graph().await,
+        };
         let query_clone = query.clone();
+        let control = control.clone();
         let handle = self.clone();
 
-        let result = tokio::task::spawn_blocking(
+        let mut result = tokio::task::spawn_blocking(
             move || -> Result<crate::features::query::QueryResult, NaviscopeError> {
                 let conventions = (*handle.naming_conventions()).clone();
-                let engine =
-                    QueryEngine::new(&graph, |lang| handle.get_node_presenter(lang), conventions);
+                let popularity = handle.engine.popularity().unwrap_or_else(|e| {
+                    tracing::warn!("Failed to read access counts: {}", e);
+                    Default::default()
+                });
+                let config = handle.engine.config();
+                let mut engine =
+                    QueryEngine::new(&graph, |lang| handle.get_node_presenter(lang), conventions)
+                        .with_control(control)
+                        .with_popularity(popularity)
+                        .with_advisories(handle.engine.advisories())
+                        .with_entry_points(config.query.entry_points.clone())
+                        .with_budget(config.query.budget());
+                if let Some(history) = handle.engine.history() {
+                    engine = engine.with_history(history);
+                }
                 engine.execute(&query_clone)
             },
         )
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?
-        .map_err(|e| ApiError::Internal(e.to_string()))?;
+        .map_err(|e| match e {
+            NaviscopeError::Cancelled => ApiError::Cancelled,
+            other => ApiError::Internal(other.to_string()),
+        })?;
 
-        Ok(models::QueryResult {
-            nodes: result.nodes,
-            edges: result.edges,
-        })
+        if let (models::GraphQuery::Cat { .. }, Some(node)) = (query, result.nodes.first()) {
+            result.notes = self.notes(&node.id).await?;
+            self.record_hits(vec![node.id.clone()]).await;
+        }
+        Ok(result)
     }
 
     async fn get_stats(&self) -> ApiResult<graph::GraphStats> {
         let graph = self.graph().await;
+        let source_roots = self
+            .engine
+            .source_roots()
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
         Ok(graph::GraphStats {
             node_count: graph.topology().node_count(),
             edge_count: graph.topology().edge_count(),
+            source_roots,
+            toolchains: self.engine.toolchains().to_vec(),
         })
     }
 
+    async fn validate_query(
+        &self,
+        query: &serde_json::Value,
+    ) -> ApiResult<models::QueryValidation> {
+        let mut validation = models::validate_query(query);
+        let fqns = match &validation.query {
+            Some(models::GraphQuery::Ls { fqn: Some(fqn), .. })
+            | Some(models::GraphQuery::Cat { fqn, .. })
+            | Some(models::GraphQuery::Deps { fqn, .. })
+            | Some(models::GraphQuery::Impact { fqn, .. })
+            | Some(models::GraphQuery::TestsFor { fqn }) => vec![("fqn", fqn.clone())],
+            Some(models::GraphQuery::Cycles {
+                scope_fqn: Some(fqn),
+                ..
+            })
+            | Some(models::GraphQuery::Dead {
+                scope_fqn: Some(fqn),
+                ..
+            }) => vec![("scope_fqn", fqn.clone())],
+            Some(models::GraphQuery::Path { from, to, .. }) => {
+                vec![("from", from.clone()), ("to", to.clone())]
+            }
+            _ => return Ok(validation),
+        };
+        let names: Vec<String> = fqns.iter().map(|(_, fqn)| fqn.clone()).collect();
+        let graph = self.engine.snapshot_for(&names).await;
+        for (field, fqn) in fqns {
+            if graph.find_node(&fqn).is_some() {
+                continue;
+            }
+            // Suggest the best fuzzy match for the simple name
+            let name = fqn.split('(').next().unwrap_or(&fqn);
+            let name = name.rsplit(['.', '#']).next().unwrap_or(name);
+            let similar = self
+                .query(&models::GraphQuery::Fuzzy {
+                    pattern: name.to_string(),
+                    kind: Vec::new(),
+                    sources: Vec::new(),
+                    statuses: Vec::new(),
+                    limit: 1,
+                })
+                .await?;
+            validation.push(
+                field,
+                format!("no symbol `{}` in the index", fqn),
+                similar.nodes.into_iter().next().map(|node| node.id),
+            );
+        }
+        Ok(validation)
+    }
+
+    async fn graph_epoch(&self) -> ApiResult<u64> {
+        Ok(self.graph().await.instance_id())
+    }
+
+    async fn wait_for_commit(&self, epoch: u64) -> ApiResult<Option<u64>> {
+        Ok(Some(self.engine.wait_for_commit(epoch).await))
+    }
+
     async fn get_node_display(&self, fqn: &str) -> ApiResult<Option<models::DisplayGraphNode>> {
         let query = models::GraphQuery::Cat {
             fqn: fqn.to_string(),
+            limits: None,
         };
         let result = self.query(&query).await?;
         Ok(result.nodes.into_iter().next())
     }
+
+    async fn safe_delete(&self, fqn: &str) -> ApiResult<models::SafeDeleteReport> {
+        let graph = self.graph().await;
+        let Some(target) = graph.find_node(fqn) else {
+            return Err(ApiError::NotFound(fqn.to_string()));
+        };
+        let language = graph.topology()[target].language(graph.symbols());
+
+        let analysis_graph = graph.clone();
+        let conventions = (*self.naming_conventions()).clone();
+        let fqn_owned = fqn.to_string();
+        let mut report = tokio::task::spawn_blocking(move || {
+            SafeDeleteAnalyzer::new(&analysis_graph, conventions).analyze(&fqn_owned)
+        })
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+        // Name matches are only hints; let the language resolver confirm them
+        let has_text_matches = report
+            .usages
+            .iter()
+            .any(|u| u.kind == models::UsageKind::TextMatch);
+        if !has_text_matches || self.get_semantic_resolver(language.clone()).is_none() {
+            return Ok(report);
+        }
+
+        let query = models::ReferenceQuery {
+            resolution: models::SymbolResolution::Global(fqn.to_string()),
+            language,
+            include_declaration: false,
+            type_usages: models::TypeUsageFilter::Include,
+        };
+        let locations = self.find_references(&query).await?;
+        let conventions = self.naming_conventions();
+        report
+            .usages
+            .retain(|u| u.kind != models::UsageKind::TextMatch);
+        let mut seen: HashSet<String> = report.usages.iter().map(|u| u.from.clone()).collect();
+        let mut confirmed = Vec::new();
+        for location in locations {
+            if safe_delete::is_test_path(&location.path) {
+                continue;
+            }
+            let path = location.path.to_string_lossy().to_string();
+            let from = graph
+                .find_container_node_at(
+                    &location.path,
+                    location.range.start_line,
+                    location.range.start_col,
+                )
+                .map(|idx| {
+                    let node = &graph.topology()[idx];
+                    let lang = graph.symbols().resolve(&node.lang.0);
+                    graph.render_fqn(node, conventions.get(lang).map(|c| c.as_ref()))
+                })
+                .unwrap_or_else(|| path.clone());
+            // Edges already account for this user
+            if !seen.insert(from.clone()) {
+                continue;
+            }
+            confirmed.push(models::BlockingUsage {
+                kind: models::UsageKind::Reference,
+                from,
+                detail: "resolved reference".to_string(),
+                location: Some(models::DisplaySymbolLocation {
+                    path,
+                    range: location.range,
+                    selection_range: location.selection_range,
+                }),
+            });
+        }
+
+        report.usages.extend(confirmed);
+        report.verdict = safe_delete::verdict(&report.usages);
+        Ok(report)
+    }
+
+    async fn references(
+        &self,
+        fqn: &str,
+        type_usages: models::TypeUsageFilter,
+    ) -> ApiResult<Vec<models::ReferenceSite>> {
+        let language = {
+            let graph = self.graph().await;
+            let Some(target) = graph.find_node(fqn) else {
+                return Err(ApiError::NotFound(fqn.to_string()));
+            };
+            graph.topology()[target].language(graph.symbols())
+        };
+
+        let query = models::ReferenceQuery {
+            resolution: models::SymbolResolution::Global(fqn.to_string()),
+            language,
+            include_declaration: false,
+            type_usages,
+        };
+        self.find_reference_sites(&query).await
+    }
+
+    async fn notes(&self, fqn: &str) -> ApiResult<Vec<models::NodeNote>> {
+        let engine = self.engine.clone();
+        let fqn = fqn.to_string();
+        tokio::task::spawn_blocking(move || engine.notes(&fqn))
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .map_err(|e| ApiError::Internal(e.to_string()))
+    }
+
+    async fn annotate(
+        &self,
+        fqn: &str,
+        text: &str,
+        tags: &[String],
+    ) -> ApiResult<models::NodeNote> {
+        if text.trim().is_empty() && tags.is_empty() {
+            return Err(ApiError::InvalidArgument(
+                "a note needs text or at least one tag".to_string(),
+            ));
+        }
+        // Notes are keyed by the node's canonical FQN, as `cat` renders it
+        let key = {
+            let graph = self.graph().await;
+            let Some(target) = graph.find_node(fqn) else {
+                return Err(ApiError::NotFound(fqn.to_string()));
+            };
+            let node = &graph.topology()[target];
+            let lang = graph.symbols().resolve(&node.lang.0);
+            let conventions = self.naming_conventions();
+            graph.render_fqn(node, conventions.get(lang).map(|c| c.as_ref()))
+        };
+
+        let engine = self.engine.clone();
+        let text = text.to_string();
+        let tags = tags.to_vec();
+        tokio::task::spawn_blocking(move || engine.add_note(&key, &text, &tags))
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .map_err(|e| ApiError::Internal(e.to_string()))
+    }
+
+    async fn remove_note(&self, fqn: &str, id: u64) -> ApiResult<bool> {
+        let engine = self.engine.clone();
+        let fqn = fqn.to_string();
+        tokio::task::spawn_blocking(move || engine.remove_note(&fqn, id))
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .map_err(|e| ApiError::Internal(e.to_string()))
+    }
+
+    async fn save_snapshot(&self, name: &str) -> ApiResult<std::path::PathBuf> {
+        self.engine
+            .save_snapshot(name)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))
+    }
+
+    async fn diff(&self, reference: &str) -> ApiResult<models::GraphDelta> {
+        self.engine
+            .diff_snapshot(reference)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))
+    }
+
+    async fn index_revision(&self, rev: &str) -> ApiResult<std::path::PathBuf> {
+        self.engine
+            .index_revision(rev)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))
+    }
+
+    async fn diff_between(&self, old: &str, new: &str) -> ApiResult<models::GraphDelta> {
+        self.engine
+            .diff_snapshots(old, new)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))
+    }
+
+    async fn export_graph(
+        &self,
+        format: models::ExportFormat,
+        out_dir: &std::path::Path,
+    ) -> ApiResult<models::ExportSummary> {
+        match format {
+            models::ExportFormat::Neo4jCsv => self
+                .engine
+                .export_neo4j(out_dir)
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string())),
+            models::ExportFormat::Lsif => self.export_lsif(out_dir).await,
+        }
+    }
+
+    async fn export_bundle(&self, path: &std::path::Path) -> ApiResult<models::ExportSummary> {
+        self.engine
+            .write_bundle(path)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))
+    }
+
+    async fn external_api_usage(
+        &self,
+        artifact: Option<&str>,
+        top: usize,
+    ) -> ApiResult<models::ExternalApiReport> {
+        let graph = self.graph().await;
+        let conventions = (*self.naming_conventions()).clone();
+        let assets = self.engine.asset_service().cloned();
+        let artifact = artifact.map(str::to_string);
+        tokio::task::spawn_blocking(move || {
+            ExternalUsageAnalyzer::new(&graph, conventions).analyze(
+                |package| assets.as_ref()?.artifact_name(package),
+                artifact.as_deref(),
+                top,
+            )
+        })
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))
+    }
+}
+
+/// Kinds that get a definition in an LSIF dump
+const LSIF_KINDS: &[models::NodeKind] = &[
+    models::NodeKind::Class,
+    models::NodeKind::Interface,
+    models::NodeKind::Enum,
+    models::NodeKind::Annotation,
+    models::NodeKind::Method,
+    models::NodeKind::Constructor,
+    models::NodeKind::Field,
+];
+
+impl EngineHandle {
+    /// Count a visit of each of `fqns`. Losing a hit is harmless, so
+    /// failures are only logged.
+    pub(crate) async fn record_hits(&self, fqns: Vec<String>) {
+        if fqns.is_empty() {
+            return;
+        }
+        let engine = self.engine.clone();
+        let result = tokio::task::spawn_blocking(move || engine.record_hits(&fqns)).await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::warn!("Failed to record access counts: {}", e),
+            Err(e) => tracing::warn!("Failed to record access counts: {}", e),
+        }
+    }
+
+    /// Write an LSIF dump of the project's symbols to `out_dir`.
+    ///
+    /// Hovers are the rendered nodes and references come from the semantic
+    /// resolvers, as in the LSP server; symbols of languages without one are
+    /// written without references.
+    async fn export_lsif(&self, out_dir: &std::path::Path) -> ApiResult<models::ExportSummary> {
+        let graph = self.graph().await;
+        let conventions = self.naming_conventions();
+        let mut targets = Vec::new();
+        for node in graph.topology().node_weights() {
+            if node.source != models::NodeSource::Project
+                || node.location.is_none()
+                || !LSIF_KINDS.contains(&node.kind)
+            {
+                continue;
+            }
+            let language = node.language(graph.symbols());
+            let convention = conventions.get(language.as_str()).map(|c| c.as_ref());
+            targets.push((graph.render_fqn(node, convention), language));
+        }
+        drop(graph);
+
+        let mut symbols = Vec::with_capacity(targets.len());
+        let mut reference_count = 0;
+        for (fqn, language) in targets {
+            let Some(display) = self.get_node_display(&fqn).await? else {
+                continue;
+            };
+            let Some(location) = display.location else {
+                continue;
+            };
+            let query = models::ReferenceQuery {
+                resolution: models::SymbolResolution::Global(fqn.clone()),
+                language,
+                include_declaration: false,
+                type_usages: models::TypeUsageFilter::Include,
+            };
+            let references: Vec<_> = match self.find_reference_sites(&query).await {
+                Ok(sites) => sites
+                    .into_iter()
+                    .map(|site| {
+                        let range = site.location.selection_range.unwrap_or(site.location.range);
+                        (site.location.path.to_string_lossy().into_owned(), range)
+                    })
+                    .collect(),
+                Err(ApiError::Cancelled) => return Err(ApiError::Cancelled),
+                Err(e) => {
+                    tracing::debug!("No references for {} in LSIF export: {}", fqn, e);
+                    Vec::new()
+                }
+            };
+            reference_count += references.len();
+            symbols.push(LsifSymbol {
+                symbol_id: display.symbol_id,
+                lang: display.lang,
+                path: location.path,
+                range: location.selection_range.unwrap_or(location.range),
+                signature: display.signature,
+                detail: display.detail,
+                references,
+            });
+        }
+
+        let project_root = self.engine.root_path().to_path_buf();
+        let out_dir = out_dir.to_path_buf();
+        tokio::task::spawn_blocking(move || -> std::io::Result<models::ExportSummary> {
+            std::fs::create_dir_all(&out_dir)?;
+            let path = out_dir.join(LSIF_FILE);
+            let out = std::io::BufWriter::new(std::fs::File::create(&path)?);
+            let mut writer = LsifWriter::new(out, &project_root, env!("CARGO_PKG_VERSION"))?;
+            for symbol in &symbols {
+                writer.add_symbol(symbol)?;
+            }
+            writer.finish()?;
+            Ok(models::ExportSummary {
+                files: vec![path],
+                nodes: symbols.len(),
+                edges: reference_count,
+            })
+        })
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .map_err(|e| ApiError::Internal(e.to_string()))
+    }
 }
@@ -1,9 +1,18 @@
 use super::EngineHandle;
 use crate::error::NaviscopeError;
+use crate::model::CodeGraph;
+use crate::runtime::NaviscopeEngine;
 use async_trait::async_trait;
-use naviscope_api::lifecycle::{EngineLifecycle, EngineWatchHandle};
+use naviscope_api::lifecycle::{
+    AuditEntry, BuildResume, ChangeSubscription, ChangeSummary, DocumentEvent, EngineLifecycle,
+    EngineWatchHandle, EpochProgress, HostSettings, IndexControl, IndexWarning,
+    ProgressSubscription, RuntimeMetrics, WatchFlow,
+};
+use naviscope_api::models::DetailLimits;
 use naviscope_api::{ApiError, ApiResult};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 struct WatchHandle {
     token: tokio_util::sync::CancellationToken,
@@ -15,6 +24,55 @@ impl EngineWatchHandle for WatchHandle {
     }
 }
 
+/// Diffs each committed snapshot against the last one it reported
+struct ChangeStream {
+    engine: Arc<NaviscopeEngine>,
+    baseline: CodeGraph,
+}
+
+#[async_trait]
+impl ChangeSubscription for ChangeStream {
+    async fn next(&mut self) -> ApiResult<ChangeSummary> {
+        loop {
+            self.engine
+                .wait_for_commit(self.baseline.instance_id())
+                .await;
+            let current = self.engine.snapshot().await;
+            let baseline = std::mem::replace(&mut self.baseline, current.clone());
+            let conventions = self.engine.naming_conventions();
+            let summary = tokio::task::spawn_blocking(move || {
+                crate::runtime::change_summary(&baseline, &current, &conventions)
+            })
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+            // Commits that only touched library stubs change no indexed file
+            if !summary.files.is_empty() {
+                return Ok(summary);
+            }
+        }
+    }
+}
+
+/// Progress changes of the engine's updates
+struct ProgressStream {
+    updates: tokio::sync::watch::Receiver<Option<EpochProgress>>,
+}
+
+#[async_trait]
+impl ProgressSubscription for ProgressStream {
+    async fn next(&mut self) -> ApiResult<EpochProgress> {
+        loop {
+            self.updates
+                .changed()
+                .await
+                .map_err(|_| ApiError::Internal("the engine was dropped".to_string()))?;
+            if let Some(progress) = *self.updates.borrow_and_update() {
+                return Ok(progress);
+            }
+        }
+    }
+}
+
 #[async_trait]
 impl EngineLifecycle for EngineHandle {
     async fn rebuild(&self) -> ApiResult<()> {
@@ -24,6 +82,95 @@ impl EngineLifecycle for EngineHandle {
             .map_err(|e| ApiError::Internal(e.to_string()))
     }
 
+    async fn rebuild_with_control(&self, control: &IndexControl) -> ApiResult<()> {
+        self.engine
+            .rebuild_with_control(control)
+            .await
+            .map_err(|e| match e {
+                NaviscopeError::Cancelled => ApiError::Cancelled,
+                other => ApiError::Internal(other.to_string()),
+            })
+    }
+
+    async fn update_files(&self, files: Vec<PathBuf>) -> ApiResult<()> {
+        self.engine.update_files(files).await.map_err(|e| match e {
+            NaviscopeError::Cancelled => ApiError::Cancelled,
+            other => ApiError::Internal(other.to_string()),
+        })
+    }
+
+    async fn document_event(&self, path: &Path, event: DocumentEvent) -> ApiResult<()> {
+        self.engine
+            .document_event(path, event)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))
+    }
+
+    async fn pending_resume(&self) -> ApiResult<Option<BuildResume>> {
+        Ok(self.engine.pending_resume().await)
+    }
+
+    async fn reload_config(&self) -> ApiResult<()> {
+        self.engine
+            .reload_config()
+            .map_err(|e| ApiError::Internal(e.to_string()))
+    }
+
+    async fn host_settings(&self) -> ApiResult<HostSettings> {
+        let config = self.engine.config();
+        Ok(HostSettings {
+            mcp_port: config.mcp.port,
+            mcp_host: config.mcp.host,
+            mcp_token: config.mcp.token.clone(),
+            mcp_tools: config.mcp.tools.clone(),
+            lsp_detail: Some(config.detail.lsp.over(DetailLimits::LSP)),
+            mcp_detail: Some(config.detail.mcp.over(DetailLimits::MCP)),
+        })
+    }
+
+    async fn watch_flow(&self) -> ApiResult<WatchFlow> {
+        Ok(self.engine.watch_flow())
+    }
+
+    async fn runtime_metrics(&self) -> ApiResult<RuntimeMetrics> {
+        Ok(self.engine.runtime_metrics())
+    }
+
+    async fn index_warnings(&self) -> ApiResult<Vec<IndexWarning>> {
+        self.engine
+            .index_warnings()
+            .map_err(|e| ApiError::Internal(e.to_string()))
+    }
+
+    async fn record_audit(&self, entry: AuditEntry) -> ApiResult<()> {
+        self.engine
+            .record_audit(&entry)
+            .map_err(|e| ApiError::Internal(e.to_string()))
+    }
+
+    async fn audit_log(&self) -> ApiResult<Vec<AuditEntry>> {
+        self.engine
+            .audit_log()
+            .map_err(|e| ApiError::Internal(e.to_string()))
+    }
+
+    async fn subscribe_changes(&self) -> ApiResult<Box<dyn ChangeSubscription>> {
+        Ok(Box::new(ChangeStream {
+            engine: self.engine.clone(),
+            baseline: self.engine.snapshot().await,
+        }))
+    }
+
+    async fn epoch_progress(&self, epoch: u64) -> ApiResult<Option<EpochProgress>> {
+        Ok(self.engine.epoch_progress(epoch))
+    }
+
+    async fn subscribe_progress(&self) -> ApiResult<Box<dyn ProgressSubscription>> {
+        Ok(Box::new(ProgressStream {
+            updates: self.engine.subscribe_progress(),
+        }))
+    }
+
     async fn load(&self) -> ApiResult<bool> {
         self.engine
             .load()
@@ -62,4 +209,11 @@ impl EngineLifecycle for EngineHandle {
             .await
             .map_err(|e: NaviscopeError| ApiError::Internal(e.to_string()))
     }
+
+    async fn shutdown(&self, deadline: Duration) -> ApiResult<()> {
+        self.engine
+            .shutdown(deadline)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))
+    }
 }
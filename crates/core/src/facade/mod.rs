@@ -1,9 +1,9 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use crate::error::Result;
+use crate::error::{NaviscopeError, Result};
 use crate::model::CodeGraph;
-use crate::runtime::NaviscopeEngine as InternalEngine;
+use crate::runtime::{NaviscopeEngine as InternalEngine, NaviscopeEngineBuilder};
 use naviscope_api::NaviscopeEngine;
 
 mod graph;
@@ -34,6 +34,31 @@ impl EngineHandle {
         Self { engine }
     }
 
+    /// Open the prebuilt index at `index_path` read-only, e.g. an index
+    /// artifact shared by parallel CI jobs. Nothing is locked, watched or
+    /// written, so any number of processes can query the same file. The
+    /// current directory is taken as the project root.
+    pub async fn open_readonly(index_path: PathBuf) -> Result<Self> {
+        let root = std::env::current_dir()?;
+        Self::open_readonly_with(InternalEngine::builder(root), index_path).await
+    }
+
+    /// Like `open_readonly`, with the plugins and settings of `builder`.
+    /// Fails if there is no usable index at `index_path`.
+    pub async fn open_readonly_with(
+        builder: NaviscopeEngineBuilder,
+        index_path: PathBuf,
+    ) -> Result<Self> {
+        let engine = builder.with_read_only_index(index_path.clone()).build();
+        if !engine.load().await? {
+            return Err(NaviscopeError::Internal(format!(
+                "No usable index at {}",
+                index_path.display()
+            )));
+        }
+        Ok(Self::from_engine(Arc::new(engine)))
+    }
+
     // ---- Async API (for LSP/MCP) ----
 
     /// Get a snapshot of the current graph (async)
@@ -180,13 +205,49 @@ mod tests {
             pattern: "test".to_string(),
             kind: vec![],
             sources: vec![],
+            statuses: vec![],
+            modifiers: vec![],
             limit: 10,
+            group_by: None,
+            count: false,
         };
 
         let result = handle.query(&query).await;
         assert!(result.is_ok(), "Query should execute successfully");
     }
 
+    #[tokio::test]
+    async fn test_annotate_checks_arguments() {
+        use naviscope_api::ApiError;
+
+        let engine = Arc::new(InternalEngine::builder(PathBuf::from(".")).build());
+        let handle = EngineHandle::from_engine(engine);
+
+        let tags = vec!["legacy".to_string()];
+        assert!(matches!(
+            handle.annotate("com.example.Missing", "", &tags).await,
+            Err(ApiError::NotFound(_))
+        ));
+        assert!(matches!(
+            handle.annotate("com.example.Missing", "  ", &[]).await,
+            Err(ApiError::InvalidArgument(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_validate_query_checks_symbols() {
+        let engine = Arc::new(InternalEngine::builder(PathBuf::from(".")).build());
+        let handle = EngineHandle::from_engine(engine);
+
+        let find = serde_json::json!({"command": "find", "pattern": "Order"});
+        assert!(handle.validate_query(&find).await.unwrap().valid);
+
+        let cat = serde_json::json!({"command": "cat", "fqn": "com.example.Missing"});
+        let validation = handle.validate_query(&cat).await.unwrap();
+        assert!(!validation.valid);
+        assert_eq!(validation.issues[0].field, "fqn");
+    }
+
     #[test]
     fn test_query_blocking() {
         use naviscope_api::GraphService;
@@ -203,7 +264,11 @@ mod tests {
                 pattern: "test".to_string(),
                 kind: vec![],
                 sources: vec![],
+                statuses: vec![],
+                modifiers: vec![],
                 limit: 10,
+                group_by: None,
+                count: false,
             };
 
             // Use trait method via async runtime
@@ -17,6 +17,13 @@ impl NavigationService for EngineHandle {
         engine.resolve_path(target, current_context)
     }
 
+    async fn nearest_existing(&self, fqn: &str) -> ApiResult<Option<String>> {
+        let graph = self.graph().await;
+        let conventions = (*self.naming_conventions()).clone();
+        let engine = NavigationEngine::new(&graph, conventions);
+        engine.nearest_existing(fqn)
+    }
+
     async fn get_completion_candidates(
         &self,
         prefix: &str,
@@ -1,5 +1,6 @@
 use crate::facade::EngineHandle;
 use crate::features::CodeGraphLike;
+use crate::features::alias;
 use crate::features::discovery::DiscoveryEngine;
 use crate::util::utf16_col_to_byte_col;
 use async_trait::async_trait;
@@ -7,15 +8,15 @@ use naviscope_api::graph::GraphService;
 use naviscope_api::{ApiError, ApiResult};
 
 use naviscope_api::models::{
-    CallHierarchyIncomingCall, CallHierarchyOutgoingCall, DisplayGraphNode, Language, NodeKind,
-    PositionContext, Range, ReferenceQuery, SymbolLocation, SymbolQuery, SymbolResolution,
+    CallHierarchyIncomingCall, CallHierarchyOutgoingCall, DisplayGraphNode, FoldingRange, Language,
+    NodeKind, PositionContext, Range, ReferenceQuery, ReferenceSite, ResolutionCandidate,
+    ResolutionTrace, ResolvedSymbol, SignatureHelp, SymbolLocation, SymbolQuery, SymbolResolution,
 };
 use naviscope_api::semantic::{
     CallHierarchyAnalyzer, ReferenceAnalyzer, SymbolInfoProvider, SymbolNavigator,
 };
 use std::collections::{HashMap, HashSet};
-use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::time::{Duration, sleep};
 
@@ -27,7 +28,51 @@ fn path_from_uri_like(uri: &str) -> PathBuf {
     }
 }
 
+/// Letters, digits, `_` and `$`, not starting with a digit
+fn is_identifier(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| !c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// `resolution` with the indexed node of its symbol, if it has one
+fn resolved_symbol(resolution: SymbolResolution, node: Option<DisplayGraphNode>) -> ResolvedSymbol {
+    match resolution {
+        SymbolResolution::Local(range, type_name) => ResolvedSymbol {
+            fqn: None,
+            local_declaration: Some(range),
+            type_name,
+            node: None,
+        },
+        SymbolResolution::Precise(fqn, _) | SymbolResolution::Global(fqn) => ResolvedSymbol {
+            fqn: Some(fqn),
+            local_declaration: None,
+            type_name: None,
+            node,
+        },
+    }
+}
+
+struct PositionDocument {
+    semantic: Arc<dyn naviscope_plugin::SemanticCap>,
+    language: Language,
+    content: Arc<str>,
+    tree: tree_sitter::Tree,
+    line: usize,
+    byte_col: usize,
+}
+
 impl EngineHandle {
+    /// Text of the file at `path`, as the engine's source store has it
+    fn source_text(&self, path: &Path) -> ApiResult<Arc<str>> {
+        self.engine
+            .sources()
+            .read(path)
+            .map(|text| text.shared())
+            .map_err(|e| ApiError::Internal(format!("{}: {}", path.display(), e)))
+    }
+
     async fn hydrate_symbol_if_missing(&self, fqn: &str) -> ApiResult<()> {
         if self
             .get_node_display(fqn)
@@ -38,9 +83,12 @@ impl EngineHandle {
             return Ok(());
         }
 
-        if !self.engine.request_stub_for_fqn(fqn) {
-            let _ = self.engine.scan_global_assets().await;
-            let _ = self.engine.request_stub_for_fqn(fqn);
+        let engine = Arc::clone(&self.engine);
+        if !engine.clone().request_stub(fqn.to_string()).await {
+            let _ = engine.scan_global_assets().await;
+            if !engine.request_stub(fqn.to_string()).await {
+                tracing::debug!("No stub could be requested for {}", fqn);
+            }
         }
 
         for _ in 0..3 {
@@ -58,6 +106,44 @@ impl EngineHandle {
         Ok(())
     }
 
+    /// Load and parse the document at `ctx` for a position-based request.
+    fn parse_position(
+        &self,
+        ctx: &PositionContext,
+        capability: &'static str,
+    ) -> ApiResult<PositionDocument> {
+        let path = path_from_uri_like(&ctx.uri);
+
+        let (semantic, language) =
+            self.get_services_for_path(&path)
+                .ok_or_else(|| ApiError::UnsupportedCapability {
+                    capability,
+                    language: "unknown".to_string(),
+                })?;
+
+        let content = match &ctx.content {
+            Some(c) => Arc::from(c.as_str()),
+            None => self.source_text(&path)?,
+        };
+
+        let tree = self
+            .engine
+            .trees()
+            .get_or_parse(&path, &content, || semantic.parse(&content, None))
+            .ok_or_else(|| ApiError::Internal("Failed to parse".into()))?;
+
+        let byte_col = utf16_col_to_byte_col(&content, ctx.line as usize, ctx.char as usize);
+
+        Ok(PositionDocument {
+            semantic,
+            language,
+            content,
+            tree,
+            line: ctx.line as usize,
+            byte_col,
+        })
+    }
+
     async fn hydrate_resolution_if_needed(&self, resolution: &SymbolResolution) -> ApiResult<()> {
         if let Some(fqn) = resolution.fqn() {
             self.hydrate_symbol_if_missing(fqn).await?;
@@ -84,10 +170,9 @@ impl SymbolNavigator for EngineHandle {
             }
         };
 
-        let content = if let Some(c) = &ctx.content {
-            c.clone()
-        } else {
-            fs::read_to_string(&path).map_err(|e| ApiError::Internal(e.to_string()))?
+        let content = match &ctx.content {
+            Some(c) => Arc::from(c.as_str()),
+            None => self.source_text(&path)?,
         };
 
         let tree = semantic
@@ -101,6 +186,131 @@ impl SymbolNavigator for EngineHandle {
         Ok(semantic.resolve_at(&tree, &content, ctx.line as usize, byte_col, &graph))
     }
 
+    async fn resolve_at(
+        &self,
+        path: &std::path::Path,
+        line: u32,
+        col: u32,
+    ) -> ApiResult<Option<ResolvedSymbol>> {
+        let ctx = PositionContext {
+            uri: path.to_string_lossy().into_owned(),
+            line,
+            char: col,
+            content: None,
+        };
+        let Some(resolution) = self.resolve_symbol_at(&ctx).await? else {
+            return Ok(None);
+        };
+        let node = match resolution.fqn() {
+            Some(fqn) => self.get_symbol_info(fqn).await?,
+            None => None,
+        };
+        Ok(Some(resolved_symbol(resolution, node)))
+    }
+
+    async fn resolve_batch(
+        &self,
+        path: &std::path::Path,
+        positions: &[(u32, u32)],
+    ) -> ApiResult<Vec<Option<ResolvedSymbol>>> {
+        let ctx = PositionContext {
+            uri: path.to_string_lossy().into_owned(),
+            line: 0,
+            char: 0,
+            content: None,
+        };
+        let doc = self.parse_position(&ctx, "semantic.resolve_batch")?;
+        let graph = self.graph().await;
+        let resolutions: Vec<Option<SymbolResolution>> = positions
+            .iter()
+            .map(|&(line, col)| {
+                let byte_col = utf16_col_to_byte_col(&doc.content, line as usize, col as usize);
+                doc.semantic
+                    .resolve_at(&doc.tree, &doc.content, line as usize, byte_col, &graph)
+            })
+            .collect();
+
+        // Identifiers repeat within a file; look each symbol up once
+        let mut nodes: HashMap<String, Option<DisplayGraphNode>> = HashMap::new();
+        for fqn in resolutions.iter().flatten().filter_map(|r| r.fqn()) {
+            if !nodes.contains_key(fqn) {
+                nodes.insert(fqn.to_string(), self.get_symbol_info(fqn).await?);
+            }
+        }
+
+        Ok(resolutions
+            .into_iter()
+            .map(|resolution| {
+                let resolution = resolution?;
+                let node = resolution.fqn().and_then(|fqn| nodes[fqn].clone());
+                Some(resolved_symbol(resolution, node))
+            })
+            .collect())
+    }
+
+    async fn explain_resolution(
+        &self,
+        ctx: &PositionContext,
+    ) -> ApiResult<Option<ResolutionTrace>> {
+        let doc = self.parse_position(ctx, "semantic.explain_resolution")?;
+        let semantic = &doc.semantic;
+
+        let graph = self.graph().await;
+        let (resolution, mut trace) =
+            semantic.explain_at(&doc.tree, &doc.content, doc.line, doc.byte_col, &graph);
+
+        let Some(resolution) = resolution else {
+            return Ok(Some(trace));
+        };
+        let Some(fqn) = resolution.fqn() else {
+            return Ok(Some(trace));
+        };
+
+        // Mirror `find_definitions`: every indexed match with a location is a target
+        let matches = semantic.find_matches(&graph, &resolution);
+        if matches.is_empty() {
+            trace.step(
+                "lookup",
+                false,
+                format!("no indexed node for {fqn}; definition falls back to external stubs"),
+            );
+        }
+
+        let convention = self.get_naming_convention(doc.language.as_str());
+        for fqn_id in matches {
+            let Some(&idx) = graph.fqn_map().get(&fqn_id) else {
+                continue;
+            };
+            let node = &graph.topology()[idx];
+            if let SymbolResolution::Precise(_, intent) = &resolution
+                && !naviscope_api::models::symbol::matches_intent(&node.kind, *intent)
+            {
+                trace.step(
+                    "lookup",
+                    false,
+                    format!("{:?} node does not match {:?} intent", node.kind, intent),
+                );
+            }
+            let reason = node
+                .location
+                .is_none()
+                .then(|| "node has no source location".to_string());
+            trace.candidates.push(ResolutionCandidate {
+                fqn: graph.render_fqn(node, convention.as_deref()),
+                kind: node.kind.clone(),
+                accepted: reason.is_none(),
+                reason,
+            });
+        }
+        trace.chosen = trace
+            .candidates
+            .iter()
+            .find(|c| c.accepted)
+            .map(|c| c.fqn.clone());
+
+        Ok(Some(trace))
+    }
+
     async fn find_highlights(&self, ctx: &PositionContext) -> ApiResult<Vec<Range>> {
         let path = path_from_uri_like(&ctx.uri);
 
@@ -114,10 +324,9 @@ impl SymbolNavigator for EngineHandle {
             }
         };
 
-        let content = if let Some(c) = &ctx.content {
-            c.clone()
-        } else {
-            fs::read_to_string(&path).map_err(|e| ApiError::Internal(e.to_string()))?
+        let content = match &ctx.content {
+            Some(c) => Arc::from(c.as_str()),
+            None => self.source_text(&path)?,
         };
 
         let tree = semantic
@@ -133,6 +342,50 @@ impl SymbolNavigator for EngineHandle {
         Ok(semantic.find_occurrences(&content, &tree, &res, Some(&graph)))
     }
 
+    async fn prepare_rename(&self, ctx: &PositionContext) -> ApiResult<Option<Range>> {
+        let doc = self.parse_position(ctx, "semantic.prepare_rename")?;
+        let graph = self.graph().await;
+        let target =
+            doc.semantic
+                .resolve_at(&doc.tree, &doc.content, doc.line, doc.byte_col, &graph);
+        let Some(target @ SymbolResolution::Local(..)) = target else {
+            return Ok(None);
+        };
+        Ok(doc
+            .semantic
+            .find_occurrences(&doc.content, &doc.tree, &target, Some(&graph))
+            .into_iter()
+            .find(|range| range.contains(doc.line, doc.byte_col)))
+    }
+
+    async fn rename_local(&self, ctx: &PositionContext, new_name: &str) -> ApiResult<Vec<Range>> {
+        if !is_identifier(new_name) {
+            return Err(ApiError::InvalidArgument(format!(
+                "'{}' is not a valid identifier",
+                new_name
+            )));
+        }
+        let doc = self.parse_position(ctx, "semantic.rename_local")?;
+        let graph = self.graph().await;
+        let target =
+            doc.semantic
+                .resolve_at(&doc.tree, &doc.content, doc.line, doc.byte_col, &graph);
+        let Some(target @ SymbolResolution::Local(..)) = target else {
+            return Err(ApiError::InvalidArgument(
+                "Only local variables and parameters can be renamed".to_string(),
+            ));
+        };
+        if let Some(conflict) =
+            doc.semantic
+                .check_local_rename(&doc.content, &doc.tree, &target, new_name)
+        {
+            return Err(ApiError::InvalidArgument(conflict));
+        }
+        Ok(doc
+            .semantic
+            .find_occurrences(&doc.content, &doc.tree, &target, Some(&graph)))
+    }
+
     async fn find_definitions(&self, query: &SymbolQuery) -> ApiResult<Vec<SymbolLocation>> {
         self.hydrate_resolution_if_needed(&query.resolution).await?;
 
@@ -150,11 +403,21 @@ impl SymbolNavigator for EngineHandle {
         let matches = resolver.find_matches(&graph, &query.resolution);
 
         let topology = graph.topology();
+        let conventions = self.naming_conventions();
         let mut locations = Vec::new();
+        let mut targets = Vec::new();
+        let mut seen = HashSet::new();
 
         for fqn_id in matches {
             if let Some(&idx) = graph.fqn_map().get(&fqn_id) {
+                // An alias is defined where the symbol it names is
+                let idx = alias::original(&graph, idx);
+                if !seen.insert(idx) {
+                    continue;
+                }
                 let node = &topology[idx];
+                let lang = graph.symbols().resolve(&node.lang.0);
+                targets.push(graph.render_fqn(node, conventions.get(lang).map(|c| c.as_ref())));
                 if let Some(loc) = &node.location {
                     let path_str = graph.symbols().resolve(&loc.path.0);
                     locations.push(SymbolLocation {
@@ -165,6 +428,21 @@ impl SymbolNavigator for EngineHandle {
                 }
             }
         }
+
+        // External symbols without sources: fall back to a document rendered from bytecode
+        if locations.is_empty()
+            && let Some(fqn) = query.resolution.fqn()
+        {
+            let engine = self.engine.clone();
+            let fqn = fqn.to_string();
+            let rendered =
+                tokio::task::spawn_blocking(move || engine.synthetic_source_location(&fqn))
+                    .await
+                    .ok()
+                    .flatten();
+            locations.extend(rendered);
+        }
+        self.record_hits(targets).await;
         Ok(locations)
     }
 
@@ -242,7 +520,7 @@ impl SymbolNavigator for EngineHandle {
 
 #[async_trait]
 impl ReferenceAnalyzer for EngineHandle {
-    async fn find_references(&self, query: &ReferenceQuery) -> ApiResult<Vec<SymbolLocation>> {
+    async fn find_reference_sites(&self, query: &ReferenceQuery) -> ApiResult<Vec<ReferenceSite>> {
         self.hydrate_resolution_if_needed(&query.resolution).await?;
 
         let resolver = match self.get_semantic_resolver(query.language.clone()) {
@@ -263,11 +541,27 @@ impl ReferenceAnalyzer for EngineHandle {
         let impls = resolver.find_implementations(&graph, &query.resolution);
         matches.extend(impls);
 
-        let match_indices: Vec<_> = matches
+        let mut match_indices: Vec<_> = matches
             .iter()
             .filter_map(|id| graph.fqn_map().get(id).copied())
             .collect();
         let conventions = (*self.naming_conventions()).clone();
+
+        // References through any name of the symbol count: its original and aliases
+        let mut alias_resolutions = Vec::new();
+        for idx in match_indices.clone() {
+            for name in alias::names_of(&graph, idx) {
+                if match_indices.contains(&name) {
+                    continue;
+                }
+                match_indices.push(name);
+                let node = &graph.topology()[name];
+                let lang = graph.symbols().resolve(&node.lang.0);
+                let convention = conventions.get(lang).map(|c| c.as_ref());
+                alias_resolutions
+                    .push(SymbolResolution::Global(graph.render_fqn(node, convention)));
+            }
+        }
         let discovery = DiscoveryEngine::new(&graph, conventions.clone());
         let candidate_paths = discovery.scout_references(&match_indices);
 
@@ -276,7 +570,10 @@ impl ReferenceAnalyzer for EngineHandle {
 
         for path in candidate_paths {
             let handle = self.clone();
-            let resolution = query.resolution.clone();
+            let resolutions: Vec<SymbolResolution> = std::iter::once(query.resolution.clone())
+                .chain(alias_resolutions.iter().cloned())
+                .collect();
+            let type_usages = query.type_usages;
             let graph_snap = Arc::clone(&shared_graph);
             let conventions_clone = conventions.clone();
 
@@ -286,10 +583,10 @@ impl ReferenceAnalyzer for EngineHandle {
                     None => return Vec::new(),
                 };
 
-                let content = match fs::read_to_string(&path) {
+                let content = match handle.source_text(&path) {
                     Ok(c) => c,
                     Err(e) => {
-                        tracing::warn!("find_references failed to read {}: {}", path.display(), e);
+                        tracing::warn!("find_references failed to read {}", e);
                         return Vec::new();
                     }
                 };
@@ -305,23 +602,28 @@ impl ReferenceAnalyzer for EngineHandle {
                     }
                 };
 
-                let locations = discovery.scan_file(semantic.as_ref(), &content, &resolution, &uri);
+                let sites = resolutions.iter().flat_map(|resolution| {
+                    discovery.scan_file_sites(semantic.as_ref(), &content, resolution, &uri)
+                });
 
-                locations
-                    .into_iter()
-                    .filter_map(|loc| {
+                sites
+                    .filter(|(_, kind)| type_usages.accepts(*kind))
+                    .filter_map(|(loc, kind)| {
                         let path_buf = url::Url::parse(&loc.uri.to_string())
                             .ok()
                             .and_then(|u| u.to_file_path().ok())?;
-                        Some(SymbolLocation {
-                            path: Arc::from(path_buf),
-                            range: Range {
-                                start_line: loc.range.start.line as usize,
-                                start_col: loc.range.start.character as usize,
-                                end_line: loc.range.end.line as usize,
-                                end_col: loc.range.end.character as usize,
+                        Some(ReferenceSite {
+                            location: SymbolLocation {
+                                path: Arc::from(path_buf),
+                                range: Range {
+                                    start_line: loc.range.start.line as usize,
+                                    start_col: loc.range.start.character as usize,
+                                    end_line: loc.range.end.line as usize,
+                                    end_col: loc.range.end.character as usize,
+                                },
+                                selection_range: None,
                             },
-                            selection_range: None,
+                            kind,
                         })
                     })
                     .collect::<Vec<_>>()
@@ -348,19 +650,21 @@ impl ReferenceAnalyzer for EngineHandle {
                 })
                 .collect();
 
-            all_locations.retain(|loc| {
-                let path_str = loc.path.to_string_lossy().to_string();
-                !decl_locations.contains(&(path_str, loc.range))
+            all_locations.retain(|site| {
+                let path_str = site.location.path.to_string_lossy().to_string();
+                !decl_locations.contains(&(path_str, site.location.range))
             });
         }
 
         all_locations.sort_by(|a, b| {
+            let (a, b) = (&a.location, &b.location);
             a.path
                 .cmp(&b.path)
                 .then(a.range.start_line.cmp(&b.range.start_line))
                 .then(a.range.start_col.cmp(&b.range.start_col))
         });
         all_locations.dedup_by(|a, b| {
+            let (a, b) = (&a.location, &b.location);
             a.path == b.path
                 && a.range.start_line == b.range.start_line
                 && a.range.start_col == b.range.start_col
@@ -428,14 +732,10 @@ impl CallHierarchyAnalyzer for EngineHandle {
                     None => return vec![],
                 };
 
-                let content = match fs::read_to_string(&path) {
+                let content = match handle.source_text(&path) {
                     Ok(c) => c,
                     Err(e) => {
-                        tracing::warn!(
-                            "find_incoming_calls failed to read {}: {}",
-                            path.display(),
-                            e
-                        );
+                        tracing::warn!("find_incoming_calls failed to read {}", e);
                         return vec![];
                     }
                 };
@@ -531,9 +831,9 @@ impl CallHierarchyAnalyzer for EngineHandle {
             None => return Ok(vec![]),
         };
 
-        let node = graph
-            .get_node(node_idx)
-            .ok_or_else(|| ApiError::Internal(format!("missing node for index {}", node_idx.index())))?;
+        let node = graph.get_node(node_idx).ok_or_else(|| {
+            ApiError::Internal(format!("missing node for index {}", node_idx.index()))
+        })?;
         let symbols = graph.symbols();
         let path_str = node
             .path(symbols)
@@ -548,7 +848,7 @@ impl CallHierarchyAnalyzer for EngineHandle {
             .get_services_for_path(&path)
             .ok_or_else(|| ApiError::Internal("No services for file".into()))?;
 
-        let content = fs::read_to_string(&path).map_err(|e| ApiError::Internal(e.to_string()))?;
+        let content = self.source_text(&path)?;
 
         // Micro-level scanning: extract method body and find all calls
         let tree = semantic
@@ -585,7 +885,7 @@ impl CallHierarchyAnalyzer for EngineHandle {
                     uri: format!("file://{}", path.display()),
                     line: n_range.start_point.row as u32,
                     char: n_range.start_point.column as u32,
-                    content: Some(content.clone()),
+                    content: Some(content.to_string()),
                 };
 
                 if let Ok(Some(res)) = self.resolve_symbol_at(&pos_ctx).await {
@@ -647,6 +947,14 @@ impl SymbolInfoProvider for EngineHandle {
             .map_err(|e| ApiError::Internal(e.to_string()))
     }
 
+    async fn get_signature_help(&self, ctx: &PositionContext) -> ApiResult<Option<SignatureHelp>> {
+        let doc = self.parse_position(ctx, "semantic.signature_help")?;
+        let graph = self.graph().await;
+        Ok(doc
+            .semantic
+            .signature_help_at(&doc.tree, &doc.content, doc.line, doc.byte_col, &graph))
+    }
+
     async fn get_document_symbols(&self, uri: &str) -> ApiResult<Vec<DisplayGraphNode>> {
         let path = path_from_uri_like(uri);
 
@@ -660,7 +968,7 @@ impl SymbolInfoProvider for EngineHandle {
             }
         };
 
-        let content = fs::read_to_string(&path).map_err(|e| ApiError::Internal(e.to_string()))?;
+        let content = self.source_text(&path)?;
 
         let tree = semantic
             .parse(&content, None)
@@ -671,11 +979,41 @@ impl SymbolInfoProvider for EngineHandle {
         Ok(symbols)
     }
 
+    async fn get_folding_ranges(
+        &self,
+        uri: &str,
+        content: Option<String>,
+    ) -> ApiResult<Vec<FoldingRange>> {
+        let ctx = PositionContext {
+            uri: uri.to_string(),
+            line: 0,
+            char: 0,
+            content,
+        };
+        let doc = self.parse_position(&ctx, "semantic.folding_ranges")?;
+        Ok(doc.semantic.folding_ranges(&doc.tree, &doc.content))
+    }
+
+    async fn get_selection_ranges(&self, ctx: &PositionContext) -> ApiResult<Vec<Range>> {
+        let doc = self.parse_position(ctx, "semantic.selection_ranges")?;
+        Ok(doc
+            .semantic
+            .selection_ranges(&doc.tree, doc.line, doc.byte_col))
+    }
+
     async fn get_language_for_document(&self, uri: &str) -> ApiResult<Option<Language>> {
         let path = path_from_uri_like(uri);
 
         Ok(self.get_language_for_path(&path))
     }
+
+    async fn read_source(&self, path: &Path) -> ApiResult<Arc<str>> {
+        self.engine
+            .sources()
+            .read(path)
+            .map(|text| text.shared())
+            .map_err(|e| ApiError::NotFound(format!("Cannot read {}: {}", path.display(), e)))
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,112 @@
+//! Aliases: nodes that name another node under a different FQN
+//!
+//! Re-exports, type aliases and static imports are modeled as alias nodes
+//! with an `AliasOf` edge to the node they name, which may be an alias too.
+//! Navigation looks through them: the definition of an alias is the original,
+//! and references to any name of a symbol are reported together.
+
+use super::CodeGraphLike;
+use crate::model::EdgeType;
+use petgraph::Direction;
+use petgraph::stable_graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use std::collections::HashSet;
+
+/// The node `idx` ultimately names; `idx` itself if it is not an alias.
+pub fn original<G: CodeGraphLike>(graph: &G, idx: NodeIndex) -> NodeIndex {
+    let mut current = idx;
+    let mut seen = HashSet::from([idx]);
+    while let Some(target) = alias_target(graph, current) {
+        // Stop at the last node before a cycle of aliases closes
+        if !seen.insert(target) {
+            break;
+        }
+        current = target;
+    }
+    current
+}
+
+/// The original of `idx`, followed by all its aliases, direct or through
+/// other aliases.
+pub fn names_of<G: CodeGraphLike>(graph: &G, idx: NodeIndex) -> Vec<NodeIndex> {
+    let root = original(graph, idx);
+    let mut names = vec![root];
+    let mut seen = HashSet::from([root]);
+    let mut next = 0;
+    while let Some(&current) = names.get(next) {
+        for edge in graph
+            .topology()
+            .edges_directed(current, Direction::Incoming)
+        {
+            if edge.weight().edge_type == EdgeType::AliasOf && seen.insert(edge.source()) {
+                names.push(edge.source());
+            }
+        }
+        next += 1;
+    }
+    names
+}
+
+fn alias_target<G: CodeGraphLike>(graph: &G, idx: NodeIndex) -> Option<NodeIndex> {
+    graph
+        .topology()
+        .edges_directed(idx, Direction::Outgoing)
+        .find(|edge| edge.weight().edge_type == EdgeType::AliasOf)
+        .map(|edge| edge.target())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::builder::CodeGraphBuilder;
+    use crate::model::{GraphEdge, NodeKind};
+    use crate::test_support::node;
+
+    fn builder() -> CodeGraphBuilder {
+        let mut builder = CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            crate::model::Language::new("java".to_string()),
+            std::sync::Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        builder
+    }
+
+    #[test]
+    fn aliases_resolve_to_original_and_group_together() {
+        let mut builder = builder();
+        let original_idx = builder.add_node(node("lib.Widget", NodeKind::Class));
+        let reexport = builder.add_node(node("api.Widget", NodeKind::Class));
+        let alias = builder.add_node(node("app.W", NodeKind::Class));
+        let other = builder.add_node(node("lib.Other", NodeKind::Class));
+        builder.add_edge(reexport, original_idx, GraphEdge::new(EdgeType::AliasOf));
+        builder.add_edge(alias, reexport, GraphEdge::new(EdgeType::AliasOf));
+        builder.add_edge(other, original_idx, GraphEdge::new(EdgeType::TypedAs));
+        let graph = builder.build();
+
+        let find = |fqn: &str| graph.find_node(fqn).unwrap();
+        assert_eq!(original(&graph, find("app.W")), find("lib.Widget"));
+        assert_eq!(original(&graph, find("lib.Other")), find("lib.Other"));
+
+        let mut names = names_of(&graph, find("api.Widget"));
+        assert_eq!(names[0], find("lib.Widget"));
+        names.sort();
+        let mut expected = vec![find("lib.Widget"), find("api.Widget"), find("app.W")];
+        expected.sort();
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn alias_cycles_terminate() {
+        let mut builder = builder();
+        let a = builder.add_node(node("p.A", NodeKind::Class));
+        let b = builder.add_node(node("p.B", NodeKind::Class));
+        builder.add_edge(a, b, GraphEdge::new(EdgeType::AliasOf));
+        builder.add_edge(b, a, GraphEdge::new(EdgeType::AliasOf));
+        let graph = builder.build();
+
+        let a = graph.find_node("p.A").unwrap();
+        let b = graph.find_node("p.B").unwrap();
+        assert_eq!(original(&graph, a), b);
+        assert_eq!(names_of(&graph, a).len(), 2);
+    }
+}
@@ -0,0 +1,194 @@
+//! Differences between two graphs
+//!
+//! Nodes are matched by rendered FQN and edges by their endpoints and type,
+//! so graphs loaded from different files compare correctly. Only project
+//! nodes and the edges leaving them are compared: which library stubs a graph
+//! holds depends on what happened to be requested, not on the code.
+
+use crate::model::GraphNode;
+use naviscope_api::models::graph::{NodeSource, ResolutionStatus};
+pub use naviscope_api::models::{EdgeChange, GraphDelta, NodeChange};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use super::CodeGraphLike;
+
+/// Parts of a node that make it "modified" when they change. Positions are
+/// compared by extent only, so code moving down a file does not count.
+#[derive(PartialEq, Eq)]
+struct NodeSignature {
+    change: NodeChange,
+    lines: Option<usize>,
+    status: ResolutionStatus,
+}
+
+type EdgeKey = (String, String, String);
+
+pub struct GraphDiff {
+    naming_conventions: HashMap<String, Arc<dyn naviscope_plugin::NamingConvention>>,
+}
+
+impl GraphDiff {
+    pub fn new(
+        naming_conventions: HashMap<String, Arc<dyn naviscope_plugin::NamingConvention>>,
+    ) -> Self {
+        Self { naming_conventions }
+    }
+
+    /// Changes from `old` to `new`; every list is sorted.
+    pub fn compute<G: CodeGraphLike>(&self, old: &G, new: &G) -> GraphDelta {
+        let old_nodes = self.nodes(old);
+        let new_nodes = self.nodes(new);
+        let mut delta = GraphDelta::default();
+
+        for (fqn, after) in &new_nodes {
+            match old_nodes.get(fqn) {
+                None => delta.added_nodes.push(after.change.clone()),
+                Some(before) if before != after => delta.modified_nodes.push(after.change.clone()),
+                Some(_) => {}
+            }
+        }
+        delta.removed_nodes = old_nodes
+            .iter()
+            .filter(|(fqn, _)| !new_nodes.contains_key(*fqn))
+            .map(|(_, before)| before.change.clone())
+            .collect();
+
+        let old_edges = self.edges(old);
+        let new_edges = self.edges(new);
+        delta.added_edges = new_edges
+            .iter()
+            .filter(|(key, _)| !old_edges.contains_key(*key))
+            .map(|(_, edge)| edge.clone())
+            .collect();
+        delta.removed_edges = old_edges
+            .iter()
+            .filter(|(key, _)| !new_edges.contains_key(*key))
+            .map(|(_, edge)| edge.clone())
+            .collect();
+
+        delta
+    }
+
+    fn fqn<G: CodeGraphLike>(&self, graph: &G, node: &GraphNode) -> String {
+        let lang = graph.symbols().resolve(&node.lang.0);
+        graph.render_fqn(node, self.naming_conventions.get(lang).map(|c| c.as_ref()))
+    }
+
+    fn nodes<G: CodeGraphLike>(&self, graph: &G) -> BTreeMap<String, NodeSignature> {
+        graph
+            .topology()
+            .node_weights()
+            .filter(|node| node.source == NodeSource::Project)
+            .map(|node| {
+                let fqn = self.fqn(graph, node);
+                let signature = NodeSignature {
+                    change: NodeChange {
+                        fqn: fqn.clone(),
+                        kind: node.kind.clone(),
+                        path: node
+                            .location
+                            .as_ref()
+                            .map(|loc| graph.symbols().resolve(&loc.path.0).to_string()),
+                    },
+                    lines: node
+                        .location
+                        .as_ref()
+                        .map(|loc| loc.range.end_line.saturating_sub(loc.range.start_line)),
+                    status: node.status,
+                };
+                (fqn, signature)
+            })
+            .collect()
+    }
+
+    fn edges<G: CodeGraphLike>(&self, graph: &G) -> BTreeMap<EdgeKey, EdgeChange> {
+        let topology = graph.topology();
+        topology
+            .edge_indices()
+            .filter_map(|idx| {
+                let (from, to) = topology.edge_endpoints(idx)?;
+                let from = &topology[from];
+                if from.source != NodeSource::Project {
+                    return None;
+                }
+                let change = EdgeChange {
+                    from: self.fqn(graph, from),
+                    to: self.fqn(graph, &topology[to]),
+                    edge_type: topology[idx].edge_type.clone(),
+                };
+                let key = (
+                    change.from.clone(),
+                    change.to.clone(),
+                    format!("{:?}", change.edge_type),
+                );
+                Some((key, change))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::CodeGraph;
+    use crate::model::builder::CodeGraphBuilder;
+    use crate::model::source::SourceFile;
+    use crate::test_support::node_at;
+    use naviscope_api::models::graph::NodeKind;
+    use std::path::{Path, PathBuf};
+
+    fn graph(nodes: &[(&str, usize)], edges: &[(&str, &str)]) -> CodeGraph {
+        let mut builder = CodeGraphBuilder::new();
+        let mut indices = HashMap::new();
+        for (fqn, end_line) in nodes {
+            let mut node = node_at(fqn, NodeKind::Class, "/p/A.java");
+            if let Some(location) = node.location.as_mut() {
+                location.range.end_line = *end_line;
+            }
+            indices.insert(*fqn, builder.add_node(node));
+        }
+        for (from, to) in edges {
+            builder.add_edge(
+                indices[from],
+                indices[to],
+                crate::model::GraphEdge::new(crate::model::EdgeType::Implements),
+            );
+        }
+        builder.update_file(
+            Path::new("/p/A.java"),
+            SourceFile::new(PathBuf::from("/p/A.java"), 1, 0),
+        );
+        builder.build()
+    }
+
+    #[test]
+    fn diff_reports_node_and_edge_changes() {
+        let old = graph(
+            &[("a.A", 10), ("a.B", 5), ("a.Gone", 3), ("a.I", 2)],
+            &[("a.A", "a.I")],
+        );
+        let new = graph(
+            &[("a.A", 12), ("a.B", 5), ("a.New", 3), ("a.I", 2)],
+            &[("a.B", "a.I")],
+        );
+
+        let delta = GraphDiff::new(HashMap::new()).compute(&old, &new);
+        let fqns = |changes: &[NodeChange]| -> Vec<String> {
+            changes.iter().map(|c| c.fqn.clone()).collect()
+        };
+        assert_eq!(fqns(&delta.added_nodes), vec!["a.New"]);
+        assert_eq!(fqns(&delta.removed_nodes), vec!["a.Gone"]);
+        assert_eq!(fqns(&delta.modified_nodes), vec!["a.A"]);
+        assert_eq!(delta.added_edges.len(), 1);
+        assert_eq!(delta.added_edges[0].from, "a.B");
+        assert_eq!(delta.removed_edges.len(), 1);
+        assert_eq!(delta.removed_edges[0].from, "a.A");
+
+        assert!(
+            GraphDiff::new(HashMap::new())
+                .compute(&new, &new)
+                .is_empty()
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use super::CodeGraphLike;
 use lsp_types::{Location, Uri};
+use naviscope_api::models::ReferenceKind;
 pub use naviscope_api::models::SymbolResolution;
 use naviscope_plugin::SemanticCap;
 use std::collections::HashSet;
@@ -153,6 +154,20 @@ impl<'a> DiscoveryEngine<'a> {
         target_resolution: &SymbolResolution,
         uri: &Uri,
     ) -> Vec<Location> {
+        self.scan_file_sites(semantic, source, target_resolution, uri)
+            .into_iter()
+            .map(|(location, _)| location)
+            .collect()
+    }
+
+    /// Like `scan_file`, with each occurrence tagged by how it uses the target.
+    pub fn scan_file_sites(
+        &self,
+        semantic: &dyn SemanticCap,
+        source: &str,
+        target_resolution: &SymbolResolution,
+        uri: &Uri,
+    ) -> Vec<(Location, ReferenceKind)> {
         if let Some(tree) = semantic.parse(source, None) {
             // 1. Syntactic Scan (Fast)
             let candidates = semantic.find_occurrences(
@@ -180,7 +195,8 @@ impl<'a> DiscoveryEngine<'a> {
                         &resolved_at_loc,
                         target_resolution,
                     ) {
-                        valid_locations.push(Location {
+                        let kind = semantic.classify_occurrence(&tree, source, &range);
+                        let location = Location {
                             uri: uri.clone(),
                             range: lsp_types::Range {
                                 start: lsp_types::Position::new(
@@ -192,7 +208,8 @@ impl<'a> DiscoveryEngine<'a> {
                                     range.end_col as u32,
                                 ),
                             },
-                        });
+                        };
+                        valid_locations.push((location, kind));
                     }
                 }
             }
@@ -0,0 +1,491 @@
+//! Graph export for other tools
+//!
+//! [`Neo4jExporter`] writes the graph in the bulk format of `neo4j-admin
+//! database import`: one CSV file of nodes labelled by `NodeKind`, keyed by
+//! their symbol ID, and one of relationships typed by `EdgeType`
+//! (`InheritsFrom` -> `INHERITS_FROM`). Rows are written as the graph is
+//! walked; only the node IDs are kept, to write the relationships.
+//!
+//! [`LsifWriter`] writes an LSIF dump for code hosts that offer navigation
+//! from one: a definition, hover and references per symbol, linked to the
+//! symbol ID through a moniker.
+
+use crate::model::GraphNode;
+use naviscope_api::models::graph::{EdgeType, NodeKind};
+use naviscope_api::models::symbol::{FqnReader, Range};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use super::CodeGraphLike;
+
+pub const NEO4J_NODES_FILE: &str = "nodes.csv";
+pub const NEO4J_RELATIONSHIPS_FILE: &str = "relationships.csv";
+pub const LSIF_FILE: &str = "dump.lsif";
+
+/// LSIF version the dump follows
+const LSIF_VERSION: &str = "0.4.3";
+
+const NODE_HEADER: &[&str] = &[
+    "id:ID", ":LABEL", "fqn", "name", "lang", "source", "status", "path", "line:int",
+];
+const RELATIONSHIP_HEADER: &[&str] = &[":START_ID", ":END_ID", ":TYPE", "inaccessible:boolean"];
+
+pub struct Neo4jExporter {
+    naming_conventions: HashMap<String, Arc<dyn naviscope_plugin::NamingConvention>>,
+}
+
+impl Neo4jExporter {
+    pub fn new(
+        naming_conventions: HashMap<String, Arc<dyn naviscope_plugin::NamingConvention>>,
+    ) -> Self {
+        Self { naming_conventions }
+    }
+
+    /// Write the nodes and relationships of `graph`; returns their counts.
+    pub fn write<G: CodeGraphLike>(
+        &self,
+        graph: &G,
+        mut nodes: impl Write,
+        mut relationships: impl Write,
+    ) -> io::Result<(usize, usize)> {
+        let topology = graph.topology();
+        let mut ids = HashMap::with_capacity(topology.node_count());
+
+        write_row(&mut nodes, NODE_HEADER)?;
+        for idx in topology.node_indices() {
+            let node = &topology[idx];
+            let row = self.node_row(graph, node);
+            write_row(&mut nodes, &row)?;
+            ids.insert(idx, row.into_iter().next().unwrap_or_default());
+        }
+
+        write_row(&mut relationships, RELATIONSHIP_HEADER)?;
+        let mut edge_count = 0;
+        for idx in topology.edge_indices() {
+            let Some((from, to)) = topology.edge_endpoints(idx) else {
+                continue;
+            };
+            let edge = &topology[idx];
+            write_row(
+                &mut relationships,
+                &[
+                    ids[&from].as_str(),
+                    ids[&to].as_str(),
+                    &relationship_type(&edge.edge_type),
+                    if edge.inaccessible { "true" } else { "false" },
+                ],
+            )?;
+            edge_count += 1;
+        }
+
+        nodes.flush()?;
+        relationships.flush()?;
+        Ok((ids.len(), edge_count))
+    }
+
+    fn node_row<G: CodeGraphLike>(&self, graph: &G, node: &GraphNode) -> Vec<String> {
+        let symbols = graph.symbols();
+        let lang = symbols.resolve(&node.lang.0);
+        let convention = self.naming_conventions.get(lang).map(|c| c.as_ref());
+        let fqn = graph.render_fqn(node, convention);
+        let symbol_id = match convention {
+            Some(convention) => convention.symbol_id(lang, &fqn),
+            None => naviscope_api::models::symbol_id(lang, &fqn),
+        };
+        let (path, line) = match &node.location {
+            Some(loc) => (
+                symbols.resolve(&loc.path.0).to_string(),
+                // One-based, as editors show it
+                (loc.range.start_line + 1).to_string(),
+            ),
+            None => (String::new(), String::new()),
+        };
+        vec![
+            symbol_id,
+            node_label(&node.kind),
+            fqn,
+            graph.fqns().resolve_atom(node.name).to_string(),
+            lang.to_string(),
+            serde_name(&node.source),
+            serde_name(&node.status),
+            path,
+            line,
+        ]
+    }
+}
+
+/// `Class`, `Method`, ...; custom kinds keep their letters and digits
+fn node_label(kind: &NodeKind) -> String {
+    match kind {
+        NodeKind::Custom(name) => name.chars().filter(|c| c.is_ascii_alphanumeric()).collect(),
+        other => format!("{:?}", other),
+    }
+}
+
+/// Neo4j's convention for relationship types: `InheritsFrom` -> `INHERITS_FROM`
+fn relationship_type(edge_type: &EdgeType) -> String {
+    let mut name = String::new();
+    for (i, c) in format!("{:?}", edge_type).chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            name.push('_');
+        }
+        name.push(c.to_ascii_uppercase());
+    }
+    name
+}
+
+/// Name of a unit enum value as serialized, e.g. `Project`
+fn serde_name<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn write_row<S: AsRef<str>>(out: &mut impl Write, fields: &[S]) -> io::Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+        write_field(out, field.as_ref())?;
+    }
+    out.write_all(b"\n")
+}
+
+/// Quote fields that contain a delimiter, quote or line break
+fn write_field(out: &mut impl Write, field: &str) -> io::Result<()> {
+    if field.contains([',', '"', '\n', '\r']) {
+        write!(out, "\"{}\"", field.replace('"', "\"\""))
+    } else {
+        out.write_all(field.as_bytes())
+    }
+}
+
+/// A project symbol as written to an LSIF dump
+#[derive(Debug, Clone)]
+pub struct LsifSymbol {
+    pub symbol_id: String,
+    pub lang: String,
+    /// File and name range of the declaration
+    pub path: String,
+    pub range: Range,
+    /// Code signature shown first in the hover
+    pub signature: Option<String>,
+    /// Markdown shown below the signature
+    pub detail: Option<String>,
+    pub references: Vec<(String, Range)>,
+}
+
+/// Writes an LSIF dump as JSON lines, one vertex or edge per line.
+///
+/// Documents and ranges are created as symbols refer to them; the `contains`
+/// edges tying them to the project are written by [`LsifWriter::finish`].
+pub struct LsifWriter<W: Write> {
+    out: W,
+    next_id: u64,
+    project: u64,
+    /// Document ID and range IDs per file, in the order files were seen
+    documents: BTreeMap<String, (u64, Vec<u64>)>,
+    ranges: HashMap<(String, Range), u64>,
+}
+
+impl<W: Write> LsifWriter<W> {
+    /// Start a dump of the project at `project_root`.
+    pub fn new(out: W, project_root: &Path, tool_version: &str) -> io::Result<Self> {
+        let mut writer = Self {
+            out,
+            next_id: 0,
+            project: 0,
+            documents: BTreeMap::new(),
+            ranges: HashMap::new(),
+        };
+        writer.vertex(
+            "metaData",
+            json!({
+                "version": LSIF_VERSION,
+                "projectRoot": file_uri(&project_root.to_string_lossy()),
+                "positionEncoding": "utf-16",
+                "toolInfo": { "name": "naviscope", "version": tool_version },
+            }),
+        )?;
+        writer.project = writer.vertex("project", json!({ "kind": "naviscope" }))?;
+        Ok(writer)
+    }
+
+    pub fn add_symbol(&mut self, symbol: &LsifSymbol) -> io::Result<()> {
+        let result_set = self.vertex("resultSet", json!({}))?;
+
+        let moniker = self.vertex(
+            "moniker",
+            json!({ "scheme": "naviscope", "identifier": symbol.symbol_id, "kind": "export" }),
+        )?;
+        self.edge("moniker", result_set, json!({ "inV": moniker }))?;
+
+        let mut contents = Vec::new();
+        if let Some(signature) = &symbol.signature {
+            contents.push(json!({ "language": symbol.lang, "value": signature }));
+        }
+        if let Some(detail) = &symbol.detail {
+            contents.push(json!(detail));
+        }
+        if !contents.is_empty() {
+            let hover =
+                self.vertex("hoverResult", json!({ "result": { "contents": contents } }))?;
+            self.edge("textDocument/hover", result_set, json!({ "inV": hover }))?;
+        }
+
+        let (definition, document) =
+            self.range(&symbol.path, symbol.range, &symbol.lang, result_set)?;
+        let definitions = self.vertex("definitionResult", json!({}))?;
+        self.edge(
+            "textDocument/definition",
+            result_set,
+            json!({ "inV": definitions }),
+        )?;
+        self.edge(
+            "item",
+            definitions,
+            json!({ "inVs": [definition], "document": document }),
+        )?;
+
+        let references = self.vertex("referenceResult", json!({}))?;
+        self.edge(
+            "textDocument/references",
+            result_set,
+            json!({ "inV": references }),
+        )?;
+        self.edge(
+            "item",
+            references,
+            json!({ "inVs": [definition], "document": document, "property": "definitions" }),
+        )?;
+        let mut by_document: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+        for (path, range) in &symbol.references {
+            let (range, document) = self.range(path, *range, &symbol.lang, result_set)?;
+            by_document.entry(document).or_default().push(range);
+        }
+        for (document, ranges) in by_document {
+            self.edge(
+                "item",
+                references,
+                json!({ "inVs": ranges, "document": document, "property": "references" }),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write the `contains` edges; returns the number of elements written.
+    pub fn finish(mut self) -> io::Result<u64> {
+        let documents = std::mem::take(&mut self.documents);
+        let mut document_ids = Vec::with_capacity(documents.len());
+        for (document, ranges) in documents.into_values() {
+            if !ranges.is_empty() {
+                self.edge("contains", document, json!({ "inVs": ranges }))?;
+            }
+            document_ids.push(document);
+        }
+        if !document_ids.is_empty() {
+            self.edge("contains", self.project, json!({ "inVs": document_ids }))?;
+        }
+        self.out.flush()?;
+        Ok(self.next_id)
+    }
+
+    /// The range vertex for `range` of `path` and its document. A new range is
+    /// bound to `result_set`; one seen before keeps the symbol it had.
+    fn range(
+        &mut self,
+        path: &str,
+        range: Range,
+        lang: &str,
+        result_set: u64,
+    ) -> io::Result<(u64, u64)> {
+        let document = match self.documents.get(path) {
+            Some((document, _)) => *document,
+            None => {
+                let document = self.vertex(
+                    "document",
+                    json!({ "uri": file_uri(path), "languageId": lang }),
+                )?;
+                self.documents
+                    .insert(path.to_string(), (document, Vec::new()));
+                document
+            }
+        };
+        let key = (path.to_string(), range);
+        if let Some(&id) = self.ranges.get(&key) {
+            return Ok((id, document));
+        }
+        let id = self.vertex(
+            "range",
+            json!({
+                "start": { "line": range.start_line, "character": range.start_col },
+                "end": { "line": range.end_line, "character": range.end_col },
+            }),
+        )?;
+        self.edge("next", id, json!({ "inV": result_set }))?;
+        self.ranges.insert(key, id);
+        if let Some((_, ranges)) = self.documents.get_mut(path) {
+            ranges.push(id);
+        }
+        Ok((id, document))
+    }
+
+    fn vertex(&mut self, label: &str, properties: Value) -> io::Result<u64> {
+        self.element("vertex", label, properties)
+    }
+
+    fn edge(&mut self, label: &str, out_v: u64, mut properties: Value) -> io::Result<u64> {
+        properties["outV"] = json!(out_v);
+        self.element("edge", label, properties)
+    }
+
+    fn element(&mut self, kind: &str, label: &str, mut properties: Value) -> io::Result<u64> {
+        self.next_id += 1;
+        properties["id"] = json!(self.next_id);
+        properties["type"] = json!(kind);
+        properties["label"] = json!(label);
+        serde_json::to_writer(&mut self.out, &properties)?;
+        self.out.write_all(b"\n")?;
+        Ok(self.next_id)
+    }
+}
+
+fn file_uri(path: &str) -> String {
+    if path.starts_with('/') {
+        format!("file://{}", path)
+    } else {
+        format!("file:///{}", path.replace('\\', "/"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::builder::CodeGraphBuilder;
+    use crate::model::{GraphEdge, Language};
+    use naviscope_api::models::symbol::NodeId;
+
+    fn node(parts: &[(NodeKind, &str)]) -> crate::indexing::IndexNode {
+        let (kind, name) = parts.last().unwrap().clone();
+        crate::indexing::IndexNode {
+            id: NodeId::Structured(
+                parts
+                    .iter()
+                    .map(|(k, n)| (k.clone(), n.to_string()))
+                    .collect(),
+            ),
+            name: name.to_string(),
+            ..crate::test_support::node(name, kind)
+        }
+    }
+
+    #[test]
+    fn exports_labelled_nodes_and_typed_relationships() {
+        let conventions: HashMap<String, Arc<dyn naviscope_plugin::NamingConvention>> =
+            HashMap::from([(
+                "java".to_string(),
+                Arc::new(naviscope_plugin::StandardNamingConvention) as _,
+            )]);
+        let mut builder = CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            Language::new("java".to_string()),
+            Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        let a = builder.add_node(node(&[(NodeKind::Package, "a"), (NodeKind::Class, "A")]));
+        let m = builder.add_node(node(&[
+            (NodeKind::Package, "a"),
+            (NodeKind::Class, "A"),
+            (NodeKind::Method, "put(int,String)"),
+        ]));
+        builder.add_edge(a, m, GraphEdge::new(EdgeType::Contains));
+        builder.add_edge(m, a, GraphEdge::inaccessible(EdgeType::InheritsFrom));
+        let graph = builder.build();
+
+        let (mut nodes, mut relationships) = (Vec::new(), Vec::new());
+        let counts = Neo4jExporter::new(conventions)
+            .write(&graph, &mut nodes, &mut relationships)
+            .unwrap();
+        assert_eq!(counts, (2, 2));
+
+        let nodes = String::from_utf8(nodes).unwrap();
+        let lines: Vec<_> = nodes.lines().collect();
+        assert_eq!(
+            lines[0],
+            "id:ID,:LABEL,fqn,name,lang,source,status,path,line:int"
+        );
+        assert!(lines.contains(&"java:a.A,Class,a.A,A,java,Project,resolved,,"));
+        assert!(lines.contains(
+            &"\"java:a.A#put(int,String)\",Method,\"a.A#put(int,String)\",\"put(int,String)\",java,Project,resolved,,"
+        ));
+
+        let relationships = String::from_utf8(relationships).unwrap();
+        assert!(relationships.contains("java:a.A,\"java:a.A#put(int,String)\",CONTAINS,false\n"));
+        assert!(
+            relationships.contains("\"java:a.A#put(int,String)\",java:a.A,INHERITS_FROM,true\n")
+        );
+    }
+
+    fn range(line: usize, start: usize, end: usize) -> Range {
+        Range {
+            start_line: line,
+            start_col: start,
+            end_line: line,
+            end_col: end,
+        }
+    }
+
+    #[test]
+    fn lsif_links_definitions_references_and_hovers() {
+        let mut out = Vec::new();
+        let mut writer = LsifWriter::new(&mut out, Path::new("/p"), "0.0.0").unwrap();
+        writer
+            .add_symbol(&LsifSymbol {
+                symbol_id: "java:a.A".to_string(),
+                lang: "java".to_string(),
+                path: "/p/a/A.java".to_string(),
+                range: range(1, 13, 14),
+                signature: Some("class A".to_string()),
+                detail: None,
+                references: vec![
+                    ("/p/a/B.java".to_string(), range(3, 4, 5)),
+                    ("/p/a/B.java".to_string(), range(4, 4, 5)),
+                ],
+            })
+            .unwrap();
+        let count = writer.finish().unwrap();
+
+        let elements: Vec<Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(elements.len() as u64, count);
+        let labelled = |label: &str| -> Vec<&Value> {
+            elements.iter().filter(|e| e["label"] == label).collect()
+        };
+        assert_eq!(labelled("metaData")[0]["projectRoot"], "file:///p");
+        assert_eq!(labelled("document").len(), 2);
+        assert_eq!(labelled("range").len(), 3);
+        assert_eq!(labelled("moniker")[0]["identifier"], "java:a.A");
+        assert_eq!(
+            labelled("hoverResult")[0]["result"]["contents"][0]["value"],
+            "class A"
+        );
+
+        // Both references of B.java are listed under its document
+        let references = labelled("item")
+            .into_iter()
+            .find(|e| e["property"] == "references")
+            .unwrap();
+        assert_eq!(references["inVs"].as_array().unwrap().len(), 2);
+
+        // Every range belongs to a document, every document to the project
+        let contains = labelled("contains");
+        assert_eq!(contains.len(), 3);
+        assert_eq!(contains[2]["outV"], labelled("project")[0]["id"]);
+    }
+}
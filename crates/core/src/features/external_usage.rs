@@ -0,0 +1,263 @@
+//! External API usage report
+//!
+//! Counts the edges from project symbols into external ones and groups them
+//! by library and package, answering how coupled the project is to a library
+//! and which parts of it it actually uses, e.g. before an upgrade.
+
+use crate::model::{EdgeType, GraphNode, NodeKind};
+use naviscope_api::models::graph::NodeSource;
+pub use naviscope_api::models::{
+    ExternalApiReport, ExternalArtifactUsage, ExternalPackageUsage, RankedSymbol,
+};
+use petgraph::Direction;
+use petgraph::stable_graph::NodeIndex;
+use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use super::CodeGraphLike;
+
+/// Artifact of symbols found in no scanned asset
+pub const UNRESOLVED_ARTIFACT: &str = "unresolved";
+
+pub struct ExternalUsageAnalyzer<G> {
+    graph: G,
+    naming_conventions: HashMap<String, Arc<dyn naviscope_plugin::NamingConvention>>,
+}
+
+#[derive(Default)]
+struct ArtifactTally<'a> {
+    references: usize,
+    files: HashSet<&'a str>,
+    /// Package -> symbol -> (kind, references)
+    packages: BTreeMap<String, BTreeMap<String, (NodeKind, usize)>>,
+}
+
+impl<G: CodeGraphLike> ExternalUsageAnalyzer<G> {
+    pub fn new(
+        graph: G,
+        naming_conventions: HashMap<String, Arc<dyn naviscope_plugin::NamingConvention>>,
+    ) -> Self {
+        Self {
+            graph,
+            naming_conventions,
+        }
+    }
+
+    /// Group the references into external symbols by the artifact
+    /// `artifact_of` names for their package. `filter` keeps the artifacts
+    /// containing it (ignoring case); `top` caps the symbols per package.
+    pub fn analyze(
+        &self,
+        artifact_of: impl Fn(&str) -> Option<String>,
+        filter: Option<&str>,
+        top: usize,
+    ) -> ExternalApiReport {
+        let topology = self.graph.topology();
+        let filter = filter.map(str::to_lowercase);
+        let mut artifacts_by_package: HashMap<String, String> = HashMap::new();
+        let mut tallies: HashMap<String, ArtifactTally> = HashMap::new();
+
+        for edge in topology.edge_references() {
+            if edge.weight().edge_type == EdgeType::Contains {
+                continue;
+            }
+            let from = &topology[edge.source()];
+            let to = &topology[edge.target()];
+            if from.source != NodeSource::Project || to.source != NodeSource::External {
+                continue;
+            }
+            let fqn = self.fqn_of(to);
+            let package = self.package_of(edge.target(), &fqn);
+            let artifact = artifacts_by_package
+                .entry(package.clone())
+                .or_insert_with(|| {
+                    artifact_of(&package).unwrap_or_else(|| UNRESOLVED_ARTIFACT.to_string())
+                });
+            if let Some(filter) = &filter
+                && !artifact.to_lowercase().contains(filter)
+            {
+                continue;
+            }
+
+            let tally = tallies.entry(artifact.clone()).or_default();
+            tally.references += 1;
+            if let Some(location) = &from.location {
+                tally
+                    .files
+                    .insert(self.graph.symbols().resolve(&location.path.0));
+            }
+            tally
+                .packages
+                .entry(package)
+                .or_default()
+                .entry(fqn)
+                .or_insert((to.kind.clone(), 0))
+                .1 += 1;
+        }
+
+        let mut artifacts: Vec<ExternalArtifactUsage> = tallies
+            .into_iter()
+            .map(|(artifact, tally)| {
+                let symbols = tally.packages.values().map(BTreeMap::len).sum();
+                let mut packages: Vec<ExternalPackageUsage> = tally
+                    .packages
+                    .into_iter()
+                    .map(|(package, symbols)| package_usage(package, symbols, top))
+                    .collect();
+                packages.sort_by_key(|p| Reverse(p.references));
+                ExternalArtifactUsage {
+                    artifact,
+                    references: tally.references,
+                    symbols,
+                    files: tally.files.len(),
+                    packages,
+                }
+            })
+            .collect();
+        artifacts.sort_by(|a, b| {
+            b.references
+                .cmp(&a.references)
+                .then_with(|| a.artifact.cmp(&b.artifact))
+        });
+        ExternalApiReport { artifacts }
+    }
+
+    /// Package of an external symbol: its enclosing package node, or the
+    /// leading lowercase segments of its owning type's FQN.
+    fn package_of(&self, idx: NodeIndex, fqn: &str) -> String {
+        let topology = self.graph.topology();
+        let mut current = idx;
+        while let Some(parent) = topology
+            .edges_directed(current, Direction::Incoming)
+            .find(|e| e.weight().edge_type == EdgeType::Contains)
+            .map(|e| e.source())
+        {
+            if topology[parent].kind == NodeKind::Package {
+                return self.fqn_of(&topology[parent]);
+            }
+            current = parent;
+        }
+
+        let owner = naviscope_plugin::naming::parse_member_fqn(fqn)
+            .map(|(owner, _)| owner)
+            .unwrap_or(fqn);
+        let mut package = owner;
+        while let Some((head, last)) = package.rsplit_once('.') {
+            if !last.starts_with(char::is_uppercase) {
+                break;
+            }
+            package = head;
+        }
+        if package.starts_with(char::is_uppercase) {
+            return String::new();
+        }
+        package.to_string()
+    }
+
+    fn fqn_of(&self, node: &GraphNode) -> String {
+        let lang = self.graph.symbols().resolve(&node.lang.0);
+        let convention = self.naming_conventions.get(lang).map(|c| c.as_ref());
+        self.graph.render_fqn(node, convention)
+    }
+}
+
+fn package_usage(
+    package: String,
+    symbols: BTreeMap<String, (NodeKind, usize)>,
+    top: usize,
+) -> ExternalPackageUsage {
+    let mut symbols: Vec<RankedSymbol> = symbols
+        .into_iter()
+        .map(|(fqn, (kind, count))| RankedSymbol { fqn, kind, count })
+        .collect();
+    let references = symbols.iter().map(|s| s.count).sum();
+    symbols.sort_by_key(|s| Reverse(s.count));
+    symbols.truncate(top);
+    ExternalPackageUsage {
+        package,
+        references,
+        symbols,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::GraphEdge;
+    use crate::model::builder::CodeGraphBuilder;
+    use crate::test_support::{node, node_at};
+
+    fn external(fqn: &str, kind: NodeKind) -> crate::indexing::IndexNode {
+        crate::indexing::IndexNode {
+            source: NodeSource::External,
+            ..node(fqn, kind)
+        }
+    }
+
+    #[test]
+    fn references_are_grouped_by_artifact_and_package() {
+        let mut builder = CodeGraphBuilder::new();
+        let order = builder.add_node(node_at(
+            "shop.Order",
+            NodeKind::Class,
+            "/p/src/shop/Order.java",
+        ));
+        let cart = builder.add_node(node_at(
+            "shop.Cart",
+            NodeKind::Class,
+            "/p/src/shop/Cart.java",
+        ));
+        let list = builder.add_node(external("java.util.List", NodeKind::Interface));
+        let entry = builder.add_node(external("java.util.Map.Entry", NodeKind::Interface));
+        let strings = builder.add_node(external(
+            "com.google.common.base.Strings#isNullOrEmpty(java.lang.String)",
+            NodeKind::Method,
+        ));
+        let lost = builder.add_node(external("org.lost.Thing", NodeKind::Class));
+        builder.add_edge(order, list, GraphEdge::new(EdgeType::TypedAs));
+        builder.add_edge(cart, list, GraphEdge::new(EdgeType::TypedAs));
+        builder.add_edge(cart, entry, GraphEdge::new(EdgeType::TypedAs));
+        builder.add_edge(order, strings, GraphEdge::new(EdgeType::TypedAs));
+        builder.add_edge(order, lost, GraphEdge::new(EdgeType::InheritsFrom));
+        let graph = builder.build();
+
+        let artifact_of = |package: &str| match package {
+            "java.util" => Some("jdk 17".to_string()),
+            "com.google.common.base" => Some("com.google.guava:guava:33.0".to_string()),
+            _ => None,
+        };
+        let analyzer = ExternalUsageAnalyzer::new(&graph, HashMap::new());
+        let report = analyzer.analyze(artifact_of, None, 1);
+        let summary: Vec<_> = report
+            .artifacts
+            .iter()
+            .map(|a| (a.artifact.as_str(), a.references, a.symbols, a.files))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("jdk 17", 3, 2, 2),
+                ("com.google.guava:guava:33.0", 1, 1, 1),
+                (UNRESOLVED_ARTIFACT, 1, 1, 1),
+            ]
+        );
+        let jdk = &report.artifacts[0].packages;
+        assert_eq!(jdk.len(), 1);
+        assert_eq!(jdk[0].package, "java.util");
+        assert_eq!(jdk[0].references, 3);
+        // `top` keeps the most used symbol
+        assert_eq!(jdk[0].symbols.len(), 1);
+        assert_eq!(jdk[0].symbols[0].fqn, "java.util.List");
+        assert_eq!(jdk[0].symbols[0].count, 2);
+        assert_eq!(
+            report.artifacts[1].packages[0].package,
+            "com.google.common.base"
+        );
+
+        let report = analyzer.analyze(artifact_of, Some("GUAVA"), 10);
+        assert_eq!(report.artifacts.len(), 1);
+        assert_eq!(report.artifacts[0].references, 1);
+    }
+}
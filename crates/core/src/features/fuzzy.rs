@@ -0,0 +1,111 @@
+//! Fuzzy matching of symbol names
+//!
+//! A pattern matches a name when its characters appear in the name in order,
+//! ignoring case. Matches at the start of the name and at camel humps or
+//! after separators score higher, so `UsrCtl` ranks `UserController` above
+//! `UnusedRecordCatalog`. Gaps between matched characters lower the score.
+
+const MATCH: i32 = 16;
+const START_BONUS: i32 = 12;
+const BOUNDARY_BONUS: i32 = 10;
+const CONSECUTIVE_BONUS: i32 = 6;
+const CASE_BONUS: i32 = 1;
+const GAP_PENALTY: i32 = 2;
+const EXACT_BONUS: i32 = 100;
+const PREFIX_BONUS: i32 = 40;
+
+/// Score of `candidate` for `pattern`, or `None` if it does not match.
+/// Higher is better; an empty pattern matches everything with score 0.
+pub fn score(pattern: &str, candidate: &str) -> Option<i32> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = candidate.chars().collect();
+    if pattern.is_empty() {
+        return Some(0);
+    }
+    if pattern.len() > text.len() {
+        return None;
+    }
+
+    let bonus: Vec<i32> = (0..text.len()).map(|j| position_bonus(&text, j)).collect();
+    let same = |p: char, t: char| p.to_lowercase().eq(t.to_lowercase());
+
+    // best[j]: best score with the current pattern char matched at text[j]
+    let mut best: Vec<Option<i32>> = text
+        .iter()
+        .enumerate()
+        .map(|(j, &t)| {
+            same(pattern[0], t).then(|| {
+                MATCH + bonus[j] + i32::from(pattern[0] == t) * CASE_BONUS
+                    - (j as i32).min(3) * GAP_PENALTY
+            })
+        })
+        .collect();
+
+    for &p in &pattern[1..] {
+        let mut next = vec![None; text.len()];
+        // Best of `best[k] - gap(k, j)` over k < j - 1, carried along j
+        let mut carried: Option<i32> = None;
+        for j in 1..text.len() {
+            if j >= 2 {
+                carried = carried
+                    .map(|s| s - GAP_PENALTY)
+                    .max(best[j - 2].map(|s| s - GAP_PENALTY));
+            }
+            if !same(p, text[j]) {
+                continue;
+            }
+            let adjacent = best[j - 1].map(|s| s + CONSECUTIVE_BONUS);
+            next[j] = adjacent
+                .max(carried)
+                .map(|s| s + MATCH + bonus[j] + i32::from(p == text[j]) * CASE_BONUS);
+        }
+        best = next;
+    }
+
+    let mut score = best.into_iter().flatten().max()?;
+    if pattern.len() == text.len() && pattern.iter().zip(&text).all(|(&p, &t)| same(p, t)) {
+        score += EXACT_BONUS;
+    } else if pattern.iter().zip(&text).all(|(&p, &t)| same(p, t)) {
+        score += PREFIX_BONUS;
+    }
+    Some(score)
+}
+
+/// Bonus for matching at `text[j]`: the start, a camel hump, a digit run or
+/// the character after a separator such as `.`, `_` or `$`.
+fn position_bonus(text: &[char], j: usize) -> i32 {
+    let Some(&prev) = j.checked_sub(1).and_then(|i| text.get(i)) else {
+        return START_BONUS;
+    };
+    let cur = text[j];
+    let hump = cur.is_uppercase() && !prev.is_uppercase();
+    let digits = cur.is_ascii_digit() && !prev.is_ascii_digit();
+    if hump || digits || (!prev.is_alphanumeric() && cur.is_alphanumeric()) {
+        BOUNDARY_BONUS
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn camel_humps_rank_above_scattered_matches() {
+        let hump = score("UsrCtl", "UserController").unwrap();
+        let scattered = score("UsrCtl", "UnusedRecordCatalog").unwrap();
+        assert!(hump > scattered, "{} <= {}", hump, scattered);
+        assert!(score("UsrCtl", "Controller").is_none());
+        assert!(score("uc", "UserController").is_some());
+    }
+
+    #[test]
+    fn exact_and_prefix_matches_rank_first() {
+        let exact = score("user", "User").unwrap();
+        let prefix = score("user", "UserService").unwrap();
+        let inner = score("user", "AbstractUserService").unwrap();
+        assert!(exact > prefix && prefix > inner);
+        assert_eq!(score("", "Anything"), Some(0));
+    }
+}
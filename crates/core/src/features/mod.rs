@@ -2,9 +2,15 @@ use crate::model::FqnManager;
 use naviscope_api::models::symbol::{FqnId, Symbol};
 use std::path::Path;
 
+pub mod alias;
+pub mod diff;
 pub mod discovery;
+pub mod export;
+pub mod external_usage;
+pub mod fuzzy;
 pub mod navigation;
 pub mod query;
+pub mod safe_delete;
 
 /// Trait to abstract over different CodeGraph implementations for features.
 /// This allows features to operate on both the full indexed graph and partial/mocked graphs for tests.
@@ -15,6 +21,17 @@ pub trait CodeGraphLike: Send + Sync {
     fn fqn_map(&self) -> &std::collections::HashMap<FqnId, petgraph::stable_graph::NodeIndex>;
     fn path_to_nodes(&self, path: &Path) -> Option<&[petgraph::stable_graph::NodeIndex]>;
     fn reference_index(&self) -> &std::collections::HashMap<Symbol, Vec<Symbol>>;
+    /// Indexed files with their content trigrams
+    fn file_trigrams(&self) -> Vec<(&str, &[u32])>;
+    /// Fingerprinted methods with their fingerprint
+    fn code_fingerprints(
+        &self,
+    ) -> Vec<(
+        petgraph::stable_graph::NodeIndex,
+        &naviscope_plugin::CodeFingerprint,
+    )>;
+    /// Whether `node` is declared in a file of a generated source root
+    fn is_generated(&self, node: &crate::model::GraphNode) -> bool;
     fn find_container_node_at(
         &self,
         path: &std::path::Path,
@@ -41,8 +58,11 @@ pub trait CodeGraphLike: Send + Sync {
         }
     }
 
-    /// Helper to find node by string FQN
+    /// Helper to find node by string FQN, or a file node by `file:<path>`
     fn find_node(&self, fqn: &str) -> Option<petgraph::stable_graph::NodeIndex> {
+        if let Some(path) = fqn.strip_prefix(crate::model::graph::FILE_ID_PREFIX) {
+            return self.find_file_node(path);
+        }
         let ids = self.fqns().resolve_fqn_string(fqn);
         for id in ids {
             if let Some(&idx) = self.fqn_map().get(&id) {
@@ -52,6 +72,30 @@ pub trait CodeGraphLike: Send + Sync {
         None
     }
 
+    /// Node of the indexed file at `path`. A relative path matches the
+    /// indexed file it ends with, if only one does.
+    fn find_file_node(&self, path: &str) -> Option<petgraph::stable_graph::NodeIndex> {
+        let node = |path: &Path| {
+            let id = self
+                .fqns()
+                .resolve_path(&crate::model::graph::file_node_parts(path))?;
+            self.fqn_map().get(&id).copied()
+        };
+        let path = Path::new(path);
+        if path.is_absolute() {
+            return node(path);
+        }
+        let files = self.file_trigrams();
+        let mut matches = files
+            .iter()
+            .map(|(file, _)| Path::new(*file))
+            .filter(|file| file.ends_with(path));
+        match (matches.next(), matches.next()) {
+            (Some(file), None) => node(file),
+            _ => None,
+        }
+    }
+
     /// Find all nodes matching an FQN string (handle duplicates if any)
     fn find_matches_by_fqn(&self, fqn: &str) -> Vec<petgraph::stable_graph::NodeIndex> {
         if let Some(idx) = self.find_node(fqn) {
@@ -83,6 +127,23 @@ impl<T: CodeGraphLike> CodeGraphLike for &T {
         (*self).reference_index()
     }
 
+    fn file_trigrams(&self) -> Vec<(&str, &[u32])> {
+        (*self).file_trigrams()
+    }
+
+    fn code_fingerprints(
+        &self,
+    ) -> Vec<(
+        petgraph::stable_graph::NodeIndex,
+        &naviscope_plugin::CodeFingerprint,
+    )> {
+        (*self).code_fingerprints()
+    }
+
+    fn is_generated(&self, node: &crate::model::GraphNode) -> bool {
+        (*self).is_generated(node)
+    }
+
     fn find_container_node_at(
         &self,
         path: &std::path::Path,
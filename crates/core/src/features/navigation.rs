@@ -173,6 +173,26 @@ impl<'a> NavigationEngine<'a> {
         })
     }
 
+    /// `fqn` if it is still in the graph, otherwise its nearest ancestor that is,
+    /// falling back to the project root. `None` if nothing qualifies.
+    pub fn nearest_existing(&self, fqn: &str) -> ApiResult<Option<String>> {
+        let mut candidate = fqn;
+        loop {
+            if self.graph.find_node(candidate).is_some() {
+                return Ok(Some(candidate.to_string()));
+            }
+            match parent_fqn(candidate) {
+                Some(parent) => candidate = parent,
+                None => break,
+            }
+        }
+
+        match self.resolve_path("/", None) {
+            Ok(ResolveResult::Found(root)) => Ok(Some(root)),
+            _ => Ok(None),
+        }
+    }
+
     pub fn get_completion_candidates(&self, prefix: &str, limit: usize) -> ApiResult<Vec<String>> {
         let candidates = self
             .graph
@@ -194,3 +214,36 @@ impl<'a> NavigationEngine<'a> {
         Ok(candidates)
     }
 }
+
+/// Textual parent of an FQN: the owning type of a member, otherwise the FQN
+/// without its last `.` or `::` segment.
+fn parent_fqn(fqn: &str) -> Option<&str> {
+    if let Some((owner, _)) = naviscope_plugin::naming::parse_member_fqn(fqn) {
+        return Some(owner);
+    }
+    let dot = fqn.rfind('.');
+    let path = fqn.rfind("::");
+    match (dot, path) {
+        (Some(d), Some(p)) if d > p => Some(&fqn[..d]),
+        (_, Some(p)) => Some(&fqn[..p]),
+        (Some(d), None) => Some(&fqn[..d]),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parent_fqn_strips_members_then_segments() {
+        assert_eq!(
+            parent_fqn("com.example.Foo#bar(java.lang.String)"),
+            Some("com.example.Foo")
+        );
+        assert_eq!(parent_fqn("com.example.Foo"), Some("com.example"));
+        assert_eq!(parent_fqn("app::core"), Some("app"));
+        assert_eq!(parent_fqn("app::com.example"), Some("app::com"));
+        assert_eq!(parent_fqn("com"), None);
+    }
+}
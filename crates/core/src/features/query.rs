@@ -1,19 +1,104 @@
-use naviscope_plugin::NodePresenter;
+use crate::advisory::Advisories;
 use crate::error::{NaviscopeError, Result};
+use crate::history::GitHistory;
+use crate::indexing::text::{may_contain, trigrams};
 use crate::model::source::Language;
-use crate::model::{DisplayGraphNode, EdgeType, NodeKind};
-pub use naviscope_api::models::{GraphQuery, QueryResult, QueryResultEdge};
+use crate::model::{DisplayGraphNode, EdgeType, GraphEdge, NodeKind};
+pub use naviscope_api::graph::QueryControl;
+use naviscope_api::models::graph::{NodeSource, ResolutionStatus};
+use naviscope_api::models::{
+    DetailLimits, Distribution, GraphMetrics, MemberSummary, RankedSymbol, TextHit,
+};
+pub use naviscope_api::models::{GraphQuery, GroupBy, QueryGroup, QueryResult, QueryResultEdge};
+use naviscope_plugin::{CodeFingerprint, NodePresenter};
 use petgraph::Direction as PetDirection;
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::DiGraph;
+use petgraph::stable_graph::NodeIndex;
+use petgraph::visit::EdgeRef;
 use regex::RegexBuilder;
+use std::cell::Cell;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use super::CodeGraphLike;
+use super::{CodeGraphLike, fuzzy};
+
+/// Group key for nodes without an enclosing package/module
+const NO_GROUP: &str = "(none)";
+
+/// Nodes visited between cancellation checks and progress reports
+const CHECKPOINT_INTERVAL: usize = 4096;
+
+/// Share of their fingerprints two methods have in common to be clones
+const CLONE_SIMILARITY: f64 = 0.7;
+
+/// Methods sharing a fingerprint hash beyond which it is ignored
+const MAX_CLONE_HOLDERS: usize = 64;
+
+/// Entry points of the `dead` query: callbacks of test and application
+/// frameworks, and methods the runtime invokes by name
+const DEFAULT_ENTRY_POINTS: &[&str] = &[
+    "@Test",
+    "@ParameterizedTest",
+    "@RepeatedTest",
+    "@TestFactory",
+    "@BeforeEach",
+    "@AfterEach",
+    "@BeforeAll",
+    "@AfterAll",
+    "@Before",
+    "@After",
+    "@Bean",
+    "@PostConstruct",
+    "@PreDestroy",
+    "@EventListener",
+    "@Scheduled",
+    "@SpringBootApplication",
+    "main",
+];
+
+/// Access counts of nodes by FQN, used to break ties in searches
+pub type Popularity = HashMap<String, u64>;
+
+/// Kind, source and status filters of a query; an empty list accepts all
+#[derive(Clone, Copy, Default)]
+struct NodeFilter<'a> {
+    kind: &'a [NodeKind],
+    sources: &'a [NodeSource],
+    statuses: &'a [ResolutionStatus],
+}
+
+impl NodeFilter<'_> {
+    fn accepts(&self, node: &crate::model::GraphNode) -> bool {
+        (self.kind.is_empty() || self.kind.contains(&node.kind))
+            && (self.sources.is_empty() || self.sources.contains(&node.source))
+            && (self.statuses.is_empty() || self.statuses.contains(&node.status))
+    }
+}
 
 pub struct QueryEngine<G, L> {
     graph: G,
     lookup: L,
-    naming_conventions:
-        std::collections::HashMap<String, Arc<dyn naviscope_plugin::NamingConvention>>,
+    naming_conventions: HashMap<String, Arc<dyn naviscope_plugin::NamingConvention>>,
+    control: QueryControl,
+    popularity: Arc<Popularity>,
+    advisories: Option<Arc<Advisories>>,
+    history: Option<Arc<GitHistory>>,
+    entry_points: Vec<String>,
+    deadline: Option<Instant>,
+    max_results: Option<usize>,
+    /// Set once the budget cut the running query short
+    truncated: Cell<bool>,
+}
+
+/// Time and size limits of a query; unset limits do not apply
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryBudget {
+    pub timeout: Option<Duration>,
+    pub max_results: Option<usize>,
 }
 
 impl<G, L> QueryEngine<G, L>
@@ -24,23 +109,114 @@ where
     pub fn new(
         graph: G,
         lookup: L,
-        naming_conventions: std::collections::HashMap<
-            String,
-            Arc<dyn naviscope_plugin::NamingConvention>,
-        >,
+        naming_conventions: HashMap<String, Arc<dyn naviscope_plugin::NamingConvention>>,
     ) -> Self {
         Self {
             graph,
             lookup,
             naming_conventions,
+            control: QueryControl::default(),
+            popularity: Arc::default(),
+            advisories: None,
+            history: None,
+            entry_points: Vec::new(),
+            deadline: None,
+            max_results: None,
+            truncated: Cell::new(false),
         }
     }
 
+    /// Check `control` for cancellation and report progress while scanning.
+    pub fn with_control(mut self, control: QueryControl) -> Self {
+        self.control = control;
+        self
+    }
+
+    /// Rank equally good search matches by how often they were visited.
+    pub fn with_popularity(mut self, popularity: Arc<Popularity>) -> Self {
+        self.popularity = popularity;
+        self
+    }
+
+    /// Attach vulnerability and license advice to rendered dependency nodes.
+    pub fn with_advisories(mut self, advisories: Arc<Advisories>) -> Self {
+        self.advisories = Some(advisories);
+        self
+    }
+
+    /// Show the last change of a node in `cat` and rank hot spots in `stats`.
+    pub fn with_history(mut self, history: Arc<GitHistory>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Treat `entry_points` as used in `dead` queries, on top of the built-in ones.
+    pub fn with_entry_points(mut self, entry_points: Vec<String>) -> Self {
+        self.entry_points = entry_points;
+        self
+    }
+
+    fn hits(&self, fqn: &str) -> u64 {
+        self.popularity.get(fqn).copied().unwrap_or(0)
+    }
+
+    /// Stop each query after `timeout` and return at most `max_results`
+    /// nodes, edges or hits; the result is then marked truncated.
+    pub fn with_budget(mut self, budget: QueryBudget) -> Self {
+        self.deadline = budget.timeout.map(|timeout| Instant::now() + timeout);
+        self.max_results = budget.max_results;
+        self
+    }
+
+    /// Called for every visited node of a full scan. False once the query
+    /// is out of time and should stop with what it found.
+    fn checkpoint(&self, visited: usize) -> Result<bool> {
+        if !visited.is_multiple_of(CHECKPOINT_INTERVAL) {
+            return Ok(true);
+        }
+        if self.control.is_cancelled() {
+            return Err(NaviscopeError::Cancelled);
+        }
+        let total = self.graph.topology().node_count() as u64;
+        self.control.report(visited as u64, Some(total));
+        Ok(!self.out_of_time())
+    }
+
+    fn out_of_time(&self) -> bool {
+        let expired = self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline);
+        if expired {
+            self.truncated.set(true);
+        }
+        expired
+    }
+
+    /// Cut `items` down to the result budget, returning how many there were
+    /// when some were left out.
+    fn within_budget<T>(&self, items: &mut Vec<T>) -> Option<usize> {
+        let max = self.max_results?;
+        let total = items.len();
+        if total <= max {
+            return None;
+        }
+        items.truncate(max);
+        self.truncated.set(true);
+        Some(total)
+    }
+
     fn render_node(&self, node: &crate::model::GraphNode) -> DisplayGraphNode {
         let symbols = self.graph.symbols();
         let lang = node.language(symbols);
         if let Some(renderer) = (self.lookup)(lang.clone()) {
-            renderer.render_display_node(node, self.graph.fqns())
+            let mut display = renderer.render_display_node(node, self.graph.fqns());
+            display.generated = self.graph.is_generated(node);
+            if node.kind == NodeKind::Dependency
+                && let Some(advisories) = &self.advisories
+            {
+                advisories.enrich(&mut display);
+            }
+            display
         } else {
             panic!(
                 "CRITICAL: No renderer found for language '{}'. This indicates a missing plugin for indexed data.",
@@ -49,58 +225,135 @@ where
         }
     }
 
+    /// Whether the rendered node has every one of `modifiers`, in any case
+    fn has_modifiers(&self, node: &crate::model::GraphNode, modifiers: &[String]) -> bool {
+        if modifiers.is_empty() {
+            return true;
+        }
+        let display = self.render_node(node);
+        modifiers
+            .iter()
+            .all(|m| display.modifiers.iter().any(|d| d.eq_ignore_ascii_case(m)))
+    }
+
+    /// Run `query`. Out of time or over the result budget, the query returns
+    /// what it found so far, marked truncated.
     pub fn execute(&self, query: &GraphQuery) -> Result<QueryResult> {
+        self.truncated.set(false);
+        let mut result = self.answer(query)?;
+        if let Some(total) = self.within_budget(&mut result.nodes) {
+            result.total.get_or_insert(total);
+        }
+        self.within_budget(&mut result.edges);
+        self.within_budget(&mut result.hits);
+        result.truncated = self.truncated.get();
+        Ok(result)
+    }
+
+    fn answer(&self, query: &GraphQuery) -> Result<QueryResult> {
         let symbols = self.graph.symbols();
         match query {
             GraphQuery::Find {
                 pattern,
                 kind,
                 sources,
+                statuses,
+                modifiers,
                 limit,
+                group_by,
+                count,
             } => {
+                let filter = NodeFilter {
+                    kind,
+                    sources,
+                    statuses,
+                };
                 let regex = RegexBuilder::new(pattern)
                     .case_insensitive(true)
                     .build()
                     .map_err(|e| NaviscopeError::Parsing(format!("Invalid regex: {}", e)))?;
+                let aggregate = group_by.is_some() || *count;
+                // Ranking by popularity needs every match before the limit applies
+                let ranked = !aggregate && !self.popularity.is_empty();
 
-                let mut nodes = Vec::new();
+                let mut matched = Vec::new();
 
-                for node in self.graph.topology().node_weights() {
+                for (visited, idx) in self.graph.topology().node_indices().enumerate() {
+                    if !self.checkpoint(visited)? {
+                        break;
+                    }
+                    let node = &self.graph.topology()[idx];
                     let lang_str = symbols.resolve(&node.lang.0);
                     let convention = self.naming_conventions.get(lang_str).map(|c| c.as_ref());
                     let fqn_str = self.graph.render_fqn(node, convention);
-                    if regex.is_match(&fqn_str) || regex.is_match(node.name(symbols)) {
-                        let kind_match = kind.is_empty() || kind.contains(&node.kind);
-                        let source_match = sources.is_empty() || sources.contains(&node.source);
-                        if kind_match && source_match {
-                            nodes.push(self.render_node(node));
-                        }
+                    if (regex.is_match(&fqn_str) || regex.is_match(node.name(symbols)))
+                        && filter.accepts(node)
+                        && self.has_modifiers(node, modifiers)
+                    {
+                        matched.push((self.hits(&fqn_str), idx));
                     }
 
-                    if nodes.len() >= *limit {
+                    if !aggregate && !ranked && matched.len() >= *limit {
                         break;
                     }
                 }
-                Ok(QueryResult::new(nodes, vec![]))
+                if ranked {
+                    matched.sort_by_key(|(hits, _)| std::cmp::Reverse(*hits));
+                    matched.truncate(*limit);
+                }
+                let matched = matched.into_iter().map(|(_, idx)| idx).collect();
+                Ok(self.finish(matched, vec![], *group_by, *count))
+            }
+            GraphQuery::Fuzzy {
+                pattern,
+                kind,
+                sources,
+                statuses,
+                limit,
+            } => {
+                let filter = NodeFilter {
+                    kind,
+                    sources,
+                    statuses,
+                };
+                self.fuzzy(pattern, filter, *limit)
             }
             GraphQuery::Ls {
                 fqn,
                 kind,
                 sources,
+                statuses,
                 modifiers: _,
+                group_by,
+                count,
             } => {
+                let filter = NodeFilter {
+                    kind,
+                    sources,
+                    statuses,
+                };
                 if let Some(target_fqn) = fqn {
-                    self.traverse_neighbors(
+                    // Members of a symbol, or the symbols a file declares
+                    let (matched, edges) = self.traverse_neighbors(
                         target_fqn,
-                        &[EdgeType::Contains],
+                        &[EdgeType::Contains, EdgeType::Declares],
                         PetDirection::Outgoing,
-                        kind,
-                        sources,
-                    )
+                        filter,
+                    )?;
+                    Ok(self.finish(matched, edges, *group_by, *count))
                 } else {
-                    let mut nodes = Vec::new();
+                    let aggregate = group_by.is_some() || *count;
+                    let mut matched = Vec::new();
+                    // Root modules are listed whatever the kind filter
+                    let module_filter = NodeFilter {
+                        kind: &[],
+                        ..filter
+                    };
 
-                    for idx in self.graph.topology().node_indices() {
+                    for (visited, idx) in self.graph.topology().node_indices().enumerate() {
+                        if !self.checkpoint(visited)? {
+                            break;
+                        }
                         let node = &self.graph.topology()[idx];
                         if node.kind == NodeKind::Module {
                             let has_parent = self
@@ -109,18 +362,17 @@ where
                                 .edges_directed(idx, PetDirection::Incoming)
                                 .any(|e| e.weight().edge_type == EdgeType::Contains);
 
-                            if !has_parent {
-                                let source_match =
-                                    sources.is_empty() || sources.contains(&node.source);
-                                if source_match {
-                                    nodes.push(self.render_node(node));
-                                }
+                            if !has_parent && module_filter.accepts(node) {
+                                matched.push(idx);
                             }
                         }
                     }
 
-                    if nodes.is_empty() {
-                        for idx in self.graph.topology().node_indices() {
+                    if matched.is_empty() {
+                        for (visited, idx) in self.graph.topology().node_indices().enumerate() {
+                            if !self.checkpoint(visited)? {
+                                break;
+                            }
                             let node = &self.graph.topology()[idx];
                             let has_parent = self
                                 .graph
@@ -128,44 +380,1074 @@ where
                                 .edges_directed(idx, PetDirection::Incoming)
                                 .any(|e| e.weight().edge_type == EdgeType::Contains);
 
-                            if !has_parent {
-                                let kind_match = kind.is_empty() || kind.contains(&node.kind);
-                                let source_match =
-                                    sources.is_empty() || sources.contains(&node.source);
-                                if kind_match && source_match {
-                                    nodes.push(self.render_node(node));
-                                }
+                            // Files are listed with `ls file:<path>`, not as roots
+                            if !has_parent && node.kind != NodeKind::File && filter.accepts(node) {
+                                matched.push(idx);
                             }
-                            if nodes.len() >= 50 {
+                            if !aggregate && matched.len() >= 50 {
                                 break;
                             }
                         }
                     }
 
-                    Ok(QueryResult::new(nodes, vec![]))
+                    Ok(self.finish(matched, vec![], *group_by, *count))
                 }
             }
-            GraphQuery::Cat { fqn } => {
-                if let Some(idx) = self.graph.find_node(fqn) {
-                    let node = &self.graph.topology()[idx];
-                    Ok(QueryResult::new(vec![self.render_node(node)], vec![]))
-                } else {
-                    Ok(QueryResult::default())
+            GraphQuery::Cat { fqn, limits } => match self.graph.find_node(fqn) {
+                Some(idx) => {
+                    let node = self.render_node(&self.graph.topology()[idx]);
+                    let history = self.history_of(idx);
+                    let mut result = match limits {
+                        Some(limits) => self.abridged(idx, node, limits),
+                        None => QueryResult::new(vec![node], vec![]),
+                    };
+                    result.history = history;
+                    Ok(result)
                 }
-            }
+                None => Ok(QueryResult::default()),
+            },
             GraphQuery::Deps {
                 fqn,
                 rev,
                 edge_types,
+                sources,
+                statuses,
+                group_by,
+                count,
             } => {
                 let direction = if *rev {
                     PetDirection::Incoming
                 } else {
                     PetDirection::Outgoing
                 };
-                self.traverse_neighbors(fqn.as_str(), edge_types, direction, &[], &[])
+                let filter = NodeFilter {
+                    kind: &[],
+                    sources,
+                    statuses,
+                };
+                let (matched, edges) =
+                    self.traverse_neighbors(fqn.as_str(), edge_types, direction, filter)?;
+                Ok(self.finish(matched, edges, *group_by, *count))
+            }
+            GraphQuery::Impact {
+                fqn,
+                max_depth,
+                edge_types,
+            } => self.impact(fqn, *max_depth, edge_types),
+            GraphQuery::Cycles {
+                scope_fqn,
+                edge_types,
+                packages,
+            } => self.cycles(scope_fqn.as_deref(), edge_types, *packages),
+            GraphQuery::Path {
+                from,
+                to,
+                edge_types,
+                max_depth,
+            } => self.path(from, to, edge_types, *max_depth),
+            GraphQuery::Stats { top } => self.stats(*top),
+            GraphQuery::Text {
+                pattern,
+                ignore_case,
+                limit,
+            } => self.text(pattern, *ignore_case, *limit),
+            GraphQuery::Duplicates { min_tokens } => self.duplicates(*min_tokens),
+            GraphQuery::Dead {
+                scope_fqn,
+                entry_points,
+            } => self.dead(scope_fqn.as_deref(), entry_points),
+            GraphQuery::TestsFor { fqn } => self.tests_for(fqn),
+        }
+    }
+
+    /// Nodes whose name fuzzily matches `pattern`, best first. Ties go to
+    /// the shorter name, then the FQN.
+    fn fuzzy(&self, pattern: &str, filter: NodeFilter<'_>, limit: usize) -> Result<QueryResult> {
+        let symbols = self.graph.symbols();
+        let qualified = pattern.contains('.');
+        let mut ranked = Vec::new();
+        for (visited, idx) in self.graph.topology().node_indices().enumerate() {
+            if !self.checkpoint(visited)? {
+                break;
+            }
+            let node = &self.graph.topology()[idx];
+            if !filter.accepts(node) {
+                continue;
+            }
+            let lang_str = symbols.resolve(&node.lang.0);
+            let convention = self.naming_conventions.get(lang_str).map(|c| c.as_ref());
+            let fqn = self.graph.render_fqn(node, convention);
+            let name = node.name(symbols);
+            let target = if qualified { fqn.as_str() } else { name };
+            if let Some(score) = fuzzy::score(pattern, target) {
+                ranked.push((score, self.hits(&fqn), name.len(), fqn, idx));
+            }
+        }
+
+        ranked.sort_by(|a, b| {
+            b.0.cmp(&a.0)
+                .then(b.1.cmp(&a.1))
+                .then(a.2.cmp(&b.2))
+                .then(a.3.cmp(&b.3))
+        });
+        let matched = ranked
+            .into_iter()
+            .take(limit)
+            .map(|(.., idx)| idx)
+            .collect();
+        Ok(self.finish(matched, vec![], None, false))
+    }
+
+    /// Walk incoming edges from `fqn` up to `max_depth` steps. Members reached
+    /// this way also affect their enclosing types, whose dependents are
+    /// walked in turn. Affected classes and methods are returned ordered by
+    /// module, with one group per module.
+    fn impact(&self, fqn: &str, max_depth: usize, edge_filter: &[EdgeType]) -> Result<QueryResult> {
+        let start = self
+            .graph
+            .find_node(fqn)
+            .ok_or_else(|| NaviscopeError::Parsing(format!("Node not found: {}", fqn)))?;
+        let topology = self.graph.topology();
+        let follows = |edge_type: &EdgeType| {
+            if edge_filter.is_empty() {
+                !matches!(edge_type, EdgeType::Contains | EdgeType::Declares)
+            } else {
+                edge_filter.contains(edge_type)
+            }
+        };
+
+        let mut depth_of: HashMap<NodeIndex, usize> = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+        let mut edges = Vec::new();
+        while let Some(idx) = queue.pop_front() {
+            if !self.checkpoint(depth_of.len())? {
+                break;
+            }
+            let depth = depth_of[&idx];
+            if depth >= max_depth {
+                continue;
+            }
+            for edge in topology.edges_directed(idx, PetDirection::Incoming) {
+                if !follows(&edge.weight().edge_type) {
+                    continue;
+                }
+                let dependent = edge.source();
+                edges.push(QueryResultEdge {
+                    from: Arc::from(self.fqn_of(dependent)),
+                    to: Arc::from(self.fqn_of(idx)),
+                    data: edge.weight().clone(),
+                });
+                let owner = self.enclosing_type(dependent);
+                for affected in std::iter::once(dependent).chain(owner) {
+                    if let Entry::Vacant(e) = depth_of.entry(affected) {
+                        e.insert(depth + 1);
+                        queue.push_back(affected);
+                    }
+                }
+            }
+        }
+
+        let mut affected: Vec<(String, String, NodeIndex)> = depth_of
+            .into_keys()
+            .filter(|&idx| idx != start && is_impact_kind(&topology[idx].kind))
+            .map(|idx| (self.group_key(idx, GroupBy::Module), self.fqn_of(idx), idx))
+            .collect();
+        affected.sort();
+
+        let mut groups: Vec<QueryGroup> = Vec::new();
+        for (module, _, _) in &affected {
+            match groups.last_mut() {
+                Some(group) if group.key == *module => group.count += 1,
+                _ => groups.push(QueryGroup {
+                    key: module.clone(),
+                    count: 1,
+                }),
+            }
+        }
+
+        // Groups count every affected node, the budget limits those rendered
+        let total = self.within_budget(&mut affected);
+        let nodes = affected
+            .into_iter()
+            .map(|(_, _, idx)| self.render_node(&topology[idx]))
+            .collect();
+        let mut result = QueryResult::new(nodes, edges);
+        result.groups = groups;
+        result.total = total;
+        Ok(result)
+    }
+
+    /// Breadth-first search along outgoing edges from `from`, so the first
+    /// chain reaching `to` is a shortest one.
+    fn path(
+        &self,
+        from: &str,
+        to: &str,
+        edge_filter: &[EdgeType],
+        max_depth: usize,
+    ) -> Result<QueryResult> {
+        let find = |fqn: &str| {
+            self.graph
+                .find_node(fqn)
+                .ok_or_else(|| NaviscopeError::Parsing(format!("Node not found: {}", fqn)))
+        };
+        let (start, goal) = (find(from)?, find(to)?);
+        let topology = self.graph.topology();
+
+        // Reached node -> the node and edge it was reached from
+        let mut reached: HashMap<NodeIndex, Option<(NodeIndex, &GraphEdge)>> =
+            HashMap::from([(start, None)]);
+        let mut queue = VecDeque::from([(start, 0)]);
+        while let Some((idx, depth)) = queue.pop_front() {
+            if idx == goal {
+                break;
+            }
+            if !self.checkpoint(reached.len())? {
+                break;
+            }
+            if depth >= max_depth {
+                continue;
+            }
+            for edge in topology.edges_directed(idx, PetDirection::Outgoing) {
+                let weight = edge.weight();
+                if !edge_filter.is_empty() && !edge_filter.contains(&weight.edge_type) {
+                    continue;
+                }
+                if let Entry::Vacant(e) = reached.entry(edge.target()) {
+                    e.insert(Some((idx, weight)));
+                    queue.push_back((edge.target(), depth + 1));
+                }
+            }
+        }
+        if !reached.contains_key(&goal) {
+            return Ok(QueryResult::default());
+        }
+
+        let mut chain = vec![goal];
+        let mut edges = Vec::new();
+        let mut current = goal;
+        while let Some(&Some((previous, data))) = reached.get(&current) {
+            edges.push(QueryResultEdge {
+                from: Arc::from(self.fqn_of(previous)),
+                to: Arc::from(self.fqn_of(current)),
+                data: data.clone(),
+            });
+            chain.push(previous);
+            current = previous;
+        }
+        chain.reverse();
+        edges.reverse();
+        let nodes = chain
+            .into_iter()
+            .map(|idx| self.render_node(&topology[idx]))
+            .collect();
+        Ok(QueryResult::new(nodes, edges))
+    }
+
+    /// Strongly connected components (Tarjan) of the project dependency
+    /// graph lifted to classes or packages. Components with more than one
+    /// member are cycles; the edges between their members are returned too.
+    fn cycles(
+        &self,
+        scope_fqn: Option<&str>,
+        edge_filter: &[EdgeType],
+        packages: bool,
+    ) -> Result<QueryResult> {
+        let topology = self.graph.topology();
+        let scope = scope_fqn
+            .map(|fqn| {
+                self.graph
+                    .find_node(fqn)
+                    .ok_or_else(|| NaviscopeError::Parsing(format!("Node not found: {}", fqn)))
+            })
+            .transpose()?;
+        let unit_of = |idx| {
+            if packages {
+                self.enclosing_or_self(idx, |kind| *kind == NodeKind::Package)
+            } else {
+                self.enclosing_or_self(idx, is_type_kind)
+            }
+        };
+        let mut in_scope: HashMap<NodeIndex, bool> = HashMap::new();
+        let mut in_scope = |unit: NodeIndex| {
+            *in_scope.entry(unit).or_insert_with(|| {
+                topology[unit].source == NodeSource::Project
+                    && scope.is_none_or(|scope| self.is_within(unit, scope))
+            })
+        };
+
+        let mut lifted: HashMap<(NodeIndex, NodeIndex), GraphEdge> = HashMap::new();
+        for (visited, idx) in topology.node_indices().enumerate() {
+            if !self.checkpoint(visited)? {
+                break;
+            }
+            let Some(from) = unit_of(idx) else {
+                continue;
+            };
+            for edge in topology.edges_directed(idx, PetDirection::Outgoing) {
+                let edge_type = &edge.weight().edge_type;
+                let follows = if edge_filter.is_empty() {
+                    *edge_type != EdgeType::Contains
+                } else {
+                    edge_filter.contains(edge_type)
+                };
+                if !follows {
+                    continue;
+                }
+                let Some(to) = unit_of(edge.target()) else {
+                    continue;
+                };
+                if from != to && in_scope(from) && in_scope(to) {
+                    lifted
+                        .entry((from, to))
+                        .or_insert_with(|| edge.weight().clone());
+                }
             }
         }
+
+        let mut dependencies = DiGraph::<NodeIndex, ()>::new();
+        let mut slots: HashMap<NodeIndex, petgraph::graph::NodeIndex> = HashMap::new();
+        for &(from, to) in lifted.keys() {
+            let mut slot = |unit| {
+                *slots
+                    .entry(unit)
+                    .or_insert_with(|| dependencies.add_node(unit))
+            };
+            let (from, to) = (slot(from), slot(to));
+            dependencies.add_edge(from, to, ());
+        }
+
+        let mut cycles: Vec<Vec<(String, NodeIndex)>> = tarjan_scc(&dependencies)
+            .into_iter()
+            .filter(|component| component.len() > 1)
+            .map(|component| {
+                let mut members: Vec<_> = component
+                    .into_iter()
+                    .map(|slot| {
+                        let unit = dependencies[slot];
+                        (self.fqn_of(unit), unit)
+                    })
+                    .collect();
+                members.sort();
+                members
+            })
+            .collect();
+        cycles.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+
+        let mut cycle_of: HashMap<NodeIndex, usize> = HashMap::new();
+        let mut groups = Vec::new();
+        let mut nodes = Vec::new();
+        for (cycle, members) in cycles.iter().enumerate() {
+            groups.push(QueryGroup {
+                key: members[0].0.clone(),
+                count: members.len(),
+            });
+            for (_, unit) in members {
+                cycle_of.insert(*unit, cycle);
+                nodes.push(self.render_node(&topology[*unit]));
+            }
+        }
+
+        let mut edges: Vec<QueryResultEdge> = lifted
+            .into_iter()
+            .filter(|((from, to), _)| {
+                cycle_of
+                    .get(from)
+                    .is_some_and(|cycle| cycle_of.get(to) == Some(cycle))
+            })
+            .map(|((from, to), data)| QueryResultEdge {
+                from: Arc::from(self.fqn_of(from)),
+                to: Arc::from(self.fqn_of(to)),
+                data,
+            })
+            .collect();
+        edges.sort_by(|a, b| a.from.cmp(&b.from).then_with(|| a.to.cmp(&b.to)));
+
+        let mut result = QueryResult::new(nodes, edges);
+        result.groups = groups;
+        Ok(result)
+    }
+
+    /// Project methods of at least `min_tokens` tokens grouped with those
+    /// whose fingerprints are similar enough. Groups are ordered by the size
+    /// of their largest method, members by FQN.
+    fn duplicates(&self, min_tokens: usize) -> Result<QueryResult> {
+        let topology = self.graph.topology();
+        let methods: Vec<(NodeIndex, &CodeFingerprint)> = self
+            .graph
+            .code_fingerprints()
+            .into_iter()
+            .filter(|(idx, fingerprint)| {
+                let node = &topology[*idx];
+                fingerprint.tokens >= min_tokens
+                    && node.source == NodeSource::Project
+                    && !self.graph.is_generated(node)
+            })
+            .collect();
+
+        let mut holders: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (method, (_, fingerprint)) in methods.iter().enumerate() {
+            for &hash in &fingerprint.hashes {
+                holders.entry(hash).or_default().push(method);
+            }
+        }
+        let mut shared: HashMap<(usize, usize), usize> = HashMap::new();
+        for (visited, holders) in holders.values().enumerate() {
+            if !self.checkpoint(visited)? {
+                break;
+            }
+            // Boilerplate most methods contain says nothing about copies
+            if holders.len() > MAX_CLONE_HOLDERS {
+                continue;
+            }
+            for (i, &a) in holders.iter().enumerate() {
+                for &b in &holders[i + 1..] {
+                    *shared.entry((a, b)).or_default() += 1;
+                }
+            }
+        }
+
+        // Union-find over the pairs similar enough
+        let mut parent: Vec<usize> = (0..methods.len()).collect();
+        for ((a, b), common) in shared {
+            let union = methods[a].1.hashes.len() + methods[b].1.hashes.len() - common;
+            if common as f64 >= CLONE_SIMILARITY * union as f64 {
+                let (a, b) = (root(&mut parent, a), root(&mut parent, b));
+                parent[a] = b;
+            }
+        }
+
+        let mut clones: HashMap<usize, Vec<usize>> = HashMap::new();
+        for method in 0..methods.len() {
+            clones
+                .entry(root(&mut parent, method))
+                .or_default()
+                .push(method);
+        }
+        let mut groups: Vec<(usize, Vec<(String, NodeIndex)>)> = clones
+            .into_values()
+            .filter(|members| members.len() > 1)
+            .map(|members| {
+                let tokens = members.iter().map(|&m| methods[m].1.tokens).max();
+                let mut members: Vec<(String, NodeIndex)> = members
+                    .into_iter()
+                    .map(|m| (self.fqn_of(methods[m].0), methods[m].0))
+                    .collect();
+                members.sort();
+                (tokens.unwrap_or(0), members)
+            })
+            .collect();
+        groups.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+        let mut result = QueryResult::default();
+        for (_, members) in groups {
+            result.groups.push(QueryGroup {
+                key: members[0].0.clone(),
+                count: members.len(),
+            });
+            result.nodes.extend(
+                members
+                    .into_iter()
+                    .map(|(_, idx)| self.render_node(&topology[idx])),
+            );
+        }
+        Ok(result)
+    }
+
+    /// Public project types and methods without a known use. Calls are not
+    /// edges of the graph, so a symbol counts as used when a file other than
+    /// its own mentions its name, as well as when an edge reaches it (or, for
+    /// a type, one of its members) from outside.
+    fn dead(&self, scope_fqn: Option<&str>, extra: &[String]) -> Result<QueryResult> {
+        let topology = self.graph.topology();
+        let symbols = self.graph.symbols();
+        let scope = scope_fqn
+            .map(|fqn| {
+                self.graph
+                    .find_node(fqn)
+                    .ok_or_else(|| NaviscopeError::Parsing(format!("Node not found: {}", fqn)))
+            })
+            .transpose()?;
+        let entry_points = DEFAULT_ENTRY_POINTS
+            .iter()
+            .copied()
+            .chain(self.entry_points.iter().map(String::as_str))
+            .chain(extra.iter().map(String::as_str));
+        let (annotations, names): (HashSet<&str>, HashSet<&str>) = {
+            let (annotations, names): (Vec<&str>, Vec<&str>) =
+                entry_points.partition(|entry| entry.starts_with('@'));
+            (
+                annotations.into_iter().map(|a| &a[1..]).collect(),
+                names.into_iter().collect(),
+            )
+        };
+        let is_entry_point = |idx: NodeIndex| {
+            names.contains(topology[idx].name(symbols))
+                || topology
+                    .edges_directed(idx, PetDirection::Outgoing)
+                    .filter(|e| e.weight().edge_type == EdgeType::DecoratedBy)
+                    .any(|e| annotations.contains(topology[e.target()].name(symbols)))
+        };
+
+        let mut candidates = Vec::new();
+        for (visited, idx) in topology.node_indices().enumerate() {
+            if !self.checkpoint(visited)? {
+                break;
+            }
+            let node = &topology[idx];
+            if !(is_type_kind(&node.kind) || node.kind == NodeKind::Method)
+                || node.source != NodeSource::Project
+                || self.graph.is_generated(node)
+                || node
+                    .path(symbols)
+                    .is_none_or(|path| super::safe_delete::is_test_path(Path::new(path)))
+                || scope.is_some_and(|scope| !self.is_within(idx, scope))
+            {
+                continue;
+            }
+            let members = self.subtree(idx);
+            let used = members.iter().any(|&member| is_entry_point(member))
+                || self.mentioned_elsewhere(idx)
+                || members.iter().any(|&member| {
+                    topology
+                        .edges_directed(member, PetDirection::Incoming)
+                        .any(|e| {
+                            !matches!(
+                                e.weight().edge_type,
+                                EdgeType::Contains | EdgeType::Declares
+                            ) && !members.contains(&e.source())
+                        })
+                })
+                || (node.kind == NodeKind::Method && self.overrides(idx));
+            if !used {
+                candidates.push(idx);
+            }
+        }
+
+        // Interface members are public without saying so
+        let is_public = |idx: NodeIndex| {
+            self.render_node(&topology[idx])
+                .modifiers
+                .iter()
+                .any(|m| m == "public")
+                || self
+                    .enclosing_type(idx)
+                    .is_some_and(|owner| topology[owner].kind == NodeKind::Interface)
+        };
+        let dead: HashSet<NodeIndex> = candidates
+            .into_iter()
+            .filter(|&idx| is_public(idx))
+            .collect();
+        let mut matched: Vec<(String, NodeIndex)> = dead
+            .iter()
+            .filter(|&&idx| {
+                self.enclosing_type(idx)
+                    .is_none_or(|owner| !dead.contains(&owner))
+            })
+            .map(|&idx| (self.fqn_of(idx), idx))
+            .collect();
+        matched.sort();
+        let matched = matched.into_iter().map(|(_, idx)| idx).collect();
+        Ok(self.finish(matched, vec![], None, false))
+    }
+
+    /// Test methods with a `Tests` edge to the class at `fqn`, to the class
+    /// of the member at `fqn`, or to a class within the package at `fqn`
+    fn tests_for(&self, fqn: &str) -> Result<QueryResult> {
+        let topology = self.graph.topology();
+        let target = self
+            .graph
+            .find_node(fqn)
+            .ok_or_else(|| NaviscopeError::Parsing(format!("Node not found: {}", fqn)))?;
+        let classes: Vec<NodeIndex> = if is_type_kind(&topology[target].kind) {
+            vec![target]
+        } else if let Some(owner) = self.enclosing_type(target) {
+            vec![owner]
+        } else {
+            self.subtree(target)
+                .into_iter()
+                .filter(|&idx| is_type_kind(&topology[idx].kind))
+                .collect()
+        };
+
+        let mut tests: Vec<(String, NodeIndex)> = Vec::new();
+        let mut edges = Vec::new();
+        for class in classes {
+            for edge in topology.edges_directed(class, PetDirection::Incoming) {
+                if edge.weight().edge_type != EdgeType::Tests {
+                    continue;
+                }
+                let test = self.fqn_of(edge.source());
+                edges.push(QueryResultEdge {
+                    from: Arc::from(test.as_str()),
+                    to: Arc::from(self.fqn_of(class)),
+                    data: edge.weight().clone(),
+                });
+                tests.push((test, edge.source()));
+            }
+        }
+        tests.sort();
+        tests.dedup();
+        edges.sort_by(|a, b| a.from.cmp(&b.from).then_with(|| a.to.cmp(&b.to)));
+        let tests = tests.into_iter().map(|(_, idx)| idx).collect();
+        Ok(self.finish(tests, edges, None, false))
+    }
+
+    /// `idx` and everything it (transitively) contains
+    fn subtree(&self, idx: NodeIndex) -> HashSet<NodeIndex> {
+        let topology = self.graph.topology();
+        let mut seen = HashSet::from([idx]);
+        let mut queue = VecDeque::from([idx]);
+        while let Some(current) = queue.pop_front() {
+            for edge in topology.edges_directed(current, PetDirection::Outgoing) {
+                if edge.weight().edge_type == EdgeType::Contains && seen.insert(edge.target()) {
+                    queue.push_back(edge.target());
+                }
+            }
+        }
+        seen
+    }
+
+    /// Whether a file other than the one declaring `idx` mentions its name
+    fn mentioned_elsewhere(&self, idx: NodeIndex) -> bool {
+        let symbols = self.graph.symbols();
+        let node = &self.graph.topology()[idx];
+        let declared_in = node.path(symbols);
+        symbols
+            .get(node.name(symbols))
+            .and_then(|token| {
+                self.graph
+                    .reference_index()
+                    .get(&naviscope_api::models::symbol::Symbol(token))
+            })
+            .is_some_and(|files| {
+                files
+                    .iter()
+                    .any(|file| Some(symbols.resolve(&file.0)) != declared_in)
+            })
+    }
+
+    /// Whether the method `idx` is declared `@Override` or a supertype of
+    /// its class declares the same member, so that callers reach it through
+    /// the supertype
+    fn overrides(&self, idx: NodeIndex) -> bool {
+        use naviscope_api::models::symbol::FqnReader;
+        let topology = self.graph.topology();
+        let symbols = self.graph.symbols();
+        let fqns = self.graph.fqns();
+        if topology
+            .edges_directed(idx, PetDirection::Outgoing)
+            .any(|e| {
+                e.weight().edge_type == EdgeType::DecoratedBy
+                    && topology[e.target()].name(symbols) == "Override"
+            })
+        {
+            return true;
+        }
+        let atom = |idx: NodeIndex| fqns.resolve_node(topology[idx].id).map(|n| n.name);
+        let (Some(owner), Some(member)) = (self.enclosing_type(idx), atom(idx)) else {
+            return false;
+        };
+        let mut seen = HashSet::from([owner]);
+        let mut queue = VecDeque::from([owner]);
+        while let Some(current) = queue.pop_front() {
+            for edge in topology.edges_directed(current, PetDirection::Outgoing) {
+                match edge.weight().edge_type {
+                    EdgeType::InheritsFrom | EdgeType::Implements if seen.insert(edge.target()) => {
+                        queue.push_back(edge.target());
+                    }
+                    EdgeType::Contains
+                        if current != owner && atom(edge.target()) == Some(member) =>
+                    {
+                        return true;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        false
+    }
+
+    /// Counts per kind and type, fan-in/fan-out over project types and
+    /// callables, the `top` largest classes and most-referenced symbols, and
+    /// with history the `top` types changed most often.
+    fn stats(&self, top: usize) -> Result<QueryResult> {
+        let topology = self.graph.topology();
+        let mut nodes_by_kind: HashMap<String, usize> = HashMap::new();
+        let mut edges_by_type: HashMap<String, usize> = HashMap::new();
+        let mut unresolved_edges = 0;
+        let mut fan_in = Vec::new();
+        let mut fan_out = Vec::new();
+        let mut class_sizes = Vec::new();
+        let mut references = Vec::new();
+        let mut churn = Vec::new();
+        let changes = self.history.as_ref().map(|history| history.churn());
+
+        for (visited, idx) in topology.node_indices().enumerate() {
+            if !self.checkpoint(visited)? {
+                break;
+            }
+            let node = &topology[idx];
+            *nodes_by_kind.entry(node.kind.to_string()).or_default() += 1;
+
+            let mut members = 0;
+            let mut outgoing = 0;
+            for edge in topology.edges_directed(idx, PetDirection::Outgoing) {
+                *edges_by_type
+                    .entry(format!("{:?}", edge.weight().edge_type))
+                    .or_default() += 1;
+                if topology[edge.target()].status == ResolutionStatus::Unresolved {
+                    unresolved_edges += 1;
+                }
+                if edge.weight().edge_type == EdgeType::Contains {
+                    members += 1;
+                } else {
+                    outgoing += 1;
+                }
+            }
+
+            if node.source != NodeSource::Project || !is_impact_kind(&node.kind) {
+                continue;
+            }
+            let incoming = topology
+                .edges_directed(idx, PetDirection::Incoming)
+                .filter(|e| {
+                    !matches!(
+                        e.weight().edge_type,
+                        EdgeType::Contains | EdgeType::Declares
+                    )
+                })
+                .count();
+            fan_in.push(incoming);
+            fan_out.push(outgoing);
+            references.push((incoming, idx));
+            if is_type_kind(&node.kind) {
+                class_sizes.push((members, idx));
+                // Nested types share the churn of their file with the outermost
+                if let Some(changes) = &changes
+                    && self.enclosing_type(idx).is_none()
+                    && let Some(commits) = node
+                        .path(self.graph.symbols())
+                        .and_then(|path| changes.get(Path::new(path)))
+                {
+                    churn.push((*commits, idx));
+                }
+            }
+        }
+
+        let metrics = GraphMetrics {
+            node_count: topology.node_count(),
+            edge_count: topology.edge_count(),
+            nodes_by_kind: sorted_groups(nodes_by_kind),
+            edges_by_type: sorted_groups(edges_by_type),
+            fan_in: Distribution::of(fan_in),
+            fan_out: Distribution::of(fan_out),
+            largest_classes: self.ranked(class_sizes, top),
+            most_referenced: self.ranked(references, top),
+            unresolved_edges,
+            hot_spots: self.ranked(churn, top),
+        };
+        Ok(QueryResult {
+            metrics: Some(metrics),
+            ..Default::default()
+        })
+    }
+
+    /// Last change of the lines of `idx`, when history is enabled
+    fn history_of(&self, idx: NodeIndex) -> Option<naviscope_api::models::NodeHistory> {
+        let history = self.history.as_ref()?;
+        let node = &self.graph.topology()[idx];
+        let range = node.range()?;
+        let path = node.path(self.graph.symbols())?;
+        history.node_history(Path::new(path), range.start_line, range.end_line)
+    }
+
+    /// The `top` entries with the highest non-zero counts, ties by FQN
+    fn ranked(&self, counts: Vec<(usize, NodeIndex)>, top: usize) -> Vec<RankedSymbol> {
+        let mut ranked: Vec<RankedSymbol> = counts
+            .into_iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, idx)| RankedSymbol {
+                fqn: self.fqn_of(idx),
+                kind: self.graph.topology()[idx].kind.clone(),
+                count,
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.fqn.cmp(&b.fqn)));
+        ranked.truncate(top);
+        ranked
+    }
+
+    /// Lines containing `pattern`, reading only the files whose content
+    /// trigrams include all of the pattern's. Each hit is attributed to the
+    /// innermost node enclosing it; those nodes are returned in hit order.
+    fn text(&self, pattern: &str, ignore_case: bool, limit: usize) -> Result<QueryResult> {
+        if pattern.is_empty() {
+            return Err(NaviscopeError::Parsing("Empty search pattern".to_string()));
+        }
+        let wanted = trigrams(pattern);
+        let needle = if ignore_case {
+            pattern.to_ascii_lowercase()
+        } else {
+            pattern.to_string()
+        };
+        let mut files: Vec<&str> = self
+            .graph
+            .file_trigrams()
+            .into_iter()
+            .filter(|(_, file)| may_contain(file, &wanted))
+            .map(|(path, _)| path)
+            .collect();
+        files.sort_unstable();
+
+        let mut hits = Vec::new();
+        let mut nodes = Vec::new();
+        let mut seen = HashSet::new();
+        'files: for path in files {
+            if self.control.is_cancelled() {
+                return Err(NaviscopeError::Cancelled);
+            }
+            if self.out_of_time() {
+                break;
+            }
+            let Ok(content) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            for (line, text) in content.lines().enumerate() {
+                let haystack = if ignore_case {
+                    std::borrow::Cow::Owned(text.to_ascii_lowercase())
+                } else {
+                    std::borrow::Cow::Borrowed(text)
+                };
+                let Some(column) = haystack.find(&needle) else {
+                    continue;
+                };
+                if hits.len() >= limit {
+                    break 'files;
+                }
+                let node = self
+                    .graph
+                    .find_container_node_at(Path::new(path), line, column);
+                if let Some(idx) = node
+                    && seen.insert(idx)
+                {
+                    nodes.push(self.render_node(&self.graph.topology()[idx]));
+                }
+                hits.push(TextHit {
+                    path: path.to_string(),
+                    line,
+                    column,
+                    text: text.trim().to_string(),
+                    node: node.map(|idx| self.fqn_of(idx)),
+                });
+            }
+        }
+        Ok(QueryResult {
+            nodes,
+            hits,
+            ..Default::default()
+        })
+    }
+
+    /// Details of `node` within `limits`: long signatures shortened and
+    /// members beyond the limit only counted
+    fn abridged(
+        &self,
+        idx: NodeIndex,
+        mut node: DisplayGraphNode,
+        limits: &DetailLimits,
+    ) -> QueryResult {
+        let topology = self.graph.topology();
+        let shorten = |node: &mut DisplayGraphNode| match node
+            .signature
+            .as_deref()
+            .and_then(|s| limits.abridge(s))
+        {
+            Some(short) => {
+                node.signature = Some(short);
+                true
+            }
+            None => false,
+        };
+        let mut abridged = shorten(&mut node);
+
+        // Like `ls`: the members of a symbol, or the symbols a file declares
+        let mut members: Vec<NodeIndex> = topology
+            .edges_directed(idx, PetDirection::Outgoing)
+            .filter(|e| {
+                matches!(
+                    e.weight().edge_type,
+                    EdgeType::Contains | EdgeType::Declares
+                )
+            })
+            .map(|e| e.target())
+            .collect();
+        let members = if members.is_empty() {
+            None
+        } else {
+            // Declaration order, members without a location last
+            members.sort_by_key(|&m| {
+                let location = topology[m].location.as_ref();
+                (
+                    location.is_none(),
+                    location.map(|l| (l.range.start_line, l.range.start_col)),
+                )
+            });
+            let mut kinds = HashMap::new();
+            for &m in &members {
+                *kinds.entry(topology[m].kind().to_string()).or_insert(0) += 1;
+            }
+            let mut listed: Vec<DisplayGraphNode> = members
+                .iter()
+                .take(limits.members)
+                .map(|&m| self.render_node(&topology[m]))
+                .collect();
+            for member in &mut listed {
+                abridged |= shorten(member);
+            }
+            abridged |= members.len() > listed.len();
+            Some(MemberSummary {
+                total: members.len(),
+                kinds: sorted_groups(kinds),
+                listed,
+            })
+        };
+
+        QueryResult {
+            members,
+            abridged,
+            ..QueryResult::new(vec![node], vec![])
+        }
+    }
+
+    fn fqn_of(&self, idx: NodeIndex) -> String {
+        let node = &self.graph.topology()[idx];
+        let lang = self.graph.symbols().resolve(&node.lang.0);
+        let convention = self.naming_conventions.get(lang).map(|c| c.as_ref());
+        self.graph.render_fqn(node, convention)
+    }
+
+    /// Nearest class-like node containing `idx`
+    fn enclosing_type(&self, idx: NodeIndex) -> Option<NodeIndex> {
+        let topology = self.graph.topology();
+        let mut current = idx;
+        let mut visited = HashSet::new();
+        while visited.insert(current) {
+            let parent = topology
+                .edges_directed(current, PetDirection::Incoming)
+                .find(|e| e.weight().edge_type == EdgeType::Contains)
+                .map(|e| e.source())?;
+            if is_type_kind(&topology[parent].kind) {
+                return Some(parent);
+            }
+            current = parent;
+        }
+        None
+    }
+
+    /// `idx` itself if its kind matches, otherwise its nearest container that does
+    fn enclosing_or_self(
+        &self,
+        idx: NodeIndex,
+        matches: impl Fn(&NodeKind) -> bool,
+    ) -> Option<NodeIndex> {
+        let topology = self.graph.topology();
+        let mut current = idx;
+        let mut visited = HashSet::new();
+        while visited.insert(current) {
+            if matches(&topology[current].kind) {
+                return Some(current);
+            }
+            current = topology
+                .edges_directed(current, PetDirection::Incoming)
+                .find(|e| e.weight().edge_type == EdgeType::Contains)
+                .map(|e| e.source())?;
+        }
+        None
+    }
+
+    /// Whether `idx` is `scope` or contained in it
+    fn is_within(&self, idx: NodeIndex, scope: NodeIndex) -> bool {
+        let topology = self.graph.topology();
+        let mut current = idx;
+        let mut visited = HashSet::new();
+        while visited.insert(current) {
+            if current == scope {
+                return true;
+            }
+            let Some(parent) = topology
+                .edges_directed(current, PetDirection::Incoming)
+                .find(|e| e.weight().edge_type == EdgeType::Contains)
+                .map(|e| e.source())
+            else {
+                break;
+            };
+            current = parent;
+        }
+        false
+    }
+
+    /// Render matched nodes, or reduce them to counts when aggregation is requested.
+    fn finish(
+        &self,
+        mut matched: Vec<NodeIndex>,
+        mut edges: Vec<QueryResultEdge>,
+        group_by: Option<GroupBy>,
+        count: bool,
+    ) -> QueryResult {
+        let Some(group_by) = group_by else {
+            if count {
+                return QueryResult::aggregated(matched.len(), vec![]);
+            }
+            // Only the nodes within the budget are rendered
+            let total = self.within_budget(&mut matched);
+            self.within_budget(&mut edges);
+            let topology = self.graph.topology();
+            let nodes = matched
+                .into_iter()
+                .map(|idx| self.render_node(&topology[idx]))
+                .collect();
+            let mut result = QueryResult::new(nodes, edges);
+            result.total = total;
+            return result;
+        };
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for &idx in &matched {
+            *counts.entry(self.group_key(idx, group_by)).or_default() += 1;
+        }
+
+        QueryResult::aggregated(matched.len(), sorted_groups(counts))
+    }
+
+    fn group_key(&self, idx: NodeIndex, group_by: GroupBy) -> String {
+        let topology = self.graph.topology();
+        let container_kind = match group_by {
+            GroupBy::Kind => return topology[idx].kind.to_string(),
+            GroupBy::Package => NodeKind::Package,
+            GroupBy::Module => NodeKind::Module,
+        };
+
+        // Walk up the containment chain to the nearest enclosing package/module
+        let mut current = idx;
+        let mut visited = HashSet::new();
+        while visited.insert(current) {
+            let parent = topology
+                .edges_directed(current, PetDirection::Incoming)
+                .find(|e| e.weight().edge_type == EdgeType::Contains)
+                .map(|e| e.source());
+            let Some(parent) = parent else {
+                break;
+            };
+            let node = &topology[parent];
+            if node.kind == container_kind {
+                let lang = self.graph.symbols().resolve(&node.lang.0);
+                let convention = self.naming_conventions.get(lang).map(|c| c.as_ref());
+                return self.graph.render_fqn(node, convention);
+            }
+            current = parent;
+        }
+        NO_GROUP.to_string()
     }
 
     fn traverse_neighbors(
@@ -173,9 +1455,8 @@ where
         fqn: &str,
         edge_filter: &[EdgeType],
         dir: PetDirection,
-        kind_filter: &[NodeKind],
-        source_filter: &[naviscope_api::models::graph::NodeSource],
-    ) -> Result<QueryResult> {
+        filter: NodeFilter<'_>,
+    ) -> Result<(Vec<NodeIndex>, Vec<QueryResultEdge>)> {
         let start_idx = self
             .graph
             .find_node(fqn)
@@ -186,16 +1467,19 @@ where
         let topology = self.graph.topology();
         let mut edges = topology.neighbors_directed(start_idx, dir).detach();
 
+        let mut visited = 0;
         while let Some((edge_idx, neighbor_idx)) = edges.next(topology) {
+            if !self.checkpoint(visited)? {
+                break;
+            }
+            visited += 1;
             let edge_data = &topology[edge_idx];
             if edge_filter.is_empty() || edge_filter.contains(&edge_data.edge_type) {
                 let neighbor_node = &topology[neighbor_idx];
                 let start_node = &topology[start_idx];
 
-                if (kind_filter.is_empty() || kind_filter.contains(&neighbor_node.kind))
-                    && (source_filter.is_empty() || source_filter.contains(&neighbor_node.source))
-                {
-                    nodes.push(self.render_node(neighbor_node));
+                if filter.accepts(neighbor_node) {
+                    nodes.push(neighbor_idx);
 
                     let symbols = self.graph.symbols();
                     let start_lang = symbols.resolve(&start_node.lang.0);
@@ -228,6 +1512,1141 @@ where
             }
         }
 
-        Ok(QueryResult::new(nodes, edges_result))
+        Ok((nodes, edges_result))
+    }
+}
+
+/// Representative of the set of `i` in a union-find forest
+fn root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Groups by descending count, ties by key
+fn sorted_groups(counts: HashMap<String, usize>) -> Vec<QueryGroup> {
+    let mut groups: Vec<QueryGroup> = counts
+        .into_iter()
+        .map(|(key, count)| QueryGroup { key, count })
+        .collect();
+    groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+    groups
+}
+
+fn is_type_kind(kind: &NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Class | NodeKind::Interface | NodeKind::Enum | NodeKind::Annotation
+    )
+}
+
+/// Kinds reported by impact analysis: types and callables
+fn is_impact_kind(kind: &NodeKind) -> bool {
+    is_type_kind(kind) || matches!(kind, NodeKind::Method | NodeKind::Constructor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::builder::CodeGraphBuilder;
+    use crate::model::{CodeGraph, GraphEdge};
+    use crate::test_support::{node, sample_graph};
+    use naviscope_api::models::graph::{NodeSource, ResolutionStatus};
+
+    /// Renders nodes with their dotted FQN as id
+    struct IdPresenter;
+
+    impl NodePresenter for IdPresenter {
+        fn render_display_node(
+            &self,
+            node: &crate::model::GraphNode,
+            fqns: &dyn naviscope_api::models::symbol::FqnReader,
+        ) -> DisplayGraphNode {
+            use naviscope_plugin::NamingConvention;
+            DisplayGraphNode {
+                id: naviscope_plugin::StandardNamingConvention.render_fqn(node.id, fqns),
+                symbol_id: String::new(),
+                generated: false,
+                name: fqns.resolve_atom(node.name).to_string(),
+                kind: node.kind.clone(),
+                lang: fqns.resolve_atom(node.lang).to_string(),
+                source: node.source.clone(),
+                status: node.status,
+                location: None,
+                detail: None,
+                signature: None,
+                modifiers: vec![],
+                children: None,
+            }
+        }
+    }
+
+    fn run(graph: &CodeGraph, query: GraphQuery) -> QueryResult {
+        QueryEngine::new(graph, |_| None, HashMap::new())
+            .execute(&query)
+            .unwrap()
+    }
+
+    #[test]
+    fn find_groups_matches_by_package() {
+        let graph = sample_graph();
+        let result = run(
+            &graph,
+            GraphQuery::Find {
+                pattern: "^com\\.app\\.".to_string(),
+                kind: vec![NodeKind::Class, NodeKind::Interface],
+                sources: vec![],
+                statuses: vec![],
+                modifiers: vec![],
+                limit: 1,
+                group_by: Some(GroupBy::Package),
+                count: false,
+            },
+        );
+
+        assert!(result.nodes.is_empty());
+        assert_eq!(result.total, Some(3));
+        assert_eq!(
+            result.groups,
+            vec![
+                QueryGroup {
+                    key: "com.app".to_string(),
+                    count: 2
+                },
+                QueryGroup {
+                    key: "com.app.sub".to_string(),
+                    count: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ls_groups_roots_by_kind() {
+        let graph = sample_graph();
+        let result = run(
+            &graph,
+            GraphQuery::Ls {
+                fqn: None,
+                kind: vec![],
+                sources: vec![],
+                statuses: vec![],
+                modifiers: vec![],
+                group_by: Some(GroupBy::Kind),
+                count: false,
+            },
+        );
+
+        assert_eq!(result.total, Some(2));
+        assert_eq!(
+            result.groups,
+            vec![QueryGroup {
+                key: "package".to_string(),
+                count: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn count_ignores_limit_and_returns_total_only() {
+        let graph = sample_graph();
+        let result = run(
+            &graph,
+            GraphQuery::Find {
+                pattern: "^com\\.app".to_string(),
+                kind: vec![],
+                sources: vec![],
+                statuses: vec![],
+                modifiers: vec![],
+                limit: 1,
+                group_by: None,
+                count: true,
+            },
+        );
+
+        assert_eq!(result.total, Some(6));
+        assert!(result.groups.is_empty());
+        assert!(result.nodes.is_empty());
+    }
+
+    #[test]
+    fn source_and_status_filters_restrict_find_and_deps() {
+        let mut builder = CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            Language::new("java".to_string()),
+            Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        let a = builder.add_node(node("com.app.Text", NodeKind::Class));
+        let external = |fqn: &str, status| crate::indexing::IndexNode {
+            source: NodeSource::External,
+            status,
+            ..node(fqn, NodeKind::Class)
+        };
+        let stub = builder.add_node(external("java.text.Text", ResolutionStatus::Stubbed));
+        let missing = builder.add_node(external("org.lib.Text", ResolutionStatus::Unresolved));
+        builder.add_edge(a, stub, GraphEdge::new(EdgeType::TypedAs));
+        builder.add_edge(a, missing, GraphEdge::new(EdgeType::TypedAs));
+        let graph = builder.build();
+
+        let presenter: Arc<dyn NodePresenter> = Arc::new(IdPresenter);
+        let engine = QueryEngine::new(&graph, |_| Some(presenter.clone()), HashMap::new());
+        let ids = |query: GraphQuery| {
+            let mut ids: Vec<String> = engine
+                .execute(&query)
+                .unwrap()
+                .nodes
+                .into_iter()
+                .map(|n| n.id.to_string())
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        let find = |sources, statuses| GraphQuery::Find {
+            pattern: "Text$".to_string(),
+            kind: vec![],
+            sources,
+            statuses,
+            modifiers: vec![],
+            limit: 10,
+            group_by: None,
+            count: false,
+        };
+        assert_eq!(
+            ids(find(vec![NodeSource::Project], vec![])),
+            ["com.app.Text"]
+        );
+        assert_eq!(
+            ids(find(vec![], vec![ResolutionStatus::Stubbed])),
+            ["java.text.Text"]
+        );
+
+        let deps = |sources, statuses| GraphQuery::Deps {
+            fqn: "com.app.Text".to_string(),
+            rev: false,
+            edge_types: vec![],
+            sources,
+            statuses,
+            group_by: None,
+            count: false,
+        };
+        assert_eq!(ids(deps(vec![], vec![])).len(), 2);
+        assert_eq!(
+            ids(deps(
+                vec![NodeSource::External],
+                vec![ResolutionStatus::Unresolved]
+            )),
+            ["org.lib.Text"]
+        );
+        assert!(ids(deps(vec![NodeSource::Project], vec![])).is_empty());
+    }
+
+    #[test]
+    fn queries_over_budget_return_part_of_their_answer() {
+        let mut builder = CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            Language::new("java".to_string()),
+            Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        let object = builder.add_node(node("java.lang.Object", NodeKind::Class));
+        for i in 0..5 {
+            let class = builder.add_node(node(&format!("com.app.C{}", i), NodeKind::Class));
+            builder.add_edge(class, object, GraphEdge::new(EdgeType::InheritsFrom));
+        }
+        let graph = builder.build();
+        let presenter: Arc<dyn NodePresenter> = Arc::new(IdPresenter);
+        let engine = |budget| {
+            QueryEngine::new(&graph, |_| Some(presenter.clone()), HashMap::new())
+                .with_budget(budget)
+        };
+        let dependents = GraphQuery::Deps {
+            fqn: "java.lang.Object".to_string(),
+            rev: true,
+            edge_types: vec![],
+            sources: vec![],
+            statuses: vec![],
+            group_by: None,
+            count: false,
+        };
+
+        let result = engine(QueryBudget {
+            timeout: None,
+            max_results: Some(2),
+        })
+        .execute(&dependents)
+        .unwrap();
+        assert!(result.truncated);
+        assert_eq!((result.nodes.len(), result.edges.len()), (2, 2));
+        assert_eq!(result.total, Some(5));
+
+        // Out of time before the first neighbor
+        let result = engine(QueryBudget {
+            timeout: Some(Duration::ZERO),
+            max_results: None,
+        })
+        .execute(&dependents)
+        .unwrap();
+        assert!(result.truncated);
+        assert!(result.nodes.is_empty());
+
+        let result = engine(QueryBudget::default()).execute(&dependents).unwrap();
+        assert!(!result.truncated);
+        assert_eq!(result.nodes.len(), 5);
+    }
+
+    #[test]
+    fn impact_follows_dependents_through_enclosing_types() {
+        let mut builder = CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            Language::new("java".to_string()),
+            Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        let module = builder.add_node(node("app", NodeKind::Module));
+        let pkg = builder.add_node(node("com.app", NodeKind::Package));
+        let a = builder.add_node(node("com.app.A", NodeKind::Class));
+        let b = builder.add_node(node("com.app.B", NodeKind::Class));
+        let method = builder.add_node(node("com.app.A#run()", NodeKind::Method));
+        let c = builder.add_node(node("other.C", NodeKind::Class));
+        for (from, to) in [(module, pkg), (pkg, a), (pkg, b), (a, method)] {
+            builder.add_edge(from, to, GraphEdge::new(EdgeType::Contains));
+        }
+        builder.add_edge(method, b, GraphEdge::new(EdgeType::TypedAs));
+        builder.add_edge(c, a, GraphEdge::new(EdgeType::InheritsFrom));
+        let graph = builder.build();
+
+        let presenter: Arc<dyn NodePresenter> = Arc::new(IdPresenter);
+        let engine = QueryEngine::new(&graph, |_| Some(presenter.clone()), HashMap::new());
+        let impact = |max_depth| {
+            engine
+                .execute(&GraphQuery::Impact {
+                    fqn: "com.app.B".to_string(),
+                    max_depth,
+                    edge_types: vec![],
+                })
+                .unwrap()
+        };
+
+        let result = impact(5);
+        let ids: Vec<&str> = result.nodes.iter().map(|n| n.id.as_ref()).collect();
+        assert_eq!(ids, vec!["other.C", "com.app.A", "com.app.A#run()"]);
+        assert_eq!(
+            result.groups,
+            vec![
+                QueryGroup {
+                    key: "(none)".to_string(),
+                    count: 1
+                },
+                QueryGroup {
+                    key: "app".to_string(),
+                    count: 2
+                },
+            ]
+        );
+        assert_eq!(result.edges.len(), 2);
+
+        assert_eq!(impact(1).nodes.len(), 2);
+    }
+
+    #[test]
+    fn impact_skips_the_declaring_file() {
+        use naviscope_plugin::GraphOp;
+
+        let path = "/p/src/main/java/com/app/A.java";
+        let mut builder = CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            Language::new("java".to_string()),
+            Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        builder
+            .apply_ops(vec![
+                GraphOp::UpdateFile {
+                    metadata: crate::model::source::SourceFile::new(path.into(), 1, 0),
+                },
+                GraphOp::AddNode {
+                    data: Some(crate::test_support::node_at(
+                        "com.app.A",
+                        NodeKind::Class,
+                        path,
+                    )),
+                },
+            ])
+            .unwrap();
+        let graph = builder.build();
+
+        let presenter: Arc<dyn NodePresenter> = Arc::new(IdPresenter);
+        let engine = QueryEngine::new(&graph, |_| Some(presenter.clone()), HashMap::new());
+        let result = engine
+            .execute(&GraphQuery::Impact {
+                fqn: "com.app.A".to_string(),
+                max_depth: 5,
+                edge_types: vec![],
+            })
+            .unwrap();
+        assert!(result.nodes.is_empty());
+        assert!(result.edges.is_empty());
+    }
+
+    #[test]
+    fn cycles_are_reported_per_class_and_per_package() {
+        let mut builder = CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            Language::new("java".to_string()),
+            Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        let p = builder.add_node(node("p", NodeKind::Package));
+        let q = builder.add_node(node("q", NodeKind::Package));
+        let a = builder.add_node(node("p.A", NodeKind::Class));
+        let b = builder.add_node(node("p.B", NodeKind::Class));
+        let c = builder.add_node(node("q.C", NodeKind::Class));
+        let d = builder.add_node(node("q.D", NodeKind::Class));
+        let run = builder.add_node(node("p.A#run()", NodeKind::Method));
+        for (from, to) in [(p, a), (p, b), (q, c), (q, d), (a, run)] {
+            builder.add_edge(from, to, GraphEdge::new(EdgeType::Contains));
+        }
+        builder.add_edge(run, b, GraphEdge::new(EdgeType::TypedAs));
+        builder.add_edge(b, c, GraphEdge::new(EdgeType::InheritsFrom));
+        builder.add_edge(c, a, GraphEdge::new(EdgeType::Implements));
+        builder.add_edge(d, a, GraphEdge::new(EdgeType::TypedAs));
+        let graph = builder.build();
+
+        let presenter: Arc<dyn NodePresenter> = Arc::new(IdPresenter);
+        let engine = QueryEngine::new(&graph, |_| Some(presenter.clone()), HashMap::new());
+        let cycles = |scope_fqn: Option<&str>, edge_types, packages| {
+            engine
+                .execute(&GraphQuery::Cycles {
+                    scope_fqn: scope_fqn.map(str::to_string),
+                    edge_types,
+                    packages,
+                })
+                .unwrap()
+        };
+
+        let classes = cycles(None, vec![], false);
+        let ids: Vec<&str> = classes.nodes.iter().map(|n| n.id.as_ref()).collect();
+        assert_eq!(ids, vec!["p.A", "p.B", "q.C"]);
+        assert_eq!(
+            classes.groups,
+            vec![QueryGroup {
+                key: "p.A".to_string(),
+                count: 3
+            }]
+        );
+        assert_eq!(classes.edges.len(), 3);
+
+        let packages = cycles(None, vec![], true);
+        let ids: Vec<&str> = packages.nodes.iter().map(|n| n.id.as_ref()).collect();
+        assert_eq!(ids, vec!["p", "q"]);
+
+        assert!(cycles(Some("p"), vec![], false).nodes.is_empty());
+        assert!(
+            cycles(None, vec![EdgeType::InheritsFrom], false)
+                .nodes
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn path_follows_the_shortest_chain() {
+        let mut builder = CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            Language::new("java".to_string()),
+            Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        let a = builder.add_node(node("p.A", NodeKind::Class));
+        let run = builder.add_node(node("p.A#run()", NodeKind::Method));
+        let b = builder.add_node(node("p.B", NodeKind::Class));
+        let c = builder.add_node(node("q.C", NodeKind::Class));
+        builder.add_edge(a, run, GraphEdge::new(EdgeType::Contains));
+        builder.add_edge(run, b, GraphEdge::new(EdgeType::TypedAs));
+        builder.add_edge(b, c, GraphEdge::new(EdgeType::InheritsFrom));
+        builder.add_edge(a, c, GraphEdge::new(EdgeType::Implements));
+        let graph = builder.build();
+
+        let presenter: Arc<dyn NodePresenter> = Arc::new(IdPresenter);
+        let engine = QueryEngine::new(&graph, |_| Some(presenter.clone()), HashMap::new());
+        let path = |from: &str, to: &str, edge_types, max_depth| {
+            let result = engine
+                .execute(&GraphQuery::Path {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    edge_types,
+                    max_depth,
+                })
+                .unwrap();
+            let ids: Vec<String> = result.nodes.iter().map(|n| n.id.clone()).collect();
+            let edges: Vec<EdgeType> = result
+                .edges
+                .iter()
+                .map(|e| e.data.edge_type.clone())
+                .collect();
+            (ids, edges)
+        };
+
+        assert_eq!(
+            path("p.A", "q.C", vec![], 10),
+            (
+                vec!["p.A".to_string(), "q.C".to_string()],
+                vec![EdgeType::Implements]
+            )
+        );
+        assert_eq!(
+            path(
+                "p.A",
+                "q.C",
+                vec![
+                    EdgeType::Contains,
+                    EdgeType::TypedAs,
+                    EdgeType::InheritsFrom
+                ],
+                10
+            ),
+            (
+                vec![
+                    "p.A".to_string(),
+                    "p.A#run()".to_string(),
+                    "p.B".to_string(),
+                    "q.C".to_string()
+                ],
+                vec![
+                    EdgeType::Contains,
+                    EdgeType::TypedAs,
+                    EdgeType::InheritsFrom
+                ]
+            )
+        );
+        // Too long for the depth limit, and edges are not followed backwards
+        assert!(path("p.A", "p.B", vec![], 1).0.is_empty());
+        assert!(path("q.C", "p.A", vec![], 10).0.is_empty());
+        assert!(
+            engine
+                .execute(&GraphQuery::Path {
+                    from: "p.A".to_string(),
+                    to: "p.Missing".to_string(),
+                    edge_types: vec![],
+                    max_depth: 10,
+                })
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn duplicates_group_methods_with_similar_fingerprints() {
+        use crate::indexing::clones::method_fingerprints;
+        use naviscope_api::models::{DisplaySymbolLocation, Range};
+        use naviscope_plugin::GraphOp;
+
+        let path = std::path::PathBuf::from("/p/Orders.java");
+        let total = "  int total(List<Order> orders) {\n    int sum = 0;\n    for (Order o : orders) { if (o.paid()) { sum += o.amount() * 100; } }\n    return sum;\n  }\n";
+        let source = format!(
+            "class Orders {{\n{}{}{}}}\n",
+            total,
+            total
+                .replace("total", "refunds")
+                .replace("sum", "acc")
+                .replace("paid", "refunded"),
+            "  void log(String message) { if (enabled) { System.out.println(\"[orders] \" + message); } }\n",
+        );
+        let methods = [("total()", 1, 5), ("refunds()", 6, 10), ("log()", 11, 12)];
+
+        let mut builder = CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            Language::new("java".to_string()),
+            Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        builder
+            .apply_op(GraphOp::UpdateFile {
+                metadata: crate::model::source::SourceFile::new(path.clone(), 0, 0),
+            })
+            .unwrap();
+        let ops: Vec<GraphOp> = methods
+            .iter()
+            .map(|&(name, start_line, end_line)| {
+                let mut n = node(&format!("p.Orders#{}", name), NodeKind::Method);
+                n.location = Some(DisplaySymbolLocation {
+                    path: path.to_string_lossy().to_string(),
+                    range: Range {
+                        start_line,
+                        start_col: 2,
+                        end_line,
+                        end_col: 0,
+                    },
+                    selection_range: None,
+                });
+                GraphOp::AddNode { data: Some(n) }
+            })
+            .collect();
+        let fingerprints = method_fingerprints(&path, &source, &ops);
+        assert_eq!(fingerprints.len(), 3);
+        for op in ops {
+            builder.apply_op(op).unwrap();
+        }
+        builder
+            .apply_op(GraphOp::UpdateContent {
+                path: Arc::from(path.as_path()),
+                trigrams: vec![],
+                fingerprints,
+            })
+            .unwrap();
+        let graph = builder.build();
+
+        let presenter: Arc<dyn NodePresenter> = Arc::new(IdPresenter);
+        let engine = QueryEngine::new(&graph, |_| Some(presenter.clone()), HashMap::new());
+        let duplicates = |min_tokens| {
+            engine
+                .execute(&GraphQuery::Duplicates { min_tokens })
+                .unwrap()
+        };
+        let result = duplicates(20);
+        let ids: Vec<&str> = result.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["p.Orders#refunds()", "p.Orders#total()"]);
+        assert_eq!(
+            result.groups,
+            [QueryGroup {
+                key: "p.Orders#refunds()".to_string(),
+                count: 2,
+            }]
+        );
+        assert!(duplicates(500).nodes.is_empty());
+    }
+
+    #[test]
+    fn text_hits_map_to_enclosing_nodes() {
+        use naviscope_api::models::{DisplaySymbolLocation, Range};
+        use naviscope_plugin::GraphOp;
+
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("Repo.java");
+        let other = dir.path().join("Other.java");
+        let repo_src =
+            "class Repo {\n  void load() {\n    sql(\"SELECT * FROM orders\");\n  }\n}\n";
+        std::fs::write(&repo, repo_src).unwrap();
+        std::fs::write(&other, "class Other {}\n").unwrap();
+
+        let mut builder = CodeGraphBuilder::new();
+        let located = |fqn: &str, kind, start_line, end_line| {
+            let mut n = node(fqn, kind);
+            n.location = Some(DisplaySymbolLocation {
+                path: repo.to_string_lossy().to_string(),
+                range: Range {
+                    start_line,
+                    start_col: 0,
+                    end_line,
+                    end_col: 1,
+                },
+                selection_range: None,
+            });
+            n
+        };
+        builder.add_node(located("p.Repo", NodeKind::Class, 0, 4));
+        builder.add_node(located("p.Repo#load()", NodeKind::Method, 1, 3));
+        for (path, content) in [(&repo, repo_src), (&other, "class Other {}\n")] {
+            builder
+                .apply_op(GraphOp::UpdateFile {
+                    metadata: crate::model::source::SourceFile::new(path.clone(), 0, 0),
+                })
+                .unwrap();
+            builder
+                .apply_op(GraphOp::UpdateContent {
+                    path: Arc::from(path.as_path()),
+                    trigrams: trigrams(content),
+                    fingerprints: vec![],
+                })
+                .unwrap();
+        }
+        let graph = builder.build();
+
+        let presenter: Arc<dyn NodePresenter> = Arc::new(IdPresenter);
+        let engine = QueryEngine::new(&graph, |_| Some(presenter.clone()), HashMap::new());
+        let search = |pattern: &str, ignore_case| {
+            engine
+                .execute(&GraphQuery::Text {
+                    pattern: pattern.to_string(),
+                    ignore_case,
+                    limit: 10,
+                })
+                .unwrap()
+        };
+        let result = search("select * from", true);
+        assert_eq!(result.hits.len(), 1);
+        let hit = &result.hits[0];
+        assert_eq!((hit.line, hit.column), (2, 9));
+        assert_eq!(hit.text, "sql(\"SELECT * FROM orders\");");
+        assert_eq!(hit.node.as_deref(), Some("p.Repo#load()"));
+        assert_eq!(result.nodes[0].id, "p.Repo#load()");
+
+        assert!(search("select * from", false).hits.is_empty());
+        assert_eq!(search("class", false).hits.len(), 2);
+    }
+
+    #[test]
+    fn cat_with_limits_shortens_signatures_and_summarizes_members() {
+        use naviscope_api::models::DisplaySymbolLocation;
+        use naviscope_api::models::symbol::Range;
+
+        /// Gives every node a long generic signature
+        struct SignaturePresenter;
+
+        impl NodePresenter for SignaturePresenter {
+            fn render_display_node(
+                &self,
+                node: &crate::model::GraphNode,
+                fqns: &dyn naviscope_api::models::symbol::FqnReader,
+            ) -> DisplayGraphNode {
+                let mut display = IdPresenter.render_display_node(node, fqns);
+                display.signature = Some(format!("{}(Map<String, List<Long>>)", display.name));
+                display
+            }
+        }
+
+        let mut builder = CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            Language::new("java".to_string()),
+            Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        let class = builder.add_node(node("com.app.A", NodeKind::Class));
+        for (name, line) in [("c", 30), ("a", 10), ("b", 20)] {
+            let mut method = node(&format!("com.app.A#{}()", name), NodeKind::Method);
+            method.name = name.to_string();
+            method.location = Some(DisplaySymbolLocation {
+                path: "/p/A.java".to_string(),
+                range: Range {
+                    start_line: line,
+                    start_col: 0,
+                    end_line: line,
+                    end_col: 1,
+                },
+                selection_range: None,
+            });
+            let method = builder.add_node(method);
+            builder.add_edge(class, method, GraphEdge::new(EdgeType::Contains));
+        }
+        let graph = builder.build();
+        let presenter: Arc<dyn NodePresenter> = Arc::new(SignaturePresenter);
+        let engine = QueryEngine::new(&graph, |_| Some(presenter.clone()), HashMap::new());
+        let cat = |limits| {
+            engine
+                .execute(&GraphQuery::Cat {
+                    fqn: "com.app.A".to_string(),
+                    limits,
+                })
+                .unwrap()
+        };
+
+        let full = cat(None);
+        assert!(full.members.is_none());
+        assert!(!full.abridged);
+
+        let result = cat(Some(DetailLimits {
+            signature_chars: 20,
+            members: 2,
+        }));
+        assert!(result.abridged);
+        assert_eq!(result.nodes[0].signature.as_deref(), Some("A(Map<…>)"));
+        let members = result.members.unwrap();
+        assert_eq!(members.total, 3);
+        assert_eq!(
+            members.kinds,
+            [QueryGroup {
+                key: "method".to_string(),
+                count: 3
+            }]
+        );
+        let listed: Vec<_> = members
+            .listed
+            .iter()
+            .map(|m| m.signature.as_deref().unwrap())
+            .collect();
+        assert_eq!(listed, ["a(Map<…>)", "b(Map<…>)"]);
+    }
+
+    #[test]
+    fn fuzzy_ranks_camel_hump_matches_first() {
+        let mut builder = CodeGraphBuilder::new();
+        for fqn in [
+            "com.app.UnusedRecordCatalog",
+            "com.app.UserController",
+            "com.app.UserControllerTest",
+            "com.app.Order",
+        ] {
+            builder.add_node(node(fqn, NodeKind::Class));
+        }
+        builder.add_node(node("com.app.UserController#usrCtl", NodeKind::Field));
+        let graph = builder.build();
+
+        let presenter: Arc<dyn NodePresenter> = Arc::new(IdPresenter);
+        let engine = QueryEngine::new(&graph, |_| Some(presenter.clone()), HashMap::new());
+        let search = |pattern: &str, kind: Vec<NodeKind>| {
+            engine
+                .execute(&GraphQuery::Fuzzy {
+                    pattern: pattern.to_string(),
+                    kind,
+                    sources: vec![],
+                    statuses: vec![],
+                    limit: 10,
+                })
+                .unwrap()
+                .nodes
+                .into_iter()
+                .map(|n| n.id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            search("UsrCtl", vec![NodeKind::Class]),
+            vec![
+                "com.app.UserController",
+                "com.app.UserControllerTest",
+                "com.app.UnusedRecordCatalog",
+            ]
+        );
+        assert_eq!(search("UsrCtl", vec![])[0], "com.app.UserController#usrCtl");
+        assert_eq!(search("app.Ord", vec![])[0], "com.app.Order");
+    }
+
+    #[test]
+    fn popular_symbols_win_ties() {
+        let mut builder = CodeGraphBuilder::new();
+        for fqn in ["com.a.Order", "com.b.Order", "com.c.Order"] {
+            builder.add_node(node(fqn, NodeKind::Class));
+        }
+        let graph = builder.build();
+
+        let presenter: Arc<dyn NodePresenter> = Arc::new(IdPresenter);
+        let popularity = Arc::new(Popularity::from([("com.b.Order".to_string(), 3)]));
+        let engine = QueryEngine::new(&graph, |_| Some(presenter.clone()), HashMap::new())
+            .with_popularity(popularity);
+        let first = |query: GraphQuery| engine.execute(&query).unwrap().nodes[0].id.clone();
+
+        assert_eq!(
+            first(GraphQuery::Fuzzy {
+                pattern: "Order".to_string(),
+                kind: vec![],
+                sources: vec![],
+                statuses: vec![],
+                limit: 10,
+            }),
+            "com.b.Order"
+        );
+        assert_eq!(
+            first(GraphQuery::Find {
+                pattern: "Order$".to_string(),
+                kind: vec![],
+                sources: vec![],
+                statuses: vec![],
+                modifiers: vec![],
+                limit: 1,
+                group_by: None,
+                count: false,
+            }),
+            "com.b.Order"
+        );
+    }
+
+    struct Log4Shell;
+
+    impl naviscope_plugin::DependencyAdvisor for Log4Shell {
+        fn name(&self) -> &str {
+            "log4shell"
+        }
+
+        fn advise(&self, coordinates: &str) -> Option<naviscope_plugin::DependencyAdvice> {
+            (coordinates == "org.apache.logging.log4j:log4j-core:2.14.1").then(|| {
+                naviscope_plugin::DependencyAdvice {
+                    vulnerabilities: vec!["CVE-2021-44228".to_string()],
+                    licenses: vec!["Apache-2.0".to_string()],
+                }
+            })
+        }
+    }
+
+    #[test]
+    fn dependencies_are_found_by_advice() {
+        let mut builder = CodeGraphBuilder::new();
+        for (fqn, coordinates) in [
+            ("deps.log4j", "org.apache.logging.log4j:log4j-core:2.14.1"),
+            ("deps.log4j2", "org.apache.logging.log4j:log4j-core:2.17.1"),
+        ] {
+            let mut dependency = node(fqn, NodeKind::Dependency);
+            dependency.name = coordinates.to_string();
+            builder.add_node(dependency);
+        }
+        builder.add_node(node("deps.Log4j", NodeKind::Class));
+        let graph = builder.build();
+
+        let presenter: Arc<dyn NodePresenter> = Arc::new(IdPresenter);
+        let engine = QueryEngine::new(&graph, |_| Some(presenter.clone()), HashMap::new())
+            .with_advisories(Arc::new(Advisories::new(vec![Arc::new(Log4Shell)])));
+        let find = |modifiers: &[&str]| {
+            let query = GraphQuery::Find {
+                pattern: "log4j".to_string(),
+                kind: vec![NodeKind::Dependency],
+                sources: vec![],
+                statuses: vec![],
+                modifiers: modifiers.iter().map(|m| m.to_string()).collect(),
+                limit: 10,
+                group_by: None,
+                count: false,
+            };
+            engine.execute(&query).unwrap().nodes
+        };
+
+        assert_eq!(find(&[]).len(), 2);
+        let vulnerable = find(&["Vulnerable", "license:apache-2.0"]);
+        assert_eq!(vulnerable.len(), 1);
+        assert_eq!(vulnerable[0].id, "deps.log4j");
+        assert_eq!(
+            vulnerable[0].detail.as_deref(),
+            Some("vulnerable: CVE-2021-44228; license: Apache-2.0")
+        );
+        assert!(find(&["CVE-2021-45046"]).is_empty());
+    }
+
+    #[test]
+    fn dead_reports_public_symbols_without_uses() {
+        let mut builder = CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            Language::new("java".to_string()),
+            Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        let mut add = |fqn: &str, kind: NodeKind, file: &str| {
+            let mut node = node(fqn, kind);
+            node.name = node.name.trim_end_matches("()").to_string();
+            node.location = Some(naviscope_api::models::DisplaySymbolLocation {
+                path: format!("/p/src/{}", file),
+                range: naviscope_api::models::Range {
+                    start_line: 0,
+                    start_col: 0,
+                    end_line: 0,
+                    end_col: 0,
+                },
+                selection_range: None,
+            });
+            builder.add_node(node)
+        };
+        let api = add("com.app.Api", NodeKind::Interface, "main/Api.java");
+        let api_call = add("com.app.Api#call()", NodeKind::Method, "main/Api.java");
+        let service = add("com.app.Service", NodeKind::Class, "main/Service.java");
+        let call = add(
+            "com.app.Service#call()",
+            NodeKind::Method,
+            "main/Service.java",
+        );
+        let helper = add(
+            "com.app.Service#helper()",
+            NodeKind::Method,
+            "main/Service.java",
+        );
+        let secret = add(
+            "com.app.Service#secret()",
+            NodeKind::Method,
+            "main/Service.java",
+        );
+        let nightly = add(
+            "com.app.Service#nightly()",
+            NodeKind::Method,
+            "main/Service.java",
+        );
+        let scheduled = add("Nightly", NodeKind::Annotation, "main/Nightly.java");
+        let orphan = add("com.app.Orphan", NodeKind::Class, "main/Orphan.java");
+        let work = add(
+            "com.app.Orphan#work()",
+            NodeKind::Method,
+            "main/Orphan.java",
+        );
+        let app = add("com.app.App", NodeKind::Class, "main/App.java");
+        let app_main = add("com.app.App#main()", NodeKind::Method, "main/App.java");
+        let test = add(
+            "com.app.ServiceTest",
+            NodeKind::Class,
+            "test/ServiceTest.java",
+        );
+        for (from, to) in [
+            (api, api_call),
+            (service, call),
+            (service, helper),
+            (service, secret),
+            (service, nightly),
+            (orphan, work),
+            (app, app_main),
+        ] {
+            builder.add_edge(from, to, GraphEdge::new(EdgeType::Contains));
+        }
+        builder.add_edge(service, api, GraphEdge::new(EdgeType::Implements));
+        builder.add_edge(nightly, scheduled, GraphEdge::new(EdgeType::DecoratedBy));
+        builder.add_edge(test, service, GraphEdge::new(EdgeType::TypedAs));
+        builder
+            .apply_op(naviscope_plugin::GraphOp::UpdateIdentifiers {
+                path: Arc::from(Path::new("/p/src/main/Client.java")),
+                identifiers: vec!["Api".to_string(), "call".to_string()],
+            })
+            .unwrap();
+        for file in ["Api", "Service", "Nightly", "Orphan", "App"] {
+            builder.link_file(Path::new(&format!("/p/src/main/{}.java", file)));
+        }
+        let graph = builder.build();
+
+        /// Everything public but `secret`
+        struct PublicPresenter;
+        impl NodePresenter for PublicPresenter {
+            fn render_display_node(
+                &self,
+                node: &crate::model::GraphNode,
+                fqns: &dyn naviscope_api::models::symbol::FqnReader,
+            ) -> DisplayGraphNode {
+                let mut display = IdPresenter.render_display_node(node, fqns);
+                if display.name != "secret" {
+                    display.modifiers.push("public".to_string());
+                }
+                display
+            }
+        }
+        let presenter: Arc<dyn NodePresenter> = Arc::new(PublicPresenter);
+        let dead = |configured: &[&str], scope_fqn: Option<&str>, extra: &[&str]| {
+            let query = GraphQuery::Dead {
+                scope_fqn: scope_fqn.map(str::to_string),
+                entry_points: extra.iter().map(|e| e.to_string()).collect(),
+            };
+            QueryEngine::new(&graph, |_| Some(presenter.clone()), HashMap::new())
+                .with_entry_points(configured.iter().map(|e| e.to_string()).collect())
+                .execute(&query)
+                .unwrap()
+                .nodes
+                .into_iter()
+                .map(|n| n.id)
+                .collect::<Vec<_>>()
+        };
+
+        // Api and call are mentioned elsewhere, Service is used by a test,
+        // main is an entry point and the members of Orphan go with it
+        assert_eq!(
+            dead(&[], None, &[]),
+            [
+                "com.app.Orphan",
+                "com.app.Service#helper()",
+                "com.app.Service#nightly()"
+            ]
+        );
+        assert_eq!(
+            dead(&["@Nightly"], Some("com.app.Service"), &["helper"]),
+            Vec::<String>::new()
+        );
+        assert_eq!(dead(&[], Some("com.app.Orphan"), &[]), ["com.app.Orphan"]);
+    }
+
+    #[test]
+    fn tests_for_lists_test_methods_of_a_class_member_or_package() {
+        let mut builder = CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            Language::new("java".to_string()),
+            Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        let app = builder.add_node(node("com.app", NodeKind::Package));
+        let a = builder.add_node(node("com.app.A", NodeKind::Class));
+        let b = builder.add_node(node("com.app.B", NodeKind::Class));
+        let run = builder.add_node(node("com.app.A#run()", NodeKind::Method));
+        let a_test = builder.add_node(node("com.app.ATest#runs()", NodeKind::Method));
+        let both = builder.add_node(node("com.app.ITest#both()", NodeKind::Method));
+        for (from, to) in [(app, a), (app, b), (a, run)] {
+            builder.add_edge(from, to, GraphEdge::new(EdgeType::Contains));
+        }
+        for (test, class) in [(a_test, a), (both, a), (both, b)] {
+            builder.add_edge(test, class, GraphEdge::new(EdgeType::Tests));
+        }
+        let graph = builder.build();
+        let presenter: Arc<dyn NodePresenter> = Arc::new(IdPresenter);
+        let engine = QueryEngine::new(&graph, |_| Some(presenter.clone()), HashMap::new());
+        let tests_for = |fqn: &str| {
+            let query = GraphQuery::TestsFor {
+                fqn: fqn.to_string(),
+            };
+            let result = engine.execute(&query).unwrap();
+            let nodes: Vec<String> = result.nodes.into_iter().map(|n| n.id).collect();
+            (nodes, result.edges.len())
+        };
+
+        let expected = vec![
+            "com.app.ATest#runs()".to_string(),
+            "com.app.ITest#both()".to_string(),
+        ];
+        assert_eq!(tests_for("com.app.A"), (expected.clone(), 2));
+        assert_eq!(tests_for("com.app.A#run()"), (expected.clone(), 2));
+        assert_eq!(tests_for("com.app"), (expected, 3));
+        assert_eq!(
+            tests_for("com.app.B"),
+            (vec!["com.app.ITest#both()".to_string()], 1)
+        );
+    }
+
+    #[test]
+    fn stats_rank_classes_and_references() {
+        let mut builder = CodeGraphBuilder::new();
+        let pkg = builder.add_node(node("p", NodeKind::Package));
+        let a = builder.add_node(node("p.A", NodeKind::Class));
+        let b = builder.add_node(node("p.B", NodeKind::Class));
+        let start = builder.add_node(node("p.A#start()", NodeKind::Method));
+        let stop = builder.add_node(node("p.A#stop()", NodeKind::Method));
+        for (from, to) in [(pkg, a), (pkg, b), (a, start), (a, stop)] {
+            builder.add_edge(from, to, GraphEdge::new(EdgeType::Contains));
+        }
+        builder.add_edge(b, a, GraphEdge::new(EdgeType::InheritsFrom));
+        builder.add_edge(start, b, GraphEdge::new(EdgeType::TypedAs));
+        builder.add_edge(stop, b, GraphEdge::new(EdgeType::TypedAs));
+        let mut missing = node("x.Missing", NodeKind::Class);
+        missing.status = ResolutionStatus::Unresolved;
+        missing.source = NodeSource::External;
+        let missing = builder.add_node(missing);
+        builder.add_edge(b, missing, GraphEdge::new(EdgeType::Implements));
+        let graph = builder.build();
+
+        let metrics = run(&graph, GraphQuery::Stats { top: 1 }).metrics.unwrap();
+        assert_eq!(metrics.node_count, 6);
+        assert_eq!(metrics.edge_count, 8);
+        assert_eq!(
+            metrics.edges_by_type[0],
+            QueryGroup {
+                key: "Contains".to_string(),
+                count: 4
+            }
+        );
+        assert_eq!(metrics.unresolved_edges, 1);
+        assert_eq!(metrics.largest_classes.len(), 1);
+        assert_eq!(metrics.largest_classes[0].fqn, "p.A");
+        assert_eq!(metrics.largest_classes[0].count, 2);
+        assert_eq!(metrics.most_referenced[0].fqn, "p.B");
+        assert_eq!(metrics.most_referenced[0].count, 2);
+        assert_eq!(metrics.fan_in.max, 2);
+        assert_eq!(metrics.fan_out.max, 2);
+    }
+
+    #[test]
+    fn cancelled_control_stops_scan() {
+        let graph = sample_graph();
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let control = QueryControl::new()
+            .with_progress(move |done, total| sink.lock().unwrap().push((done, total)));
+
+        let query = GraphQuery::Find {
+            pattern: "app".to_string(),
+            kind: vec![],
+            sources: vec![],
+            statuses: vec![],
+            modifiers: vec![],
+            limit: 10,
+            group_by: None,
+            count: true,
+        };
+        let engine =
+            QueryEngine::new(&graph, |_| None, HashMap::new()).with_control(control.clone());
+        assert_eq!(engine.execute(&query).unwrap().total, Some(6));
+        assert_eq!(*reports.lock().unwrap(), vec![(0, Some(6))]);
+
+        control.cancel();
+        assert!(matches!(
+            engine.execute(&query),
+            Err(NaviscopeError::Cancelled)
+        ));
     }
 }
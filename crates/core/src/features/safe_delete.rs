@@ -0,0 +1,474 @@
+//! Safe delete analysis
+//!
+//! Collects everything that would break (or might break) if a symbol were
+//! removed: edges from outside the symbol's own subtree, overriding methods,
+//! reflective-use hints and files that mention its name without an edge.
+//! Usages located in test sources are ignored.
+
+use crate::error::{NaviscopeError, Result};
+use crate::model::{EdgeType, GraphNode, NodeKind};
+use naviscope_api::models::symbol::FqnReader;
+pub use naviscope_api::models::{BlockingUsage, SafeDeleteReport, SafeDeleteVerdict, UsageKind};
+use petgraph::Direction;
+use petgraph::stable_graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::sync::Arc;
+
+use super::CodeGraphLike;
+
+/// Annotations that say nothing about how a symbol is reached
+const INERT_ANNOTATIONS: &[&str] = &[
+    "Override",
+    "Deprecated",
+    "SuppressWarnings",
+    "SafeVarargs",
+    "FunctionalInterface",
+];
+
+/// Member names invoked by the runtime rather than by project code
+const RUNTIME_ENTRY_POINTS: &[&str] = &[
+    "main",
+    "serialVersionUID",
+    "readObject",
+    "writeObject",
+    "readResolve",
+    "writeReplace",
+    "readObjectNoData",
+];
+
+pub struct SafeDeleteAnalyzer<G> {
+    graph: G,
+    naming_conventions: HashMap<String, Arc<dyn naviscope_plugin::NamingConvention>>,
+}
+
+impl<G: CodeGraphLike> SafeDeleteAnalyzer<G> {
+    pub fn new(
+        graph: G,
+        naming_conventions: HashMap<String, Arc<dyn naviscope_plugin::NamingConvention>>,
+    ) -> Self {
+        Self {
+            graph,
+            naming_conventions,
+        }
+    }
+
+    pub fn analyze(&self, fqn: &str) -> Result<SafeDeleteReport> {
+        let target = self
+            .graph
+            .find_node(fqn)
+            .ok_or_else(|| NaviscopeError::Internal(format!("Node not found: {}", fqn)))?;
+
+        let subtree = self.subtree(target);
+        let mut usages = self.references(&subtree);
+        usages.extend(self.overrides(target));
+        usages.extend(self.reflection_hints(target));
+
+        let referencing: HashSet<&str> = usages
+            .iter()
+            .filter_map(|u| u.location.as_ref().map(|l| l.path.as_str()))
+            .collect();
+        let text_matches = self.text_matches(target, &referencing);
+        usages.extend(text_matches);
+
+        Ok(SafeDeleteReport {
+            fqn: fqn.to_string(),
+            verdict: verdict(&usages),
+            usages,
+        })
+    }
+
+    /// The target and everything it (transitively) contains
+    fn subtree(&self, target: NodeIndex) -> HashSet<NodeIndex> {
+        let topology = self.graph.topology();
+        let mut seen = HashSet::from([target]);
+        let mut queue = VecDeque::from([target]);
+        while let Some(idx) = queue.pop_front() {
+            for edge in topology.edges_directed(idx, Direction::Outgoing) {
+                if edge.weight().edge_type == EdgeType::Contains && seen.insert(edge.target()) {
+                    queue.push_back(edge.target());
+                }
+            }
+        }
+        seen
+    }
+
+    /// Non-structural edges into the subtree from outside it
+    fn references(&self, subtree: &HashSet<NodeIndex>) -> Vec<BlockingUsage> {
+        let topology = self.graph.topology();
+        let mut usages = Vec::new();
+        for &idx in subtree {
+            for edge in topology.edges_directed(idx, Direction::Incoming) {
+                let source = edge.source();
+                if matches!(
+                    edge.weight().edge_type,
+                    EdgeType::Contains | EdgeType::Declares
+                ) || subtree.contains(&source)
+                    || self.in_tests(&topology[source])
+                {
+                    continue;
+                }
+                let (from, location) = self.describe(source);
+                usages.push(BlockingUsage {
+                    kind: UsageKind::Reference,
+                    from,
+                    detail: format!(
+                        "{:?} {}",
+                        edge.weight().edge_type,
+                        self.fqn_of(&topology[idx])
+                    ),
+                    location,
+                });
+            }
+        }
+        usages.sort_by(|a, b| a.from.cmp(&b.from).then(a.detail.cmp(&b.detail)));
+        usages
+    }
+
+    /// Methods overriding the target, and the methods the target overrides
+    fn overrides(&self, target: NodeIndex) -> Vec<BlockingUsage> {
+        let topology = self.graph.topology();
+        if topology[target].kind != NodeKind::Method {
+            return Vec::new();
+        }
+        let Some(owner) = self.parent(target) else {
+            return Vec::new();
+        };
+        let Some(member) = self.member_atom(target) else {
+            return Vec::new();
+        };
+
+        let mut usages = Vec::new();
+        for (direction, detail) in [
+            (Direction::Incoming, "overridden by"),
+            (Direction::Outgoing, "overrides"),
+        ] {
+            for related in self.type_hierarchy(owner, direction) {
+                let Some(method) = self.child_with_atom(related, &member) else {
+                    continue;
+                };
+                if direction == Direction::Incoming && self.in_tests(&topology[method]) {
+                    continue;
+                }
+                let (from, location) = self.describe(method);
+                usages.push(BlockingUsage {
+                    kind: UsageKind::Override,
+                    detail: format!("{} {}", detail, from),
+                    from,
+                    location,
+                });
+            }
+        }
+        usages
+    }
+
+    /// Framework annotations and runtime entry points
+    fn reflection_hints(&self, target: NodeIndex) -> Vec<BlockingUsage> {
+        let topology = self.graph.topology();
+        let symbols = self.graph.symbols();
+        let node = &topology[target];
+        let (from, location) = self.describe(target);
+        let mut hints = Vec::new();
+
+        for annotation in self.annotations(target) {
+            hints.push(format!("annotated with @{}", annotation));
+        }
+        if matches!(node.kind, NodeKind::Method | NodeKind::Field)
+            && let Some(owner) = self.parent(target)
+        {
+            for annotation in self.annotations(owner) {
+                hints.push(format!("declared in a type annotated with @{}", annotation));
+            }
+        }
+        let name = node.name(symbols);
+        if RUNTIME_ENTRY_POINTS.contains(&name) {
+            hints.push(format!("'{}' is invoked by the runtime", name));
+        }
+
+        hints
+            .into_iter()
+            .map(|detail| BlockingUsage {
+                kind: UsageKind::Reflection,
+                from: from.clone(),
+                detail,
+                location: location.clone(),
+            })
+            .collect()
+    }
+
+    /// Files mentioning the target's name that no edge accounts for
+    fn text_matches(&self, target: NodeIndex, referencing: &HashSet<&str>) -> Vec<BlockingUsage> {
+        let symbols = self.graph.symbols();
+        let node = &self.graph.topology()[target];
+        let name = node.name(symbols);
+        let declared_in = node.path(symbols);
+
+        let Some(token) = symbols.get(name) else {
+            return Vec::new();
+        };
+        let Some(files) = self
+            .graph
+            .reference_index()
+            .get(&naviscope_api::models::symbol::Symbol(token))
+        else {
+            return Vec::new();
+        };
+
+        let paths: BTreeSet<&str> = files
+            .iter()
+            .map(|file| symbols.resolve(&file.0))
+            .filter(|path| {
+                Some(*path) != declared_in
+                    && !referencing.contains(path)
+                    && !is_test_path(Path::new(path))
+            })
+            .collect();
+        paths
+            .into_iter()
+            .map(|path| BlockingUsage {
+                kind: UsageKind::TextMatch,
+                from: path.to_string(),
+                detail: format!("mentions '{}'", name),
+                location: None,
+            })
+            .collect()
+    }
+
+    fn annotations(&self, idx: NodeIndex) -> Vec<&str> {
+        let topology = self.graph.topology();
+        let symbols = self.graph.symbols();
+        let mut names: Vec<&str> = topology
+            .edges_directed(idx, Direction::Outgoing)
+            .filter(|e| e.weight().edge_type == EdgeType::DecoratedBy)
+            .map(|e| topology[e.target()].name(symbols))
+            .filter(|name| !INERT_ANNOTATIONS.contains(name))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Transitive sub-types (`Incoming`) or super-types (`Outgoing`) of `start`
+    fn type_hierarchy(&self, start: NodeIndex, direction: Direction) -> Vec<NodeIndex> {
+        let topology = self.graph.topology();
+        let mut seen = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        let mut found = Vec::new();
+        while let Some(idx) = queue.pop_front() {
+            for edge in topology.edges_directed(idx, direction) {
+                if !matches!(
+                    edge.weight().edge_type,
+                    EdgeType::InheritsFrom | EdgeType::Implements
+                ) {
+                    continue;
+                }
+                let next = match direction {
+                    Direction::Incoming => edge.source(),
+                    Direction::Outgoing => edge.target(),
+                };
+                if seen.insert(next) {
+                    found.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+        found
+    }
+
+    fn parent(&self, idx: NodeIndex) -> Option<NodeIndex> {
+        self.graph
+            .topology()
+            .edges_directed(idx, Direction::Incoming)
+            .find(|e| e.weight().edge_type == EdgeType::Contains)
+            .map(|e| e.source())
+    }
+
+    /// Last FQN segment, which carries the signature for methods
+    fn member_atom(&self, idx: NodeIndex) -> Option<String> {
+        let fqns = self.graph.fqns();
+        let node = fqns.resolve_node(self.graph.topology()[idx].id)?;
+        Some(fqns.resolve_atom(node.name).to_string())
+    }
+
+    fn child_with_atom(&self, owner: NodeIndex, atom: &str) -> Option<NodeIndex> {
+        self.graph
+            .topology()
+            .edges_directed(owner, Direction::Outgoing)
+            .filter(|e| e.weight().edge_type == EdgeType::Contains)
+            .map(|e| e.target())
+            .find(|&child| self.member_atom(child).as_deref() == Some(atom))
+    }
+
+    fn in_tests(&self, node: &GraphNode) -> bool {
+        node.path(self.graph.symbols())
+            .is_some_and(|path| is_test_path(Path::new(path)))
+    }
+
+    fn describe(
+        &self,
+        idx: NodeIndex,
+    ) -> (String, Option<naviscope_api::models::DisplaySymbolLocation>) {
+        let node = &self.graph.topology()[idx];
+        let location = node
+            .location
+            .as_ref()
+            .map(|l| l.to_display(self.graph.fqns()));
+        (self.fqn_of(node), location)
+    }
+
+    fn fqn_of(&self, node: &GraphNode) -> String {
+        let lang = self.graph.symbols().resolve(&node.lang.0);
+        let convention = self.naming_conventions.get(lang).map(|c| c.as_ref());
+        self.graph.render_fqn(node, convention)
+    }
+}
+
+/// Whether `path` belongs to test sources (`src/test/...`, `tests/`, `*Test.java`)
+pub fn is_test_path(path: &Path) -> bool {
+    let in_test_dir = path.parent().is_some_and(|dir| {
+        dir.components()
+            .any(|c| matches!(c.as_os_str().to_str(), Some("test" | "tests")))
+    });
+    let test_file = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .is_some_and(|stem| stem.ends_with("Test") || stem.ends_with("Tests"));
+    in_test_dir || test_file
+}
+
+/// `Unsafe` for hard usages, `Review` when only hints remain
+pub fn verdict(usages: &[BlockingUsage]) -> SafeDeleteVerdict {
+    if usages
+        .iter()
+        .any(|u| matches!(u.kind, UsageKind::Reference | UsageKind::Override))
+    {
+        SafeDeleteVerdict::Unsafe
+    } else if usages.is_empty() {
+        SafeDeleteVerdict::Safe
+    } else {
+        SafeDeleteVerdict::Review
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::builder::CodeGraphBuilder;
+    use crate::model::{CodeGraph, GraphEdge};
+    use crate::test_support::node_at;
+    use naviscope_plugin::GraphOp;
+
+    /// `Child` overrides `Base#run()`; `Unused` is only used by a test and
+    /// named in `Wiring.java`
+    fn hierarchy_graph() -> CodeGraph {
+        let main = "/p/src/main/java/com/app";
+        let mut builder = CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            crate::model::Language::new("java".to_string()),
+            Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        let base = builder.add_node(node_at(
+            "com.app.Base",
+            NodeKind::Class,
+            &format!("{main}/Base.java"),
+        ));
+        let run = builder.add_node(node_at(
+            "com.app.Base#run()",
+            NodeKind::Method,
+            &format!("{main}/Base.java"),
+        ));
+        let child = builder.add_node(node_at(
+            "com.app.Child",
+            NodeKind::Class,
+            &format!("{main}/Child.java"),
+        ));
+        let child_run = builder.add_node(node_at(
+            "com.app.Child#run()",
+            NodeKind::Method,
+            &format!("{main}/Child.java"),
+        ));
+        let unused = builder.add_node(node_at(
+            "com.app.Unused",
+            NodeKind::Class,
+            &format!("{main}/Unused.java"),
+        ));
+        let base_test = builder.add_node(node_at(
+            "com.app.BaseTest",
+            NodeKind::Class,
+            "/p/src/test/java/com/app/BaseTest.java",
+        ));
+        builder.add_edge(base, run, GraphEdge::new(EdgeType::Contains));
+        builder.add_edge(child, child_run, GraphEdge::new(EdgeType::Contains));
+        builder.add_edge(child, base, GraphEdge::new(EdgeType::InheritsFrom));
+        builder.add_edge(base_test, unused, GraphEdge::new(EdgeType::TypedAs));
+        builder
+            .apply_op(GraphOp::UpdateIdentifiers {
+                path: Arc::from(Path::new("/p/src/main/java/com/app/Wiring.java")),
+                identifiers: vec!["Unused".to_string()],
+            })
+            .unwrap();
+        builder.build()
+    }
+
+    fn analyze(graph: &CodeGraph, fqn: &str) -> SafeDeleteReport {
+        SafeDeleteAnalyzer::new(graph, HashMap::new())
+            .analyze(fqn)
+            .unwrap()
+    }
+
+    #[test]
+    fn subclass_blocks_deleting_base_type_and_overridden_method() {
+        let graph = hierarchy_graph();
+
+        let report = analyze(&graph, "com.app.Base");
+        assert_eq!(report.verdict, SafeDeleteVerdict::Unsafe);
+        assert_eq!(report.usages.len(), 1);
+        assert_eq!(report.usages[0].kind, UsageKind::Reference);
+        assert_eq!(report.usages[0].from, "com.app.Child");
+
+        let report = analyze(&graph, "com.app.Base#run()");
+        assert_eq!(report.verdict, SafeDeleteVerdict::Unsafe);
+        assert_eq!(report.usages[0].kind, UsageKind::Override);
+        assert_eq!(report.usages[0].from, "com.app.Child#run()");
+    }
+
+    #[test]
+    fn test_usages_are_ignored_but_text_matches_need_review() {
+        let graph = hierarchy_graph();
+
+        let report = analyze(&graph, "com.app.Unused");
+        assert_eq!(report.verdict, SafeDeleteVerdict::Review);
+        assert_eq!(report.usages.len(), 1);
+        assert_eq!(report.usages[0].kind, UsageKind::TextMatch);
+        assert_eq!(
+            report.usages[0].from,
+            "/p/src/main/java/com/app/Wiring.java"
+        );
+    }
+
+    #[test]
+    fn declaring_file_does_not_block_deletion() {
+        let path = "/p/src/main/java/com/x/Lonely.java";
+        let mut builder = CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            crate::model::Language::new("java".to_string()),
+            Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        builder
+            .apply_ops(vec![
+                GraphOp::UpdateFile {
+                    metadata: crate::model::source::SourceFile::new(path.into(), 1, 0),
+                },
+                GraphOp::AddNode {
+                    data: Some(node_at("com.x.Lonely", NodeKind::Class, path)),
+                },
+            ])
+            .unwrap();
+        let graph = builder.build();
+
+        let report = analyze(&graph, "com.x.Lonely");
+        assert_eq!(report.verdict, SafeDeleteVerdict::Safe);
+        assert!(report.usages.is_empty());
+    }
+}
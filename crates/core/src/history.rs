@@ -0,0 +1,388 @@
+//! Git history of indexed code
+//!
+//! When `[git] history` is set, `cat` shows when the lines of a node last
+//! changed, by whom and in how many commits, and `stats` ranks the project
+//! types whose files change most often. Both run `git log` in the project's
+//! repository on demand: `git log -L` for the lines of one node, and a
+//! single pass over the whole log counting commits per file. Answers are
+//! kept until `HEAD` moves. Lines are those of `HEAD`, so the history of a
+//! node edited since the last commit may cover neighbouring lines.
+//!
+//! The files of any revision are also read from here, straight from the
+//! object store, to index the revision without checking it out.
+
+use crate::platform::Clock;
+use naviscope_api::models::NodeHistory;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Commits changing each file, by absolute path
+pub type Churn = HashMap<PathBuf, usize>;
+
+/// Last commit and number of commits changing a range of lines
+#[derive(Clone)]
+struct LineChange {
+    commit: String,
+    author: String,
+    committed_at: u64,
+    commits: usize,
+}
+
+/// Answers for one `HEAD`
+#[derive(Default)]
+struct Cache {
+    head: String,
+    churn: Option<Arc<Churn>>,
+    lines: HashMap<(PathBuf, usize, usize), Option<LineChange>>,
+}
+
+pub struct GitHistory {
+    root: PathBuf,
+    clock: Arc<dyn Clock>,
+    cache: Mutex<Cache>,
+}
+
+impl GitHistory {
+    /// History of the repository holding `root`
+    pub fn new(root: PathBuf, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            root,
+            clock,
+            cache: Mutex::new(Cache::default()),
+        }
+    }
+
+    /// Commits changing each file of the repository since it began. Empty
+    /// outside a repository or without git.
+    pub fn churn(&self) -> Arc<Churn> {
+        self.with_cache(|toplevel, cache| {
+            if let Some(churn) = &cache.churn {
+                return churn.clone();
+            }
+            let mut churn = Churn::new();
+            let log = git(
+                toplevel,
+                &["log", "--no-merges", "--format=%x1e", "--name-only"],
+            );
+            for commit in log.as_deref().unwrap_or_default().split('\x1e') {
+                for file in commit.lines().filter(|line| !line.is_empty()) {
+                    *churn.entry(toplevel.join(file)).or_default() += 1;
+                }
+            }
+            let churn = Arc::new(churn);
+            cache.churn = Some(churn.clone());
+            churn
+        })
+        .unwrap_or_default()
+    }
+
+    /// Last change of the zero-based, inclusive lines `start..=end` of
+    /// `path`, if it is committed
+    pub fn node_history(&self, path: &Path, start: usize, end: usize) -> Option<NodeHistory> {
+        let change = self.with_cache(|toplevel, cache| {
+            let key = (path.to_path_buf(), start, end);
+            if let Some(change) = cache.lines.get(&key) {
+                return change.clone();
+            }
+            let change = path
+                .strip_prefix(toplevel)
+                .ok()
+                .and_then(|relative| line_change(toplevel, relative, start, end));
+            cache.lines.insert(key, change.clone());
+            change
+        })??;
+
+        let now = self
+            .clock
+            .now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Some(NodeHistory {
+            commit: change.commit,
+            author: change.author,
+            committed_at: change.committed_at,
+            age_days: now.saturating_sub(change.committed_at) / SECONDS_PER_DAY,
+            commits: change.commits,
+        })
+    }
+
+    /// Run `f` on the answers for the current `HEAD` of the repository,
+    /// given its top-level directory. None outside a repository.
+    fn with_cache<T>(&self, f: impl FnOnce(&Path, &mut Cache) -> T) -> Option<T> {
+        let output = git(&self.root, &["rev-parse", "--show-toplevel", "HEAD"])?;
+        let mut lines = output.lines();
+        let toplevel = PathBuf::from(lines.next()?);
+        let toplevel = toplevel.canonicalize().unwrap_or(toplevel);
+        let head = lines.next()?;
+
+        let mut cache = self.cache.lock().unwrap();
+        if cache.head != head {
+            *cache = Cache {
+                head: head.to_string(),
+                ..Default::default()
+            };
+        }
+        Some(f(&toplevel, &mut cache))
+    }
+}
+
+/// Commits changing the lines of `relative`, newest first, as `git log -L`
+/// follows them back through edits
+fn line_change(toplevel: &Path, relative: &Path, start: usize, end: usize) -> Option<LineChange> {
+    let range = format!("{},{}:{}", start + 1, end + 1, relative.display());
+    let log = git(
+        toplevel,
+        &[
+            "log",
+            "--format=%H%x1f%an%x1f%ct",
+            "--no-patch",
+            "-L",
+            &range,
+        ],
+    )?;
+    let commits: Vec<Vec<&str>> = log
+        .lines()
+        .map(|line| line.split('\x1f').collect::<Vec<_>>())
+        .filter(|fields| fields.len() == 3)
+        .collect();
+    let last = commits.first()?;
+    Some(LineChange {
+        commit: last[0].to_string(),
+        author: last[1].to_string(),
+        committed_at: last[2].parse().ok()?,
+        commits: commits.len(),
+    })
+}
+
+/// Full hash of the commit `rev` names in the repository holding `dir`
+pub(crate) fn resolve_commit(dir: &Path, rev: &str) -> Option<String> {
+    if rev.is_empty() || rev.starts_with('-') {
+        return None;
+    }
+    let spec = format!("{}^{{commit}}", rev);
+    let hash = git(dir, &["rev-parse", "--verify", "--quiet", &spec])?;
+    let hash = hash.trim();
+    (!hash.is_empty()).then(|| hash.to_string())
+}
+
+/// Write the files of `commit` below `dest`, as `git show` would print them.
+/// Symbolic links and submodules are left out. Returns the number of files.
+pub(crate) fn write_tree(dir: &Path, commit: &str, dest: &Path) -> std::io::Result<usize> {
+    let listing = git(dir, &["ls-tree", "-r", "-z", commit])
+        .ok_or_else(|| std::io::Error::other(format!("Cannot list the files of {}", commit)))?;
+    // `<mode> <type> <object>\t<path>`
+    let blobs: Vec<(&str, &str)> = listing
+        .split('\0')
+        .filter_map(|entry| {
+            let (meta, path) = entry.split_once('\t')?;
+            let mut meta = meta.split(' ');
+            let (mode, kind, object) = (meta.next()?, meta.next()?, meta.next()?);
+            (kind == "blob" && mode != "120000").then_some((object, path))
+        })
+        .filter(|(_, path)| {
+            Path::new(path)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+        })
+        .collect();
+
+    // One `cat-file` for all blobs; requests are written from another thread
+    // so that neither side blocks on a full pipe
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["cat-file", "--batch"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let mut stdin = child.stdin.take().expect("piped stdin");
+    let requests: String = blobs
+        .iter()
+        .map(|(object, _)| format!("{}\n", object))
+        .collect();
+    let writer = std::thread::spawn(move || stdin.write_all(requests.as_bytes()));
+
+    let mut output = BufReader::new(child.stdout.take().expect("piped stdout"));
+    let mut header = String::new();
+    for (_, path) in &blobs {
+        // `<object> blob <size>`, the content and a newline
+        header.clear();
+        output.read_line(&mut header)?;
+        let size: usize = header
+            .split_whitespace()
+            .nth(2)
+            .and_then(|size| size.parse().ok())
+            .ok_or_else(|| std::io::Error::other(format!("Cannot read {}", path)))?;
+        let mut content = vec![0; size + 1];
+        output.read_exact(&mut content)?;
+        content.pop();
+
+        let target = dest.join(path);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(target, content)?;
+    }
+    writer
+        .join()
+        .map_err(|_| std::io::Error::other("git cat-file writer panicked"))??;
+    child.wait()?;
+    Ok(blobs.len())
+}
+
+/// Standard output of `git args` run in `dir`, if it succeeds
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| tracing::debug!("Failed to run git: {}", e))
+        .ok()?;
+    if !output.status.success() {
+        tracing::debug!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::ManualClock;
+    use std::time::Duration;
+
+    fn commit(dir: &Path, author: &str, at: u64, files: &[(&str, &str)]) {
+        for (name, content) in files {
+            let path = dir.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        let git = |args: &[&str]| Command::new("git").arg("-C").arg(dir).args(args).status();
+        assert!(git(&["add", "."]).unwrap().success());
+        let date = format!("{} +0000", at);
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", &format!("user.name={}", author)])
+            .args(["-c", "user.email=dev@example.com"])
+            .args(["commit", "-q", "--allow-empty-message", "-m", ""])
+            .args(["--date", &date])
+            .env("GIT_COMMITTER_DATE", &date)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    fn repository() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let init = Command::new("git")
+            .arg("-C")
+            .arg(&root)
+            .args(["init", "-q"])
+            .status();
+        assert!(init.unwrap().success());
+        (dir, root)
+    }
+
+    #[test]
+    fn node_history_follows_the_lines_of_the_node() {
+        let (_dir, root) = repository();
+
+        // Days into 2022
+        let day = |n: u64| (19_000 + n) * SECONDS_PER_DAY;
+        commit(
+            &root,
+            "ann",
+            day(10),
+            &[("A.java", "class A {\n  int a;\n  int b;\n}\n")],
+        );
+        commit(
+            &root,
+            "bob",
+            day(20),
+            &[("A.java", "class A {\n  int a;\n  long b;\n}\n")],
+        );
+        commit(&root, "cid", day(30), &[("B.java", "class B {}\n")]);
+
+        let clock = Arc::new(ManualClock::new(UNIX_EPOCH + Duration::from_secs(day(32))));
+        let history = GitHistory::new(root.clone(), clock);
+        let file = root.join("A.java");
+
+        let field_a = history.node_history(&file, 1, 1).unwrap();
+        assert_eq!(field_a.author, "ann");
+        assert_eq!(field_a.commits, 1);
+        assert_eq!(field_a.age_days, 22);
+        let class = history.node_history(&file, 0, 3).unwrap();
+        assert_eq!(class.author, "bob");
+        assert_eq!(class.commits, 2);
+        assert_eq!(class.committed_at, day(20));
+        assert!(history.node_history(&file, 9, 9).is_none());
+        assert!(
+            history
+                .node_history(Path::new("/elsewhere/A.java"), 0, 0)
+                .is_none()
+        );
+
+        let churn = history.churn();
+        assert_eq!(churn.get(&file), Some(&2));
+        assert_eq!(churn.get(&root.join("B.java")), Some(&1));
+
+        // A new commit invalidates what was learned
+        commit(
+            &root,
+            "dan",
+            day(31),
+            &[("A.java", "class A {\n  short a;\n  long b;\n}\n")],
+        );
+        assert_eq!(history.node_history(&file, 1, 1).unwrap().author, "dan");
+        assert_eq!(history.churn().get(&file), Some(&3));
+    }
+
+    #[test]
+    fn revisions_are_written_from_the_object_store() {
+        let (_dir, root) = repository();
+        commit(
+            &root,
+            "ann",
+            19_000 * SECONDS_PER_DAY,
+            &[("README", "hi\n"), ("app/src/Main.java", "class Main {}\n")],
+        );
+        let first = resolve_commit(&root, "HEAD").unwrap();
+        commit(
+            &root,
+            "bob",
+            19_001 * SECONDS_PER_DAY,
+            &[("app/src/Main.java", "class Main { int x; }\n")],
+        );
+        std::fs::write(root.join("app/src/Main.java"), "unsaved").unwrap();
+        assert_eq!(resolve_commit(&root, "HEAD~1"), Some(first.clone()));
+        assert!(resolve_commit(&root, "no-such-branch").is_none());
+        assert!(resolve_commit(&root, "--all").is_none());
+
+        // From a project below the top level, only its own files
+        let out = tempfile::tempdir().unwrap();
+        assert_eq!(
+            write_tree(&root.join("app"), &first, out.path()).unwrap(),
+            1
+        );
+        assert_eq!(
+            std::fs::read_to_string(out.path().join("src/Main.java")).unwrap(),
+            "class Main {}\n"
+        );
+        assert!(!out.path().join("README").exists());
+    }
+}
@@ -1,6 +1,8 @@
 use crate::error::Result;
 use crate::indexing::scanner::ParsedFile;
+use crate::indexing::warnings::WarningCollector;
 use crate::model::GraphOp;
+use naviscope_api::lifecycle::IndexPhase;
 use naviscope_plugin::{BuildCaps, BuildContent, ParsedContent, ProjectContext};
 use std::fs;
 
@@ -17,6 +19,7 @@ impl BuildCompiler {
         &self,
         build_files: &[ParsedFile],
         context: &mut ProjectContext,
+        warnings: &WarningCollector,
     ) -> Result<Vec<GraphOp>> {
         let mut all_ops = Vec::new();
         for caps in &self.build_caps {
@@ -28,13 +31,15 @@ impl BuildCompiler {
             if !tool_files.is_empty() {
                 let parsed_tool_files: Vec<ParsedFile> = tool_files
                     .iter()
-                    .map(|f| Self::prepare_build_file(caps, f))
+                    .map(|f| Self::prepare_build_file(caps, f, warnings))
                     .collect::<Result<Vec<_>>>()?;
                 let parsed_tool_file_refs: Vec<&ParsedFile> = parsed_tool_files.iter().collect();
                 let (unit, ctx) = caps
                     .indexing
                     .compile_build(&parsed_tool_file_refs)
                     .map_err(crate::error::NaviscopeError::from)?;
+                // Build units span several files; default to the first
+                warnings.extend(tool_files[0].path(), IndexPhase::Resolving, unit.warnings);
                 all_ops.extend(unit.ops);
                 context.path_to_module.extend(ctx.path_to_module);
             }
@@ -42,7 +47,11 @@ impl BuildCompiler {
         Ok(all_ops)
     }
 
-    fn prepare_build_file(caps: &BuildCaps, file: &ParsedFile) -> Result<ParsedFile> {
+    fn prepare_build_file(
+        caps: &BuildCaps,
+        file: &ParsedFile,
+        warnings: &WarningCollector,
+    ) -> Result<ParsedFile> {
         let source = match &file.content {
             ParsedContent::Unparsed(s) => s.clone(),
             ParsedContent::Lazy => fs::read_to_string(file.path()).map_err(|e| {
@@ -60,6 +69,7 @@ impl BuildCompiler {
             .parser
             .parse_build_file(&source)
             .map_err(crate::error::NaviscopeError::from)?;
+        warnings.extend(file.path(), IndexPhase::Parsing, parse_result.warnings);
 
         let content = match parse_result.content {
             BuildContent::Metadata(value) => ParsedContent::Metadata(value),
@@ -0,0 +1,229 @@
+//! Fingerprints of methods, used to find duplicated code
+//!
+//! The source of a method is reduced to a stream of normalized tokens:
+//! comments and whitespace are dropped, identifiers and literals become
+//! placeholders, so a copy with renamed variables yields the same stream.
+//! Of the hashes of its `K`-token runs, the smallest of every `WINDOW`
+//! consecutive ones is kept (winnowing): any run of `K + WINDOW - 1` tokens
+//! two methods share leaves a common hash, while only a fraction of the
+//! hashes is stored.
+
+use naviscope_api::models::NodeKind;
+use naviscope_plugin::{CodeFingerprint, GraphOp};
+use std::path::Path;
+use xxhash_rust::xxh3::{xxh3_64, xxh3_64_with_seed};
+
+/// Tokens hashed together
+const K: usize = 5;
+/// Consecutive hashes of which the smallest is kept
+const WINDOW: usize = 4;
+/// Methods with fewer tokens are too small to be worth reporting as copies
+const MIN_TOKENS: usize = 20;
+
+/// Words kept as they are; any other identifier is a placeholder
+const KEYWORDS: &[&str] = &[
+    "abstract",
+    "as",
+    "assert",
+    "boolean",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "continue",
+    "def",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extends",
+    "false",
+    "final",
+    "finally",
+    "float",
+    "for",
+    "fun",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "int",
+    "interface",
+    "is",
+    "long",
+    "new",
+    "null",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "short",
+    "static",
+    "super",
+    "switch",
+    "synchronized",
+    "this",
+    "throw",
+    "throws",
+    "true",
+    "try",
+    "val",
+    "var",
+    "void",
+    "when",
+    "while",
+    "yield",
+];
+
+const IDENTIFIER: u64 = 1;
+const LITERAL: u64 = 2;
+
+/// Hashes of the normalized tokens of `code`
+fn tokens(code: &str) -> Vec<u64> {
+    let bytes = code.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let rest = &bytes[i..];
+        if b.is_ascii_whitespace() {
+            i += 1;
+        } else if rest.starts_with(b"//") {
+            i += rest.iter().position(|&c| c == b'\n').unwrap_or(rest.len());
+        } else if rest.starts_with(b"/*") {
+            i += find(&rest[2..], b"*/").map_or(rest.len(), |end| end + 4);
+        } else if rest.starts_with(b"\"\"\"") {
+            i += find(&rest[3..], b"\"\"\"").map_or(rest.len(), |end| end + 6);
+            tokens.push(LITERAL);
+        } else if b == b'"' || b == b'\'' {
+            let mut end = 1;
+            while end < rest.len() && rest[end] != b && rest[end] != b'\n' {
+                end += if rest[end] == b'\\' { 2 } else { 1 };
+            }
+            i += (end + 1).min(rest.len());
+            tokens.push(LITERAL);
+        } else if b.is_ascii_digit() {
+            i += word_len(rest);
+            tokens.push(LITERAL);
+        } else if b.is_ascii_alphabetic() || b == b'_' || b == b'$' || !b.is_ascii() {
+            let len = word_len(rest);
+            let word = &code[i..i + len];
+            tokens.push(if KEYWORDS.binary_search(&word).is_ok() {
+                xxh3_64(word.as_bytes())
+            } else {
+                IDENTIFIER
+            });
+            i += len;
+        } else {
+            tokens.push(xxh3_64(&[b]));
+            i += 1;
+        }
+    }
+    tokens
+}
+
+fn word_len(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .position(|&c| !(c.is_ascii_alphanumeric() || c == b'_' || c == b'$' || !c.is_ascii()))
+        .unwrap_or(bytes.len())
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Number of normalized tokens of `code` and its winnowed hashes, sorted
+/// and distinct
+pub fn fingerprint(code: &str) -> (usize, Vec<u64>) {
+    let tokens = tokens(code);
+    let grams: Vec<u64> = tokens
+        .windows(K)
+        .map(|gram| {
+            let bytes: Vec<u8> = gram.iter().flat_map(|t| t.to_le_bytes()).collect();
+            xxh3_64_with_seed(&bytes, K as u64)
+        })
+        .collect();
+    let mut hashes: Vec<u64> = if grams.len() <= WINDOW {
+        grams.iter().min().copied().into_iter().collect()
+    } else {
+        grams
+            .windows(WINDOW)
+            .map(|window| *window.iter().min().unwrap())
+            .collect()
+    };
+    hashes.sort_unstable();
+    hashes.dedup();
+    (tokens.len(), hashes)
+}
+
+/// Fingerprints of the methods and constructors `ops` declare in the file at
+/// `path`, whose content is `source`
+pub fn method_fingerprints(path: &Path, source: &str, ops: &[GraphOp]) -> Vec<CodeFingerprint> {
+    let path = path.to_string_lossy();
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let offset = |line: usize, col: usize| {
+        line_starts
+            .get(line)
+            .map(|start| (start + col).min(source.len()))
+    };
+
+    ops.iter()
+        .filter_map(|op| match op {
+            GraphOp::AddNode { data: Some(node) }
+                if matches!(node.kind, NodeKind::Method | NodeKind::Constructor) =>
+            {
+                node.location.as_ref().filter(|loc| loc.path == path)
+            }
+            _ => None,
+        })
+        .filter_map(|loc| {
+            let range = &loc.range;
+            let start = offset(range.start_line, range.start_col)?;
+            let end = offset(range.end_line, range.end_col)?;
+            let (tokens, hashes) = fingerprint(source.get(start..end)?);
+            (tokens >= MIN_TOKENS).then_some(CodeFingerprint {
+                line: range.start_line,
+                col: range.start_col,
+                tokens,
+                hashes,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renamed_copies_share_their_fingerprint() {
+        let original = r#"int total(List<Item> items) {
+            int sum = 0; // running total
+            for (Item item : items) { sum += item.price() * 2; }
+            return sum;
+        }"#;
+        let renamed = r#"int cost(List<Line> lines) {
+            /* same loop */
+            int acc = 0;
+            for (Line l : lines) { acc += l.amount() * 3; }
+            return acc;
+        }"#;
+        let other = r#"void log(String message) {
+            if (enabled) { System.out.println("[app] " + message); }
+        }"#;
+
+        assert!(KEYWORDS.windows(2).all(|w| w[0] < w[1]));
+        let (tokens, hashes) = fingerprint(original);
+        assert_eq!(tokens, 39);
+        assert_eq!(fingerprint(renamed), (tokens, hashes.clone()));
+        let (_, unrelated) = fingerprint(other);
+        assert!(unrelated.iter().all(|h| hashes.binary_search(h).is_err()));
+    }
+}
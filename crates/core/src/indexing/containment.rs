@@ -0,0 +1,172 @@
+//! Module containment of packages after the build topology changes
+//!
+//! Packages hang off the module whose directory holds their source files.
+//! Recompiling the build files recreates the module nodes, so adding or
+//! removing a module in `settings.gradle` left packages attached to the old
+//! module, or to none, until a full rebuild. Instead, after the build phase
+//! every project package is moved to the modules its files now belong to.
+//! Only the edges of the modules involved change, so the next save writes
+//! just their shards.
+
+use crate::model::{CodeGraph, GraphEdge, NodeKind};
+use naviscope_api::models::graph::{EdgeType, NodeSource};
+use naviscope_plugin::ProjectContext;
+use petgraph::Direction;
+use petgraph::stable_graph::{EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Attach every project package to the modules declaring its files and
+/// detach it from the others. Returns the graph and how many packages moved.
+pub fn rehome_packages(graph: CodeGraph, context: &ProjectContext) -> (CodeGraph, usize) {
+    let modules = module_nodes(&graph, context);
+    if modules.is_empty() {
+        return (graph, 0);
+    }
+    let topology = graph.topology();
+    let mut stale: Vec<EdgeIndex> = Vec::new();
+    let mut missing: Vec<(NodeIndex, NodeIndex)> = Vec::new();
+    let mut moved = 0;
+    for idx in topology.node_indices() {
+        let node = &topology[idx];
+        if node.kind != NodeKind::Package || node.source != NodeSource::Project {
+            continue;
+        }
+        let expected: BTreeSet<NodeIndex> = topology
+            .edges_directed(idx, Direction::Outgoing)
+            .filter(|e| e.weight().edge_type == EdgeType::Contains)
+            .filter_map(|e| topology[e.target()].location.as_ref())
+            .filter_map(|location| {
+                let path = Path::new(graph.symbols().resolve(&location.path.0));
+                module_dir(path, context).and_then(|dir| modules.get(dir).copied())
+            })
+            .collect();
+        // Packages without declarations are left alone
+        if expected.is_empty() {
+            continue;
+        }
+        let current: Vec<(EdgeIndex, NodeIndex)> = topology
+            .edges_directed(idx, Direction::Incoming)
+            .filter(|e| e.weight().edge_type == EdgeType::Contains)
+            .filter(|e| topology[e.source()].kind == NodeKind::Module)
+            .map(|e| (e.id(), e.source()))
+            .collect();
+        let before = stale.len() + missing.len();
+        stale.extend(
+            current
+                .iter()
+                .filter(|(_, module)| !expected.contains(module))
+                .map(|(edge, _)| *edge),
+        );
+        missing.extend(
+            expected
+                .iter()
+                .filter(|module| !current.iter().any(|(_, m)| m == *module))
+                .map(|&module| (module, idx)),
+        );
+        if stale.len() + missing.len() > before {
+            moved += 1;
+        }
+    }
+    if moved == 0 {
+        return (graph, 0);
+    }
+
+    let mut builder = graph.to_builder();
+    for edge in stale {
+        builder.remove_edge(edge);
+    }
+    for (module, package) in missing {
+        builder.add_edge(module, package, GraphEdge::new(EdgeType::Contains));
+    }
+    (builder.build(), moved)
+}
+
+/// Build module nodes by the directory in `path_to_module` they stand for;
+/// a module node is located at the build or settings file of its directory.
+fn module_nodes(graph: &CodeGraph, context: &ProjectContext) -> HashMap<PathBuf, NodeIndex> {
+    let topology = graph.topology();
+    topology
+        .node_indices()
+        .filter(|&idx| topology[idx].kind == NodeKind::Module)
+        .filter_map(|idx| {
+            let location = topology[idx].location.as_ref()?;
+            let dir = Path::new(graph.symbols().resolve(&location.path.0)).parent()?;
+            context
+                .path_to_module
+                .contains_key(dir)
+                .then(|| (dir.to_path_buf(), idx))
+        })
+        .collect()
+}
+
+/// Innermost module directory containing `path`
+fn module_dir<'a>(path: &Path, context: &'a ProjectContext) -> Option<&'a Path> {
+    path.ancestors()
+        .skip(1)
+        .find_map(|dir| context.path_to_module.get_key_value(dir))
+        .map(|(dir, _)| dir.as_path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::builder::CodeGraphBuilder;
+    use crate::test_support::{node, node_at};
+
+    #[test]
+    fn packages_move_to_the_module_of_their_files() {
+        let mut builder = CodeGraphBuilder::new();
+        let root = builder.add_node(node_at(
+            "p::module:",
+            NodeKind::Module,
+            "/p/settings.gradle",
+        ));
+        let app = builder.add_node(node_at(
+            "p::module:app",
+            NodeKind::Module,
+            "/p/app/build.gradle",
+        ));
+        let moved = builder.add_node(node("shop", NodeKind::Package));
+        let order = builder.add_node(node_at(
+            "shop.Order",
+            NodeKind::Class,
+            "/p/app/src/main/java/shop/Order.java",
+        ));
+        builder.add_edge(moved, order, GraphEdge::new(EdgeType::Contains));
+        builder.add_edge(root, moved, GraphEdge::new(EdgeType::Contains));
+        let kept = builder.add_node(node("util", NodeKind::Package));
+        let text = builder.add_node(node_at(
+            "util.Text",
+            NodeKind::Class,
+            "/p/src/main/java/util/Text.java",
+        ));
+        builder.add_edge(kept, text, GraphEdge::new(EdgeType::Contains));
+        builder.add_edge(root, kept, GraphEdge::new(EdgeType::Contains));
+
+        let mut context = ProjectContext::new();
+        context
+            .path_to_module
+            .insert(PathBuf::from("/p"), "p::module:".to_string());
+        context
+            .path_to_module
+            .insert(PathBuf::from("/p/app"), "p::module:app".to_string());
+
+        let (graph, count) = rehome_packages(builder.build(), &context);
+        assert_eq!(count, 1);
+        let containers = |idx: NodeIndex| -> Vec<NodeIndex> {
+            graph
+                .topology()
+                .edges_directed(idx, Direction::Incoming)
+                .filter(|e| e.weight().edge_type == EdgeType::Contains)
+                .map(|e| e.source())
+                .collect()
+        };
+        assert_eq!(containers(moved), [app]);
+        assert_eq!(containers(kept), [root]);
+
+        // A second pass finds nothing to move
+        assert_eq!(rehome_packages(graph, &context).1, 0);
+    }
+}
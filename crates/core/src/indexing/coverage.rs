@@ -0,0 +1,379 @@
+//! Links from test methods to the classes they exercise
+//!
+//! A test method is a method annotated `@Test` (JUnit 4/5, TestNG) or with
+//! another test annotation, or any method of a class annotated `@Test`
+//! (TestNG). Calls are not edges of the graph, so the classes a test
+//! exercises are guessed from what the graph does know: the project types
+//! its body declares, instantiates or casts to (`TypedAs`), the types of the
+//! fields the framework fills with the object under test (`@InjectMocks`,
+//! `@Autowired`, `@Inject`), and the class named after the test class
+//! (`OrderService` for `OrderServiceTest`). Each gets a `Tests` edge from the
+//! test method. Test sources are never targets. Like the class-level
+//! `Injects` edges, these are derived and rebuilt whenever indexing ends.
+
+use crate::features::safe_delete::is_test_path;
+use crate::model::{CodeGraph, GraphEdge, NodeKind};
+use naviscope_api::models::graph::{EdgeType, NodeSource};
+use naviscope_api::models::symbol::Symbol;
+use petgraph::Direction;
+use petgraph::stable_graph::NodeIndex;
+use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Annotations marking a test method
+const TEST_ANNOTATIONS: &[&str] = &[
+    "Test",
+    "ParameterizedTest",
+    "RepeatedTest",
+    "TestFactory",
+    "TestTemplate",
+];
+
+/// Methods of a TestNG test class that are not tests themselves
+const LIFECYCLE_ANNOTATIONS: &[&str] = &[
+    "BeforeMethod",
+    "AfterMethod",
+    "BeforeClass",
+    "AfterClass",
+    "BeforeSuite",
+    "AfterSuite",
+    "BeforeTest",
+    "AfterTest",
+    "DataProvider",
+];
+
+/// Field annotations of the object under test
+const SUBJECT_ANNOTATIONS: &[&str] = &["InjectMocks", "Autowired", "Inject"];
+
+/// Name suffixes and prefixes of test classes
+const TEST_SUFFIXES: &[&str] = &["Tests", "Test", "IT", "TestCase"];
+const TEST_PREFIX: &str = "Test";
+
+/// Replace the `Tests` edges with those the test methods of the graph yield.
+/// Returns the graph unchanged when they are the same.
+pub fn link_tested_classes(graph: CodeGraph) -> CodeGraph {
+    let topology = graph.topology();
+    let symbols = graph.symbols();
+    // Simple names: an annotation that did not resolve is named by the
+    // FQN it was imported as
+    let annotations = |idx: NodeIndex| {
+        topology
+            .edges_directed(idx, Direction::Outgoing)
+            .filter(|e| e.weight().edge_type == EdgeType::DecoratedBy)
+            .map(move |e| {
+                let name = topology[e.target()].name(symbols);
+                name.rsplit('.').next().unwrap_or(name)
+            })
+    };
+    let is_production_type = |idx: NodeIndex| {
+        let node = &topology[idx];
+        is_type(&node.kind)
+            && node.source == NodeSource::Project
+            && node
+                .path(symbols)
+                .is_some_and(|path| !is_test_path(Path::new(path)))
+    };
+    let members = |idx: NodeIndex| {
+        topology
+            .edges_directed(idx, Direction::Outgoing)
+            .filter(|e| e.weight().edge_type == EdgeType::Contains)
+            .map(|e| e.target())
+    };
+    let typed_as = |idx: NodeIndex| {
+        topology
+            .edges_directed(idx, Direction::Outgoing)
+            .filter(|e| e.weight().edge_type == EdgeType::TypedAs)
+            .map(|e| e.target())
+    };
+
+    let mut existing = BTreeSet::new();
+    let mut links = BTreeSet::new();
+    for edge in topology.edge_references() {
+        if edge.weight().edge_type == EdgeType::Tests {
+            existing.insert((edge.source(), edge.target(), edge.id()));
+        }
+    }
+    for class in topology.node_indices() {
+        if !is_type(&topology[class].kind) || topology[class].source != NodeSource::Project {
+            continue;
+        }
+        let class_is_test = annotations(class).any(|name| name == "Test");
+        let tests: Vec<NodeIndex> = members(class)
+            .filter(|&member| topology[member].kind == NodeKind::Method)
+            .filter(|&method| {
+                let mut names = annotations(method);
+                if class_is_test {
+                    !names.any(|name| LIFECYCLE_ANNOTATIONS.contains(&name))
+                } else {
+                    names.any(|name| TEST_ANNOTATIONS.contains(&name))
+                }
+            })
+            .collect();
+        if tests.is_empty() {
+            continue;
+        }
+
+        // Exercised by every test of the class
+        let mut subjects: BTreeSet<NodeIndex> = members(class)
+            .filter(|&field| {
+                topology[field].kind == NodeKind::Field
+                    && annotations(field).any(|name| SUBJECT_ANNOTATIONS.contains(&name))
+            })
+            .flat_map(typed_as)
+            .collect();
+        if let Some(subject) = named_subject(&graph, class) {
+            subjects.insert(subject);
+        }
+        for method in tests {
+            for target in subjects.iter().copied().chain(typed_as(method)) {
+                if target != class && is_production_type(target) {
+                    links.insert((method, target));
+                }
+            }
+        }
+    }
+    let existing_links: BTreeSet<(NodeIndex, NodeIndex)> =
+        existing.iter().map(|&(from, to, _)| (from, to)).collect();
+    if existing_links == links {
+        return graph;
+    }
+
+    let mut builder = graph.to_builder();
+    for (_, _, edge) in existing {
+        builder.remove_edge(edge);
+    }
+    for (method, class) in links {
+        builder.add_edge(method, class, GraphEdge::new(EdgeType::Tests));
+    }
+    builder.build()
+}
+
+/// The type `OrderService` for the test class `OrderServiceTest` (or
+/// `TestOrderService`), preferring one in the package of the test
+fn named_subject(graph: &CodeGraph, class: NodeIndex) -> Option<NodeIndex> {
+    let topology = graph.topology();
+    let symbols = graph.symbols();
+    let name = topology[class].name(symbols);
+    let subject = TEST_SUFFIXES
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .or_else(|| name.strip_prefix(TEST_PREFIX))
+        .filter(|subject| !subject.is_empty())?;
+    let parent = |idx: NodeIndex| {
+        topology
+            .edges_directed(idx, Direction::Incoming)
+            .find(|e| e.weight().edge_type == EdgeType::Contains)
+            .map(|e| e.source())
+    };
+    let candidates: Vec<NodeIndex> = graph
+        .name_map()
+        .get(&Symbol(symbols.get(subject)?))?
+        .iter()
+        .copied()
+        .filter(|&idx| {
+            let node = &topology[idx];
+            is_type(&node.kind)
+                && node.source == NodeSource::Project
+                && node
+                    .path(symbols)
+                    .is_some_and(|path| !is_test_path(Path::new(path)))
+        })
+        .collect();
+    candidates
+        .iter()
+        .copied()
+        .find(|&idx| parent(idx) == parent(class))
+        .or_else(|| (candidates.len() == 1).then(|| candidates[0]))
+}
+
+fn is_type(kind: &NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Class | NodeKind::Interface | NodeKind::Enum | NodeKind::Annotation
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexing::IndexNode;
+    use crate::model::builder::CodeGraphBuilder;
+    use crate::test_support::{node, node_at};
+    use naviscope_api::models::graph::ResolutionStatus;
+
+    fn tested(graph: &CodeGraph, method: NodeIndex) -> Vec<NodeIndex> {
+        let mut targets: Vec<NodeIndex> = graph
+            .topology()
+            .edges_directed(method, Direction::Outgoing)
+            .filter(|e| e.weight().edge_type == EdgeType::Tests)
+            .map(|e| e.target())
+            .collect();
+        targets.sort();
+        targets
+    }
+
+    #[test]
+    fn test_methods_are_linked_to_the_classes_they_exercise() {
+        let mut builder = CodeGraphBuilder::new();
+        let app = builder.add_node(node_at("app", NodeKind::Package, "/p/src/main/app"));
+        let service = builder.add_node(node_at(
+            "app.OrderService",
+            NodeKind::Class,
+            "/p/src/main/app/OrderService.java",
+        ));
+        let clock = builder.add_node(node_at(
+            "app.Clock",
+            NodeKind::Class,
+            "/p/src/main/app/Clock.java",
+        ));
+        let repository = builder.add_node(node_at(
+            "app.Repository",
+            NodeKind::Interface,
+            "/p/src/main/app/Repository.java",
+        ));
+        let fixture = builder.add_node(node_at(
+            "app.Fixture",
+            NodeKind::Class,
+            "/p/src/test/app/Fixture.java",
+        ));
+        let test_class = builder.add_node(node_at(
+            "app.OrderServiceTest",
+            NodeKind::Class,
+            "/p/src/test/app/OrderServiceTest.java",
+        ));
+        let places = builder.add_node(node_at(
+            "app.OrderServiceTest#places",
+            NodeKind::Method,
+            "/p/src/test/app/OrderServiceTest.java",
+        ));
+        let setup = builder.add_node(node_at(
+            "app.OrderServiceTest#setUp",
+            NodeKind::Method,
+            "/p/src/test/app/OrderServiceTest.java",
+        ));
+        let field = builder.add_node(node_at(
+            "app.OrderServiceTest#repository",
+            NodeKind::Field,
+            "/p/src/test/app/OrderServiceTest.java",
+        ));
+        let test = builder.add_node(node_at(
+            "Test",
+            NodeKind::Annotation,
+            "/p/src/lib/Test.java",
+        ));
+        let inject = builder.add_node(node_at(
+            "Autowired",
+            NodeKind::Annotation,
+            "/p/src/lib/Autowired.java",
+        ));
+        for (from, to) in [
+            (app, service),
+            (app, clock),
+            (app, test_class),
+            (test_class, places),
+            (test_class, setup),
+            (test_class, field),
+        ] {
+            builder.add_edge(from, to, GraphEdge::new(EdgeType::Contains));
+        }
+        builder.add_edge(places, test, GraphEdge::new(EdgeType::DecoratedBy));
+        builder.add_edge(places, clock, GraphEdge::new(EdgeType::TypedAs));
+        builder.add_edge(places, fixture, GraphEdge::new(EdgeType::TypedAs));
+        builder.add_edge(setup, clock, GraphEdge::new(EdgeType::TypedAs));
+        builder.add_edge(field, inject, GraphEdge::new(EdgeType::DecoratedBy));
+        builder.add_edge(field, repository, GraphEdge::new(EdgeType::TypedAs));
+
+        let graph = link_tested_classes(builder.build());
+        // The named subject, the injected field and the body, but neither
+        // test sources nor the set-up method
+        assert_eq!(tested(&graph, places), {
+            let mut expected = vec![service, clock, repository];
+            expected.sort();
+            expected
+        });
+        assert!(tested(&graph, setup).is_empty());
+        let edges = graph.edge_count();
+        assert_eq!(link_tested_classes(graph).edge_count(), edges);
+    }
+
+    #[test]
+    fn unresolved_annotations_match_by_simple_name() {
+        let unresolved = |fqn: &str| IndexNode {
+            name: fqn.to_string(),
+            source: NodeSource::External,
+            status: ResolutionStatus::Unresolved,
+            ..node(fqn, NodeKind::Annotation)
+        };
+        let mut builder = CodeGraphBuilder::new();
+        let service = builder.add_node(node_at("s.Svc", NodeKind::Class, "/p/src/main/s/Svc.java"));
+        let repository = builder.add_node(node_at(
+            "s.Repo",
+            NodeKind::Class,
+            "/p/src/main/s/Repo.java",
+        ));
+        let test_class = builder.add_node(node_at(
+            "s.SvcTest",
+            NodeKind::Class,
+            "/p/src/test/s/SvcTest.java",
+        ));
+        let runs = builder.add_node(node_at(
+            "s.SvcTest#runs()",
+            NodeKind::Method,
+            "/p/src/test/s/SvcTest.java",
+        ));
+        let field = builder.add_node(node_at(
+            "s.SvcTest#repo",
+            NodeKind::Field,
+            "/p/src/test/s/SvcTest.java",
+        ));
+        let checks = builder.add_node(node_at(
+            "s.RepoChecks",
+            NodeKind::Class,
+            "/p/src/test/s/RepoChecks.java",
+        ));
+        let check = builder.add_node(node_at(
+            "s.RepoChecks#check()",
+            NodeKind::Method,
+            "/p/src/test/s/RepoChecks.java",
+        ));
+        let setup = builder.add_node(node_at(
+            "s.RepoChecks#setUp()",
+            NodeKind::Method,
+            "/p/src/test/s/RepoChecks.java",
+        ));
+        let test = builder.add_node(unresolved("org.junit.jupiter.api.Test"));
+        let inject = builder.add_node(unresolved("org.mockito.InjectMocks"));
+        let testng = builder.add_node(unresolved("org.testng.annotations.Test"));
+        let before = builder.add_node(unresolved("org.testng.annotations.BeforeMethod"));
+        for (from, to) in [
+            (test_class, runs),
+            (test_class, field),
+            (checks, check),
+            (checks, setup),
+        ] {
+            builder.add_edge(from, to, GraphEdge::new(EdgeType::Contains));
+        }
+        for (from, to) in [
+            (runs, test),
+            (field, inject),
+            (checks, testng),
+            (setup, before),
+        ] {
+            builder.add_edge(from, to, GraphEdge::new(EdgeType::DecoratedBy));
+        }
+        builder.add_edge(field, repository, GraphEdge::new(EdgeType::TypedAs));
+        for method in [check, setup] {
+            builder.add_edge(method, repository, GraphEdge::new(EdgeType::TypedAs));
+        }
+
+        let graph = link_tested_classes(builder.build());
+        assert_eq!(tested(&graph, runs), {
+            let mut expected = vec![service, repository];
+            expected.sort();
+            expected
+        });
+        assert_eq!(tested(&graph, check), vec![repository]);
+        assert!(tested(&graph, setup).is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+//! Links from generated sources to the code they were generated from
+//!
+//! Annotation processors name their output after the annotated type: QueryDSL
+//! writes `QOrder` for the entity `Order`, MapStruct `OrderMapperImpl` for the
+//! mapper `OrderMapper`, Dagger `DaggerAppComponent` and `Order_Factory`, the
+//! JPA metamodel `Order_`. After indexing, every type declared in a generated
+//! file gets a `GeneratedFrom` edge to the annotated project type of the same
+//! package its name derives from.
+
+use crate::features::CodeGraphLike;
+use crate::model::{CodeGraph, GraphEdge, NodeKind};
+use naviscope_api::models::graph::{EdgeType, NodeSource};
+use naviscope_plugin::NamingConvention;
+use petgraph::Direction;
+use petgraph::stable_graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Prefixes processors put before the name of the annotated type
+const ORIGIN_PREFIXES: &[&str] = &["Dagger", "Immutable", "Q"];
+/// Suffixes processors put after it
+const ORIGIN_SUFFIXES: &[&str] = &["Impl", "Builder", "_"];
+
+/// Names the annotated type may have, most specific first.
+pub fn origin_names(name: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut push = |candidate: &str| {
+        let starts_upper = candidate.starts_with(|c: char| c.is_ascii_uppercase());
+        if starts_upper && candidate != name && !names.iter().any(|n| n == candidate) {
+            names.push(candidate.to_string());
+        }
+    };
+    for prefix in ORIGIN_PREFIXES {
+        if let Some(rest) = name.strip_prefix(prefix) {
+            push(rest);
+        }
+    }
+    for suffix in ORIGIN_SUFFIXES {
+        if let Some(rest) = name.strip_suffix(suffix) {
+            push(rest);
+        }
+    }
+    // `Order_Factory`, `AppModule_ProvideClockFactory`, `AutoValue_Order`
+    if let Some((head, _)) = name.split_once('_') {
+        push(head);
+    }
+    if let Some((_, tail)) = name.rsplit_once('_') {
+        push(tail);
+    }
+    names
+}
+
+/// Add `GeneratedFrom` edges for the types of generated files.
+pub fn link_generated_types(
+    graph: CodeGraph,
+    conventions: &HashMap<String, Arc<dyn NamingConvention>>,
+) -> CodeGraph {
+    let topology = graph.topology();
+    let mut links = Vec::new();
+    for entry in graph.file_index().values() {
+        if !entry.metadata.generated {
+            continue;
+        }
+        for &idx in &entry.nodes {
+            let Some(node) = topology.node_weight(idx) else {
+                continue;
+            };
+            if !is_type(&node.kind) {
+                continue;
+            }
+            let lang = graph.symbols().resolve(&node.lang.0);
+            let convention = conventions.get(lang).map(|c| c.as_ref());
+            let fqn = graph.render_fqn(node, convention);
+            let (package, name) = fqn.rsplit_once('.').unwrap_or(("", &fqn));
+            let origin = origin_names(name).into_iter().find_map(|candidate| {
+                let origin_fqn = if package.is_empty() {
+                    candidate
+                } else {
+                    format!("{}.{}", package, candidate)
+                };
+                graph
+                    .find_node(&origin_fqn)
+                    .filter(|&origin| is_annotated_origin(&graph, origin))
+            });
+            if let Some(origin) = origin {
+                links.push((idx, origin));
+            }
+        }
+    }
+    if links.is_empty() {
+        return graph;
+    }
+
+    let mut builder = graph.to_builder();
+    for (generated, origin) in links {
+        builder.add_edge(generated, origin, GraphEdge::new(EdgeType::GeneratedFrom));
+    }
+    builder.build()
+}
+
+fn is_type(kind: &NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Class | NodeKind::Interface | NodeKind::Enum | NodeKind::Annotation
+    )
+}
+
+/// A hand-written project type that carries an annotation, on itself or on
+/// one of its members (`@Inject` constructors for Dagger factories)
+fn is_annotated_origin(graph: &CodeGraph, idx: NodeIndex) -> bool {
+    let topology = graph.topology();
+    let node = &topology[idx];
+    if node.source != NodeSource::Project || !is_type(&node.kind) || graph.is_generated(node) {
+        return false;
+    }
+    let decorated = |idx: NodeIndex| {
+        topology
+            .edges_directed(idx, Direction::Outgoing)
+            .any(|e| e.weight().edge_type == EdgeType::DecoratedBy)
+    };
+    decorated(idx)
+        || topology
+            .edges_directed(idx, Direction::Outgoing)
+            .filter(|e| e.weight().edge_type == EdgeType::Contains)
+            .any(|e| decorated(e.target()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::builder::CodeGraphBuilder;
+    use crate::model::source::SourceFile;
+    use crate::test_support::node_at;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn origin_names_strip_processor_affixes() {
+        assert_eq!(origin_names("QOrder"), ["Order"]);
+        assert_eq!(origin_names("OrderMapperImpl"), ["OrderMapper"]);
+        assert_eq!(origin_names("Order_"), ["Order"]);
+        assert_eq!(origin_names("Order_Factory"), ["Order", "Factory"]);
+        assert_eq!(origin_names("AutoValue_Order"), ["AutoValue", "Order"]);
+        assert!(origin_names("Query").is_empty());
+    }
+
+    #[test]
+    fn generated_types_link_to_annotated_origins() {
+        let mut builder = CodeGraphBuilder::new();
+        let java: Arc<dyn NamingConvention> = Arc::new(naviscope_plugin::StandardNamingConvention);
+        builder.naming_conventions.insert(
+            crate::model::Language::new("java".to_string()),
+            java.clone(),
+        );
+        let entity = builder.add_node(node_at(
+            "shop.Order",
+            NodeKind::Class,
+            "/p/src/shop/Order.java",
+        ));
+        let plain = builder.add_node(node_at(
+            "shop.Cart",
+            NodeKind::Class,
+            "/p/src/shop/Cart.java",
+        ));
+        let annotation = builder.add_node(node_at(
+            "javax.persistence.Entity",
+            NodeKind::Class,
+            "/lib/Entity.java",
+        ));
+        builder.add_edge(entity, annotation, GraphEdge::new(EdgeType::DecoratedBy));
+        let q_order = builder.add_node(node_at(
+            "shop.QOrder",
+            NodeKind::Class,
+            "/p/gen/shop/QOrder.java",
+        ));
+        let q_cart = builder.add_node(node_at(
+            "shop.QCart",
+            NodeKind::Class,
+            "/p/gen/shop/QCart.java",
+        ));
+        for path in ["/p/gen/shop/QOrder.java", "/p/gen/shop/QCart.java"] {
+            builder.update_file(
+                Path::new(path),
+                SourceFile {
+                    generated: true,
+                    ..SourceFile::new(PathBuf::from(path), 0, 0)
+                },
+            );
+        }
+
+        let conventions = HashMap::from([("java".to_string(), java)]);
+        let graph = link_generated_types(builder.build(), &conventions);
+        let topology = graph.topology();
+        let generated_from = |idx: NodeIndex| -> Vec<NodeIndex> {
+            topology
+                .edges_directed(idx, Direction::Outgoing)
+                .filter(|e| e.weight().edge_type == EdgeType::GeneratedFrom)
+                .map(|e| e.target())
+                .collect()
+        };
+        assert_eq!(generated_from(q_order), [entity]);
+        // `Cart` carries no annotation, so nothing was generated from it
+        assert!(generated_from(q_cart).is_empty());
+        assert!(generated_from(plain).is_empty());
+        assert!(graph.is_generated(&topology[q_order]));
+        assert!(!graph.is_generated(&topology[entity]));
+    }
+}
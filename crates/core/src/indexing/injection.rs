@@ -0,0 +1,135 @@
+//! Links from classes to the beans injected into them
+//!
+//! Language plugins record an injection point as an `Injects` edge from the
+//! injected member (a field, a constructor, a setter) to its declared type,
+//! usually an interface, and the beans that stand in for a type as `BeanOf`
+//! edges. After indexing, the class declaring an injection point gets an
+//! `Injects` edge to each bean of the declared type, or to the type itself
+//! when no bean is known for it, so the concrete classes a class depends on
+//! are one edge away. These class-level edges are derived from the member
+//! edges alone and rebuilt whenever those change.
+
+use crate::model::{CodeGraph, GraphEdge, NodeKind};
+use naviscope_api::models::graph::EdgeType;
+use petgraph::Direction;
+use petgraph::stable_graph::NodeIndex;
+use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+use std::collections::BTreeSet;
+
+/// Replace the class-level `Injects` edges with those the injection points
+/// of the graph yield. Returns the graph unchanged when they are the same.
+pub fn link_injected_beans(graph: CodeGraph) -> CodeGraph {
+    let topology = graph.topology();
+    let mut existing = Vec::new();
+    let mut links = BTreeSet::new();
+    for edge in topology.edge_references() {
+        if edge.weight().edge_type != EdgeType::Injects {
+            continue;
+        }
+        let (member, declared) = (edge.source(), edge.target());
+        if is_type(&topology[member].kind) {
+            existing.push((edge.id(), member, declared));
+            continue;
+        }
+        let Some(class) = topology
+            .edges_directed(member, Direction::Incoming)
+            .find(|e| e.weight().edge_type == EdgeType::Contains)
+            .map(|e| e.source())
+        else {
+            continue;
+        };
+        let beans: Vec<NodeIndex> = topology
+            .edges_directed(declared, Direction::Incoming)
+            .filter(|e| e.weight().edge_type == EdgeType::BeanOf)
+            .map(|e| e.source())
+            .collect();
+        if beans.is_empty() {
+            links.insert((class, declared));
+        }
+        // A decorator is injected with the bean it wraps, not with itself
+        links.extend(
+            beans
+                .into_iter()
+                .filter(|&bean| bean != class)
+                .map(|bean| (class, bean)),
+        );
+    }
+    let existing_links: BTreeSet<(NodeIndex, NodeIndex)> =
+        existing.iter().map(|&(_, from, to)| (from, to)).collect();
+    if existing_links == links {
+        return graph;
+    }
+
+    let mut builder = graph.to_builder();
+    for (edge, _, _) in existing {
+        builder.remove_edge(edge);
+    }
+    for (class, bean) in links {
+        builder.add_edge(class, bean, GraphEdge::new(EdgeType::Injects));
+    }
+    builder.build()
+}
+
+fn is_type(kind: &NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Class | NodeKind::Interface | NodeKind::Enum | NodeKind::Annotation
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::builder::CodeGraphBuilder;
+    use crate::test_support::node;
+
+    fn injected(graph: &CodeGraph, class: NodeIndex) -> Vec<NodeIndex> {
+        let mut targets: Vec<NodeIndex> = graph
+            .topology()
+            .edges_directed(class, Direction::Outgoing)
+            .filter(|e| e.weight().edge_type == EdgeType::Injects)
+            .map(|e| e.target())
+            .collect();
+        targets.sort();
+        targets
+    }
+
+    #[test]
+    fn classes_are_injected_with_the_beans_of_declared_types() {
+        let mut builder = CodeGraphBuilder::new();
+        let service = builder.add_node(node("OrderService", NodeKind::Class));
+        let field = builder.add_node(node("repository", NodeKind::Field));
+        let clock_field = builder.add_node(node("clock", NodeKind::Field));
+        let repository = builder.add_node(node("OrderRepository", NodeKind::Interface));
+        let jpa = builder.add_node(node("JpaOrderRepository", NodeKind::Class));
+        let clock = builder.add_node(node("Clock", NodeKind::Class));
+        builder.add_edge(service, field, GraphEdge::new(EdgeType::Contains));
+        builder.add_edge(service, clock_field, GraphEdge::new(EdgeType::Contains));
+        builder.add_edge(field, repository, GraphEdge::new(EdgeType::Injects));
+        builder.add_edge(clock_field, clock, GraphEdge::new(EdgeType::Injects));
+        builder.add_edge(jpa, repository, GraphEdge::new(EdgeType::BeanOf));
+
+        let graph = link_injected_beans(builder.build());
+        assert_eq!(injected(&graph, service), {
+            let mut expected = vec![jpa, clock];
+            expected.sort();
+            expected
+        });
+        // Linking again changes nothing
+        let graph = link_injected_beans(graph);
+        assert_eq!(injected(&graph, service).len(), 2);
+
+        // Another bean of the interface is injected as well, the field no
+        // longer asks for a clock
+        let mut builder = graph.to_builder();
+        let memory = builder.add_node(node("InMemoryOrderRepository", NodeKind::Class));
+        builder.add_edge(memory, repository, GraphEdge::new(EdgeType::BeanOf));
+        builder.remove_node(clock_field);
+        let graph = link_injected_beans(builder.build());
+        assert_eq!(injected(&graph, service), {
+            let mut expected = vec![jpa, memory];
+            expected.sort();
+            expected
+        });
+    }
+}
@@ -1,6 +1,14 @@
 pub mod build;
+pub mod clones;
+pub mod containment;
+pub mod coverage;
+pub mod generated;
+pub mod injection;
 pub mod scanner;
 pub mod source;
+pub mod source_roots;
+pub mod text;
+pub mod warnings;
 
 pub use naviscope_plugin::IndexNode;
 
@@ -2,6 +2,8 @@ use super::is_relevant_path;
 
 use crate::model::source::SourceFile;
 use ignore::WalkBuilder;
+use ignore::gitignore::Gitignore;
+use rayon::prelude::*;
 use std::collections::HashMap;
 use std::fs;
 use std::hash::Hasher;
@@ -13,6 +15,15 @@ pub use naviscope_plugin::{ParsedContent, ParsedFile};
 
 pub struct Scanner;
 
+/// A file that differs from its indexed version
+pub(crate) enum ScannedFile {
+    /// The content changed
+    Changed(ParsedFile),
+    /// Only the modification time changed; the metadata to record so the
+    /// next scan need not read the file again
+    Touched(SourceFile),
+}
+
 impl Scanner {
     pub fn scan_files_iter<'a>(
         paths: Vec<PathBuf>,
@@ -23,8 +34,26 @@ impl Scanner {
             .filter_map(|path| Self::parse_path(&path, existing_files))
     }
 
-    pub(crate) fn collect_paths(root: &Path) -> Vec<PathBuf> {
-        WalkBuilder::new(root)
+    /// Relevant files under `root`, skipping those matched by `ignore`.
+    pub(crate) fn collect_paths(root: &Path, ignore: &Gitignore) -> Vec<PathBuf> {
+        Self::walk(WalkBuilder::new(root), ignore)
+    }
+
+    /// Relevant files under the generated source root `root`. Build output is
+    /// usually excluded from version control, so only `ignore` applies.
+    pub(crate) fn collect_generated_paths(root: &Path, ignore: &Gitignore) -> Vec<PathBuf> {
+        let mut builder = WalkBuilder::new(root);
+        builder.standard_filters(false);
+        Self::walk(builder, ignore)
+    }
+
+    fn walk(mut builder: WalkBuilder, ignore: &Gitignore) -> Vec<PathBuf> {
+        let ignore = ignore.clone();
+        builder
+            .filter_entry(move |entry| {
+                let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+                !ignore.matched(entry.path(), is_dir).is_ignore()
+            })
             .build()
             .filter_map(|entry| {
                 let entry = entry.ok()?;
@@ -47,10 +76,38 @@ impl Scanner {
             path: path.to_path_buf(),
             content_hash: hash,
             last_modified: mtime,
+            generated: false,
         })
     }
 
-    fn parse_path(path: &Path, existing_files: &HashMap<PathBuf, SourceFile>) -> Option<ParsedFile> {
+    /// Read and hash the files at `paths` in parallel, keeping their order
+    /// and leaving out those unchanged since `existing_files`
+    pub(crate) fn check_files(
+        paths: Vec<PathBuf>,
+        existing_files: &HashMap<PathBuf, SourceFile>,
+    ) -> Vec<ScannedFile> {
+        paths
+            .par_iter()
+            .filter_map(|path| Self::check_path(path, existing_files))
+            .collect()
+    }
+
+    /// The file at `path`, unless it is unchanged since `existing_files` or
+    /// cannot be read
+    pub(crate) fn parse_path(
+        path: &Path,
+        existing_files: &HashMap<PathBuf, SourceFile>,
+    ) -> Option<ParsedFile> {
+        match Self::check_path(path, existing_files)? {
+            ScannedFile::Changed(file) => Some(file),
+            ScannedFile::Touched(_) => None,
+        }
+    }
+
+    fn check_path(
+        path: &Path,
+        existing_files: &HashMap<PathBuf, SourceFile>,
+    ) -> Option<ScannedFile> {
         // 1. Check metadata (mtime) first
         let metadata = fs::metadata(path).ok()?;
         let modified = metadata
@@ -72,17 +129,58 @@ impl Scanner {
         // 3. Double check hash (mtime might change but content remains same)
         if let Some(existing) = existing_files.get(path) {
             if existing.content_hash == source_file.content_hash {
-                return None;
+                return Some(ScannedFile::Touched(SourceFile {
+                    last_modified: modified,
+                    ..existing.clone()
+                }));
             }
         }
 
         if path.extension().is_some() {
-            Some(ParsedFile {
+            Some(ScannedFile::Changed(ParsedFile {
                 file: source_file,
                 content: ParsedContent::Lazy,
-            })
+            }))
         } else {
             None
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_with_a_new_mtime_but_the_same_content_are_only_touched() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("A.java");
+        fs::write(&path, "class A {}").unwrap();
+        let ScannedFile::Changed(file) = Scanner::check_path(&path, &HashMap::new()).unwrap()
+        else {
+            panic!("a new file is changed");
+        };
+
+        let mut existing = HashMap::new();
+        existing.insert(path.clone(), file.file.clone());
+        assert!(Scanner::check_path(&path, &existing).is_none());
+
+        let stale = SourceFile {
+            last_modified: file.file.last_modified - 60,
+            generated: true,
+            ..file.file.clone()
+        };
+        existing.insert(path.clone(), stale);
+        let Some(ScannedFile::Touched(touched)) = Scanner::check_path(&path, &existing) else {
+            panic!("an unchanged file is touched");
+        };
+        assert_eq!(touched.last_modified, file.file.last_modified);
+        assert!(touched.generated);
+
+        fs::write(&path, "class A { int x; }").unwrap();
+        assert!(matches!(
+            Scanner::check_files(vec![path], &existing).as_slice(),
+            [ScannedFile::Changed(_)]
+        ));
+    }
+}
@@ -1,14 +1,20 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, RwLock};
 
 use naviscope_plugin::{
-    LanguageCaps, ParsedFile, ProjectContext, SourceAnalyzeArtifact, SourceCollectArtifact,
+    LanguageCaps, ParsedContent, ParsedFile, ProjectContext, SourceAnalyzeArtifact,
+    SourceCollectArtifact, SourceFile,
 };
 
 use crate::error::{NaviscopeError, Result};
 use crate::indexing::StubRequest;
+use crate::indexing::clones::method_fingerprints;
+use crate::indexing::text::trigrams;
+use crate::indexing::warnings::WarningCollector;
 use crate::model::{CodeGraph, GraphOp};
+use naviscope_api::lifecycle::{IndexPhase, WarningSeverity};
 
 use super::stub_ops::{find_asset_for_fqn, plan_stub_requests, resolve_stub_requests};
 
@@ -22,6 +28,7 @@ pub struct SourcePhaseExecutor {
     pub analyze_cache: Arc<Mutex<HashMap<PathBuf, Box<dyn SourceAnalyzeArtifact>>>>,
     pub collect_cache_limit: usize,
     pub analyze_cache_limit: usize,
+    pub warnings: WarningCollector,
 }
 
 pub struct SourceLowerOutput {
@@ -45,7 +52,7 @@ impl SourcePhaseExecutor {
             .map_err(|_| NaviscopeError::Internal("collect cache poisoned".to_string()))?;
 
         if let Some(collected) = cache.get(file.path()) {
-            self.merge_collected_symbols(collected.as_ref())?;
+            self.merge_collected_symbols(file.path(), collected.as_ref())?;
             return Ok(());
         }
 
@@ -58,7 +65,7 @@ impl SourcePhaseExecutor {
             .indexing
             .collect_source(file, &context)
             .map_err(|e| NaviscopeError::Internal(e.to_string()))?;
-        self.merge_collected_symbols(collected.as_ref())?;
+        self.merge_collected_symbols(file.path(), collected.as_ref())?;
         bounded_insert(
             &mut cache,
             file.path().to_path_buf(),
@@ -151,7 +158,7 @@ impl SourcePhaseExecutor {
             }
         };
 
-        let unit = caps
+        let mut unit = caps
             .indexing
             .lower_source(analyzed, &context)
             .map_err(|e| NaviscopeError::Internal(e.to_string()))?;
@@ -162,12 +169,33 @@ impl SourcePhaseExecutor {
             path: Arc::from(path.as_path()),
         });
         ops.push(GraphOp::UpdateFile {
-            metadata: file.file.clone(),
+            metadata: SourceFile {
+                generated: context.is_generated(&path),
+                ..file.file.clone()
+            },
         });
+        self.warnings.extend(
+            &path,
+            IndexPhase::Resolving,
+            std::mem::take(&mut unit.warnings),
+        );
 
         let deferred_targets: Vec<String> =
             unit.deferred_symbols.into_iter().map(|d| d.target).collect();
         ops.extend(unit.ops);
+        match content_text(file) {
+            Ok(text) => ops.push(GraphOp::UpdateContent {
+                path: Arc::from(path.as_path()),
+                trigrams: trigrams(&text),
+                fingerprints: method_fingerprints(&path, &text, &ops),
+            }),
+            Err(e) => self.warnings.push(
+                &path,
+                IndexPhase::Resolving,
+                WarningSeverity::Info,
+                format!("not searchable as text: {}", e),
+            ),
+        }
 
         let routes_snapshot = self
             .routes
@@ -192,7 +220,11 @@ impl SourcePhaseExecutor {
         )
     }
 
-    fn merge_collected_symbols(&self, collected: &dyn SourceCollectArtifact) -> Result<()> {
+    fn merge_collected_symbols(
+        &self,
+        path: &Path,
+        collected: &dyn SourceCollectArtifact,
+    ) -> Result<()> {
         let mut ctx = self
             .project_context
             .write()
@@ -203,6 +235,18 @@ impl SourcePhaseExecutor {
         for sym in collected.collected_method_symbols() {
             ctx.symbol_table.method_symbols.insert(sym.clone());
         }
+        if let (Some(package), Some(dir)) = (collected.declared_package(), path.parent()) {
+            ctx.symbol_table
+                .package_dirs
+                .entry(package.to_string())
+                .or_default()
+                .insert(dir.to_path_buf());
+        }
+        if let Some(module) = collected.module_descriptor() {
+            ctx.symbol_table
+                .module_descriptors
+                .insert(module.name.clone(), module.clone());
+        }
         Ok(())
     }
 }
@@ -237,3 +281,14 @@ fn deferred_targets_to_stub_requests(
 
     out
 }
+
+/// Content of a source file, read from disk unless already loaded
+fn content_text(file: &ParsedFile) -> std::io::Result<Cow<'_, str>> {
+    match &file.content {
+        ParsedContent::Language(result) if result.source.is_some() => {
+            Ok(Cow::Borrowed(result.source.as_deref().unwrap_or_default()))
+        }
+        ParsedContent::Unparsed(text) => Ok(Cow::Borrowed(text)),
+        _ => std::fs::read_to_string(file.path()).map(Cow::Owned),
+    }
+}
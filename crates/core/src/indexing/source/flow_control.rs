@@ -1,32 +1,55 @@
+use crate::config::Tuning;
+
 #[derive(Clone, Copy)]
 pub(super) struct SourceFlowControl {
     pub(super) max_parallelism: usize,
     pub(super) collect_cache_limit: usize,
     pub(super) analyze_cache_limit: usize,
+    pub(super) prefetch_stubs: bool,
+    /// Quotas of the stub topics, see `permits`
+    pub(super) bulk_stub_slots: usize,
+    pub(super) interactive_stub_slots: usize,
+}
+
+impl SourceFlowControl {
+    /// Limits from the active profile in `naviscope.toml`; environment
+    /// variables take precedence.
+    pub(super) fn from_tuning(tuning: &Tuning) -> Self {
+        Self {
+            max_parallelism: env_limit("NAVISCOPE_SOURCE_MAX_PARALLELISM")
+                .unwrap_or(tuning.threads),
+            collect_cache_limit: env_limit("NAVISCOPE_SOURCE_COLLECT_CACHE_LIMIT")
+                .unwrap_or(tuning.collect_cache_limit),
+            analyze_cache_limit: env_limit("NAVISCOPE_SOURCE_ANALYZE_CACHE_LIMIT")
+                .unwrap_or(tuning.analyze_cache_limit),
+            prefetch_stubs: tuning.prefetch_stubs,
+            bulk_stub_slots: tuning.stub_bulk_slots,
+            interactive_stub_slots: tuning.stub_interactive_slots,
+        }
+    }
+}
+
+fn env_limit(name: &str) -> Option<usize> {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|v| *v > 0)
 }
 
 impl Default for SourceFlowControl {
     fn default() -> Self {
-        let max_parallelism = std::env::var("NAVISCOPE_SOURCE_MAX_PARALLELISM")
-            .ok()
-            .and_then(|v| v.parse::<usize>().ok())
-            .filter(|v| *v > 0)
+        let max_parallelism = env_limit("NAVISCOPE_SOURCE_MAX_PARALLELISM")
             .unwrap_or_else(|| std::thread::available_parallelism().map_or(4, usize::from));
-        let collect_cache_limit = std::env::var("NAVISCOPE_SOURCE_COLLECT_CACHE_LIMIT")
-            .ok()
-            .and_then(|v| v.parse::<usize>().ok())
-            .filter(|v| *v > 0)
-            .unwrap_or(512);
-        let analyze_cache_limit = std::env::var("NAVISCOPE_SOURCE_ANALYZE_CACHE_LIMIT")
-            .ok()
-            .and_then(|v| v.parse::<usize>().ok())
-            .filter(|v| *v > 0)
-            .unwrap_or(512);
+        let collect_cache_limit = env_limit("NAVISCOPE_SOURCE_COLLECT_CACHE_LIMIT").unwrap_or(512);
+        let analyze_cache_limit = env_limit("NAVISCOPE_SOURCE_ANALYZE_CACHE_LIMIT").unwrap_or(512);
 
         Self {
             max_parallelism,
             collect_cache_limit,
             analyze_cache_limit,
+            prefetch_stubs: true,
+            bulk_stub_slots: 1,
+            interactive_stub_slots: 2,
         }
     }
 }
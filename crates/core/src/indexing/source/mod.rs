@@ -1,5 +1,6 @@
 mod executor;
 mod flow_control;
+mod permits;
 mod stub_ops;
 
 use std::collections::HashMap;
@@ -7,15 +8,22 @@ use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
 
+use naviscope_api::lifecycle::{IndexControl, IndexPhase};
 use naviscope_plugin::{LanguageCaps, NamingConvention, ParsedFile, ProjectContext};
 use rayon::prelude::*;
 
+use crate::config::Tuning;
 use crate::error::{NaviscopeError, Result};
 use crate::indexing::StubRequest;
+use crate::indexing::scanner::Scanner;
+use crate::indexing::warnings::WarningCollector;
 use crate::model::{CodeGraph, GraphOp, Language};
+use crate::telemetry::{IngestQueue, record_queue_depth};
 
 use executor::{SourceLowerOutput, SourcePhaseExecutor};
 use flow_control::SourceFlowControl;
+use permits::IntakePermits;
+pub use permits::{IntakeSlot, IntakeTopic};
 use stub_ops::resolve_stub_requests;
 pub use stub_ops::plan_stub_requests;
 
@@ -24,18 +32,59 @@ pub struct SourceCompiler {
     completed_source_epochs: AtomicU64,
     pending_stub_requests: Arc<Mutex<Vec<StubRequest>>>,
     flow_control: SourceFlowControl,
+    permits: IntakePermits,
 }
 
 impl SourceCompiler {
     pub fn new() -> Self {
+        Self::with_flow_control(SourceFlowControl::default())
+    }
+
+    /// Compiler using the thread count, cache limits, stub prefetching and
+    /// stub quotas of a profile.
+    pub fn with_tuning(tuning: &Tuning) -> Self {
+        Self::with_flow_control(SourceFlowControl::from_tuning(tuning))
+    }
+
+    fn with_flow_control(flow_control: SourceFlowControl) -> Self {
         Self {
             inflight_compiles: AtomicUsize::new(0),
             completed_source_epochs: AtomicU64::new(0),
             pending_stub_requests: Arc::new(Mutex::new(Vec::new())),
-            flow_control: SourceFlowControl::default(),
+            permits: IntakePermits::new(&flow_control),
+            flow_control,
         }
     }
 
+    /// Wait for a stub generation slot of `topic`; it is held until the
+    /// returned slot is dropped.
+    pub async fn acquire_slot(&self, topic: IntakeTopic) -> IntakeSlot {
+        self.permits.acquire(topic).await
+    }
+
+    /// Read and hash the files at `paths` on the compiler's threads, keeping
+    /// their order. Files that cannot be read are left out.
+    pub async fn scan_source_files(
+        &self,
+        paths: Vec<PathBuf>,
+        control: IndexControl,
+    ) -> Result<Vec<ParsedFile>> {
+        let flow = self.flow_control;
+        tokio::task::spawn_blocking(move || {
+            let existing = HashMap::new();
+            let scanned = PhaseCounter::new(&control, IndexPhase::Scanning, paths.len());
+            let files: Vec<Option<ParsedFile>> = thread_pool(flow)?.install(|| {
+                paths
+                    .par_iter()
+                    .map(|path| scanned.run(|| Ok(Scanner::parse_path(path, &existing))))
+                    .collect::<Result<_>>()
+            })?;
+            Ok(files.into_iter().flatten().collect())
+        })
+        .await
+        .map_err(|e| NaviscopeError::Internal(e.to_string()))?
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn compile_source_files(
         &self,
@@ -47,6 +96,8 @@ impl SourceCompiler {
         naming_conventions: Arc<HashMap<String, Arc<dyn NamingConvention>>>,
         lang_caps: Arc<Vec<LanguageCaps>>,
         stub_cache: Arc<crate::cache::GlobalStubCache>,
+        control: IndexControl,
+        warnings: WarningCollector,
     ) -> Result<CodeGraph> {
         if source_files.is_empty() {
             return Ok(base_graph);
@@ -62,6 +113,7 @@ impl SourceCompiler {
             let phase_lang_caps = Arc::clone(&lang_caps);
             let phase_stub_cache = Arc::clone(&stub_cache);
             let flow = self.flow_control;
+            let permits = self.permits.clone();
             move || {
                 run_source_phases_blocking(
                     source_files,
@@ -72,6 +124,9 @@ impl SourceCompiler {
                     phase_lang_caps,
                     phase_stub_cache,
                     flow,
+                    permits,
+                    control,
+                    warnings,
                 )
             }
         })
@@ -93,6 +148,7 @@ impl SourceCompiler {
     ) -> bool {
         if let Ok(mut pending) = self.pending_stub_requests.lock() {
             pending.push(req);
+            record_queue_depth(IngestQueue::StubRequests, pending.len());
         } else {
             return false;
         }
@@ -121,12 +177,16 @@ impl SourceCompiler {
 
     fn drain_pending_stub_requests(queue: &Arc<Mutex<Vec<StubRequest>>>) -> Vec<StubRequest> {
         match queue.lock() {
-            Ok(mut pending) => pending.drain(..).collect(),
+            Ok(mut pending) => {
+                record_queue_depth(IngestQueue::StubRequests, 0);
+                pending.drain(..).collect()
+            }
             Err(_) => Vec::new(),
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_source_phases_blocking(
     source_files: Vec<ParsedFile>,
     project_context: ProjectContext,
@@ -136,9 +196,11 @@ fn run_source_phases_blocking(
     lang_caps: Arc<Vec<LanguageCaps>>,
     stub_cache: Arc<crate::cache::GlobalStubCache>,
     flow: SourceFlowControl,
+    permits: IntakePermits,
+    control: IndexControl,
+    warnings: WarningCollector,
 ) -> Result<Vec<GraphOp>> {
-    let mut queued_stub_requests =
-        SourceCompiler::drain_pending_stub_requests(&pending_stub_requests);
+    let queued_stub_requests = SourceCompiler::drain_pending_stub_requests(&pending_stub_requests);
 
     let executor = Arc::new(SourcePhaseExecutor {
         lang_caps,
@@ -150,27 +212,39 @@ fn run_source_phases_blocking(
         analyze_cache: Arc::new(Mutex::new(HashMap::new())),
         collect_cache_limit: flow.collect_cache_limit,
         analyze_cache_limit: flow.analyze_cache_limit,
+        warnings,
     });
 
-    let thread_pool = rayon::ThreadPoolBuilder::new()
-        .num_threads(flow.max_parallelism.max(1))
-        .build()
-        .map_err(|e| NaviscopeError::Internal(e.to_string()))?;
+    let thread_pool = thread_pool(flow)?;
 
+    let total = source_files.len();
+    // Parent of the spans of the files, which run on the pool's threads
+    let compile_span = tracing::info_span!("compile_sources", files = total);
+    let parsed = PhaseCounter::new(&control, IndexPhase::Parsing, total);
     let collect_results: Vec<Result<()>> = thread_pool.install(|| {
         source_files
             .par_iter()
-            .map(|file| executor.collect_file(file))
+            .map(|file| {
+                let _span = tracing::info_span!(parent: &compile_span, "parse_file", path = %file.path().display())
+                    .entered();
+                parsed.run(|| executor.collect_file(file))
+            })
             .collect()
     });
     for result in collect_results {
         result?;
     }
 
+    // Analysis and lowering both count towards resolving
+    let resolved = PhaseCounter::new(&control, IndexPhase::Resolving, total * 2);
     let analyze_results: Vec<Result<()>> = thread_pool.install(|| {
         source_files
             .par_iter()
-            .map(|file| executor.analyze_file(file))
+            .map(|file| {
+                let _span = tracing::info_span!(parent: &compile_span, "resolve_file", path = %file.path().display())
+                    .entered();
+                resolved.run(|| executor.analyze_file(file))
+            })
             .collect()
     });
     for result in analyze_results {
@@ -180,7 +254,11 @@ fn run_source_phases_blocking(
     let lowered_results: Vec<Result<SourceLowerOutput>> = thread_pool.install(|| {
         source_files
             .par_iter()
-            .map(|file| executor.lower_file(file))
+            .map(|file| {
+                let _span = tracing::info_span!(parent: &compile_span, "lower_file", path = %file.path().display())
+                    .entered();
+                resolved.run(|| executor.lower_file(file))
+            })
             .collect()
     });
 
@@ -191,18 +269,54 @@ fn run_source_phases_blocking(
         ops.extend(output.ops);
         stub_requests.extend(output.stub_requests);
     }
-    queued_stub_requests.extend(stub_requests);
-    queued_stub_requests.extend(SourceCompiler::drain_pending_stub_requests(
-        &pending_stub_requests,
-    ));
-    let stub_ops = executor.stub_phase(queued_stub_requests);
-    if !stub_ops.is_empty() {
-        ops.extend(stub_ops);
+    // Each symbol is generated once, however many requests name it
+    let mut seen = std::collections::HashSet::new();
+    let mut stub_phase = |requests: Vec<StubRequest>| {
+        let fresh = requests
+            .into_iter()
+            .filter(|req| seen.insert(req.fqn.clone()))
+            .collect();
+        executor.stub_phase(fresh)
+    };
+
+    // Requests queued before the phase were made on demand
+    ops.extend(stub_phase(queued_stub_requests));
+
+    // Without prefetching, library stubs are only generated on request.
+    // Planned stubs go in chunks, each under a bulk slot; requests queued
+    // meanwhile are served before every chunk instead of after all of them.
+    let mut planned = if flow.prefetch_stubs {
+        stub_requests
+    } else {
+        Vec::new()
+    };
+    loop {
+        let interactive = SourceCompiler::drain_pending_stub_requests(&pending_stub_requests);
+        ops.extend(stub_phase(interactive));
+        if planned.is_empty() {
+            break;
+        }
+        let chunk: Vec<StubRequest> = planned
+            .drain(..planned.len().min(BULK_STUB_CHUNK))
+            .collect();
+        let _slot = permits.blocking_acquire(IntakeTopic::Bulk);
+        ops.extend(stub_phase(chunk));
     }
 
     Ok(ops)
 }
 
+/// Planned stub requests generated per bulk slot
+const BULK_STUB_CHUNK: usize = 64;
+
+/// Workers parsing, resolving and scanning sources
+fn thread_pool(flow: SourceFlowControl) -> Result<rayon::ThreadPool> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(flow.max_parallelism.max(1))
+        .build()
+        .map_err(|e| NaviscopeError::Internal(e.to_string()))
+}
+
 fn apply_ops_to_graph(
     base_graph: CodeGraph,
     naming_conventions: Arc<HashMap<String, Arc<dyn NamingConvention>>>,
@@ -254,6 +368,36 @@ fn apply_ops_to_current(
     }
 }
 
+/// Counts files finished in one phase and stops work once cancelled
+struct PhaseCounter<'a> {
+    control: &'a IndexControl,
+    phase: IndexPhase,
+    total: usize,
+    done: AtomicUsize,
+}
+
+impl<'a> PhaseCounter<'a> {
+    fn new(control: &'a IndexControl, phase: IndexPhase, total: usize) -> Self {
+        control.report(phase, 0, total);
+        Self {
+            control,
+            phase,
+            total,
+            done: AtomicUsize::new(0),
+        }
+    }
+
+    fn run<T>(&self, work: impl FnOnce() -> Result<T>) -> Result<T> {
+        if self.control.is_cancelled() {
+            return Err(NaviscopeError::Cancelled);
+        }
+        let result = work();
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        self.control.report(self.phase, done, self.total);
+        result
+    }
+}
+
 struct CompileGuard<'a> {
     inflight_compiles: &'a AtomicUsize,
 }
@@ -263,3 +407,39 @@ impl Drop for CompileGuard<'_> {
         self.inflight_compiles.fetch_sub(1, Ordering::AcqRel);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn parallel_scan_yields_the_files_of_the_serial_one_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut paths: Vec<PathBuf> = (0..32)
+            .map(|i| dir.path().join(format!("F{i}.java")))
+            .collect();
+        for (i, path) in paths.iter().enumerate() {
+            std::fs::write(path, format!("class F{i} {{}}")).unwrap();
+        }
+        // Unreadable files are left out by both
+        paths.insert(5, dir.path().join("Missing.java"));
+        let compiler = SourceCompiler::with_flow_control(SourceFlowControl {
+            max_parallelism: 4,
+            ..SourceFlowControl::default()
+        });
+
+        let parallel = compiler
+            .scan_source_files(paths.clone(), IndexControl::new())
+            .await
+            .unwrap();
+        let serial: Vec<_> = Scanner::scan_files_iter(paths, &HashMap::new()).collect();
+        let files = |files: &[ParsedFile]| {
+            files
+                .iter()
+                .map(|f| (f.file.path.clone(), f.file.content_hash))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(parallel.len(), 32);
+        assert_eq!(files(&parallel), files(&serial));
+    }
+}
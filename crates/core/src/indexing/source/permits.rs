@@ -0,0 +1,88 @@
+//! Slots for library stub generation, reserved per topic
+//!
+//! Stubs are generated for two kinds of callers: builds, which ask for
+//! thousands at once, and editors or agents waiting on a single symbol. Each
+//! topic has its own quota of slots, so a large build can neither take the
+//! slots interactive requests need nor make them queue behind it.
+
+use super::flow_control::SourceFlowControl;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Kind of caller asking for stubs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntakeTopic {
+    /// Stubs planned by a build for the library types its files reference
+    Bulk,
+    /// A request waiting on one symbol
+    Interactive,
+}
+
+/// A taken slot, given back when dropped
+pub struct IntakeSlot {
+    _permit: OwnedSemaphorePermit,
+}
+
+#[derive(Clone)]
+pub(super) struct IntakePermits {
+    bulk: Arc<Semaphore>,
+    interactive: Arc<Semaphore>,
+}
+
+impl IntakePermits {
+    pub(super) fn new(flow: &SourceFlowControl) -> Self {
+        Self {
+            bulk: Arc::new(Semaphore::new(flow.bulk_stub_slots.max(1))),
+            interactive: Arc::new(Semaphore::new(flow.interactive_stub_slots.max(1))),
+        }
+    }
+
+    /// Wait for a free slot of `topic`.
+    pub(super) async fn acquire(&self, topic: IntakeTopic) -> IntakeSlot {
+        let semaphore = match topic {
+            IntakeTopic::Bulk => &self.bulk,
+            IntakeTopic::Interactive => &self.interactive,
+        };
+        let permit = Arc::clone(semaphore)
+            .acquire_owned()
+            .await
+            .expect("intake semaphores are never closed");
+        IntakeSlot { _permit: permit }
+    }
+
+    /// Like `acquire`, for the compiler's blocking threads. Outside a tokio
+    /// runtime no other caller can hold a slot, so none is taken.
+    pub(super) fn blocking_acquire(&self, topic: IntakeTopic) -> Option<IntakeSlot> {
+        let handle = tokio::runtime::Handle::try_current().ok()?;
+        Some(handle.block_on(self.acquire(topic)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn bulk_slots_do_not_hold_back_interactive_ones() {
+        let flow = SourceFlowControl {
+            bulk_stub_slots: 1,
+            interactive_stub_slots: 1,
+            ..SourceFlowControl::default()
+        };
+        let permits = IntakePermits::new(&flow);
+
+        let _bulk = permits.acquire(IntakeTopic::Bulk).await;
+        let waiting = tokio::time::timeout(
+            Duration::from_millis(20),
+            permits.acquire(IntakeTopic::Bulk),
+        );
+        assert!(waiting.await.is_err(), "the only bulk slot is taken");
+
+        let interactive = tokio::time::timeout(
+            Duration::from_millis(20),
+            permits.acquire(IntakeTopic::Interactive),
+        );
+        assert!(interactive.await.is_ok());
+    }
+}
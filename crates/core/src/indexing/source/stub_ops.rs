@@ -1,13 +1,18 @@
 use std::sync::Arc;
+use std::time::Duration;
 use std::{collections::HashMap, path::PathBuf};
 
 use naviscope_api::models::EdgeType;
 use naviscope_api::models::graph::NodeSource;
 use naviscope_plugin::{AssetEntry, AssetSource, LanguageCaps};
 
+use crate::cache::StubClaim;
 use crate::indexing::StubRequest;
 use crate::model::{CodeGraph, GraphOp};
 
+/// Longest wait for another process generating the same stub
+const CLAIM_WAIT: Duration = Duration::from_secs(5);
+
 pub fn plan_stub_requests(
     ops: &[GraphOp],
     routes: &HashMap<String, Vec<PathBuf>>,
@@ -81,7 +86,11 @@ pub fn generate_stub_ops(
                 if let Some(idx) = graph.find_node(&fqn)
                     && let Some(node) = graph.get_node(idx)
                 {
-                    return node.status == naviscope_api::models::graph::ResolutionStatus::Resolved;
+                    return matches!(
+                        node.status,
+                        naviscope_api::models::graph::ResolutionStatus::Resolved
+                            | naviscope_api::models::graph::ResolutionStatus::PartialParse
+                    );
                 }
                 false
             })
@@ -100,7 +109,11 @@ pub fn generate_stub_ops(
             if let Some(idx) = graph.find_node(&fqn)
                 && let Some(node) = graph.get_node(idx)
             {
-                return node.status == naviscope_api::models::graph::ResolutionStatus::Resolved;
+                return matches!(
+                    node.status,
+                    naviscope_api::models::graph::ResolutionStatus::Resolved
+                        | naviscope_api::models::graph::ResolutionStatus::PartialParse
+                );
             }
             false
         })
@@ -120,6 +133,24 @@ pub fn generate_stub_ops(
             if !generator.can_generate(asset_path) {
                 continue;
             }
+
+            // Another engine process sharing the cache may be generating this stub:
+            // wait for it and reuse the stored result instead of duplicating work.
+            let _claim = match asset_key.as_ref() {
+                Some(key) => match stub_cache.claim(key, &req.fqn) {
+                    StubClaim::Acquired(guard) => Some(guard),
+                    StubClaim::Busy => {
+                        if let Some(node) = stub_cache.wait_for(key, &req.fqn, CLAIM_WAIT) {
+                            ops.push(GraphOp::AddNode { data: Some(node) });
+                            break;
+                        }
+                        None
+                    }
+                    StubClaim::Unavailable => None,
+                },
+                None => None,
+            };
+
             let cached_primary = asset_key
                 .as_ref()
                 .and_then(|k| stub_cache.lookup(k, &req.fqn));
@@ -145,8 +176,9 @@ pub fn generate_stub_ops(
 
                     if let Some(ref key) = asset_key
                         && let Some(primary) = nodes.iter().find(|n| n.id.to_string() == req.fqn)
+                        && let Err(e) = stub_cache.store(key, primary)
                     {
-                        stub_cache.store(key, primary);
+                        tracing::debug!("Failed to persist stub for {}: {}", req.fqn, e);
                     }
 
                     let mut seen = std::collections::HashSet::new();
@@ -0,0 +1,238 @@
+//! Source root detection for projects with non-standard layouts.
+//!
+//! Roots come from three places, in order of precedence:
+//! 1. `[sources] roots` in `naviscope.toml` (replaces everything else)
+//! 2. Conventional directories under modules known from build metadata,
+//!    including the output directories of annotation processors
+//! 3. Package declarations whose segments match the trailing directories of the file
+
+use crate::config::SourcesConfig;
+use naviscope_api::graph::{SourceRoot, SourceRootOrigin};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Source directories looked up under every module directory.
+const CONVENTIONAL_ROOTS: &[&str] = &[
+    "src/main/java",
+    "src/test/java",
+    "src/main/kotlin",
+    "src/test/kotlin",
+    "src/main/groovy",
+    "src/test/groovy",
+];
+
+/// Annotation processor output looked up under every module directory, as
+/// written by Gradle (`annotationProcessor`) and Maven (`maven-compiler-plugin`).
+pub const GENERATED_ROOTS: &[&str] = &[
+    "build/generated/sources/annotationProcessor/java/main",
+    "build/generated/sources/annotationProcessor/java/test",
+    "target/generated-sources/annotations",
+    "target/generated-test-sources/test-annotations",
+];
+
+pub struct SourceRootDetector<'a> {
+    project_root: &'a Path,
+    config: &'a SourcesConfig,
+}
+
+impl<'a> SourceRootDetector<'a> {
+    pub fn new(project_root: &'a Path, config: &'a SourcesConfig) -> Self {
+        Self {
+            project_root,
+            config,
+        }
+    }
+
+    /// Detect source roots for `files`, using `module_dirs` from build metadata.
+    pub fn detect(&self, module_dirs: &[PathBuf], files: &[PathBuf]) -> Vec<SourceRoot> {
+        if !self.config.roots.is_empty() {
+            return self
+                .config
+                .roots
+                .iter()
+                .map(|root| SourceRoot {
+                    path: self.project_root.join(root),
+                    origin: SourceRootOrigin::Config,
+                })
+                .collect();
+        }
+
+        let mut roots: BTreeMap<PathBuf, SourceRootOrigin> = BTreeMap::new();
+
+        let dirs =
+            std::iter::once(self.project_root).chain(module_dirs.iter().map(PathBuf::as_path));
+        for dir in dirs {
+            for rel in CONVENTIONAL_ROOTS {
+                let candidate = dir.join(rel);
+                if candidate.is_dir() {
+                    roots.insert(candidate, SourceRootOrigin::BuildMetadata);
+                }
+            }
+            for rel in GENERATED_ROOTS {
+                let candidate = dir.join(rel);
+                if candidate.is_dir() {
+                    roots.insert(candidate, SourceRootOrigin::Generated);
+                }
+            }
+        }
+
+        let build_roots: Vec<PathBuf> = roots.keys().cloned().collect();
+        for file in files {
+            if build_roots.iter().any(|r| file.starts_with(r)) {
+                continue;
+            }
+            let Some(package) = fs::read_to_string(file)
+                .ok()
+                .and_then(|content| declared_package(&content))
+            else {
+                continue;
+            };
+            if let Some(root) = root_from_package(file, &package) {
+                roots.entry(root).or_insert(SourceRootOrigin::PackageLayout);
+            }
+        }
+
+        roots
+            .into_iter()
+            .map(|(path, origin)| SourceRoot { path, origin })
+            .collect()
+    }
+}
+
+/// Extract the package declared at the top of a source file.
+///
+/// Only leading comments, blank lines and annotations may precede the declaration.
+pub fn declared_package(content: &str) -> Option<String> {
+    let mut in_block_comment = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if in_block_comment {
+            if line.contains("*/") {
+                in_block_comment = false;
+            }
+            continue;
+        }
+        if line.starts_with("/*") {
+            in_block_comment = !line.contains("*/");
+            continue;
+        }
+        if line.is_empty() || line.starts_with("//") || line.starts_with('@') {
+            continue;
+        }
+
+        let rest = line.strip_prefix("package")?;
+        if !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let name = rest.trim().trim_end_matches(';').trim();
+        return (!name.is_empty()).then(|| name.to_string());
+    }
+    None
+}
+
+/// Infer the source root that makes `package` match the directory of `file`.
+///
+/// Returns `None` when the trailing directories do not spell out the package.
+pub fn root_from_package(file: &Path, package: &str) -> Option<PathBuf> {
+    let mut dir = file.parent()?;
+    for segment in package.rsplit('.') {
+        if dir.file_name()? != segment {
+            return None;
+        }
+        dir = dir.parent()?;
+    }
+    Some(dir.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn declared_package_skips_header_comments() {
+        let src = "/*\n * License\n */\n// comment\npackage com.example.app;\n\nclass A {}";
+        assert_eq!(declared_package(src), Some("com.example.app".to_string()));
+    }
+
+    #[test]
+    fn declared_package_none_for_default_package() {
+        assert_eq!(declared_package("import java.util.List;\nclass A {}"), None);
+    }
+
+    #[test]
+    fn root_from_package_matches_trailing_dirs() {
+        let file = Path::new("/proj/sources/com/example/Foo.java");
+        assert_eq!(
+            root_from_package(file, "com.example"),
+            Some(PathBuf::from("/proj/sources"))
+        );
+    }
+
+    #[test]
+    fn root_from_package_rejects_mismatch() {
+        let file = Path::new("/proj/sources/com/other/Foo.java");
+        assert_eq!(root_from_package(file, "com.example"), None);
+    }
+
+    #[test]
+    fn detect_prefers_configured_roots() {
+        let config = SourcesConfig {
+            roots: vec![PathBuf::from("gen")],
+        };
+        let root = Path::new("/proj");
+        let roots = SourceRootDetector::new(root, &config).detect(&[], &[]);
+        assert_eq!(
+            roots,
+            vec![SourceRoot {
+                path: PathBuf::from("/proj/gen"),
+                origin: SourceRootOrigin::Config,
+            }]
+        );
+    }
+
+    #[test]
+    fn detect_finds_generated_roots_of_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        let module = dir.path().join("app");
+        fs::create_dir_all(module.join("src/main/java")).unwrap();
+        let generated = module.join("build/generated/sources/annotationProcessor/java/main");
+        fs::create_dir_all(&generated).unwrap();
+
+        let config = SourcesConfig::default();
+        let roots =
+            SourceRootDetector::new(dir.path(), &config).detect(std::slice::from_ref(&module), &[]);
+        assert_eq!(
+            roots,
+            vec![
+                SourceRoot {
+                    path: generated,
+                    origin: SourceRootOrigin::Generated,
+                },
+                SourceRoot {
+                    path: module.join("src/main/java"),
+                    origin: SourceRootOrigin::BuildMetadata,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn detect_infers_roots_from_package_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let pkg_dir = dir.path().join("java/com/example");
+        fs::create_dir_all(&pkg_dir).unwrap();
+        let file = pkg_dir.join("Foo.java");
+        fs::write(&file, "package com.example;\nclass Foo {}").unwrap();
+
+        let config = SourcesConfig::default();
+        let roots = SourceRootDetector::new(dir.path(), &config).detect(&[], &[file]);
+        assert_eq!(
+            roots,
+            vec![SourceRoot {
+                path: dir.path().join("java"),
+                origin: SourceRootOrigin::PackageLayout,
+            }]
+        );
+    }
+}
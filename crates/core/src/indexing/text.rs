@@ -0,0 +1,41 @@
+//! Trigram content index used by full-text search
+//!
+//! Each indexed file keeps the set of byte trigrams of its ASCII-lowercased
+//! content. A file can only contain a pattern if it has every trigram of the
+//! pattern, so a search reads just the files passing that test.
+
+/// Sorted, distinct trigrams of `text`, case-folded for ASCII.
+pub fn trigrams(text: &str) -> Vec<u32> {
+    let mut trigrams: Vec<u32> = text
+        .as_bytes()
+        .windows(3)
+        .map(|w| {
+            u32::from(w[0].to_ascii_lowercase()) << 16
+                | u32::from(w[1].to_ascii_lowercase()) << 8
+                | u32::from(w[2].to_ascii_lowercase())
+        })
+        .collect();
+    trigrams.sort_unstable();
+    trigrams.dedup();
+    trigrams
+}
+
+/// Whether a file with the sorted trigram set `file` may contain a text
+/// whose trigrams are `pattern`.
+pub fn may_contain(file: &[u32], pattern: &[u32]) -> bool {
+    pattern.iter().all(|t| file.binary_search(t).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trigram_sets_filter_candidate_files() {
+        let file = trigrams("log.info(\"Order SHIPPED\");");
+        assert!(may_contain(&file, &trigrams("order shipped")));
+        assert!(may_contain(&file, &trigrams("ab")));
+        assert!(!may_contain(&file, &trigrams("SELECT")));
+        assert!(file.windows(2).all(|w| w[0] < w[1]));
+    }
+}
@@ -0,0 +1,60 @@
+//! Warnings of an indexing run
+//!
+//! Plugins skip what they cannot make sense of, such as declarations inside
+//! syntax errors or a build script whose dependency block does not parse.
+//! Each run collects these as warnings about the files it indexed; committing
+//! the run replaces the warnings previously recorded for those files.
+
+use naviscope_api::lifecycle::{IndexPhase, IndexWarning, WarningSeverity};
+use naviscope_plugin::PluginWarning;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Warnings and indexed files of one run. Clones share state.
+#[derive(Clone, Default)]
+pub struct WarningCollector {
+    inner: Arc<Mutex<RunWarnings>>,
+}
+
+/// What a run, or the part of it since the last commit, found
+#[derive(Debug, Default)]
+pub struct RunWarnings {
+    /// Files whose earlier warnings no longer apply
+    pub indexed: BTreeSet<PathBuf>,
+    pub warnings: Vec<IndexWarning>,
+}
+
+impl WarningCollector {
+    /// Record that `paths` were indexed, with or without warnings.
+    pub fn indexed<'a>(&self, paths: impl IntoIterator<Item = &'a Path>) {
+        let mut run = self.inner.lock().unwrap();
+        run.indexed.extend(paths.into_iter().map(Path::to_path_buf));
+    }
+
+    pub fn push(&self, path: &Path, phase: IndexPhase, severity: WarningSeverity, message: String) {
+        if severity >= WarningSeverity::Warning {
+            tracing::debug!("{} ({}): {}", path.display(), phase.label(), message);
+        }
+        self.inner.lock().unwrap().warnings.push(IndexWarning {
+            path: path.to_string_lossy().to_string(),
+            phase,
+            severity,
+            message,
+        });
+    }
+
+    /// Record the warnings a plugin reported for `path`, or for the file
+    /// a warning names itself.
+    pub fn extend(&self, path: &Path, phase: IndexPhase, warnings: Vec<PluginWarning>) {
+        for warning in warnings {
+            let path = warning.path.as_deref().unwrap_or(path);
+            self.push(path, phase, warning.severity, warning.message);
+        }
+    }
+
+    /// Everything recorded so far, leaving the collector empty
+    pub fn take(&self) -> RunWarnings {
+        std::mem::take(&mut *self.inner.lock().unwrap())
+    }
+}
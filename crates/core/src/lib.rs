@@ -1,14 +1,22 @@
+pub mod advisory;
 pub mod asset;
 pub mod cache;
+pub mod config;
 pub mod error;
 pub mod logging;
+pub mod telemetry;
 pub mod util;
 
 pub mod facade;
 pub mod features;
+pub mod history;
 pub mod indexing;
+pub mod linker;
 pub mod model;
+pub mod platform;
 pub mod runtime;
+#[cfg(test)]
+pub(crate) mod test_support;
 // FQN types are now exported from model module
 
 pub use error::Result;
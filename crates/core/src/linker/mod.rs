@@ -0,0 +1,243 @@
+//! Links between symbols of different languages
+//!
+//! Each language resolves references within itself; what crosses a language
+//! boundary, an HTTP call to the handler of its route or a JNI `native`
+//! method to the function implementing it, is only known by a shared key.
+//! Plugins register a `CrossLanguageResolver` that reads the providers and
+//! consumers of such keys from source files. After indexing, the linker
+//! matches consumers to providers across the whole project and replaces the
+//! graph's `CrossLanguage` edges with the result.
+
+use crate::model::{CodeGraph, GraphEdge};
+use naviscope_api::models::graph::EdgeType;
+use naviscope_plugin::{CrossLanguageResolver, LinkEndpoint, LinkRole};
+use petgraph::stable_graph::NodeIndex;
+use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Endpoints a resolver read from a file, with the content hash they were read at
+struct CachedEndpoints {
+    content_hash: u64,
+    endpoints: Vec<LinkEndpoint>,
+}
+
+/// Registered resolvers, and the endpoints they read from each file
+#[derive(Default)]
+pub struct Linker {
+    resolvers: Vec<Arc<dyn CrossLanguageResolver>>,
+    /// Keyed by resolver position and file, reused while the file is unchanged
+    cache: Mutex<HashMap<(usize, PathBuf), CachedEndpoints>>,
+}
+
+impl Linker {
+    pub fn new(resolvers: Vec<Arc<dyn CrossLanguageResolver>>) -> Self {
+        Self {
+            resolvers,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.resolvers.is_empty()
+    }
+
+    pub fn resolvers(&self) -> &[Arc<dyn CrossLanguageResolver>] {
+        &self.resolvers
+    }
+
+    /// Replace the `CrossLanguage` edges of `graph` with those the endpoints
+    /// of its files yield. Returns the graph unchanged when they are the same.
+    pub fn link(&self, graph: CodeGraph) -> CodeGraph {
+        let links = self.resolve_links(&graph);
+        let topology = graph.topology();
+        let existing: Vec<_> = topology
+            .edge_references()
+            .filter(|e| e.weight().edge_type == EdgeType::CrossLanguage)
+            .map(|e| (e.id(), e.source(), e.target()))
+            .collect();
+        let existing_links: BTreeSet<(NodeIndex, NodeIndex)> =
+            existing.iter().map(|&(_, from, to)| (from, to)).collect();
+        if existing_links == links {
+            return graph;
+        }
+
+        let mut builder = graph.to_builder();
+        for (edge, _, _) in existing {
+            builder.remove_edge(edge);
+        }
+        for (from, to) in links {
+            builder.add_edge(from, to, GraphEdge::new(EdgeType::CrossLanguage));
+        }
+        builder.build()
+    }
+
+    /// Consumer-provider pairs of indexed symbols
+    fn resolve_links(&self, graph: &CodeGraph) -> BTreeSet<(NodeIndex, NodeIndex)> {
+        let mut cache = match self.cache.lock() {
+            Ok(cache) => cache,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let files: Vec<(PathBuf, u64)> = graph
+            .file_index()
+            .values()
+            .map(|entry| (entry.metadata.path.clone(), entry.metadata.content_hash))
+            .collect();
+        cache.retain(|(_, path), _| files.iter().any(|(p, _)| p == path));
+
+        let mut links = BTreeSet::new();
+        for (position, resolver) in self.resolvers.iter().enumerate() {
+            let mut providers: Vec<(String, NodeIndex)> = Vec::new();
+            let mut consumers: Vec<(String, NodeIndex)> = Vec::new();
+            for (path, content_hash) in &files {
+                if !resolver.supports_path(path) {
+                    continue;
+                }
+                let key = (position, path.clone());
+                let stale = cache
+                    .get(&key)
+                    .is_none_or(|cached| cached.content_hash != *content_hash);
+                if stale {
+                    let endpoints = match std::fs::read_to_string(path) {
+                        Ok(source) => resolver.endpoints(path, &source),
+                        Err(e) => {
+                            tracing::debug!("Not linking {}: {}", path.display(), e);
+                            Vec::new()
+                        }
+                    };
+                    cache.insert(
+                        key.clone(),
+                        CachedEndpoints {
+                            content_hash: *content_hash,
+                            endpoints,
+                        },
+                    );
+                }
+                let Some(cached) = cache.get(&key) else {
+                    continue;
+                };
+                for endpoint in &cached.endpoints {
+                    let Some(node) = enclosing_node(graph, path, endpoint) else {
+                        continue;
+                    };
+                    match endpoint.role {
+                        LinkRole::Provider => providers.push((endpoint.key.clone(), node)),
+                        LinkRole::Consumer => consumers.push((endpoint.key.clone(), node)),
+                    }
+                }
+            }
+            for (consumer_key, consumer) in &consumers {
+                for (provider_key, provider) in &providers {
+                    if consumer != provider && resolver.matches(consumer_key, provider_key) {
+                        links.insert((*consumer, *provider));
+                    }
+                }
+            }
+            tracing::debug!(
+                "Resolver {} read {} providers and {} consumers",
+                resolver.name(),
+                providers.len(),
+                consumers.len()
+            );
+        }
+        links
+    }
+}
+
+fn enclosing_node(graph: &CodeGraph, path: &Path, endpoint: &LinkEndpoint) -> Option<NodeIndex> {
+    graph.find_container_node_at(path, endpoint.line, endpoint.col)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::builder::CodeGraphBuilder;
+    use crate::model::source::SourceFile;
+    use crate::test_support::node_at;
+    use naviscope_api::models::graph::NodeKind;
+
+    /// Lines `serve <route>` provide a route, lines `call <route>` consume it
+    struct RouteResolver;
+
+    impl CrossLanguageResolver for RouteResolver {
+        fn name(&self) -> &str {
+            "route"
+        }
+
+        fn supports_path(&self, _path: &Path) -> bool {
+            true
+        }
+
+        fn endpoints(&self, _path: &Path, source: &str) -> Vec<LinkEndpoint> {
+            source
+                .lines()
+                .enumerate()
+                .filter_map(|(line, text)| {
+                    let (verb, key) = text.split_once(' ')?;
+                    let role = match verb {
+                        "serve" => LinkRole::Provider,
+                        "call" => LinkRole::Consumer,
+                        _ => return None,
+                    };
+                    Some(LinkEndpoint {
+                        role,
+                        key: key.to_string(),
+                        line,
+                        col: 0,
+                    })
+                })
+                .collect()
+        }
+
+        fn matches(&self, consumer: &str, provider: &str) -> bool {
+            consumer.strip_prefix("/api").unwrap_or(consumer) == provider
+        }
+    }
+
+    /// Method spanning the first lines of `path`
+    fn node(fqn: &str, lang: &str, path: &Path) -> crate::indexing::IndexNode {
+        let mut node = node_at(fqn, NodeKind::Method, &path.to_string_lossy());
+        node.lang = lang.to_string();
+        if let Some(location) = node.location.as_mut() {
+            location.range.end_line = 10;
+        }
+        node
+    }
+
+    fn cross_language_edges(graph: &CodeGraph) -> Vec<(NodeIndex, NodeIndex)> {
+        graph
+            .topology()
+            .edge_references()
+            .filter(|e| e.weight().edge_type == EdgeType::CrossLanguage)
+            .map(|e| (e.source(), e.target()))
+            .collect()
+    }
+
+    #[test]
+    fn consumers_link_to_the_providers_of_their_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let handler_path = dir.path().join("UserController.java");
+        let client_path = dir.path().join("users.ts");
+        std::fs::write(&handler_path, "serve /users\n").unwrap();
+        std::fs::write(&client_path, "call /api/users\ncall /api/orders\n").unwrap();
+
+        let mut builder = CodeGraphBuilder::new();
+        let handler = builder.add_node(node("UserController#list", "java", &handler_path));
+        let client = builder.add_node(node("loadUsers", "typescript", &client_path));
+        for (path, hash) in [(&handler_path, 1), (&client_path, 2)] {
+            builder.update_file(path, SourceFile::new(path.clone(), hash, 0));
+        }
+
+        let linker = Linker::new(vec![Arc::new(RouteResolver)]);
+        let graph = linker.link(builder.build());
+        assert_eq!(cross_language_edges(&graph), [(client, handler)]);
+
+        // The client stops calling the route
+        std::fs::write(&client_path, "call /api/orders\n").unwrap();
+        let mut builder = graph.to_builder();
+        builder.update_file(&client_path, SourceFile::new(client_path.clone(), 3, 0));
+        let graph = linker.link(builder.build());
+        assert!(cross_language_edges(&graph).is_empty());
+    }
+}
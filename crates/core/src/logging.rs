@@ -1,8 +1,17 @@
+use crate::telemetry::Telemetry;
 use std::path::Path;
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::{EnvFilter, fmt, prelude::*};
 
-pub fn init_logging(component: &str, to_stderr: bool) -> WorkerGuard {
+/// Keeps the log writer and the OpenTelemetry exporters running; dropping
+/// it flushes them.
+pub struct LoggingGuard {
+    // Dropped first, while the log still works
+    _telemetry: Option<Telemetry>,
+    _file: WorkerGuard,
+}
+
+pub fn init_logging(component: &str, to_stderr: bool) -> LoggingGuard {
     let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
     let log_dir = Path::new(&home).join(".naviscope/logs");
     let _ = std::fs::create_dir_all(&log_dir);
@@ -20,7 +29,11 @@ pub fn init_logging(component: &str, to_stderr: bool) -> WorkerGuard {
         .with_ansi(false)
         .with_target(true);
 
-    let registry = tracing_subscriber::registry().with(filter).with(file_layer);
+    let telemetry = Telemetry::from_env(component);
+    let registry = tracing_subscriber::registry()
+        .with(filter)
+        .with(file_layer)
+        .with(telemetry.as_ref().map(Telemetry::layer));
 
     if to_stderr {
         let stderr_layer = fmt::layer()
@@ -32,5 +45,8 @@ pub fn init_logging(component: &str, to_stderr: bool) -> WorkerGuard {
         registry.init();
     }
 
-    guard
+    LoggingGuard {
+        _telemetry: telemetry,
+        _file: guard,
+    }
 }
@@ -9,10 +9,16 @@ use crate::model::graph::CodeGraphInner;
 use crate::model::source::SourceFile;
 // codec context unused
 use crate::model::{GraphEdge, GraphOp};
-use naviscope_api::models::symbol::Symbol;
+use naviscope_api::models::Language;
+use naviscope_api::models::graph::{
+    DisplaySymbolLocation, EdgeType, NodeKind, NodeSource, ResolutionStatus,
+};
+use naviscope_api::models::symbol::{FqnId, NodeId, Symbol};
 use naviscope_plugin::{FqnInterner, ModelConverter};
-use petgraph::stable_graph::{NodeIndex, StableDiGraph};
-use std::collections::HashMap;
+use petgraph::Direction;
+use petgraph::stable_graph::{EdgeIndex, NodeIndex, StableDiGraph};
+use petgraph::visit::EdgeRef;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 /// Mutable graph builder
@@ -37,6 +43,7 @@ impl CodeGraphBuilder {
                 name_index: HashMap::new(),
                 file_index: HashMap::new(),
                 reference_index: HashMap::new(),
+                shard_log: crate::model::storage::shard::ShardLog::new(),
             },
             naming_conventions: HashMap::new(),
         }
@@ -48,7 +55,8 @@ impl CodeGraphBuilder {
     }
 
     /// Create builder from internal data
-    pub(crate) fn from_inner(inner: CodeGraphInner) -> Self {
+    pub(crate) fn from_inner(mut inner: CodeGraphInner) -> Self {
+        inner.shard_log.advance();
         Self {
             inner,
             naming_conventions: HashMap::new(),
@@ -57,6 +65,22 @@ impl CodeGraphBuilder {
 
     // ---- Mutation methods ----
 
+    /// Record a change to the shard of the file `path` is interned as.
+    fn mark_file(&mut self, path: Option<Symbol>) {
+        self.inner.shard_log.mark(path);
+    }
+
+    /// Record a change to the shard holding the node at `idx`.
+    fn mark_node(&mut self, idx: NodeIndex) {
+        let path = self
+            .inner
+            .topology
+            .node_weight(idx)
+            .and_then(|node| node.location.as_ref())
+            .map(|loc| loc.path);
+        self.mark_file(path);
+    }
+
     /// Resolve and intern an ID, potentially upgrading it using NamingConventions
     fn resolve_storage_id(
         &self,
@@ -115,6 +139,7 @@ impl CodeGraphBuilder {
         };
 
         if let Some(&idx) = self.inner.fqn_index.get(&fqn_id) {
+            self.mark_node(idx);
             // Node already exists - check if we should update metadata
             if let Some(existing_node) = self.inner.topology.node_weight_mut(idx) {
                 // If the new metadata is NOT empty, or we want to force an update, do it here.
@@ -139,6 +164,7 @@ impl CodeGraphBuilder {
                 // Also update source if it was External and now it's Project (or just keep it updated)
                 existing_node.source = node_data.source;
             }
+            self.mark_node(idx);
             idx
         } else {
             let name_sym = self.inner.fqns.intern_atom(&node_data.name);
@@ -164,6 +190,7 @@ impl CodeGraphBuilder {
             };
 
             let idx = self.inner.topology.add_node(node);
+            self.mark_node(idx);
             self.inner.fqn_index.insert(fqn_id, idx);
             self.inner.name_index.entry(name_sym).or_default().push(idx);
 
@@ -181,6 +208,8 @@ impl CodeGraphBuilder {
                                 0,
                             ),
                             nodes: vec![idx],
+                            trigrams: Vec::new(),
+                            fingerprints: Vec::new(),
                         }
                     });
             }
@@ -199,16 +228,37 @@ impl CodeGraphBuilder {
         );
 
         if !already_exists {
+            self.mark_node(from);
             self.inner.topology.add_edge(from, to, edge);
         }
     }
 
+    /// Remove an edge
+    pub fn remove_edge(&mut self, edge: EdgeIndex) {
+        if let Some((from, _)) = self.inner.topology.edge_endpoints(edge) {
+            self.mark_node(from);
+        }
+        self.inner.topology.remove_edge(edge);
+    }
+
     /// Remove a node
     pub fn remove_node(&mut self, idx: NodeIndex) {
         if let Some(node) = self.inner.topology.node_weight(idx) {
             let fqn = node.id; // Symbol implements Copy
             let _name = node.name;
 
+            // Edges are stored with their source, so the shards of the
+            // nodes pointing here change as well
+            let sources: Vec<NodeIndex> = self
+                .inner
+                .topology
+                .neighbors_directed(idx, Direction::Incoming)
+                .collect();
+            self.mark_node(idx);
+            for source in sources {
+                self.mark_node(source);
+            }
+
             // Remove from indices
             self.inner.fqn_index.remove(&fqn);
 
@@ -220,11 +270,15 @@ impl CodeGraphBuilder {
     /// Remove all nodes associated with a file path
     pub fn remove_path(&mut self, path: &Path) {
         let interned_path = Symbol(self.inner.symbols.get_or_intern(&path.to_string_lossy()));
+        self.mark_file(Some(interned_path));
         if let Some(entry) = self.inner.file_index.remove(&interned_path) {
             for idx in entry.nodes {
                 self.remove_node(idx);
             }
         }
+        if let Some(file) = self.file_node(path) {
+            self.remove_node(file);
+        }
 
         // Also remove from reference_index
         for files in self.inner.reference_index.values_mut() {
@@ -235,6 +289,7 @@ impl CodeGraphBuilder {
     /// Update file metadata (creates or updates FileEntry)
     pub fn update_file(&mut self, path: &Path, source: SourceFile) {
         let interned_path = Symbol(self.inner.symbols.get_or_intern(&path.to_string_lossy()));
+        self.mark_file(Some(interned_path));
         self.inner
             .file_index
             .entry(interned_path)
@@ -242,9 +297,103 @@ impl CodeGraphBuilder {
             .or_insert(crate::model::graph::FileEntry {
                 metadata: source,
                 nodes: Vec::new(),
+                trigrams: Vec::new(),
+                fingerprints: Vec::new(),
             });
     }
 
+    fn file_node(&self, path: &Path) -> Option<NodeIndex> {
+        let id = self
+            .inner
+            .fqns
+            .resolve_path(&crate::model::graph::file_node_parts(path))?;
+        self.inner.fqn_index.get(&id).copied()
+    }
+
+    /// Add the node of the indexed file at `path` and link it to the
+    /// symbols of the file that no other symbol of the file contains.
+    ///
+    /// The file node is located in its file, so it is stored with the file's
+    /// directory, but it is not one of the file's nodes: lookups by position
+    /// and symbol outlines never see it.
+    pub fn link_file(&mut self, path: &Path) {
+        let path_str = path.to_string_lossy();
+        let interned_path = Symbol(self.inner.symbols.get_or_intern(&path_str));
+        let Some(entry) = self.inner.file_index.get(&interned_path) else {
+            return;
+        };
+        let topology = &self.inner.topology;
+        let in_file = |idx: NodeIndex| {
+            topology[idx]
+                .location
+                .as_ref()
+                .is_some_and(|loc| loc.path == interned_path)
+        };
+        let declared: Vec<NodeIndex> = entry
+            .nodes
+            .iter()
+            .copied()
+            .filter(|&idx| topology.contains_node(idx))
+            .filter(|&idx| {
+                !topology
+                    .edges_directed(idx, Direction::Incoming)
+                    .any(|e| e.weight().edge_type == EdgeType::Contains && in_file(e.source()))
+            })
+            .collect();
+
+        // The language of the plugin that indexed the file, which renders it
+        let lang = match declared.first() {
+            Some(&idx) => self
+                .inner
+                .symbols
+                .resolve(&topology[idx].lang.0)
+                .to_string(),
+            // Everything after the first dot, so `build.gradle.kts` is gradle
+            None => path
+                .file_name()
+                .map(|name| name.to_string_lossy())
+                .and_then(|name| Some(name.split_once('.')?.1.to_string()))
+                .and_then(|ext| Language::from_extension(&ext))
+                .unwrap_or(Language::UNKNOWN)
+                .to_string(),
+        };
+        let file = self.add_node(crate::indexing::IndexNode {
+            id: NodeId::Structured(crate::model::graph::file_node_parts(path)),
+            name: path.file_name().map_or_else(
+                || path_str.to_string(),
+                |name| name.to_string_lossy().into(),
+            ),
+            kind: NodeKind::File,
+            lang,
+            source: NodeSource::Project,
+            status: ResolutionStatus::Resolved,
+            location: Some(DisplaySymbolLocation {
+                path: path_str.to_string(),
+                range: Default::default(),
+                selection_range: None,
+            }),
+            metadata: std::sync::Arc::new(crate::model::EmptyMetadata),
+        });
+        if let Some(entry) = self.inner.file_index.get_mut(&interned_path) {
+            entry.nodes.retain(|&idx| idx != file);
+        }
+
+        // Replace the edges of the file's previous version
+        let stale: Vec<EdgeIndex> = self
+            .inner
+            .topology
+            .edges_directed(file, Direction::Outgoing)
+            .filter(|e| e.weight().edge_type == EdgeType::Declares)
+            .map(|e| e.id())
+            .collect();
+        for edge in stale {
+            self.remove_edge(edge);
+        }
+        for idx in declared {
+            self.add_edge(file, idx, GraphEdge::new(EdgeType::Declares));
+        }
+    }
+
     /// Apply a graph operation
     pub fn apply_op(&mut self, op: GraphOp) -> crate::error::Result<()> {
         match op {
@@ -296,6 +445,7 @@ impl CodeGraphBuilder {
             }
             GraphOp::UpdateIdentifiers { path, identifiers } => {
                 let path_sym = Symbol(self.inner.symbols.get_or_intern(&path.to_string_lossy()));
+                self.mark_file(Some(path_sym));
                 for token in identifiers {
                     let token_sym = Symbol(self.inner.symbols.get_or_intern(token.as_str()));
                     let files = self.inner.reference_index.entry(token_sym).or_default();
@@ -308,6 +458,18 @@ impl CodeGraphBuilder {
                 let path = metadata.path.clone();
                 self.update_file(&path, metadata);
             }
+            GraphOp::UpdateContent {
+                path,
+                trigrams,
+                fingerprints,
+            } => {
+                let path_sym = Symbol(self.inner.symbols.get_or_intern(path.to_string_lossy()));
+                self.mark_file(Some(path_sym));
+                if let Some(entry) = self.inner.file_index.get_mut(&path_sym) {
+                    entry.trigrams = trigrams;
+                    entry.fingerprints = fingerprints;
+                }
+            }
         }
         Ok(())
     }
@@ -317,21 +479,42 @@ impl CodeGraphBuilder {
     /// 1. Removals
     /// 2. Node additions & updates
     /// 3. Edge additions (Relational)
+    /// 4. File nodes of the updated files
+    ///
+    /// Edges from other files into the removed paths survive when their
+    /// targets are added back, so files that only reference a replaced file
+    /// need not be lowered again.
     pub fn apply_ops(&mut self, ops: Vec<GraphOp>) -> crate::error::Result<()> {
         let mut destructive = Vec::new();
         let mut additive = Vec::new();
         let mut relational = Vec::new();
+        let mut files = Vec::new();
 
         for op in ops {
             match op {
                 GraphOp::RemovePath { .. } => destructive.push(op),
+                GraphOp::UpdateFile { ref metadata } => {
+                    files.push(metadata.path.clone());
+                    additive.push(op);
+                }
                 GraphOp::AddNode { .. }
-                | GraphOp::UpdateFile { .. }
+                | GraphOp::UpdateContent { .. }
                 | GraphOp::UpdateIdentifiers { .. } => additive.push(op),
                 GraphOp::AddEdge { .. } => relational.push(op),
             }
         }
 
+        let removed: HashSet<Symbol> = destructive
+            .iter()
+            .filter_map(|op| match op {
+                GraphOp::RemovePath { path } => {
+                    self.inner.symbols.get(path.to_string_lossy()).map(Symbol)
+                }
+                _ => None,
+            })
+            .collect();
+        let incoming = self.incoming_edges(&removed);
+
         for op in destructive {
             self.apply_op(op)?;
         }
@@ -341,9 +524,43 @@ impl CodeGraphBuilder {
         for op in relational {
             self.apply_op(op)?;
         }
+        for path in files {
+            self.link_file(&path);
+        }
+        for (from, to, edge) in incoming {
+            if let Some(&to) = self.inner.fqn_index.get(&to)
+                && self.inner.topology.contains_node(from)
+            {
+                self.add_edge(from, to, edge);
+            }
+        }
         Ok(())
     }
 
+    /// Edges into the nodes of the files in `paths` from nodes outside them,
+    /// with their targets by FQN
+    fn incoming_edges(&self, paths: &HashSet<Symbol>) -> Vec<(NodeIndex, FqnId, GraphEdge)> {
+        let topology = &self.inner.topology;
+        let mut edges = Vec::new();
+        for entry in paths.iter().filter_map(|p| self.inner.file_index.get(p)) {
+            for &idx in &entry.nodes {
+                let Some(target) = topology.node_weight(idx) else {
+                    continue;
+                };
+                for edge in topology.edges_directed(idx, Direction::Incoming) {
+                    let inside = topology[edge.source()]
+                        .location
+                        .as_ref()
+                        .is_some_and(|loc| paths.contains(&loc.path));
+                    if !inside {
+                        edges.push((edge.source(), target.id, edge.weight().clone()));
+                    }
+                }
+            }
+        }
+        edges
+    }
+
     /// Build the immutable graph
     pub fn build(self) -> CodeGraph {
         CodeGraph::from_inner(self.inner)
@@ -359,7 +576,8 @@ impl Default for CodeGraphBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::NodeKind;
+    use crate::features::CodeGraphLike;
+    use std::path::PathBuf;
 
     #[test]
     fn test_build_from_scratch() {
@@ -406,4 +624,152 @@ mod tests {
 
         assert_eq!(updated.node_count(), 1);
     }
+
+    fn file_ops(path: &str, fqns: &[(&str, NodeKind)], contains: &[(&str, &str)]) -> Vec<GraphOp> {
+        let mut ops = vec![
+            GraphOp::RemovePath {
+                path: Path::new(path).into(),
+            },
+            GraphOp::UpdateFile {
+                metadata: SourceFile::new(path.into(), 1, 0),
+            },
+        ];
+        for (fqn, kind) in fqns {
+            ops.push(GraphOp::AddNode {
+                data: Some(crate::test_support::node_at(fqn, kind.clone(), path)),
+            });
+        }
+        for (from, to) in contains {
+            ops.push(GraphOp::AddEdge {
+                from_id: (*from).into(),
+                to_id: (*to).into(),
+                edge: GraphEdge::new(EdgeType::Contains),
+            });
+        }
+        ops
+    }
+
+    fn declared(graph: &CodeGraph, file: &str) -> Vec<String> {
+        let idx = graph.find_node(file).unwrap();
+        let topology = graph.topology();
+        let mut names: Vec<String> = topology
+            .edges_directed(idx, Direction::Outgoing)
+            .filter(|e| e.weight().edge_type == EdgeType::Declares)
+            .map(|e| {
+                graph
+                    .symbols()
+                    .resolve(&topology[e.target()].name.0)
+                    .to_string()
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn file_nodes_declare_top_level_symbols() {
+        let mut builder = CodeGraphBuilder::new();
+        builder
+            .apply_ops(file_ops(
+                "/p/src/A.java",
+                &[
+                    ("com.A", NodeKind::Class),
+                    ("com.A#run", NodeKind::Method),
+                    ("com.AHelper", NodeKind::Class),
+                ],
+                &[("com.A", "com.A#run")],
+            ))
+            .unwrap();
+        let graph = builder.build();
+
+        assert_eq!(declared(&graph, "file:/p/src/A.java"), ["A", "AHelper"]);
+        // Relative paths match the file they end with
+        assert_eq!(declared(&graph, "file:src/A.java"), ["A", "AHelper"]);
+        assert!(graph.find_node("file:B.java").is_none());
+        // The file node is not one of the file's symbols
+        assert_eq!(
+            graph
+                .path_to_nodes(Path::new("/p/src/A.java"))
+                .unwrap()
+                .len(),
+            3
+        );
+
+        // A commit of the file's next version replaces its node and edges
+        let mut builder = graph.to_builder();
+        builder
+            .apply_ops(file_ops(
+                "/p/src/A.java",
+                &[("com.A", NodeKind::Class)],
+                &[],
+            ))
+            .unwrap();
+        let graph = builder.build();
+        assert_eq!(declared(&graph, "file:src/A.java"), ["A"]);
+
+        let mut builder = graph.to_builder();
+        builder.remove_path(Path::new("/p/src/A.java"));
+        let graph = builder.build();
+        assert!(graph.find_node("file:src/A.java").is_none());
+        assert_eq!(graph.node_count(), 0);
+    }
+
+    #[test]
+    fn relowered_files_keep_references_from_other_files() {
+        let reference = |from: &str, to: &str| GraphOp::AddEdge {
+            from_id: from.into(),
+            to_id: to.into(),
+            edge: GraphEdge::new(EdgeType::TypedAs),
+        };
+        let java = |mut builder: CodeGraphBuilder| {
+            builder.naming_conventions.insert(
+                crate::model::Language::new("java".to_string()),
+                std::sync::Arc::new(naviscope_plugin::StandardNamingConvention),
+            );
+            builder
+        };
+        let mut builder = java(CodeGraphBuilder::new());
+        let mut ops = file_ops(
+            "/p/A.java",
+            &[("com.A", NodeKind::Class), ("com.AHelper", NodeKind::Class)],
+            &[],
+        );
+        ops.extend(file_ops("/p/B.java", &[("com.B", NodeKind::Class)], &[]));
+        ops.extend(file_ops("/p/C.java", &[("com.C", NodeKind::Class)], &[]));
+        ops.push(reference("com.B", "com.A"));
+        ops.push(reference("com.B", "com.AHelper"));
+        ops.push(reference("com.C", "com.B"));
+        builder.apply_ops(ops).unwrap();
+        let graph = builder.build();
+
+        let a = [PathBuf::from("/p/A.java")];
+        assert_eq!(
+            graph.dependent_files(&a, Some(1)),
+            [PathBuf::from("/p/B.java")]
+        );
+        assert_eq!(
+            graph.dependent_files(&a, None),
+            [PathBuf::from("/p/B.java"), PathBuf::from("/p/C.java")]
+        );
+
+        // The next version of A drops AHelper
+        let mut builder = java(graph.to_builder());
+        builder
+            .apply_ops(file_ops("/p/A.java", &[("com.A", NodeKind::Class)], &[]))
+            .unwrap();
+        let graph = builder.build();
+        let b = graph.find_node("com.B").unwrap();
+        let targets: Vec<_> = graph
+            .topology()
+            .neighbors_directed(b, Direction::Outgoing)
+            .map(|idx| {
+                graph
+                    .symbols()
+                    .resolve(&graph.topology()[idx].name.0)
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(targets, ["A"]);
+        assert_eq!(graph.dependent_files(&a, None).len(), 2);
+    }
 }
@@ -136,12 +136,23 @@ impl FqnManager {
         current
     }
 
-    /// Resolve a dot/colon separated string to potential FqnIds.
-    /// Uses StandardNamingConvention for parsing, and performs intelligent lookup
-    /// based on the parsed NodeKinds.
     /// Resolve a dot/colon separated string to potential FqnIds.
     /// Uses all registered NamingConventions to parse and lookup path logic.
+    ///
+    /// Accepts language-tagged symbol IDs (`java:com.app.Foo`) as well; a
+    /// string that resolves as a plain FQN is taken as one.
     pub fn resolve_fqn_string(&self, fqn: &str) -> Vec<FqnId> {
+        let ids = self.resolve_plain_fqn(fqn);
+        if !ids.is_empty() {
+            return ids;
+        }
+        match naviscope_api::models::parse_symbol_id(fqn) {
+            Some((_lang, fqn)) => self.resolve_plain_fqn(fqn),
+            None => ids,
+        }
+    }
+
+    fn resolve_plain_fqn(&self, fqn: &str) -> Vec<FqnId> {
         let registry = self.registry.read().unwrap();
 
         let mut all_results = Vec::new();
@@ -280,11 +291,13 @@ impl Serialize for FqnManager {
         let mut state = serializer.serialize_struct("FqnManager", 3)?;
         state.serialize_field("rodeo", &*self.rodeo)?;
 
-        let nodes_vec: Vec<_> = self
+        let mut nodes_vec: Vec<_> = self
             .nodes
             .iter()
             .map(|entry| (*entry.key(), entry.value().clone()))
             .collect();
+        // Stable order, so an unchanged tree serializes to identical bytes
+        nodes_vec.sort_unstable_by_key(|(id, _)| *id);
         state.serialize_field("nodes", &nodes_vec)?;
         state.serialize_field(
             "next_id",
@@ -9,18 +9,33 @@ use naviscope_plugin::NodeMetadataCodec;
 use crate::features::CodeGraphLike;
 use crate::model::FqnManager;
 use crate::model::source::SourceFile;
+use crate::model::storage::shard::{DecodedShard, NodeShard, ShardLayout, ShardLog};
 use crate::model::{GraphEdge, GraphNode};
 use lasso::ThreadedRodeo;
 use naviscope_api::models::symbol::{FqnId, FqnReader, Symbol};
+use petgraph::Direction;
 use petgraph::stable_graph::{NodeIndex, StableDiGraph};
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 static NEXT_INSTANCE_ID: AtomicU64 = AtomicU64::new(1);
 
-pub const CURRENT_VERSION: u32 = 1;
+/// Version of the graph layout; indexes of another version are rebuilt.
+/// 2: file nodes
+pub const CURRENT_VERSION: u32 = 2;
+
+/// Prefix of the IDs of file nodes, followed by the file's path
+pub const FILE_ID_PREFIX: &str = "file:";
+
+/// Parts of the structured ID of the node of the file at `path`
+pub fn file_node_parts(path: &Path) -> Vec<(naviscope_api::models::NodeKind, String)> {
+    vec![(
+        naviscope_api::models::NodeKind::File,
+        format!("{}{}", FILE_ID_PREFIX, path.display()),
+    )]
+}
 
 fn next_instance_id() -> u64 {
     NEXT_INSTANCE_ID.fetch_add(1, Ordering::Relaxed)
@@ -59,6 +74,9 @@ pub struct CodeGraphInner {
     /// Reference Index: Token (e.g. Method Name) -> Files that contain this token.
     /// Used for fast "scouting" during reference discovery.
     pub reference_index: HashMap<Symbol, Vec<Symbol>>,
+
+    /// Files changed since the graph's lineage began, for saving shards
+    pub shard_log: ShardLog,
 }
 
 /// Metadata and nodes associated with a single source file
@@ -66,6 +84,10 @@ pub struct CodeGraphInner {
 pub struct FileEntry {
     pub metadata: SourceFile,
     pub nodes: Vec<NodeIndex>,
+    /// Sorted content trigrams, see `indexing::text`
+    pub trigrams: Vec<u32>,
+    /// Fingerprints of the file's methods, see `indexing::clones`
+    pub fingerprints: Vec<naviscope_plugin::CodeFingerprint>,
 }
 
 impl CodeGraph {
@@ -83,6 +105,7 @@ impl CodeGraph {
                 name_index: HashMap::new(),
                 file_index: HashMap::new(),
                 reference_index: HashMap::new(),
+                shard_log: ShardLog::new(),
             }),
         }
     }
@@ -95,6 +118,16 @@ impl CodeGraph {
         }
     }
 
+    /// The same graph under `instance_id`, for views of one snapshot that
+    /// are assembled in steps
+    pub(crate) fn with_instance_id(self, instance_id: u64) -> Self {
+        let mut inner = Arc::unwrap_or_clone(self.inner);
+        inner.instance_id = instance_id;
+        Self {
+            inner: Arc::new(inner),
+        }
+    }
+
     /// Create a builder for modifying this graph
     ///
     /// Note: This performs a deep copy, so it should only be called when
@@ -103,6 +136,47 @@ impl CodeGraph {
         CodeGraphBuilder::from_inner((*self.inner).clone())
     }
 
+    /// Copy of the graph with the files under `from` moved to `to`: the
+    /// paths of locations, file nodes and the file index are rewritten.
+    ///
+    /// Symbols keep their keys, so a path that would become equal to
+    /// another interned string is an error.
+    pub fn relocate(&self, from: &Path, to: &Path) -> Result<Self> {
+        let moved = |path: &str| -> Option<String> {
+            let rest = Path::new(path).strip_prefix(from).ok()?;
+            Some(to.join(rest).to_string_lossy().into_owned())
+        };
+        let mut strings: Vec<_> = self.inner.symbols.iter().collect();
+        strings.sort_by_key(|(key, _)| lasso::Key::into_usize(*key));
+
+        let rodeo = Arc::new(ThreadedRodeo::new());
+        for (key, string) in strings {
+            let relocated = match string.strip_prefix(FILE_ID_PREFIX) {
+                Some(path) => moved(path).map(|path| format!("{}{}", FILE_ID_PREFIX, path)),
+                None => moved(string),
+            };
+            let new_key = rodeo.get_or_intern(relocated.as_deref().unwrap_or(string));
+            if new_key != key {
+                return Err(NaviscopeError::Internal(format!(
+                    "Cannot move '{}' to {}: the target is already in the graph",
+                    string,
+                    to.display()
+                )));
+            }
+        }
+
+        let mut inner = (*self.inner).clone();
+        inner.fqns.rodeo = rodeo.clone();
+        inner.symbols = rodeo;
+        inner.shard_log = ShardLog::new();
+        for entry in inner.file_index.values_mut() {
+            if let Ok(rest) = entry.metadata.path.strip_prefix(from) {
+                entry.metadata.path = to.join(rest);
+            }
+        }
+        Ok(Self::from_inner(inner))
+    }
+
     /// Register a new naming convention for this graph instance.
     /// This allows plugins to provide language-specific FQN parsing logic.
     /// Note: This affects global query behavior for this graph instance.
@@ -158,9 +232,12 @@ impl CodeGraph {
         &self.inner.reference_index
     }
 
-    /// Find node index by FQN (flat string)
+    /// Find node index by FQN (flat string), or a file node by `file:<path>`
     /// If multiple nodes match (e.g. overloads), it returns the first one found.
     pub fn find_node(&self, fqn: &str) -> Option<NodeIndex> {
+        if let Some(path) = fqn.strip_prefix(FILE_ID_PREFIX) {
+            return self.find_file_node(path);
+        }
         let ids = self.inner.fqns.resolve_fqn_string(fqn);
         for id in ids {
             if let Some(&idx) = self.inner.fqn_index.get(&id) {
@@ -238,6 +315,48 @@ impl CodeGraph {
         results
     }
 
+    /// Files holding a symbol that references one declared in `paths`,
+    /// following the references of those files in turn for up to `depth`
+    /// hops (`None` for the whole closure). `paths` themselves are left out.
+    ///
+    /// Only references from other files count, so symbols nothing outside
+    /// their file uses never pull in a dependent.
+    pub fn dependent_files(&self, paths: &[PathBuf], depth: Option<usize>) -> Vec<PathBuf> {
+        let topology = &self.inner.topology;
+        let mut seen: HashSet<Symbol> = paths
+            .iter()
+            .filter_map(|path| self.inner.symbols.get(path.to_string_lossy()))
+            .map(Symbol)
+            .collect();
+        let mut frontier: Vec<Symbol> = seen.iter().copied().collect();
+        let mut dependents = Vec::new();
+        let mut hops = 0;
+        while !frontier.is_empty() && depth.is_none_or(|depth| hops < depth) {
+            let mut next = Vec::new();
+            for entry in frontier.iter().filter_map(|p| self.inner.file_index.get(p)) {
+                for &idx in &entry.nodes {
+                    for source in topology.neighbors_directed(idx, Direction::Incoming) {
+                        let Some(location) = &topology[source].location else {
+                            continue;
+                        };
+                        if seen.insert(location.path) {
+                            next.push(location.path);
+                        }
+                    }
+                }
+            }
+            dependents.extend(next.iter().copied());
+            frontier = next;
+            hops += 1;
+        }
+        let mut dependents: Vec<PathBuf> = dependents
+            .into_iter()
+            .map(|path| PathBuf::from(self.inner.symbols.resolve(&path.0)))
+            .collect();
+        dependents.sort();
+        dependents
+    }
+
     /// Get the number of nodes
     pub fn node_count(&self) -> usize {
         self.inner.topology.node_count()
@@ -284,6 +403,36 @@ impl CodeGraph {
         Ok(Self::from_inner(inner))
     }
 
+    /// Changes recorded for saving the graph's shards
+    pub fn shard_log(&self) -> &ShardLog {
+        &self.inner.shard_log
+    }
+
+    /// Shard of every node and the links between shards
+    pub fn shard_layout(&self) -> ShardLayout {
+        ShardLayout::of(&self.inner)
+    }
+
+    /// Encode the per-directory node shards whose keys `wanted` accepts.
+    pub fn to_shards(
+        &self,
+        wanted: impl Fn(&str) -> bool,
+        get_codec: impl Fn(&str) -> Option<Arc<dyn NodeMetadataCodec>>,
+    ) -> std::collections::BTreeMap<String, NodeShard> {
+        super::storage::shard::build_shards(&self.inner, wanted, get_codec)
+    }
+
+    /// Assemble a graph from decoded node shards over a restored FQN tree.
+    pub fn from_shards<'a>(
+        version: u32,
+        fqns: &FqnManager,
+        shards: impl IntoIterator<Item = &'a DecodedShard> + Clone,
+        shard_log: ShardLog,
+    ) -> Self {
+        let inner = super::storage::shard::assemble_shards(version, fqns, shards, shard_log);
+        Self::from_inner(inner)
+    }
+
     /// Save graph to JSON file (for debugging)
     pub fn save_to_json<P: AsRef<std::path::Path>>(
         &self,
@@ -324,6 +473,55 @@ impl CodeGraphLike for CodeGraph {
         &self.inner.reference_index
     }
 
+    fn file_trigrams(&self) -> Vec<(&str, &[u32])> {
+        self.inner
+            .file_index
+            .iter()
+            .map(|(path, entry)| {
+                (
+                    self.inner.symbols.resolve(&path.0),
+                    entry.trigrams.as_slice(),
+                )
+            })
+            .collect()
+    }
+
+    fn code_fingerprints(
+        &self,
+    ) -> Vec<(
+        petgraph::stable_graph::NodeIndex,
+        &naviscope_plugin::CodeFingerprint,
+    )> {
+        let topology = &self.inner.topology;
+        let mut fingerprints = Vec::new();
+        for entry in self.inner.file_index.values() {
+            for fingerprint in &entry.fingerprints {
+                let method = entry.nodes.iter().copied().find(|&idx| {
+                    topology[idx].location.as_ref().is_some_and(|loc| {
+                        loc.range.start_line == fingerprint.line
+                            && loc.range.start_col == fingerprint.col
+                    }) && matches!(
+                        topology[idx].kind,
+                        crate::model::NodeKind::Method | crate::model::NodeKind::Constructor
+                    )
+                });
+                if let Some(method) = method {
+                    fingerprints.push((method, fingerprint));
+                }
+            }
+        }
+        fingerprints
+    }
+
+    fn is_generated(&self, node: &crate::model::GraphNode) -> bool {
+        node.location.as_ref().is_some_and(|loc| {
+            self.inner
+                .file_index
+                .get(&loc.path)
+                .is_some_and(|entry| entry.metadata.generated)
+        })
+    }
+
     fn find_container_node_at(
         &self,
         path: &std::path::Path,
@@ -476,4 +674,95 @@ mod tests {
         assert_eq!(recovered_node.name(symbols), "node");
         assert_eq!(recovered_node.language(symbols).as_str(), "java");
     }
+
+    #[test]
+    fn test_graph_shard_roundtrip() {
+        use crate::model::builder::CodeGraphBuilder;
+        use crate::model::storage::shard;
+        use crate::model::{EdgeType, GraphEdge, NodeKind};
+        use crate::test_support::node_at;
+
+        let node = |fqn: &str, path: &str| node_at(fqn, NodeKind::Class, path);
+
+        let mut builder = CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            crate::model::Language::new("java".to_string()),
+            std::sync::Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        let a = builder.add_node(node("a.A", "/p/a/A.java"));
+        let b = builder.add_node(node("b.B", "/p/b/B.java"));
+        builder.add_edge(a, b, GraphEdge::new(EdgeType::TypedAs));
+        let graph = builder.build();
+
+        let shards = graph.to_shards(|_| true, |_| None);
+        assert_eq!(
+            shards.keys().cloned().collect::<Vec<_>>(),
+            vec!["/p/a".to_string(), "/p/b".to_string()]
+        );
+        assert_eq!(shards["/p/a"].edges.len(), 1);
+
+        // Re-splitting an unchanged graph yields identical shard bytes
+        let encode = |g: &CodeGraph| {
+            let shards = g.to_shards(|_| true, |_| None);
+            shards
+                .values()
+                .map(|shard| shard::encode_shard(shard).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(encode(&graph), encode(&graph));
+
+        // The string table and FQN tree travel in chunks
+        let core = shard::CoreShard {
+            version: graph.version(),
+            next_id: graph.fqns().next_id.load(Ordering::SeqCst),
+        };
+        let count = shard::string_count(graph.symbols());
+        let strings = (0..count.div_ceil(shard::STRING_CHUNK))
+            .map(|i| shard::string_chunk(graph.symbols(), count, i))
+            .collect();
+        let chunks = (0..shard::fqn_chunks(core.next_id))
+            .map(|i| shard::fqn_chunk(graph.fqns(), i))
+            .collect();
+        let fqns = shard::restore_fqns(&core, strings, chunks).unwrap();
+        let decoded = shard::decode_shards(&fqns, shards.into_values().collect(), |_| None);
+
+        let restored = CodeGraph::from_shards(core.version, &fqns, &decoded, ShardLog::new());
+        assert_eq!(restored.node_count(), 2);
+        assert_eq!(restored.edge_count(), 1);
+        let a = restored.find_node("a.A").unwrap();
+        let b = restored.find_node("b.B").unwrap();
+        assert!(restored.topology().find_edge(a, b).is_some());
+        assert_eq!(restored.file_index().len(), 2);
+
+        // A subset of the shards keeps the edges among them only
+        let only_a = CodeGraph::from_shards(core.version, &fqns, &decoded[..1], ShardLog::new());
+        assert_eq!((only_a.node_count(), only_a.edge_count()), (1, 0));
+    }
+
+    #[test]
+    fn test_find_node_accepts_symbol_ids() {
+        use crate::model::NodeKind;
+        use crate::model::builder::CodeGraphBuilder;
+        use naviscope_api::models::symbol::NodeId;
+
+        let mut builder = CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            crate::model::Language::new("java".to_string()),
+            std::sync::Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        builder.add_node(crate::indexing::IndexNode {
+            id: NodeId::Structured(vec![
+                (NodeKind::Package, "a".to_string()),
+                (NodeKind::Class, "A".to_string()),
+                (NodeKind::Method, "run(int)".to_string()),
+            ]),
+            ..crate::test_support::node("run", NodeKind::Method)
+        });
+        let graph = builder.build();
+
+        let by_fqn = graph.find_node("a.A#run(int)");
+        assert!(by_fqn.is_some());
+        assert_eq!(graph.find_node("java:a.A#run(int)"), by_fqn);
+        assert_eq!(graph.find_node("java:a.A#stop()"), None);
+    }
 }
@@ -10,7 +10,7 @@ use std::path::Path;
 use std::sync::Arc;
 
 /// Fallback codec that uses empty metadata.
-struct DefaultNodeMetadataCodec;
+pub(super) struct DefaultNodeMetadataCodec;
 impl NodeMetadataCodec for DefaultNodeMetadataCodec {
     fn encode_metadata(
         &self,
@@ -30,7 +30,7 @@ impl NodeMetadataCodec for DefaultNodeMetadataCodec {
 }
 
 /// Read-only context used during deserialization
-struct ReadOnlyStorageContext(Arc<ThreadedRodeo>);
+pub(super) struct ReadOnlyStorageContext(pub(super) Arc<ThreadedRodeo>);
 
 impl crate::model::metadata::SymbolInterner for ReadOnlyStorageContext {
     fn intern_str(&mut self, _s: &str) -> u32 {
@@ -91,29 +91,14 @@ pub fn to_storage(
     let mut nodes = Vec::new();
 
     for idx in inner.topology.node_indices() {
-        let node = &inner.topology[idx];
         let storage_idx = nodes.len() as u32;
         node_id_map.insert(idx, storage_idx);
-
-        // Resolve language string for plugin lookup
-        let lang_str = ctx.resolve_str(node.lang.0.into_usize() as u32).to_string();
-        let codec = get_codec(&lang_str).unwrap_or_else(|| default_codec.clone());
-        let metadata = codec.encode_metadata(&*node.metadata, &mut ctx);
-
-        nodes.push(StorageNode {
-            id_sid: node.id.0,
-            name_sid: node.name.0.into_usize() as u32,
-            kind: node.kind.clone(),
-            lang_sid: node.lang.0.into_usize() as u32,
-            source: node.source.clone(),
-            status: node.status,
-            location: node.location.as_ref().map(|loc| StorageLocation {
-                path_id: loc.path.0.into_usize() as u32,
-                range: loc.range,
-                selection_range: loc.selection_range,
-            }),
-            metadata: metadata.into_boxed_slice(),
-        });
+        nodes.push(encode_node(
+            &inner.topology[idx],
+            &mut ctx,
+            &get_codec,
+            &default_codec,
+        ));
     }
 
     let edges: Vec<StorageEdge> = inner
@@ -164,6 +149,8 @@ pub fn to_storage(
                         .iter()
                         .map(|i| *node_id_map.get(i).unwrap())
                         .collect(),
+                    trigrams: entry.trigrams.clone(),
+                    fingerprints: entry.fingerprints.clone(),
                 },
             )
         })
@@ -205,25 +192,7 @@ pub fn from_storage(
     let ctx = ReadOnlyStorageContext(rodeo.clone());
 
     for snode in &storage.nodes {
-        let lang_str = ctx.resolve_str(snode.lang_sid).to_string();
-        let codec = get_codec(&lang_str).unwrap_or_else(|| default_codec.clone());
-        let metadata = codec.decode_metadata(&snode.metadata, &ctx);
-
-        let node = GraphNode {
-            id: FqnId(snode.id_sid),
-            name: Symbol(Spur::try_from_usize(snode.name_sid as usize).unwrap()),
-            kind: snode.kind.clone(),
-            lang: Symbol(Spur::try_from_usize(snode.lang_sid as usize).unwrap()),
-            source: snode.source.clone(),
-            status: snode.status,
-            location: snode.location.as_ref().map(|loc| InternedLocation {
-                path: Symbol(Spur::try_from_usize(loc.path_id as usize).unwrap()),
-                range: loc.range,
-                selection_range: loc.selection_range,
-            }),
-            metadata,
-        };
-        topology.add_node(node);
+        topology.add_node(decode_node(snode, &ctx, &get_codec, &default_codec));
     }
 
     for sedge in storage.edges {
@@ -267,6 +236,8 @@ pub fn from_storage(
                         .into_iter()
                         .map(|i| NodeIndex::new(i as usize))
                         .collect(),
+                    trigrams: entry.trigrams,
+                    fingerprints: entry.fingerprints,
                 },
             )
         })
@@ -296,5 +267,61 @@ pub fn from_storage(
         name_index,
         file_index,
         reference_index,
+        shard_log: super::shard::ShardLog::new(),
+    }
+}
+
+/// Convert a node to its stored form, encoding metadata with the node's language codec.
+pub(super) fn encode_node(
+    node: &GraphNode,
+    ctx: &mut GenericStorageContext,
+    get_codec: &impl Fn(&str) -> Option<Arc<dyn NodeMetadataCodec>>,
+    default_codec: &Arc<DefaultNodeMetadataCodec>,
+) -> StorageNode {
+    // Resolve language string for plugin lookup
+    let lang_str = ctx.resolve_str(node.lang.0.into_usize() as u32).to_string();
+    let codec = get_codec(&lang_str).unwrap_or_else(|| default_codec.clone());
+    let metadata = codec.encode_metadata(&*node.metadata, ctx);
+
+    StorageNode {
+        id_sid: node.id.0,
+        name_sid: node.name.0.into_usize() as u32,
+        kind: node.kind.clone(),
+        lang_sid: node.lang.0.into_usize() as u32,
+        source: node.source.clone(),
+        status: node.status,
+        location: node.location.as_ref().map(|loc| StorageLocation {
+            path_id: loc.path.0.into_usize() as u32,
+            range: loc.range,
+            selection_range: loc.selection_range,
+        }),
+        metadata: metadata.into_boxed_slice(),
+    }
+}
+
+/// Rebuild a node from its stored form.
+pub(super) fn decode_node(
+    snode: &StorageNode,
+    ctx: &ReadOnlyStorageContext,
+    get_codec: &impl Fn(&str) -> Option<Arc<dyn NodeMetadataCodec>>,
+    default_codec: &Arc<DefaultNodeMetadataCodec>,
+) -> GraphNode {
+    let lang_str = ctx.resolve_str(snode.lang_sid).to_string();
+    let codec = get_codec(&lang_str).unwrap_or_else(|| default_codec.clone());
+    let metadata = codec.decode_metadata(&snode.metadata, ctx);
+
+    GraphNode {
+        id: FqnId(snode.id_sid),
+        name: Symbol(Spur::try_from_usize(snode.name_sid as usize).unwrap()),
+        kind: snode.kind.clone(),
+        lang: Symbol(Spur::try_from_usize(snode.lang_sid as usize).unwrap()),
+        source: snode.source.clone(),
+        status: snode.status,
+        location: snode.location.as_ref().map(|loc| InternedLocation {
+            path: Symbol(Spur::try_from_usize(loc.path_id as usize).unwrap()),
+            range: loc.range,
+            selection_range: loc.selection_range,
+        }),
+        metadata,
     }
 }
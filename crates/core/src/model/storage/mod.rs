@@ -1,5 +1,6 @@
 pub mod converter;
 pub mod model;
+pub mod shard;
 
 pub use converter::{from_storage, to_storage};
 pub use model::StorageGraph;
@@ -142,4 +142,8 @@ pub struct StorageEdge {
 pub struct StorageFileEntry {
     pub metadata: crate::model::source::SourceFile,
     pub nodes: Vec<u32>,
+    #[serde(default)]
+    pub trigrams: Vec<u32>,
+    #[serde(default)]
+    pub fingerprints: Vec<naviscope_plugin::CodeFingerprint>,
 }
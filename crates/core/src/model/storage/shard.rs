@@ -0,0 +1,544 @@
+//! Sharded index layout
+//!
+//! The graph is split into one shard per source directory (a package, for
+//! Java). Nodes without a source file share the global shard. Edges are
+//! stored with the shard of their source node and address both endpoints by
+//! FQN id, so a shard can be encoded without knowing where its neighbours
+//! live.
+//!
+//! All shards refer to the interned strings and the FQN tree. Both only
+//! grow while graphs are built from one another, so they are stored in
+//! fixed-size chunks: a chunk that was full when it was saved never changes,
+//! and a save only writes the chunk the newest entries went to.
+
+use super::converter::{
+    DefaultNodeMetadataCodec, ReadOnlyStorageContext, decode_node, encode_node,
+};
+use super::model::*;
+use crate::error::{NaviscopeError, Result};
+use crate::model::graph::{CodeGraphInner, FileEntry};
+use crate::model::{FqnManager, GraphNode};
+use lasso::{Key, Spur, ThreadedRodeo};
+use naviscope_api::models::symbol::{FqnId, FqnNode, Symbol};
+use naviscope_plugin::NodeMetadataCodec;
+use petgraph::stable_graph::NodeIndex;
+use rayon::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Key of the shard holding nodes that have no source file
+pub const GLOBAL_SHARD: &str = "";
+
+/// Interned strings per chunk of the string table
+pub const STRING_CHUNK: usize = 1 << 16;
+
+/// FQN ids per chunk of the FQN tree
+pub const FQN_CHUNK: usize = 1 << 16;
+
+/// Scalars shared by all shards
+#[derive(Serialize, Deserialize)]
+pub struct CoreShard {
+    pub version: u32,
+    /// Next id of the FQN tree
+    pub next_id: u32,
+}
+
+/// Interned strings `i * STRING_CHUNK..`, in key order
+pub type StringChunk = Vec<String>;
+
+/// FQN tree nodes with ids `i * FQN_CHUNK..`
+pub type FqnChunk = Vec<(FqnId, FqnNode)>;
+
+/// Nodes declared in one source directory, with their outgoing edges
+#[derive(Serialize, Deserialize, Default)]
+pub struct NodeShard {
+    pub nodes: Vec<StorageNode>,
+    /// Edges leaving nodes of this shard; `from` and `to` are FQN ids
+    pub edges: Vec<StorageEdge>,
+    /// Files in this directory; node lists hold FQN ids
+    pub files: Vec<(u32, StorageFileEntry)>,
+    /// Reference tokens of the files in this directory: (token, files)
+    pub reference_index: Vec<(u32, Vec<u32>)>,
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// An id no other lineage or epoch is given, in this process or another
+fn unique_id() -> u64 {
+    let seed = (
+        std::process::id(),
+        NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    xxhash_rust::xxh3::xxh3_64(format!("{:?}", seed).as_bytes())
+}
+
+/// Files whose shards a graph changed since the start of its lineage
+///
+/// A lineage is a line of graphs built from one another, starting at a new
+/// graph or at one loaded from shards, and sharing one string table and
+/// FQN tree. Each builder starts an epoch and records the files it changes
+/// in it, so a save re-encodes only the shards changed since the epoch
+/// already on disk, provided the graph descends from that epoch.
+#[derive(Clone, Debug)]
+pub struct ShardLog {
+    pub lineage: u64,
+    /// Epochs the graph descends from, its own last
+    history: Arc<Vec<u64>>,
+    /// Epoch of the last change to each file; `None` is the global shard
+    changed: Arc<HashMap<Option<Symbol>, u64>>,
+}
+
+impl ShardLog {
+    /// Log of a graph that starts a lineage
+    pub fn new() -> Self {
+        Self::resume(unique_id(), unique_id())
+    }
+
+    /// Log of a graph loaded from the shards saved at `epoch` of `lineage`
+    pub fn resume(lineage: u64, epoch: u64) -> Self {
+        Self {
+            lineage,
+            history: Arc::new(vec![epoch]),
+            changed: Arc::new(HashMap::new()),
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        *self.history.last().unwrap()
+    }
+
+    /// Start the epoch of the next graph built from this one.
+    pub(crate) fn advance(&mut self) {
+        Arc::make_mut(&mut self.history).push(unique_id());
+    }
+
+    /// Record a change to the shard of `file`.
+    pub(crate) fn mark(&mut self, file: Option<Symbol>) {
+        let epoch = self.epoch();
+        if self.changed.get(&file) != Some(&epoch) {
+            Arc::make_mut(&mut self.changed).insert(file, epoch);
+        }
+    }
+
+    /// Keys of the shards changed after `epoch` of `lineage`, or `None` if
+    /// the graph does not descend from it
+    pub fn changed_since(
+        &self,
+        lineage: u64,
+        epoch: u64,
+        symbols: &ThreadedRodeo,
+    ) -> Option<BTreeSet<String>> {
+        if lineage != self.lineage {
+            return None;
+        }
+        let since = self.history.iter().rposition(|e| *e == epoch)?;
+        let later: HashSet<u64> = self.history[since + 1..].iter().copied().collect();
+        Some(
+            self.changed
+                .iter()
+                .filter(|(_, changed)| later.contains(changed))
+                .map(|(file, _)| file_shard(*file, symbols))
+                .collect(),
+        )
+    }
+}
+
+impl Default for ShardLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Shard key for a file: its parent directory
+fn shard_key(path: &str) -> String {
+    Path::new(path)
+        .parent()
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn file_shard(file: Option<Symbol>, symbols: &ThreadedRodeo) -> String {
+    match file {
+        Some(path) => shard_key(symbols.resolve(&path.0)),
+        None => GLOBAL_SHARD.to_string(),
+    }
+}
+
+fn spur(sid: u32) -> Symbol {
+    Symbol(Spur::try_from_usize(sid as usize).unwrap())
+}
+
+/// Shard key of every node, computed once per file
+fn node_shards(inner: &CodeGraphInner) -> HashMap<NodeIndex, Arc<str>> {
+    let mut keys: HashMap<Option<Symbol>, Arc<str>> = HashMap::new();
+    inner
+        .topology
+        .node_indices()
+        .map(|idx| {
+            let file = inner.topology[idx].location.as_ref().map(|loc| loc.path);
+            let key = keys
+                .entry(file)
+                .or_insert_with(|| file_shard(file, &inner.symbols).into())
+                .clone();
+            (idx, key)
+        })
+        .collect()
+}
+
+/// Where the nodes of a graph are stored, worked out without encoding them
+pub struct ShardLayout {
+    /// Shard key -> FQN ids of its nodes whose FQN parent is not a node of
+    /// the same shard. Every other node descends from one of them.
+    pub roots: BTreeMap<String, Vec<u32>>,
+    /// Shard key -> keys of the other shards its nodes have edges with
+    pub links: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl ShardLayout {
+    pub fn of(inner: &CodeGraphInner) -> Self {
+        let shards = node_shards(inner);
+        let mut roots: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+        let mut links: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+        for idx in inner.topology.node_indices() {
+            let node = &inner.topology[idx];
+            let key = &shards[&idx];
+            let parent_shard = inner
+                .fqns
+                .nodes
+                .get(&node.id)
+                .and_then(|fqn| fqn.parent)
+                .and_then(|parent| inner.fqn_index.get(&parent))
+                .map(|parent| &shards[parent]);
+            let entry = roots.entry(key.to_string()).or_default();
+            if parent_shard != Some(key) {
+                entry.push(node.id.0);
+            }
+        }
+        for edge in inner.topology.edge_indices() {
+            let (from, to) = inner.topology.edge_endpoints(edge).unwrap();
+            let (from, to) = (&shards[&from], &shards[&to]);
+            if from != to {
+                links
+                    .entry(from.to_string())
+                    .or_default()
+                    .insert(to.to_string());
+                links
+                    .entry(to.to_string())
+                    .or_default()
+                    .insert(from.to_string());
+            }
+        }
+        for ids in roots.values_mut() {
+            ids.sort_unstable();
+        }
+        // Files without nodes still have a shard
+        for path in inner.file_index.keys() {
+            roots
+                .entry(file_shard(Some(*path), &inner.symbols))
+                .or_default();
+        }
+        Self { roots, links }
+    }
+}
+
+/// Build the node shards whose keys `wanted` accepts.
+pub fn build_shards(
+    inner: &CodeGraphInner,
+    wanted: impl Fn(&str) -> bool,
+    get_codec: impl Fn(&str) -> Option<Arc<dyn NodeMetadataCodec>>,
+) -> BTreeMap<String, NodeShard> {
+    let mut ctx = GenericStorageContext {
+        rodeo: inner.symbols.clone(),
+    };
+    let default_codec = Arc::new(DefaultNodeMetadataCodec);
+    let node_shards = node_shards(inner);
+    let mut shards: BTreeMap<String, NodeShard> = BTreeMap::new();
+    fn shard<'a>(
+        shards: &'a mut BTreeMap<String, NodeShard>,
+        wanted: &impl Fn(&str) -> bool,
+        key: &str,
+    ) -> Option<&'a mut NodeShard> {
+        wanted(key).then(|| shards.entry(key.to_string()).or_default())
+    }
+
+    for idx in inner.topology.node_indices() {
+        let node = &inner.topology[idx];
+        if let Some(shard) = shard(&mut shards, &wanted, &node_shards[&idx]) {
+            shard
+                .nodes
+                .push(encode_node(node, &mut ctx, &get_codec, &default_codec));
+        }
+    }
+
+    for idx in inner.topology.edge_indices() {
+        let (from, to) = inner.topology.edge_endpoints(idx).unwrap();
+        if let Some(shard) = shard(&mut shards, &wanted, &node_shards[&from]) {
+            shard.edges.push(StorageEdge {
+                from: inner.topology[from].id.0,
+                to: inner.topology[to].id.0,
+                data: inner.topology[idx].clone(),
+            });
+        }
+    }
+
+    for (path, entry) in &inner.file_index {
+        let key = shard_key(inner.symbols.resolve(&path.0));
+        if let Some(shard) = shard(&mut shards, &wanted, &key) {
+            shard.files.push((
+                path.0.into_usize() as u32,
+                StorageFileEntry {
+                    metadata: entry.metadata.clone(),
+                    nodes: entry
+                        .nodes
+                        .iter()
+                        .filter_map(|i| inner.topology.node_weight(*i))
+                        .map(|node| node.id.0)
+                        .collect(),
+                    trigrams: entry.trigrams.clone(),
+                    fingerprints: entry.fingerprints.clone(),
+                },
+            ));
+        }
+    }
+
+    for (token, paths) in &inner.reference_index {
+        let mut by_shard: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+        for path in paths {
+            by_shard
+                .entry(shard_key(inner.symbols.resolve(&path.0)))
+                .or_default()
+                .push(path.0.into_usize() as u32);
+        }
+        for (key, paths) in by_shard {
+            if let Some(shard) = shard(&mut shards, &wanted, &key) {
+                shard
+                    .reference_index
+                    .push((token.0.into_usize() as u32, paths));
+            }
+        }
+    }
+
+    // Stable order, so unchanged shards encode to identical bytes
+    for shard in shards.values_mut() {
+        shard.nodes.sort_unstable_by_key(|n| n.id_sid);
+        shard.edges.sort_by_key(|e| (e.from, e.to));
+        shard.files.sort_unstable_by_key(|f| f.0);
+        for (_, entry) in &mut shard.files {
+            entry.nodes.sort_unstable();
+        }
+        shard.reference_index.sort_unstable_by_key(|r| r.0);
+        for (_, paths) in &mut shard.reference_index {
+            paths.sort_unstable();
+        }
+    }
+    shards
+}
+
+/// Number of strings interned so far. Strings interned while the table is
+/// read may be missed, but never leave a gap.
+pub fn string_count(rodeo: &ThreadedRodeo) -> usize {
+    (0..rodeo.len())
+        .find(|&key| {
+            rodeo
+                .try_resolve(&Spur::try_from_usize(key).unwrap())
+                .is_none()
+        })
+        .unwrap_or(rodeo.len())
+}
+
+/// Chunk `index` of the first `count` strings
+pub fn string_chunk(rodeo: &ThreadedRodeo, count: usize, index: usize) -> StringChunk {
+    (index * STRING_CHUNK..count.min((index + 1) * STRING_CHUNK))
+        .map(|key| {
+            rodeo
+                .resolve(&Spur::try_from_usize(key).unwrap())
+                .to_string()
+        })
+        .collect()
+}
+
+/// Lowest FQN id not in the tree yet. Ids are handed out before their node
+/// is added, so a concurrent build may leave later ids in place as well.
+pub fn fqn_count(fqns: &FqnManager) -> u32 {
+    let next_id = fqns.next_id.load(Ordering::SeqCst);
+    (1..next_id)
+        .find(|id| !fqns.nodes.contains_key(&FqnId(*id)))
+        .unwrap_or(next_id)
+}
+
+/// Chunk `index` of the FQN tree, with every node it holds so far
+pub fn fqn_chunk(fqns: &FqnManager, index: usize) -> FqnChunk {
+    let start = (index * FQN_CHUNK) as u32;
+    (start..start + FQN_CHUNK as u32)
+        .filter_map(|id| {
+            fqns.nodes
+                .get(&FqnId(id))
+                .map(|node| (FqnId(id), node.clone()))
+        })
+        .collect()
+}
+
+/// Number of chunks of the FQN tree below `next_id`
+pub fn fqn_chunks(next_id: u32) -> usize {
+    match next_id {
+        0 | 1 => 0,
+        next_id => (next_id as usize - 1) / FQN_CHUNK + 1,
+    }
+}
+
+/// Reassemble the string table and FQN tree from their chunks.
+pub fn restore_fqns(
+    core: &CoreShard,
+    strings: Vec<StringChunk>,
+    fqns: Vec<FqnChunk>,
+) -> Result<FqnManager> {
+    let rodeo: Arc<ThreadedRodeo> = Arc::new(ThreadedRodeo::new());
+    for (expected, string) in strings.into_iter().flatten().enumerate() {
+        let key = rodeo.get_or_intern(string);
+        if key.into_usize() != expected {
+            return Err(NaviscopeError::Parsing(format!(
+                "string {} of the index is stored twice",
+                expected
+            )));
+        }
+    }
+    let manager = FqnManager::with_rodeo(rodeo);
+    for (id, node) in fqns.into_iter().flatten() {
+        manager
+            .lookup
+            .insert((node.parent, node.name, node.kind.clone()), id);
+        manager.nodes.insert(id, node);
+    }
+    // Ids handed out while the tree was saved may lie past the saved next id
+    let next_id = manager
+        .nodes
+        .iter()
+        .map(|e| e.key().0 + 1)
+        .max()
+        .unwrap_or(1);
+    manager
+        .next_id
+        .store(core.next_id.max(next_id), Ordering::SeqCst);
+    Ok(manager)
+}
+
+/// A node shard with its nodes decoded
+pub struct DecodedShard {
+    pub nodes: Vec<GraphNode>,
+    /// The rest of the shard; its `nodes` are left empty
+    pub shard: NodeShard,
+}
+
+/// Decode the nodes of `shards` in parallel.
+pub fn decode_shards(
+    fqns: &FqnManager,
+    shards: Vec<NodeShard>,
+    get_codec: impl Fn(&str) -> Option<Arc<dyn NodeMetadataCodec>> + Sync,
+) -> Vec<DecodedShard> {
+    let ctx = ReadOnlyStorageContext(fqns.rodeo.clone());
+    let default_codec = Arc::new(DefaultNodeMetadataCodec);
+    shards
+        .into_par_iter()
+        .map(|mut shard| {
+            let nodes = std::mem::take(&mut shard.nodes)
+                .iter()
+                .map(|snode| decode_node(snode, &ctx, &get_codec, &default_codec))
+                .collect();
+            DecodedShard { nodes, shard }
+        })
+        .collect()
+}
+
+/// Assemble a graph from decoded shards. Edges whose endpoint is in none
+/// of them are left out.
+pub fn assemble_shards<'a>(
+    version: u32,
+    fqns: &FqnManager,
+    shards: impl IntoIterator<Item = &'a DecodedShard> + Clone,
+    shard_log: ShardLog,
+) -> CodeGraphInner {
+    let mut topology = petgraph::stable_graph::StableDiGraph::new();
+    let mut fqn_index: HashMap<FqnId, NodeIndex> = HashMap::new();
+    let mut name_index: HashMap<Symbol, Vec<NodeIndex>> = HashMap::new();
+    for decoded in shards.clone() {
+        for node in &decoded.nodes {
+            let idx = topology.add_node(node.clone());
+            fqn_index.insert(node.id, idx);
+            name_index.entry(node.name).or_default().push(idx);
+        }
+    }
+
+    let mut file_index = HashMap::new();
+    let mut reference_index: HashMap<Symbol, Vec<Symbol>> = HashMap::new();
+    for decoded in shards {
+        let shard = &decoded.shard;
+        for edge in &shard.edges {
+            let endpoints = (
+                fqn_index.get(&FqnId(edge.from)),
+                fqn_index.get(&FqnId(edge.to)),
+            );
+            if let (Some(&from), Some(&to)) = endpoints {
+                topology.add_edge(from, to, edge.data.clone());
+            }
+        }
+        for (pid, entry) in &shard.files {
+            file_index.insert(
+                spur(*pid),
+                FileEntry {
+                    metadata: entry.metadata.clone(),
+                    nodes: entry
+                        .nodes
+                        .iter()
+                        .filter_map(|id| fqn_index.get(&FqnId(*id)).copied())
+                        .collect(),
+                    trigrams: entry.trigrams.clone(),
+                    fingerprints: entry.fingerprints.clone(),
+                },
+            );
+        }
+        for (token, paths) in &shard.reference_index {
+            reference_index
+                .entry(spur(*token))
+                .or_default()
+                .extend(paths.iter().copied().map(spur));
+        }
+    }
+
+    CodeGraphInner {
+        instance_id: 0, // Will be updated when wrapped in CodeGraph
+        version,
+        topology,
+        fqns: fqns.clone(),
+        symbols: fqns.rodeo.clone(),
+        fqn_index,
+        name_index,
+        file_index,
+        reference_index,
+        shard_log,
+    }
+}
+
+/// Encode a shard as compressed MessagePack.
+pub fn encode_shard<T: Serialize>(shard: &T) -> Result<Vec<u8>> {
+    let bytes = rmp_serde::to_vec(shard)
+        .map_err(|e| NaviscopeError::Internal(format!("MSGPACK error: {}", e)))?;
+    zstd::encode_all(&bytes[..], 0)
+        .map_err(|e| NaviscopeError::Internal(format!("Zstd compression failed: {}", e)))
+}
+
+/// Decode a shard written by `encode_shard`.
+pub fn decode_shard<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    let decoder = zstd::stream::read::Decoder::new(bytes)
+        .map_err(|e| NaviscopeError::Internal(format!("Zstd decoder init failed: {}", e)))?;
+    rmp_serde::from_read(decoder)
+        .map_err(|e| NaviscopeError::Internal(format!("MSGPACK error: {}", e)))
+}
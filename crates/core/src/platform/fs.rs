@@ -0,0 +1,257 @@
+use super::clock::{Clock, SystemClock};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Contents of a file opened with [`Fs::read_mapped`]
+pub enum FileBytes {
+    Mapped(memmap2::Mmap),
+    Owned(Vec<u8>),
+}
+
+impl std::ops::Deref for FileBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            FileBytes::Mapped(map) => map,
+            FileBytes::Owned(bytes) => bytes,
+        }
+    }
+}
+
+/// File operations used by the runtime
+pub trait Fs: Send + Sync {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+    /// Read a file, memory-mapping it where the filesystem allows.
+    fn read_mapped(&self, path: &Path) -> io::Result<FileBytes> {
+        self.read(path).map(FileBytes::Owned)
+    }
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+    /// Add `contents` to the end of the file, creating it if needed.
+    fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut existing = match self.read(path) {
+            Ok(existing) => existing,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        existing.extend_from_slice(contents);
+        self.write(path, &existing)
+    }
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()>;
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+    fn exists(&self, path: &Path) -> bool;
+    fn modified(&self, path: &Path) -> io::Result<SystemTime>;
+    /// Flush a written file, or the entries of a directory, to stable storage.
+    fn sync(&self, _path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The host filesystem
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RealFs;
+
+impl Fs for RealFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+
+    fn read_mapped(&self, path: &Path) -> io::Result<FileBytes> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: index files are written to a temporary name and renamed into
+        // place, so a mapped file is never modified while it is mapped.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(FileBytes::Mapped(map))
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        std::fs::write(path, contents)
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        use std::io::Write;
+        // In append mode, so processes sharing the file never overwrite each other
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(contents)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        std::fs::rename(from, to)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        std::fs::remove_file(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        std::fs::remove_dir_all(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn modified(&self, path: &Path) -> io::Result<SystemTime> {
+        std::fs::metadata(path)?.modified()
+    }
+
+    fn sync(&self, path: &Path) -> io::Result<()> {
+        // Only Unix lets a directory be opened and synced
+        if cfg!(not(unix)) && path.is_dir() {
+            return Ok(());
+        }
+        std::fs::File::open(path)?.sync_all()
+    }
+}
+
+struct MemoryFile {
+    contents: Vec<u8>,
+    modified: SystemTime,
+}
+
+/// In-memory filesystem for tests
+///
+/// Directories are implicit: any prefix of a stored file exists. Modification
+/// times come from the injected clock.
+pub struct MemoryFs {
+    files: Mutex<BTreeMap<PathBuf, MemoryFile>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            files: Mutex::new(BTreeMap::new()),
+            clock,
+        }
+    }
+
+    /// Paths of all stored files, in sorted order
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.files.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+impl Default for MemoryFs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} not found", path.display()),
+    )
+}
+
+impl Fs for MemoryFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(path)
+            .map(|f| f.contents.clone())
+            .ok_or_else(|| not_found(path))
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let file = MemoryFile {
+            contents: contents.to_vec(),
+            modified: self.clock.now(),
+        };
+        self.files.lock().unwrap().insert(path.to_path_buf(), file);
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut files = self.files.lock().unwrap();
+        let file = files.remove(from).ok_or_else(|| not_found(from))?;
+        files.insert(to.to_path_buf(), file);
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.files
+            .lock()
+            .unwrap()
+            .remove(path)
+            .map(|_| ())
+            .ok_or_else(|| not_found(path))
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.files
+            .lock()
+            .unwrap()
+            .retain(|p, _| !p.starts_with(path));
+        Ok(())
+    }
+
+    fn create_dir_all(&self, _path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files
+            .lock()
+            .unwrap()
+            .keys()
+            .any(|p| p.starts_with(path))
+    }
+
+    fn modified(&self, path: &Path) -> io::Result<SystemTime> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(path)
+            .map(|f| f.modified)
+            .ok_or_else(|| not_found(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::ManualClock;
+    use std::time::Duration;
+
+    #[test]
+    fn memory_fs_tracks_writes_and_renames() {
+        let clock = Arc::new(ManualClock::default());
+        let fs = MemoryFs::with_clock(clock.clone());
+        let tmp = Path::new("/idx/a.tmp");
+        let dst = Path::new("/idx/a.bin");
+
+        clock.advance(Duration::from_secs(3));
+        fs.write(tmp, b"data").unwrap();
+        fs.rename(tmp, dst).unwrap();
+
+        assert!(!fs.exists(tmp));
+        assert!(fs.exists(Path::new("/idx")));
+        assert_eq!(fs.read(dst).unwrap(), b"data");
+        assert_eq!(
+            fs.modified(dst).unwrap(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(3)
+        );
+
+        fs.remove_dir_all(Path::new("/idx")).unwrap();
+        assert!(fs.paths().is_empty());
+    }
+}
@@ -0,0 +1,11 @@
+//! Injectable access to the system clock and filesystem
+//!
+//! Runtime code goes through these traits instead of calling `std::fs` or
+//! `SystemTime` directly, so lifecycle, storage and watcher behavior can be
+//! tested deterministically with the in-memory doubles.
+
+pub mod clock;
+pub mod fs;
+
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use fs::{FileBytes, Fs, MemoryFs, RealFs};
@@ -0,0 +1,116 @@
+//! Audit log of the actions clients took through the engine
+//!
+//! MCP servers record each tool call of their agents: who called which tool
+//! with which arguments, when, and whether it failed. Entries are appended as
+//! JSON lines to a file next to the index and never rewritten; rebuilds and
+//! `clear` leave the log in place.
+
+use super::*;
+use naviscope_api::lifecycle::AuditEntry;
+
+pub(super) struct AuditLog {
+    fs: Arc<dyn Fs>,
+    path: PathBuf,
+    /// Keeps the appends of this process in order
+    lock: std::sync::Mutex<()>,
+}
+
+impl AuditLog {
+    pub(super) fn new(fs: Arc<dyn Fs>, index_path: &Path) -> Self {
+        Self {
+            fs,
+            path: index_path.with_extension("audit.jsonl"),
+            lock: std::sync::Mutex::new(()),
+        }
+    }
+
+    pub(super) fn append(&self, entry: &AuditEntry) -> Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        let _guard = self.lock.lock().unwrap();
+        if let Some(parent) = self.path.parent() {
+            self.fs.create_dir_all(parent)?;
+        }
+        self.fs.append(&self.path, &line)?;
+        Ok(())
+    }
+
+    /// Entries in the order they were recorded. Lines that cannot be read,
+    /// e.g. one cut short by a crash, are skipped.
+    pub(super) fn entries(&self) -> Result<Vec<AuditEntry>> {
+        if !self.fs.exists(&self.path) {
+            return Ok(Vec::new());
+        }
+        let contents = self.fs.read(&self.path)?;
+        Ok(contents
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .filter_map(|line| match serde_json::from_slice(line) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable audit log entry: {}", e);
+                    None
+                }
+            })
+            .collect())
+    }
+}
+
+impl NaviscopeEngine {
+    /// Append `entry` to the audit log; not recorded on a read-only index
+    pub fn record_audit(&self, entry: &AuditEntry) -> Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        self.audit.append(entry)
+    }
+
+    /// Entries of the audit log, oldest first
+    pub fn audit_log(&self) -> Result<Vec<AuditEntry>> {
+        self.audit.entries()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::MemoryFs;
+
+    fn entry(at: u64, tool: &str, error: Option<&str>) -> AuditEntry {
+        AuditEntry {
+            at,
+            client: Some("agent".to_string()),
+            tool: tool.to_string(),
+            arguments: serde_json::json!({"fqn": "com.app.A"}),
+            error: error.map(str::to_string),
+            duration_ms: 3,
+        }
+    }
+
+    #[test]
+    fn entries_are_appended_and_read_back_in_order() {
+        let fs = Arc::new(MemoryFs::new());
+        let index_path = PathBuf::from("/idx/p.bin");
+        let log = AuditLog::new(fs.clone(), &index_path);
+        assert!(log.entries().unwrap().is_empty());
+
+        log.append(&entry(1, "annotate", None)).unwrap();
+        // A line cut short by a crash does not hide the entries around it
+        fs.append(
+            &index_path.with_extension("audit.jsonl"),
+            b"{\"at\":2,\"to\n",
+        )
+        .unwrap();
+        log.append(&entry(3, "cat", Some("No node com.app.A")))
+            .unwrap();
+
+        let reopened = AuditLog::new(fs, &index_path);
+        assert_eq!(
+            reopened.entries().unwrap(),
+            [
+                entry(1, "annotate", None),
+                entry(3, "cat", Some("No node com.app.A"))
+            ]
+        );
+    }
+}
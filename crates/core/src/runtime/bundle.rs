@@ -0,0 +1,138 @@
+//! Portable index bundles
+//!
+//! The sharded index spreads over a manifest and a directory of shards; a
+//! bundle holds the same graph, interned symbols included, in one file that
+//! can be copied to another machine and opened there read-only. It also
+//! records the project root the index was built at and the library assets
+//! its external nodes were stubbed from, by package prefix. Node locations
+//! stay those of the build machine.
+
+use super::*;
+use naviscope_api::models::graph::ExportSummary;
+use std::collections::BTreeMap;
+
+/// Leading bytes of a bundle
+const MAGIC: &[u8] = b"NVSCBDL1";
+
+#[derive(Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(super) struct BundleHeader {
+    /// Project root the index was built at
+    pub(super) project_root: PathBuf,
+    /// Package prefix -> library assets its stubs were read from
+    pub(super) stubs: BTreeMap<String, Vec<PathBuf>>,
+}
+
+impl BundleHeader {
+    pub(super) fn is_bundle(bytes: &[u8]) -> bool {
+        bytes.starts_with(MAGIC)
+    }
+
+    /// The bundle of `graph`, an encoded `CodeGraph`
+    pub(super) fn encode(&self, graph: &[u8]) -> Result<Vec<u8>> {
+        let header = rmp_serde::to_vec(self)
+            .map_err(|e| NaviscopeError::Internal(format!("MSGPACK error: {}", e)))?;
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 4 + header.len() + graph.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&header);
+        bytes.extend_from_slice(graph);
+        Ok(bytes)
+    }
+
+    /// The header of a bundle and its encoded graph
+    pub(super) fn decode(bytes: &[u8]) -> Result<(Self, &[u8])> {
+        let invalid = || NaviscopeError::Parsing("not an index bundle".to_string());
+        let body = bytes.strip_prefix(MAGIC).ok_or_else(invalid)?;
+        let (len, body) = body.split_first_chunk::<4>().ok_or_else(invalid)?;
+        let len = u32::from_le_bytes(*len) as usize;
+        if body.len() < len {
+            return Err(invalid());
+        }
+        let (header, graph) = body.split_at(len);
+        let header = rmp_serde::from_slice(header)
+            .map_err(|e| NaviscopeError::Internal(format!("MSGPACK error: {}", e)))?;
+        Ok((header, graph))
+    }
+}
+
+impl NaviscopeEngine {
+    /// Write the current graph to the bundle `path`, replacing any previous one.
+    pub async fn write_bundle(&self, path: &Path) -> Result<ExportSummary> {
+        let graph = self.snapshot().await;
+        let header = BundleHeader {
+            project_root: self.project_root.clone(),
+            stubs: self.global_asset_routes().into_iter().collect(),
+        };
+        let lang_caps = self.lang_caps.clone();
+        let build_caps = self.build_caps.clone();
+        let fs = self.fs.clone();
+        let path = path.to_path_buf();
+
+        tokio::task::spawn_blocking(move || -> Result<ExportSummary> {
+            let encoded = graph.serialize(storage::codec_lookup(&lang_caps, &build_caps))?;
+            let bytes = header.encode(&encoded)?;
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs.create_dir_all(parent)?;
+            }
+            let temp_path = path.with_extension("tmp");
+            fs.write(&temp_path, &bytes)?;
+            fs.rename(&temp_path, &path)?;
+            Ok(ExportSummary {
+                files: vec![path],
+                nodes: graph.node_count(),
+                edges: graph.edge_count(),
+            })
+        })
+        .await
+        .map_err(|e| NaviscopeError::Internal(e.to_string()))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::NodeKind;
+    use crate::model::builder::CodeGraphBuilder;
+    use crate::platform::MemoryFs;
+
+    #[test]
+    fn header_and_graph_roundtrip() {
+        let header = BundleHeader {
+            project_root: PathBuf::from("/ci/project"),
+            stubs: BTreeMap::from([(
+                "java.util".to_string(),
+                vec![PathBuf::from("/jdk/lib/modules")],
+            )]),
+        };
+        let bytes = header.encode(b"graph").unwrap();
+        assert!(BundleHeader::is_bundle(&bytes));
+        let (decoded, graph) = BundleHeader::decode(&bytes).unwrap();
+        assert_eq!(decoded, header);
+        assert_eq!(graph, b"graph");
+        assert!(BundleHeader::decode(&bytes[..12]).is_err());
+    }
+
+    #[tokio::test]
+    async fn bundles_open_as_read_only_indices() {
+        let fs = Arc::new(MemoryFs::new());
+        let engine = NaviscopeEngine::builder(PathBuf::from("."))
+            .with_fs(fs.clone())
+            .build();
+        let mut builder = CodeGraphBuilder::new();
+        builder.add_node(crate::test_support::node("a.A", NodeKind::Class));
+        engine.apply_graph_snapshot(builder.build()).await;
+
+        let path = PathBuf::from("/artifacts/p.nsidx");
+        let summary = engine.write_bundle(&path).await.unwrap();
+        assert_eq!(summary.files, std::slice::from_ref(&path));
+        assert_eq!(summary.nodes, 1);
+
+        let opened = NaviscopeEngine::builder(PathBuf::from("/elsewhere"))
+            .with_fs(fs.clone())
+            .with_read_only_index(path.clone())
+            .build();
+        assert!(opened.load().await.unwrap());
+        assert_eq!(opened.snapshot().await.node_count(), 1);
+        assert_eq!(fs.paths(), [path]);
+    }
+}
@@ -0,0 +1,136 @@
+//! Editor state of open documents
+//!
+//! While a document has unsaved edits, the file on disk and the editor buffer
+//! differ. Re-indexing the disk version then, because a formatter, a branch
+//! switch or an autosave touched the file, makes goto results jump between
+//! the two. Updates of such files are held back and replayed once the
+//! document is saved or closed, when disk and editor agree again.
+
+use naviscope_api::lifecycle::DocumentEvent;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+#[derive(Default)]
+pub struct OpenDocuments {
+    state: Mutex<DocumentsState>,
+}
+
+#[derive(Default)]
+struct DocumentsState {
+    /// Open documents: their latest version and whether it is unsaved
+    open: HashMap<PathBuf, (i32, bool)>,
+    /// Files whose updates were held back
+    held: BTreeSet<PathBuf>,
+}
+
+impl OpenDocuments {
+    /// Record `event` for `path`. Returns `path` when updates held back for it
+    /// should now be applied.
+    pub fn apply(&self, path: &Path, event: DocumentEvent) -> Option<PathBuf> {
+        let mut state = self.state.lock().unwrap();
+        match event {
+            DocumentEvent::Opened { version } => {
+                state.open.insert(path.to_path_buf(), (version, false));
+                None
+            }
+            DocumentEvent::Changed { version } => {
+                let entry = state
+                    .open
+                    .entry(path.to_path_buf())
+                    .or_insert((version, true));
+                // Notifications may arrive out of order
+                if version >= entry.0 {
+                    *entry = (version, true);
+                }
+                None
+            }
+            DocumentEvent::Saved => {
+                if let Some(entry) = state.open.get_mut(path) {
+                    entry.1 = false;
+                }
+                state.held.remove(path).then(|| path.to_path_buf())
+            }
+            DocumentEvent::Closed => {
+                state.open.remove(path);
+                state.held.remove(path).then(|| path.to_path_buf())
+            }
+        }
+    }
+
+    /// Split off the files with unsaved edits, holding their updates back,
+    /// and return the rest.
+    pub fn hold_dirty(&self, files: Vec<PathBuf>) -> Vec<PathBuf> {
+        let mut state = self.state.lock().unwrap();
+        if state.open.is_empty() {
+            return files;
+        }
+        let (dirty, clean): (Vec<PathBuf>, Vec<PathBuf>) = files
+            .into_iter()
+            .partition(|path| state.open.get(path).is_some_and(|(_, dirty)| *dirty));
+        if !dirty.is_empty() {
+            tracing::debug!(
+                "Holding back updates of {} files with unsaved edits",
+                dirty.len()
+            );
+        }
+        state.held.extend(dirty);
+        clean
+    }
+
+    /// Version of `path` last reported by the editor, if it is open
+    pub fn version(&self, path: &Path) -> Option<i32> {
+        self.state
+            .lock()
+            .unwrap()
+            .open
+            .get(path)
+            .map(|(version, _)| *version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn updates_of_dirty_documents_wait_for_save() {
+        let documents = OpenDocuments::default();
+        let open = PathBuf::from("/p/A.java");
+        let other = PathBuf::from("/p/B.java");
+        documents.apply(&open, DocumentEvent::Opened { version: 1 });
+
+        // A clean document is indexed as usual
+        assert_eq!(
+            documents.hold_dirty(vec![open.clone(), other.clone()]),
+            [open.clone(), other.clone()]
+        );
+
+        documents.apply(&open, DocumentEvent::Changed { version: 3 });
+        documents.apply(&open, DocumentEvent::Changed { version: 2 });
+        assert_eq!(documents.version(&open), Some(3));
+        assert_eq!(
+            documents.hold_dirty(vec![open.clone(), other.clone()]),
+            std::slice::from_ref(&other)
+        );
+
+        assert_eq!(
+            documents.apply(&open, DocumentEvent::Saved),
+            Some(open.clone())
+        );
+        // Nothing is held any more
+        assert_eq!(documents.apply(&open, DocumentEvent::Saved), None);
+        assert_eq!(
+            documents.hold_dirty(vec![open.clone()]),
+            std::slice::from_ref(&open)
+        );
+
+        documents.apply(&open, DocumentEvent::Changed { version: 4 });
+        assert!(documents.hold_dirty(vec![open.clone()]).is_empty());
+        assert_eq!(
+            documents.apply(&open, DocumentEvent::Closed),
+            Some(open.clone())
+        );
+        assert_eq!(documents.version(&open), None);
+    }
+}
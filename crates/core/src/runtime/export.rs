@@ -0,0 +1,37 @@
+//! Export of the current graph to files for other tools
+
+use super::*;
+use crate::features::export::{NEO4J_NODES_FILE, NEO4J_RELATIONSHIPS_FILE, Neo4jExporter};
+use naviscope_api::models::graph::ExportSummary;
+use std::io::BufWriter;
+
+impl NaviscopeEngine {
+    /// Write the current graph as Neo4j import CSV to `out_dir`, creating it
+    /// if needed.
+    ///
+    /// Files are streamed to disk directly rather than through `Fs`, since an
+    /// export of a large project does not fit comfortably in memory.
+    pub async fn export_neo4j(&self, out_dir: &Path) -> Result<ExportSummary> {
+        let graph = self.snapshot().await;
+        let conventions = (*self.naming_conventions).clone();
+        let out_dir = out_dir.to_path_buf();
+
+        tokio::task::spawn_blocking(move || -> Result<ExportSummary> {
+            std::fs::create_dir_all(&out_dir)?;
+            let nodes_path = out_dir.join(NEO4J_NODES_FILE);
+            let relationships_path = out_dir.join(NEO4J_RELATIONSHIPS_FILE);
+            let (nodes, edges) = Neo4jExporter::new(conventions).write(
+                &graph,
+                BufWriter::new(std::fs::File::create(&nodes_path)?),
+                BufWriter::new(std::fs::File::create(&relationships_path)?),
+            )?;
+            Ok(ExportSummary {
+                files: vec![nodes_path, relationships_path],
+                nodes,
+                edges,
+            })
+        })
+        .await
+        .map_err(|e| NaviscopeError::Internal(e.to_string()))?
+    }
+}
@@ -0,0 +1,155 @@
+//! Adaptive batching of watch updates
+//!
+//! A burst of file events (a branch switch, a code generator) used to be
+//! applied as one incremental update, holding the index lock for seconds.
+//! The watcher now applies at most `limit` files per update and adjusts the
+//! limit from the observed latency: it halves when an update exceeds the
+//! target and doubles while files queue up and updates stay well below it.
+
+use naviscope_api::lifecycle::{FlowDecision, FlowReason, WatchFlow};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Adaptation decisions kept for inspection
+const DECISION_HISTORY: usize = 16;
+
+#[derive(Debug)]
+pub(super) struct AdaptiveBatch {
+    min: usize,
+    max: usize,
+    target: Duration,
+    limit: usize,
+    backlog: usize,
+    last_batch: usize,
+    last_latency: Duration,
+    decisions: VecDeque<FlowDecision>,
+}
+
+impl AdaptiveBatch {
+    /// Start at the lower bound; `max` is raised to `min` if it is smaller.
+    pub(super) fn new(min: usize, max: usize, target: Duration) -> Self {
+        let min = min.max(1);
+        Self {
+            min,
+            max: max.max(min),
+            target,
+            limit: min,
+            backlog: 0,
+            last_batch: 0,
+            last_latency: Duration::ZERO,
+            decisions: VecDeque::new(),
+        }
+    }
+
+    /// Maximum number of files for the next update
+    pub(super) fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Record files still waiting after the events seen so far
+    pub(super) fn set_backlog(&mut self, backlog: usize) {
+        self.backlog = backlog;
+    }
+
+    /// Record an update of `files` that took `latency`, with `backlog` files
+    /// still waiting, and adapt the limit. Returns the decision, if any.
+    pub(super) fn observe(
+        &mut self,
+        files: usize,
+        latency: Duration,
+        backlog: usize,
+    ) -> Option<FlowDecision> {
+        self.last_batch = files;
+        self.last_latency = latency;
+        self.backlog = backlog;
+
+        let (limit, reason) = if latency > self.target {
+            ((self.limit / 2).max(self.min), FlowReason::Slow)
+        } else if files >= self.limit && backlog > 0 && latency < self.target / 2 {
+            ((self.limit * 2).min(self.max), FlowReason::Backlog)
+        } else {
+            return None;
+        };
+        if limit == self.limit {
+            return None;
+        }
+
+        let decision = FlowDecision {
+            from: self.limit,
+            to: limit,
+            reason,
+            latency_ms: latency.as_millis() as u64,
+            backlog,
+        };
+        self.limit = limit;
+        if self.decisions.len() == DECISION_HISTORY {
+            self.decisions.pop_front();
+        }
+        self.decisions.push_back(decision.clone());
+        Some(decision)
+    }
+
+    pub(super) fn metrics(&self) -> WatchFlow {
+        WatchFlow {
+            limit: self.limit,
+            min: self.min,
+            max: self.max,
+            target_latency_ms: self.target.as_millis() as u64,
+            backlog: self.backlog,
+            last_batch: self.last_batch,
+            last_latency_ms: self.last_latency.as_millis() as u64,
+            decisions: self.decisions.iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: Duration = Duration::from_millis(500);
+
+    #[test]
+    fn limit_grows_with_backlog_and_shrinks_on_slow_updates() {
+        let mut batch = AdaptiveBatch::new(8, 64, TARGET);
+        assert_eq!(batch.limit(), 8);
+
+        // Fast, full batches with files waiting: double up to the bound
+        let grow = batch.observe(8, Duration::from_millis(50), 100).unwrap();
+        assert_eq!(
+            (grow.from, grow.to, grow.reason),
+            (8, 16, FlowReason::Backlog)
+        );
+        batch.observe(16, Duration::from_millis(50), 100);
+        batch.observe(32, Duration::from_millis(50), 100);
+        assert_eq!(batch.limit(), 64);
+        assert!(batch.observe(64, Duration::from_millis(50), 100).is_none());
+
+        // A slow update halves it
+        let shrink = batch.observe(64, Duration::from_millis(900), 40).unwrap();
+        assert_eq!(
+            (shrink.from, shrink.to, shrink.reason),
+            (64, 32, FlowReason::Slow)
+        );
+
+        let metrics = batch.metrics();
+        assert_eq!(metrics.limit, 32);
+        assert_eq!(metrics.backlog, 40);
+        assert_eq!(metrics.last_latency_ms, 900);
+        assert_eq!(metrics.decisions.len(), 4);
+    }
+
+    #[test]
+    fn limit_holds_without_backlog_or_within_bounds() {
+        let mut batch = AdaptiveBatch::new(4, 2, TARGET);
+        assert_eq!(batch.metrics().max, 4);
+        // Idle watcher: a small batch with nothing waiting changes nothing
+        assert!(batch.observe(1, Duration::from_millis(10), 0).is_none());
+        // Already at the lower bound
+        assert!(batch.observe(4, Duration::from_secs(2), 10).is_none());
+        // Between half the target and the target: keep the limit
+        let mut batch = AdaptiveBatch::new(4, 16, TARGET);
+        assert!(batch.observe(4, Duration::from_millis(300), 10).is_none());
+        assert!(batch.metrics().decisions.is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+//! Journal of files committed by an in-progress full build
+//!
+//! A full build commits its graph in batches. After each batch the index is
+//! saved and the journal records the fingerprint of every committed file,
+//! so a build that dies midway can resume from the saved index instead of
+//! starting over. The journal is removed once the build completes.
+
+use crate::error::Result;
+use crate::model::source::SourceFile;
+use crate::platform::Fs;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct JournalEntry {
+    content_hash: u64,
+    last_modified: u64,
+    /// Epoch of the batch that committed this fingerprint
+    epoch: u64,
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub(super) struct BuildJournal {
+    /// Epoch of the most recently committed batch
+    epoch: u64,
+    files: BTreeMap<PathBuf, JournalEntry>,
+}
+
+impl BuildJournal {
+    /// Journal location for the index stored at `index_path`
+    pub(super) fn path_for(index_path: &Path) -> PathBuf {
+        index_path.with_extension("journal")
+    }
+
+    /// Read a journal left behind by an interrupted build.
+    ///
+    /// A journal that cannot be parsed is discarded, which makes the next
+    /// build start from scratch.
+    pub(super) fn load(fs: &dyn Fs, path: &Path) -> Option<Self> {
+        if !fs.exists(path) {
+            return None;
+        }
+        let parsed = fs
+            .read(path)
+            .map_err(crate::error::NaviscopeError::from)
+            .and_then(|bytes| Ok(serde_json::from_slice::<Self>(&bytes)?));
+        match parsed {
+            Ok(journal) => Some(journal),
+            Err(e) => {
+                tracing::warn!(
+                    "Discarding unreadable build journal at {}: {}",
+                    path.display(),
+                    e
+                );
+                let _ = fs.remove_file(path);
+                None
+            }
+        }
+    }
+
+    pub(super) fn save(&self, fs: &dyn Fs, path: &Path) -> Result<()> {
+        let bytes = serde_json::to_vec(self)?;
+        let temp_path = path.with_extension("journal.tmp");
+        fs.write(&temp_path, &bytes)?;
+        fs.rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    pub(super) fn remove(fs: &dyn Fs, path: &Path) -> Result<()> {
+        if fs.exists(path) {
+            fs.remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Record a committed batch, returning its epoch.
+    ///
+    /// Only new or changed fingerprints are stamped with the new epoch.
+    pub(super) fn commit(&mut self, files: impl IntoIterator<Item = SourceFile>) -> u64 {
+        self.epoch += 1;
+        for file in files {
+            let unchanged = self.files.get(&file.path).is_some_and(|entry| {
+                entry.content_hash == file.content_hash && entry.last_modified == file.last_modified
+            });
+            if !unchanged {
+                self.files.insert(
+                    file.path,
+                    JournalEntry {
+                        content_hash: file.content_hash,
+                        last_modified: file.last_modified,
+                        epoch: self.epoch,
+                    },
+                );
+            }
+        }
+        self.epoch
+    }
+
+    pub(super) fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub(super) fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.files.keys()
+    }
+
+    /// Committed fingerprints, in the form the scanner uses to skip unchanged files
+    pub(super) fn fingerprints(&self) -> HashMap<PathBuf, SourceFile> {
+        self.files
+            .iter()
+            .map(|(path, entry)| {
+                (
+                    path.clone(),
+                    SourceFile::new(path.clone(), entry.content_hash, entry.last_modified),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::MemoryFs;
+
+    #[test]
+    fn commit_stamps_only_changed_files_and_round_trips() {
+        let fs = MemoryFs::new();
+        let path = BuildJournal::path_for(Path::new("/idx/0001.bin"));
+        let file = |name: &str, hash| SourceFile::new(PathBuf::from(name), hash, 10);
+
+        let mut journal = BuildJournal::default();
+        assert_eq!(journal.commit([file("/p/A.java", 1)]), 1);
+        assert_eq!(
+            journal.commit([file("/p/A.java", 1), file("/p/B.java", 2)]),
+            2
+        );
+        journal.save(&fs, &path).unwrap();
+
+        let loaded = BuildJournal::load(&fs, &path).unwrap();
+        assert_eq!(loaded.epoch(), 2);
+        assert_eq!(loaded.files[Path::new("/p/A.java")].epoch, 1);
+        assert_eq!(loaded.files[Path::new("/p/B.java")].epoch, 2);
+        assert_eq!(
+            loaded.fingerprints()[Path::new("/p/B.java")].content_hash,
+            2
+        );
+
+        fs.write(&path, b"not json").unwrap();
+        assert!(BuildJournal::load(&fs, &path).is_none());
+        assert!(!fs.exists(&path));
+    }
+}
@@ -1,80 +1,464 @@
+use super::journal::BuildJournal;
 use super::*;
+use crate::indexing::containment::rehome_packages;
+use crate::indexing::coverage::link_tested_classes;
+use crate::indexing::generated::link_generated_types;
+use crate::indexing::injection::link_injected_beans;
+use crate::indexing::scanner::ScannedFile;
+use crate::indexing::source_roots::SourceRootDetector;
+use crate::indexing::warnings::WarningCollector;
+use naviscope_api::graph::SourceRootOrigin;
+use naviscope_api::lifecycle::{BuildResume, DocumentEvent, IndexControl, IndexPhase};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tracing::Instrument;
+
+/// Source files compiled per committed batch during a journaled full build
+const JOURNAL_BATCH_SIZE: usize = 1000;
 
 impl NaviscopeEngine {
-    /// Load index from disk
+    /// Load index from disk. An unusable index is discarded unless it was
+    /// opened read-only.
+    ///
+    /// A sharded index opened read-only is read on demand: the load reads
+    /// its FQN tree and queries read the node shards they need.
     pub async fn load(&self) -> Result<bool> {
+        if self.read_only && self.load_partial().await? {
+            return Ok(true);
+        }
         let path = self.index_path.clone();
         let lang_caps = self.lang_caps.clone();
         let build_caps = self.build_caps.clone();
+        let fs = self.fs.clone();
+        let discard = !self.read_only;
 
         // Load in blocking pool
-        let graph_opt =
-            tokio::task::spawn_blocking(move || Self::load_from_disk(&path, lang_caps, build_caps))
-                .await
-                .map_err(|e| NaviscopeError::Internal(e.to_string()))??;
+        let graph_opt = tokio::task::spawn_blocking(move || {
+            Self::load_from_disk(fs.as_ref(), &path, lang_caps, build_caps, discard)
+        })
+        .await
+        .map_err(|e| NaviscopeError::Internal(e.to_string()))??;
 
         if let Some(graph) = graph_opt {
-            let mut lock = self.current.write().await;
-            *lock = Arc::new(graph);
+            self.writer.mark_persisted(Some(graph.instance_id())).await;
+            self.apply_graph_snapshot(graph).await;
             Ok(true)
         } else {
             Ok(false)
         }
     }
 
-    /// Save current graph to disk
-    pub async fn save(&self) -> Result<()> {
-        let graph = self.snapshot().await;
+    /// Open the index for on-demand loading, if it is sharded and usable.
+    async fn load_partial(&self) -> Result<bool> {
         let path = self.index_path.clone();
         let lang_caps = self.lang_caps.clone();
         let build_caps = self.build_caps.clone();
-
-        tokio::task::spawn_blocking(move || {
-            Self::save_to_disk(&graph, &path, lang_caps, build_caps)
+        let fs = self.fs.clone();
+        let opened = tokio::task::spawn_blocking(move || {
+            super::partial::PartialIndex::open(fs, &path, lang_caps, build_caps)
         })
         .await
-        .map_err(|e| NaviscopeError::Internal(e.to_string()))?
+        .map_err(|e| NaviscopeError::Internal(e.to_string()))?;
+
+        // An index that cannot be opened this way is left to the full load
+        let mut partial = match opened {
+            Ok(Some(partial)) => partial,
+            Ok(None) => return Ok(false),
+            Err(e) => {
+                tracing::debug!("Reading the whole index: {}", e);
+                return Ok(false);
+            }
+        };
+        let graph = partial.graph();
+        *self.partial.lock().unwrap() = (!partial.is_complete()).then_some(partial);
+        tracing::info!("Opened index at {}", self.index_path.display());
+        self.apply_graph_snapshot(graph).await;
+        Ok(true)
+    }
+
+    /// Save current graph to disk
+    pub async fn save(&self) -> Result<()> {
+        self.ensure_writable()?;
+        let graph = self.current.read().await.clone();
+        self.writer.save(graph).await?;
+        self.popularity.flush()
     }
 
     /// Rebuild the index from scratch
     pub async fn rebuild(&self) -> Result<()> {
-        {
-            let mut lock = self.current.write().await;
-            *lock = Arc::new(CodeGraph::empty());
+        self.rebuild_with_control(&IndexControl::default()).await
+    }
+
+    /// Rebuild the index from scratch, reporting progress through `control`.
+    ///
+    /// Cancellation is checked between files; a cancelled rebuild leaves the
+    /// last committed graph in place and returns `NaviscopeError::Cancelled`.
+    ///
+    /// Large builds commit in batches and journal the committed files. If a
+    /// journal from an interrupted build is found next to a readable index,
+    /// the build resumes from that index and skips files whose fingerprint
+    /// is unchanged.
+    pub async fn rebuild_with_control(&self, control: &IndexControl) -> Result<()> {
+        self.ensure_writable()?;
+        let _guard = self.index_lock.lock().await;
+        self.ensure_open()?;
+        let journal_path = BuildJournal::path_for(&self.index_path);
+        let (base_graph, mut journal) = match self.resumable_build().await? {
+            Some((graph, journal)) => {
+                tracing::info!(
+                    "Resuming interrupted index build at epoch {} ({} files already committed)",
+                    journal.epoch(),
+                    journal.len()
+                );
+                (graph, journal)
+            }
+            None => {
+                BuildJournal::remove(self.fs.as_ref(), &journal_path)?;
+                self.warnings.clear()?;
+                self.sources.clear();
+                (CodeGraph::empty(), BuildJournal::default())
+            }
+        };
+        // Not logged: the op log diffs the finished build against the last
+        // graph it recorded
+        *self.current.write().await = Arc::new(base_graph);
+        self.source_roots.write().await.clear();
+
+        control.report(IndexPhase::Scanning, 0, 0);
+        let mut paths = self.collect_project_paths().await?;
+
+        // Committed files that disappeared since the interruption are removed
+        let known: std::collections::HashSet<PathBuf> = paths.iter().cloned().collect();
+        let vanished: Vec<PathBuf> = journal
+            .paths()
+            .filter(|p| !known.contains(*p))
+            .cloned()
+            .collect();
+        paths.extend(vanished);
+
+        // Build files are always recompiled so the project model is complete
+        let build_caps = self.build_caps.clone();
+        let mut existing_metadata = journal.fingerprints();
+        existing_metadata
+            .retain(|p, _| !build_caps.iter().any(|caps| caps.matcher.supports_path(p)));
+
+        self.index_files(paths, existing_metadata, control, Some(&mut journal))
+            .await?;
+        // The journal may only go once the finished index is on disk
+        self.writer.flush().await?;
+        BuildJournal::remove(self.fs.as_ref(), &journal_path)
+    }
+
+    /// Stop taking updates, wait for the running one to commit and write the
+    /// index and the access counts, all within `deadline`.
+    ///
+    /// Updates asked for afterwards fail with `NaviscopeError::Cancelled`. An
+    /// update or write still running at the deadline is left to finish on
+    /// its own, and an error says so.
+    pub async fn shutdown(&self, deadline: Duration) -> Result<()> {
+        self.closing.store(true, Ordering::Release);
+        let until = tokio::time::Instant::now() + deadline;
+
+        let drained = tokio::time::timeout_at(until, self.index_lock.lock()).await;
+        let flushed = tokio::time::timeout_at(until, self.writer.flush()).await;
+        // Stops the background writer once it has nothing left to write; a
+        // late update still needs it to save its commit
+        if drained.is_ok() && flushed.is_ok() {
+            self.cancel_token.cancel();
         }
+        self.popularity.flush()?;
 
-        let project_root = self.project_root.clone();
-        let paths = tokio::task::spawn_blocking(move || Scanner::collect_paths(&project_root))
-            .await
-            .map_err(|e| NaviscopeError::Internal(e.to_string()))?;
+        match (drained, flushed) {
+            (Err(_), _) => Err(NaviscopeError::Internal(
+                "an index update was still running at the shutdown deadline".to_string(),
+            )),
+            (_, Err(_)) => Err(NaviscopeError::Internal(
+                "the index was still being written at the shutdown deadline".to_string(),
+            )),
+            (Ok(_guard), Ok(result)) => result,
+        }
+    }
 
-        self.update_files(paths).await
+    /// Progress of an interrupted full build that the next rebuild will resume.
+    pub async fn pending_resume(&self) -> Option<BuildResume> {
+        let journal_path = BuildJournal::path_for(&self.index_path);
+        let journal = BuildJournal::load(self.fs.as_ref(), &journal_path)?;
+        if journal.len() == 0 || !self.fs.exists(&self.index_path) {
+            return None;
+        }
+        Some(BuildResume {
+            committed_files: journal.len(),
+            epoch: journal.epoch(),
+        })
+    }
+
+    /// The journal and partial index of an interrupted build, if both are usable.
+    async fn resumable_build(&self) -> Result<Option<(CodeGraph, BuildJournal)>> {
+        let journal_path = BuildJournal::path_for(&self.index_path);
+        let Some(journal) = BuildJournal::load(self.fs.as_ref(), &journal_path) else {
+            return Ok(None);
+        };
+        if journal.len() == 0 {
+            return Ok(None);
+        }
+
+        let path = self.index_path.clone();
+        let lang_caps = self.lang_caps.clone();
+        let build_caps = self.build_caps.clone();
+        let fs = self.fs.clone();
+        let graph = tokio::task::spawn_blocking(move || {
+            Self::load_from_disk(fs.as_ref(), &path, lang_caps, build_caps, true)
+        })
+        .await
+        .map_err(|e| NaviscopeError::Internal(e.to_string()))??;
+
+        match graph {
+            Some(graph) => Ok(Some((graph, journal))),
+            None => {
+                tracing::warn!("Build journal has no matching index; starting a full build");
+                Ok(None)
+            }
+        }
     }
 
     /// Update specific files incrementally
     pub async fn update_files(&self, files: Vec<PathBuf>) -> Result<()> {
+        self.update_files_with_control(files, &IndexControl::default())
+            .await
+    }
+
+    /// Record an editor event for `path`, applying the updates held back
+    /// while it had unsaved edits once it is saved or closed.
+    pub async fn document_event(&self, path: &Path, event: DocumentEvent) -> Result<()> {
+        match self.documents.apply(path, event) {
+            Some(path) => self.update_files(vec![path]).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn update_files_with_control(
+        &self,
+        files: Vec<PathBuf>,
+        control: &IndexControl,
+    ) -> Result<()> {
+        self.ensure_writable()?;
+        self.sources.invalidate(files.iter().map(PathBuf::as_path));
+        let submitted = files.len();
+        let files = self.documents.hold_dirty(files);
+        self.metrics.deferred(submitted - files.len());
+        if files.is_empty() {
+            return Ok(());
+        }
+        let _guard = self.index_lock.lock().await;
+        self.ensure_open()?;
+        let ignore = self.config().index.ignore_matcher(&self.project_root)?;
+        let files: Vec<PathBuf> = files
+            .into_iter()
+            .filter(|path| {
+                // The matcher only accepts paths under the project root
+                !path.starts_with(&self.project_root)
+                    || !ignore.matched_path_or_any_parents(path, false).is_ignore()
+            })
+            .collect();
+        let existing_metadata = Self::collect_existing_metadata(&self.snapshot().await);
+        self.index_files(files, existing_metadata, control, None)
+            .await
+    }
+
+    /// Index `files` on top of the current graph, skipping those whose
+    /// fingerprint matches `existing_metadata`.
+    ///
+    /// With a `journal`, source files are committed in batches and recorded in it.
+    async fn index_files(
+        &self,
+        files: Vec<PathBuf>,
+        existing_metadata: std::collections::HashMap<PathBuf, crate::model::source::SourceFile>,
+        control: &IndexControl,
+        journal: Option<&mut BuildJournal>,
+    ) -> Result<()> {
+        self.metrics.runnable(files.len());
+        self.progress.start(*self.commits.borrow(), files.len());
+        let caller = control.clone();
+        let tracker = self.progress.clone();
+        let control = control.clone().with_progress(move |current| {
+            caller.report(current.phase, current.done, current.total);
+            tracker.report(current);
+        });
+        let started = std::time::Instant::now();
+        let result = self
+            .run_index_files(files, existing_metadata, &control, journal)
+            .await;
+        self.metrics.executed(started.elapsed());
+        self.progress.finish(result.is_ok());
+        result
+    }
+
+    async fn run_index_files(
+        &self,
+        files: Vec<PathBuf>,
+        existing_metadata: std::collections::HashMap<PathBuf, crate::model::source::SourceFile>,
+        control: &IndexControl,
+        journal: Option<&mut BuildJournal>,
+    ) -> Result<()> {
         let _ = self.scan_global_assets().await;
+        checkpoint(control)?;
         let base_graph = self.snapshot().await;
-        let existing_metadata = Self::collect_existing_metadata(&base_graph);
-        let (graph_after_build, source_paths, project_context) =
-            self.run_build_phase(base_graph, files, existing_metadata).await?;
-        let next_graph = self
-            .run_source_phase(graph_after_build, source_paths, project_context)
+        let mut changed: Vec<PathBuf> = files.iter().filter(|p| !p.exists()).cloned().collect();
+        let warnings = WarningCollector::default();
+        let (graph_after_build, mut source_paths, mut project_context) = self
+            .run_build_phase(
+                base_graph.clone(),
+                files,
+                existing_metadata.clone(),
+                &warnings,
+            )
+            .await?;
+        checkpoint(control)?;
+        // The symbols of the changed files may be gone or different now, so
+        // the files referencing them are resolved again. Files further away
+        // keep their edges to the symbols that are added back.
+        changed.extend(source_paths.iter().cloned());
+        for path in base_graph.dependent_files(&changed, Some(1)) {
+            if path.exists() && !source_paths.contains(&path) {
+                source_paths.push(path);
+            }
+        }
+        let graph_after_build = if project_context.path_to_module.is_empty() {
+            graph_after_build
+        } else {
+            // Modules may have been added or removed; move the packages
+            // instead of waiting for their files to be indexed again
+            let context = project_context.clone();
+            let (graph, moved) =
+                tokio::task::spawn_blocking(move || rehome_packages(graph_after_build, &context))
+                    .await
+                    .map_err(|e| NaviscopeError::Internal(e.to_string()))?;
+            if moved > 0 {
+                tracing::info!("Build topology changed; moved {} packages", moved);
+            }
+            graph
+        };
+        if !project_context.path_to_module.is_empty() {
+            // Build files were recompiled: new dependencies may satisfy references
+            // that previously had no target
+            for path in Self::files_with_unresolved_references(&graph_after_build) {
+                if !source_paths.contains(&path) {
+                    source_paths.push(path);
+                }
+            }
+        }
+        let source_roots = self
+            .detect_source_roots(&project_context, &source_paths)
             .await?;
+        project_context.generated_roots = source_roots
+            .iter()
+            .filter(|root| root.origin == SourceRootOrigin::Generated)
+            .map(|root| root.path.clone())
+            .collect();
+        // Generated roots are usually outside version control, so the scan of
+        // the project did not list their files
+        for path in self
+            .collect_generated_paths(&project_context.generated_roots, existing_metadata)
+            .await?
+        {
+            if !source_paths.contains(&path) {
+                source_paths.push(path);
+            }
+        }
+        let has_generated_roots = !project_context.generated_roots.is_empty();
+        let next_graph = match journal {
+            Some(journal) => {
+                self.run_journaled_source_phase(
+                    graph_after_build,
+                    source_paths,
+                    project_context,
+                    control,
+                    journal,
+                    &warnings,
+                )
+                .await?
+            }
+            None => {
+                self.run_source_phase(
+                    graph_after_build,
+                    source_paths,
+                    project_context,
+                    control,
+                    &warnings,
+                )
+                .await?
+            }
+        };
+        let next_graph = if has_generated_roots {
+            let conventions = self.naming_conventions();
+            tokio::task::spawn_blocking(move || link_generated_types(next_graph, &conventions))
+                .await
+                .map_err(|e| NaviscopeError::Internal(e.to_string()))?
+        } else {
+            next_graph
+        };
+        let next_graph = tokio::task::spawn_blocking(move || {
+            link_tested_classes(link_injected_beans(next_graph))
+        })
+        .await
+        .map_err(|e| NaviscopeError::Internal(e.to_string()))?;
+        let next_graph = if self.linker.is_empty() {
+            next_graph
+        } else {
+            let linker = self.linker.clone();
+            tokio::task::spawn_blocking(move || linker.link(next_graph))
+                .await
+                .map_err(|e| NaviscopeError::Internal(e.to_string()))?
+        };
+        checkpoint(control)?;
+        control.report(IndexPhase::Committing, 0, 1);
         self.apply_graph_snapshot(next_graph).await;
+        self.commit_warnings(&warnings);
         self.finalize_update().await?;
+        control.report(IndexPhase::Committing, 1, 1);
         Ok(())
     }
 
     /// Refresh index (detect changes and update)
     pub async fn refresh(&self) -> Result<()> {
-        let project_root = self.project_root.clone();
+        self.ensure_writable()?;
+        let paths = self.collect_project_paths().await?;
+        self.update_files(paths).await
+    }
 
-        let paths = tokio::task::spawn_blocking(move || Scanner::collect_paths(&project_root))
+    /// Indexable files under the project root, honoring `[index] ignore`.
+    async fn collect_project_paths(&self) -> Result<Vec<PathBuf>> {
+        let project_root = self.project_root.clone();
+        let ignore = self.config().index.ignore_matcher(&project_root)?;
+        tokio::task::spawn_blocking(move || Scanner::collect_paths(&project_root, &ignore))
             .await
-            .map_err(|e| NaviscopeError::Internal(e.to_string()))?;
+            .map_err(|e| NaviscopeError::Internal(e.to_string()))
+    }
 
-        self.update_files(paths).await
+    /// Changed source files under `roots`, honoring `[index] ignore`.
+    async fn collect_generated_paths(
+        &self,
+        roots: &[PathBuf],
+        existing_metadata: std::collections::HashMap<PathBuf, crate::model::source::SourceFile>,
+    ) -> Result<Vec<PathBuf>> {
+        if roots.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ignore = self.config().index.ignore_matcher(&self.project_root)?;
+        let lang_caps = self.lang_caps.clone();
+        let roots = roots.to_vec();
+        tokio::task::spawn_blocking(move || {
+            let paths: Vec<PathBuf> = roots
+                .iter()
+                .flat_map(|root| Scanner::collect_generated_paths(root, &ignore))
+                .filter(|p| lang_caps.iter().any(|c| c.matcher.supports_path(p)))
+                .collect();
+            Scanner::scan_files_iter(paths, &existing_metadata)
+                .map(|file| file.path().to_path_buf())
+                .collect()
+        })
+        .await
+        .map_err(|e| NaviscopeError::Internal(e.to_string()))
     }
 
     fn collect_existing_metadata(
@@ -90,14 +474,33 @@ impl NaviscopeEngine {
         existing_metadata
     }
 
+    /// Source files containing an edge to an unresolved placeholder node.
+    fn files_with_unresolved_references(graph: &CodeGraph) -> Vec<PathBuf> {
+        let topology = graph.topology();
+        let mut paths = std::collections::BTreeSet::new();
+        for idx in topology.node_indices() {
+            if topology[idx].status != naviscope_api::models::graph::ResolutionStatus::Unresolved {
+                continue;
+            }
+            for source in topology.neighbors_directed(idx, petgraph::Direction::Incoming) {
+                if let Some(location) = &topology[source].location {
+                    paths.insert(PathBuf::from(graph.symbols().resolve(&location.path.0)));
+                }
+            }
+        }
+        paths.into_iter().collect()
+    }
+
     async fn run_build_phase(
         &self,
         base_graph: CodeGraph,
         files: Vec<PathBuf>,
         existing_metadata: std::collections::HashMap<PathBuf, crate::model::source::SourceFile>,
+        warnings: &WarningCollector,
     ) -> Result<(CodeGraph, Vec<PathBuf>, naviscope_plugin::ProjectContext)> {
         let build_caps = self.build_caps.clone();
         let lang_caps = self.lang_caps.clone();
+        let warnings = warnings.clone();
         tokio::task::spawn_blocking(move || -> Result<_> {
             let mut manual_ops = Vec::new();
             let mut to_scan = Vec::new();
@@ -106,6 +509,7 @@ impl NaviscopeEngine {
                 if path.exists() {
                     to_scan.push(path);
                 } else {
+                    warnings.indexed([path.as_path()]);
                     manual_ops.push(GraphOp::RemovePath {
                         path: Arc::from(path.as_path()),
                     });
@@ -114,7 +518,17 @@ impl NaviscopeEngine {
 
             let mut build_files = Vec::new();
             let mut source_paths = Vec::new();
-            for file in Scanner::scan_files_iter(to_scan, &existing_metadata) {
+            for scanned in Scanner::check_files(to_scan, &existing_metadata) {
+                let file = match scanned {
+                    ScannedFile::Changed(file) => file,
+                    // Recorded so that the next refresh, e.g. after a checkout
+                    // or a restart, can tell the file is unchanged from its
+                    // modification time alone
+                    ScannedFile::Touched(metadata) => {
+                        manual_ops.push(GraphOp::UpdateFile { metadata });
+                        continue;
+                    }
+                };
                 if build_caps
                     .iter()
                     .any(|caps| caps.matcher.supports_path(file.path()))
@@ -125,11 +539,36 @@ impl NaviscopeEngine {
                 }
             }
 
+            if !build_files.is_empty() {
+                // The project model is only complete when every build file is compiled
+                // together, so unchanged build files are rescanned as well.
+                let changed: Vec<PathBuf> =
+                    build_files.iter().map(|f| f.path().to_path_buf()).collect();
+                let unchanged: Vec<PathBuf> = existing_metadata
+                    .keys()
+                    .filter(|p| !changed.contains(p) && p.exists())
+                    .filter(|p| build_caps.iter().any(|caps| caps.matcher.supports_path(p)))
+                    .cloned()
+                    .collect();
+                tracing::info!(
+                    "Build files changed ({}); rescanning {} build files",
+                    changed.len(),
+                    changed.len() + unchanged.len()
+                );
+                let no_metadata = std::collections::HashMap::new();
+                build_files.extend(Scanner::scan_files_iter(unchanged, &no_metadata));
+            }
+
             if build_files.is_empty() && source_paths.is_empty() && manual_ops.is_empty() {
-                return Ok((base_graph, Vec::new(), naviscope_plugin::ProjectContext::new()));
+                return Ok((
+                    base_graph,
+                    Vec::new(),
+                    naviscope_plugin::ProjectContext::new(),
+                ));
             }
 
             let compiler = crate::indexing::build::BuildCompiler::with_caps((*build_caps).clone());
+            warnings.indexed(build_files.iter().map(|f| f.path()));
 
             let mut project_context = naviscope_plugin::ProjectContext::new();
             let mut initial_ops = manual_ops;
@@ -143,7 +582,8 @@ impl NaviscopeEngine {
                 });
             }
 
-            let build_ops = compiler.compile_build_batch(&build_files, &mut project_context)?;
+            let build_ops =
+                compiler.compile_build_batch(&build_files, &mut project_context, &warnings)?;
             initial_ops.extend(build_ops);
 
             let mut builder = base_graph.to_builder();
@@ -160,9 +600,78 @@ impl NaviscopeEngine {
         .map_err(|e| NaviscopeError::Internal(e.to_string()))?
     }
 
-    async fn apply_graph_snapshot(&self, graph: CodeGraph) {
-        let mut lock = self.current.write().await;
-        *lock = Arc::new(graph);
+    /// Detect source roots for the given files and merge them with roots already known.
+    async fn detect_source_roots(
+        &self,
+        project_context: &naviscope_plugin::ProjectContext,
+        source_paths: &[PathBuf],
+    ) -> Result<Vec<SourceRoot>> {
+        let project_root = self.project_root.clone();
+        let config = self.config();
+        let lang_caps = self.lang_caps.clone();
+        let module_dirs: Vec<PathBuf> = project_context.path_to_module.keys().cloned().collect();
+        let files: Vec<PathBuf> = source_paths
+            .iter()
+            .filter(|p| lang_caps.iter().any(|c| c.matcher.supports_path(p)))
+            .cloned()
+            .collect();
+
+        let detected = tokio::task::spawn_blocking(move || {
+            SourceRootDetector::new(&project_root, &config.sources).detect(&module_dirs, &files)
+        })
+        .await
+        .map_err(|e| NaviscopeError::Internal(e.to_string()))?;
+
+        let mut roots = self.source_roots.write().await;
+        for root in detected {
+            if !roots.iter().any(|r| r.path == root.path) {
+                roots.push(root);
+            }
+        }
+        roots.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(roots.clone())
+    }
+
+    /// Source roots of the project, detecting them from the working tree if no
+    /// indexing run has populated them yet (e.g. after loading from disk).
+    pub async fn source_roots(&self) -> Result<Vec<SourceRoot>> {
+        {
+            let roots = self.source_roots.read().await;
+            if !roots.is_empty() {
+                return Ok(roots.clone());
+            }
+        }
+
+        let paths = self.collect_project_paths().await?;
+        self.detect_source_roots(&naviscope_plugin::ProjectContext::new(), &paths)
+            .await
+    }
+
+    /// Commit `graph` as the current snapshot, logging its node changes.
+    pub(super) async fn apply_graph_snapshot(&self, graph: CodeGraph) {
+        let epoch = graph.instance_id();
+        let span = tracing::info_span!("commit", epoch, nodes = graph.node_count());
+        async {
+            let graph = Arc::new(graph);
+            if let Some(op_log) = &self.op_log {
+                op_log.record(&graph);
+            }
+            *self.current.write().await = graph;
+            self.metrics.committed();
+            self.commits.send_replace(epoch);
+        }
+        .instrument(span)
+        .await
+    }
+
+    /// Wait until a snapshot other than `epoch` is committed; returns its epoch.
+    pub async fn wait_for_commit(&self, epoch: u64) -> u64 {
+        let mut commits = self.commits.subscribe();
+        // The engine owns the sender, so the channel stays open while it lives
+        commits
+            .wait_for(|current| *current != epoch)
+            .await
+            .map_or(epoch, |current| *current)
     }
 
     async fn run_source_phase(
@@ -170,18 +679,19 @@ impl NaviscopeEngine {
         base_graph: CodeGraph,
         source_paths: Vec<PathBuf>,
         project_context: naviscope_plugin::ProjectContext,
+        control: &IndexControl,
+        warnings: &WarningCollector,
     ) -> Result<CodeGraph> {
         if source_paths.is_empty() {
             return Ok(base_graph);
         }
+        warnings.indexed(source_paths.iter().map(PathBuf::as_path));
 
         let routes = self.global_asset_routes();
-        let source_files = tokio::task::spawn_blocking(move || {
-            let existing = std::collections::HashMap::new();
-            Scanner::scan_files_iter(source_paths, &existing).collect::<Vec<_>>()
-        })
-        .await
-        .map_err(|e| NaviscopeError::Internal(e.to_string()))?;
+        let source_files = self
+            .source_compiler
+            .scan_source_files(source_paths, control.clone())
+            .await?;
 
         if source_files.is_empty() {
             return Ok(base_graph);
@@ -197,11 +707,239 @@ impl NaviscopeEngine {
                 self.naming_conventions(),
                 self.lang_caps_arc(),
                 self.stub_cache_arc(),
+                control.clone(),
+                warnings.clone(),
             )
             .await
     }
 
+    /// Compile sources in batches of `JOURNAL_BATCH_SIZE`, saving the graph
+    /// and journaling its files after every batch but the last, which the
+    /// caller commits.
+    async fn run_journaled_source_phase(
+        &self,
+        base_graph: CodeGraph,
+        source_paths: Vec<PathBuf>,
+        project_context: naviscope_plugin::ProjectContext,
+        control: &IndexControl,
+        journal: &mut BuildJournal,
+        warnings: &WarningCollector,
+    ) -> Result<CodeGraph> {
+        let journal_path = BuildJournal::path_for(&self.index_path);
+        let batches: Vec<Vec<PathBuf>> = source_paths
+            .chunks(JOURNAL_BATCH_SIZE)
+            .map(<[PathBuf]>::to_vec)
+            .collect();
+        let batch_count = batches.len();
+
+        let mut graph = base_graph;
+        for (i, batch) in batches.into_iter().enumerate() {
+            let batch_len = batch.len();
+            graph = self
+                .run_source_phase(graph, batch, project_context.clone(), control, warnings)
+                .await?;
+            checkpoint(control)?;
+            if i + 1 < batch_count {
+                self.apply_graph_snapshot(graph.clone()).await;
+                self.save().await?;
+                self.commit_warnings(warnings);
+                let epoch = journal.commit(Self::collect_existing_metadata(&graph).into_values());
+                journal.save(self.fs.as_ref(), &journal_path)?;
+                tracing::debug!("Committed index epoch {} ({} files)", epoch, journal.len());
+                self.progress.committed(batch_len);
+            }
+        }
+
+        if batch_count > 1 {
+            // Earlier batches could not see declarations from later ones
+            let unresolved = Self::files_with_unresolved_references(&graph);
+            graph = self
+                .run_source_phase(graph, unresolved, project_context, control, warnings)
+                .await?;
+        }
+        Ok(graph)
+    }
+
+    /// Store the warnings collected since the last commit. Failing to do so
+    /// does not fail the run.
+    fn commit_warnings(&self, warnings: &WarningCollector) {
+        if let Err(e) = self.warnings.commit(warnings.take()) {
+            tracing::warn!("Failed to store index warnings: {}", e);
+        }
+    }
+
+    /// Queue the committed snapshot for the background writer, keeping disk
+    /// speed out of commit latency.
     async fn finalize_update(&self) -> Result<()> {
-        self.save().await
+        let graph = self.current.read().await.clone();
+        self.writer.schedule(graph, &self.cancel_token);
+        Ok(())
+    }
+}
+
+fn checkpoint(control: &IndexControl) -> Result<()> {
+    if control.is_cancelled() {
+        return Err(NaviscopeError::Cancelled);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::builder::CodeGraphBuilder;
+    use crate::test_support::node_at;
+    use naviscope_api::models::graph::{EdgeType, NodeKind};
+
+    #[test]
+    fn test_files_with_unresolved_references() {
+        let mut builder = CodeGraphBuilder::new();
+        for (fqn, path) in [("com.app.A", "/p/A.java"), ("com.app.B", "/p/B.java")] {
+            builder.add_node(node_at(fqn, NodeKind::Class, path));
+        }
+        builder
+            .apply_ops(vec![GraphOp::AddEdge {
+                from_id: "com.app.A".into(),
+                to_id: "org.lib.Missing".into(),
+                edge: crate::model::GraphEdge::new(EdgeType::TypedAs),
+            }])
+            .unwrap();
+        let graph = builder.build();
+
+        assert_eq!(
+            NaviscopeEngine::files_with_unresolved_references(&graph),
+            vec![PathBuf::from("/p/A.java")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_reports_phases_and_honors_cancellation() {
+        let dir = tempfile::tempdir().unwrap();
+        let fs = Arc::new(crate::platform::MemoryFs::new());
+        let engine = NaviscopeEngine::builder(dir.path().to_path_buf())
+            .with_fs(fs.clone())
+            .build();
+
+        let phases = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = phases.clone();
+        let control = IndexControl::new().with_progress(move |p| seen.lock().unwrap().push(p));
+        engine.rebuild_with_control(&control).await.unwrap();
+        let last = phases.lock().unwrap().last().copied();
+        assert_eq!(
+            last.map(|p| (p.phase, p.percentage())),
+            Some((IndexPhase::Committing, 100))
+        );
+        assert_eq!(fs.paths().len(), 2, "manifest and core shard");
+
+        phases.lock().unwrap().clear();
+        control.cancel();
+        let result = engine.rebuild_with_control(&control).await;
+        assert!(matches!(result, Err(NaviscopeError::Cancelled)));
+        assert!(
+            !phases
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|p| p.phase == IndexPhase::Committing)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_resumes_interrupted_build_and_clears_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let fs = Arc::new(crate::platform::MemoryFs::new());
+        let engine = NaviscopeEngine::builder(dir.path().to_path_buf())
+            .with_fs(fs.clone())
+            .build();
+        assert_eq!(engine.pending_resume().await, None);
+
+        // Simulate a build that died after committing one batch
+        engine.save().await.unwrap();
+        let journal_path = BuildJournal::path_for(&engine.index_path);
+        let mut journal = BuildJournal::default();
+        journal.commit([crate::model::source::SourceFile::new(
+            dir.path().join("Gone.java"),
+            1,
+            1,
+        )]);
+        journal.save(fs.as_ref(), &journal_path).unwrap();
+        assert_eq!(
+            engine.pending_resume().await,
+            Some(BuildResume {
+                committed_files: 1,
+                epoch: 1
+            })
+        );
+
+        engine.rebuild().await.unwrap();
+        assert_eq!(engine.pending_resume().await, None);
+        assert!(!fs.exists(&journal_path));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_commit_returns_next_epoch() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = Arc::new(
+            NaviscopeEngine::builder(dir.path().to_path_buf())
+                .with_fs(Arc::new(crate::platform::MemoryFs::new()))
+                .build(),
+        );
+        let epoch = engine.snapshot().await.instance_id();
+
+        let waiter = tokio::spawn({
+            let engine = engine.clone();
+            async move { engine.wait_for_commit(epoch).await }
+        });
+        engine.rebuild().await.unwrap();
+        let committed = waiter.await.unwrap();
+        assert_ne!(committed, epoch);
+        assert_eq!(committed, engine.snapshot().await.instance_id());
+        // Already past `epoch`: returns without waiting
+        assert_eq!(engine.wait_for_commit(epoch).await, committed);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_running_update_and_refuses_new_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = NaviscopeEngine::builder(dir.path().to_path_buf())
+            .with_fs(Arc::new(crate::platform::MemoryFs::new()))
+            .build();
+        engine.rebuild().await.unwrap();
+
+        // An update holding the index lock past the deadline
+        let running = engine.index_lock.lock().await;
+        let result = engine.shutdown(Duration::from_millis(20)).await;
+        assert!(matches!(result, Err(NaviscopeError::Internal(_))));
+        // The writer stays up to save what the update commits
+        assert!(!engine.cancel_token.is_cancelled());
+        drop(running);
+
+        engine.shutdown(Duration::from_secs(5)).await.unwrap();
+        assert!(engine.cancel_token.is_cancelled());
+        let update = engine.update_files(vec![dir.path().join("A.java")]).await;
+        assert!(matches!(update, Err(NaviscopeError::Cancelled)));
+        assert!(matches!(
+            engine.rebuild().await,
+            Err(NaviscopeError::Cancelled)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_reports_progress_under_the_epoch_it_started_from() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = NaviscopeEngine::builder(dir.path().to_path_buf())
+            .with_fs(Arc::new(crate::platform::MemoryFs::new()))
+            .build();
+        let epoch = engine.snapshot().await.instance_id();
+        let mut updates = engine.subscribe_progress();
+        engine.rebuild().await.unwrap();
+
+        let progress = engine.epoch_progress(epoch).unwrap();
+        assert!(progress.finished);
+        assert_eq!(progress.percent, 100);
+        assert_eq!(progress.committed, progress.submitted);
+        assert_eq!(*updates.borrow_and_update(), Some(progress));
+        let committed = engine.wait_for_commit(epoch).await;
+        assert!(engine.epoch_progress(committed).is_none());
     }
 }
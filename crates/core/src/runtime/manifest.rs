@@ -0,0 +1,135 @@
+//! Manifest of a sharded index
+//!
+//! The manifest sits at the index path and names the shard files, which live
+//! in a sibling `.shards` directory. Shard files are named after a hash of
+//! their contents, so a save only writes shards whose contents changed and
+//! the manifest switches to the new set in a single rename.
+//!
+//! Besides the files, the manifest records the FQN ids each node shard is
+//! entered by and the shards it has edges with. Together with the FQN tree
+//! this locates the shards a symbol needs without reading the others.
+
+use crate::error::{NaviscopeError, Result};
+use crate::platform::Fs;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Leading bytes of a manifest; older indices are a single compressed graph,
+/// and manifests of earlier layouts are rebuilt
+const MAGIC: &[u8] = b"NVSCSHD2";
+
+#[derive(Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(super) struct IndexManifest {
+    /// Lineage and epoch of the saved graph, see `ShardLog`
+    pub(super) lineage: u64,
+    pub(super) epoch: u64,
+    /// File name of the core shard
+    pub(super) core: String,
+    /// Number of interned strings, and file names of their chunks
+    pub(super) string_count: usize,
+    pub(super) strings: Vec<String>,
+    /// Lowest FQN id missing from the tree, and file names of its chunks
+    pub(super) fqn_count: u32,
+    pub(super) fqns: Vec<String>,
+    /// Source directory -> its node shard
+    pub(super) shards: BTreeMap<String, ShardEntry>,
+}
+
+/// A node shard named by the manifest
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(super) struct ShardEntry {
+    /// File name of the shard
+    pub(super) file: String,
+    /// FQN ids of the shard's nodes whose parent is not in the shard
+    pub(super) roots: Vec<u32>,
+    /// Keys of the shards connected to this one by edges
+    pub(super) links: Vec<String>,
+}
+
+impl IndexManifest {
+    /// Directory holding the shards of the index at `index_path`
+    pub(super) fn shard_dir(index_path: &Path) -> PathBuf {
+        index_path.with_extension("shards")
+    }
+
+    /// Content-addressed file name for an encoded shard
+    pub(super) fn shard_file_name(bytes: &[u8]) -> String {
+        format!("{:016x}.shard", xxhash_rust::xxh3::xxh3_64(bytes))
+    }
+
+    pub(super) fn is_manifest(bytes: &[u8]) -> bool {
+        bytes.starts_with(MAGIC)
+    }
+
+    pub(super) fn decode(bytes: &[u8]) -> Result<Self> {
+        let body = bytes
+            .strip_prefix(MAGIC)
+            .ok_or_else(|| NaviscopeError::Parsing("not an index manifest".to_string()))?;
+        rmp_serde::from_slice(body)
+            .map_err(|e| NaviscopeError::Internal(format!("MSGPACK error: {}", e)))
+    }
+
+    /// Manifest currently stored at `path`, if it is a sharded index
+    pub(super) fn read(fs: &dyn Fs, path: &Path) -> Option<Self> {
+        let bytes = fs.read(path).ok()?;
+        if !Self::is_manifest(&bytes) {
+            return None;
+        }
+        Self::decode(&bytes).ok()
+    }
+
+    pub(super) fn save(&self, fs: &dyn Fs, path: &Path) -> Result<()> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(
+            rmp_serde::to_vec(self)
+                .map_err(|e| NaviscopeError::Internal(format!("MSGPACK error: {}", e)))?,
+        );
+        let temp_path = path.with_extension("tmp");
+        fs.write(&temp_path, &bytes)?;
+        fs.rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    /// Every shard file the manifest refers to
+    pub(super) fn files(&self) -> BTreeSet<&str> {
+        std::iter::once(self.core.as_str())
+            .chain(self.strings.iter().map(String::as_str))
+            .chain(self.fqns.iter().map(String::as_str))
+            .chain(self.shards.values().map(|entry| entry.file.as_str()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::MemoryFs;
+
+    #[test]
+    fn manifest_round_trips_and_rejects_legacy_indices() {
+        let fs = MemoryFs::new();
+        let path = Path::new("/idx/0001.bin");
+        let manifest = IndexManifest {
+            core: IndexManifest::shard_file_name(b"core"),
+            shards: BTreeMap::from([(
+                "/p/a".to_string(),
+                ShardEntry {
+                    file: "a.shard".to_string(),
+                    roots: vec![1],
+                    links: vec![String::new()],
+                },
+            )]),
+            ..Default::default()
+        };
+        manifest.save(&fs, path).unwrap();
+
+        assert_eq!(IndexManifest::read(&fs, path), Some(manifest));
+        assert_eq!(
+            IndexManifest::shard_dir(path),
+            PathBuf::from("/idx/0001.shards")
+        );
+
+        fs.write(path, b"\x28\xb5\x2f\xfd legacy").unwrap();
+        assert_eq!(IndexManifest::read(&fs, path), None);
+    }
+}
@@ -0,0 +1,119 @@
+//! Counters of index updates
+//!
+//! Recorded as updates run and reported by `runtime_metrics`: files handed
+//! to updates, updates held back by unsaved edits, committed snapshots and
+//! how long each update took.
+
+use naviscope_api::lifecycle::{LatencyBucket, LatencyHistogram, RuntimeMetrics};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds of the latency buckets, in milliseconds
+const LATENCY_BOUNDS_MS: [u64; 12] = [
+    10, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 30_000, 60_000, 300_000,
+];
+
+#[derive(Debug, Default)]
+pub(super) struct RuntimeCounters {
+    runnable: AtomicU64,
+    deferred: AtomicU64,
+    committed: AtomicU64,
+    execute: Mutex<Latencies>,
+}
+
+#[derive(Debug, Default)]
+struct Latencies {
+    /// Observations at or below each of `LATENCY_BOUNDS_MS`
+    buckets: [u64; LATENCY_BOUNDS_MS.len()],
+    count: u64,
+    sum_ms: u64,
+}
+
+impl RuntimeCounters {
+    /// `files` were handed to an update
+    pub(super) fn runnable(&self, files: usize) {
+        self.runnable.fetch_add(files as u64, Ordering::Relaxed);
+    }
+
+    /// Updates of `files` were held back
+    pub(super) fn deferred(&self, files: usize) {
+        self.deferred.fetch_add(files as u64, Ordering::Relaxed);
+    }
+
+    pub(super) fn committed(&self) {
+        self.committed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// An update finished after `latency`
+    pub(super) fn executed(&self, latency: Duration) {
+        let ms = latency.as_millis() as u64;
+        let mut execute = self.execute.lock().unwrap();
+        for (bound, count) in LATENCY_BOUNDS_MS.iter().zip(execute.buckets.iter_mut()) {
+            if ms <= *bound {
+                *count += 1;
+            }
+        }
+        execute.count += 1;
+        execute.sum_ms += ms;
+    }
+
+    pub(super) fn snapshot(&self) -> RuntimeMetrics {
+        let execute = self.execute.lock().unwrap();
+        RuntimeMetrics {
+            runnable: self.runnable.load(Ordering::Relaxed),
+            deferred: self.deferred.load(Ordering::Relaxed),
+            committed: self.committed.load(Ordering::Relaxed),
+            execute_latency: LatencyHistogram {
+                buckets: LATENCY_BOUNDS_MS
+                    .iter()
+                    .zip(execute.buckets)
+                    .map(|(&le_ms, count)| LatencyBucket { le_ms, count })
+                    .collect(),
+                count: execute.count,
+                sum_ms: execute.sum_ms,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latencies_fill_cumulative_buckets() {
+        let counters = RuntimeCounters::default();
+        counters.runnable(3);
+        counters.deferred(1);
+        counters.committed();
+        counters.executed(Duration::from_millis(40));
+        counters.executed(Duration::from_millis(700));
+        counters.executed(Duration::from_secs(600));
+
+        let metrics = counters.snapshot();
+        assert_eq!(
+            (metrics.runnable, metrics.deferred, metrics.committed),
+            (3, 1, 1)
+        );
+        let latency = &metrics.execute_latency;
+        let count_at = |le_ms| {
+            latency
+                .buckets
+                .iter()
+                .find(|b| b.le_ms == le_ms)
+                .unwrap()
+                .count
+        };
+        assert_eq!(count_at(10), 0);
+        assert_eq!(count_at(50), 1);
+        assert_eq!(count_at(1_000), 2);
+        assert_eq!(count_at(300_000), 2);
+        assert_eq!((latency.count, latency.sum_ms), (3, 600_740));
+
+        let text = metrics.to_prometheus();
+        assert!(text.contains("naviscope_commits_total 1\n"));
+        assert!(text.contains("naviscope_execute_duration_seconds_bucket{le=\"0.05\"} 1\n"));
+        assert!(text.contains("naviscope_execute_duration_seconds_bucket{le=\"+Inf\"} 3\n"));
+    }
+}
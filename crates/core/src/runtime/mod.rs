@@ -1,13 +1,22 @@
 //! Core indexing engine with MVCC support
 
+use crate::advisory::{Advisories, OfflineAdvisor};
+use crate::asset::fetcher::SourceFetcher;
 use crate::asset::service::AssetStubService;
+use crate::config::{Profile, ProjectConfig};
 use crate::error::{NaviscopeError, Result};
-use crate::indexing::source::SourceCompiler;
-use crate::indexing::scanner::Scanner;
+use crate::history::GitHistory;
 use crate::indexing::StubRequest;
+use crate::indexing::scanner::Scanner;
+use crate::indexing::source::{IntakeTopic, SourceCompiler};
+use crate::linker::Linker;
 use crate::model::{CodeGraph, GraphOp};
+use crate::platform::{Clock, Fs, RealFs, SystemClock};
+use naviscope_api::graph::{SourceRoot, Toolchain};
+use naviscope_api::models::SymbolLocation;
 use naviscope_plugin::{
-    AssetDiscoverer, AssetIndexer, AssetSourceLocator, BuildCaps, LanguageCaps, NamingConvention,
+    AssetDiscoverer, AssetIndexer, AssetSource, AssetSourceLocator, BuildCaps,
+    CrossLanguageResolver, DependencyAdvisor, LanguageCaps, NamingConvention,
 };
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -15,10 +24,33 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use xxhash_rust::xxh3::xxh3_64;
 
+mod audit;
+mod bundle;
+mod documents;
+mod export;
+mod flow;
+mod journal;
 mod lifecycle;
+mod manifest;
+mod metrics;
+mod notes;
+mod op_log;
+mod partial;
+mod persist;
+mod popularity;
+mod progress;
+mod snapshot;
+mod sources;
 mod storage;
+mod trees;
+mod warnings;
 mod watch;
 
+pub use documents::OpenDocuments;
+pub use op_log::{OpEvent, OpKind, OpLogTarget, change_summary};
+pub use sources::{SourceStore, SourceText};
+pub use trees::TreeCache;
+
 pub const DEFAULT_INDEX_DIR: &str = ".naviscope/indices";
 
 /// Naviscope indexing engine
@@ -55,12 +87,95 @@ pub struct NaviscopeEngine {
 
     /// Source compiler facade that owns source runtime lifecycle.
     source_compiler: Arc<SourceCompiler>,
+
+    /// Project configuration from `naviscope.toml`, replaced on reload
+    config: std::sync::RwLock<Arc<ProjectConfig>>,
+
+    /// Source roots detected during the latest indexing runs
+    source_roots: Arc<RwLock<Vec<SourceRoot>>>,
+
+    /// Filesystem used for index storage
+    fs: Arc<dyn Fs>,
+
+    /// Time source for debouncing and expiry decisions
+    clock: Arc<dyn Clock>,
+
+    /// Parsed trees of documents served to position-based requests
+    trees: TreeCache,
+
+    /// Decoded contents of the source files features read
+    sources: SourceStore,
+
+    /// Documents open in an editor, whose unsaved edits hold back updates
+    documents: OpenDocuments,
+
+    /// Serializes rebuilds and incremental updates, which each start from
+    /// the current snapshot and would otherwise overwrite one another
+    index_lock: tokio::sync::Mutex<()>,
+
+    /// NDJSON log of committed node changes, if `[index] op_log` is set
+    op_log: Option<op_log::OpLog>,
+
+    /// Batch size of watcher updates, adapted to their latency
+    watch_flow: std::sync::Mutex<flow::AdaptiveBatch>,
+
+    /// Counters and latencies of index updates
+    metrics: metrics::RuntimeCounters,
+
+    /// Set by `shutdown`; updates are refused from then on
+    closing: std::sync::atomic::AtomicBool,
+
+    /// Epoch of the latest committed snapshot, for waiting on commits
+    commits: tokio::sync::watch::Sender<u64>,
+
+    /// Progress of the latest update
+    progress: Arc<progress::EpochTracker>,
+
+    /// Writes committed snapshots to `index_path` in the background
+    writer: Arc<persist::IndexWriter>,
+
+    /// Notes attached to nodes, stored next to the index
+    notes: notes::NoteStore,
+
+    /// Access counts of nodes, stored next to the index
+    popularity: popularity::PopularityStore,
+
+    /// Warnings of the indexed files, stored next to the index
+    warnings: warnings::WarningStore,
+
+    /// Tool calls of MCP clients, appended next to the index
+    audit: audit::AuditLog,
+
+    /// Toolchains whose standard libraries the asset service discovers
+    toolchains: Vec<Toolchain>,
+
+    /// Cross-language resolvers, run over the graph after each index update
+    linker: Arc<Linker>,
+
+    /// Vulnerability and license advisors of dependency nodes
+    advisories: Arc<Advisories>,
+
+    /// Git history of the project, when `[git] history` is set
+    history: Option<Arc<GitHistory>>,
+
+    /// Whether the index was opened read-only; nothing is written next to it
+    read_only: bool,
+
+    /// Shards of a read-only index not read yet, see `partial`
+    partial: Arc<std::sync::Mutex<Option<partial::PartialIndex>>>,
 }
 
 pub struct NaviscopeEngineBuilder {
     project_root: PathBuf,
     build_caps: Vec<BuildCaps>,
     lang_caps: Vec<LanguageCaps>,
+    fs: Arc<dyn Fs>,
+    clock: Arc<dyn Clock>,
+    config: Option<ProjectConfig>,
+    profile: Profile,
+    link_resolvers: Vec<Arc<dyn CrossLanguageResolver>>,
+    advisors: Vec<Arc<dyn DependencyAdvisor>>,
+    read_only_index: Option<PathBuf>,
 }
 
 impl NaviscopeEngineBuilder {
@@ -69,6 +184,13 @@ impl NaviscopeEngineBuilder {
             project_root,
             build_caps: Vec::new(),
             lang_caps: Vec::new(),
+            fs: Arc::new(RealFs),
+            clock: Arc::new(SystemClock),
+            config: None,
+            profile: Profile::default(),
+            link_resolvers: Vec::new(),
+            advisors: Vec::new(),
+            read_only_index: None,
         }
     }
 
@@ -82,13 +204,74 @@ impl NaviscopeEngineBuilder {
         self
     }
 
-    pub fn build(self) -> NaviscopeEngine {
+    /// Replace the filesystem used for index storage (defaults to the host filesystem).
+    pub fn with_fs(mut self, fs: Arc<dyn Fs>) -> Self {
+        self.fs = fs;
+        self
+    }
+
+    /// Replace the time source (defaults to the system clock).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Use `config` instead of reading `naviscope.toml` from the project root.
+    pub fn with_config(mut self, config: ProjectConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Tune indexing for the component hosting the engine (defaults to `Profile::Cli`).
+    pub fn with_profile(mut self, profile: Profile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Link symbols across languages with `resolver` after each index update.
+    pub fn with_link_resolver(mut self, resolver: Arc<dyn CrossLanguageResolver>) -> Self {
+        self.link_resolvers.push(resolver);
+        self
+    }
+
+    /// Attach the advice of `advisor` to rendered dependency nodes, next to
+    /// that of the files in `[advisories] files`.
+    pub fn with_dependency_advisor(mut self, advisor: Arc<dyn DependencyAdvisor>) -> Self {
+        self.advisors.push(advisor);
+        self
+    }
+
+    /// Serve the prebuilt index at `index_path` read-only instead of the
+    /// project's own: it is never rebuilt, updated, discarded or watched,
+    /// and neither stubs nor notes, hits or audit entries are written.
+    pub fn with_read_only_index(mut self, index_path: PathBuf) -> Self {
+        self.read_only_index = Some(index_path);
+        self
+    }
+
+    pub fn build(mut self) -> NaviscopeEngine {
         let canonical_root = self
             .project_root
             .canonicalize()
             .unwrap_or_else(|_| self.project_root.clone());
-        let index_path = NaviscopeEngine::compute_index_path(&canonical_root);
+        let config = self.config.take().unwrap_or_else(|| {
+            ProjectConfig::load(&canonical_root).unwrap_or_else(|e| {
+                tracing::warn!("Failed to load project config: {}", e);
+                ProjectConfig::default()
+            })
+        });
+        let read_only = self.read_only_index.is_some();
+        let index_path = self
+            .read_only_index
+            .take()
+            .unwrap_or_else(|| NaviscopeEngine::compute_index_path(&canonical_root, &config));
         let cancel_token = tokio_util::sync::CancellationToken::new();
+
+        // Drop plugins not listed in `[plugins] enabled`
+        self.lang_caps
+            .retain(|caps| config.plugins.is_enabled(caps.language.as_str()));
+        self.build_caps
+            .retain(|caps| config.plugins.is_enabled(caps.build_tool.as_str()));
         // Initialize global cache once
         let stub_cache = Arc::new(crate::cache::GlobalStubCache::at_default_location());
 
@@ -110,9 +293,42 @@ impl NaviscopeEngineBuilder {
         // Collect asset discoverers from all plugins
         let mut discoverers: Vec<Box<dyn AssetDiscoverer>> = Vec::new();
 
-        // From language plugins (e.g., JdkDiscoverer from Java)
+        // From language plugins (e.g., JdkDiscoverer from Java), preferring
+        // the configured toolchain over the installed one the project targets
+        let mut toolchains = Vec::new();
         for caps in &self.lang_caps {
-            if let Some(d) = caps.asset.global_asset_discoverer() {
+            let toolchain = config.jdk.home.as_deref().and_then(|home| {
+                let discoverer = caps.asset.toolchain_asset_discoverer(home);
+                if discoverer.is_none() {
+                    tracing::warn!(
+                        "No {} toolchain found at {}; falling back to auto-detection",
+                        caps.language,
+                        home.display()
+                    );
+                }
+                discoverer
+            });
+            let discoverer = toolchain
+                .or_else(|| {
+                    caps.asset.project_toolchain_discoverer(
+                        &canonical_root,
+                        config.jdk.version.as_deref(),
+                    )
+                })
+                .or_else(|| caps.asset.global_asset_discoverer());
+            if let Some(d) = discoverer {
+                for entry in d.discover() {
+                    if let AssetSource::Jdk { version, path } = entry.source {
+                        let toolchain = Toolchain {
+                            language: caps.language.to_string(),
+                            version,
+                            home: path,
+                        };
+                        if !toolchains.contains(&toolchain) {
+                            toolchains.push(toolchain);
+                        }
+                    }
+                }
                 discoverers.push(d);
             }
         }
@@ -151,32 +367,123 @@ impl NaviscopeEngineBuilder {
         }
 
         // Create asset service with discoverers from plugins
-        let asset_service = if !indexers.is_empty() && !discoverers.is_empty() {
-            Some(Arc::new(AssetStubService::new(
+        let asset_service = if !read_only && !indexers.is_empty() && !discoverers.is_empty() {
+            let service = AssetStubService::new(
                 discoverers,
                 indexers,
                 vec![], // Generators will be added later
                 source_locators,
-            )))
+            );
+            let service = if config.assets.download_sources {
+                service.with_source_fetcher(Arc::new(SourceFetcher::new(
+                    config.assets.repositories.clone(),
+                    SourceFetcher::default_cache_dir(),
+                )))
+            } else {
+                service
+            };
+            Some(Arc::new(service))
         } else {
             None
         };
 
         let build_caps = Arc::new(self.build_caps);
         let lang_caps = Arc::new(self.lang_caps);
-        let source_compiler = Arc::new(SourceCompiler::new());
+        let tuning = config.tuning(self.profile);
+        let source_compiler = Arc::new(SourceCompiler::with_tuning(&tuning));
+        let watch_flow = flow::AdaptiveBatch::new(
+            tuning.watch_batch_min,
+            tuning.watch_batch_max,
+            tuning.watch_target_latency,
+        );
+        let conventions = Arc::new(conventions);
+
+        let op_log = config
+            .index
+            .op_log
+            .as_deref()
+            .filter(|_| !read_only)
+            .and_then(|target| {
+                let target = OpLogTarget::parse(target, &canonical_root);
+                match op_log::OpLog::open(&target, conventions.clone()) {
+                    Ok(log) => {
+                        tracing::info!("Logging graph changes to {}", target);
+                        Some(log)
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to open op log {}: {}", target, e);
+                        None
+                    }
+                }
+            });
+
+        let graph = Arc::new(CodeGraph::empty());
+        let (commits, _) = tokio::sync::watch::channel(graph.instance_id());
+        let writer = Arc::new(persist::IndexWriter::new(
+            self.fs.clone(),
+            index_path.clone(),
+            lang_caps.clone(),
+            build_caps.clone(),
+        ));
+        let notes = notes::NoteStore::new(self.fs.clone(), self.clock.clone(), &index_path);
+        let popularity = popularity::PopularityStore::new(self.fs.clone(), &index_path);
+        let warnings = warnings::WarningStore::new(self.fs.clone(), &index_path);
+        let audit = audit::AuditLog::new(self.fs.clone(), &index_path);
+
+        if !config.advisories.files.is_empty() {
+            let files: Vec<PathBuf> = config
+                .advisories
+                .files
+                .iter()
+                .map(|file| canonical_root.join(file))
+                .collect();
+            match OfflineAdvisor::load(&files) {
+                Ok(advisor) => self.advisors.insert(0, Arc::new(advisor)),
+                Err(e) => tracing::warn!("Failed to load advisories: {}", e),
+            }
+        }
+
+        let history = config
+            .git
+            .history
+            .then(|| Arc::new(GitHistory::new(canonical_root.clone(), self.clock.clone())));
 
         NaviscopeEngine {
-            current: Arc::new(RwLock::new(Arc::new(CodeGraph::empty()))),
+            current: Arc::new(RwLock::new(graph)),
             project_root: canonical_root,
             index_path,
             build_caps,
             lang_caps,
-            naming_conventions: Arc::new(conventions),
+            naming_conventions: conventions,
             cancel_token,
             stub_cache,
             asset_service,
             source_compiler,
+            config: std::sync::RwLock::new(Arc::new(config)),
+            source_roots: Arc::new(RwLock::new(Vec::new())),
+            sources: SourceStore::new(self.fs.clone()),
+            fs: self.fs,
+            clock: self.clock,
+            trees: TreeCache::default(),
+            documents: OpenDocuments::default(),
+            index_lock: tokio::sync::Mutex::new(()),
+            op_log,
+            watch_flow: std::sync::Mutex::new(watch_flow),
+            metrics: metrics::RuntimeCounters::default(),
+            closing: std::sync::atomic::AtomicBool::new(false),
+            commits,
+            progress: Arc::default(),
+            writer,
+            notes,
+            popularity,
+            warnings,
+            audit,
+            toolchains,
+            linker: Arc::new(Linker::new(self.link_resolvers)),
+            advisories: Arc::new(Advisories::new(self.advisors)),
+            history,
+            read_only,
+            partial: Arc::default(),
         }
     }
 }
@@ -184,6 +491,9 @@ impl NaviscopeEngineBuilder {
 impl Drop for NaviscopeEngine {
     fn drop(&mut self) {
         self.cancel_token.cancel();
+        if let Err(e) = self.popularity.flush() {
+            tracing::warn!("Failed to save access counts: {}", e);
+        }
     }
 }
 
@@ -198,6 +508,97 @@ impl NaviscopeEngine {
         &self.project_root
     }
 
+    /// Get the project configuration
+    pub fn config(&self) -> Arc<ProjectConfig> {
+        self.config.read().unwrap().clone()
+    }
+
+    /// Toolchains selected for the project, e.g. the JDK
+    pub fn toolchains(&self) -> &[Toolchain] {
+        &self.toolchains
+    }
+
+    /// Advisors of the dependency nodes
+    pub fn advisories(&self) -> Arc<Advisories> {
+        self.advisories.clone()
+    }
+
+    /// Git history of the project, if enabled
+    pub fn history(&self) -> Option<Arc<GitHistory>> {
+        self.history.clone()
+    }
+
+    /// Whether the index was opened read-only
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Fail with `NaviscopeError::ReadOnly` if the index was opened read-only.
+    pub(crate) fn ensure_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(NaviscopeError::ReadOnly(self.index_path.clone()));
+        }
+        Ok(())
+    }
+
+    /// Whether `shutdown` has begun
+    fn is_closing(&self) -> bool {
+        self.closing.load(std::sync::atomic::Ordering::Acquire)
+    }
+
+    /// Fail with `NaviscopeError::Cancelled` once `shutdown` has begun.
+    fn ensure_open(&self) -> Result<()> {
+        if self.is_closing() {
+            return Err(NaviscopeError::Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Batching state of the file watcher
+    pub fn watch_flow(&self) -> naviscope_api::lifecycle::WatchFlow {
+        self.watch_flow.lock().unwrap().metrics()
+    }
+
+    /// Counters and latencies of the index updates since the engine started
+    pub fn runtime_metrics(&self) -> naviscope_api::lifecycle::RuntimeMetrics {
+        self.metrics.snapshot()
+    }
+
+    /// Progress of the update that started from the snapshot of `epoch`, if
+    /// it is the latest one
+    pub fn epoch_progress(&self, epoch: u64) -> Option<naviscope_api::lifecycle::EpochProgress> {
+        self.progress.get(epoch)
+    }
+
+    /// Follow the progress of the updates run from now on
+    pub fn subscribe_progress(
+        &self,
+    ) -> tokio::sync::watch::Receiver<Option<naviscope_api::lifecycle::EpochProgress>> {
+        self.progress.subscribe()
+    }
+
+    /// Re-read `naviscope.toml`.
+    ///
+    /// Ignore globs and source roots apply from the next indexing run. Settings
+    /// consumed while the engine was built (storage, plugins, JDK, memory, MCP,
+    /// advisories) are stored but only take effect once the engine is recreated.
+    pub fn reload_config(&self) -> Result<()> {
+        let config = ProjectConfig::load(&self.project_root)?;
+        config.index.ignore_matcher(&self.project_root)?;
+
+        let mut current = self.config.write().unwrap();
+        let restart = config.restart_required(&current);
+        if !restart.is_empty() {
+            tracing::warn!(
+                "Changes to {} in {} take effect after a restart",
+                restart.join(", "),
+                crate::config::CONFIG_FILE_NAME
+            );
+        }
+        *current = Arc::new(config);
+        Ok(())
+    }
+
     /// Query semantic capabilities for a language.
     pub fn semantic_cap(
         &self,
@@ -305,6 +706,32 @@ impl NaviscopeEngine {
         )
     }
 
+    /// Like `request_stub_for_fqn`, for a caller waiting on the symbol: takes
+    /// an interactive slot, so builds cannot hold it back, and generates the
+    /// stub on a blocking thread.
+    pub async fn request_stub(self: Arc<Self>, fqn: String) -> bool {
+        let _slot = self
+            .source_compiler
+            .acquire_slot(IntakeTopic::Interactive)
+            .await;
+        tokio::task::spawn_blocking(move || self.request_stub_for_fqn(&fqn))
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Locate the declaration of an external symbol in a readable document.
+    ///
+    /// The asset's source artifact is used when there is one, locally or
+    /// downloaded; otherwise the asset is rendered into a document of its
+    /// signatures. Either way the document is written below the stub cache and
+    /// the returned location points at the symbol's declaration (or the file
+    /// start when it is not found).
+    /// Reads the asset synchronously; call from a blocking context.
+    pub fn synthetic_source_location(&self, fqn: &str) -> Option<SymbolLocation> {
+        let service = self.asset_service.as_ref()?;
+        external_source_location(service, &self.lang_caps, &self.stub_cache, fqn)
+    }
+
     /// Run the global asset scan and populate routes
     /// Returns the scan result with statistics
     pub async fn scan_global_assets(&self) -> Option<crate::asset::scanner::ScanResult> {
@@ -319,6 +746,21 @@ impl NaviscopeEngine {
         }
     }
 
+    /// Parse cache for open documents
+    pub fn trees(&self) -> &TreeCache {
+        &self.trees
+    }
+
+    /// Contents of source files, cached until they change
+    pub fn sources(&self) -> &SourceStore {
+        &self.sources
+    }
+
+    /// Documents open in an editor
+    pub fn documents(&self) -> &OpenDocuments {
+        &self.documents
+    }
+
     /// Get global asset routes snapshot (for passing to resolvers)
     pub fn global_asset_routes(&self) -> HashMap<String, Vec<PathBuf>> {
         if let Some(service) = &self.asset_service {
@@ -329,8 +771,11 @@ impl NaviscopeEngine {
     }
 
     /// Compute index storage path for a project
-    fn compute_index_path(project_root: &Path) -> PathBuf {
-        let base_dir = Self::get_base_index_dir();
+    fn compute_index_path(project_root: &Path, config: &ProjectConfig) -> PathBuf {
+        let base_dir = match &config.index.dir {
+            Some(dir) => project_root.join(dir),
+            None => Self::get_base_index_dir(),
+        };
         let abs_path = project_root
             .canonicalize()
             .unwrap_or_else(|_| project_root.to_path_buf());
@@ -340,9 +785,116 @@ impl NaviscopeEngine {
 
     /// Get a snapshot of the current graph (cheap operation)
     pub async fn snapshot(&self) -> CodeGraph {
+        self.read_pending_shards(None).await;
+        let lock = self.current.read().await;
+        (**lock).clone()
+    }
+
+    /// Snapshot holding at least the nodes `fqns` name, with their edges.
+    /// A read-only index reads only the shards they need.
+    pub(crate) async fn snapshot_for(&self, fqns: &[String]) -> CodeGraph {
+        self.read_pending_shards(Some(fqns)).await;
         let lock = self.current.read().await;
         (**lock).clone()
     }
+
+    /// Read the shards of a partially loaded index that `fqns` need, or all
+    /// of them, into the current snapshot.
+    async fn read_pending_shards(&self, fqns: Option<&[String]>) {
+        if self.partial.lock().unwrap().is_none() {
+            return;
+        }
+        let partial = self.partial.clone();
+        let current = self.current.clone();
+        let fqns = fqns.map(<[String]>::to_vec);
+        let loaded = tokio::task::spawn_blocking(move || {
+            let mut partial = partial.lock().unwrap();
+            let Some(index) = partial.as_mut() else {
+                return;
+            };
+            match index.load(fqns.as_deref()) {
+                Ok(Some(graph)) => *current.blocking_write() = Arc::new(graph),
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::warn!("Failed to read index shards: {}", e);
+                    *partial = None;
+                    return;
+                }
+            }
+            if index.is_complete() {
+                *partial = None;
+            }
+        })
+        .await;
+        if let Err(e) = loaded {
+            tracing::warn!("Failed to read index shards: {}", e);
+        }
+    }
+}
+
+/// See `synthetic_source_location`
+fn external_source_location(
+    service: &AssetStubService,
+    lang_caps: &[LanguageCaps],
+    stub_cache: &crate::cache::GlobalStubCache,
+    fqn: &str,
+) -> Option<SymbolLocation> {
+    let entries = service.lookup_asset(fqn)?;
+
+    for entry in &entries {
+        for caps in lang_caps {
+            let Some(generator) = caps.asset.stub_generator() else {
+                continue;
+            };
+            if !generator.can_generate(&entry.path) {
+                continue;
+            }
+
+            let extracted = service.resolve_source(entry).and_then(|sources| {
+                generator
+                    .extract_source(fqn, &sources)
+                    .inspect_err(|e| tracing::debug!("Failed to read {:?}: {}", sources, e))
+                    .ok()
+                    .flatten()
+            });
+            let (dir, source) = match extracted {
+                Some(source) => (stub_cache.extracted_source_dir(), source),
+                None => match generator.render_source(fqn, entry) {
+                    Ok(Some(source)) => (stub_cache.synthetic_source_dir(), source),
+                    Ok(None) => continue,
+                    Err(e) => {
+                        tracing::debug!("Failed to render source for {}: {}", fqn, e);
+                        continue;
+                    }
+                },
+            };
+
+            let path = dir.join(&source.relative_path);
+            if let Err(e) = write_synthetic_source(&path, &source.content) {
+                tracing::warn!("Failed to write source document {:?}: {}", path, e);
+                return None;
+            }
+
+            let range = source.symbols.get(fqn).copied().unwrap_or_default();
+            return Some(SymbolLocation {
+                path: Arc::from(path),
+                range,
+                selection_range: Some(range),
+            });
+        }
+    }
+    None
+}
+
+/// Write a source document, leaving an identical existing file untouched.
+fn write_synthetic_source(path: &Path, content: &str) -> std::io::Result<()> {
+    if std::fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)
 }
 
 #[cfg(test)]
@@ -385,4 +937,231 @@ mod tests {
             result.unwrap();
         }
     }
+
+    #[tokio::test]
+    async fn test_index_roundtrip_through_injected_fs() {
+        let fs = Arc::new(crate::platform::MemoryFs::new());
+        let engine = NaviscopeEngine::builder(PathBuf::from("."))
+            .with_fs(fs.clone())
+            .build();
+
+        assert!(!engine.load().await.unwrap());
+
+        engine.save().await.unwrap();
+        let shard_dir = engine.index_path.with_extension("shards");
+        let paths = fs.paths();
+        assert_eq!(paths[0], engine.index_path);
+        assert_eq!(paths.len(), 2, "manifest and core shard: {:?}", paths);
+        assert!(paths[1].starts_with(&shard_dir));
+        assert!(engine.load().await.unwrap());
+
+        // Nothing changed, so no shard is rewritten
+        engine.save().await.unwrap();
+        assert_eq!(fs.paths(), paths);
+
+        engine.clear_project_index().await.unwrap();
+        assert!(fs.paths().is_empty());
+        assert!(!engine.load().await.unwrap());
+    }
+
+    /// Classes `a.A` -> `b.B` and `c.C`, each in its own directory
+    fn sharded_graph() -> CodeGraph {
+        use crate::model::{EdgeType, GraphEdge};
+        use naviscope_api::models::NodeKind;
+
+        let mut builder = crate::model::builder::CodeGraphBuilder::new();
+        builder.naming_conventions.insert(
+            crate::model::Language::new("java".to_string()),
+            Arc::new(naviscope_plugin::StandardNamingConvention),
+        );
+        let class =
+            |fqn: &str, path: &str| crate::test_support::node_at(fqn, NodeKind::Class, path);
+        let a = builder.add_node(class("a.A", "/p/a/A.java"));
+        let b = builder.add_node(class("b.B", "/p/b/B.java"));
+        builder.add_node(class("c.C", "/p/c/C.java"));
+        builder.add_edge(a, b, GraphEdge::new(EdgeType::TypedAs));
+        builder.build()
+    }
+
+    #[tokio::test]
+    async fn test_save_writes_only_changed_shards() {
+        use crate::model::storage::shard::STRING_CHUNK;
+        use naviscope_api::models::NodeKind;
+
+        let fs = Arc::new(crate::platform::MemoryFs::new());
+        let engine = NaviscopeEngine::builder(PathBuf::from("."))
+            .with_fs(fs.clone())
+            .build();
+        engine.apply_graph_snapshot(sharded_graph()).await;
+        engine.save().await.unwrap();
+        let before = manifest::IndexManifest::read(fs.as_ref(), &engine.index_path).unwrap();
+
+        let mut builder = engine.snapshot().await.to_builder();
+        builder.add_node(crate::test_support::node_at(
+            "c.D",
+            NodeKind::Class,
+            "/p/c/D.java",
+        ));
+        let graph = builder.build();
+        let changed = graph
+            .shard_log()
+            .changed_since(before.lineage, before.epoch, graph.symbols())
+            .unwrap();
+        assert_eq!(changed.into_iter().collect::<Vec<_>>(), vec!["/p/c"]);
+        engine.apply_graph_snapshot(graph).await;
+
+        let paths: std::collections::HashSet<_> = fs.paths().into_iter().collect();
+        engine.save().await.unwrap();
+        let after = manifest::IndexManifest::read(fs.as_ref(), &engine.index_path).unwrap();
+        for key in ["/p/a", "/p/b"] {
+            assert_eq!(after.shards[key].file, before.shards[key].file);
+        }
+        assert_ne!(after.shards["/p/c"].file, before.shards["/p/c"].file);
+
+        // The directory's shard, the core shard and the last chunks of the
+        // string table and FQN tree
+        let written: Vec<_> = fs
+            .paths()
+            .into_iter()
+            .filter(|path| !paths.contains(path) && *path != engine.index_path)
+            .collect();
+        assert_eq!(written.len(), 4, "{:?}", written);
+        assert!(after.string_count < STRING_CHUNK);
+
+        // A graph that does not descend from the saved one rewrites every shard
+        let other = sharded_graph();
+        assert_eq!(
+            other
+                .shard_log()
+                .changed_since(after.lineage, after.epoch, other.symbols()),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_only_index_loads_shards_on_demand() {
+        let fs = Arc::new(crate::platform::MemoryFs::new());
+        let writer = NaviscopeEngine::builder(PathBuf::from("."))
+            .with_fs(fs.clone())
+            .build();
+        writer.apply_graph_snapshot(sharded_graph()).await;
+        writer.save().await.unwrap();
+
+        let engine = NaviscopeEngine::builder(PathBuf::from("/elsewhere"))
+            .with_fs(fs.clone())
+            .with_read_only_index(writer.index_path.clone())
+            .build();
+        assert!(engine.load().await.unwrap());
+        assert_eq!(engine.current.read().await.node_count(), 0);
+
+        // `a.A` and the shard it has an edge into
+        let graph = engine.snapshot_for(&["a.A".to_string()]).await;
+        let a = graph.find_node("a.A").unwrap();
+        let b = graph.find_node("b.B").unwrap();
+        assert!(graph.topology().find_edge(a, b).is_some());
+        assert!(graph.find_node("c.C").is_none());
+
+        // Symbols that are not in the index read nothing
+        let instance_id = graph.instance_id();
+        let graph = engine.snapshot_for(&["x.X".to_string()]).await;
+        assert_eq!(graph.node_count(), 2);
+
+        let graph = engine.snapshot().await;
+        assert_eq!((graph.node_count(), graph.edge_count()), (3, 1));
+        assert_eq!(graph.instance_id(), instance_id);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_index_is_discarded() {
+        let fs = Arc::new(crate::platform::MemoryFs::new());
+        let engine = NaviscopeEngine::builder(PathBuf::from("."))
+            .with_fs(fs.clone())
+            .build();
+
+        fs.write(&engine.index_path, b"not an index").unwrap();
+        assert!(!engine.load().await.unwrap());
+        assert!(fs.paths().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_read_only_index_is_never_written() {
+        let fs = Arc::new(crate::platform::MemoryFs::new());
+        let writer = NaviscopeEngine::builder(PathBuf::from("."))
+            .with_fs(fs.clone())
+            .build();
+        writer.save().await.unwrap();
+        let paths = fs.paths();
+
+        let engine = NaviscopeEngine::builder(PathBuf::from("/elsewhere"))
+            .with_fs(fs.clone())
+            .with_read_only_index(writer.index_path.clone())
+            .build();
+        assert!(engine.is_read_only());
+        assert!(engine.load().await.unwrap());
+        assert!(matches!(
+            engine.rebuild().await,
+            Err(NaviscopeError::ReadOnly(_))
+        ));
+        assert!(matches!(
+            engine.add_note("a.A", "note", &[]),
+            Err(NaviscopeError::ReadOnly(_))
+        ));
+        assert!(engine.clear_project_index().await.is_err());
+        assert!(engine.save().await.is_err());
+        engine.record_hits(&vec!["a.A".to_string(); 64]).unwrap();
+        drop(engine);
+        assert_eq!(fs.paths(), paths);
+
+        // An unusable index is left for its owner to replace
+        fs.write(&writer.index_path, b"not an index").unwrap();
+        let engine = NaviscopeEngine::builder(PathBuf::from("."))
+            .with_fs(fs.clone())
+            .with_read_only_index(writer.index_path.clone())
+            .build();
+        assert!(!engine.load().await.unwrap());
+        assert_eq!(fs.paths(), paths);
+    }
+
+    #[test]
+    fn test_external_source_location_reads_fetched_sources() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let source = AssetSource::Maven {
+            group: "com.example".to_string(),
+            artifact: "lib".to_string(),
+            version: "1.0".to_string(),
+        };
+        // Only the downloaded artifact has the sources; no repository is reachable
+        let fetched = dir.path().join("sources/com/example/lib/1.0");
+        std::fs::create_dir_all(&fetched).unwrap();
+        let file = std::fs::File::create(fetched.join("lib-1.0-sources.jar")).unwrap();
+        let mut jar = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default();
+        jar.start_file("com/example/Greeter.java", options).unwrap();
+        jar.write_all(b"package com.example;\n\npublic class Greeter {\n    public String greet() {\n        return \"hi\";\n    }\n}\n")
+            .unwrap();
+        jar.finish().unwrap();
+
+        let service = AssetStubService::new(vec![], vec![], vec![], vec![]).with_source_fetcher(
+            Arc::new(SourceFetcher::new(vec![], dir.path().join("sources"))),
+        );
+        let entry = naviscope_plugin::AssetEntry::new(dir.path().join("lib-1.0.jar"), source);
+        service
+            .registry()
+            .register_batch([("com.example".to_string(), entry)]);
+        let caps = vec![naviscope_java::java_caps().unwrap()];
+        let stub_cache = crate::cache::GlobalStubCache::new(dir.path().join("cache"));
+
+        let location =
+            external_source_location(&service, &caps, &stub_cache, "com.example.Greeter#greet()")
+                .unwrap();
+        assert!(location.path.starts_with(stub_cache.extracted_source_dir()));
+        let content = std::fs::read_to_string(&*location.path).unwrap();
+        assert!(content.contains("return \"hi\";"));
+        assert_eq!(
+            (location.range.start_line, location.range.start_col),
+            (3, 18)
+        );
+    }
 }
@@ -0,0 +1,145 @@
+//! Notes attached to graph nodes
+//!
+//! Agents record what they learn about the code with `annotate`, so a later
+//! session can read it back from `cat` or hover. Notes are keyed by FQN and
+//! kept in a JSON file next to the index rather than in the graph: rebuilds,
+//! `clear` and nodes that go away for a while leave them in place.
+
+use super::*;
+use naviscope_api::models::NodeNote;
+use std::collections::BTreeMap;
+use std::time::UNIX_EPOCH;
+
+type NoteMap = BTreeMap<String, Vec<NodeNote>>;
+
+pub(super) struct NoteStore {
+    fs: Arc<dyn Fs>,
+    clock: Arc<dyn Clock>,
+    path: PathBuf,
+    /// Loaded on first use
+    notes: std::sync::Mutex<Option<NoteMap>>,
+}
+
+impl NoteStore {
+    pub(super) fn new(fs: Arc<dyn Fs>, clock: Arc<dyn Clock>, index_path: &Path) -> Self {
+        Self {
+            fs,
+            clock,
+            path: index_path.with_extension("notes.json"),
+            notes: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Run `f` on the notes, loading them first if needed.
+    fn with_notes<T>(&self, f: impl FnOnce(&mut NoteMap) -> T) -> Result<T> {
+        let mut guard = self.notes.lock().unwrap();
+        if guard.is_none() {
+            let notes = if self.fs.exists(&self.path) {
+                serde_json::from_slice(&self.fs.read(&self.path)?)?
+            } else {
+                NoteMap::new()
+            };
+            *guard = Some(notes);
+        }
+        Ok(f(guard.as_mut().unwrap()))
+    }
+
+    fn store(&self, notes: &NoteMap) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            self.fs.create_dir_all(parent)?;
+        }
+        let temp_path = self.path.with_extension("tmp");
+        self.fs
+            .write(&temp_path, &serde_json::to_vec_pretty(notes)?)?;
+        self.fs.rename(&temp_path, &self.path)?;
+        Ok(())
+    }
+
+    pub(super) fn list(&self, fqn: &str) -> Result<Vec<NodeNote>> {
+        self.with_notes(|notes| notes.get(fqn).cloned().unwrap_or_default())
+    }
+
+    pub(super) fn add(&self, fqn: &str, text: &str, tags: &[String]) -> Result<NodeNote> {
+        let created_at = self
+            .clock
+            .now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.with_notes(|notes| {
+            let entries = notes.entry(fqn.to_string()).or_default();
+            let note = NodeNote {
+                id: entries.iter().map(|n| n.id).max().map_or(1, |id| id + 1),
+                text: text.trim().to_string(),
+                tags: tags.to_vec(),
+                created_at,
+            };
+            entries.push(note.clone());
+            self.store(notes).map(|()| note)
+        })?
+    }
+
+    pub(super) fn remove(&self, fqn: &str, id: u64) -> Result<bool> {
+        self.with_notes(|notes| {
+            let Some(entries) = notes.get_mut(fqn) else {
+                return Ok(false);
+            };
+            let before = entries.len();
+            entries.retain(|n| n.id != id);
+            if entries.len() == before {
+                return Ok(false);
+            }
+            if entries.is_empty() {
+                notes.remove(fqn);
+            }
+            self.store(notes).map(|()| true)
+        })?
+    }
+}
+
+impl NaviscopeEngine {
+    /// Notes attached to `fqn`, oldest first
+    pub fn notes(&self, fqn: &str) -> Result<Vec<NodeNote>> {
+        self.notes.list(fqn)
+    }
+
+    /// Attach a note to `fqn`; the caller checks that the node exists.
+    pub fn add_note(&self, fqn: &str, text: &str, tags: &[String]) -> Result<NodeNote> {
+        self.ensure_writable()?;
+        self.notes.add(fqn, text, tags)
+    }
+
+    /// Remove the note `id` from `fqn`; `false` if there was none.
+    pub fn remove_note(&self, fqn: &str, id: u64) -> Result<bool> {
+        self.ensure_writable()?;
+        self.notes.remove(fqn, id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::MemoryFs;
+
+    #[test]
+    fn notes_persist_and_get_fresh_ids() {
+        let fs = Arc::new(MemoryFs::new());
+        let index_path = PathBuf::from("/idx/p.bin");
+        let store = NoteStore::new(fs.clone(), Arc::new(SystemClock), &index_path);
+
+        let first = store.add("a.A", " caches per tenant ", &[]).unwrap();
+        let second = store.add("a.A", "", &["hot-path".to_string()]).unwrap();
+        assert_eq!((first.id, second.id), (1, 2));
+        assert_eq!(first.text, "caches per tenant");
+
+        // A new store (a later session) reads them back from disk
+        let store = NoteStore::new(fs.clone(), Arc::new(SystemClock), &index_path);
+        assert_eq!(store.list("a.A").unwrap(), [first.clone(), second]);
+        assert!(store.list("a.B").unwrap().is_empty());
+
+        assert!(store.remove("a.A", 2).unwrap());
+        assert!(!store.remove("a.A", 2).unwrap());
+        assert_eq!(store.add("a.A", "again", &[]).unwrap().id, 2);
+        assert_eq!(store.list("a.A").unwrap()[0], first);
+    }
+}
@@ -0,0 +1,360 @@
+//! NDJSON log of committed graph changes
+//!
+//! When `[index] op_log` is set, every graph the engine commits is compared
+//! with the previously logged one and the node changes of files that changed
+//! are written as one JSON object per line. External tools (code search
+//! indexes, dashboards) can follow the log to mirror the engine's view.
+//!
+//! Only nodes declared in indexed files are logged; library stubs loaded on
+//! demand are not.
+
+use crate::model::CodeGraph;
+use crate::model::source::SourceFile;
+use naviscope_api::lifecycle::ChangeSummary;
+use naviscope_api::models::graph::NodeKind;
+use naviscope_plugin::NamingConvention;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, mpsc};
+
+/// Where the log is written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpLogTarget {
+    /// Append to a file
+    File(PathBuf),
+    /// Connect to a TCP listener (`tcp://host:port`)
+    Tcp(String),
+    /// Connect to a Unix domain socket (`unix:///path`)
+    Unix(PathBuf),
+}
+
+impl OpLogTarget {
+    /// Parse a configured target; relative file paths are resolved against `project_root`.
+    pub fn parse(target: &str, project_root: &Path) -> Self {
+        if let Some(addr) = target.strip_prefix("tcp://") {
+            OpLogTarget::Tcp(addr.to_string())
+        } else if let Some(path) = target.strip_prefix("unix://") {
+            OpLogTarget::Unix(PathBuf::from(path))
+        } else {
+            OpLogTarget::File(project_root.join(target))
+        }
+    }
+
+    fn open(&self) -> io::Result<Box<dyn Write + Send>> {
+        match self {
+            OpLogTarget::File(path) => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)?;
+                Ok(Box::new(file))
+            }
+            OpLogTarget::Tcp(addr) => Ok(Box::new(std::net::TcpStream::connect(addr)?)),
+            #[cfg(unix)]
+            OpLogTarget::Unix(path) => Ok(Box::new(std::os::unix::net::UnixStream::connect(path)?)),
+            #[cfg(not(unix))]
+            OpLogTarget::Unix(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Unix sockets are not supported on this platform",
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for OpLogTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OpLogTarget::File(path) => write!(f, "{}", path.display()),
+            OpLogTarget::Tcp(addr) => write!(f, "tcp://{}", addr),
+            OpLogTarget::Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpKind {
+    Add,
+    Update,
+    Remove,
+}
+
+/// One line of the log
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct OpEvent {
+    /// Epoch of the graph that committed the change
+    pub epoch: u64,
+    pub op: OpKind,
+    pub fqn: String,
+    pub kind: NodeKind,
+    pub path: String,
+}
+
+type Conventions = HashMap<String, Arc<dyn NamingConvention>>;
+
+/// Node changes between `old` and `new`, restricted to files whose
+/// fingerprint or node count differs. Events are ordered by path, then FQN.
+pub fn diff_graphs(old: &CodeGraph, new: &CodeGraph, conventions: &Conventions) -> Vec<OpEvent> {
+    let old_files = file_nodes(old, conventions);
+    let new_files = file_nodes(new, conventions);
+    let epoch = new.instance_id();
+    let mut events = Vec::new();
+
+    let unchanged = |a: &FileNodes, b: &FileNodes| {
+        a.metadata.content_hash == b.metadata.content_hash
+            && a.metadata.last_modified == b.metadata.last_modified
+            && a.nodes.len() == b.nodes.len()
+    };
+
+    let mut paths: Vec<&String> = old_files.keys().chain(new_files.keys()).collect();
+    paths.sort();
+    paths.dedup();
+    for path in paths {
+        let (before, after) = (old_files.get(path), new_files.get(path));
+        if let (Some(a), Some(b)) = (before, after)
+            && unchanged(a, b)
+        {
+            continue;
+        }
+        let empty = BTreeMap::new();
+        let before = before.map(|f| &f.nodes).unwrap_or(&empty);
+        let after = after.map(|f| &f.nodes).unwrap_or(&empty);
+
+        let mut fqns: Vec<&String> = before.keys().chain(after.keys()).collect();
+        fqns.sort();
+        fqns.dedup();
+        for fqn in fqns {
+            let (op, kind) = match (before.get(fqn), after.get(fqn)) {
+                (None, Some(kind)) => (OpKind::Add, kind),
+                (Some(_), Some(kind)) => (OpKind::Update, kind),
+                (Some(kind), None) => (OpKind::Remove, kind),
+                (None, None) => continue,
+            };
+            events.push(OpEvent {
+                epoch,
+                op,
+                fqn: fqn.clone(),
+                kind: kind.clone(),
+                path: path.clone(),
+            });
+        }
+    }
+    events
+}
+
+/// Changed files and added and removed FQNs between `old` and `new`
+pub fn change_summary(
+    old: &CodeGraph,
+    new: &CodeGraph,
+    conventions: &Conventions,
+) -> ChangeSummary {
+    let mut summary = ChangeSummary {
+        epoch: new.instance_id(),
+        ..Default::default()
+    };
+    for event in diff_graphs(old, new, conventions) {
+        if summary.files.last() != Some(&event.path) {
+            summary.files.push(event.path);
+        }
+        match event.op {
+            OpKind::Add => summary.added.push(event.fqn),
+            OpKind::Remove => summary.removed.push(event.fqn),
+            OpKind::Update => {}
+        }
+    }
+    summary
+}
+
+struct FileNodes {
+    metadata: SourceFile,
+    nodes: BTreeMap<String, NodeKind>,
+}
+
+fn file_nodes(graph: &CodeGraph, conventions: &Conventions) -> HashMap<String, FileNodes> {
+    use crate::features::CodeGraphLike;
+
+    graph
+        .file_index()
+        .iter()
+        .map(|(path, entry)| {
+            let nodes = entry
+                .nodes
+                .iter()
+                .filter_map(|idx| graph.get_node(*idx))
+                .map(|node| {
+                    let convention = conventions.get(graph.symbols().resolve(&node.lang.0));
+                    (
+                        graph.render_fqn(node, convention.map(|c| c.as_ref())),
+                        node.kind.clone(),
+                    )
+                })
+                .collect();
+            (
+                graph.symbols().resolve(&path.0).to_string(),
+                FileNodes {
+                    metadata: entry.metadata.clone(),
+                    nodes,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Writes the changes of each committed graph to an `OpLogTarget`.
+///
+/// Diffing happens on the committing task; writes happen on a dedicated
+/// thread so a slow consumer does not hold up indexing. If a write fails
+/// the log stops and later commits are not recorded.
+pub(super) struct OpLog {
+    /// Last graph whose changes were logged
+    baseline: Mutex<Arc<CodeGraph>>,
+    conventions: Arc<Conventions>,
+    sender: Option<mpsc::Sender<Vec<u8>>>,
+    writer: Option<std::thread::JoinHandle<()>>,
+}
+
+impl OpLog {
+    pub(super) fn open(target: &OpLogTarget, conventions: Arc<Conventions>) -> io::Result<Self> {
+        let mut out = target.open()?;
+        let (sender, receiver) = mpsc::channel::<Vec<u8>>();
+        let name = target.to_string();
+        let writer = std::thread::Builder::new()
+            .name("naviscope-op-log".to_string())
+            .spawn(move || {
+                for batch in receiver {
+                    if let Err(e) = out.write_all(&batch).and_then(|_| out.flush()) {
+                        tracing::warn!("Op log {} failed: {}; logging stopped", name, e);
+                        return;
+                    }
+                }
+            })?;
+        Ok(Self {
+            baseline: Mutex::new(Arc::new(CodeGraph::empty())),
+            conventions,
+            sender: Some(sender),
+            writer: Some(writer),
+        })
+    }
+
+    /// Log the changes from the previously recorded graph to `graph`.
+    pub(super) fn record(&self, graph: &Arc<CodeGraph>) {
+        let mut baseline = self.baseline.lock().unwrap();
+        let events = diff_graphs(&baseline, graph, &self.conventions);
+        *baseline = graph.clone();
+        if events.is_empty() {
+            return;
+        }
+
+        let mut batch = Vec::new();
+        for event in &events {
+            if serde_json::to_writer(&mut batch, event).is_ok() {
+                batch.push(b'\n');
+            }
+        }
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(batch);
+        }
+    }
+}
+
+impl Drop for OpLog {
+    fn drop(&mut self) {
+        // Closing the channel lets the writer drain pending batches and exit
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::builder::CodeGraphBuilder;
+    use crate::test_support::node_at;
+
+    fn graph(files: &[(&str, u64, &[&str])]) -> CodeGraph {
+        let mut builder = CodeGraphBuilder::new();
+        for (path, hash, fqns) in files {
+            for fqn in *fqns {
+                builder.add_node(node_at(fqn, NodeKind::Class, path));
+            }
+            builder.update_file(
+                Path::new(path),
+                SourceFile::new(PathBuf::from(path), *hash, 0),
+            );
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn diff_reports_nodes_of_changed_files_only() {
+        let old = graph(&[
+            ("/p/A.java", 1, &["a.A", "a.AHelper"]),
+            ("/p/B.java", 1, &["b.B"]),
+            ("/p/C.java", 1, &["c.C"]),
+        ]);
+        let new = graph(&[
+            ("/p/A.java", 2, &["a.A", "a.ANew"]),
+            ("/p/B.java", 1, &["b.B"]),
+        ]);
+
+        let events: Vec<(OpKind, String)> = diff_graphs(&old, &new, &HashMap::new())
+            .into_iter()
+            .map(|e| (e.op, e.fqn))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (OpKind::Update, "a.A".to_string()),
+                (OpKind::Remove, "a.AHelper".to_string()),
+                (OpKind::Add, "a.ANew".to_string()),
+                (OpKind::Remove, "c.C".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn change_summary_lists_files_and_declarations() {
+        let old = graph(&[("/p/A.java", 1, &["a.A", "a.AHelper"])]);
+        let new = graph(&[
+            ("/p/A.java", 2, &["a.A"]),
+            ("/p/B.java", 1, &["b.B", "b.BImpl"]),
+        ]);
+
+        let summary = change_summary(&old, &new, &HashMap::new());
+        assert_eq!(summary.epoch, new.instance_id());
+        assert_eq!(summary.files, ["/p/A.java", "/p/B.java"]);
+        assert_eq!(summary.added, ["b.B", "b.BImpl"]);
+        assert_eq!(summary.removed, ["a.AHelper"]);
+    }
+
+    #[test]
+    fn op_log_appends_ndjson_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = OpLogTarget::parse("ops.ndjson", dir.path());
+        let log = OpLog::open(&target, Arc::new(HashMap::new())).unwrap();
+
+        let first = Arc::new(graph(&[("/p/A.java", 1, &["a.A"])]));
+        log.record(&first);
+        log.record(&first);
+        log.record(&Arc::new(graph(&[])));
+        drop(log);
+
+        let text = std::fs::read_to_string(dir.path().join("ops.ndjson")).unwrap();
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["op"], "add");
+        assert_eq!(lines[0]["fqn"], "a.A");
+        assert_eq!(lines[0]["kind"], "class");
+        assert_eq!(lines[0]["epoch"], first.instance_id());
+        assert_eq!(lines[1]["op"], "remove");
+    }
+}
@@ -0,0 +1,210 @@
+//! On-demand loading of sharded indices
+//!
+//! A read-only engine answers queries from a saved index without ever
+//! changing it, so it need not decode the whole graph before the first
+//! lookup. It opens the string table and FQN tree only, and reads node
+//! shards as queries ask for symbols: the manifest names the shard a
+//! symbol's nearest root ancestor enters, and the shards that one has edges
+//! with. Queries that scan the whole graph load the remaining shards first.
+
+use super::manifest::IndexManifest;
+use super::storage::codec_lookup;
+use crate::error::Result;
+use crate::model::graph::{CURRENT_VERSION, FILE_ID_PREFIX, file_node_parts};
+use crate::model::storage::shard::{
+    self, CoreShard, DecodedShard, FqnChunk, NodeShard, ShardLog, StringChunk, decode_shard,
+};
+use crate::model::{CodeGraph, FqnManager};
+use crate::platform::Fs;
+use naviscope_api::models::symbol::FqnId;
+use naviscope_plugin::{BuildCaps, LanguageCaps, NodeMetadataCodec};
+use rayon::prelude::*;
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The string table and FQN tree of a sharded index
+pub(super) struct ShardedIndex {
+    dir: PathBuf,
+    pub(super) manifest: IndexManifest,
+    pub(super) version: u32,
+    pub(super) fqns: FqnManager,
+}
+
+impl ShardedIndex {
+    /// Read the core of the index at `path`. The FQN tree of an index of
+    /// another version is left empty.
+    pub(super) fn open(fs: &dyn Fs, path: &Path, manifest: IndexManifest) -> Result<Self> {
+        let dir = IndexManifest::shard_dir(path);
+        let core: CoreShard = decode_shard(&fs.read_mapped(&dir.join(&manifest.core))?)?;
+        let fqns = if core.version == CURRENT_VERSION {
+            let strings = read_all::<StringChunk>(fs, &dir, &manifest.strings)?;
+            let chunks = read_all::<FqnChunk>(fs, &dir, &manifest.fqns)?;
+            shard::restore_fqns(&core, strings, chunks)?
+        } else {
+            FqnManager::new()
+        };
+        Ok(Self {
+            dir,
+            manifest,
+            version: core.version,
+            fqns,
+        })
+    }
+
+    /// Log of the graph the index was saved from
+    pub(super) fn log(&self) -> ShardLog {
+        ShardLog::resume(self.manifest.lineage, self.manifest.epoch)
+    }
+
+    /// Map the node shards `keys` and decode them in parallel.
+    pub(super) fn read_shards<'a>(
+        &self,
+        fs: &dyn Fs,
+        keys: impl IntoIterator<Item = &'a String>,
+        get_codec: impl Fn(&str) -> Option<Arc<dyn NodeMetadataCodec>> + Sync,
+    ) -> Result<Vec<DecodedShard>> {
+        let names: Vec<String> = keys
+            .into_iter()
+            .filter_map(|key| self.manifest.shards.get(key))
+            .map(|entry| entry.file.clone())
+            .collect();
+        let shards = read_all::<NodeShard>(fs, &self.dir, &names)?;
+        Ok(shard::decode_shards(&self.fqns, shards, get_codec))
+    }
+}
+
+fn read_all<T: DeserializeOwned + Send>(
+    fs: &dyn Fs,
+    dir: &Path,
+    names: &[String],
+) -> Result<Vec<T>> {
+    let files = names
+        .iter()
+        .map(|name| fs.read_mapped(&dir.join(name)))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    files.par_iter().map(|bytes| decode_shard(bytes)).collect()
+}
+
+/// A sharded index whose node shards are read as they are needed
+pub(super) struct PartialIndex {
+    fs: Arc<dyn Fs>,
+    index: ShardedIndex,
+    lang_caps: Arc<Vec<LanguageCaps>>,
+    build_caps: Arc<Vec<BuildCaps>>,
+    /// Root FQN id -> key of the shard it enters
+    roots: HashMap<FqnId, String>,
+    loaded: BTreeMap<String, DecodedShard>,
+    /// Instance id of every graph handed out; they are views of one snapshot
+    instance_id: Option<u64>,
+}
+
+impl PartialIndex {
+    /// Open the index at `path` if it is sharded and of the current version.
+    pub(super) fn open(
+        fs: Arc<dyn Fs>,
+        path: &Path,
+        lang_caps: Arc<Vec<LanguageCaps>>,
+        build_caps: Arc<Vec<BuildCaps>>,
+    ) -> Result<Option<Self>> {
+        let Some(manifest) = IndexManifest::read(fs.as_ref(), path) else {
+            return Ok(None);
+        };
+        let index = ShardedIndex::open(fs.as_ref(), path, manifest)?;
+        if index.version != CURRENT_VERSION {
+            return Ok(None);
+        }
+        let roots = index
+            .manifest
+            .shards
+            .iter()
+            .flat_map(|(key, entry)| entry.roots.iter().map(move |id| (FqnId(*id), key.clone())))
+            .collect();
+        Ok(Some(Self {
+            fs,
+            index,
+            lang_caps,
+            build_caps,
+            roots,
+            loaded: BTreeMap::new(),
+            instance_id: None,
+        }))
+    }
+
+    /// Graph of the shards loaded so far
+    pub(super) fn graph(&mut self) -> CodeGraph {
+        let graph = CodeGraph::from_shards(
+            self.index.version,
+            &self.index.fqns,
+            self.loaded.values(),
+            self.index.log(),
+        );
+        let instance_id = *self.instance_id.get_or_insert(graph.instance_id());
+        graph.with_instance_id(instance_id)
+    }
+
+    /// Whether every shard is loaded
+    pub(super) fn is_complete(&self) -> bool {
+        self.loaded.len() == self.index.manifest.shards.len()
+    }
+
+    /// Load the shards holding `fqns` and their neighbours, or every shard
+    /// if `fqns` is `None`. Returns the graph if a shard was added.
+    pub(super) fn load(&mut self, fqns: Option<&[String]>) -> Result<Option<CodeGraph>> {
+        let keys: Vec<String> = self
+            .wanted(fqns)
+            .into_iter()
+            .filter(|key| !self.loaded.contains_key(key))
+            .collect();
+        if keys.is_empty() {
+            return Ok(None);
+        }
+        let get_codec = codec_lookup(&self.lang_caps, &self.build_caps);
+        let shards = self.index.read_shards(self.fs.as_ref(), &keys, get_codec)?;
+        tracing::debug!(
+            "Loaded {} of {} shards",
+            keys.len(),
+            self.index.manifest.shards.len()
+        );
+        self.loaded.extend(keys.into_iter().zip(shards));
+        Ok(Some(self.graph()))
+    }
+
+    fn wanted(&self, fqns: Option<&[String]>) -> BTreeSet<String> {
+        let all = || self.index.manifest.shards.keys().cloned().collect();
+        let Some(fqns) = fqns else {
+            return all();
+        };
+        let mut keys = BTreeSet::new();
+        for fqn in fqns {
+            let ids = match fqn.strip_prefix(FILE_ID_PREFIX).map(Path::new) {
+                Some(path) if path.is_absolute() => self
+                    .index
+                    .fqns
+                    .resolve_path(&file_node_parts(path))
+                    .into_iter()
+                    .collect(),
+                // A relative path is matched against every indexed file
+                Some(_) => return all(),
+                None => self.index.fqns.resolve_fqn_string(fqn),
+            };
+            for key in ids.into_iter().filter_map(|id| self.shard_of(id)) {
+                keys.insert(key.clone());
+                keys.extend(self.index.manifest.shards[key].links.iter().cloned());
+            }
+        }
+        keys
+    }
+
+    /// Shard holding the node `id`, if it is a node: the one its nearest
+    /// root ancestor enters
+    fn shard_of(&self, mut id: FqnId) -> Option<&String> {
+        loop {
+            if let Some(key) = self.roots.get(&id) {
+                return Some(key);
+            }
+            id = self.index.fqns.nodes.get(&id)?.parent?;
+        }
+    }
+}
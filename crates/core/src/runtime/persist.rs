@@ -0,0 +1,220 @@
+//! Background persistence of committed snapshots
+//!
+//! Incremental updates used to save the index before returning, so a watch
+//! update on a slow disk took as long as the write. Commits now hand their
+//! snapshot to an `IndexWriter`, whose task writes the latest one once commits
+//! pause for `COALESCE_DELAY`: a burst of updates costs one write, and the
+//! shards it adds are synced together before the manifest naming them is
+//! replaced.
+
+use super::NaviscopeEngine;
+use crate::error::{NaviscopeError, Result};
+use crate::model::CodeGraph;
+use crate::platform::Fs;
+use naviscope_plugin::{BuildCaps, LanguageCaps};
+use std::path::PathBuf;
+use std::sync::{Arc, Once};
+use std::time::Duration;
+use tokio::sync::{Mutex, Notify, watch};
+use tokio_util::sync::CancellationToken;
+
+/// Quiet time after a commit before its snapshot is written
+const COALESCE_DELAY: Duration = Duration::from_millis(250);
+
+pub(super) struct IndexWriter {
+    fs: Arc<dyn Fs>,
+    path: PathBuf,
+    lang_caps: Arc<Vec<LanguageCaps>>,
+    build_caps: Arc<Vec<BuildCaps>>,
+    /// Latest committed snapshot not yet written
+    pending: watch::Sender<Option<Arc<CodeGraph>>>,
+    /// Epoch of the snapshot on disk, if known. Held while writing, so writes
+    /// never overlap each other or a reset of the index.
+    persisted: Mutex<Option<u64>>,
+    /// Ends the coalescing delay early
+    flush_now: Notify,
+    started: Once,
+}
+
+impl IndexWriter {
+    pub(super) fn new(
+        fs: Arc<dyn Fs>,
+        path: PathBuf,
+        lang_caps: Arc<Vec<LanguageCaps>>,
+        build_caps: Arc<Vec<BuildCaps>>,
+    ) -> Self {
+        Self {
+            fs,
+            path,
+            lang_caps,
+            build_caps,
+            pending: watch::Sender::new(None),
+            persisted: Mutex::new(None),
+            flush_now: Notify::new(),
+            started: Once::new(),
+        }
+    }
+
+    /// Queue a committed snapshot for the background writer, starting it on
+    /// first use. It stops, after writing what is queued, once `cancel_token`
+    /// is cancelled.
+    pub(super) fn schedule(
+        self: &Arc<Self>,
+        graph: Arc<CodeGraph>,
+        cancel_token: &CancellationToken,
+    ) {
+        self.started.call_once(|| {
+            tokio::spawn(self.clone().run(cancel_token.clone()));
+        });
+        self.pending.send_replace(Some(graph));
+    }
+
+    /// Write the queued snapshot now, if there is one.
+    pub(super) async fn flush(&self) -> Result<()> {
+        self.flush_now.notify_waiters();
+        let mut persisted = self.persisted.lock().await;
+        match self.pending.send_replace(None) {
+            Some(graph) => self.write(&mut persisted, graph).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Write `graph`, superseding any queued snapshot.
+    pub(super) async fn save(&self, graph: Arc<CodeGraph>) -> Result<()> {
+        let mut persisted = self.persisted.lock().await;
+        self.pending.send_replace(None);
+        self.write(&mut persisted, graph).await
+    }
+
+    /// Record that the index on disk holds `epoch`, e.g. after loading it.
+    pub(super) async fn mark_persisted(&self, epoch: Option<u64>) {
+        *self.persisted.lock().await = epoch;
+    }
+
+    /// Run `clear` with writes held off, dropping any queued snapshot.
+    pub(super) async fn reset(&self, clear: impl FnOnce() -> Result<()>) -> Result<()> {
+        let mut persisted = self.persisted.lock().await;
+        self.pending.send_replace(None);
+        *persisted = None;
+        clear()
+    }
+
+    async fn write(&self, persisted: &mut Option<u64>, graph: Arc<CodeGraph>) -> Result<()> {
+        let epoch = graph.instance_id();
+        if *persisted == Some(epoch) {
+            return Ok(());
+        }
+        let fs = self.fs.clone();
+        let path = self.path.clone();
+        let lang_caps = self.lang_caps.clone();
+        let build_caps = self.build_caps.clone();
+        tokio::task::spawn_blocking(move || {
+            NaviscopeEngine::save_to_disk(fs.as_ref(), &graph, &path, lang_caps, build_caps)
+        })
+        .await
+        .map_err(|e| NaviscopeError::Internal(e.to_string()))??;
+        *persisted = Some(epoch);
+        Ok(())
+    }
+
+    async fn run(self: Arc<Self>, cancel_token: CancellationToken) {
+        let mut pending = self.pending.subscribe();
+        loop {
+            tokio::select! {
+                changed = pending.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                }
+                _ = cancel_token.cancelled() => break,
+            }
+            // Let further commits replace the snapshot before writing it
+            tokio::select! {
+                _ = tokio::time::sleep(COALESCE_DELAY) => {}
+                _ = self.flush_now.notified() => {}
+                _ = cancel_token.cancelled() => {}
+            }
+            if let Err(e) = self.flush().await {
+                tracing::warn!("Failed to save index: {}", e);
+            }
+        }
+        if let Err(e) = self.flush().await {
+            tracing::warn!("Failed to save index: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::NodeKind;
+    use crate::model::builder::CodeGraphBuilder;
+    use crate::platform::MemoryFs;
+    use crate::test_support::node;
+
+    fn graph(fqns: &[&str]) -> Arc<CodeGraph> {
+        let mut builder = CodeGraphBuilder::new();
+        for fqn in fqns {
+            builder.add_node(node(fqn, NodeKind::Class));
+        }
+        Arc::new(builder.build())
+    }
+
+    fn writer(fs: Arc<MemoryFs>) -> Arc<IndexWriter> {
+        Arc::new(IndexWriter::new(
+            fs,
+            PathBuf::from("/idx/p.bin"),
+            Arc::new(Vec::new()),
+            Arc::new(Vec::new()),
+        ))
+    }
+
+    fn load(fs: &MemoryFs) -> Option<CodeGraph> {
+        NaviscopeEngine::load_from_disk(
+            fs,
+            &PathBuf::from("/idx/p.bin"),
+            Arc::new(Vec::new()),
+            Arc::new(Vec::new()),
+            true,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn queued_commits_coalesce_into_the_latest_snapshot() {
+        let fs = Arc::new(MemoryFs::new());
+        let writer = writer(fs.clone());
+        let cancel_token = CancellationToken::new();
+
+        writer.schedule(graph(&["a.A"]), &cancel_token);
+        writer.schedule(graph(&["a.A", "a.B"]), &cancel_token);
+        assert!(load(&fs).is_none(), "commits must not wait for the disk");
+
+        writer.flush().await.unwrap();
+        assert_eq!(load(&fs).unwrap().node_count(), 2);
+
+        // The background task writes on its own once commits pause
+        writer.schedule(graph(&["a.A", "a.B", "a.C"]), &cancel_token);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while load(&fs).unwrap().node_count() != 3 {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("queued snapshot should be written in the background");
+        cancel_token.cancel();
+    }
+
+    #[tokio::test]
+    async fn reset_drops_the_queued_snapshot() {
+        let fs = Arc::new(MemoryFs::new());
+        let writer = writer(fs.clone());
+        let cancel_token = CancellationToken::new();
+
+        writer.schedule(graph(&["a.A"]), &cancel_token);
+        writer.reset(|| Ok(())).await.unwrap();
+        writer.flush().await.unwrap();
+        assert!(load(&fs).is_none());
+        cancel_token.cancel();
+    }
+}
@@ -0,0 +1,143 @@
+//! Access counts of graph nodes
+//!
+//! Inspecting a node with `cat` and landing on it with goto each count as a
+//! hit. Symbol searches break ties by these counts, so the symbols people
+//! actually navigate to come first. Like notes, counts are keyed by FQN and
+//! kept in a JSON file next to the index. They are written when the index is
+//! saved and every few dozen hits in between.
+
+use super::*;
+use crate::features::query::Popularity;
+
+/// Hits recorded before the counts are written without waiting for a save
+const FLUSH_INTERVAL: usize = 32;
+
+#[derive(Default)]
+struct Counts {
+    counts: Arc<Popularity>,
+    /// Hits recorded since the last write
+    unsaved: usize,
+}
+
+pub(super) struct PopularityStore {
+    fs: Arc<dyn Fs>,
+    path: PathBuf,
+    /// Loaded on first use
+    counts: std::sync::Mutex<Option<Counts>>,
+}
+
+impl PopularityStore {
+    pub(super) fn new(fs: Arc<dyn Fs>, index_path: &Path) -> Self {
+        Self {
+            fs,
+            path: index_path.with_extension("popularity.json"),
+            counts: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Run `f` on the counts, loading them first if needed.
+    fn with_counts<T>(&self, f: impl FnOnce(&mut Counts) -> T) -> Result<T> {
+        let mut guard = self.counts.lock().unwrap();
+        if guard.is_none() {
+            let counts = if self.fs.exists(&self.path) {
+                serde_json::from_slice(&self.fs.read(&self.path)?)?
+            } else {
+                Popularity::new()
+            };
+            *guard = Some(Counts {
+                counts: Arc::new(counts),
+                unsaved: 0,
+            });
+        }
+        Ok(f(guard.as_mut().unwrap()))
+    }
+
+    fn store(&self, counts: &mut Counts) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            self.fs.create_dir_all(parent)?;
+        }
+        let temp_path = self.path.with_extension("tmp");
+        self.fs
+            .write(&temp_path, &serde_json::to_vec(counts.counts.as_ref())?)?;
+        self.fs.rename(&temp_path, &self.path)?;
+        counts.unsaved = 0;
+        Ok(())
+    }
+
+    pub(super) fn snapshot(&self) -> Result<Arc<Popularity>> {
+        self.with_counts(|counts| counts.counts.clone())
+    }
+
+    pub(super) fn record(&self, fqns: &[String]) -> Result<()> {
+        if fqns.is_empty() {
+            return Ok(());
+        }
+        self.with_counts(|counts| {
+            let map = Arc::make_mut(&mut counts.counts);
+            for fqn in fqns {
+                *map.entry(fqn.clone()).or_default() += 1;
+            }
+            counts.unsaved += fqns.len();
+            if counts.unsaved >= FLUSH_INTERVAL {
+                self.store(counts)
+            } else {
+                Ok(())
+            }
+        })?
+    }
+
+    /// Write hits recorded since the last write
+    pub(super) fn flush(&self) -> Result<()> {
+        let mut guard = self.counts.lock().unwrap();
+        match guard.as_mut() {
+            Some(counts) if counts.unsaved > 0 => self.store(counts),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl NaviscopeEngine {
+    /// Access counts of nodes by FQN
+    pub fn popularity(&self) -> Result<Arc<Popularity>> {
+        self.popularity.snapshot()
+    }
+
+    /// Count a hit on each of `fqns`; not counted on a read-only index
+    pub fn record_hits(&self, fqns: &[String]) -> Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        self.popularity.record(fqns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::MemoryFs;
+
+    #[test]
+    fn hits_are_written_on_flush_and_read_back() {
+        let fs = Arc::new(MemoryFs::new());
+        let index_path = PathBuf::from("/idx/p.bin");
+        let store = PopularityStore::new(fs.clone(), &index_path);
+
+        let before = store.snapshot().unwrap();
+        store
+            .record(&["a.A".to_string(), "a.B".to_string(), "a.A".to_string()])
+            .unwrap();
+        // Snapshots taken earlier are not affected
+        assert!(before.is_empty());
+        assert_eq!(store.snapshot().unwrap().get("a.A"), Some(&2));
+
+        // Nothing is written until a flush or enough hits
+        let reopened = PopularityStore::new(fs.clone(), &index_path);
+        assert!(reopened.snapshot().unwrap().is_empty());
+
+        store.flush().unwrap();
+        let reopened = PopularityStore::new(fs.clone(), &index_path);
+        let counts = reopened.snapshot().unwrap();
+        assert_eq!(counts.get("a.A"), Some(&2));
+        assert_eq!(counts.get("a.B"), Some(&1));
+    }
+}
@@ -0,0 +1,139 @@
+//! Progress of index updates, by the epoch they started from
+//!
+//! The running update reports its phases and committed batches here. Callers
+//! read the latest state through `epoch_progress` or follow it through
+//! `subscribe_progress`; only the latest update is kept.
+
+use naviscope_api::lifecycle::{EpochProgress, IndexPhase, IndexProgress};
+use tokio::sync::watch;
+
+#[derive(Debug)]
+pub(super) struct EpochTracker {
+    progress: watch::Sender<Option<EpochProgress>>,
+}
+
+impl Default for EpochTracker {
+    fn default() -> Self {
+        Self {
+            progress: watch::channel(None).0,
+        }
+    }
+}
+
+impl EpochTracker {
+    /// An update of `submitted` files started from the snapshot of `epoch`
+    pub(super) fn start(&self, epoch: u64, submitted: usize) {
+        self.progress.send_replace(Some(EpochProgress {
+            epoch,
+            submitted,
+            committed: 0,
+            current: IndexProgress {
+                phase: IndexPhase::Scanning,
+                done: 0,
+                total: 0,
+            },
+            percent: 0,
+            finished: false,
+        }));
+    }
+
+    pub(super) fn report(&self, current: IndexProgress) {
+        self.update(|progress| progress.current = current);
+    }
+
+    /// A batch of `files` was committed before the update finished; the
+    /// phases start over for the next one
+    pub(super) fn committed(&self, files: usize) {
+        self.update(|progress| {
+            progress.committed = (progress.committed + files).min(progress.submitted);
+            progress.current = IndexProgress {
+                phase: IndexPhase::Scanning,
+                done: 0,
+                total: 0,
+            };
+        });
+    }
+
+    /// The update ended, committing all its files if `committed` is set
+    pub(super) fn finish(&self, committed: bool) {
+        self.update(|progress| {
+            if committed {
+                progress.committed = progress.submitted;
+                progress.percent = 100;
+            }
+            progress.finished = true;
+        });
+    }
+
+    pub(super) fn get(&self, epoch: u64) -> Option<EpochProgress> {
+        self.progress
+            .borrow()
+            .filter(|progress| progress.epoch == epoch)
+    }
+
+    /// Receiver seeing the changes made from now on
+    pub(super) fn subscribe(&self) -> watch::Receiver<Option<EpochProgress>> {
+        self.progress.subscribe()
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut EpochProgress)) {
+        self.progress.send_if_modified(|progress| {
+            let Some(progress) = progress.as_mut().filter(|p| !p.finished) else {
+                return false;
+            };
+            let before = *progress;
+            apply(progress);
+            progress.percent = progress.percent.max(percent_of(progress));
+            *progress != before
+        });
+    }
+}
+
+/// Committed files plus the share of the rest the running phases account for
+fn percent_of(progress: &EpochProgress) -> u32 {
+    let phases = progress.current.overall_percentage();
+    if progress.submitted == 0 {
+        return phases;
+    }
+    let committed = (progress.committed * 100 / progress.submitted) as u32;
+    committed + (100 - committed) * phases / 100
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(phase: IndexPhase, done: usize, total: usize) -> IndexProgress {
+        IndexProgress { phase, done, total }
+    }
+
+    #[test]
+    fn batches_move_the_percentage_forward_only() {
+        let tracker = EpochTracker::default();
+        tracker.start(7, 100);
+        let mut updates = tracker.subscribe();
+
+        tracker.report(at(IndexPhase::Resolving, 50, 50));
+        assert_eq!(tracker.get(7).unwrap().percent, 90);
+        assert!(updates.has_changed().unwrap());
+
+        // The next batch starts its phases over
+        tracker.committed(50);
+        tracker.report(at(IndexPhase::Parsing, 0, 50));
+        let progress = tracker.get(7).unwrap();
+        assert_eq!((progress.committed, progress.percent), (50, 90));
+        tracker.report(at(IndexPhase::Resolving, 50, 50));
+        assert_eq!(tracker.get(7).unwrap().percent, 95);
+
+        tracker.finish(true);
+        let progress = *updates.borrow_and_update();
+        let progress = progress.unwrap();
+        assert!(progress.finished);
+        assert_eq!((progress.committed, progress.percent), (100, 100));
+        assert!(tracker.get(8).is_none());
+
+        // Late reports of a finished update are ignored
+        tracker.report(at(IndexPhase::Parsing, 0, 1));
+        assert!(!updates.has_changed().unwrap());
+    }
+}
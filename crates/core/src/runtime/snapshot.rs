@@ -0,0 +1,331 @@
+//! Named graph snapshots for `diff`
+//!
+//! A snapshot is the whole graph in the single-file format, stored next to
+//! the index in a `.snapshots` directory. Snapshots are never loaded as the
+//! current graph; they only serve as sides of a comparison.
+//!
+//! A git revision gets a snapshot named after its commit. Its files are
+//! read from the object store into a scratch directory next to the
+//! snapshots and indexed there by a separate engine with the same plugins
+//! and configuration, so neither the working tree nor the index changes.
+//! The paths in its graph are then moved back under the project root, so
+//! revisions compare with each other and with the current graph.
+
+use super::*;
+use crate::features::diff::{GraphDelta, GraphDiff};
+use crate::history::{resolve_commit, write_tree};
+
+/// Extension of snapshot files
+const SNAPSHOT_EXTENSION: &str = "bin";
+
+impl NaviscopeEngine {
+    /// Directory holding the snapshots of the index at `index_path`
+    fn snapshot_dir(index_path: &Path) -> PathBuf {
+        index_path.with_extension("snapshots")
+    }
+
+    /// File for `reference`: a snapshot name, or a path if it contains a
+    /// separator or ends in `.bin`.
+    fn snapshot_path(index_path: &Path, reference: &str) -> Result<PathBuf> {
+        let is_path = reference.contains(std::path::MAIN_SEPARATOR)
+            || reference.contains('/')
+            || reference.ends_with(".bin");
+        if is_path {
+            return Ok(PathBuf::from(reference));
+        }
+        let valid = !reference.is_empty()
+            && reference
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+        if !valid {
+            return Err(NaviscopeError::Internal(format!(
+                "Invalid snapshot name '{}': use letters, digits, '.', '_' or '-'",
+                reference
+            )));
+        }
+        Ok(Self::snapshot_dir(index_path).join(format!("{}.{}", reference, SNAPSHOT_EXTENSION)))
+    }
+
+    /// Save the current graph as the snapshot `name`, replacing any previous one.
+    pub async fn save_snapshot(&self, name: &str) -> Result<PathBuf> {
+        self.ensure_writable()?;
+        let path = Self::snapshot_path(&self.index_path, name)?;
+        self.write_snapshot(self.snapshot().await, path).await
+    }
+
+    async fn write_snapshot(&self, graph: CodeGraph, path: PathBuf) -> Result<PathBuf> {
+        let lang_caps = self.lang_caps.clone();
+        let build_caps = self.build_caps.clone();
+        let fs = self.fs.clone();
+
+        tokio::task::spawn_blocking(move || -> Result<PathBuf> {
+            let bytes = graph.serialize(storage::codec_lookup(&lang_caps, &build_caps))?;
+            if let Some(parent) = path.parent() {
+                fs.create_dir_all(parent)?;
+            }
+            let temp_path = path.with_extension("tmp");
+            fs.write(&temp_path, &bytes)?;
+            fs.rename(&temp_path, &path)?;
+            Ok(path)
+        })
+        .await
+        .map_err(|e| NaviscopeError::Internal(e.to_string()))?
+    }
+
+    /// Index the git revision `rev` of the project into the snapshot named
+    /// after its commit, replacing any previous one.
+    pub async fn index_revision(&self, rev: &str) -> Result<PathBuf> {
+        let commit = resolve_commit(&self.project_root, rev).ok_or_else(|| {
+            NaviscopeError::Internal(format!(
+                "'{}' is not a git revision of {}",
+                rev,
+                self.project_root.display()
+            ))
+        })?;
+        self.index_commit(&commit).await
+    }
+
+    async fn index_commit(&self, commit: &str) -> Result<PathBuf> {
+        self.ensure_writable()?;
+        let path = Self::snapshot_path(&self.index_path, commit)?;
+        let scratch = path.with_extension("work");
+        if scratch.exists() {
+            std::fs::remove_dir_all(&scratch)?;
+        }
+        let result = self.index_tree(commit, &scratch, path).await;
+        if let Err(e) = std::fs::remove_dir_all(&scratch) {
+            tracing::warn!("Failed to remove {}: {}", scratch.display(), e);
+        }
+        result
+    }
+
+    /// Write the project's files at `commit` to `scratch`, index them with
+    /// an index of their own there, and save the graph to `path` with its
+    /// paths moved back to the project root.
+    async fn index_tree(&self, commit: &str, scratch: &Path, path: PathBuf) -> Result<PathBuf> {
+        let tree = scratch.join("src");
+        let (root, revision) = (self.project_root.clone(), commit.to_string());
+        let target = tree.clone();
+        let files = tokio::task::spawn_blocking(move || write_tree(&root, &revision, &target))
+            .await
+            .map_err(|e| NaviscopeError::Internal(e.to_string()))??;
+        tracing::info!("Indexing {} files of {}", files, commit);
+
+        let mut config = (*self.config()).clone();
+        config.index.dir = Some(scratch.join("index"));
+        config.index.op_log = None;
+        let mut builder = NaviscopeEngine::builder(tree.clone())
+            .with_config(config)
+            .with_fs(self.fs.clone())
+            .with_clock(self.clock.clone());
+        for caps in self.lang_caps.iter() {
+            builder = builder.with_language_caps(caps.clone());
+        }
+        for caps in self.build_caps.iter() {
+            builder = builder.with_build_caps(caps.clone());
+        }
+        for resolver in self.linker.resolvers() {
+            builder = builder.with_link_resolver(resolver.clone());
+        }
+        let engine = builder.build();
+        engine.rebuild().await?;
+        let graph = engine
+            .snapshot()
+            .await
+            .relocate(&tree, &self.project_root)?;
+        self.write_snapshot(graph, path).await
+    }
+
+    /// File of `reference`: the snapshot or snapshot file it names, otherwise
+    /// the snapshot of the git revision it names, indexed on first use.
+    async fn snapshot_file(&self, reference: &str) -> Result<PathBuf> {
+        if let Ok(path) = Self::snapshot_path(&self.index_path, reference)
+            && self.fs.exists(&path)
+        {
+            return Ok(path);
+        }
+        let Some(commit) = resolve_commit(&self.project_root, reference) else {
+            return Err(NaviscopeError::Internal(format!(
+                "No snapshot or git revision named '{}'",
+                reference
+            )));
+        };
+        let path = Self::snapshot_path(&self.index_path, &commit)?;
+        if self.fs.exists(&path) {
+            return Ok(path);
+        }
+        self.index_commit(&commit).await
+    }
+
+    async fn read_snapshot(&self, path: PathBuf) -> Result<CodeGraph> {
+        let lang_caps = self.lang_caps.clone();
+        let build_caps = self.build_caps.clone();
+        let fs = self.fs.clone();
+        tokio::task::spawn_blocking(move || -> Result<CodeGraph> {
+            let bytes = fs.read(&path)?;
+            CodeGraph::deserialize(&bytes, storage::codec_lookup(&lang_caps, &build_caps))
+        })
+        .await
+        .map_err(|e| NaviscopeError::Internal(e.to_string()))?
+    }
+
+    /// Changes from the snapshot or git revision `reference` to the current graph.
+    pub async fn diff_snapshot(&self, reference: &str) -> Result<GraphDelta> {
+        let old = self
+            .read_snapshot(self.snapshot_file(reference).await?)
+            .await?;
+        let current = Arc::new(self.snapshot().await);
+        self.compare(old, current).await
+    }
+
+    /// Changes from the snapshot or git revision `old` to `new`.
+    pub async fn diff_snapshots(&self, old: &str, new: &str) -> Result<GraphDelta> {
+        let old = self.read_snapshot(self.snapshot_file(old).await?).await?;
+        let new = self.read_snapshot(self.snapshot_file(new).await?).await?;
+        self.compare(old, Arc::new(new)).await
+    }
+
+    async fn compare(&self, old: CodeGraph, new: Arc<CodeGraph>) -> Result<GraphDelta> {
+        let conventions = (*self.naming_conventions).clone();
+        tokio::task::spawn_blocking(move || GraphDiff::new(conventions).compute(&old, new.as_ref()))
+            .await
+            .map_err(|e| NaviscopeError::Internal(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_references_resolve_to_names_or_paths() {
+        let index = Path::new("/idx/0001.bin");
+        assert_eq!(
+            NaviscopeEngine::snapshot_path(index, "main").unwrap(),
+            PathBuf::from("/idx/0001.snapshots/main.bin")
+        );
+        assert_eq!(
+            NaviscopeEngine::snapshot_path(index, "/tmp/base.bin").unwrap(),
+            PathBuf::from("/tmp/base.bin")
+        );
+        assert!(NaviscopeEngine::snapshot_path(index, "a b").is_err());
+        assert!(NaviscopeEngine::snapshot_path(index, "").is_err());
+    }
+
+    #[tokio::test]
+    async fn saved_snapshot_diffs_against_current_graph() {
+        let fs = Arc::new(crate::platform::MemoryFs::new());
+        let engine = NaviscopeEngine::builder(PathBuf::from("."))
+            .with_fs(fs.clone())
+            .build();
+
+        let path = engine.save_snapshot("base").await.unwrap();
+        assert!(fs.exists(&path));
+        assert!(engine.diff_snapshot("base").await.unwrap().is_empty());
+        assert!(engine.diff_snapshot("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn git_revisions_are_indexed_into_snapshots() {
+        let repo = tempfile::tempdir().unwrap();
+        let root = repo.path().canonicalize().unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&root)
+                .args(["-c", "user.name=dev", "-c", "user.email=dev@example.com"])
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        std::fs::write(root.join("notes.txt"), "v1").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "first"]);
+
+        let index = tempfile::tempdir().unwrap();
+        let mut config = ProjectConfig::default();
+        config.index.dir = Some(index.path().to_path_buf());
+        let fs = Arc::new(crate::platform::MemoryFs::new());
+        let engine = NaviscopeEngine::builder(root.clone())
+            .with_fs(fs.clone())
+            .with_config(config)
+            .build();
+
+        let path = engine.index_revision("HEAD").await.unwrap();
+        let commit = crate::history::resolve_commit(&root, "HEAD").unwrap();
+        assert_eq!(
+            path,
+            engine
+                .index_path
+                .with_extension("snapshots")
+                .join(format!("{}.bin", commit))
+        );
+        assert!(fs.exists(&path));
+        assert!(!path.with_extension("work").exists());
+        assert!(
+            engine
+                .diff_snapshots("HEAD", &commit)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(engine.index_revision("no-such-branch").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn revisions_compare_by_project_paths() {
+        let repo = tempfile::tempdir().unwrap();
+        let root = repo.path().canonicalize().unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&root)
+                .args(["-c", "user.name=dev", "-c", "user.email=dev@example.com"])
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        let source = root.join("src/p/A.java");
+        std::fs::create_dir_all(source.parent().unwrap()).unwrap();
+        git(&["init", "-q"]);
+        std::fs::write(
+            &source,
+            "package p;\npublic class A {\n    void a() {}\n}\n",
+        )
+        .unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "first"]);
+        let first = crate::history::resolve_commit(&root, "HEAD").unwrap();
+        std::fs::write(
+            &source,
+            "package p;\npublic class A {\n    void a() {} void b() {}\n}\n",
+        )
+        .unwrap();
+        git(&["commit", "-q", "-a", "-m", "second"]);
+
+        let index = tempfile::tempdir().unwrap();
+        let mut config = ProjectConfig::default();
+        config.index.dir = Some(index.path().to_path_buf());
+        let engine = NaviscopeEngine::builder(root.clone())
+            .with_config(config)
+            .with_language_caps(naviscope_java::java_caps().unwrap())
+            .build();
+        engine.rebuild().await.unwrap();
+
+        let delta = engine.diff_snapshots(&first, "HEAD").await.unwrap();
+        assert_eq!(delta.added_nodes.len(), 1, "{:?}", delta);
+        assert_eq!(delta.added_nodes[0].fqn, "p.A#b()");
+        assert_eq!(
+            delta.added_nodes[0].path.as_deref(),
+            Some(&*source.to_string_lossy())
+        );
+        assert!(delta.removed_nodes.is_empty(), "{:?}", delta);
+        assert!(delta.modified_nodes.is_empty(), "{:?}", delta);
+
+        let delta = engine.diff_snapshot("HEAD").await.unwrap();
+        assert!(delta.is_empty(), "{:?}", delta);
+    }
+}
@@ -0,0 +1,191 @@
+//! Contents of source files, shared by the features reading them
+//!
+//! Reference search, call hierarchy, document symbols and snippets all need
+//! the text of indexed files. The store decodes each file once, indexes its
+//! lines and serves the same text until the file's modification time changes
+//! or an update of the file is reported, so features never disagree about
+//! what a file contains.
+
+use crate::platform::Fs;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Files kept before the store is reset
+const CAPACITY: usize = 256;
+
+/// Decoded text of a file with its line index
+#[derive(Debug)]
+pub struct SourceText {
+    text: Arc<str>,
+    /// Byte offset at which each line starts
+    line_starts: Vec<usize>,
+    fingerprint: u64,
+}
+
+impl SourceText {
+    pub fn new(text: impl Into<Arc<str>>) -> Self {
+        let text: Arc<str> = text.into();
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        let fingerprint = xxh3_64(text.as_bytes());
+        Self {
+            text,
+            line_starts,
+            fingerprint,
+        }
+    }
+
+    /// Decode file contents: UTF-8, with or without a byte order mark, or
+    /// UTF-16 with one. Other bytes are read as Latin-1, so that every byte
+    /// stays one character and columns keep their meaning.
+    pub fn decode(bytes: &[u8]) -> Self {
+        let utf16 = |bytes: &[u8], unit: fn([u8; 2]) -> u16| {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|pair| unit([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        };
+        let text = match bytes {
+            [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+            [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+            [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+            _ => match std::str::from_utf8(bytes) {
+                Ok(text) => text.to_string(),
+                Err(_) => bytes.iter().map(|&b| b as char).collect(),
+            },
+        };
+        Self::new(text)
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The text, shared rather than copied
+    pub fn shared(&self) -> Arc<str> {
+        self.text.clone()
+    }
+
+    /// Hash of the text, e.g. to key caches of what is derived from it
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The 0-based line `line`, without its line terminator
+    pub fn line(&self, line: usize) -> Option<&str> {
+        let start = *self.line_starts.get(line)?;
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .map_or(self.text.len(), |next| next - 1);
+        let text = &self.text[start..end];
+        Some(text.strip_suffix('\r').unwrap_or(text))
+    }
+
+    /// Byte offset of the start of `line`
+    pub fn line_offset(&self, line: usize) -> Option<usize> {
+        self.line_starts.get(line).copied()
+    }
+}
+
+pub struct SourceStore {
+    fs: Arc<dyn Fs>,
+    entries: Mutex<HashMap<PathBuf, (SystemTime, Arc<SourceText>)>>,
+}
+
+impl SourceStore {
+    pub fn new(fs: Arc<dyn Fs>) -> Self {
+        Self {
+            fs,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Text of the file at `path`, read again only when it was modified.
+    pub fn read(&self, path: &Path) -> io::Result<Arc<SourceText>> {
+        let modified = self.fs.modified(path)?;
+        if let Some((cached, text)) = self.entries.lock().unwrap().get(path)
+            && *cached == modified
+        {
+            return Ok(text.clone());
+        }
+
+        let text = Arc::new(SourceText::decode(&self.fs.read(path)?));
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= CAPACITY && !entries.contains_key(path) {
+            entries.clear();
+        }
+        entries.insert(path.to_path_buf(), (modified, text.clone()));
+        Ok(text)
+    }
+
+    /// Forget `paths`, e.g. when the watcher reports them changed. Their
+    /// modification time alone may not tell, when it has a coarse resolution.
+    pub fn invalidate<'a>(&self, paths: impl IntoIterator<Item = &'a Path>) {
+        let mut entries = self.entries.lock().unwrap();
+        for path in paths {
+            entries.remove(path);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::{ManualClock, MemoryFs};
+
+    #[test]
+    fn lines_are_indexed_without_terminators() {
+        let text = SourceText::new("class A {\r\n  int x;\n}");
+        assert_eq!(text.line_count(), 3);
+        assert_eq!(text.line(0), Some("class A {"));
+        assert_eq!(text.line(1), Some("  int x;"));
+        assert_eq!(text.line(2), Some("}"));
+        assert_eq!(text.line(3), None);
+        assert_eq!(text.line_offset(2), Some(20));
+
+        // Byte order marks are dropped and UTF-16 decoded
+        assert_eq!(SourceText::decode(b"\xEF\xBB\xBFint").text(), "int");
+        assert_eq!(SourceText::decode(b"\xFF\xFEi\0n\0t\0").text(), "int");
+        // Latin-1 keeps one character per byte
+        assert_eq!(SourceText::decode(b"caf\xE9;").text(), "café;");
+    }
+
+    #[test]
+    fn files_are_read_again_when_modified_or_invalidated() {
+        let clock = Arc::new(ManualClock::default());
+        let fs = Arc::new(MemoryFs::with_clock(clock.clone()));
+        let path = Path::new("/p/A.java");
+        fs.write(path, b"class A {}").unwrap();
+        let store = SourceStore::new(fs.clone());
+
+        let first = store.read(path).unwrap();
+        assert!(Arc::ptr_eq(&first, &store.read(path).unwrap()));
+
+        // Rewritten within the same clock tick: only an update reveals it
+        fs.write(path, b"class B {}").unwrap();
+        assert_eq!(store.read(path).unwrap().text(), "class A {}");
+        store.invalidate([path]);
+        assert_eq!(store.read(path).unwrap().text(), "class B {}");
+
+        clock.advance(std::time::Duration::from_secs(1));
+        fs.write(path, b"class C {}").unwrap();
+        let third = store.read(path).unwrap();
+        assert_eq!(third.text(), "class C {}");
+        assert_ne!(third.fingerprint(), first.fingerprint());
+    }
+}
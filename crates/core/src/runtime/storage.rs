@@ -1,16 +1,58 @@
+use super::bundle::BundleHeader;
+use super::manifest::{IndexManifest, ShardEntry};
+use super::partial::ShardedIndex;
 use super::*;
+use crate::model::storage::shard::{self, CoreShard, FQN_CHUNK, STRING_CHUNK, encode_shard};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+type CodecLookup<'a> =
+    dyn Fn(&str) -> Option<Arc<dyn naviscope_plugin::NodeMetadataCodec>> + Sync + 'a;
+
+/// Metadata codec of the language or build tool named `lang`
+pub(super) fn codec_lookup<'a>(
+    lang_caps: &'a [LanguageCaps],
+    build_caps: &'a [BuildCaps],
+) -> impl Fn(&str) -> Option<Arc<dyn naviscope_plugin::NodeMetadataCodec>> + Sync + 'a {
+    move |lang: &str| {
+        for caps in lang_caps.iter() {
+            if caps.language.as_str() == lang {
+                return caps.metadata_codec.metadata_codec();
+            }
+        }
+        for caps in build_caps.iter() {
+            if caps.build_tool.as_str() == lang {
+                return caps.metadata_codec.metadata_codec();
+            }
+        }
+        None
+    }
+}
 
 impl NaviscopeEngine {
     /// Clear the index for the current project
     pub async fn clear_project_index(&self) -> Result<()> {
-        let path = self.index_path.clone();
-        if path.exists() {
-            tokio::fs::remove_file(path).await?;
-        }
+        self.ensure_writable()?;
+        // A pending background write would bring the index back
+        self.writer
+            .reset(|| {
+                if self.fs.exists(&self.index_path) {
+                    self.fs.remove_file(&self.index_path)?;
+                }
+                let shard_dir = IndexManifest::shard_dir(&self.index_path);
+                if self.fs.exists(&shard_dir) {
+                    self.fs.remove_dir_all(&shard_dir)?;
+                }
+                super::journal::BuildJournal::remove(
+                    self.fs.as_ref(),
+                    &super::journal::BuildJournal::path_for(&self.index_path),
+                )
+            })
+            .await?;
+        self.warnings.clear()?;
 
         // Reset current graph
-        let mut lock = self.current.write().await;
-        *lock = Arc::new(CodeGraph::empty());
+        self.apply_graph_snapshot(CodeGraph::empty()).await;
 
         Ok(())
     }
@@ -37,31 +79,37 @@ impl NaviscopeEngine {
     // ---- Helper methods ----
 
     pub(super) fn load_from_disk(
+        fs: &dyn Fs,
         path: &Path,
         lang_caps: Arc<Vec<LanguageCaps>>,
         build_caps: Arc<Vec<BuildCaps>>,
+        discard: bool,
     ) -> Result<Option<CodeGraph>> {
-        if !path.exists() {
+        if !fs.exists(path) {
             return Ok(None);
         }
 
-        let bytes = std::fs::read(path)?;
+        let bytes = fs.read(path)?;
+        let get_codec = codec_lookup(&lang_caps, &build_caps);
 
-        let get_codec = |lang: &str| -> Option<Arc<dyn naviscope_plugin::NodeMetadataCodec>> {
-            for caps in lang_caps.iter() {
-                if caps.language.as_str() == lang {
-                    return caps.metadata_codec.metadata_codec();
-                }
-            }
-            for caps in build_caps.iter() {
-                if caps.build_tool.as_str() == lang {
-                    return caps.metadata_codec.metadata_codec();
-                }
-            }
-            None
+        let loaded = if IndexManifest::is_manifest(&bytes) {
+            IndexManifest::decode(&bytes)
+                .and_then(|manifest| Self::load_shards(fs, path, manifest, &get_codec))
+        } else if BundleHeader::is_bundle(&bytes) {
+            BundleHeader::decode(&bytes).and_then(|(header, graph)| {
+                tracing::info!(
+                    "Opening index bundle built at {} ({} stubbed packages)",
+                    header.project_root.display(),
+                    header.stubs.len()
+                );
+                CodeGraph::deserialize(graph, &get_codec)
+            })
+        } else {
+            // Indices written before sharding are a single compressed graph
+            CodeGraph::deserialize(&bytes, &get_codec)
         };
 
-        match CodeGraph::deserialize(&bytes, get_codec) {
+        match loaded {
             Ok(graph) => {
                 if graph.version() != crate::model::graph::CURRENT_VERSION {
                     tracing::warn!(
@@ -70,7 +118,9 @@ impl NaviscopeEngine {
                         graph.version(),
                         crate::model::graph::CURRENT_VERSION
                     );
-                    let _ = std::fs::remove_file(path);
+                    if discard {
+                        Self::discard_index(fs, path);
+                    }
                     return Ok(None);
                 }
                 tracing::info!("Loaded index from {}", path.display());
@@ -82,46 +132,199 @@ impl NaviscopeEngine {
                     path.display(),
                     e
                 );
-                let _ = std::fs::remove_file(path);
+                if discard {
+                    Self::discard_index(fs, path);
+                }
                 Ok(None)
             }
         }
     }
 
+    /// Map every shard named by `manifest` and decode them in parallel.
+    fn load_shards(
+        fs: &dyn Fs,
+        path: &Path,
+        manifest: IndexManifest,
+        get_codec: &CodecLookup<'_>,
+    ) -> Result<CodeGraph> {
+        let index = ShardedIndex::open(fs, path, manifest)?;
+        if index.version != crate::model::graph::CURRENT_VERSION {
+            return Ok(CodeGraph::from_shards(
+                index.version,
+                &index.fqns,
+                std::iter::empty(),
+                index.log(),
+            ));
+        }
+        let shards = index.read_shards(fs, index.manifest.shards.keys(), get_codec)?;
+        Ok(CodeGraph::from_shards(
+            index.version,
+            &index.fqns,
+            &shards,
+            index.log(),
+        ))
+    }
+
+    /// Remove an unusable index and its shards so the next load starts clean.
+    fn discard_index(fs: &dyn Fs, path: &Path) {
+        let _ = fs.remove_file(path);
+        let dir = IndexManifest::shard_dir(path);
+        if fs.exists(&dir) {
+            let _ = fs.remove_dir_all(&dir);
+        }
+    }
+
     pub(super) fn save_to_disk(
+        fs: &dyn Fs,
         graph: &CodeGraph,
         path: &Path,
         lang_caps: Arc<Vec<LanguageCaps>>,
         build_caps: Arc<Vec<BuildCaps>>,
     ) -> Result<()> {
-        // Ensure directory exists
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
+        let dir = IndexManifest::shard_dir(path);
+        fs.create_dir_all(&dir)?;
+        let previous = IndexManifest::read(fs, path);
+        let log = graph.shard_log();
+        let layout = graph.shard_layout();
 
-        let get_codec = |lang: &str| -> Option<Arc<dyn naviscope_plugin::NodeMetadataCodec>> {
-            for caps in lang_caps.iter() {
-                if caps.language.as_str() == lang {
-                    return caps.metadata_codec.metadata_codec();
-                }
+        // Shards unchanged since the saved epoch keep their files; a graph
+        // that does not descend from that epoch writes every shard
+        let changed = previous.as_ref().and_then(|previous| {
+            log.changed_since(previous.lineage, previous.epoch, graph.symbols())
+        });
+        let reused: BTreeMap<&str, &str> = match (&previous, &changed) {
+            (Some(previous), Some(changed)) => layout
+                .roots
+                .keys()
+                .filter(|key| !changed.contains(*key))
+                .filter_map(|key| Some((key.as_str(), previous.shards.get(key)?.file.as_str())))
+                .filter(|(_, file)| fs.exists(&dir.join(file)))
+                .collect(),
+            _ => BTreeMap::new(),
+        };
+        // The string table and FQN tree are shared with the previous save
+        // as long as the graph is of its lineage
+        let base = previous
+            .as_ref()
+            .filter(|previous| previous.lineage == log.lineage);
+
+        let get_codec = codec_lookup(&lang_caps, &build_caps);
+        let shards = graph.to_shards(|key| !reused.contains_key(key), &get_codec);
+        let encoded = shards
+            .par_iter()
+            .map(|(key, shard)| Ok((key.clone(), encode_shard(shard)?)))
+            .collect::<Result<BTreeMap<_, _>>>()?;
+
+        // Shards are content-addressed: an existing file already holds these bytes
+        let mut written = Vec::new();
+        let mut write_shard = |bytes: &[u8]| -> Result<String> {
+            let name = IndexManifest::shard_file_name(bytes);
+            let shard_path = dir.join(&name);
+            if !fs.exists(&shard_path) {
+                let temp_path = shard_path.with_extension("tmp");
+                fs.write(&temp_path, bytes)?;
+                fs.rename(&temp_path, &shard_path)?;
+                written.push(shard_path);
             }
-            for caps in build_caps.iter() {
-                if caps.build_tool.as_str() == lang {
-                    return caps.metadata_codec.metadata_codec();
+            Ok(name)
+        };
+        // A chunk that was full at the previous save still holds the same
+        // entries; only the chunks after it are written
+        let kept = |names: &[String], index: usize, full: bool| {
+            names
+                .get(index)
+                .filter(|name| full && fs.exists(&dir.join(name)))
+                .cloned()
+        };
+
+        // Encoding shards may intern strings, so the tables are read after
+        let fqns = graph.fqns();
+        let core = CoreShard {
+            version: graph.version(),
+            next_id: fqns.next_id.load(std::sync::atomic::Ordering::SeqCst),
+        };
+        let string_count = shard::string_count(graph.symbols());
+        let strings = (0..string_count.div_ceil(STRING_CHUNK))
+            .map(|i| {
+                let full = base.is_some_and(|base| (i + 1) * STRING_CHUNK <= base.string_count);
+                match kept(base.map_or(&[], |base| &base.strings), i, full) {
+                    Some(name) => Ok(name),
+                    None => write_shard(&encode_shard(&shard::string_chunk(
+                        graph.symbols(),
+                        string_count,
+                        i,
+                    ))?),
                 }
-            }
-            None
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let fqn_count = shard::fqn_count(fqns);
+        let fqn_chunks = (0..shard::fqn_chunks(core.next_id))
+            .map(|i| {
+                let full = base.is_some_and(|base| (i + 1) * FQN_CHUNK <= base.fqn_count as usize);
+                match kept(base.map_or(&[], |base| &base.fqns), i, full) {
+                    Some(name) => Ok(name),
+                    None => write_shard(&encode_shard(&shard::fqn_chunk(fqns, i))?),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut entries = BTreeMap::new();
+        let keys: BTreeSet<&String> = layout.roots.keys().chain(encoded.keys()).collect();
+        for key in keys {
+            let file = match (reused.get(key.as_str()), encoded.get(key)) {
+                (_, Some(bytes)) => write_shard(bytes)?,
+                (Some(file), None) => file.to_string(),
+                (None, None) => continue,
+            };
+            let entry = ShardEntry {
+                file,
+                roots: layout.roots.get(key).cloned().unwrap_or_default(),
+                links: layout
+                    .links
+                    .get(key)
+                    .map(|links| links.iter().cloned().collect())
+                    .unwrap_or_default(),
+            };
+            entries.insert(key.clone(), entry);
+        }
+
+        let manifest = IndexManifest {
+            lineage: log.lineage,
+            epoch: log.epoch(),
+            core: write_shard(&encode_shard(&core)?)?,
+            string_count,
+            strings,
+            fqn_count,
+            fqns: fqn_chunks,
+            shards: entries,
         };
 
-        // Serialize the graph
-        let bytes = graph.serialize(get_codec)?;
+        // Sync the new shards in one pass, before the manifest names them
+        written.par_iter().try_for_each(|shard| fs.sync(shard))?;
+        if !written.is_empty() {
+            fs.sync(&dir)?;
+        }
 
-        // Write to file atomically (write to temp, then rename)
-        let temp_path = path.with_extension("tmp");
-        std::fs::write(&temp_path, bytes)?;
-        std::fs::rename(temp_path, path)?;
+        manifest.save(fs, path)?;
+        fs.sync(path)?;
+        if let Some(parent) = path.parent() {
+            fs.sync(parent)?;
+        }
+
+        // Drop shards only the previous manifest referred to
+        if let Some(previous) = previous {
+            let current = manifest.files();
+            for name in previous.files().difference(&current) {
+                let _ = fs.remove_file(&dir.join(name));
+            }
+        }
 
-        tracing::info!("Saved index to {}", path.display());
+        tracing::info!(
+            "Saved index to {} ({} of {} shards written)",
+            path.display(),
+            written.len(),
+            manifest.files().len()
+        );
 
         Ok(())
     }
@@ -0,0 +1,44 @@
+//! Parsed trees of recently requested documents
+//!
+//! Position-based requests (folding, selection, signature help) arrive in
+//! bursts for the same buffer. Trees are keyed by path and content hash, so an
+//! unchanged document is parsed once.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tree_sitter::Tree;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Documents kept before the cache is reset
+const CAPACITY: usize = 32;
+
+#[derive(Default)]
+pub struct TreeCache {
+    entries: Mutex<HashMap<PathBuf, (u64, Tree)>>,
+}
+
+impl TreeCache {
+    /// Tree for `content`, calling `parse` only when the document changed.
+    pub fn get_or_parse(
+        &self,
+        path: &Path,
+        content: &str,
+        parse: impl FnOnce() -> Option<Tree>,
+    ) -> Option<Tree> {
+        let hash = xxh3_64(content.as_bytes());
+        if let Some((cached, tree)) = self.entries.lock().unwrap().get(path)
+            && *cached == hash
+        {
+            return Some(tree.clone());
+        }
+
+        let tree = parse()?;
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= CAPACITY && !entries.contains_key(path) {
+            entries.clear();
+        }
+        entries.insert(path.to_path_buf(), (hash, tree.clone()));
+        Some(tree)
+    }
+}
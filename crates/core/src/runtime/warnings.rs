@@ -0,0 +1,147 @@
+//! Index warnings kept with the index
+//!
+//! Each indexing run replaces the warnings of the files it indexed, so the
+//! store always describes the files as they are in the index. Like notes,
+//! the warnings live in a JSON file next to the index; it is rewritten when
+//! a run commits and its warnings differ from the stored ones.
+
+use super::*;
+use crate::indexing::warnings::RunWarnings;
+use naviscope_api::lifecycle::IndexWarning;
+
+pub(super) struct WarningStore {
+    fs: Arc<dyn Fs>,
+    path: PathBuf,
+    /// Loaded on first use, sorted by path
+    warnings: std::sync::Mutex<Option<Vec<IndexWarning>>>,
+}
+
+impl WarningStore {
+    pub(super) fn new(fs: Arc<dyn Fs>, index_path: &Path) -> Self {
+        Self {
+            fs,
+            path: index_path.with_extension("warnings.json"),
+            warnings: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Run `f` on the warnings, loading them first if needed.
+    fn with_warnings<T>(&self, f: impl FnOnce(&mut Vec<IndexWarning>) -> T) -> Result<T> {
+        let mut guard = self.warnings.lock().unwrap();
+        if guard.is_none() {
+            let warnings = if self.fs.exists(&self.path) {
+                serde_json::from_slice(&self.fs.read(&self.path)?)?
+            } else {
+                Vec::new()
+            };
+            *guard = Some(warnings);
+        }
+        Ok(f(guard.as_mut().unwrap()))
+    }
+
+    fn store(&self, warnings: &[IndexWarning]) -> Result<()> {
+        if warnings.is_empty() {
+            if self.fs.exists(&self.path) {
+                self.fs.remove_file(&self.path)?;
+            }
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            self.fs.create_dir_all(parent)?;
+        }
+        let temp_path = self.path.with_extension("tmp");
+        self.fs
+            .write(&temp_path, &serde_json::to_vec_pretty(warnings)?)?;
+        self.fs.rename(&temp_path, &self.path)?;
+        Ok(())
+    }
+
+    pub(super) fn list(&self) -> Result<Vec<IndexWarning>> {
+        self.with_warnings(|warnings| warnings.clone())
+    }
+
+    /// Replace the warnings of the files `run` indexed with the ones it found.
+    pub(super) fn commit(&self, run: RunWarnings) -> Result<()> {
+        self.with_warnings(|warnings| {
+            let before = warnings.len();
+            warnings.retain(|w| !run.indexed.contains(Path::new(&w.path)));
+            if warnings.len() == before && run.warnings.is_empty() {
+                return Ok(());
+            }
+            warnings.extend(run.warnings);
+            warnings.sort_by(|a, b| a.path.cmp(&b.path));
+            self.store(warnings)
+        })?
+    }
+
+    /// Forget all warnings, before a build from scratch.
+    pub(super) fn clear(&self) -> Result<()> {
+        self.with_warnings(|warnings| {
+            warnings.clear();
+            self.store(warnings)
+        })?
+    }
+}
+
+impl NaviscopeEngine {
+    /// Warnings of the indexed files, sorted by path
+    pub fn index_warnings(&self) -> Result<Vec<IndexWarning>> {
+        self.warnings.list()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::MemoryFs;
+    use naviscope_api::lifecycle::{IndexPhase, WarningSeverity};
+
+    fn warning(path: &str, message: &str) -> IndexWarning {
+        IndexWarning {
+            path: path.to_string(),
+            phase: IndexPhase::Parsing,
+            severity: WarningSeverity::Warning,
+            message: message.to_string(),
+        }
+    }
+
+    fn run(indexed: &[&str], warnings: Vec<IndexWarning>) -> RunWarnings {
+        RunWarnings {
+            indexed: indexed.iter().map(PathBuf::from).collect(),
+            warnings,
+        }
+    }
+
+    #[test]
+    fn runs_replace_the_warnings_of_their_files() {
+        let fs = Arc::new(MemoryFs::new());
+        let index_path = PathBuf::from("/idx/p.bin");
+        let store = WarningStore::new(fs.clone(), &index_path);
+
+        store
+            .commit(run(
+                &["/p/A.java", "/p/B.java"],
+                vec![warning("/p/B.java", "b"), warning("/p/A.java", "a")],
+            ))
+            .unwrap();
+        // A later run that only indexed B keeps the warnings of A
+        store
+            .commit(run(&["/p/B.java"], vec![warning("/p/B.java", "b2")]))
+            .unwrap();
+
+        let reopened = WarningStore::new(fs.clone(), &index_path);
+        let messages: Vec<String> = reopened
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|w| w.message)
+            .collect();
+        assert_eq!(messages, ["a", "b2"]);
+
+        reopened
+            .commit(run(&["/p/A.java", "/p/B.java"], vec![]))
+            .unwrap();
+        assert!(reopened.list().unwrap().is_empty());
+        assert!(!fs.exists(&index_path.with_extension("warnings.json")));
+    }
+}
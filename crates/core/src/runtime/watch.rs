@@ -1,8 +1,15 @@
 use super::*;
+use crate::telemetry::{IngestQueue, record_queue_depth};
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher as NotifyWatcher};
+use std::collections::{BTreeSet, HashSet};
 use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
 use tokio::sync::mpsc;
 
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(500);
+/// Builds write many files into dependency caches; wait for them to settle.
+const ASSET_DEBOUNCE_INTERVAL: Duration = Duration::from_secs(2);
+
 struct FsWatcher {
     _watcher: RecommendedWatcher,
     rx: mpsc::UnboundedReceiver<notify::Result<Event>>,
@@ -10,6 +17,10 @@ struct FsWatcher {
 
 impl FsWatcher {
     fn new(root: &Path) -> notify::Result<Self> {
+        Self::watching(&[root.to_path_buf()], RecursiveMode::Recursive)
+    }
+
+    fn watching(roots: &[PathBuf], mode: RecursiveMode) -> notify::Result<Self> {
         let (tx, rx) = mpsc::unbounded_channel();
         let mut watcher = RecommendedWatcher::new(
             move |res| {
@@ -17,7 +28,9 @@ impl FsWatcher {
             },
             Config::default(),
         )?;
-        watcher.watch(root, RecursiveMode::Recursive)?;
+        for root in roots {
+            watcher.watch(root, mode)?;
+        }
         Ok(Self {
             _watcher: watcher,
             rx,
@@ -32,6 +45,65 @@ impl FsWatcher {
     }
 }
 
+/// Collects changed paths until no new event arrives for `interval`.
+struct Debouncer {
+    clock: Arc<dyn Clock>,
+    interval: Duration,
+    filter: fn(&Path) -> bool,
+    pending: HashSet<PathBuf>,
+    last_event: Option<SystemTime>,
+}
+
+impl Debouncer {
+    fn new(clock: Arc<dyn Clock>, interval: Duration) -> Self {
+        Self::with_filter(clock, interval, crate::indexing::is_relevant_path)
+    }
+
+    /// Keep only the paths accepted by `filter`
+    fn with_filter(clock: Arc<dyn Clock>, interval: Duration, filter: fn(&Path) -> bool) -> Self {
+        Self {
+            clock,
+            interval,
+            filter,
+            pending: HashSet::new(),
+            last_event: None,
+        }
+    }
+
+    /// Record the relevant paths of an event and restart the quiet period.
+    fn record(&mut self, paths: impl IntoIterator<Item = PathBuf>) {
+        let filter = self.filter;
+        self.pending.extend(paths.into_iter().filter(|p| filter(p)));
+        if self.is_pending() {
+            self.last_event = Some(self.clock.now());
+        }
+    }
+
+    fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Time left until the pending batch may be flushed.
+    fn remaining(&self) -> Duration {
+        let elapsed = self
+            .last_event
+            .and_then(|t| self.clock.now().duration_since(t).ok())
+            .unwrap_or_default();
+        self.interval.saturating_sub(elapsed)
+    }
+
+    /// Drain the pending batch once the quiet period has passed.
+    fn take_ready(&mut self) -> Option<Vec<PathBuf>> {
+        if !self.is_pending() || !self.remaining().is_zero() {
+            return None;
+        }
+        self.last_event = None;
+        let mut paths: Vec<_> = self.pending.drain().collect();
+        paths.sort();
+        Some(paths)
+    }
+}
+
 impl NaviscopeEngine {
     /// Watch for filesystem changes and update incrementally.
     /// The watcher task exits when `cancel_token` is cancelled.
@@ -39,18 +111,19 @@ impl NaviscopeEngine {
         self: Arc<Self>,
         cancel_token: tokio_util::sync::CancellationToken,
     ) -> Result<()> {
-        use std::collections::HashSet;
-        use std::time::Duration;
-
+        self.ensure_writable()?;
         let root = self.project_root.clone();
-        let mut watcher = FsWatcher::new(&root).map_err(|e| NaviscopeError::Internal(e.to_string()))?;
+        let mut watcher =
+            FsWatcher::new(&root).map_err(|e| NaviscopeError::Internal(e.to_string()))?;
 
         let engine_weak = Arc::downgrade(&self);
 
+        let mut debouncer = Debouncer::new(self.clock.clone(), DEBOUNCE_INTERVAL);
+        let mut backlog = BTreeSet::new();
+        self.watch_asset_caches(cancel_token.clone());
+
         tokio::spawn(async move {
             tracing::info!("Started watching {}", root.display());
-            let mut pending_events: Vec<notify::Event> = Vec::new();
-            let debounce_interval = Duration::from_millis(500);
 
             loop {
                 tokio::select! {
@@ -59,32 +132,34 @@ impl NaviscopeEngine {
                     }
                     event = watcher.next_event_async() => {
                         match event {
-                            Some(e) => pending_events.push(e),
+                            Some(e) => {
+                                debouncer.record(e.paths);
+                                record_queue_depth(IngestQueue::WatchDebounce, debouncer.pending.len());
+                            }
                             None => break,
                         }
                     }
-                    _ = tokio::time::sleep(debounce_interval), if !pending_events.is_empty() => {
-                        let mut paths = HashSet::new();
-                        for event in &pending_events {
-                            for path in &event.paths {
-                                if crate::indexing::is_relevant_path(path) {
-                                    paths.insert(path.clone());
-                                }
-                            }
+                    _ = tokio::time::sleep(debouncer.remaining()), if debouncer.is_pending() => {
+                        let Some(path_vec) = debouncer.take_ready() else {
+                            continue;
+                        };
+                        tracing::info!("Detected changes in {} files. Updating...", path_vec.len());
+                        backlog.extend(path_vec);
+                        record_queue_depth(IngestQueue::WatchDebounce, 0);
+                        record_queue_depth(IngestQueue::WatchBacklog, backlog.len());
+                        if let Some(engine) = engine_weak.upgrade() {
+                            engine.watch_flow.lock().unwrap().set_backlog(backlog.len());
                         }
-                        pending_events.clear();
-
-                        if !paths.is_empty() {
-                            if let Some(engine) = engine_weak.upgrade() {
-                                let path_vec: Vec<_> = paths.into_iter().collect();
-                                tracing::info!("Detected changes in {} files. Updating...", path_vec.len());
-                                if let Err(err) = engine.update_files(path_vec).await {
-                                    tracing::error!("Failed to update files: {}", err);
-                                }
-                            } else {
-                                break;
-                            }
+                    }
+                    // Apply queued changes in batches, still taking events in between
+                    _ = std::future::ready(()), if !backlog.is_empty() => {
+                        let Some(engine) = engine_weak.upgrade() else {
+                            break;
+                        };
+                        if engine.is_closing() {
+                            break;
                         }
+                        engine.apply_watch_batch(&mut backlog).await;
                     }
                 }
             }
@@ -94,9 +169,146 @@ impl NaviscopeEngine {
         Ok(())
     }
 
+    /// Update the first files of `backlog`, as many as the adaptive limit
+    /// allows, and adapt the limit to how long that took.
+    async fn apply_watch_batch(&self, backlog: &mut BTreeSet<PathBuf>) {
+        let limit = self.watch_flow.lock().unwrap().limit();
+        let batch: Vec<PathBuf> = std::iter::from_fn(|| backlog.pop_first())
+            .take(limit)
+            .collect();
+        let files = batch.len();
+
+        let started = Instant::now();
+        if let Err(err) = self.update_files(batch).await {
+            tracing::error!("Failed to update files: {}", err);
+        }
+        let latency = started.elapsed();
+        record_queue_depth(IngestQueue::WatchBacklog, backlog.len());
+
+        let decision = self
+            .watch_flow
+            .lock()
+            .unwrap()
+            .observe(files, latency, backlog.len());
+        if let Some(decision) = decision {
+            tracing::debug!(
+                "Watch batch limit {} -> {} ({:?}, {} ms, {} files waiting)",
+                decision.from,
+                decision.to,
+                decision.reason,
+                decision.latency_ms,
+                decision.backlog
+            );
+        }
+    }
+
+    /// Watch the roots of dependency caches (non-recursively) and register
+    /// newly fetched assets, so their packages resolve without a restart.
+    /// Failing to watch only disables this; the project watch still runs.
+    fn watch_asset_caches(&self, cancel_token: tokio_util::sync::CancellationToken) {
+        let Some(service) = self.asset_service.clone() else {
+            return;
+        };
+        let roots = service.watch_roots();
+        if roots.is_empty() {
+            return;
+        }
+        let mut watcher = match FsWatcher::watching(&roots, RecursiveMode::NonRecursive) {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                tracing::warn!("Cannot watch dependency caches: {}", err);
+                return;
+            }
+        };
+        let mut debouncer =
+            Debouncer::with_filter(self.clock.clone(), ASSET_DEBOUNCE_INTERVAL, |_| true);
+
+        tokio::spawn(async move {
+            tracing::info!("Watching {} dependency cache roots", roots.len());
+            loop {
+                tokio::select! {
+                    _ = cancel_token.cancelled() => break,
+                    event = watcher.next_event_async() => {
+                        match event {
+                            Some(e) => debouncer.record(e.paths),
+                            None => break,
+                        }
+                    }
+                    _ = tokio::time::sleep(debouncer.remaining()), if debouncer.is_pending() => {
+                        if debouncer.take_ready().is_none() {
+                            continue;
+                        }
+                        let service = service.clone();
+                        match tokio::task::spawn_blocking(move || service.scan_new_assets()).await {
+                            Ok(result) if result.indexed_assets > 0 => tracing::info!(
+                                "Registered {} new dependency assets",
+                                result.indexed_assets
+                            ),
+                            Ok(_) => {}
+                            Err(err) => tracing::error!("Dependency cache rescan failed: {}", err),
+                        }
+                    }
+                }
+            }
+        });
+    }
+
     /// Backward-compatible helper that uses the engine-wide cancellation token.
     pub async fn watch(self: Arc<Self>) -> Result<()> {
         let cancel_token = self.cancel_token.clone();
         self.start_watch_with_token(cancel_token).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::ManualClock;
+
+    #[test]
+    fn debouncer_flushes_after_quiet_period() {
+        let clock = Arc::new(ManualClock::default());
+        let mut debouncer = Debouncer::new(clock.clone(), DEBOUNCE_INTERVAL);
+
+        debouncer.record([PathBuf::from("/p/A.java")]);
+        clock.advance(Duration::from_millis(300));
+        debouncer.record([PathBuf::from("/p/B.java"), PathBuf::from("/p/.git")]);
+        assert_eq!(debouncer.remaining(), DEBOUNCE_INTERVAL);
+
+        // A new event restarts the quiet period
+        clock.advance(Duration::from_millis(300));
+        assert!(debouncer.take_ready().is_none());
+
+        clock.advance(Duration::from_millis(200));
+        assert_eq!(
+            debouncer.take_ready(),
+            Some(vec![PathBuf::from("/p/A.java"), PathBuf::from("/p/B.java")])
+        );
+        assert!(!debouncer.is_pending());
+    }
+
+    #[test]
+    fn debouncer_ignores_irrelevant_paths() {
+        let clock = Arc::new(ManualClock::default());
+        let mut debouncer = Debouncer::new(clock.clone(), DEBOUNCE_INTERVAL);
+
+        debouncer.record([PathBuf::from("/p/target"), PathBuf::from("/p/.idea")]);
+        clock.advance(DEBOUNCE_INTERVAL);
+        assert!(!debouncer.is_pending());
+        assert!(debouncer.take_ready().is_none());
+    }
+
+    #[test]
+    fn debouncer_with_filter_keeps_any_path() {
+        let clock = Arc::new(ManualClock::default());
+        let mut debouncer =
+            Debouncer::with_filter(clock.clone(), ASSET_DEBOUNCE_INTERVAL, |_| true);
+
+        debouncer.record([PathBuf::from("/m2/modules-2.lock")]);
+        clock.advance(ASSET_DEBOUNCE_INTERVAL);
+        assert_eq!(
+            debouncer.take_ready(),
+            Some(vec![PathBuf::from("/m2/modules-2.lock")])
+        );
+    }
+}
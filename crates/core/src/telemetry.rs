@@ -0,0 +1,150 @@
+//! OpenTelemetry export
+//!
+//! Off unless `OTEL_EXPORTER_OTLP_ENDPOINT` is set. Then the spans of the
+//! log (parsing, resolving and lowering each file, committing snapshots,
+//! LSP requests) are exported over OTLP/HTTP as traces, together with
+//! metrics: the depths of the queues feeding the index and the latencies of
+//! LSP requests. The other `OTEL_*` variables configure the exporters as
+//! usual, e.g. `OTEL_EXPORTER_OTLP_HEADERS`.
+
+use once_cell::sync::Lazy;
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::Gauge;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::{MetricExporter, SpanExporter};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use tracing::Subscriber;
+use tracing_subscriber::Layer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Variable that turns export on
+const ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// Meter and tracer name of every instrument
+pub const INSTRUMENTATION_SCOPE: &str = "naviscope";
+
+/// Queues whose depth is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IngestQueue {
+    /// Changed files waiting for the watcher's quiet period to end
+    WatchDebounce,
+    /// Changed files waiting for a watch update to take them
+    WatchBacklog,
+    /// Library stub requests waiting for the next source phase
+    StubRequests,
+}
+
+impl IngestQueue {
+    fn label(self) -> &'static str {
+        match self {
+            IngestQueue::WatchDebounce => "watch_debounce",
+            IngestQueue::WatchBacklog => "watch_backlog",
+            IngestQueue::StubRequests => "stub_requests",
+        }
+    }
+}
+
+static QUEUE_DEPTH: Lazy<Gauge<u64>> = Lazy::new(|| {
+    opentelemetry::global::meter(INSTRUMENTATION_SCOPE)
+        .u64_gauge("naviscope.ingest.queue_depth")
+        .with_description("Items waiting in a queue feeding the index")
+        .build()
+});
+
+/// Report that `depth` items wait in `queue`. A no-op unless export is on.
+pub fn record_queue_depth(queue: IngestQueue, depth: usize) {
+    QUEUE_DEPTH.record(depth as u64, &[KeyValue::new("queue", queue.label())]);
+}
+
+/// Exporters of one process, flushed and stopped when dropped
+pub(crate) struct Telemetry {
+    tracer_provider: SdkTracerProvider,
+    meter_provider: SdkMeterProvider,
+}
+
+impl Telemetry {
+    /// Exporters for `component`, registered as the global meter provider.
+    /// `None` when export is off or cannot start.
+    pub(crate) fn from_env(component: &str) -> Option<Self> {
+        std::env::var_os(ENDPOINT_ENV).filter(|endpoint| !endpoint.is_empty())?;
+        let resource = Resource::builder()
+            .with_service_name(format!("naviscope-{}", component))
+            .build();
+
+        // Logging is not set up yet, and stdout may carry a protocol
+        let spans = SpanExporter::builder()
+            .with_http()
+            .build()
+            .inspect_err(|e| eprintln!("OpenTelemetry trace export disabled: {}", e))
+            .ok()?;
+        let metrics = MetricExporter::builder()
+            .with_http()
+            .build()
+            .inspect_err(|e| eprintln!("OpenTelemetry metric export disabled: {}", e))
+            .ok()?;
+
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_resource(resource.clone())
+            .with_batch_exporter(spans)
+            .build();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_resource(resource)
+            .with_periodic_exporter(metrics)
+            .build();
+        opentelemetry::global::set_meter_provider(meter_provider.clone());
+        Some(Self {
+            tracer_provider,
+            meter_provider,
+        })
+    }
+
+    /// Layer exporting the spans of the log
+    pub(crate) fn layer<S>(&self) -> impl Layer<S> + use<S>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        let tracer = self.tracer_provider.tracer(INSTRUMENTATION_SCOPE);
+        tracing_opentelemetry::layer().with_tracer(tracer)
+    }
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        let _ = self.tracer_provider.shutdown();
+        let _ = self.meter_provider.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TraceContextExt;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    use tracing_subscriber::prelude::*;
+
+    /// Whether spans entered under `telemetry` get an OpenTelemetry trace
+    fn traces_spans(telemetry: Option<&Telemetry>) -> bool {
+        let subscriber = tracing_subscriber::registry().with(telemetry.map(Telemetry::layer));
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request");
+            span.context().span().span_context().is_valid()
+        })
+    }
+
+    #[test]
+    fn export_follows_the_endpoint_variable() {
+        // No other test reads the variable
+        unsafe { std::env::remove_var(ENDPOINT_ENV) };
+        let telemetry = Telemetry::from_env("test");
+        assert!(telemetry.is_none());
+        assert!(!traces_spans(telemetry.as_ref()));
+
+        unsafe { std::env::set_var(ENDPOINT_ENV, "http://127.0.0.1:4318") };
+        let telemetry = Telemetry::from_env("test");
+        unsafe { std::env::remove_var(ENDPOINT_ENV) };
+        assert!(telemetry.is_some());
+        assert!(traces_spans(telemetry.as_ref()));
+    }
+}
@@ -0,0 +1,52 @@
+//! Fixtures shared by the unit tests
+
+use crate::indexing::IndexNode;
+use crate::model::builder::CodeGraphBuilder;
+use crate::model::{CodeGraph, EmptyMetadata, GraphEdge};
+use naviscope_api::models::graph::{
+    DisplaySymbolLocation, EdgeType, NodeKind, NodeSource, ResolutionStatus,
+};
+use std::sync::Arc;
+
+/// Resolved Java project node, named after the last segment of `fqn`
+pub(crate) fn node(fqn: &str, kind: NodeKind) -> IndexNode {
+    let name = fqn.rsplit(['.', '#']).next().unwrap_or(fqn);
+    IndexNode {
+        id: fqn.into(),
+        name: name.split('(').next().unwrap_or(name).to_string(),
+        kind,
+        lang: "java".to_string(),
+        source: NodeSource::Project,
+        status: ResolutionStatus::Resolved,
+        location: None,
+        metadata: Arc::new(EmptyMetadata),
+    }
+}
+
+/// Like `node`, declared at the start of the file at `path`
+pub(crate) fn node_at(fqn: &str, kind: NodeKind, path: &str) -> IndexNode {
+    IndexNode {
+        location: Some(DisplaySymbolLocation {
+            path: path.to_string(),
+            range: Default::default(),
+            selection_range: None,
+        }),
+        ..node(fqn, kind)
+    }
+}
+
+/// Packages `com.app` and `com.app.sub` with classes `A` (declaring `run()`),
+/// `B` and `sub.C`. Package `com.app` does not contain `com.app.sub`.
+pub(crate) fn sample_graph() -> CodeGraph {
+    let mut builder = CodeGraphBuilder::new();
+    let app = builder.add_node(node("com.app", NodeKind::Package));
+    let sub = builder.add_node(node("com.app.sub", NodeKind::Package));
+    let a = builder.add_node(node("com.app.A", NodeKind::Class));
+    let b = builder.add_node(node("com.app.B", NodeKind::Interface));
+    let c = builder.add_node(node("com.app.sub.C", NodeKind::Class));
+    let run = builder.add_node(node("com.app.A#run()", NodeKind::Method));
+    for (from, to) in [(app, a), (app, b), (sub, c), (a, run)] {
+        builder.add_edge(from, to, GraphEdge::new(EdgeType::Contains));
+    }
+    builder.build()
+}
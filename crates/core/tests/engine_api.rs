@@ -41,7 +41,11 @@ async fn test_engine_handle_query() {
         pattern: "test".to_string(),
         kind: vec![],
         sources: vec![],
+        statuses: vec![],
+        modifiers: vec![],
         limit: 5,
+        group_by: None,
+        count: false,
     };
 
     let result: naviscope_api::ApiResult<naviscope_api::models::QueryResult> =
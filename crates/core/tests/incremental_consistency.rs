@@ -106,6 +106,7 @@ async fn observe(
             language: Language::JAVA,
             resolution,
             include_declaration: false,
+            type_usages: Default::default(),
         })
         .await
         .unwrap()
@@ -26,6 +26,7 @@ impl BuildParseCap for MockBuildCap {
     ) -> Result<naviscope_plugin::BuildParseResult, naviscope_plugin::BoxError> {
         Ok(naviscope_plugin::BuildParseResult {
             content: BuildContent::Unparsed(String::new()),
+            warnings: Vec::new(),
         })
     }
 }
@@ -1,6 +1,6 @@
 use naviscope_api::models::{
-    DisplayGraphNode, DisplaySymbolLocation, Language, NodeKind, NodeSource, Range, SymbolQuery,
-    SymbolResolution,
+    DisplayGraphNode, DisplaySymbolLocation, FoldingKind, Language, NodeKind, NodeSource,
+    PositionContext, Range, SymbolQuery, SymbolResolution,
 };
 use naviscope_api::semantic::{SymbolInfoProvider, SymbolNavigator};
 use naviscope_core::facade::EngineHandle;
@@ -255,6 +255,8 @@ impl NodePresenter for MockCap {
     ) -> DisplayGraphNode {
         DisplayGraphNode {
             id: StandardNamingConvention.render_fqn(node.id, fqns),
+            symbol_id: String::new(),
+            generated: false,
             name: fqns.resolve_atom(node.name).to_string(),
             kind: node.kind.clone(),
             lang: fqns.resolve_atom(node.lang).to_string(),
@@ -360,6 +362,34 @@ async fn test_symbol_navigator_queries() {
     assert_eq!(defs[0].path.as_os_str(), test_file.as_os_str());
 }
 
+#[tokio::test]
+async fn test_explain_resolution_reports_candidates() {
+    let temp_dir = std::env::temp_dir().join("naviscope_test_explain");
+    std::fs::create_dir_all(&temp_dir).ok();
+
+    let engine = setup_engine(&temp_dir);
+    let test_file = temp_dir.join("test.mock");
+    std::fs::write(&test_file, "class Symbol {}").unwrap();
+    engine.update_files(vec![test_file.clone()]).await.unwrap();
+
+    let handle = EngineHandle::from_engine(Arc::new(engine));
+    let ctx = PositionContext {
+        uri: format!("file://{}", test_file.display()),
+        line: 0,
+        char: 7,
+        content: None,
+    };
+
+    let trace = handle.explain_resolution(&ctx).await.unwrap().unwrap();
+    assert_eq!(trace.symbol, "Symbol");
+    assert_eq!(trace.steps.len(), 1);
+    assert!(trace.steps[0].matched);
+    assert_eq!(trace.resolution.as_deref(), Some("global test::Symbol"));
+    assert_eq!(trace.candidates.len(), 1);
+    assert!(trace.candidates[0].accepted);
+    assert_eq!(trace.chosen, Some(trace.candidates[0].fqn.clone()));
+}
+
 #[tokio::test]
 async fn test_symbol_info_provider() {
     let temp_dir = std::env::temp_dir().join("naviscope_test_info");
@@ -378,3 +408,89 @@ async fn test_symbol_info_provider() {
     let symbols = handle.get_document_symbols(&uri).await.unwrap();
     assert!(symbols.is_empty());
 }
+
+#[tokio::test]
+async fn test_folding_and_selection_ranges() {
+    let temp_dir = std::env::temp_dir().join("naviscope_test_folding");
+    std::fs::create_dir_all(&temp_dir).ok();
+
+    let engine = setup_engine(&temp_dir);
+    let handle = EngineHandle::from_engine(Arc::new(engine));
+
+    let test_file = temp_dir.join("test.mock");
+    let content =
+        "import a.B;\nimport a.C;\n\nclass Symbol {\n  void run() {\n    call(1);\n  }\n}\n";
+    let uri = format!("file://{}", test_file.display());
+
+    let folds = handle
+        .get_folding_ranges(&uri, Some(content.to_string()))
+        .await
+        .unwrap();
+    let spans: Vec<_> = folds
+        .iter()
+        .map(|f| (f.start_line, f.end_line, f.kind))
+        .collect();
+    assert_eq!(
+        spans,
+        vec![
+            (0, 1, FoldingKind::Imports),
+            (3, 6, FoldingKind::Region),
+            (4, 5, FoldingKind::Region),
+        ]
+    );
+
+    let ctx = PositionContext {
+        uri,
+        line: 5,
+        char: 4,
+        content: Some(content.to_string()),
+    };
+    let ranges = handle.get_selection_ranges(&ctx).await.unwrap();
+    assert_eq!(ranges[0].start_line, 5);
+    assert_eq!(ranges[0].start_col, 4);
+    assert!(
+        ranges
+            .windows(2)
+            .all(|w| w[1].start_line <= w[0].start_line && w[1].end_line >= w[0].end_line)
+    );
+    assert_eq!(ranges.last().unwrap().start_line, 0);
+}
+
+#[tokio::test]
+async fn test_resolve_at_returns_the_indexed_node() {
+    let temp_dir = std::env::temp_dir().join("naviscope_test_resolve_at");
+    std::fs::create_dir_all(&temp_dir).ok();
+
+    let engine = setup_engine(&temp_dir);
+    let test_file = temp_dir.join("test.mock");
+    std::fs::write(&test_file, "class Symbol {}").unwrap();
+    engine.update_files(vec![test_file.clone()]).await.unwrap();
+
+    let handle = EngineHandle::from_engine(Arc::new(engine));
+    let resolved = handle.resolve_at(&test_file, 0, 7).await.unwrap().unwrap();
+    assert_eq!(resolved.fqn.as_deref(), Some("test::Symbol"));
+    assert!(resolved.local_declaration.is_none());
+    let node = resolved.node.unwrap();
+    assert_eq!(node.name, "Symbol");
+    assert_eq!(node.kind, NodeKind::Class);
+}
+
+#[tokio::test]
+async fn test_resolve_at_without_indexed_node() {
+    let temp_dir = std::env::temp_dir().join("naviscope_test_resolve_at_missing");
+    std::fs::create_dir_all(&temp_dir).ok();
+
+    let engine = setup_engine(&temp_dir);
+    let handle = EngineHandle::from_engine(Arc::new(engine));
+
+    // The file resolves, but was never indexed
+    let test_file = temp_dir.join("test.mock");
+    std::fs::write(&test_file, "class Symbol {}").unwrap();
+    let resolved = handle.resolve_at(&test_file, 0, 7).await.unwrap().unwrap();
+    assert_eq!(resolved.fqn.as_deref(), Some("test::Symbol"));
+    assert!(resolved.node.is_none());
+
+    // A file that cannot be read is an error, not an empty result
+    let missing = temp_dir.join("missing.mock");
+    assert!(handle.resolve_at(&missing, 0, 7).await.is_err());
+}
@@ -33,7 +33,7 @@ fn test_cache_store_and_lookup() {
 
     // Store a stub
     let stub = create_test_stub("com.example.Foo", "Foo");
-    cache.store(&asset_key, &stub);
+    cache.store(&asset_key, &stub).unwrap();
 
     // Lookup should return the stub
     let cached = cache.lookup(&asset_key, "com.example.Foo");
@@ -74,7 +74,7 @@ fn test_cache_persistence() {
     {
         let cache = GlobalStubCache::new(cache_dir.clone());
         let stub = create_test_stub("com.example.Persisted", "Persisted");
-        cache.store(&asset_key, &stub);
+        cache.store(&asset_key, &stub).unwrap();
     }
 
     // Create new cache instance and lookup
@@ -98,7 +98,7 @@ fn test_cache_invalidation_on_file_change() {
 
     // Store a stub
     let stub = create_test_stub("com.example.V1", "V1");
-    cache.store(&key1, &stub);
+    cache.store(&key1, &stub).unwrap();
 
     // "Modify" the file (change content and mtime)
     std::thread::sleep(std::time::Duration::from_millis(10));
@@ -115,6 +115,32 @@ fn test_cache_invalidation_on_file_change() {
     assert!(cache.lookup(&key2, "com.example.V1").is_none());
 }
 
+#[test]
+fn test_cache_write_timeout_is_reported() {
+    let temp = TempDir::new().unwrap();
+    let cache_dir = temp.path().join("cache");
+    let asset_file = temp.path().join("test.jar");
+    std::fs::write(&asset_file, b"content").unwrap();
+    let key = AssetKey::from_path(&asset_file).unwrap();
+
+    // Another process is writing the asset's cache file
+    std::fs::create_dir_all(&cache_dir).unwrap();
+    let lock = cache_dir.join(format!("{:016x}.stubs.lock", key.hash()));
+    std::fs::write(&lock, b"1").unwrap();
+
+    let cache = GlobalStubCache::new(cache_dir.clone());
+    let stub = create_test_stub("com.example.Late", "Late");
+    let err = cache.store(&key, &stub).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+    // The stub is still served from memory, and storing again persists it
+    assert!(cache.lookup(&key, "com.example.Late").is_some());
+
+    std::fs::remove_file(&lock).unwrap();
+    cache.store(&key, &stub).unwrap();
+    let reloaded = GlobalStubCache::new(cache_dir);
+    assert!(reloaded.lookup(&key, "com.example.Late").is_some());
+}
+
 #[test]
 fn test_cache_stats() {
     let temp = TempDir::new().unwrap();
@@ -131,7 +157,7 @@ fn test_cache_stats() {
                 &format!("com.example.Class{}_{}", i, j),
                 &format!("Class{}_{}", i, j),
             );
-            cache.store(&key, &stub);
+            cache.store(&key, &stub).unwrap();
         }
     }
 
@@ -150,7 +176,9 @@ fn test_cache_clear() {
     std::fs::write(&asset_file, b"content").unwrap();
     let key = AssetKey::from_path(&asset_file).unwrap();
 
-    cache.store(&key, &create_test_stub("com.example.Test", "Test"));
+    cache
+        .store(&key, &create_test_stub("com.example.Test", "Test"))
+        .unwrap();
     assert!(cache.lookup(&key, "com.example.Test").is_some());
 
     // Clear cache
@@ -161,6 +189,60 @@ fn test_cache_clear() {
     assert!(cache2.lookup(&key, "com.example.Test").is_none());
 }
 
+#[test]
+fn test_cache_inspect_and_remove_asset() {
+    let temp = TempDir::new().unwrap();
+    let cache = GlobalStubCache::new(temp.path().join("cache"));
+
+    let repo = temp
+        .path()
+        .join("repository/com/google/guava/guava/31.1-jre");
+    std::fs::create_dir_all(&repo).unwrap();
+    let guava = repo.join("guava-31.1-jre.jar");
+    std::fs::write(&guava, b"guava").unwrap();
+    let other = temp.path().join("other.jar");
+    std::fs::write(&other, b"other").unwrap();
+
+    let guava = AssetKey::from_path(&guava).unwrap();
+    for fqn in [
+        "com.google.common.base.Strings",
+        "com.google.common.base.Joiner",
+        "com.google.common.collect.ImmutableMap.Builder",
+    ] {
+        cache
+            .store(
+                &guava,
+                &create_test_stub(fqn, fqn.rsplit('.').next().unwrap()),
+            )
+            .unwrap();
+    }
+    let other = AssetKey::from_path(&other).unwrap();
+    cache
+        .store(&other, &create_test_stub("org.other.Thing", "Thing"))
+        .unwrap();
+
+    let result = cache
+        .inspect_asset("com.google.guava:guava:31.1-jre")
+        .unwrap();
+    assert_eq!(result.summary.stub_count, 3);
+    assert_eq!(result.summary.package_count, 2);
+    assert_eq!(result.packages["com.google.common.base"], 2);
+    assert_eq!(result.packages["com.google.common.collect"], 1);
+    assert!(cache.inspect_asset("com.google.guava:guava:32.0").is_none());
+
+    let removed = cache
+        .remove_assets(std::slice::from_ref(&result.summary.hash))
+        .unwrap();
+    assert_eq!(removed.len(), 1);
+    assert!(
+        cache
+            .lookup(&guava, "com.google.common.base.Strings")
+            .is_none()
+    );
+    assert!(cache.lookup(&other, "org.other.Thing").is_some());
+    assert_eq!(cache.stats().total_assets, 1);
+}
+
 #[test]
 fn test_cache_with_java_metadata() {
     use naviscope_java::model::JavaIndexMetadata;
@@ -197,7 +279,7 @@ fn test_cache_with_java_metadata() {
     // 2. Store in cache
     {
         let cache = GlobalStubCache::new(cache_dir.clone());
-        cache.store(&key, &stub);
+        cache.store(&key, &stub).unwrap();
     }
 
     // 3. Load from a new cache instance (simulating a restart)
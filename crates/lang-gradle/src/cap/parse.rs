@@ -1,13 +1,35 @@
 use crate::GradlePlugin;
-use naviscope_plugin::{BuildContent, BuildParseCap, BuildParseResult};
+use naviscope_api::lifecycle::WarningSeverity;
+use naviscope_plugin::{BuildContent, BuildParseCap, BuildParseResult, PluginWarning};
+
+/// Top-level statements that only a settings script makes
+const SETTINGS_STATEMENTS: &[&str] =
+    &["include", "includeBuild", "includeFlat", "rootProject.name"];
+
+/// Whether `source` is a settings script rather than a build script. Only
+/// top-level statements count: `include` inside a `jar { }` closure of a
+/// build script selects files, not projects.
+fn is_settings_script(source: &str) -> bool {
+    naviscope_groovy::script::top_level_statements(source).is_some_and(|statements| {
+        statements
+            .iter()
+            .any(|s| SETTINGS_STATEMENTS.contains(&s.target.as_str()))
+    })
+}
 
 impl BuildParseCap for GradlePlugin {
     fn parse_build_file(
         &self,
         source: &str,
     ) -> Result<BuildParseResult, Box<dyn std::error::Error + Send + Sync>> {
-        if source.contains("include") && (source.contains("'") || source.contains("\"")) {
-            let settings = crate::parser::parse_settings(source).unwrap_or_else(|_| {
+        let mut warnings = Vec::new();
+        if is_settings_script(source) {
+            let settings = crate::parser::parse_settings(source).unwrap_or_else(|e| {
+                warnings.push(PluginWarning {
+                    severity: WarningSeverity::Error,
+                    message: format!("settings could not be parsed, no modules included: {}", e),
+                    path: None,
+                });
                 crate::model::GradleSettings {
                     root_project_name: None,
                     included_projects: Vec::new(),
@@ -17,15 +39,45 @@ impl BuildParseCap for GradlePlugin {
                 content: BuildContent::Metadata(
                     serde_json::to_value(settings).unwrap_or(serde_json::Value::Null),
                 ),
+                warnings,
             })
         } else {
-            let deps = crate::parser::parse_dependencies(source).unwrap_or_default();
+            let deps = crate::parser::parse_dependencies(source).unwrap_or_else(|e| {
+                warnings.push(PluginWarning {
+                    severity: WarningSeverity::Error,
+                    message: format!("dependencies could not be parsed: {}", e),
+                    path: None,
+                });
+                Vec::new()
+            });
             Ok(BuildParseResult {
                 content: BuildContent::Metadata(
                     serde_json::to_value(crate::model::GradleParseResult { dependencies: deps })
                         .unwrap_or(serde_json::Value::Null),
                 ),
+                warnings,
             })
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_scripts_are_told_apart_by_top_level_statements() {
+        assert!(is_settings_script("include 'app', 'lib'"));
+        assert!(is_settings_script("rootProject.name = 'demo'"));
+        assert!(!is_settings_script(
+            r#"
+            jar {
+                include '**/*.class'
+            }
+            dependencies {
+                implementation 'com.google.guava:guava:31.1-jre'
+            }
+            "#
+        ));
+    }
+}
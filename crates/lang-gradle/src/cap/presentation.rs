@@ -10,6 +10,8 @@ impl NodePresenter for GradlePlugin {
     fn render_display_node(&self, node: &GraphNode, fqns: &dyn FqnReader) -> DisplayGraphNode {
         let display_id = StandardNamingConvention.render_fqn(node.id, fqns);
         let mut display = DisplayGraphNode {
+            symbol_id: StandardNamingConvention.symbol_id("gradle", &display_id),
+            generated: false,
             id: display_id,
             name: fqns.resolve_atom(node.name).to_string(),
             kind: node.kind.clone(),
@@ -28,7 +30,9 @@ impl NodePresenter for GradlePlugin {
             .as_any()
             .downcast_ref::<crate::model::GradleNodeMetadata>()
         {
-            display.detail = gradle_meta.detail_view(fqns);
+            display.detail = gradle_meta.detail_view();
+            display.signature = Some(gradle_meta.signature_view());
+            display.modifiers = gradle_meta.configurations().to_vec();
         }
 
         display
@@ -103,6 +103,20 @@ impl AssetDiscoverer for GradleCacheDiscoverer {
     fn source_type(&self) -> &str {
         "gradle"
     }
+
+    /// `files-2.1` gains a directory for each new group, and Gradle touches
+    /// `modules-2/modules-2.lock` whenever a build resolves dependencies.
+    fn watch_roots(&self) -> Vec<PathBuf> {
+        let Some(cache_path) = &self.cache_path else {
+            return Vec::new();
+        };
+        [Some(cache_path.as_path()), cache_path.parent()]
+            .into_iter()
+            .flatten()
+            .filter(|p| p.is_dir())
+            .map(|p| p.to_path_buf())
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -156,6 +170,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_watch_roots_cover_cache_and_lock_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache_path = temp.path().join("modules-2/files-2.1");
+
+        let discoverer = GradleCacheDiscoverer::with_path(cache_path.clone());
+        assert!(discoverer.watch_roots().is_empty());
+
+        fs::create_dir_all(&cache_path).unwrap();
+        assert_eq!(
+            discoverer.watch_roots(),
+            vec![cache_path.clone(), temp.path().join("modules-2")]
+        );
+    }
+
     #[test]
     fn test_parse_cache_path() {
         let temp = tempfile::tempdir().unwrap();
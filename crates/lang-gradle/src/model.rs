@@ -1,7 +1,8 @@
-use lasso::Key;
 use naviscope_api::models::graph::NodeMetadata;
+use naviscope_plugin::{IndexMetadata, SymbolInterner};
 use serde::{Deserialize, Serialize};
 use std::any::Any;
+use std::sync::Arc;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GradleNodeMetadata {
@@ -13,36 +14,32 @@ impl GradleNodeMetadata {
         Self { element }
     }
 
-    pub fn detail_view(
-        &self,
-        fqns: &dyn naviscope_api::models::symbol::FqnReader,
-    ) -> Option<String> {
+    /// Short description: coordinates for dependencies, project path for modules.
+    pub fn detail_view(&self) -> Option<String> {
         match &self.element {
+            GradleStorageElement::Dependency(d) => Some(d.coordinates.clone()),
+            GradleStorageElement::Module(m) => Some(m.project_path.clone()),
+        }
+    }
+
+    /// Gradle-style declaration, e.g. `implementation 'g:a:1.0'` or `project ':core'`.
+    pub fn signature_view(&self) -> String {
+        match &self.element {
+            GradleStorageElement::Dependency(d) if d.configurations.is_empty() => {
+                format!("'{}'", d.coordinates)
+            }
             GradleStorageElement::Dependency(d) => {
-                let mut detail = String::new();
-                if let Some(group_sid) = d.group_sid {
-                    detail.push_str(fqns.resolve_atom(naviscope_api::models::symbol::Symbol(
-                        lasso::Spur::try_from_usize(group_sid as usize).unwrap(),
-                    )));
-                }
-                if let Some(version_sid) = d.version_sid {
-                    if !detail.is_empty() {
-                        detail.push(':');
-                    }
-                    detail.push_str(fqns.resolve_atom(naviscope_api::models::symbol::Symbol(
-                        lasso::Spur::try_from_usize(version_sid as usize).unwrap(),
-                    )));
-                }
-                if d.is_project {
-                    detail.push_str(" (Project)");
-                }
-                if detail.is_empty() {
-                    None
-                } else {
-                    Some(detail)
-                }
+                format!("{} '{}'", d.configurations.join(", "), d.coordinates)
             }
-            _ => None,
+            GradleStorageElement::Module(m) => format!("project '{}'", m.project_path),
+        }
+    }
+
+    /// Configurations a dependency is declared in.
+    pub fn configurations(&self) -> &[String] {
+        match &self.element {
+            GradleStorageElement::Dependency(d) => &d.configurations,
+            GradleStorageElement::Module(_) => &[],
         }
     }
 }
@@ -53,22 +50,18 @@ impl NodeMetadata for GradleNodeMetadata {
     }
 }
 
-// The IndexMetadata trait and its implementation are removed as per the instruction
-// to remove unused imports and the provided snippet which removes IndexMetadata
-// from the naviscope_plugin import.
-// If this causes compilation errors, the user's instruction or snippet was incomplete.
-// impl IndexMetadata for GradleNodeMetadata {
-//     fn as_any(&self) -> &dyn Any {
-//         self
-//     }
-
-//     fn intern(&self, _interner: &mut dyn SymbolInterner) -> Arc<dyn NodeMetadata> {
-//         // GradleNodeMetadata is already interned (contains GradleStorageElement)
-//         Arc::new(GradleNodeMetadata {
-//             element: self.element.clone(),
-//         })
-//     }
-// }
+impl IndexMetadata for GradleNodeMetadata {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn intern(&self, _interner: &mut dyn SymbolInterner) -> Arc<dyn NodeMetadata> {
+        // Gradle metadata holds plain strings, so storage uses it unchanged
+        Arc::new(GradleNodeMetadata {
+            element: self.element.clone(),
+        })
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "type", rename_all = "lowercase")]
@@ -92,7 +85,10 @@ impl GradleStorageElement {}
 pub struct GradleModule {}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct GradleModuleStorage {}
+pub struct GradleModuleStorage {
+    /// Gradle project path (`:` for the root, `:core:spring-boot` for subprojects)
+    pub project_path: String,
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GradleDependency {
@@ -103,9 +99,10 @@ pub struct GradleDependency {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GradleDependencyStorage {
-    pub group_sid: Option<u32>,
-    pub version_sid: Option<u32>,
-    pub is_project: bool,
+    /// `group:name:version`, omitting missing parts
+    pub coordinates: String,
+    /// Configurations declaring the dependency, sorted (`api`, `implementation`, ...)
+    pub configurations: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -115,6 +112,9 @@ pub struct GradleParseResult {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RawGradleDependency {
+    /// Configuration the dependency is declared in (`implementation`, `testImplementation`, ...)
+    #[serde(default)]
+    pub configuration: Option<String>,
     pub group: Option<String>,
     pub name: String,
     pub version: Option<String>,
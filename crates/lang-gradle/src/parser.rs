@@ -52,6 +52,13 @@ pub fn parse_dependencies(source_code: &str) -> Result<Vec<RawGradleDependency>>
     let mut dependencies = Vec::new();
 
     while let Some(mat) = matches.next() {
+        let configuration = mat
+            .captures
+            .iter()
+            .find(|c| c.index == indices.configuration)
+            .and_then(|c| c.node.utf8_text(source_code.as_bytes()).ok())
+            .map(|name| name.to_string());
+
         // 1. External dependencies
         if let Some(_cap) = mat.captures.iter().find(|c| c.index == indices.item) {
             if let Some(str_cap) = mat.captures.iter().find(|c| c.index == indices.dep_string) {
@@ -61,6 +68,7 @@ pub fn parse_dependencies(source_code: &str) -> Result<Vec<RawGradleDependency>>
                     let parts: Vec<&str> = dependency_str.split(':').collect();
                     if parts.len() == 3 {
                         dependencies.push(RawGradleDependency {
+                            configuration: configuration.clone(),
                             group: Some(parts[0].to_string()),
                             name: parts[1].to_string(),
                             version: Some(parts[2].to_string()),
@@ -87,6 +95,7 @@ pub fn parse_dependencies(source_code: &str) -> Result<Vec<RawGradleDependency>>
                 if range.end - range.start >= 2 {
                     let project_path = &source_code[range.start + 1..range.end - 1];
                     dependencies.push(RawGradleDependency {
+                        configuration: configuration.clone(),
                         group: None,
                         name: project_path.to_string(),
                         version: None,
@@ -194,9 +203,21 @@ mod tests {
         assert_eq!(dependencies[0].group, Some("com.google.guava".to_string()));
         assert_eq!(dependencies[0].name, "guava");
         assert_eq!(dependencies[0].is_project, false);
+        assert_eq!(
+            dependencies[0].configuration.as_deref(),
+            Some("implementation")
+        );
+        assert_eq!(
+            dependencies[1].configuration.as_deref(),
+            Some("testImplementation")
+        );
 
         assert_eq!(dependencies[2].name, ":core:spring-boot");
         assert_eq!(dependencies[2].is_project, true);
+        assert_eq!(
+            dependencies[2].configuration.as_deref(),
+            Some("implementation")
+        );
     }
 
     #[test]
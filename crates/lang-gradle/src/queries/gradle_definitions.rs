@@ -1,6 +1,7 @@
 naviscope_plugin::decl_indices!(GradleIndices, {
     block => "dependencies_block",
     dep_string => "dep_string",
+    configuration => "method_name",
     item => "dependency_item",
     project_item => "project_dependency_item",
     project_path => "project_path",
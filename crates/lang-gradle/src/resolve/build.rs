@@ -1,3 +1,7 @@
+use crate::model::{
+    GradleDependencyStorage, GradleModuleStorage, GradleNodeMetadata, GradleStorageElement,
+    RawGradleDependency,
+};
 use naviscope_api::models::graph::{
     DisplaySymbolLocation, EdgeType, EmptyMetadata, GraphEdge, NodeKind, NodeSource,
 };
@@ -5,7 +9,7 @@ use naviscope_api::models::symbol::{NodeId, Range};
 use naviscope_plugin::{
     BuildIndexCap, IndexNode, ParsedContent, ParsedFile, ProjectContext, ResolvedUnit,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -144,28 +148,35 @@ impl BuildIndexCap for GradleResolver {
 
         // --- Step 4: Assign Module IDs ---
         let mut path_to_id: HashMap<PathBuf, NodeId> = HashMap::new();
+        let mut project_paths: HashMap<PathBuf, String> = HashMap::new();
 
         for path in &sorted_paths {
-            let id_str = if path == &root_path {
+            let (id_str, project_path) = if path == &root_path {
                 // Root module is now a child of project
-                format!("{}::module:{}", project_id_str, project_name)
+                (
+                    format!("{}::module:{}", project_id_str, project_name),
+                    ":".to_string(),
+                )
             } else if path.starts_with(&root_path) {
                 let rel = path.strip_prefix(&root_path).unwrap();
-                let logical = rel
+                let segments = rel
                     .components()
                     .map(|c| c.as_os_str().to_string_lossy())
-                    .collect::<Vec<_>>()
-                    .join("/");
-                format!("{}::module:{}", project_id_str, logical)
+                    .collect::<Vec<_>>();
+                (
+                    format!("{}::module:{}", project_id_str, segments.join("/")),
+                    format!(":{}", segments.join(":")),
+                )
             } else {
                 // External modules (e.g., buildSrc)
-                format!(
-                    "{}::module:{}",
-                    project_id_str,
-                    path.file_name().unwrap_or_default().to_string_lossy()
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                (
+                    format!("{}::module:{}", project_id_str, name),
+                    format!(":{}", name),
                 )
             };
             path_to_id.insert(path.clone(), NodeId::Flat(id_str));
+            project_paths.insert(path.clone(), project_path);
         }
 
         // --- Step 5: Construct Module Nodes and Hierarchy ---
@@ -206,7 +217,7 @@ impl BuildIndexCap for GradleResolver {
                         },
                         selection_range: None,
                     }),
-                metadata: Arc::new(EmptyMetadata),
+                metadata: module_metadata(&project_paths[&root_path]),
             });
 
             unit.add_edge(
@@ -257,7 +268,7 @@ impl BuildIndexCap for GradleResolver {
                         },
                         selection_range: None,
                     }),
-                metadata: Arc::new(EmptyMetadata),
+                metadata: module_metadata(&project_paths[path]),
             });
 
             context.path_to_module.insert(path.clone(), id.to_string());
@@ -294,11 +305,15 @@ impl BuildIndexCap for GradleResolver {
         }
 
         // --- Step 6: Build Dependencies ---
+        // External dependency nodes are shared between modules, so the
+        // configurations declaring them are merged before the nodes are added.
+        let mut external_deps: BTreeMap<String, ExternalDependency> = BTreeMap::new();
+
         for path in &sorted_paths {
             let data = module_map.get(path).unwrap();
             let id = path_to_id.get(path).unwrap();
 
-            if let Some((_, content)) = &data.build_file {
+            if let Some((build_file, content)) = &data.build_file {
                 for dep in &content.dependencies {
                     let target_id_str = if dep.is_project {
                         let clean_name = dep
@@ -311,51 +326,89 @@ impl BuildIndexCap for GradleResolver {
                         let version = dep.version.as_deref().unwrap_or("");
                         format!("dep:{}:{}:{}", group, dep.name, version)
                     };
-                    let target_id = NodeId::Flat(target_id_str);
 
                     if !dep.is_project {
-                        unit.add_node(IndexNode {
-                            id: target_id.clone(),
-                            name: dep.name.clone(),
-                            kind: NodeKind::Dependency,
-                            lang: "gradle".to_string(),
-                            source: NodeSource::External,
-                            status: naviscope_api::models::graph::ResolutionStatus::Resolved,
-                            location: Some(DisplaySymbolLocation {
-                                path: data
-                                    .build_file
-                                    .as_ref()
-                                    .unwrap()
-                                    .0
-                                    .file
-                                    .path
-                                    .to_string_lossy()
-                                    .to_string(),
-                                range: Range {
-                                    start_line: 0,
-                                    start_col: 0,
-                                    end_line: 0,
-                                    end_col: 0,
-                                },
-                                selection_range: None,
-                            }),
-                            metadata: Arc::new(EmptyMetadata),
-                        });
+                        let entry =
+                            external_deps
+                                .entry(target_id_str.clone())
+                                .or_insert_with(|| ExternalDependency {
+                                    name: dep.name.clone(),
+                                    coordinates: coordinates(dep),
+                                    declared_in: build_file.file.path.clone(),
+                                    configurations: BTreeSet::new(),
+                                });
+                        entry.configurations.extend(dep.configuration.clone());
                     }
 
                     unit.add_edge(
                         id.clone(),
-                        target_id,
+                        NodeId::Flat(target_id_str),
                         GraphEdge::new(EdgeType::UsesDependency),
                     );
                 }
             }
         }
 
+        for (id, dep) in external_deps {
+            let metadata = GradleNodeMetadata::new(GradleStorageElement::Dependency(
+                GradleDependencyStorage {
+                    coordinates: dep.coordinates,
+                    configurations: dep.configurations.into_iter().collect(),
+                },
+            ));
+            unit.add_node(IndexNode {
+                id: NodeId::Flat(id),
+                name: dep.name,
+                kind: NodeKind::Dependency,
+                lang: "gradle".to_string(),
+                source: NodeSource::External,
+                status: naviscope_api::models::graph::ResolutionStatus::Resolved,
+                location: Some(DisplaySymbolLocation {
+                    path: dep.declared_in.to_string_lossy().to_string(),
+                    range: Range {
+                        start_line: 0,
+                        start_col: 0,
+                        end_line: 0,
+                        end_col: 0,
+                    },
+                    selection_range: None,
+                }),
+                metadata: Arc::new(metadata),
+            });
+        }
+
         Ok((unit, context))
     }
 }
 
+struct ExternalDependency {
+    name: String,
+    coordinates: String,
+    declared_in: PathBuf,
+    configurations: BTreeSet<String>,
+}
+
+/// `group:name:version`, leaving out the parts the build file does not specify.
+fn coordinates(dep: &RawGradleDependency) -> String {
+    [
+        dep.group.as_deref(),
+        Some(dep.name.as_str()),
+        dep.version.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(":")
+}
+
+fn module_metadata(project_path: &str) -> Arc<GradleNodeMetadata> {
+    Arc::new(GradleNodeMetadata::new(GradleStorageElement::Module(
+        GradleModuleStorage {
+            project_path: project_path.to_string(),
+        },
+    )))
+}
+
 struct ModuleData<'a> {
     build_file: Option<(&'a ParsedFile, crate::model::GradleParseResult)>,
     settings_file: Option<(&'a ParsedFile, crate::model::GradleSettings)>,
@@ -372,6 +425,7 @@ mod tests {
                 path: PathBuf::from(path),
                 content_hash: 0,
                 last_modified: 0,
+                generated: false,
             },
             content,
         }
@@ -449,4 +503,62 @@ mod tests {
             == "project:spring-boot-build::module:spring-boot-project"
             && t == "project:spring-boot-build::module:spring-boot-project/spring-boot"));
     }
+
+    fn gradle_meta<'a>(unit: &'a ResolvedUnit, id: &str) -> &'a GradleNodeMetadata {
+        unit.nodes
+            .get(&NodeId::Flat(id.to_string()))
+            .and_then(|n| n.metadata.as_any().downcast_ref::<GradleNodeMetadata>())
+            .expect("gradle metadata")
+    }
+
+    #[test]
+    fn test_dependency_details_merge_configurations() {
+        let resolver = GradleResolver::new();
+        let guava = |configuration: &str| RawGradleDependency {
+            configuration: Some(configuration.to_string()),
+            group: Some("com.google.guava".to_string()),
+            name: "guava".to_string(),
+            version: Some("31.1-jre".to_string()),
+            is_project: false,
+            id: String::new(),
+        };
+
+        let root_build = create_mock_file(
+            "/repo/build.gradle",
+            ParsedContent::Metadata(
+                serde_json::to_value(crate::model::GradleParseResult {
+                    dependencies: vec![guava("testImplementation")],
+                })
+                .unwrap(),
+            ),
+        );
+        let app_build = create_mock_file(
+            "/repo/app/build.gradle",
+            ParsedContent::Metadata(
+                serde_json::to_value(crate::model::GradleParseResult {
+                    dependencies: vec![guava("implementation")],
+                })
+                .unwrap(),
+            ),
+        );
+
+        let files = vec![&root_build, &app_build];
+        let (unit, _) = resolver.compile_build(&files).unwrap();
+
+        let dep = gradle_meta(&unit, "dep:com.google.guava:guava:31.1-jre");
+        assert_eq!(
+            dep.configurations(),
+            ["implementation", "testImplementation"]
+        );
+        assert_eq!(
+            dep.signature_view(),
+            "implementation, testImplementation 'com.google.guava:guava:31.1-jre'"
+        );
+
+        let app = gradle_meta(&unit, "project:repo::module:app");
+        assert_eq!(app.detail_view().as_deref(), Some(":app"));
+        assert_eq!(app.signature_view(), "project ':app'");
+        let root = gradle_meta(&unit, "project:repo::module:repo");
+        assert_eq!(root.detail_view().as_deref(), Some(":"));
+    }
 }
@@ -0,0 +1,5 @@
+use crate::GroovyPlugin;
+use naviscope_plugin::AssetCap;
+
+/// Groovy libraries are jars, indexed by the Java plugin
+impl AssetCap for GroovyPlugin {}
@@ -0,0 +1,273 @@
+use crate::GroovyPlugin;
+use naviscope_api::lifecycle::WarningSeverity;
+use naviscope_api::models::graph::{
+    EdgeType, EmptyMetadata, GraphEdge, NodeKind, NodeSource, ResolutionStatus,
+};
+use naviscope_api::models::symbol::NodeId;
+use naviscope_plugin::{
+    BoxError, DeferredSymbol, GlobalParseResult, GraphOp, IndexNode, ParsedContent, ParsedFile,
+    ProjectContext, ResolvedUnit, SourceAnalyzeArtifact, SourceCollectArtifact, SourceIndexCap,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+struct GroovyCollectArtifact {
+    parse_result: GlobalParseResult,
+    unit: ResolvedUnit,
+    container_id: NodeId,
+    type_symbols: Vec<String>,
+    method_symbols: Vec<String>,
+    provided_dependency_symbols: Vec<String>,
+    required_dependency_symbols: Vec<String>,
+}
+
+struct GroovyAnalyzeArtifact {
+    unit: ResolvedUnit,
+}
+
+impl SourceCollectArtifact for GroovyCollectArtifact {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn std::any::Any + Send + Sync> {
+        self
+    }
+
+    fn collected_type_symbols(&self) -> &[String] {
+        &self.type_symbols
+    }
+
+    fn collected_method_symbols(&self) -> &[String] {
+        &self.method_symbols
+    }
+
+    fn provided_dependency_symbols(&self) -> &[String] {
+        &self.provided_dependency_symbols
+    }
+
+    fn required_dependency_symbols(&self) -> &[String] {
+        &self.required_dependency_symbols
+    }
+
+    fn declared_package(&self) -> Option<&str> {
+        self.parse_result.package_name.as_deref()
+    }
+}
+
+impl SourceAnalyzeArtifact for GroovyAnalyzeArtifact {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn std::any::Any + Send + Sync> {
+        self
+    }
+}
+
+impl SourceIndexCap for GroovyPlugin {
+    fn collect_source(
+        &self,
+        file: &ParsedFile,
+        context: &ProjectContext,
+    ) -> Result<Box<dyn SourceCollectArtifact>, BoxError> {
+        let parse_result = match &file.content {
+            ParsedContent::Language(res) => res.clone(),
+            ParsedContent::Unparsed(src) => self.parser.parse_file(src, Some(file.path()))?,
+            ParsedContent::Lazy => {
+                let src = std::fs::read_to_string(file.path())
+                    .map_err(|e| format!("Failed to read file {}: {}", file.path().display(), e))?;
+                self.parser.parse_file(&src, Some(file.path()))?
+            }
+            _ => return Err("Unsupported parsed content in Groovy collect_source".into()),
+        };
+
+        let mut unit = ResolvedUnit::new();
+        if let Some(tree) = &parse_result.tree
+            && tree.root_node().has_error()
+        {
+            unit.warn(
+                WarningSeverity::Warning,
+                "syntax errors; declarations overlapping them may be missing",
+            );
+        }
+        unit.identifiers = parse_result.output.identifiers.clone();
+        unit.ops.push(GraphOp::UpdateIdentifiers {
+            path: Arc::from(file.path()),
+            identifiers: unit.identifiers.clone(),
+        });
+
+        let module_id = context
+            .find_module_for_path(file.path())
+            .unwrap_or_else(|| "module::root".to_string());
+        let container_id = match &parse_result.package_name {
+            Some(pkg) => {
+                let package_id = NodeId::Structured(
+                    pkg.split('.')
+                        .map(|s| (NodeKind::Package, s.to_string()))
+                        .collect(),
+                );
+                unit.add_node(IndexNode {
+                    id: package_id.clone(),
+                    name: pkg.clone(),
+                    kind: NodeKind::Package,
+                    lang: "groovy".to_string(),
+                    source: NodeSource::Project,
+                    status: ResolutionStatus::Resolved,
+                    location: None,
+                    metadata: Arc::new(EmptyMetadata),
+                });
+                unit.add_edge(
+                    module_id.into(),
+                    package_id.clone(),
+                    GraphEdge::new(EdgeType::Contains),
+                );
+                package_id
+            }
+            None => module_id.into(),
+        };
+
+        let symbols_of = |kinds: &[NodeKind]| -> Vec<String> {
+            parse_result
+                .output
+                .nodes
+                .iter()
+                .filter(|n| kinds.contains(&n.kind))
+                .map(|n| n.id.to_string())
+                .collect()
+        };
+        let type_symbols = symbols_of(&[
+            NodeKind::Class,
+            NodeKind::Interface,
+            NodeKind::Enum,
+            NodeKind::Annotation,
+        ]);
+        let method_symbols = symbols_of(&[NodeKind::Method, NodeKind::Constructor]);
+        let package_symbol = parse_result
+            .package_name
+            .as_ref()
+            .map(|pkg| format!("package:{pkg}"));
+        let provided_dependency_symbols = type_symbols
+            .iter()
+            .cloned()
+            .chain(package_symbol.clone())
+            .collect();
+        let required_dependency_symbols = package_symbol
+            .into_iter()
+            .chain(parse_result.imports.iter().map(|import| {
+                import
+                    .strip_suffix(".*")
+                    .map(|pkg| format!("package:{pkg}"))
+                    .unwrap_or_else(|| import.clone())
+            }))
+            .collect();
+
+        Ok(Box::new(GroovyCollectArtifact {
+            parse_result,
+            unit,
+            container_id,
+            type_symbols,
+            method_symbols,
+            provided_dependency_symbols,
+            required_dependency_symbols,
+        }))
+    }
+
+    fn analyze_source(
+        &self,
+        collected: Box<dyn SourceCollectArtifact>,
+        context: &ProjectContext,
+    ) -> Result<Box<dyn SourceAnalyzeArtifact>, BoxError> {
+        let collected = collected
+            .into_any()
+            .downcast::<GroovyCollectArtifact>()
+            .map_err(|_| "Groovy analyze_source received incompatible collect artifact")?;
+        let GroovyCollectArtifact {
+            parse_result,
+            mut unit,
+            container_id,
+            type_symbols,
+            ..
+        } = *collected;
+
+        for node in &parse_result.output.nodes {
+            unit.add_node(node.clone());
+            let parent = match &node.id {
+                NodeId::Structured(parts) if parts.len() > 1 => {
+                    let owner = &parts[..parts.len() - 1];
+                    if owner.iter().all(|(kind, _)| *kind == NodeKind::Package) {
+                        container_id.clone()
+                    } else {
+                        NodeId::Structured(owner.to_vec())
+                    }
+                }
+                _ => container_id.clone(),
+            };
+            unit.add_edge(parent, node.id.clone(), GraphEdge::new(EdgeType::Contains));
+        }
+
+        let mut known_types: HashSet<&str> = type_symbols.iter().map(String::as_str).collect();
+        known_types.extend(context.symbol_table.type_symbols.iter().map(String::as_str));
+        for rel in &parse_result.output.relations {
+            let raw = rel.target_id.to_string();
+            let resolved = resolve_type(
+                &raw,
+                parse_result.package_name.as_deref(),
+                &parse_result.imports,
+                |fqn| known_types.contains(fqn),
+            );
+            match resolved {
+                Some(target) => unit.add_edge(
+                    rel.source_id.clone(),
+                    NodeId::Flat(target),
+                    GraphEdge::new(rel.edge_type.clone()),
+                ),
+                None => unit.deferred_symbols.push(DeferredSymbol { target: raw }),
+            }
+        }
+
+        Ok(Box::new(GroovyAnalyzeArtifact { unit }))
+    }
+
+    fn lower_source(
+        &self,
+        analyzed: Box<dyn SourceAnalyzeArtifact>,
+        _context: &ProjectContext,
+    ) -> Result<ResolvedUnit, BoxError> {
+        let analyzed = analyzed
+            .into_any()
+            .downcast::<GroovyAnalyzeArtifact>()
+            .map_err(|_| "Groovy lower_source received incompatible analyze artifact")?;
+        Ok(analyzed.unit)
+    }
+}
+
+/// FQN of the type `name` refers to in a file: a qualified name as is, then
+/// a single-type import, a type of the same package, then a type of a
+/// wildcard import.
+pub(crate) fn resolve_type(
+    name: &str,
+    package: Option<&str>,
+    imports: &[String],
+    is_known: impl Fn(&str) -> bool,
+) -> Option<String> {
+    if name.contains('.') {
+        return Some(name.to_string());
+    }
+    let suffix = format!(".{}", name);
+    if let Some(import) = imports.iter().find(|i| i.ends_with(&suffix)) {
+        return Some(import.clone());
+    }
+    let same_package = match package {
+        Some(pkg) => format!("{}.{}", pkg, name),
+        None => name.to_string(),
+    };
+    if is_known(&same_package) {
+        return Some(same_package);
+    }
+    imports
+        .iter()
+        .filter_map(|i| i.strip_suffix(".*"))
+        .map(|pkg| format!("{}.{}", pkg, name))
+        .find(|candidate| is_known(candidate))
+}
@@ -0,0 +1,12 @@
+use crate::GroovyPlugin;
+use naviscope_plugin::FileMatcherCap;
+use std::path::Path;
+
+impl FileMatcherCap for GroovyPlugin {
+    fn supports_path(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .map(|ext| ext.eq_ignore_ascii_case("groovy"))
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,4 @@
+use crate::GroovyPlugin;
+use naviscope_plugin::MetadataCodecCap;
+
+impl MetadataCodecCap for GroovyPlugin {}
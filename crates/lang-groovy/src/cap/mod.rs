@@ -0,0 +1,10 @@
+mod asset;
+mod indexing;
+mod matcher;
+mod metadata;
+mod parse;
+mod presentation;
+mod registration;
+mod semantic;
+
+pub use registration::groovy_caps;
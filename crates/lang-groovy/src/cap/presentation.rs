@@ -0,0 +1,57 @@
+use crate::GroovyPlugin;
+use naviscope_api::models::graph::{DisplayGraphNode, GraphNode, NodeKind};
+use naviscope_api::models::symbol::FqnReader;
+use naviscope_plugin::{
+    NamingConvention, NodePresenter, PresentationCap, StandardNamingConvention,
+};
+use std::sync::Arc;
+
+impl NodePresenter for GroovyPlugin {
+    fn render_display_node(&self, node: &GraphNode, fqns: &dyn FqnReader) -> DisplayGraphNode {
+        let id = StandardNamingConvention.render_fqn(node.id, fqns);
+        let container = match id.split_once('#') {
+            Some((owner, _member)) => Some(owner.to_string()),
+            None => id.rsplit_once('.').map(|(owner, _)| owner.to_string()),
+        };
+        DisplayGraphNode {
+            symbol_id: StandardNamingConvention.symbol_id("groovy", &id),
+            generated: false,
+            name: fqns.resolve_atom(node.name).to_string(),
+            kind: node.kind.clone(),
+            lang: "groovy".to_string(),
+            source: node.source.clone(),
+            status: node.status,
+            location: node.location.as_ref().map(|l| l.to_display(fqns)),
+            detail: container.map(|c| format!("*Defined in `{}`*", c)),
+            signature: None,
+            modifiers: vec![],
+            children: None,
+            id,
+        }
+    }
+}
+
+impl PresentationCap for GroovyPlugin {
+    fn naming_convention(&self) -> Option<Arc<dyn NamingConvention>> {
+        Some(Arc::new(StandardNamingConvention))
+    }
+
+    fn node_presenter(&self) -> Option<Arc<dyn NodePresenter>> {
+        Some(Arc::new(self.clone()))
+    }
+
+    fn symbol_kind(&self, kind: &NodeKind) -> lsp_types::SymbolKind {
+        use lsp_types::SymbolKind;
+        match kind {
+            NodeKind::Class => SymbolKind::CLASS,
+            NodeKind::Interface => SymbolKind::INTERFACE,
+            NodeKind::Enum => SymbolKind::ENUM,
+            NodeKind::Annotation => SymbolKind::INTERFACE,
+            NodeKind::Method => SymbolKind::METHOD,
+            NodeKind::Constructor => SymbolKind::CONSTRUCTOR,
+            NodeKind::Field => SymbolKind::FIELD,
+            NodeKind::Package => SymbolKind::PACKAGE,
+            _ => SymbolKind::VARIABLE,
+        }
+    }
+}
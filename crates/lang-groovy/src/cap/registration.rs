@@ -0,0 +1,18 @@
+use crate::GroovyPlugin;
+use naviscope_api::models::Language;
+use naviscope_plugin::{LanguageCaps, SemanticCap};
+use std::sync::Arc;
+
+pub fn groovy_caps() -> LanguageCaps {
+    let plugin = Arc::new(GroovyPlugin::new());
+    LanguageCaps {
+        language: Language::GROOVY,
+        matcher: plugin.clone(),
+        parser: plugin.clone(),
+        semantic: plugin.clone() as Arc<dyn SemanticCap>,
+        indexing: plugin.clone(),
+        asset: plugin.clone(),
+        presentation: plugin.clone(),
+        metadata_codec: plugin,
+    }
+}
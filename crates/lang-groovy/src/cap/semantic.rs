@@ -0,0 +1,177 @@
+use crate::GroovyPlugin;
+use crate::cap::indexing::resolve_type;
+use naviscope_api::models::SymbolResolution;
+use naviscope_api::models::graph::{DisplayGraphNode, EdgeType, NodeKind};
+use naviscope_api::models::symbol::{FqnId, Range, SymbolIntent};
+use naviscope_plugin::utils::{RawSymbol, build_symbol_hierarchy, range_from_ts};
+use naviscope_plugin::{
+    CodeGraph, Direction, LspSyntaxService, ReferenceCheckService, SymbolQueryService,
+    SymbolResolveService,
+};
+use tree_sitter::{Point, Tree};
+
+impl SymbolResolveService for GroovyPlugin {
+    /// Resolves declarations of the file, and types named where the imports
+    /// or the package of the file make them visible. Members used through an
+    /// expression are not resolved: Groovy types them at run time.
+    fn resolve_at(
+        &self,
+        tree: &Tree,
+        source: &str,
+        line: usize,
+        byte_col: usize,
+        index: &dyn CodeGraph,
+    ) -> Option<SymbolResolution> {
+        let point = Point::new(line, byte_col);
+        let node = tree
+            .root_node()
+            .named_descendant_for_point_range(point, point)?;
+        let file = self.parser.analyze(tree, source, None);
+        if let Some(decl) = file
+            .declarations
+            .iter()
+            .find(|d| d.name_node.is_some_and(|n| n.id() == node.id()))
+        {
+            return Some(SymbolResolution::Precise(
+                decl.id.to_string(),
+                intent(&decl.kind),
+            ));
+        }
+
+        if !matches!(
+            node.kind(),
+            "type_identifier" | "scoped_type_identifier" | "identifier"
+        ) {
+            return None;
+        }
+        let name = node.utf8_text(source.as_bytes()).ok()?;
+        let declared: Vec<String> = file
+            .declarations
+            .iter()
+            .filter(|d| is_type(&d.kind))
+            .map(|d| d.id.to_string())
+            .collect();
+        let is_known =
+            |fqn: &str| declared.iter().any(|d| d == fqn) || !index.resolve_fqn(fqn).is_empty();
+        resolve_type(name, file.package.as_deref(), &file.imports, is_known)
+            .filter(|fqn| is_known(fqn))
+            .map(|fqn| SymbolResolution::Precise(fqn, SymbolIntent::Type))
+    }
+}
+
+impl SymbolQueryService for GroovyPlugin {
+    fn find_matches(&self, index: &dyn CodeGraph, res: &SymbolResolution) -> Vec<FqnId> {
+        res.fqn()
+            .map(|fqn| index.resolve_fqn(fqn))
+            .unwrap_or_default()
+    }
+
+    fn resolve_type_of(
+        &self,
+        _index: &dyn CodeGraph,
+        _res: &SymbolResolution,
+    ) -> Vec<SymbolResolution> {
+        Vec::new()
+    }
+
+    fn find_implementations(&self, index: &dyn CodeGraph, res: &SymbolResolution) -> Vec<FqnId> {
+        self.find_matches(index, res)
+            .into_iter()
+            .flat_map(|id| {
+                let mut subtypes =
+                    index.get_neighbors(id, Direction::Incoming, Some(EdgeType::InheritsFrom));
+                subtypes.extend(index.get_neighbors(
+                    id,
+                    Direction::Incoming,
+                    Some(EdgeType::Implements),
+                ));
+                subtypes
+            })
+            .collect()
+    }
+}
+
+impl LspSyntaxService for GroovyPlugin {
+    fn parse(&self, source: &str, old_tree: Option<&Tree>) -> Option<Tree> {
+        self.parser.parse(source, old_tree)
+    }
+
+    fn extract_symbols(&self, tree: &Tree, source: &str) -> Vec<DisplayGraphNode> {
+        let raw_symbols = self
+            .parser
+            .analyze(tree, source, None)
+            .declarations
+            .into_iter()
+            .map(|decl| RawSymbol {
+                name: decl.name,
+                kind: decl.kind,
+                range: range_from_ts(decl.node.range()),
+                selection_range: range_from_ts(decl.name_node.unwrap_or(decl.node).range()),
+                node: decl.node,
+            })
+            .collect();
+        build_symbol_hierarchy(raw_symbols)
+    }
+
+    /// Identifiers spelling the simple name of the target
+    fn find_occurrences(
+        &self,
+        source: &str,
+        tree: &Tree,
+        target: &SymbolResolution,
+        _index: Option<&dyn CodeGraph>,
+    ) -> Vec<Range> {
+        let Some(name) = target.fqn().map(simple_name) else {
+            return Vec::new();
+        };
+        let mut ranges = Vec::new();
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            if matches!(node.kind(), "identifier" | "type_identifier")
+                && node.utf8_text(source.as_bytes()) == Ok(name)
+            {
+                ranges.push(range_from_ts(node.range()));
+            }
+            let mut cursor = node.walk();
+            stack.extend(node.named_children(&mut cursor));
+        }
+        ranges.sort_by_key(|r| (r.start_line, r.start_col));
+        ranges
+    }
+}
+
+impl ReferenceCheckService for GroovyPlugin {
+    fn is_reference_to(
+        &self,
+        _graph: &dyn CodeGraph,
+        candidate: &SymbolResolution,
+        target: &SymbolResolution,
+    ) -> bool {
+        candidate == target
+    }
+}
+
+fn is_type(kind: &NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Class | NodeKind::Interface | NodeKind::Enum | NodeKind::Annotation
+    )
+}
+
+fn intent(kind: &NodeKind) -> SymbolIntent {
+    match kind {
+        NodeKind::Method | NodeKind::Constructor => SymbolIntent::Method,
+        NodeKind::Field => SymbolIntent::Field,
+        kind if is_type(kind) => SymbolIntent::Type,
+        _ => SymbolIntent::Unknown,
+    }
+}
+
+/// `add` for `com.example.Calculator#add(int,int)`, `Calculator` for
+/// `com.example.Calculator`
+fn simple_name(fqn: &str) -> &str {
+    match fqn.rsplit_once('#') {
+        Some((_, member)) => member.split('(').next().unwrap_or(member),
+        None => fqn.rsplit('.').next().unwrap_or(fqn),
+    }
+}
@@ -0,0 +1,28 @@
+pub mod cap;
+pub mod parser;
+pub mod script;
+
+pub use cap::groovy_caps;
+pub use parser::GroovyParser;
+
+use std::sync::Arc;
+
+/// Groovy sources, including Spock specifications and `buildSrc` build logic
+#[derive(Clone)]
+pub struct GroovyPlugin {
+    pub(crate) parser: Arc<parser::GroovyParser>,
+}
+
+impl GroovyPlugin {
+    pub fn new() -> Self {
+        Self {
+            parser: Arc::new(parser::GroovyParser::new()),
+        }
+    }
+}
+
+impl Default for GroovyPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,506 @@
+use naviscope_api::models::graph::{DisplaySymbolLocation, EdgeType, EmptyMetadata, NodeKind};
+use naviscope_api::models::graph::{NodeSource, ResolutionStatus};
+use naviscope_api::models::symbol::NodeId;
+use naviscope_plugin::naming::format_method_name;
+use naviscope_plugin::utils::range_from_ts;
+use naviscope_plugin::{BoxError, GlobalParseResult, IndexNode, IndexRelation, ParseOutput};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Arc;
+use tree_sitter::{Node, Parser, Tree};
+
+/// Type written for parameters declared without one (`def` or untyped)
+const DYNAMIC_TYPE: &str = "Object";
+
+pub struct GroovyParser {
+    language: tree_sitter::Language,
+}
+
+/// A class, method or field declared in a Groovy file
+#[derive(Debug, Clone)]
+pub struct Declaration<'t> {
+    pub id: NodeId,
+    pub name: String,
+    pub kind: NodeKind,
+    pub node: Node<'t>,
+    pub name_node: Option<Node<'t>>,
+    /// Types named in `extends` and `implements` clauses, as written
+    pub supertypes: Vec<(EdgeType, Node<'t>)>,
+}
+
+/// Package, imports and declarations of a Groovy file
+pub struct GroovyFile<'t> {
+    pub package: Option<String>,
+    /// Imported types and `pkg.*` imports; static imports are left out
+    pub imports: Vec<String>,
+    pub declarations: Vec<Declaration<'t>>,
+}
+
+impl GroovyParser {
+    pub fn new() -> Self {
+        Self {
+            language: tree_sitter_groovy::LANGUAGE.into(),
+        }
+    }
+
+    pub fn parse(&self, source: &str, old_tree: Option<&Tree>) -> Option<Tree> {
+        let mut parser = Parser::new();
+        parser.set_language(&self.language).ok()?;
+        parser.parse(source, old_tree)
+    }
+
+    /// Parses a Groovy source file into class, method and field nodes.
+    ///
+    /// Methods declared outside a class belong to the script class Groovy
+    /// compiles the file to, named after the file.
+    pub fn parse_file(
+        &self,
+        source: &str,
+        file_path: Option<&Path>,
+    ) -> Result<GlobalParseResult, BoxError> {
+        let tree = self
+            .parse(source, None)
+            .ok_or("Failed to parse Groovy file")?;
+        let script_class = file_path
+            .and_then(|p| p.file_stem())
+            .and_then(|s| s.to_str());
+        let file = self.analyze(&tree, source, script_class);
+        let status = if tree.root_node().has_error() {
+            ResolutionStatus::PartialParse
+        } else {
+            ResolutionStatus::Resolved
+        };
+
+        let mut nodes = Vec::new();
+        let mut relations = Vec::new();
+        if let Some(script) = script_class
+            && file.declarations.iter().any(|d| is_script_member(d))
+        {
+            nodes.push(IndexNode {
+                id: type_id(file.package.as_deref(), &[script.to_string()]),
+                name: script.to_string(),
+                kind: NodeKind::Class,
+                lang: "groovy".to_string(),
+                source: NodeSource::Project,
+                status,
+                location: None,
+                metadata: Arc::new(EmptyMetadata),
+            });
+        }
+        for decl in &file.declarations {
+            nodes.push(IndexNode {
+                id: decl.id.clone(),
+                name: decl.name.clone(),
+                kind: decl.kind.clone(),
+                lang: "groovy".to_string(),
+                source: NodeSource::Project,
+                status,
+                location: file_path.map(|p| DisplaySymbolLocation {
+                    path: p.to_string_lossy().to_string(),
+                    range: range_from_ts(decl.node.range()),
+                    selection_range: decl.name_node.map(|n| range_from_ts(n.range())),
+                }),
+                metadata: Arc::new(EmptyMetadata),
+            });
+            relations.extend(
+                decl.supertypes
+                    .iter()
+                    .map(|(edge_type, node)| IndexRelation {
+                        source_id: decl.id.clone(),
+                        target_id: NodeId::Flat(type_name(*node, source)),
+                        edge_type: edge_type.clone(),
+                        range: Some(range_from_ts(node.range())),
+                    }),
+            );
+        }
+
+        Ok(GlobalParseResult {
+            package_name: file.package,
+            imports: file.imports,
+            output: ParseOutput {
+                nodes,
+                relations,
+                identifiers: identifiers(tree.root_node(), source),
+            },
+            source: Some(source.to_string()),
+            tree: Some(tree),
+        })
+    }
+
+    /// Walks the tree for the package, imports and declarations of the file.
+    /// `script_class` owns the methods declared outside a class; without it
+    /// they are left out.
+    pub fn analyze<'t>(
+        &self,
+        tree: &'t Tree,
+        source: &str,
+        script_class: Option<&str>,
+    ) -> GroovyFile<'t> {
+        let root = tree.root_node();
+        let mut package = None;
+        let mut imports = Vec::new();
+        let mut cursor = root.walk();
+        for child in root.named_children(&mut cursor) {
+            match child.kind() {
+                "package_declaration" => package = qualified_name(child, source),
+                "import_declaration" if !has_child_kind(child, "static") => {
+                    if let Some(name) = qualified_name(child, source) {
+                        if has_child_kind(child, "asterisk") {
+                            imports.push(format!("{}.*", name));
+                        } else {
+                            imports.push(name);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut declarations = Vec::new();
+        let owners: Vec<String> = script_class
+            .map(|s| vec![s.to_string()])
+            .unwrap_or_default();
+        collect_declarations(
+            root,
+            source,
+            package.as_deref(),
+            &owners,
+            true,
+            &mut declarations,
+        );
+        GroovyFile {
+            package,
+            imports,
+            declarations,
+        }
+    }
+}
+
+impl Default for GroovyParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Collects declarations below `node`. `owners` are the enclosing classes,
+/// outermost first; at the top level of a script they hold the script class,
+/// which classes declared there do not belong to.
+fn collect_declarations<'t>(
+    node: Node<'t>,
+    source: &str,
+    package: Option<&str>,
+    owners: &[String],
+    top_level: bool,
+    out: &mut Vec<Declaration<'t>>,
+) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        if child.is_error() {
+            continue;
+        }
+        if let Some(kind) = type_kind(child.kind()) {
+            let Some(name_node) = child.child_by_field_name("name") else {
+                continue;
+            };
+            let name = declared_name(&text(name_node, source));
+            let mut path = if top_level {
+                Vec::new()
+            } else {
+                owners.to_vec()
+            };
+            path.push(name.clone());
+            out.push(Declaration {
+                id: type_id(package, &path),
+                name,
+                kind,
+                node: child,
+                name_node: Some(name_node),
+                supertypes: supertypes(child),
+            });
+            if let Some(body) = child.child_by_field_name("body") {
+                collect_declarations(body, source, package, &path, false, out);
+            }
+        } else if let Some(kind) = member_kind(child.kind()) {
+            if owners.is_empty() {
+                continue;
+            }
+            if kind == NodeKind::Field {
+                let mut declarators = child.walk();
+                for declarator in child.children_by_field_name("declarator", &mut declarators) {
+                    let Some(name_node) = declarator.child_by_field_name("name") else {
+                        continue;
+                    };
+                    let name = text(name_node, source);
+                    out.push(Declaration {
+                        id: member_id(package, owners, NodeKind::Field, name.clone()),
+                        name,
+                        kind: NodeKind::Field,
+                        node: child,
+                        name_node: Some(name_node),
+                        supertypes: Vec::new(),
+                    });
+                }
+                continue;
+            }
+            let name_node = child.child_by_field_name("name");
+            let name = match (kind.clone(), name_node) {
+                (_, Some(n)) => declared_name(&text(n, source)),
+                (NodeKind::Constructor, None) => owners.last().cloned().unwrap_or_default(),
+                _ => continue,
+            };
+            let types = parameter_types(child, source);
+            let types: Vec<&str> = types.iter().map(String::as_str).collect();
+            let signature = format_method_name(&name, &types);
+            out.push(Declaration {
+                id: member_id(package, owners, kind.clone(), signature),
+                name,
+                kind,
+                node: child,
+                name_node,
+                supertypes: Vec::new(),
+            });
+        } else {
+            collect_declarations(child, source, package, owners, top_level, out);
+        }
+    }
+}
+
+fn type_kind(kind: &str) -> Option<NodeKind> {
+    match kind {
+        "class_declaration" => Some(NodeKind::Class),
+        "interface_declaration" | "trait_declaration" => Some(NodeKind::Interface),
+        "enum_declaration" => Some(NodeKind::Enum),
+        "annotation_type_declaration" => Some(NodeKind::Annotation),
+        _ => None,
+    }
+}
+
+fn member_kind(kind: &str) -> Option<NodeKind> {
+    match kind {
+        "method_declaration" | "function_definition" | "function_declaration" => {
+            Some(NodeKind::Method)
+        }
+        "constructor_declaration" => Some(NodeKind::Constructor),
+        "field_declaration" => Some(NodeKind::Field),
+        _ => None,
+    }
+}
+
+/// Whether `decl` was declared outside a class and so belongs to the script
+fn is_script_member(decl: &Declaration) -> bool {
+    if !matches!(decl.kind, NodeKind::Method | NodeKind::Field) {
+        return false;
+    }
+    let mut current = decl.node.parent();
+    while let Some(parent) = current {
+        if type_kind(parent.kind()).is_some() {
+            return false;
+        }
+        current = parent.parent();
+    }
+    true
+}
+
+/// Name of a declaration, unquoted: Spock feature methods are named by a
+/// string, as in `def "adds two numbers"()`.
+pub fn declared_name(raw: &str) -> String {
+    let trimmed = raw.trim();
+    ["\"\"\"", "'''", "\"", "'"]
+        .iter()
+        .find_map(|quote| {
+            trimmed
+                .strip_prefix(quote)
+                .and_then(|s| s.strip_suffix(quote))
+        })
+        .unwrap_or(trimmed)
+        .to_string()
+}
+
+/// ID of a class nested in the classes before it in `path`. Every kind of
+/// type is keyed as a class, as Java does, so references from either
+/// language resolve to the same node.
+fn type_id(package: Option<&str>, path: &[String]) -> NodeId {
+    let parts = package_parts(package)
+        .chain(path.iter().map(|name| (NodeKind::Class, name.clone())))
+        .collect();
+    NodeId::Structured(parts)
+}
+
+fn member_id(package: Option<&str>, owners: &[String], kind: NodeKind, name: String) -> NodeId {
+    let parts = package_parts(package)
+        .chain(owners.iter().map(|owner| (NodeKind::Class, owner.clone())))
+        .chain(std::iter::once((kind, name)))
+        .collect();
+    NodeId::Structured(parts)
+}
+
+fn package_parts(package: Option<&str>) -> impl Iterator<Item = (NodeKind, String)> + '_ {
+    package
+        .into_iter()
+        .flat_map(|p| p.split('.'))
+        .filter(|part| !part.is_empty())
+        .map(|part| (NodeKind::Package, part.to_string()))
+}
+
+/// Types of the parameters of a method, as written and without type
+/// arguments. Untyped parameters are `Object`, as Groovy compiles them.
+fn parameter_types(method: Node, source: &str) -> Vec<String> {
+    let Some(params) = method.child_by_field_name("parameters") else {
+        return Vec::new();
+    };
+    let mut cursor = params.walk();
+    params
+        .named_children(&mut cursor)
+        .filter(|p| matches!(p.kind(), "formal_parameter" | "spread_parameter"))
+        .map(|p| {
+            // The grammar reads a lone untyped name as the type of a missing one
+            let untyped = p.child_by_field_name("name").is_none_or(|n| n.is_missing());
+            p.child_by_field_name("type")
+                .filter(|_| !untyped)
+                .map(|t| type_name(t, source))
+                .filter(|t| t != "def")
+                .unwrap_or_else(|| DYNAMIC_TYPE.to_string())
+        })
+        .collect()
+}
+
+/// Supertypes named by the `extends` and `implements` clauses of a type
+fn supertypes(decl: Node) -> Vec<(EdgeType, Node)> {
+    let mut result = Vec::new();
+    let mut cursor = decl.walk();
+    for clause in decl.named_children(&mut cursor) {
+        let edge_type = match clause.kind() {
+            "superclass" | "extends_interfaces" => EdgeType::InheritsFrom,
+            "super_interfaces" => EdgeType::Implements,
+            _ => continue,
+        };
+        let mut types = Vec::new();
+        collect_types(clause, &mut types);
+        result.extend(types.into_iter().map(|t| (edge_type.clone(), t)));
+    }
+    result
+}
+
+fn collect_types<'t>(node: Node<'t>, out: &mut Vec<Node<'t>>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "type_identifier" | "scoped_type_identifier" | "generic_type" => out.push(child),
+            _ => collect_types(child, out),
+        }
+    }
+}
+
+/// Name of a type as written, without type arguments
+fn type_name(node: Node, source: &str) -> String {
+    let raw = text(node, source);
+    raw.split('<').next().unwrap_or_default().trim().to_string()
+}
+
+/// Dotted name following `package` or `import`
+fn qualified_name(decl: Node, source: &str) -> Option<String> {
+    let mut cursor = decl.walk();
+    decl.named_children(&mut cursor)
+        .find(|n| matches!(n.kind(), "scoped_identifier" | "identifier"))
+        .map(|n| text(n, source))
+}
+
+fn has_child_kind(node: Node, kind: &str) -> bool {
+    let mut cursor = node.walk();
+    node.children(&mut cursor).any(|c| c.kind() == kind)
+}
+
+/// Every distinct identifier in the file, for reference search
+fn identifiers(root: Node, source: &str) -> Vec<String> {
+    let mut found = BTreeSet::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if matches!(node.kind(), "identifier" | "type_identifier") {
+            found.insert(text(node, source));
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+    found.into_iter().collect()
+}
+
+fn text(node: Node, source: &str) -> String {
+    node.utf8_text(source.as_bytes())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(result: &GlobalParseResult) -> Vec<String> {
+        result
+            .output
+            .nodes
+            .iter()
+            .map(|n| n.id.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_declared_name_unquotes_feature_methods() {
+        assert_eq!(declared_name("\"adds two numbers\""), "adds two numbers");
+        assert_eq!(declared_name("'it works'"), "it works");
+        assert_eq!(declared_name("setup"), "setup");
+    }
+
+    #[test]
+    fn test_parse_class_members() {
+        let source = r#"
+package com.example
+
+import spock.lang.Specification
+
+class CalculatorSpec extends Specification {
+    Calculator calculator
+
+    def setup() {
+        calculator = new Calculator()
+    }
+
+    int add(int a, b) {
+        a + b
+    }
+}
+"#;
+        let result = GroovyParser::new()
+            .parse_file(source, Some(Path::new("/src/CalculatorSpec.groovy")))
+            .unwrap();
+
+        assert_eq!(result.package_name.as_deref(), Some("com.example"));
+        assert_eq!(result.imports, vec!["spock.lang.Specification"]);
+        let ids = ids(&result);
+        assert!(ids.contains(&"com.example.CalculatorSpec".to_string()));
+        assert!(ids.contains(&"com.example.CalculatorSpec#calculator".to_string()));
+        assert!(ids.contains(&"com.example.CalculatorSpec#setup()".to_string()));
+        assert!(ids.contains(&"com.example.CalculatorSpec#add(int,Object)".to_string()));
+
+        let extends = &result.output.relations[0];
+        assert_eq!(extends.edge_type, EdgeType::InheritsFrom);
+        assert_eq!(extends.target_id.to_string(), "Specification");
+    }
+
+    #[test]
+    fn test_script_methods_belong_to_the_script_class() {
+        let source = r#"
+def greet(String name) {
+    println "Hello $name"
+}
+
+greet 'world'
+"#;
+        let result = GroovyParser::new()
+            .parse_file(source, Some(Path::new("/scripts/hello.groovy")))
+            .unwrap();
+
+        let ids = ids(&result);
+        assert!(ids.contains(&"hello".to_string()));
+        assert!(ids.contains(&"hello#greet(String)".to_string()));
+    }
+}
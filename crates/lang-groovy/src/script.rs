@@ -0,0 +1,140 @@
+//! Top-level statements of Groovy scripts, such as Gradle build and settings
+//! files, read from the syntax tree rather than matched in the text.
+
+use crate::parser::{GroovyParser, declared_name};
+use tree_sitter::Node;
+
+/// A call or assignment made at the top level of a script, outside of any
+/// closure: `include 'app', 'lib'` or `rootProject.name = 'demo'`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptStatement {
+    /// Called method or assigned property, with its receiver if any
+    /// (`include`, `rootProject.name`)
+    pub target: String,
+    /// String literal arguments or assigned value, unquoted
+    pub arguments: Vec<String>,
+    /// Whether the call passes a closure, as `dependencies { ... }` does
+    pub has_closure: bool,
+}
+
+/// Parses `source` and returns its top-level statements, in order.
+pub fn top_level_statements(source: &str) -> Option<Vec<ScriptStatement>> {
+    let tree = GroovyParser::new().parse(source, None)?;
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let statements = root
+        .named_children(&mut cursor)
+        .filter_map(|child| statement(child, source))
+        .collect();
+    Some(statements)
+}
+
+fn statement(node: Node, source: &str) -> Option<ScriptStatement> {
+    match node.kind() {
+        "expression_statement" => {
+            let mut cursor = node.walk();
+            let expression = node.named_children(&mut cursor).next()?;
+            statement(expression, source)
+        }
+        "method_invocation" | "juxt_function_call" => {
+            let name = text(node.child_by_field_name("name")?, source);
+            let target = match node.child_by_field_name("object") {
+                Some(object) => format!("{}.{}", text(object, source), name),
+                None => name,
+            };
+            let args = node
+                .child_by_field_name("arguments")
+                .or_else(|| node.child_by_field_name("args"));
+            let (arguments, args_closure) = args
+                .map(|args| literal_arguments(args, source))
+                .unwrap_or_default();
+            let has_closure = args_closure || has_named_child(node, "closure");
+            Some(ScriptStatement {
+                target,
+                arguments,
+                has_closure,
+            })
+        }
+        "assignment_expression" => {
+            let left = node.child_by_field_name("left")?;
+            let right = node.child_by_field_name("right")?;
+            Some(ScriptStatement {
+                target: text(left, source),
+                arguments: is_string(right)
+                    .then(|| declared_name(&text(right, source)))
+                    .into_iter()
+                    .collect(),
+                has_closure: right.kind() == "closure",
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Unquoted string literal arguments, and whether a closure is passed
+fn literal_arguments(args: Node, source: &str) -> (Vec<String>, bool) {
+    let mut cursor = args.walk();
+    let children: Vec<Node> = args.named_children(&mut cursor).collect();
+    let literals = children
+        .iter()
+        .filter(|c| is_string(**c))
+        .map(|c| declared_name(&text(*c, source)))
+        .collect();
+    let has_closure = children.iter().any(|c| c.kind() == "closure");
+    (literals, has_closure)
+}
+
+fn is_string(node: Node) -> bool {
+    matches!(node.kind(), "string_literal" | "character_literal")
+}
+
+fn has_named_child(node: Node, kind: &str) -> bool {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor).any(|c| c.kind() == kind)
+}
+
+fn text(node: Node, source: &str) -> String {
+    node.utf8_text(source.as_bytes())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_level_statements() {
+        let source = r#"
+rootProject.name = 'demo'
+include 'app', "lib"
+"#;
+        let statements = top_level_statements(source).unwrap();
+        assert_eq!(
+            statements,
+            vec![
+                ScriptStatement {
+                    target: "rootProject.name".to_string(),
+                    arguments: vec!["demo".to_string()],
+                    has_closure: false,
+                },
+                ScriptStatement {
+                    target: "include".to_string(),
+                    arguments: vec!["app".to_string(), "lib".to_string()],
+                    has_closure: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_calls_inside_closures_are_not_top_level() {
+        let source = r#"
+jar {
+    include '**/*.class'
+}
+"#;
+        let statements = top_level_statements(source).unwrap();
+        assert!(statements.iter().all(|s| s.target != "include"));
+    }
+}
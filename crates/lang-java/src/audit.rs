@@ -0,0 +1,325 @@
+//! Index accuracy audit against compiled classes
+//!
+//! The compiler's output is the ground truth for what a source file declares.
+//! `naviscope audit-java` reads a sample of a module's class files with the
+//! same classfile reader that builds library stubs, derives the member FQNs
+//! the parser should have produced, and compares them with the indexed nodes.
+//! The share of matched members is tracked as the Java plugin's accuracy.
+
+use crate::resolve::external::converter::JavaTypeConverter;
+use naviscope_api::models::TypeRef;
+use ristretto_classfile::{ClassAccessFlags, ClassFile, FieldAccessFlags, MethodAccessFlags};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+type AuditResult<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Build output directories holding a module's main classes, relative to it
+pub const CLASS_DIRS: &[&str] = &["build/classes/java/main", "target/classes"];
+
+/// Members a class file declares, as graph FQNs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassShape {
+    pub fqn: String,
+    pub members: BTreeSet<String>,
+}
+
+/// Comparison of one class with its indexed node
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ClassAudit {
+    pub fqn: String,
+    /// Whether the class itself is indexed
+    pub indexed: bool,
+    /// Members declared in bytecode
+    pub expected: usize,
+    /// Members indexed under the expected FQN
+    pub matched: usize,
+    /// Members in bytecode that are not indexed under any signature
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+    /// Indexed members the bytecode does not declare
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra: Vec<String>,
+    /// Members indexed with a different signature: (bytecode, index)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mismatched: Vec<(String, String)>,
+}
+
+/// Totals over the audited classes
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AuditReport {
+    /// Class files found in the build output
+    pub classes_total: usize,
+    pub classes_sampled: usize,
+    pub classes_missing: usize,
+    pub members_expected: usize,
+    pub members_matched: usize,
+    pub members_missing: usize,
+    pub members_extra: usize,
+    pub members_mismatched: usize,
+    pub classes: Vec<ClassAudit>,
+}
+
+impl AuditReport {
+    pub fn new(classes_total: usize, classes: Vec<ClassAudit>) -> Self {
+        let mut report = Self {
+            classes_total,
+            classes_sampled: classes.len(),
+            ..Default::default()
+        };
+        for class in &classes {
+            report.classes_missing += usize::from(!class.indexed);
+            report.members_expected += class.expected;
+            report.members_matched += class.matched;
+            report.members_missing += class.missing.len();
+            report.members_extra += class.extra.len();
+            report.members_mismatched += class.mismatched.len();
+        }
+        report.classes = classes;
+        report
+    }
+
+    /// Matched members as a percentage of the expected ones
+    pub fn accuracy(&self) -> f64 {
+        if self.members_expected == 0 {
+            return 100.0;
+        }
+        self.members_matched as f64 * 100.0 / self.members_expected as f64
+    }
+}
+
+/// `.class` files under `dir`, sorted by path
+pub fn class_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "class") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Up to `size` items spread evenly over `items`, so that repeated audits of
+/// the same build look at the same classes
+pub fn sample<T>(items: &[T], size: usize) -> Vec<&T> {
+    if size == 0 || items.is_empty() {
+        return Vec::new();
+    }
+    if items.len() <= size {
+        return items.iter().collect();
+    }
+    (0..size).map(|i| &items[i * items.len() / size]).collect()
+}
+
+/// Source-level FQN of the class file at `relative` (e.g. `com/a/Outer$Inner.class`),
+/// or `None` for classes without one: anonymous and local classes,
+/// `package-info` and `module-info`.
+pub fn class_fqn(relative: &Path) -> Option<String> {
+    let path = relative.with_extension("");
+    let binary = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(".");
+    if binary.ends_with("package-info") || binary.ends_with("module-info") {
+        return None;
+    }
+    let mut segments = binary.split('$');
+    let mut fqn = segments.next()?.to_string();
+    for segment in segments {
+        if segment.is_empty() || segment.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        fqn.push('.');
+        fqn.push_str(segment);
+    }
+    Some(fqn)
+}
+
+/// Members declared by the class file `bytes` of the class `fqn`, leaving out
+/// what the compiler generates: synthetic and bridge members, static
+/// initializers, the implicit members of enums and the outer instance passed
+/// to constructors of inner classes.
+pub fn read_class(fqn: &str, bytes: Vec<u8>) -> AuditResult<Option<ClassShape>> {
+    let class = ClassFile::from_bytes(&mut Cursor::new(bytes))
+        .map_err(|e| format!("Failed to parse class: {e:?}"))?;
+    if class.access_flags.contains(ClassAccessFlags::SYNTHETIC) {
+        return Ok(None);
+    }
+    let pool = &class.constant_pool;
+    let is_enum = class.access_flags.contains(ClassAccessFlags::ENUM);
+    let simple_name = fqn.rsplit('.').next().unwrap_or(fqn);
+    let outer = fqn.rsplit_once('.').map(|(outer, _)| outer);
+    let mut members = BTreeSet::new();
+
+    for field in &class.fields {
+        if field.access_flags.contains(FieldAccessFlags::SYNTHETIC) {
+            continue;
+        }
+        let name = pool
+            .try_get_utf8(field.name_index)
+            .map_err(|e| format!("Failed to parse field name: {e:?}"))?;
+        members.insert(crate::naming::build_member_fqn(fqn, name));
+    }
+
+    for method in &class.methods {
+        if method
+            .access_flags
+            .intersects(MethodAccessFlags::SYNTHETIC | MethodAccessFlags::BRIDGE)
+        {
+            continue;
+        }
+        let name: &str = pool
+            .try_get_utf8(method.name_index)
+            .map_err(|e| format!("Failed to parse method name: {e:?}"))?;
+        let descriptor: &str = pool
+            .try_get_utf8(method.descriptor_index)
+            .map_err(|e| format!("Failed to parse method descriptor: {e:?}"))?;
+        let is_static = method.access_flags.contains(MethodAccessFlags::STATIC);
+        let enum_helper = is_enum && is_static && (name == "values" || name == "valueOf");
+        if name == "<clinit>" || enum_helper {
+            continue;
+        }
+        let is_varargs = method.access_flags.contains(MethodAccessFlags::VARARGS);
+        let (_, parameters) = JavaTypeConverter::convert_method(descriptor, is_varargs)
+            .map_err(|e| format!("Failed to parse method signature: {e:?}"))?;
+        let mut params: Vec<TypeRef> = parameters
+            .iter()
+            .map(|p| source_type(&p.type_ref))
+            .collect();
+
+        let display_name = if name == "<init>" {
+            // Enum constructors take the constant's name and ordinal first;
+            // inner class constructors take the enclosing instance
+            let takes_outer =
+                matches!(params.first(), Some(TypeRef::Id(id)) if Some(id.as_str()) == outer);
+            let implicit = if is_enum { 2 } else { usize::from(takes_outer) };
+            params.drain(..implicit.min(params.len()));
+            simple_name
+        } else {
+            name
+        };
+        let signed = crate::naming::build_java_method_name(display_name, &params);
+        members.insert(crate::naming::build_member_fqn(fqn, &signed));
+    }
+
+    Ok(Some(ClassShape {
+        fqn: fqn.to_string(),
+        members,
+    }))
+}
+
+/// Bytecode names nested types `Outer$Inner`; sources name them `Outer.Inner`
+fn source_type(type_ref: &TypeRef) -> TypeRef {
+    match type_ref {
+        TypeRef::Id(id) => TypeRef::Id(id.replace('$', ".")),
+        TypeRef::Array {
+            element,
+            dimensions,
+        } => TypeRef::Array {
+            element: Box::new(source_type(element)),
+            dimensions: *dimensions,
+        },
+        other => other.clone(),
+    }
+}
+
+/// Compare `shape` with the member FQNs indexed for the class, `None` if the
+/// class is not indexed.
+pub fn compare(shape: &ClassShape, indexed: Option<&BTreeSet<String>>) -> ClassAudit {
+    let mut audit = ClassAudit {
+        fqn: shape.fqn.clone(),
+        indexed: indexed.is_some(),
+        expected: shape.members.len(),
+        ..Default::default()
+    };
+    let empty = BTreeSet::new();
+    let indexed = indexed.unwrap_or(&empty);
+
+    let mut extra: Vec<&String> = indexed.difference(&shape.members).collect();
+    for member in &shape.members {
+        if indexed.contains(member) {
+            audit.matched += 1;
+            continue;
+        }
+        // The same name under another signature is a signature mismatch
+        let name = member_name(member);
+        match extra.iter().position(|e| member_name(e) == name) {
+            Some(i) => {
+                let other = extra.remove(i);
+                audit.mismatched.push((member.clone(), other.clone()));
+            }
+            None => audit.missing.push(member.clone()),
+        }
+    }
+    audit.extra = extra.into_iter().cloned().collect();
+    audit
+}
+
+/// Member name without owner or parameters: `a.A#run(int)` -> `run`
+fn member_name(fqn: &str) -> &str {
+    let member = fqn.rsplit_once('#').map_or(fqn, |(_, m)| m);
+    member.split('(').next().unwrap_or(member)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(items: &[&str]) -> BTreeSet<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn class_fqn_follows_source_names() {
+        assert_eq!(
+            class_fqn(Path::new("com/a/Outer$Inner.class")).as_deref(),
+            Some("com.a.Outer.Inner")
+        );
+        assert_eq!(class_fqn(Path::new("com/a/Outer$1.class")), None);
+        assert_eq!(class_fqn(Path::new("com/a/package-info.class")), None);
+    }
+
+    #[test]
+    fn sample_is_spread_and_stable() {
+        let items: Vec<usize> = (0..10).collect();
+        assert_eq!(sample(&items, 5), [&0, &2, &4, &6, &8]);
+        assert_eq!(sample(&items, 20).len(), 10);
+        assert!(sample(&items, 0).is_empty());
+    }
+
+    #[test]
+    fn compare_separates_missing_extra_and_mismatched() {
+        let shape = ClassShape {
+            fqn: "a.A".to_string(),
+            members: set(&["a.A#A()", "a.A#count", "a.A#run(int)", "a.A#stop()"]),
+        };
+        let indexed = set(&["a.A#A()", "a.A#count", "a.A#run(long)", "a.A#helper()"]);
+
+        let audit = compare(&shape, Some(&indexed));
+        assert_eq!((audit.expected, audit.matched), (4, 2));
+        assert_eq!(
+            audit.mismatched,
+            [("a.A#run(int)".to_string(), "a.A#run(long)".to_string())]
+        );
+        assert_eq!(audit.missing, ["a.A#stop()"]);
+        assert_eq!(audit.extra, ["a.A#helper()"]);
+
+        let report = AuditReport::new(10, vec![audit, compare(&shape, None)]);
+        assert_eq!(report.classes_missing, 1);
+        assert_eq!(report.members_expected, 8);
+        assert_eq!(report.accuracy(), 25.0);
+    }
+}
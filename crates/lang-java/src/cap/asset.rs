@@ -1,5 +1,6 @@
 use crate::JavaPlugin;
 use naviscope_plugin::{AssetCap, AssetDiscoverer, AssetIndexer, AssetSourceLocator};
+use std::path::Path;
 use std::sync::Arc;
 
 impl AssetCap for JavaPlugin {
@@ -7,6 +8,24 @@ impl AssetCap for JavaPlugin {
         Some(Box::new(crate::discoverer::JdkDiscoverer::new()))
     }
 
+    fn toolchain_asset_discoverer(&self, home: &Path) -> Option<Box<dyn AssetDiscoverer>> {
+        crate::discoverer::JdkDiscoverer::at(home)
+            .map(|discoverer| Box::new(discoverer) as Box<dyn AssetDiscoverer>)
+    }
+
+    fn project_toolchain_discoverer(
+        &self,
+        project_root: &Path,
+        version: Option<&str>,
+    ) -> Option<Box<dyn AssetDiscoverer>> {
+        let version = version
+            .map(str::to_string)
+            .or_else(|| crate::discoverer::JdkDiscoverer::declared_version(project_root));
+        Some(Box::new(crate::discoverer::JdkDiscoverer::for_version(
+            version.as_deref(),
+        )))
+    }
+
     fn asset_indexer(&self) -> Option<Arc<dyn AssetIndexer>> {
         Some(Arc::new(crate::resolve::external::JavaExternalResolver))
     }
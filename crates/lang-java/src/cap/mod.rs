@@ -7,4 +7,4 @@ mod presentation;
 mod registration;
 mod runtime;
 
-pub use registration::java_caps;
+pub use registration::{java_caps, java_caps_with};
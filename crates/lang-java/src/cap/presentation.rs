@@ -13,8 +13,12 @@ impl NodePresenter for JavaPlugin {
                 .map(|spur| fqns.resolve_atom(Symbol(spur)).to_string())
         };
 
+        let naming = crate::naming::JavaNamingConvention;
+        let id = naming.render_fqn(node.id, fqns);
         let mut display = DisplayGraphNode {
-            id: crate::naming::JavaNamingConvention::default().render_fqn(node.id, fqns),
+            symbol_id: naming.symbol_id("java", &id),
+            generated: false,
+            id,
             name: fqns.resolve_atom(node.name).to_string(),
             kind: node.kind.clone(),
             lang: "java".to_string(),
@@ -4,8 +4,13 @@ use naviscope_plugin::{LanguageCaps, SemanticCap};
 use std::sync::Arc;
 
 pub fn java_caps() -> std::result::Result<LanguageCaps, Box<dyn std::error::Error + Send + Sync>> {
-    let plugin = Arc::new(JavaPlugin::new()?);
-    Ok(LanguageCaps {
+    Ok(java_caps_with(JavaPlugin::new()?))
+}
+
+/// Capabilities backed by a configured plugin, e.g. `JavaPlugin::with_spring`
+pub fn java_caps_with(plugin: JavaPlugin) -> LanguageCaps {
+    let plugin = Arc::new(plugin);
+    LanguageCaps {
         language: Language::JAVA,
         matcher: plugin.clone(),
         parser: plugin.clone(),
@@ -14,5 +19,5 @@ pub fn java_caps() -> std::result::Result<LanguageCaps, Box<dyn std::error::Erro
         asset: plugin.clone(),
         presentation: plugin.clone(),
         metadata_codec: plugin,
-    })
+    }
 }
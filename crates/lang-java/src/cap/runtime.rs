@@ -28,6 +28,27 @@ impl LspSyntaxService for JavaPlugin {
         crate::lsp::JavaLspService::new(self.parser.clone())
             .find_occurrences(source, tree, target, index)
     }
+
+    fn classify_occurrence(
+        &self,
+        tree: &tree_sitter::Tree,
+        source: &str,
+        range: &naviscope_api::models::symbol::Range,
+    ) -> naviscope_api::models::ReferenceKind {
+        crate::lsp::JavaLspService::new(self.parser.clone())
+            .classify_occurrence(tree, source, range)
+    }
+
+    fn check_local_rename(
+        &self,
+        source: &str,
+        tree: &tree_sitter::Tree,
+        target: &naviscope_api::models::SymbolResolution,
+        new_name: &str,
+    ) -> Option<String> {
+        crate::lsp::JavaLspService::new(self.parser.clone())
+            .check_local_rename(source, tree, target, new_name)
+    }
 }
 
 impl ReferenceCheckService for JavaPlugin {
@@ -1,12 +1,16 @@
 //! JDK asset discoverer.
 //!
-//! Discovers JDK standard library assets from:
+//! Finds the JDKs installed in:
 //! - JAVA_HOME environment variable
 //! - macOS java_home tool
 //! - Common installation paths
-//! - SDKMAN
+//! - SDKMAN, jEnv and Gradle toolchains
+//!
+//! and discovers the standard library of the one matching the Java version
+//! the project targets.
 
 use naviscope_plugin::{AssetDiscoverer, AssetEntry, AssetSource};
+use regex::Regex;
 use std::path::{Path, PathBuf};
 
 /// JDK asset discoverer
@@ -15,13 +19,48 @@ pub struct JdkDiscoverer {
     cached_assets: Vec<AssetEntry>,
 }
 
+/// An installed JDK
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JdkInstall {
+    pub home: PathBuf,
+    pub version: Option<String>,
+}
+
 impl JdkDiscoverer {
     pub fn new() -> Self {
+        Self::for_version(None)
+    }
+
+    /// Use the installed JDK of the Java `version` (e.g. `"17"` or `"1.8"`).
+    ///
+    /// Falls back to the first JDK found, the one JAVA_HOME points at if
+    /// set, when `version` is `None` or no installed JDK matches it.
+    pub fn for_version(version: Option<&str>) -> Self {
+        let installed = Self::installed();
+        let wanted = version.and_then(major_version);
+        wanted
+            .and_then(|wanted| {
+                installed
+                    .iter()
+                    .find(|jdk| jdk.version.as_deref().and_then(major_version) == Some(wanted))
+            })
+            .or(installed.first())
+            .and_then(|jdk| Self::at(&jdk.home))
+            .unwrap_or_else(|| Self {
+                cached_assets: Vec::new(),
+            })
+    }
+
+    /// Use the JDK installed at `home` instead of searching for one.
+    ///
+    /// Returns `None` if `home` does not look like a JDK.
+    pub fn at(home: &Path) -> Option<Self> {
         let mut discoverer = Self {
             cached_assets: Vec::new(),
         };
-        discoverer.discover_jdk();
-        discoverer
+        discoverer.collect_sdk_assets(home)?;
+        discoverer.tag_jdk_source(home.to_path_buf());
+        Some(discoverer)
     }
 
     /// Get the discovered JDK root path (if any)
@@ -43,100 +82,136 @@ impl JdkDiscoverer {
         })
     }
 
-    fn discover_jdk(&mut self) {
-        let mut jdk_root: Option<PathBuf> = None;
+    /// The JDKs installed on this machine, the one JAVA_HOME points at first
+    pub fn installed() -> Vec<JdkInstall> {
+        let mut candidates = Vec::new();
 
         // 1. Check JAVA_HOME
         if let Ok(java_home) = std::env::var("JAVA_HOME") {
-            let path = PathBuf::from(&java_home);
-            if self.collect_sdk_assets(&path).is_some() {
-                jdk_root = Some(path);
-            }
+            candidates.push(PathBuf::from(java_home));
         }
 
         // 2. macOS specific: Use java_home tool
         #[cfg(target_os = "macos")]
-        if self.cached_assets.is_empty() {
-            if let Ok(output) = std::process::Command::new("/usr/libexec/java_home").output() {
-                if output.status.success() {
-                    let path_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                    let path = PathBuf::from(&path_str);
-                    if self.collect_sdk_assets(&path).is_some() {
-                        jdk_root = Some(path);
-                    }
-                }
+        if let Ok(output) = std::process::Command::new("/usr/libexec/java_home").output() {
+            if output.status.success() {
+                let path_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                candidates.push(PathBuf::from(path_str));
             }
         }
 
-        // 3. Search common installation paths
-        if self.cached_assets.is_empty() {
-            let mut search_roots = Vec::new();
+        // 3. Search common installation paths and version managers
+        let mut search_roots = Vec::new();
 
-            #[cfg(target_os = "macos")]
-            {
-                search_roots.push(PathBuf::from("/Library/Java/JavaVirtualMachines/"));
-                search_roots.push(PathBuf::from("/opt/homebrew/opt/openjdk/"));
-                search_roots.push(PathBuf::from("/usr/local/opt/openjdk/"));
-            }
-            #[cfg(target_os = "linux")]
-            {
-                search_roots.push(PathBuf::from("/usr/lib/jvm/"));
-            }
-            #[cfg(target_os = "windows")]
-            {
-                search_roots.push(PathBuf::from("C:\\Program Files\\Java\\"));
-            }
-
-            // SDKMAN
-            if let Some(mut sdkman) = dirs::home_dir() {
-                sdkman.push(".sdkman/candidates/java/");
-                search_roots.push(sdkman);
-            }
+        #[cfg(target_os = "macos")]
+        {
+            search_roots.push(PathBuf::from("/Library/Java/JavaVirtualMachines/"));
+            search_roots.push(PathBuf::from("/opt/homebrew/opt/openjdk/"));
+            search_roots.push(PathBuf::from("/usr/local/opt/openjdk/"));
+        }
+        #[cfg(target_os = "linux")]
+        {
+            search_roots.push(PathBuf::from("/usr/lib/jvm/"));
+        }
+        #[cfg(target_os = "windows")]
+        {
+            search_roots.push(PathBuf::from("C:\\Program Files\\Java\\"));
+        }
 
-            for root in search_roots {
-                if !root.exists() {
-                    continue;
+        if let Some(home) = dirs::home_dir() {
+            search_roots.push(home.join(".sdkman/candidates/java/"));
+            search_roots.push(home.join(".jenv/versions/"));
+        }
+        // JDKs provisioned for Gradle toolchains
+        match std::env::var_os("GRADLE_USER_HOME") {
+            Some(gradle_home) => search_roots.push(PathBuf::from(gradle_home).join("jdks")),
+            None => {
+                if let Some(home) = dirs::home_dir() {
+                    search_roots.push(home.join(".gradle/jdks/"));
                 }
+            }
+        }
 
-                // If root itself is a JDK (e.g. Homebrew symlink)
-                if self.collect_sdk_assets(&root).is_some() {
-                    jdk_root = Some(root.clone());
-                    break;
-                }
+        for root in search_roots {
+            // The root itself may be a JDK (e.g. Homebrew symlink)
+            candidates.push(root.clone());
+            // Gradle nests the JDK in a directory named after its vendor
+            for entry in sorted_dir(&root) {
+                candidates.push(entry.clone());
+                candidates.extend(sorted_dir(&entry));
+            }
+        }
 
-                // If root is a parent directory containing multiple SDKs
-                if let Ok(entries) = std::fs::read_dir(&root) {
-                    for entry in entries.flatten() {
-                        let mut sdk_path = entry.path();
-                        if cfg!(target_os = "macos") && sdk_path.join("Contents/Home").exists() {
-                            sdk_path = sdk_path.join("Contents/Home");
-                        }
-                        if self.collect_sdk_assets(&sdk_path).is_some() {
-                            jdk_root = Some(sdk_path);
-                            break;
-                        }
-                    }
+        let mut seen = std::collections::HashSet::new();
+        candidates
+            .into_iter()
+            .map(|path| {
+                if cfg!(target_os = "macos") && path.join("Contents/Home").exists() {
+                    path.join("Contents/Home")
+                } else {
+                    path
                 }
+            })
+            .filter(|path| sdk_assets(path).is_some())
+            // Version managers link to the same installations
+            .filter(|path| seen.insert(path.canonicalize().unwrap_or_else(|_| path.clone())))
+            .map(|home| JdkInstall {
+                version: Self::detect_jdk_version(&home),
+                home,
+            })
+            .collect()
+    }
 
-                if !self.cached_assets.is_empty() {
-                    break;
+    /// The Java version the build of the project at `project_root` targets:
+    /// its toolchain, or else its source compatibility or release
+    pub fn declared_version(project_root: &Path) -> Option<String> {
+        let toolchain = Regex::new(r"(?:JavaLanguageVersion\.of|jvmToolchain)\(\s*(\d+)").unwrap();
+        let compatibility = Regex::new(
+            r#"\b(?:sourceCompatibility|release)\s*(?:=|\.set\()\s*(?:JavaVersion\.(?:VERSION_)?)?["']?(1[._]\d+|\d+)"#,
+        )
+        .unwrap();
+        let maven = Regex::new(
+            r"<(?:maven\.compiler\.release|maven\.compiler\.source|release|java\.version)>\s*(1\.\d+|\d+)",
+        )
+        .unwrap();
+
+        let mut build_files = Vec::new();
+        for dir in std::iter::once(project_root.to_path_buf()).chain(sorted_dir(project_root)) {
+            for name in ["build.gradle", "build.gradle.kts", "pom.xml"] {
+                if let Ok(content) = std::fs::read_to_string(dir.join(name)) {
+                    build_files.push(content);
                 }
             }
         }
+        let first = |re: &Regex| {
+            build_files
+                .iter()
+                .find_map(|content| re.captures(content))
+                .and_then(|cap| major_version(&cap[1]))
+        };
+        first(&toolchain)
+            .or_else(|| first(&compatibility))
+            .or_else(|| first(&maven))
+            .map(|major| major.to_string())
+    }
 
-        // Update all entries with JDK source info
-        if let Some(root) = jdk_root {
-            let version = self.detect_jdk_version(&root);
-            for entry in &mut self.cached_assets {
-                entry.source = AssetSource::Jdk {
-                    version: version.clone(),
-                    path: root.clone(),
-                };
-            }
+    fn collect_sdk_assets(&mut self, sdk_path: &Path) -> Option<()> {
+        self.cached_assets.extend(sdk_assets(sdk_path)?);
+        Some(())
+    }
+
+    /// Update all entries with JDK source info
+    fn tag_jdk_source(&mut self, root: PathBuf) {
+        let version = Self::detect_jdk_version(&root);
+        for entry in &mut self.cached_assets {
+            entry.source = AssetSource::Jdk {
+                version: version.clone(),
+                path: root.clone(),
+            };
         }
     }
 
-    fn detect_jdk_version(&self, jdk_root: &Path) -> Option<String> {
+    fn detect_jdk_version(jdk_root: &Path) -> Option<String> {
         // Try to read release file
         let release_file = jdk_root.join("release");
         if let Ok(content) = std::fs::read_to_string(&release_file) {
@@ -153,7 +228,7 @@ impl JdkDiscoverer {
 
         // Fallback: try to extract from path
         let path_str = jdk_root.to_string_lossy();
-        if let Some(cap) = regex::Regex::new(r"jdk-?(\d+(?:\.\d+)*)")
+        if let Some(cap) = Regex::new(r"jdk-?(\d+(?:\.\d+)*)")
             .ok()
             .and_then(|re| re.captures(&path_str))
         {
@@ -162,50 +237,70 @@ impl JdkDiscoverer {
 
         None
     }
+}
 
-    fn collect_sdk_assets(&mut self, sdk_path: &Path) -> Option<()> {
-        if !sdk_path.exists() {
-            return None;
-        }
+/// Standard library assets of the JDK at `sdk_path`, if it is one
+fn sdk_assets(sdk_path: &Path) -> Option<Vec<AssetEntry>> {
+    if !sdk_path.exists() {
+        return None;
+    }
 
-        // Priority 1: Java 9+ Runtime Image (The most correct way for modern Java)
-        let modules = sdk_path.join("lib/modules");
-        if modules.exists() {
-            self.cached_assets.push(AssetEntry::unknown(modules));
-            return Some(());
-        }
+    // Priority 1: Java 9+ Runtime Image (The most correct way for modern Java)
+    let modules = sdk_path.join("lib/modules");
+    if modules.exists() {
+        return Some(vec![AssetEntry::unknown(modules)]);
+    }
 
-        // Priority 2: Java 8 Legacy Runtime
-        let rt_jar = sdk_path.join("jre/lib/rt.jar");
-        if rt_jar.exists() {
-            self.cached_assets.push(AssetEntry::unknown(rt_jar));
-            return Some(());
-        }
-        let lib_rt_jar = sdk_path.join("lib/rt.jar");
-        if lib_rt_jar.exists() {
-            self.cached_assets.push(AssetEntry::unknown(lib_rt_jar));
-            return Some(());
-        }
+    // Priority 2: Java 8 Legacy Runtime
+    let rt_jar = sdk_path.join("jre/lib/rt.jar");
+    if rt_jar.exists() {
+        return Some(vec![AssetEntry::unknown(rt_jar)]);
+    }
+    let lib_rt_jar = sdk_path.join("lib/rt.jar");
+    if lib_rt_jar.exists() {
+        return Some(vec![AssetEntry::unknown(lib_rt_jar)]);
+    }
 
-        // Priority 3: jmods (Fallback for some JDK builds without lib/modules)
-        let jmods = sdk_path.join("jmods");
-        if jmods.exists() {
-            if let Ok(entries) = std::fs::read_dir(&jmods) {
-                let mut found = false;
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path.extension().and_then(|e| e.to_str()) == Some("jmod") {
-                        self.cached_assets.push(AssetEntry::unknown(path));
-                        found = true;
-                    }
-                }
-                if found {
-                    return Some(());
-                }
+    // Priority 3: jmods (Fallback for some JDK builds without lib/modules)
+    let jmods = sdk_path.join("jmods");
+    if jmods.exists()
+        && let Ok(entries) = std::fs::read_dir(&jmods)
+    {
+        let mut assets = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("jmod") {
+                assets.push(AssetEntry::unknown(path));
             }
         }
+        if !assets.is_empty() {
+            return Some(assets);
+        }
+    }
 
-        None
+    None
+}
+
+/// Entries of the directory `dir`, sorted
+fn sorted_dir(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    entries.sort();
+    entries
+}
+
+/// Major version of the Java version `version`, e.g. 8 for `1.8.0_292` or
+/// `1_8`, 17 for `17.0.1`
+fn major_version(version: &str) -> Option<u32> {
+    let mut parts = version.split(['.', '_', '+', '-']);
+    match parts.next()?.parse().ok()? {
+        1 => parts.next()?.parse().ok(),
+        major => Some(major),
     }
 }
 
@@ -281,4 +376,46 @@ mod tests {
         assert_eq!(discoverer.cached_assets.len(), 1);
         assert_eq!(discoverer.cached_assets[0].path, rt_jar_path);
     }
+
+    #[test]
+    fn test_declared_version_prefers_the_toolchain() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        assert_eq!(JdkDiscoverer::declared_version(root), None);
+
+        std::fs::write(
+            root.join("build.gradle"),
+            "java {\n    sourceCompatibility = JavaVersion.VERSION_1_8\n}\n",
+        )
+        .unwrap();
+        assert_eq!(JdkDiscoverer::declared_version(root).as_deref(), Some("8"));
+
+        std::fs::create_dir(root.join("app")).unwrap();
+        std::fs::write(
+            root.join("app/build.gradle.kts"),
+            "java {\n    toolchain {\n        languageVersion.set(JavaLanguageVersion.of(21))\n    }\n}\n",
+        )
+        .unwrap();
+        assert_eq!(JdkDiscoverer::declared_version(root).as_deref(), Some("21"));
+
+        let maven = tempfile::tempdir().unwrap();
+        std::fs::write(
+            maven.path().join("pom.xml"),
+            "<properties><maven.compiler.release>17</maven.compiler.release></properties>",
+        )
+        .unwrap();
+        assert_eq!(
+            JdkDiscoverer::declared_version(maven.path()).as_deref(),
+            Some("17")
+        );
+    }
+
+    #[test]
+    fn test_major_version() {
+        assert_eq!(major_version("1.8.0_292"), Some(8));
+        assert_eq!(major_version("1_8"), Some(8));
+        assert_eq!(major_version("17.0.1"), Some(17));
+        assert_eq!(major_version("21"), Some(21));
+        assert_eq!(major_version("jdk"), None);
+    }
 }
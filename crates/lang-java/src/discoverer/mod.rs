@@ -2,4 +2,4 @@
 
 mod jdk;
 
-pub use jdk::JdkDiscoverer;
+pub use jdk::{JdkDiscoverer, JdkInstall};
@@ -106,6 +106,18 @@ impl ScopeManager {
         None
     }
 
+    /// Whether `scope_id` is `ancestor_id` or nested inside it.
+    pub fn is_within(&self, scope_id: ScopeId, ancestor_id: ScopeId) -> bool {
+        let mut current_scope_id = Some(scope_id);
+        while let Some(id) = current_scope_id {
+            if id == ancestor_id {
+                return true;
+            }
+            current_scope_id = self.scopes.get(&id).and_then(|s| s.parent_id);
+        }
+        false
+    }
+
     /// Find the enclosing class FQN for a given scope.
     pub fn find_enclosing_class(&self, start_scope_id: usize) -> Option<String> {
         let mut current_scope_id = Some(start_scope_id);
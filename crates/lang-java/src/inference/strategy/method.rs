@@ -24,11 +24,36 @@ impl MethodCallInfer {
         Some(member.fqn)
     }
 
+    /// All overloads the call could target, plus the one chosen for its arguments.
+    pub fn overloads(
+        &self,
+        node: &Node,
+        ctx: &InferContext,
+    ) -> Option<(
+        Vec<crate::inference::MemberInfo>,
+        Option<crate::inference::MemberInfo>,
+    )> {
+        let (candidates, arg_types) = self.lookup_candidates(node, ctx)?;
+        let best = ctx.ts.resolve_method(&candidates, &arg_types);
+        Some((candidates, best))
+    }
+
     fn resolve_member(
         &self,
         node: &Node,
         ctx: &InferContext,
     ) -> Option<crate::inference::MemberInfo> {
+        let (candidates, arg_types) = self.lookup_candidates(node, ctx)?;
+
+        // Resolve the best match among candidates
+        ctx.ts.resolve_method(&candidates, &arg_types)
+    }
+
+    fn lookup_candidates(
+        &self,
+        node: &Node,
+        ctx: &InferContext,
+    ) -> Option<(Vec<crate::inference::MemberInfo>, Vec<TypeRef>)> {
         let call_node = if node.kind() == "method_invocation" {
             *node
         } else if let Some(parent) = node.parent() {
@@ -90,9 +115,7 @@ impl MethodCallInfer {
         }
 
         let candidates = self.apply_receiver_substitution(candidates, &receiver_type, ctx);
-
-        // Resolve the best match among candidates
-        ctx.ts.resolve_method(&candidates, &arg_types)
+        Some((candidates, arg_types))
     }
 
     fn apply_receiver_substitution(
@@ -1,3 +1,4 @@
+pub mod audit;
 pub mod cap;
 pub mod discoverer;
 pub mod inference;
@@ -9,7 +10,7 @@ pub mod parser;
 pub mod queries;
 pub mod resolve;
 
-pub use cap::java_caps;
+pub use cap::{java_caps, java_caps_with};
 pub use discoverer::JdkDiscoverer;
 
 use std::sync::Arc;
@@ -34,4 +35,16 @@ impl JavaPlugin {
             type_system,
         })
     }
+
+    /// Also model Spring beans, injection points and request mappings.
+    pub fn with_spring(mut self, enabled: bool) -> Self {
+        self.parser = Arc::new((*self.parser).clone().with_spring(enabled));
+        self
+    }
+
+    /// Also model the tables and columns of JPA entities.
+    pub fn with_jpa(mut self, enabled: bool) -> Self {
+        self.parser = Arc::new((*self.parser).clone().with_jpa(enabled));
+        self
+    }
 }
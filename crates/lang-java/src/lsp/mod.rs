@@ -1,10 +1,11 @@
 mod references;
+mod rename;
 mod symbols;
 pub mod type_system;
 
 use crate::parser::JavaParser;
-use naviscope_api::models::SymbolResolution;
 use naviscope_api::models::graph::DisplayGraphNode;
+use naviscope_api::models::{ReferenceKind, SymbolResolution};
 use naviscope_plugin::CodeGraph;
 use naviscope_plugin::LspSyntaxService;
 use std::sync::Arc;
@@ -38,4 +39,23 @@ impl LspSyntaxService for JavaLspService {
     ) -> Vec<naviscope_api::models::symbol::Range> {
         references::find_occurrences(&self.parser, source, tree, target, index)
     }
+
+    fn classify_occurrence(
+        &self,
+        tree: &Tree,
+        _source: &str,
+        range: &naviscope_api::models::symbol::Range,
+    ) -> ReferenceKind {
+        references::classify_occurrence(tree, range)
+    }
+
+    fn check_local_rename(
+        &self,
+        source: &str,
+        tree: &Tree,
+        target: &SymbolResolution,
+        new_name: &str,
+    ) -> Option<String> {
+        rename::check_local_rename(&self.parser, source, tree, target, new_name)
+    }
 }
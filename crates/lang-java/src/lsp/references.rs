@@ -4,7 +4,7 @@ use crate::inference::create_inference_context;
 use crate::inference::scope::ScopeManager;
 use crate::parser::JavaParser;
 use naviscope_api::models::symbol::Range;
-use naviscope_api::models::{SymbolIntent, SymbolResolution, TypeRef};
+use naviscope_api::models::{ReferenceKind, SymbolIntent, SymbolResolution, TypeRef};
 use naviscope_plugin::CodeGraph;
 use naviscope_plugin::utils::{line_col_at_to_offset, range_from_ts};
 use tree_sitter::{Node, Tree};
@@ -84,7 +84,9 @@ fn collect_occurrences_with_ctx(
         }
         SymbolResolution::Precise(fqn, _) | SymbolResolution::Global(fqn) => {
             let member_target = fqn.contains('#');
-            let type_target = matches!(target, SymbolResolution::Precise(_, SymbolIntent::Type));
+            // Global FQNs without a member part name types as well
+            let type_target = matches!(target, SymbolResolution::Precise(_, SymbolIntent::Type))
+                || (matches!(target, SymbolResolution::Global(_)) && !member_target);
             let name = if member_target {
                 // For signed member FQNs like `Owner#target(java.lang.String)`,
                 // never split on '.'; extract member part first.
@@ -134,7 +136,50 @@ fn collect_occurrences_with_ctx(
     }
 }
 
-fn extract_name_from_range(source: &str, range: &Range) -> Option<String> {
+/// Tell type-only occurrences (declared types, bounds, type arguments, casts)
+/// apart from instantiations, class literals and member access.
+pub fn classify_occurrence(tree: &Tree, range: &Range) -> ReferenceKind {
+    let point = tree_sitter::Point::new(range.start_line, range.start_col);
+    let Some(node) = tree
+        .root_node()
+        .named_descendant_for_point_range(point, point)
+    else {
+        return ReferenceKind::Usage;
+    };
+    if node.kind() != "type_identifier" {
+        return ReferenceKind::Usage;
+    }
+
+    let mut current = node;
+    let mut in_type_arguments = false;
+    while let Some(parent) = current.parent() {
+        match parent.kind() {
+            "type_arguments" => {
+                in_type_arguments = true;
+                current = parent;
+            }
+            "scoped_type_identifier"
+            | "generic_type"
+            | "array_type"
+            | "annotated_type"
+            | "wildcard" => current = parent,
+            "object_creation_expression"
+            | "array_creation_expression"
+            | "class_literal"
+            | "method_reference" => {
+                return if in_type_arguments {
+                    ReferenceKind::TypeUsage
+                } else {
+                    ReferenceKind::Usage
+                };
+            }
+            _ => return ReferenceKind::TypeUsage,
+        }
+    }
+    ReferenceKind::TypeUsage
+}
+
+pub(super) fn extract_name_from_range(source: &str, range: &Range) -> Option<String> {
     let start = line_col_at_to_offset(source, range.start_line, range.start_col)?;
     let end = line_col_at_to_offset(source, range.end_line, range.end_col)?;
     if start < end && end <= source.len() {
@@ -181,7 +226,7 @@ fn visit_tree_recursive<F>(
     }
 }
 
-fn find_start_scope_id(node: &Node, sm: &ScopeManager) -> Option<usize> {
+pub(super) fn find_start_scope_id(node: &Node, sm: &ScopeManager) -> Option<usize> {
     let mut current = *node;
     while let Some(parent) = current.parent() {
         if let Some(sid) = sm.get_scope_id(parent.id()) {
@@ -0,0 +1,172 @@
+//! Validation of local variable and parameter renames
+//!
+//! Locals are not graph nodes, so a rename only touches their occurrences in
+//! one file. It is refused when the new name would clash with a declaration
+//! or capture a reference that currently binds to something else.
+
+use super::references::{extract_name_from_range, find_start_scope_id};
+use crate::inference::adapters::NoOpTypeSystem;
+use crate::inference::create_inference_context;
+use crate::inference::scope::ScopeManager;
+use crate::parser::JavaParser;
+use naviscope_api::models::SymbolResolution;
+use tree_sitter::{Node, Point, Tree};
+
+const KEYWORDS: &[&str] = &[
+    "abstract",
+    "assert",
+    "boolean",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extends",
+    "false",
+    "final",
+    "finally",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "implements",
+    "import",
+    "instanceof",
+    "int",
+    "interface",
+    "long",
+    "native",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "short",
+    "static",
+    "strictfp",
+    "super",
+    "switch",
+    "synchronized",
+    "this",
+    "throw",
+    "throws",
+    "transient",
+    "true",
+    "try",
+    "void",
+    "volatile",
+    "while",
+    "_",
+];
+
+pub fn check_local_rename(
+    parser: &JavaParser,
+    source: &str,
+    tree: &Tree,
+    target: &SymbolResolution,
+    new_name: &str,
+) -> Option<String> {
+    let SymbolResolution::Local(decl_range, _) = target else {
+        return None;
+    };
+    if KEYWORDS.contains(&new_name) {
+        return Some(format!("'{}' is a reserved keyword", new_name));
+    }
+    if extract_name_from_range(source, decl_range).as_deref() == Some(new_name) {
+        return None;
+    }
+
+    let (package, imports) = parser.extract_package_and_imports(tree, source);
+    let ts = NoOpTypeSystem;
+    let mut scope_manager = ScopeManager::new();
+    let ctx = create_inference_context(
+        &tree.root_node(),
+        source,
+        &ts,
+        &mut scope_manager,
+        package,
+        imports,
+    );
+    let sm = ctx.scope_manager?;
+
+    let decl_start = Point::new(decl_range.start_line, decl_range.start_col);
+    let decl_node = tree
+        .root_node()
+        .named_descendant_for_point_range(decl_start, decl_start)?;
+    let decl_scope = find_start_scope_id(&decl_node, sm)?;
+
+    // A local or parameter of that name is already visible at the declaration
+    if let Some(info) = sm.lookup_symbol(decl_scope, new_name) {
+        return Some(format!(
+            "'{}' is already defined at line {}",
+            new_name,
+            info.range.start_line + 1
+        ));
+    }
+
+    // Any use or declaration of the new name within reach of the renamed one
+    // would either clash with it or start binding to it.
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+
+        if node.kind() != "identifier"
+            || node.start_position() < decl_start
+            || node.utf8_text(source.as_bytes()).ok() != Some(new_name)
+            || is_member_name(&node)
+        {
+            continue;
+        }
+        let Some(scope) = find_start_scope_id(&node, sm) else {
+            continue;
+        };
+        if !sm.is_within(scope, decl_scope) {
+            continue;
+        }
+        let line = node.start_position().row + 1;
+        return Some(match sm.lookup_symbol(scope, new_name) {
+            Some(info) => format!(
+                "'{}' is already defined at line {}",
+                new_name,
+                info.range.start_line + 1
+            ),
+            None => format!(
+                "renaming would capture the reference to '{}' at line {}",
+                new_name, line
+            ),
+        });
+    }
+    None
+}
+
+/// Identifiers naming members, types or labels, which locals cannot shadow
+fn is_member_name(node: &Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    match parent.kind() {
+        "method_invocation"
+        | "method_declaration"
+        | "constructor_declaration"
+        | "class_declaration"
+        | "interface_declaration"
+        | "enum_declaration"
+        | "record_declaration"
+        | "annotation_type_declaration" => parent.child_by_field_name("name") == Some(*node),
+        "field_access" => parent.child_by_field_name("field") == Some(*node),
+        "method_reference" => parent.named_child(0) != Some(*node),
+        "labeled_statement" | "break_statement" | "continue_statement" => true,
+        _ => false,
+    }
+}
@@ -42,6 +42,16 @@ pub fn extract_symbols(parser: &JavaParser, tree: &Tree, source: &str) -> Vec<Di
                 }
                 crate::model::JavaIndexMetadata::Field { .. } => NodeKind::Field,
                 crate::model::JavaIndexMetadata::Package => NodeKind::Package,
+                crate::model::JavaIndexMetadata::Module => NodeKind::Module,
+                crate::model::JavaIndexMetadata::Route { .. } => {
+                    NodeKind::Custom(crate::parser::KIND_LABEL_ROUTE.to_string())
+                }
+                crate::model::JavaIndexMetadata::Table => {
+                    NodeKind::Custom(crate::parser::KIND_LABEL_TABLE.to_string())
+                }
+                crate::model::JavaIndexMetadata::Column => {
+                    NodeKind::Custom(crate::parser::KIND_LABEL_COLUMN.to_string())
+                }
             };
 
             RawSymbol {
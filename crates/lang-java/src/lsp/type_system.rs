@@ -21,6 +21,12 @@ impl ReferenceCheckService for JavaTypeSystem {
         if candidate == target {
             return true;
         }
+        // A global target names the symbol whatever it is used as
+        if let SymbolResolution::Global(fqn) = target
+            && candidate.fqn() == Some(fqn.as_str())
+        {
+            return true;
+        }
 
         // Handle method overrides/implementations
         let c_fqn = candidate.fqn();
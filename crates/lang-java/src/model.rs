@@ -33,6 +33,18 @@ pub enum JavaIndexMetadata {
         type_ref: TypeRef,
     },
     Package,
+    /// A Java module declared by `module-info.java`
+    Module,
+    /// An HTTP route served by a Spring handler method
+    Route {
+        /// Request method (`GET`), or `ANY` when the mapping accepts all
+        method: String,
+        path: String,
+    },
+    /// A database table a JPA entity is stored in
+    Table,
+    /// A column of a table a persistent field is stored in
+    Column,
 }
 
 impl IndexMetadata for JavaIndexMetadata {
@@ -78,6 +90,17 @@ pub enum JavaNodeMetadata {
         type_ref: TypeRef,
     },
     Package,
+    /// A Java module declared by `module-info.java`
+    Module,
+    /// An HTTP route served by a Spring handler method
+    Route {
+        method_sid: u32,
+        path_sid: u32,
+    },
+    /// A database table a JPA entity is stored in
+    Table,
+    /// A column of a table a persistent field is stored in
+    Column,
 }
 
 impl JavaIndexMetadata {
@@ -155,6 +178,13 @@ impl JavaIndexMetadata {
                 type_ref: type_ref.clone(),
             },
             JavaIndexMetadata::Package => JavaNodeMetadata::Package,
+            JavaIndexMetadata::Module => JavaNodeMetadata::Module,
+            JavaIndexMetadata::Route { method, path } => JavaNodeMetadata::Route {
+                method_sid: ctx.intern_str(method),
+                path_sid: ctx.intern_str(path),
+            },
+            JavaIndexMetadata::Table => JavaNodeMetadata::Table,
+            JavaIndexMetadata::Column => JavaNodeMetadata::Column,
         }
     }
 }
@@ -7,7 +7,7 @@ use naviscope_api::models::graph::EdgeType;
 use naviscope_api::models::symbol::Range;
 use naviscope_plugin::utils::range_from_ts;
 use std::collections::HashMap;
-use tree_sitter::QueryCapture;
+use tree_sitter::{Node, QueryCapture};
 
 impl JavaParser {
     pub(crate) fn identify_entities<'a>(
@@ -145,14 +145,34 @@ impl JavaParser {
         relations: &mut Vec<JavaRelation>,
     ) -> JavaIndexMetadata {
         match kind {
-            KIND_LABEL_CLASS => JavaIndexMetadata::Class {
-                modifiers: vec![],
-                type_parameters: self.extract_type_parameters(captures, source),
-            },
-            KIND_LABEL_INTERFACE => JavaIndexMetadata::Interface {
-                modifiers: vec![],
-                type_parameters: self.extract_type_parameters(captures, source),
-            },
+            KIND_LABEL_CLASS => {
+                if let Some(type_params_node) = self.type_parameters_node(captures) {
+                    self.generate_nested_typed_as_edges(
+                        type_params_node,
+                        source,
+                        fqn_id,
+                        relations,
+                    );
+                }
+                JavaIndexMetadata::Class {
+                    modifiers: vec![],
+                    type_parameters: self.extract_type_parameters(captures, source),
+                }
+            }
+            KIND_LABEL_INTERFACE => {
+                if let Some(type_params_node) = self.type_parameters_node(captures) {
+                    self.generate_nested_typed_as_edges(
+                        type_params_node,
+                        source,
+                        fqn_id,
+                        relations,
+                    );
+                }
+                JavaIndexMetadata::Interface {
+                    modifiers: vec![],
+                    type_parameters: self.extract_type_parameters(captures, source),
+                }
+            }
             KIND_LABEL_ENUM => JavaIndexMetadata::Enum {
                 modifiers: vec![],
                 constants: vec![],
@@ -179,6 +199,7 @@ impl JavaParser {
                     return_type = self.parse_type_node(ret_node, source);
                     self.generate_typed_as_edges(ret_node, source, fqn_id, relations);
                 }
+                self.generate_nested_typed_as_edges(anchor_node, source, fqn_id, relations);
                 JavaIndexMetadata::Method {
                     return_type,
                     parameters: self.extract_method_parameters(anchor_node, source),
@@ -219,33 +240,32 @@ impl JavaParser {
         }
     }
 
-    fn extract_type_parameters<'a>(
-        &self,
-        captures: &[QueryCapture<'a>],
-        source: &'a str,
-    ) -> Vec<String> {
+    /// The `type_parameters` node of the class or interface declaration in `captures`.
+    fn type_parameters_node<'a>(&self, captures: &[QueryCapture<'a>]) -> Option<Node<'a>> {
         let declaration_node = captures.iter().find_map(|c| {
             if c.index == self.indices.class_def || c.index == self.indices.inter_def {
                 Some(c.node)
             } else {
                 None
             }
-        });
-
-        let Some(declaration_node) = declaration_node else {
-            return Vec::new();
-        };
+        })?;
 
-        let type_params_node = declaration_node
+        declaration_node
             .child_by_field_name("type_parameters")
             .or_else(|| {
                 let mut cursor = declaration_node.walk();
                 declaration_node
                     .children(&mut cursor)
                     .find(|n| n.kind() == "type_parameters")
-            });
+            })
+    }
 
-        let Some(type_params_node) = type_params_node else {
+    fn extract_type_parameters<'a>(
+        &self,
+        captures: &[QueryCapture<'a>],
+        source: &'a str,
+    ) -> Vec<String> {
+        let Some(type_params_node) = self.type_parameters_node(captures) else {
             return Vec::new();
         };
 
@@ -0,0 +1,322 @@
+//! JPA mappings on top of the Java model
+//!
+//! An `@Entity` class is stored in a table, and each of its persistent
+//! fields in a column of that table. This stage records the tables and
+//! columns as nodes, with a `MapsTo` relation from the class and the fields,
+//! so the code touching a table is found from the table. Names follow the
+//! Spring Boot naming strategy: an explicit `@Table`/`@Column` name as is,
+//! otherwise the snake case of the Java name. SQL migrations declare the
+//! same tables and columns; both name them in lower case.
+
+use super::super::JavaParser;
+use super::super::constants::{KIND_LABEL_COLUMN, KIND_LABEL_TABLE};
+use super::spring::{annotations, argument_values, has_modifier, owner, text};
+use super::{JavaEntity, JavaRelation};
+use crate::model::JavaIndexMetadata;
+use naviscope_api::models::graph::{EdgeType, NodeKind};
+use naviscope_api::models::symbol::NodeId;
+use std::collections::HashMap;
+use tree_sitter::Node;
+
+/// Field annotations whose field is not stored in a column of the table
+const UNMAPPED: &[&str] = &[
+    "Transient",
+    "OneToMany",
+    "ManyToMany",
+    "ElementCollection",
+    "Embedded",
+    "EmbeddedId",
+];
+/// Associations stored as a foreign key column of the owning table
+const TO_ONE: &[&str] = &["ManyToOne", "OneToOne"];
+
+impl JavaParser {
+    pub(super) fn enrich_jpa<'a>(
+        &self,
+        source: &'a str,
+        entities: &mut Vec<JavaEntity<'a>>,
+        relations: &mut Vec<JavaRelation>,
+    ) {
+        // Table of each entity class
+        let mut tables: HashMap<NodeId, String> = HashMap::new();
+        let mut mapped: Vec<JavaEntity<'a>> = Vec::new();
+        for entity in entities.iter() {
+            if entity.node.kind() != "class_declaration" {
+                continue;
+            }
+            let annotations = annotations(entity.node, source);
+            let Some((_, marker)) = annotations.iter().find(|(name, _)| name == "Entity") else {
+                continue;
+            };
+            let table = annotations.iter().find(|(name, _)| name == "Table");
+            let name = table
+                .and_then(|(_, t)| literal_argument(*t, "name", source))
+                .or_else(|| literal_argument(*marker, "name", source).map(|n| snake_case(&n)))
+                .unwrap_or_else(|| snake_case(&entity.name));
+            relations.push(JavaRelation {
+                source_id: entity.fqn.clone(),
+                target_id: table_id(&name),
+                rel_type: EdgeType::MapsTo,
+                range: None,
+            });
+            mapped.push(JavaEntity {
+                element: JavaIndexMetadata::Table,
+                node: table.map(|(_, t)| *t).unwrap_or(*marker),
+                fqn: table_id(&name),
+                name: name.clone(),
+            });
+            tables.insert(entity.fqn.clone(), name);
+        }
+        if tables.is_empty() {
+            return;
+        }
+
+        for entity in entities.iter() {
+            if entity.node.kind() != "field_declaration"
+                || has_modifier(entity.node, "static")
+                || has_modifier(entity.node, "transient")
+            {
+                continue;
+            }
+            let Some(table) = owner(&entity.fqn).and_then(|owner| tables.get(&owner)) else {
+                continue;
+            };
+            let annotations = annotations(entity.node, source);
+            let has = |names: &[&str]| {
+                annotations
+                    .iter()
+                    .any(|(name, _)| names.contains(&name.as_str()))
+            };
+            if has(UNMAPPED) {
+                continue;
+            }
+            let column = annotations
+                .iter()
+                .find(|(name, _)| name == "Column" || name == "JoinColumn");
+            let name = column
+                .and_then(|(_, c)| literal_argument(*c, "name", source))
+                .unwrap_or_else(|| {
+                    let name = snake_case(&entity.name);
+                    if has(TO_ONE) {
+                        format!("{}_id", name)
+                    } else {
+                        name
+                    }
+                });
+            // Located at the annotation or the name of the field, not the
+            // whole declaration, which stays the innermost node of its body
+            let Some(node) = column
+                .map(|(_, c)| *c)
+                .or_else(|| declarator_name(entity.node, &entity.name, source))
+            else {
+                continue;
+            };
+            let id = column_id(table, &name);
+            relations.push(JavaRelation {
+                source_id: entity.fqn.clone(),
+                target_id: id.clone(),
+                rel_type: EdgeType::MapsTo,
+                range: None,
+            });
+            relations.push(JavaRelation {
+                source_id: table_id(table),
+                target_id: id.clone(),
+                rel_type: EdgeType::Contains,
+                range: None,
+            });
+            mapped.push(JavaEntity {
+                element: JavaIndexMetadata::Column,
+                node,
+                fqn: id,
+                name,
+            });
+        }
+
+        for node in mapped {
+            if !entities.iter().any(|e| e.fqn == node.fqn) {
+                entities.push(node);
+            }
+        }
+    }
+}
+
+fn table_id(table: &str) -> NodeId {
+    NodeId::Structured(vec![(
+        NodeKind::Custom(KIND_LABEL_TABLE.to_string()),
+        table.to_string(),
+    )])
+}
+
+fn column_id(table: &str, column: &str) -> NodeId {
+    NodeId::Structured(vec![
+        (
+            NodeKind::Custom(KIND_LABEL_TABLE.to_string()),
+            table.to_string(),
+        ),
+        (
+            NodeKind::Custom(KIND_LABEL_COLUMN.to_string()),
+            column.to_string(),
+        ),
+    ])
+}
+
+/// The string given to the `key` argument of an annotation, in lower case
+/// and without the quotes of a quoted identifier
+fn literal_argument(annotation: Node, key: &str, source: &str) -> Option<String> {
+    let value = argument_values(annotation, &[key], source)
+        .into_iter()
+        .find(|v| v.kind() == "string_literal")?;
+    let literal = text(value, source);
+    let name = literal
+        .strip_prefix('"')
+        .and_then(|l| l.strip_suffix('"'))
+        .unwrap_or(&literal)
+        .replace("\\\"", "")
+        .replace('`', "")
+        .to_lowercase();
+    // `public.orders` is the table `orders` of the schema `public`
+    let name = name.rsplit('.').next().unwrap_or(&name).to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+/// The name of the declarator `name` of a field declaration
+fn declarator_name<'a>(field: Node<'a>, name: &str, source: &str) -> Option<Node<'a>> {
+    let mut cursor = field.walk();
+    field
+        .children_by_field_name("declarator", &mut cursor)
+        .filter_map(|d| d.child_by_field_name("name"))
+        .find(|n| text(*n, source) == name)
+}
+
+/// `order_line` for `OrderLine`, `created_at` for `createdAt`
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    let chars: Vec<char> = name.chars().collect();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let after_lower = i > 0 && (chars[i - 1].is_lowercase() || chars[i - 1].is_numeric());
+            let before_lower = i > 0 && chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if after_lower || (before_lower && chars[i - 1].is_uppercase()) {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use naviscope_plugin::GlobalParseResult;
+
+    fn parse(source: &str) -> GlobalParseResult {
+        JavaParser::new()
+            .unwrap()
+            .with_jpa(true)
+            .parse_file(source, None)
+            .unwrap()
+    }
+
+    fn relations(result: &GlobalParseResult, edge_type: EdgeType) -> Vec<(String, String)> {
+        let mut relations: Vec<(String, String)> = result
+            .output
+            .relations
+            .iter()
+            .filter(|r| r.edge_type == edge_type)
+            .map(|r| (r.source_id.to_string(), r.target_id.to_string()))
+            .collect();
+        relations.sort();
+        relations
+    }
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(snake_case("OrderLine"), "order_line");
+        assert_eq!(snake_case("createdAt"), "created_at");
+        assert_eq!(snake_case("HTTPRequest"), "http_request");
+        assert_eq!(snake_case("address2Line"), "address2_line");
+        assert_eq!(snake_case("id"), "id");
+    }
+
+    #[test]
+    fn test_entities_map_to_tables_and_columns() {
+        let source = r#"
+package shop;
+
+@Entity
+@Table(name = "orders")
+public class Order {
+    @Id
+    private Long id;
+    @Column(name = "\"TOTAL\"")
+    private BigDecimal total;
+    private Instant createdAt = Instant.now();
+    @ManyToOne
+    private Customer customer;
+    @OneToMany(mappedBy = "order")
+    private List<OrderLine> lines;
+    @Transient
+    private String label;
+    private static final long serialVersionUID = 1L;
+}
+
+@Entity
+public class OrderLine {
+    @ManyToOne
+    @JoinColumn(name = "order_ref")
+    private Order order;
+}
+
+public class Plain {
+    private String name;
+}
+"#;
+        let result = parse(source);
+        let mut tables: Vec<&str> = result
+            .output
+            .nodes
+            .iter()
+            .filter(|n| n.kind == NodeKind::Custom(KIND_LABEL_TABLE.to_string()))
+            .map(|n| n.name.as_str())
+            .collect();
+        tables.sort();
+        assert_eq!(tables, ["order_line", "orders"]);
+
+        let maps_to = relations(&result, EdgeType::MapsTo);
+        assert_eq!(
+            maps_to,
+            [
+                ("shop.Order".to_string(), "orders".to_string()),
+                (
+                    "shop.Order#createdAt".to_string(),
+                    "orders.created_at".to_string()
+                ),
+                (
+                    "shop.Order#customer".to_string(),
+                    "orders.customer_id".to_string()
+                ),
+                ("shop.Order#id".to_string(), "orders.id".to_string()),
+                ("shop.Order#total".to_string(), "orders.total".to_string()),
+                ("shop.OrderLine".to_string(), "order_line".to_string()),
+                (
+                    "shop.OrderLine#order".to_string(),
+                    "order_line.order_ref".to_string()
+                ),
+            ]
+        );
+        assert!(
+            relations(&result, EdgeType::Contains)
+                .contains(&("orders".to_string(), "orders.total".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let result = JavaParser::new()
+            .unwrap()
+            .parse_file("@Entity class A { long id; }", None)
+            .unwrap();
+        assert!(relations(&result, EdgeType::MapsTo).is_empty());
+    }
+}
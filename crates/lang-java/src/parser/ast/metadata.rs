@@ -281,7 +281,11 @@ impl JavaParser {
                     modifiers.push(m_str);
                 }
             }
-            JavaIndexMetadata::Package => {}
+            JavaIndexMetadata::Package
+            | JavaIndexMetadata::Module
+            | JavaIndexMetadata::Route { .. }
+            | JavaIndexMetadata::Table
+            | JavaIndexMetadata::Column => {}
         }
     }
 }
@@ -5,8 +5,10 @@ use std::collections::HashMap;
 use tree_sitter::{Node, QueryCapture, StreamingIterator, Tree};
 
 mod entities;
+mod jpa;
 mod metadata;
 mod relations;
+mod spring;
 
 /// The native semantic model of a Java source file.
 pub struct JavaFileModel<'a> {
@@ -61,7 +63,17 @@ impl JavaParser {
             &entities_map,
         );
 
-        // Stage 3: Collect Reference Index (Identifiers)
+        // Stage 3: Model framework conventions (Spring beans, injections, routes)
+        if self.spring {
+            self.enrich_spring(source, &mut entities, &mut relations);
+        }
+
+        // Stage 4: Map persistent entities to tables and columns (JPA)
+        if self.jpa {
+            self.enrich_jpa(source, &mut entities, &mut relations);
+        }
+
+        // Stage 5: Collect Reference Index (Identifiers)
         let identifiers = self.collect_identifiers(tree, source);
 
         JavaFileModel {
@@ -18,7 +18,7 @@ impl JavaParser {
                 .utf8_text(source.as_bytes())
                 .unwrap_or_default()
                 .to_string();
-            if !self.is_primitive(&type_name) {
+            if !self.is_primitive(&type_name) && type_name != "var" {
                 relations.push(JavaRelation {
                     source_id: source_id.clone(),
                     target_id: naviscope_api::models::symbol::NodeId::Flat(type_name),
@@ -40,6 +40,43 @@ impl JavaParser {
         }
     }
 
+    /// Emit `TypedAs` edges for the type positions nested in `node`: parameters,
+    /// `throws` clauses, type bounds, local variables, casts, `instanceof`,
+    /// instantiations and catch clauses. Nested class bodies belong to their
+    /// own declarations and are skipped.
+    pub(super) fn generate_nested_typed_as_edges<'a>(
+        &self,
+        node: Node<'a>,
+        source: &'a str,
+        source_id: &naviscope_api::models::symbol::NodeId,
+        relations: &mut Vec<JavaRelation>,
+    ) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "class_body" | "interface_body" | "enum_body" => continue,
+                "throws" | "type_bound" | "catch_type" => {
+                    let mut inner = child.walk();
+                    for type_node in child.named_children(&mut inner) {
+                        self.generate_typed_as_edges(type_node, source, source_id, relations);
+                    }
+                    continue;
+                }
+                "instanceof_expression" => {
+                    if let Some(type_node) = child.child_by_field_name("right") {
+                        self.generate_typed_as_edges(type_node, source, source_id, relations);
+                    }
+                }
+                _ => {
+                    if let Some(type_node) = child.child_by_field_name("type") {
+                        self.generate_typed_as_edges(type_node, source, source_id, relations);
+                    }
+                }
+            }
+            self.generate_nested_typed_as_edges(child, source, source_id, relations);
+        }
+    }
+
     fn is_primitive(&self, type_name: &str) -> bool {
         matches!(
             type_name,
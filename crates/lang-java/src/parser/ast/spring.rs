@@ -0,0 +1,536 @@
+//! Spring conventions on top of the Java model
+//!
+//! Spring wires an application by annotation: stereotype classes are beans
+//! that stand in for their supertypes, `@Autowired` members and the
+//! constructors of beans have other beans injected, and controller methods
+//! serve the routes their mappings name. This stage records them as
+//! `BeanOf` and `Injects` relations, and as route nodes with a `HandledBy`
+//! relation to their handler. The injections of a member name the declared
+//! type; the engine links the classes to the beans of that type once the
+//! whole project is indexed.
+
+use super::super::JavaParser;
+use super::super::constants::KIND_LABEL_ROUTE;
+use super::{JavaEntity, JavaRelation};
+use crate::model::JavaIndexMetadata;
+use naviscope_api::models::graph::{EdgeType, NodeKind};
+use naviscope_api::models::symbol::NodeId;
+use naviscope_plugin::utils::range_from_ts;
+use std::collections::{HashMap, HashSet};
+use tree_sitter::Node;
+
+/// Annotations that make a class a bean when components are scanned
+const STEREOTYPES: &[&str] = &[
+    "Component",
+    "Service",
+    "Repository",
+    "Controller",
+    "RestController",
+    "Configuration",
+];
+/// Stereotypes of the classes whose methods handle requests
+const CONTROLLERS: &[&str] = &["Controller", "RestController"];
+/// Annotations that have a field, constructor or setter injected
+const INJECTION_MARKERS: &[&str] = &["Autowired", "Inject", "Resource"];
+/// Types wrapping the injected bean, or all beans of a type
+const INJECTION_WRAPPERS: &[&str] = &[
+    "Optional",
+    "ObjectProvider",
+    "Provider",
+    "Lazy",
+    "List",
+    "Set",
+    "Collection",
+];
+/// Request mapping annotations, with the request method they imply
+const MAPPINGS: &[(&str, Option<&str>)] = &[
+    ("RequestMapping", None),
+    ("GetMapping", Some("GET")),
+    ("PostMapping", Some("POST")),
+    ("PutMapping", Some("PUT")),
+    ("DeleteMapping", Some("DELETE")),
+    ("PatchMapping", Some("PATCH")),
+];
+
+/// What the annotations of a class mean for its members
+#[derive(Default)]
+struct SpringClass {
+    bean: bool,
+    controller: bool,
+    /// Paths of the class-level `@RequestMapping`, `None` when one of them is
+    /// not a literal
+    prefixes: Option<Vec<String>>,
+    /// Lombok generates the constructor: `Some(true)` for the `final` fields
+    /// (`@RequiredArgsConstructor`), `Some(false)` for all of them
+    lombok_constructor: Option<bool>,
+    constructors: usize,
+}
+
+impl JavaParser {
+    pub(super) fn enrich_spring<'a>(
+        &self,
+        source: &'a str,
+        entities: &mut Vec<JavaEntity<'a>>,
+        relations: &mut Vec<JavaRelation>,
+    ) {
+        let mut classes: HashMap<NodeId, SpringClass> = HashMap::new();
+        for entity in entities.iter() {
+            match entity.node.kind() {
+                "class_declaration" => {
+                    let annotations = annotations(entity.node, source);
+                    let has = |names: &[&str]| {
+                        annotations
+                            .iter()
+                            .any(|(name, _)| names.contains(&name.as_str()))
+                    };
+                    let class = classes.entry(entity.fqn.clone()).or_default();
+                    class.bean = has(STEREOTYPES);
+                    class.controller = has(CONTROLLERS);
+                    class.prefixes = match annotations
+                        .iter()
+                        .find(|(name, _)| name == "RequestMapping")
+                    {
+                        Some((_, mapping)) => mapping_paths(*mapping, source),
+                        None => Some(vec![String::new()]),
+                    };
+                    class.lombok_constructor = annotations.iter().find_map(|(name, _)| match name
+                        .as_str()
+                    {
+                        "RequiredArgsConstructor" => Some(true),
+                        "AllArgsConstructor" => Some(false),
+                        _ => None,
+                    });
+                }
+                "constructor_declaration" => {
+                    if let Some(owner) = owner(&entity.fqn) {
+                        classes.entry(owner).or_default().constructors += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+        if classes.is_empty() {
+            return;
+        }
+
+        // Component scanning registers a bean under each of its supertypes
+        let bean_of: Vec<JavaRelation> = relations
+            .iter()
+            .filter(|r| matches!(r.rel_type, EdgeType::Implements | EdgeType::InheritsFrom))
+            .filter(|r| classes.get(&r.source_id).is_some_and(|c| c.bean))
+            .map(|r| JavaRelation {
+                source_id: r.source_id.clone(),
+                target_id: r.target_id.clone(),
+                rel_type: EdgeType::BeanOf,
+                range: r.range,
+            })
+            .collect();
+        relations.extend(bean_of);
+
+        let mut routes: Vec<JavaEntity<'a>> = Vec::new();
+        for entity in entities.iter() {
+            let Some(class) = owner(&entity.fqn).and_then(|owner| classes.get(&owner)) else {
+                continue;
+            };
+            let annotations = annotations(entity.node, source);
+            let marked = annotations
+                .iter()
+                .any(|(name, _)| INJECTION_MARKERS.contains(&name.as_str()));
+            let injected: Vec<Node> = match entity.node.kind() {
+                "field_declaration" => {
+                    let generated = class.bean
+                        && !has_modifier(entity.node, "static")
+                        && class.lombok_constructor.is_some_and(|only_final| {
+                            !only_final || has_modifier(entity.node, "final")
+                        });
+                    let configured = annotations.iter().any(|(name, _)| name == "Value");
+                    if (marked || generated) && !configured {
+                        entity
+                            .node
+                            .child_by_field_name("type")
+                            .into_iter()
+                            .collect()
+                    } else {
+                        Vec::new()
+                    }
+                }
+                // A bean's only constructor needs no `@Autowired`
+                "constructor_declaration" if marked || (class.bean && class.constructors == 1) => {
+                    parameter_types(entity.node, source)
+                }
+                "method_declaration" if marked => parameter_types(entity.node, source),
+                _ => Vec::new(),
+            };
+            let mut seen = HashSet::new();
+            for type_node in injected {
+                let Some(bean_type) = injected_type(type_node, source) else {
+                    continue;
+                };
+                let name = text(bean_type, source);
+                if seen.insert(name.clone()) {
+                    relations.push(JavaRelation {
+                        source_id: entity.fqn.clone(),
+                        target_id: NodeId::Flat(name),
+                        rel_type: EdgeType::Injects,
+                        range: Some(range_from_ts(bean_type.range())),
+                    });
+                }
+            }
+
+            if class.controller && entity.node.kind() == "method_declaration" {
+                let prefixes = class.prefixes.as_deref().unwrap_or_default();
+                for (name, mapping) in &annotations {
+                    let Some((_, implied)) = MAPPINGS.iter().find(|(m, _)| *m == name.as_str())
+                    else {
+                        continue;
+                    };
+                    let methods = match implied {
+                        Some(method) => vec![method.to_string()],
+                        None => request_methods(*mapping, source),
+                    };
+                    let paths = mapping_paths(*mapping, source).unwrap_or_default();
+                    for prefix in prefixes {
+                        for path in &paths {
+                            for method in &methods {
+                                let path = join_paths(prefix, path);
+                                let name = format!("{} {}", method, path);
+                                relations.push(JavaRelation {
+                                    source_id: route_id(&name),
+                                    target_id: entity.fqn.clone(),
+                                    rel_type: EdgeType::HandledBy,
+                                    range: None,
+                                });
+                                routes.push(JavaEntity {
+                                    element: JavaIndexMetadata::Route {
+                                        method: method.clone(),
+                                        path,
+                                    },
+                                    node: *mapping,
+                                    fqn: route_id(&name),
+                                    name,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        for route in routes {
+            if !entities.iter().any(|e| e.fqn == route.fqn) {
+                entities.push(route);
+            }
+        }
+    }
+}
+
+fn route_id(name: &str) -> NodeId {
+    NodeId::Structured(vec![(
+        NodeKind::Custom(KIND_LABEL_ROUTE.to_string()),
+        name.to_string(),
+    )])
+}
+
+/// The class declaring a member
+pub(super) fn owner(fqn: &NodeId) -> Option<NodeId> {
+    match fqn {
+        NodeId::Structured(parts) if parts.len() > 1 => {
+            Some(NodeId::Structured(parts[..parts.len() - 1].to_vec()))
+        }
+        _ => None,
+    }
+}
+
+/// Annotations of a declaration by simple name
+pub(super) fn annotations<'a>(decl: Node<'a>, source: &str) -> Vec<(String, Node<'a>)> {
+    let Some(modifiers) = modifiers(decl) else {
+        return Vec::new();
+    };
+    let mut cursor = modifiers.walk();
+    modifiers
+        .named_children(&mut cursor)
+        .filter(|c| matches!(c.kind(), "annotation" | "marker_annotation"))
+        .filter_map(|annotation| {
+            let name = text(annotation.child_by_field_name("name")?, source);
+            let simple = name.rsplit('.').next().unwrap_or(&name).to_string();
+            Some((simple, annotation))
+        })
+        .collect()
+}
+
+fn modifiers(decl: Node) -> Option<Node> {
+    let mut cursor = decl.walk();
+    decl.children(&mut cursor).find(|c| c.kind() == "modifiers")
+}
+
+pub(super) fn has_modifier(decl: Node, keyword: &str) -> bool {
+    modifiers(decl).is_some_and(|modifiers| {
+        let mut cursor = modifiers.walk();
+        modifiers.children(&mut cursor).any(|c| c.kind() == keyword)
+    })
+}
+
+/// Types of the parameters of a constructor or method, except `@Value` ones
+fn parameter_types<'a>(decl: Node<'a>, source: &str) -> Vec<Node<'a>> {
+    let Some(parameters) = decl.child_by_field_name("parameters") else {
+        return Vec::new();
+    };
+    let mut cursor = parameters.walk();
+    parameters
+        .named_children(&mut cursor)
+        .filter(|p| p.kind() == "formal_parameter")
+        .filter(|p| {
+            !annotations(*p, source)
+                .iter()
+                .any(|(name, _)| name == "Value")
+        })
+        .filter_map(|p| p.child_by_field_name("type"))
+        .collect()
+}
+
+/// The type naming the injected bean: `Foo` for `Foo`, `Optional<Foo>` and
+/// `List<Foo>`
+fn injected_type<'a>(type_node: Node<'a>, source: &str) -> Option<Node<'a>> {
+    match type_node.kind() {
+        "type_identifier" | "scoped_type_identifier" => Some(type_node),
+        "generic_type" => {
+            let mut cursor = type_node.walk();
+            let children: Vec<Node> = type_node.named_children(&mut cursor).collect();
+            let base = *children.first()?;
+            let base_name = text(base, source);
+            let simple = base_name.rsplit('.').next().unwrap_or(&base_name);
+            if !INJECTION_WRAPPERS.contains(&simple) {
+                return Some(base);
+            }
+            let arguments = children.iter().find(|c| c.kind() == "type_arguments")?;
+            let mut cursor = arguments.walk();
+            let argument = arguments.named_children(&mut cursor).next()?;
+            injected_type(argument, source)
+        }
+        _ => None,
+    }
+}
+
+/// Values given to the `keys` arguments of an annotation, the unnamed
+/// argument being `value`. Array values are flattened.
+pub(super) fn argument_values<'a>(
+    annotation: Node<'a>,
+    keys: &[&str],
+    source: &str,
+) -> Vec<Node<'a>> {
+    let Some(arguments) = annotation.child_by_field_name("arguments") else {
+        return Vec::new();
+    };
+    let mut values = Vec::new();
+    let mut cursor = arguments.walk();
+    for argument in arguments.named_children(&mut cursor) {
+        let value = if argument.kind() == "element_value_pair" {
+            let key = argument.child_by_field_name("key").map(|k| text(k, source));
+            if !key.is_some_and(|k| keys.contains(&k.as_str())) {
+                continue;
+            }
+            argument.child_by_field_name("value")
+        } else if keys.contains(&"value") && !argument.is_extra() {
+            Some(argument)
+        } else {
+            None
+        };
+        match value {
+            Some(array) if array.kind() == "element_value_array_initializer" => {
+                let mut inner = array.walk();
+                values.extend(array.named_children(&mut inner).filter(|v| !v.is_extra()));
+            }
+            Some(value) => values.push(value),
+            None => {}
+        }
+    }
+    values
+}
+
+/// Paths a mapping names, `None` when one of them is not a literal
+fn mapping_paths(mapping: Node, source: &str) -> Option<Vec<String>> {
+    let values = argument_values(mapping, &["value", "path"], source);
+    if values.is_empty() {
+        return Some(vec![String::new()]);
+    }
+    values
+        .into_iter()
+        .map(|value| {
+            (value.kind() == "string_literal")
+                .then(|| text(value, source).trim_matches('"').to_string())
+        })
+        .collect()
+}
+
+/// Request methods of a `@RequestMapping`: `GET` for `RequestMethod.GET`
+fn request_methods(mapping: Node, source: &str) -> Vec<String> {
+    let methods: Vec<String> = argument_values(mapping, &["method"], source)
+        .into_iter()
+        .map(|value| {
+            let value = text(value, source);
+            value.rsplit('.').next().unwrap_or(&value).to_string()
+        })
+        .collect();
+    if methods.is_empty() {
+        vec!["ANY".to_string()]
+    } else {
+        methods
+    }
+}
+
+/// `/api/users/{id}` for `/api/` and `users/{id}`
+fn join_paths(prefix: &str, path: &str) -> String {
+    let segments: Vec<&str> = prefix
+        .split('/')
+        .chain(path.split('/'))
+        .filter(|s| !s.is_empty())
+        .collect();
+    format!("/{}", segments.join("/"))
+}
+
+pub(super) fn text(node: Node, source: &str) -> String {
+    node.utf8_text(source.as_bytes())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use naviscope_plugin::{GlobalParseResult, IndexRelation};
+
+    fn parse(source: &str) -> GlobalParseResult {
+        JavaParser::new()
+            .unwrap()
+            .with_spring(true)
+            .parse_file(source, None)
+            .unwrap()
+    }
+
+    fn relations(result: &GlobalParseResult, edge_type: EdgeType) -> Vec<(String, String)> {
+        result
+            .output
+            .relations
+            .iter()
+            .filter(|r: &&IndexRelation| r.edge_type == edge_type)
+            .map(|r| (r.source_id.to_string(), r.target_id.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_join_paths() {
+        assert_eq!(join_paths("/api/", "users/{id}"), "/api/users/{id}");
+        assert_eq!(join_paths("", "/users"), "/users");
+        assert_eq!(join_paths("/api", ""), "/api");
+        assert_eq!(join_paths("", ""), "/");
+    }
+
+    #[test]
+    fn test_beans_and_injections() {
+        let source = r#"
+package shop;
+
+@Service
+@RequiredArgsConstructor
+public class OrderService implements Orders {
+    private final OrderRepository repository;
+    private static final int LIMIT = 10;
+    @Autowired
+    private Optional<Clock> clock;
+    @Value("${shop.name}")
+    private final String name;
+}
+"#;
+        let result = parse(source);
+        assert_eq!(
+            relations(&result, EdgeType::BeanOf),
+            [("shop.OrderService".to_string(), "Orders".to_string())]
+        );
+        let mut injects = relations(&result, EdgeType::Injects);
+        injects.sort();
+        assert_eq!(
+            injects,
+            [
+                ("shop.OrderService#clock".to_string(), "Clock".to_string()),
+                (
+                    "shop.OrderService#repository".to_string(),
+                    "OrderRepository".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_constructor_injection() {
+        let source = r#"
+package shop;
+
+@Component
+public class Checkout {
+    public Checkout(Payments payments, @Value("${retries}") int retries) {}
+}
+
+public class Plain {
+    public Plain(Payments payments) {}
+}
+"#;
+        let result = parse(source);
+        let injects = relations(&result, EdgeType::Injects);
+        assert_eq!(injects.len(), 1);
+        assert!(injects[0].0.starts_with("shop.Checkout"));
+        assert_eq!(injects[0].1, "Payments");
+    }
+
+    #[test]
+    fn test_routes() {
+        let source = r#"
+package shop;
+
+@RestController
+@RequestMapping("/api/orders")
+public class OrderController {
+    @GetMapping("/{id}")
+    public Order get(long id) { return null; }
+
+    @RequestMapping(path = {"", "/all"}, method = RequestMethod.GET)
+    public List<Order> list() { return null; }
+
+    public void helper() {}
+}
+"#;
+        let result = parse(source);
+        let mut routes: Vec<&str> = result
+            .output
+            .nodes
+            .iter()
+            .filter(|n| n.kind == NodeKind::Custom(KIND_LABEL_ROUTE.to_string()))
+            .map(|n| n.name.as_str())
+            .collect();
+        routes.sort();
+        assert_eq!(
+            routes,
+            [
+                "GET /api/orders",
+                "GET /api/orders/all",
+                "GET /api/orders/{id}"
+            ]
+        );
+        let handled_by = relations(&result, EdgeType::HandledBy);
+        assert_eq!(handled_by.len(), 3);
+        assert!(
+            handled_by
+                .iter()
+                .any(|(route, handler)| route == "GET /api/orders/{id}"
+                    && handler.starts_with("shop.OrderController#get"))
+        );
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let result = JavaParser::new()
+            .unwrap()
+            .parse_file("@Service class A implements B {}", None)
+            .unwrap();
+        assert!(relations(&result, EdgeType::BeanOf).is_empty());
+    }
+}
@@ -6,3 +6,8 @@ pub const KIND_LABEL_ANNOTATION: &str = "annotation";
 pub const KIND_LABEL_METHOD: &str = "method";
 pub const KIND_LABEL_CONSTRUCTOR: &str = "constructor";
 pub const KIND_LABEL_FIELD: &str = "field";
+/// HTTP routes of Spring handlers, see `JavaIndexMetadata::Route`
+pub const KIND_LABEL_ROUTE: &str = "route";
+/// Database tables and columns of JPA entities, see `JavaIndexMetadata::Table`
+pub const KIND_LABEL_TABLE: &str = "table";
+pub const KIND_LABEL_COLUMN: &str = "column";
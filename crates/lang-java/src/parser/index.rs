@@ -2,8 +2,9 @@ use super::JavaParser;
 use naviscope_api::models::graph::{DisplaySymbolLocation, NodeKind, ResolutionStatus};
 use naviscope_plugin::utils::range_from_ts;
 use naviscope_plugin::{GlobalParseResult, IndexNode, IndexRelation, ParseOutput};
+use std::collections::HashSet;
 use std::sync::Arc;
-use tree_sitter::Parser;
+use tree_sitter::{Node, Parser};
 
 type GenericResult<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
@@ -23,7 +24,27 @@ impl JavaParser {
             .ok_or_else(|| "Failed to parse Java file")?;
 
         // Use the native AST analyzer
-        let model = self.analyze(&tree, source_code);
+        let mut model = self.analyze(&tree, source_code);
+
+        // A file being edited often has syntax errors. Keep the declarations
+        // tree-sitter recovered intact and flag them, rather than dropping the
+        // file's symbols until it parses again.
+        let partial = tree.root_node().has_error();
+        let status = if partial {
+            let dropped: HashSet<_> = model
+                .entities
+                .iter()
+                .filter(|e| !is_well_formed(&e.node))
+                .map(|e| e.fqn.clone())
+                .collect();
+            model.entities.retain(|e| !dropped.contains(&e.fqn));
+            model
+                .relations
+                .retain(|r| !dropped.contains(&r.source_id) && !dropped.contains(&r.target_id));
+            ResolutionStatus::PartialParse
+        } else {
+            ResolutionStatus::Resolved
+        };
 
         let nodes: Vec<IndexNode> = model
             .entities
@@ -43,6 +64,16 @@ impl JavaParser {
                     }
                     crate::model::JavaIndexMetadata::Field { .. } => NodeKind::Field,
                     crate::model::JavaIndexMetadata::Package => NodeKind::Package,
+                    crate::model::JavaIndexMetadata::Module => NodeKind::Module,
+                    crate::model::JavaIndexMetadata::Route { .. } => {
+                        NodeKind::Custom(super::constants::KIND_LABEL_ROUTE.to_string())
+                    }
+                    crate::model::JavaIndexMetadata::Table => {
+                        NodeKind::Custom(super::constants::KIND_LABEL_TABLE.to_string())
+                    }
+                    crate::model::JavaIndexMetadata::Column => {
+                        NodeKind::Custom(super::constants::KIND_LABEL_COLUMN.to_string())
+                    }
                 };
 
                 let location = file_path.map(|p| DisplaySymbolLocation {
@@ -60,7 +91,7 @@ impl JavaParser {
                     kind,
                     lang: "java".to_string(),
                     source: naviscope_api::models::graph::NodeSource::Project,
-                    status: ResolutionStatus::Resolved,
+                    status,
                     location,
                     metadata: Arc::new(e.element),
                 }
@@ -91,3 +122,53 @@ impl JavaParser {
         })
     }
 }
+
+/// A declaration recovered from a tree with errors can be trusted if it has
+/// an intact name and is not part of a region tree-sitter failed to parse.
+fn is_well_formed(node: &Node) -> bool {
+    if node.is_error() || node.is_missing() {
+        return false;
+    }
+    if let Some(name) = node.child_by_field_name("name")
+        && (name.is_missing() || name.has_error())
+    {
+        return false;
+    }
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if parent.is_error() {
+            return false;
+        }
+        current = parent.parent();
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn syntax_errors_keep_well_formed_declarations() {
+        let parser = JavaParser::new().unwrap();
+        let broken = "package p;\npublic class A {\n  int count;\n  void broken( {\n}\n";
+        let result = parser.parse_file(broken, None).unwrap();
+        let class = result
+            .output
+            .nodes
+            .iter()
+            .find(|n| n.name == "A")
+            .expect("class survives the syntax error");
+        assert_eq!(class.status, ResolutionStatus::PartialParse);
+        assert!(
+            result
+                .output
+                .nodes
+                .iter()
+                .all(|n| n.status == ResolutionStatus::PartialParse)
+        );
+
+        let clean = parser.parse_file("class B {}", None).unwrap();
+        assert_eq!(clean.output.nodes[0].status, ResolutionStatus::Resolved);
+    }
+}
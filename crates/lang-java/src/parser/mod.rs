@@ -5,10 +5,13 @@ use tree_sitter::{Query, StreamingIterator, Tree};
 mod ast;
 mod constants;
 mod index;
+mod module_info;
 mod naming;
 mod scope;
 mod types;
 
+pub(crate) use constants::{KIND_LABEL_COLUMN, KIND_LABEL_ROUTE, KIND_LABEL_TABLE};
+
 use crate::queries::java_definitions::JavaIndices;
 use crate::queries::java_occurrences::OccurrenceIndices;
 
@@ -18,6 +21,10 @@ pub struct JavaParser {
     pub(crate) indices: JavaIndices,
     pub(crate) occurrence_query: Arc<Query>,
     pub(crate) occurrence_indices: OccurrenceIndices,
+    /// Model Spring beans, injection points and routes, see `enrich_spring`
+    pub(crate) spring: bool,
+    /// Map JPA entities to tables and columns, see `enrich_jpa`
+    pub(crate) jpa: bool,
 }
 
 impl Clone for JavaParser {
@@ -28,6 +35,8 @@ impl Clone for JavaParser {
             indices: self.indices.clone(),
             occurrence_query: Arc::clone(&self.occurrence_query),
             occurrence_indices: self.occurrence_indices.clone(),
+            spring: self.spring,
+            jpa: self.jpa,
         }
     }
 }
@@ -54,9 +63,23 @@ impl JavaParser {
             indices,
             occurrence_query: Arc::new(occurrence_query),
             occurrence_indices,
+            spring: false,
+            jpa: false,
         })
     }
 
+    /// Also model what Spring makes of the annotations of a file.
+    pub fn with_spring(mut self, enabled: bool) -> Self {
+        self.spring = enabled;
+        self
+    }
+
+    /// Also model the tables and columns JPA entities are stored in.
+    pub fn with_jpa(mut self, enabled: bool) -> Self {
+        self.jpa = enabled;
+        self
+    }
+
     pub fn parse(&self, source: &str, old_tree: Option<&Tree>) -> Option<Tree> {
         let mut parser = tree_sitter::Parser::new();
         parser.set_language(&self.language).ok()?;
@@ -0,0 +1,67 @@
+use super::JavaParser;
+use naviscope_api::models::symbol::Range;
+use naviscope_plugin::utils::range_from_ts;
+use naviscope_plugin::{ModuleDescriptor, ModuleExport, ModuleRequire};
+use std::path::Path;
+use tree_sitter::{Node, Tree};
+
+impl JavaParser {
+    /// Module declared by a `module-info.java` tree, with the range of its
+    /// declaration. Files below `root` belong to the module.
+    pub fn extract_module_descriptor(
+        &self,
+        tree: &Tree,
+        source: &str,
+        root: &Path,
+    ) -> Option<(ModuleDescriptor, Range)> {
+        let root_node = tree.root_node();
+        let mut cursor = root_node.walk();
+        let declaration = root_node
+            .named_children(&mut cursor)
+            .find(|n| n.kind() == "module_declaration")?;
+        let text = |node: Node| node.utf8_text(source.as_bytes()).ok().map(str::to_string);
+
+        let name = text(declaration.child_by_field_name("name")?)?;
+        let mut requires = Vec::new();
+        let mut exports = Vec::new();
+        if let Some(body) = declaration.child_by_field_name("body") {
+            let mut body_cursor = body.walk();
+            for directive in body.named_children(&mut body_cursor) {
+                match directive.kind() {
+                    "requires_module_directive" => {
+                        let Some(module) = directive.child_by_field_name("module").and_then(text)
+                        else {
+                            continue;
+                        };
+                        let mut modifiers = directive.walk();
+                        let transitive = directive
+                            .children_by_field_name("modifiers", &mut modifiers)
+                            .any(|m| text(m).as_deref() == Some("transitive"));
+                        requires.push(ModuleRequire { module, transitive });
+                    }
+                    "exports_module_directive" => {
+                        let Some(package) = directive.child_by_field_name("package").and_then(text)
+                        else {
+                            continue;
+                        };
+                        let mut modules = directive.walk();
+                        let to = directive
+                            .children_by_field_name("modules", &mut modules)
+                            .filter_map(text)
+                            .collect();
+                        exports.push(ModuleExport { package, to });
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let descriptor = ModuleDescriptor {
+            name,
+            root: root.to_path_buf(),
+            requires,
+            exports,
+        };
+        Some((descriptor, range_from_ts(declaration.range())))
+    }
+}
@@ -1,6 +1,6 @@
 use naviscope_plugin::{
     AssetEntry, AssetIndexer, AssetSource, AssetSourceLocator, GlobalParseResult, IndexNode,
-    StubGenerator,
+    StubGenerator, SyntheticSource,
 };
 use ristretto_classfile::{ClassAccessFlags, ClassFile, MethodAccessFlags};
 use ristretto_jimage::Image;
@@ -11,7 +11,8 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use zip::ZipArchive;
 
-mod converter;
+pub(crate) mod converter;
+mod render;
 use converter::{JavaModifierConverter, JavaTypeConverter};
 
 pub struct JavaExternalResolver;
@@ -474,12 +475,38 @@ impl JavaExternalResolver {
         Err(format!("Member {} not found in class {}", member_name, current_fqn).into())
     }
 
+    /// Render the class declaring `fqn` from its bytecode in `asset`.
+    fn render_class_for_fqn(
+        &self,
+        fqn: &str,
+        asset: &Path,
+    ) -> std::result::Result<
+        (String, render::RenderedClass),
+        Box<dyn std::error::Error + Send + Sync>,
+    > {
+        let class_fqn = crate::naming::extract_type_fqn(fqn);
+        let bytes = self.load_class_bytes_for_fqn(class_fqn, asset)?;
+        let class = ClassFile::from_bytes(&mut Cursor::new(bytes))
+            .map_err(|e| format!("Failed to parse class: {e:?}"))?;
+        let origin = asset
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| asset.display().to_string());
+        let rendered = render::render_class(&class, class_fqn, &origin)?;
+        Ok((class_fqn.to_string(), rendered))
+    }
+
+    /// Build a parse result for the class declaring `fqn` when no source is available.
+    ///
+    /// The class file in `asset` is rendered into a synthetic Java document
+    /// (signatures and structure only), which is then parsed like regular source.
     pub fn resolve_source(
         &self,
-        _fqn: &str,
-        _source_asset: &Path,
+        fqn: &str,
+        asset: &Path,
     ) -> std::result::Result<GlobalParseResult, Box<dyn std::error::Error + Send + Sync>> {
-        Err("Source resolution not yet implemented".into())
+        let (_, rendered) = self.render_class_for_fqn(fqn, asset)?;
+        crate::parser::JavaParser::new()?.parse_file(&rendered.content, None)
     }
 }
 
@@ -523,6 +550,61 @@ impl StubGenerator for JavaExternalResolver {
         }
         Ok(nodes)
     }
+
+    fn render_source(
+        &self,
+        fqn: &str,
+        entry: &AssetEntry,
+    ) -> std::result::Result<Option<SyntheticSource>, Box<dyn std::error::Error + Send + Sync>>
+    {
+        let (class_fqn, rendered) = self.render_class_for_fqn(fqn, &entry.path)?;
+        Ok(Some(SyntheticSource {
+            relative_path: PathBuf::from(class_fqn.replace('.', "/") + ".java"),
+            content: rendered.content,
+            symbols: rendered.symbols,
+        }))
+    }
+
+    fn extract_source(
+        &self,
+        fqn: &str,
+        sources: &Path,
+    ) -> std::result::Result<Option<SyntheticSource>, Box<dyn std::error::Error + Send + Sync>>
+    {
+        let mut archive = ZipArchive::new(File::open(sources)?)?;
+        // Nested types live in the file of their outermost type
+        let mut type_fqn = crate::naming::extract_type_fqn(fqn);
+        let (relative_path, content) = loop {
+            let path = type_fqn.replace('.', "/") + ".java";
+            if let Ok(mut entry) = archive.by_name(&path) {
+                let mut content = String::new();
+                entry.read_to_string(&mut content)?;
+                break (PathBuf::from(path), content);
+            }
+            match type_fqn.rfind('.') {
+                Some(idx) => type_fqn = &type_fqn[..idx],
+                None => return Ok(None),
+            }
+        };
+
+        let parsed =
+            crate::parser::JavaParser::new()?.parse_file(&content, Some(&relative_path))?;
+        let symbols = parsed
+            .output
+            .nodes
+            .into_iter()
+            .filter_map(|node| {
+                let location = node.location?;
+                let range = location.selection_range.unwrap_or(location.range);
+                Some((node.id.to_string(), range))
+            })
+            .collect();
+        Ok(Some(SyntheticSource {
+            relative_path,
+            content,
+            symbols,
+        }))
+    }
 }
 
 impl AssetSourceLocator for JavaExternalResolver {
@@ -579,4 +661,30 @@ mod tests {
 
         assert_eq!(packages, vec!["com.example".to_string()]);
     }
+
+    #[test]
+    fn test_extract_source_of_nested_type() {
+        let dir = tempdir().unwrap();
+        let jar_path = dir.path().join("lib-1.0-sources.jar");
+        let mut zip = zip::ZipWriter::new(File::create(&jar_path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("com/example/Outer.java", options).unwrap();
+        zip.write_all(b"package com.example;\n\nclass Outer {\n    static class Inner {}\n}\n")
+            .unwrap();
+        zip.finish().unwrap();
+
+        let resolver = JavaExternalResolver;
+        let source = resolver
+            .extract_source("com.example.Outer.Inner", &jar_path)
+            .unwrap()
+            .unwrap();
+        assert_eq!(source.relative_path, Path::new("com/example/Outer.java"));
+        assert_eq!(source.symbols["com.example.Outer.Inner"].start_line, 3);
+        assert!(
+            resolver
+                .extract_source("com.example.Missing", &jar_path)
+                .unwrap()
+                .is_none()
+        );
+    }
 }
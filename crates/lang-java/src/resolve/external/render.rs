@@ -0,0 +1,492 @@
+//! Renders a readable Java document from a parsed class file.
+//!
+//! Only declarations are recovered: package, type header, fields and method
+//! signatures. Generic signatures are erased and method bodies are replaced by a
+//! placeholder, which is enough to give navigation into a dependency without
+//! sources a meaningful target.
+
+use super::converter::{JavaModifierConverter, JavaTypeConverter};
+use naviscope_api::models::{Range, TypeRef};
+use ristretto_classfile::{
+    ClassAccessFlags, ClassFile, Constant, ConstantPool, FieldAccessFlags, MethodAccessFlags,
+};
+use std::collections::HashMap;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+const INDENT: &str = "    ";
+
+pub struct RenderedClass {
+    pub content: String,
+    /// Declaration ranges keyed by the FQNs used for external stubs
+    pub symbols: HashMap<String, Range>,
+}
+
+struct DocumentWriter {
+    lines: Vec<String>,
+    symbols: HashMap<String, Range>,
+}
+
+impl DocumentWriter {
+    fn line(&mut self, text: impl Into<String>) {
+        self.lines.push(text.into());
+    }
+
+    /// Push `prefix name suffix` and record the range of `name` under `fqn`.
+    fn declaration(&mut self, fqn: String, prefix: &str, name: &str, suffix: &str) {
+        let line = self.lines.len();
+        let start_col = prefix.len();
+        self.symbols.insert(
+            fqn,
+            Range {
+                start_line: line,
+                start_col,
+                end_line: line,
+                end_col: start_col + name.len(),
+            },
+        );
+        self.lines.push(format!("{prefix}{name}{suffix}"));
+    }
+}
+
+/// Render `class` (whose stub FQN is `class_fqn`) as a synthetic Java document.
+pub fn render_class(
+    class: &ClassFile,
+    class_fqn: &str,
+    origin: &str,
+) -> Result<RenderedClass, BoxError> {
+    let pool = &class.constant_pool;
+    let flags = class.access_flags;
+
+    let binary_name =
+        binary_class_name(pool, class.this_class).unwrap_or_else(|| class_fqn.replace('.', "/"));
+    let (package, simple_name) = match binary_name.rsplit_once('/') {
+        Some((pkg, name)) => (Some(pkg.replace('/', ".")), name),
+        None => (None, binary_name.as_str()),
+    };
+    let simple_name = simple_name.rsplit('$').next().unwrap_or(simple_name);
+
+    let is_interface = flags.contains(ClassAccessFlags::INTERFACE);
+    let is_annotation = flags.contains(ClassAccessFlags::ANNOTATION);
+    let is_enum = flags.contains(ClassAccessFlags::ENUM);
+
+    let mut out = DocumentWriter {
+        lines: Vec::new(),
+        symbols: HashMap::new(),
+    };
+    out.line(format!(
+        "// Decompiled from {origin}: declarations only, method bodies are not available"
+    ));
+    if let Some(package) = &package {
+        out.line(format!("package {package};"));
+    }
+    out.line("");
+
+    let mut modifiers = JavaModifierConverter::parse_class(flags);
+    if is_enum {
+        modifiers.retain(|m| m != "final");
+    }
+    let keyword = if is_annotation {
+        "@interface"
+    } else if is_interface {
+        "interface"
+    } else if is_enum {
+        "enum"
+    } else {
+        "class"
+    };
+
+    let super_name = class_name(pool, class.super_class)
+        .filter(|s| s != "java.lang.Object" && !(is_enum && s == "java.lang.Enum"));
+    let interfaces: Vec<String> = class
+        .interfaces
+        .iter()
+        .filter_map(|&index| class_name(pool, index))
+        .filter(|i| !(is_annotation && i == "java.lang.annotation.Annotation"))
+        .collect();
+
+    let mut header_suffix = String::new();
+    if let Some(super_name) = super_name {
+        header_suffix.push_str(&format!(" extends {super_name}"));
+    }
+    if !interfaces.is_empty() {
+        let keyword = if is_interface {
+            "extends"
+        } else {
+            "implements"
+        };
+        header_suffix.push_str(&format!(" {keyword} {}", interfaces.join(", ")));
+    }
+    header_suffix.push_str(" {");
+
+    let mut header_prefix = modifiers.join(" ");
+    if !header_prefix.is_empty() {
+        header_prefix.push(' ');
+    }
+    header_prefix.push_str(keyword);
+    header_prefix.push(' ');
+    out.declaration(
+        class_fqn.to_string(),
+        &header_prefix,
+        simple_name,
+        &header_suffix,
+    );
+
+    // Enum constants must come first in the body
+    if is_enum {
+        let constants: Vec<String> = class
+            .fields
+            .iter()
+            .filter(|f| f.access_flags.contains(FieldAccessFlags::ENUM))
+            .filter_map(|f| pool.try_get_utf8(f.name_index).ok().map(|n| n.to_string()))
+            .collect();
+        for (i, name) in constants.iter().enumerate() {
+            let suffix = if i + 1 == constants.len() { ";" } else { "," };
+            let fqn = crate::naming::build_member_fqn(class_fqn, name);
+            out.declaration(fqn, INDENT, name, suffix);
+        }
+        if constants.is_empty() {
+            out.line(format!("{INDENT};"));
+        }
+    }
+
+    let fields = class.fields.iter().filter(|f| {
+        !f.access_flags.contains(FieldAccessFlags::SYNTHETIC)
+            && (!is_enum || !f.access_flags.contains(FieldAccessFlags::ENUM))
+    });
+    for field in fields {
+        let name: &str = pool
+            .try_get_utf8(field.name_index)
+            .map_err(|e| format!("Failed to parse field name: {e:?}"))?;
+        let type_ref = JavaTypeConverter::convert_field(&field.field_type);
+        let mut prefix = INDENT.to_string();
+        for modifier in JavaModifierConverter::parse_field(field.access_flags) {
+            prefix.push_str(&modifier);
+            prefix.push(' ');
+        }
+        prefix.push_str(&render_type(&type_ref));
+        prefix.push(' ');
+        out.line("");
+        let fqn = crate::naming::build_member_fqn(class_fqn, name);
+        out.declaration(fqn, &prefix, name, ";");
+    }
+
+    let methods = class.methods.iter().filter(|m| {
+        !m.access_flags
+            .intersects(MethodAccessFlags::SYNTHETIC | MethodAccessFlags::BRIDGE)
+    });
+    for method in methods {
+        let method_name: &str = pool
+            .try_get_utf8(method.name_index)
+            .map_err(|e| format!("Failed to parse method name: {e:?}"))?;
+        if method_name == "<clinit>" {
+            continue;
+        }
+        let descriptor: &str = pool
+            .try_get_utf8(method.descriptor_index)
+            .map_err(|e| format!("Failed to parse method descriptor: {e:?}"))?;
+        let is_varargs = method.access_flags.contains(MethodAccessFlags::VARARGS);
+        let (return_type, parameters) =
+            JavaTypeConverter::convert_method(descriptor, is_varargs)
+                .map_err(|e| format!("Failed to parse method signature: {e:?}"))?;
+
+        let is_constructor = method_name == "<init>";
+        let display_name = if is_constructor {
+            simple_name
+        } else {
+            method_name
+        };
+
+        let mut modifiers = JavaModifierConverter::parse_method(method.access_flags);
+        if is_interface {
+            modifiers.retain(|m| m != "abstract" && m != "public");
+        }
+        let mut prefix = INDENT.to_string();
+        for modifier in &modifiers {
+            prefix.push_str(modifier);
+            prefix.push(' ');
+        }
+        if !is_constructor {
+            prefix.push_str(&render_type(&return_type));
+            prefix.push(' ');
+        }
+
+        let params: Vec<String> = parameters
+            .iter()
+            .map(|p| {
+                let ty = render_type(&p.type_ref);
+                match ty.strip_suffix("[]").filter(|_| p.is_varargs) {
+                    Some(element) => format!("{element}... {}", p.name),
+                    None => format!("{ty} {}", p.name),
+                }
+            })
+            .collect();
+        let has_body = !method
+            .access_flags
+            .intersects(MethodAccessFlags::ABSTRACT | MethodAccessFlags::NATIVE);
+        let body = if has_body {
+            " { /* compiled code */ }"
+        } else {
+            ";"
+        };
+        let suffix = format!("({}){body}", params.join(", "));
+
+        let param_types: Vec<TypeRef> = parameters.iter().map(|p| p.type_ref.clone()).collect();
+        let signed_name = crate::naming::build_java_method_name(display_name, &param_types);
+        let fqn = crate::naming::build_member_fqn(class_fqn, &signed_name);
+        out.line("");
+        out.declaration(fqn, &prefix, display_name, &suffix);
+    }
+
+    out.line("}");
+
+    let mut content = out.lines.join("\n");
+    content.push('\n');
+    Ok(RenderedClass {
+        content,
+        symbols: out.symbols,
+    })
+}
+
+/// Resolve a `CONSTANT_Class` entry to its internal name (`java/util/Map$Entry`).
+fn binary_class_name(pool: &ConstantPool, index: u16) -> Option<String> {
+    if index == 0 {
+        return None;
+    }
+    match pool.get(index)? {
+        Constant::Class(name_index) => pool.try_get_utf8(*name_index).ok().map(|n| n.to_string()),
+        _ => None,
+    }
+}
+
+/// Resolve a `CONSTANT_Class` entry to a source-style class name (`java.util.Map.Entry`).
+fn class_name(pool: &ConstantPool, index: u16) -> Option<String> {
+    binary_class_name(pool, index).map(|name| name.replace(['/', '$'], "."))
+}
+
+/// Java source spelling of a descriptor type.
+fn render_type(type_ref: &TypeRef) -> String {
+    crate::naming::normalize_type_for_signature(type_ref).replace('$', ".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ristretto_classfile::attributes::Attribute;
+    use ristretto_classfile::{Field, FieldType, Method};
+
+    fn class(
+        name: &str,
+        flags: ClassAccessFlags,
+        super_name: &str,
+        interfaces: &[&str],
+    ) -> ClassFile {
+        let mut pool = ConstantPool::default();
+        let this_class = pool.add_class(name).unwrap();
+        let super_class = pool.add_class(super_name).unwrap();
+        let interfaces = interfaces
+            .iter()
+            .map(|i| pool.add_class(i).unwrap())
+            .collect();
+        ClassFile {
+            constant_pool: pool,
+            access_flags: flags,
+            this_class,
+            super_class,
+            interfaces,
+            ..Default::default()
+        }
+    }
+
+    fn add_field(class: &mut ClassFile, flags: FieldAccessFlags, name: &str, descriptor: &str) {
+        let name_index = class.constant_pool.add_utf8(name).unwrap();
+        let descriptor_index = class.constant_pool.add_utf8(descriptor).unwrap();
+        class.fields.push(Field {
+            access_flags: flags,
+            name_index,
+            descriptor_index,
+            field_type: FieldType::parse(descriptor).unwrap(),
+            attributes: vec![],
+        });
+    }
+
+    fn add_method(class: &mut ClassFile, flags: MethodAccessFlags, name: &str, descriptor: &str) {
+        let name_index = class.constant_pool.add_utf8(name).unwrap();
+        let descriptor_index = class.constant_pool.add_utf8(descriptor).unwrap();
+        class.methods.push(Method {
+            access_flags: flags,
+            name_index,
+            descriptor_index,
+            attributes: vec![],
+        });
+    }
+
+    /// Line of the declaration recorded for `fqn` and the text its range covers
+    fn symbol<'a>(rendered: &'a RenderedClass, fqn: &str) -> (usize, &'a str) {
+        let range = rendered.symbols[fqn];
+        assert_eq!(range.start_line, range.end_line);
+        let line = rendered.content.lines().nth(range.start_line).unwrap();
+        (range.start_line, &line[range.start_col..range.end_col])
+    }
+
+    #[test]
+    fn renders_class_members_and_their_ranges() {
+        let mut box_class = class(
+            "com/example/Box",
+            ClassAccessFlags::PUBLIC,
+            "java/lang/Object",
+            &["java/io/Serializable"],
+        );
+        add_field(&mut box_class, FieldAccessFlags::PRIVATE, "size", "I");
+        add_field(
+            &mut box_class,
+            FieldAccessFlags::PRIVATE | FieldAccessFlags::SYNTHETIC,
+            "this$0",
+            "Ljava/lang/Object;",
+        );
+        add_method(
+            &mut box_class,
+            MethodAccessFlags::PUBLIC,
+            "<init>",
+            "(Ljava/lang/String;)V",
+        );
+        add_method(&mut box_class, MethodAccessFlags::STATIC, "<clinit>", "()V");
+
+        let rendered = render_class(&box_class, "com.example.Box", "box.jar").unwrap();
+        assert_eq!(
+            rendered.content,
+            "// Decompiled from box.jar: declarations only, method bodies are not available\n\
+             package com.example;\n\
+             \n\
+             public class Box implements java.io.Serializable {\n\
+             \n    private int size;\n\
+             \n    public Box(java.lang.String arg0) { /* compiled code */ }\n\
+             }\n"
+        );
+        assert_eq!(symbol(&rendered, "com.example.Box"), (3, "Box"));
+        assert_eq!(symbol(&rendered, "com.example.Box#size"), (5, "size"));
+        assert_eq!(
+            symbol(&rendered, "com.example.Box#Box(java.lang.String)"),
+            (7, "Box")
+        );
+        assert_eq!(rendered.symbols.len(), 3);
+    }
+
+    #[test]
+    fn renders_varargs_and_erases_generic_signatures() {
+        let mut lists = class(
+            "com/example/Lists",
+            ClassAccessFlags::PUBLIC | ClassAccessFlags::FINAL,
+            "java/lang/Object",
+            &[],
+        );
+        add_method(
+            &mut lists,
+            MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC | MethodAccessFlags::VARARGS,
+            "of",
+            "([Ljava/lang/Object;)Ljava/util/List;",
+        );
+        let name_index = lists.constant_pool.add_utf8("Signature").unwrap();
+        let signature_index = lists
+            .constant_pool
+            .add_utf8("<T:Ljava/lang/Object;>([TT;)Ljava/util/List<TT;>;")
+            .unwrap();
+        lists.methods[0].attributes.push(Attribute::Signature {
+            name_index,
+            signature_index,
+        });
+        add_method(
+            &mut lists,
+            MethodAccessFlags::PUBLIC | MethodAccessFlags::NATIVE,
+            "fill",
+            "([I[[Ljava/lang/String;)V",
+        );
+
+        let rendered = render_class(&lists, "com.example.Lists", "lists.jar").unwrap();
+        let lines: Vec<&str> = rendered.content.lines().collect();
+        assert_eq!(lines[3], "public final class Lists {");
+        assert_eq!(
+            lines[5],
+            "    public static java.util.List of(java.lang.Object... arg0) { /* compiled code */ }"
+        );
+        assert_eq!(
+            lines[7],
+            "    public native void fill(int[] arg0, java.lang.String[][] arg1);"
+        );
+        assert_eq!(
+            symbol(&rendered, "com.example.Lists#of(java.lang.Object[])"),
+            (5, "of")
+        );
+        assert_eq!(
+            symbol(
+                &rendered,
+                "com.example.Lists#fill(int[],java.lang.String[][])"
+            ),
+            (7, "fill")
+        );
+    }
+
+    #[test]
+    fn renders_interfaces_without_implied_modifiers() {
+        let mut named = class(
+            "com/example/Named",
+            ClassAccessFlags::PUBLIC | ClassAccessFlags::INTERFACE | ClassAccessFlags::ABSTRACT,
+            "java/lang/Object",
+            &["java/lang/Comparable"],
+        );
+        add_method(
+            &mut named,
+            MethodAccessFlags::PUBLIC | MethodAccessFlags::ABSTRACT,
+            "name",
+            "()Ljava/lang/String;",
+        );
+
+        let rendered = render_class(&named, "com.example.Named", "named.jar").unwrap();
+        let lines: Vec<&str> = rendered.content.lines().collect();
+        assert_eq!(
+            lines[3],
+            "public interface Named extends java.lang.Comparable {"
+        );
+        assert_eq!(lines[5], "    java.lang.String name();");
+        assert_eq!(symbol(&rendered, "com.example.Named#name()"), (5, "name"));
+    }
+
+    #[test]
+    fn renders_enum_constants_first() {
+        let mut color = class(
+            "com/example/Color",
+            ClassAccessFlags::PUBLIC | ClassAccessFlags::FINAL | ClassAccessFlags::ENUM,
+            "java/lang/Enum",
+            &[],
+        );
+        let constant = FieldAccessFlags::PUBLIC
+            | FieldAccessFlags::STATIC
+            | FieldAccessFlags::FINAL
+            | FieldAccessFlags::ENUM;
+        add_field(&mut color, constant, "RED", "Lcom/example/Color;");
+        add_field(&mut color, constant, "GREEN", "Lcom/example/Color;");
+        add_method(
+            &mut color,
+            MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC,
+            "values",
+            "()[Lcom/example/Color;",
+        );
+
+        let rendered = render_class(&color, "com.example.Color", "color.jar").unwrap();
+        let lines: Vec<&str> = rendered.content.lines().collect();
+        assert_eq!(
+            lines[3..],
+            [
+                "public enum Color {",
+                "    RED,",
+                "    GREEN;",
+                "",
+                "    public static com.example.Color[] values() { /* compiled code */ }",
+                "}",
+            ]
+        );
+        assert_eq!(symbol(&rendered, "com.example.Color"), (3, "Color"));
+        assert_eq!(symbol(&rendered, "com.example.Color#RED"), (4, "RED"));
+        assert_eq!(symbol(&rendered, "com.example.Color#GREEN"), (5, "GREEN"));
+    }
+}
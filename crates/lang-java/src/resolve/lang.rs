@@ -3,15 +3,33 @@ use crate::inference::adapters::HeuristicAdapter;
 use crate::inference::{TypeProvider, TypeResolutionContext};
 use crate::model::JavaIndexMetadata;
 use crate::resolve::context::ResolutionContext;
-use naviscope_api::models::graph::{EdgeType, GraphEdge, NodeKind};
-use naviscope_api::models::symbol::{NodeId, SymbolResolution};
+use naviscope_api::lifecycle::WarningSeverity;
+use naviscope_api::models::graph::{DisplaySymbolLocation, EdgeType, GraphEdge, NodeKind};
+use naviscope_api::models::symbol::{NodeId, Range, SymbolResolution};
 use naviscope_plugin::{
-    GlobalParseResult, GraphOp, IndexNode, IndexRelation, ParsedContent, ParsedFile, ProjectContext,
-    ResolvedUnit, SourceAnalyzeArtifact, SourceCollectArtifact, SourceIndexCap,
+    GlobalParseResult, GraphOp, IndexNode, IndexRelation, ModuleDescriptor, ParsedContent,
+    ParsedFile, ProjectContext, ResolvedUnit, SourceAnalyzeArtifact, SourceCollectArtifact,
+    SourceIndexCap,
 };
 use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// File name of a Java module descriptor
+const MODULE_INFO_FILE: &str = "module-info.java";
+
+/// The first node tree-sitter could not parse, in source order
+fn first_syntax_error(node: tree_sitter::Node) -> Option<tree_sitter::Node> {
+    if node.is_error() || node.is_missing() {
+        return Some(node);
+    }
+    if !node.has_error() {
+        return None;
+    }
+    let mut cursor = node.walk();
+    node.children(&mut cursor).find_map(first_syntax_error)
+}
+
 struct CollectOutput {
     unit: ResolvedUnit,
     container_id: NodeId,
@@ -35,7 +53,10 @@ struct DeferredRelation {
 }
 
 struct JavaCollectArtifact {
+    path: PathBuf,
     parse_result: GlobalParseResult,
+    /// Module declared by a `module-info.java`, with its declaration range
+    module: Option<(ModuleDescriptor, Range)>,
     collected: CollectOutput,
     type_symbols: Vec<String>,
     method_symbols: Vec<String>,
@@ -72,6 +93,14 @@ impl SourceCollectArtifact for JavaCollectArtifact {
     fn required_dependency_symbols(&self) -> &[String] {
         &self.required_dependency_symbols
     }
+
+    fn declared_package(&self) -> Option<&str> {
+        self.parse_result.package_name.as_deref()
+    }
+
+    fn module_descriptor(&self) -> Option<&ModuleDescriptor> {
+        self.module.as_ref().map(|(descriptor, _)| descriptor)
+    }
 }
 
 impl SourceAnalyzeArtifact for JavaAnalyzeArtifact {
@@ -152,8 +181,26 @@ impl SourceIndexCap for JavaPlugin {
             }
         }
 
+        let module = match (
+            &parse_result.tree,
+            &parse_result.source,
+            file.path().parent(),
+        ) {
+            (Some(tree), Some(source), Some(root))
+                if file
+                    .path()
+                    .file_name()
+                    .is_some_and(|n| n == MODULE_INFO_FILE) =>
+            {
+                self.parser.extract_module_descriptor(tree, source, root)
+            }
+            _ => None,
+        };
+
         Ok(Box::new(JavaCollectArtifact {
+            path: file.path().to_path_buf(),
             parse_result: parse_result.clone(),
+            module,
             collected,
             type_symbols,
             method_symbols,
@@ -173,7 +220,21 @@ impl SourceIndexCap for JavaPlugin {
             .downcast::<JavaCollectArtifact>()
             .map_err(|_| "Java analyze_source received incompatible collect artifact")?;
         let mut analyzed = self.analyze_pass(collected.collected, &collected.parse_result, context);
-        self.bind_all_relations(&mut analyzed, &collected.parse_result);
+        self.bind_all_relations(
+            &mut analyzed,
+            &collected.parse_result,
+            &collected.path,
+            context,
+        );
+        if let Some((descriptor, range)) = &collected.module {
+            Self::add_module_declaration(
+                &mut analyzed.unit,
+                descriptor,
+                range,
+                &collected.path,
+                context,
+            );
+        }
 
         Ok(Box::new(JavaAnalyzeArtifact {
             parse_result: collected.parse_result,
@@ -202,6 +263,19 @@ impl JavaPlugin {
         parse_result: &GlobalParseResult,
     ) -> CollectOutput {
         let mut unit = ResolvedUnit::new();
+        if let Some(error) = parse_result
+            .tree
+            .as_ref()
+            .and_then(|tree| first_syntax_error(tree.root_node()))
+        {
+            unit.warn(
+                WarningSeverity::Warning,
+                format!(
+                    "syntax error at line {}; declarations overlapping errors are not indexed",
+                    error.start_position().row + 1
+                ),
+            );
+        }
         unit.identifiers = parse_result.output.identifiers.clone();
         unit.ops.push(GraphOp::UpdateIdentifiers {
             path: Arc::from(file.file.path.as_path()),
@@ -241,10 +315,7 @@ impl JavaPlugin {
             module_id.into()
         };
 
-        CollectOutput {
-            unit,
-            container_id,
-        }
+        CollectOutput { unit, container_id }
     }
 
     fn analyze_pass(
@@ -276,7 +347,8 @@ impl JavaPlugin {
         for node in &parse_result.output.nodes {
             let mut node = node.clone();
 
-            if let Some(java_idx_meta) = node.metadata.as_any().downcast_ref::<JavaIndexMetadata>() {
+            if let Some(java_idx_meta) = node.metadata.as_any().downcast_ref::<JavaIndexMetadata>()
+            {
                 let mut element = java_idx_meta.clone();
                 match &mut element {
                     JavaIndexMetadata::Method {
@@ -286,11 +358,8 @@ impl JavaPlugin {
                     } => {
                         *return_type = self.resolve_type_ref(return_type, &type_provider, &res_ctx);
                         for param in parameters {
-                            param.type_ref = self.resolve_type_ref(
-                                &param.type_ref,
-                                &type_provider,
-                                &res_ctx,
-                            );
+                            param.type_ref =
+                                self.resolve_type_ref(&param.type_ref, &type_provider, &res_ctx);
                         }
                     }
                     JavaIndexMetadata::Field { type_ref, .. } => {
@@ -328,12 +397,89 @@ impl JavaPlugin {
         &self,
         analyzed: &mut AnalyzeOutput,
         parse_result: &GlobalParseResult,
+        path: &Path,
+        context: &ProjectContext,
     ) {
         for rel in &parse_result.output.relations {
-            self.bind_relation(rel, parse_result, analyzed);
+            self.bind_relation(rel, parse_result, analyzed, path, context);
         }
     }
 
+    /// Module node for a `module-info.java`, with `Requires` edges to the
+    /// project modules it reads and `Exports` edges to its exported packages.
+    /// Modules outside the project (the JDK, libraries) are not modeled.
+    fn add_module_declaration(
+        unit: &mut ResolvedUnit,
+        descriptor: &ModuleDescriptor,
+        range: &Range,
+        path: &Path,
+        context: &ProjectContext,
+    ) {
+        let module_id = |name: &str| NodeId::Structured(vec![(NodeKind::Module, name.to_string())]);
+        let id = module_id(&descriptor.name);
+        unit.add_node(IndexNode {
+            id: id.clone(),
+            name: descriptor.name.clone(),
+            kind: NodeKind::Module,
+            lang: "java".to_string(),
+            source: naviscope_api::models::graph::NodeSource::Project,
+            status: naviscope_api::models::graph::ResolutionStatus::Resolved,
+            location: Some(DisplaySymbolLocation {
+                path: path.to_string_lossy().to_string(),
+                range: *range,
+                selection_range: None,
+            }),
+            metadata: Arc::new(JavaIndexMetadata::Module),
+        });
+        if let Some(build_module) = context.find_module_for_path(path) {
+            unit.add_edge(
+                build_module.into(),
+                id.clone(),
+                GraphEdge::new(EdgeType::Contains),
+            );
+        }
+
+        let descriptors = &context.symbol_table.module_descriptors;
+        for require in &descriptor.requires {
+            if descriptors.contains_key(&require.module) {
+                unit.add_edge(
+                    id.clone(),
+                    module_id(&require.module),
+                    GraphEdge::new(EdgeType::Requires),
+                );
+            }
+        }
+        for export in &descriptor.exports {
+            if context
+                .symbol_table
+                .package_dirs
+                .contains_key(&export.package)
+            {
+                let package_id = NodeId::Structured(
+                    export
+                        .package
+                        .split('.')
+                        .map(|s| (NodeKind::Package, s.to_string()))
+                        .collect(),
+                );
+                unit.add_edge(id.clone(), package_id, GraphEdge::new(EdgeType::Exports));
+            }
+        }
+    }
+
+    /// Package of `fqn` among the packages declared in the project
+    fn project_package<'a>(fqn: &str, context: &'a ProjectContext) -> Option<&'a str> {
+        let packages = &context.symbol_table.package_dirs;
+        let mut end = fqn.len();
+        while let Some(dot) = fqn[..end].rfind('.') {
+            if let Some((package, _)) = packages.get_key_value(&fqn[..dot]) {
+                return Some(package);
+            }
+            end = dot;
+        }
+        None
+    }
+
     fn lower_pass(
         &self,
         mut analyzed: AnalyzeOutput,
@@ -346,9 +492,12 @@ impl JavaPlugin {
         }
 
         for deferred in analyzed.deferred_relations.drain(..) {
-            analyzed.unit.deferred_symbols.push(naviscope_plugin::DeferredSymbol {
-                target: deferred.raw_target,
-            });
+            analyzed
+                .unit
+                .deferred_symbols
+                .push(naviscope_plugin::DeferredSymbol {
+                    target: deferred.raw_target,
+                });
         }
 
         Ok(analyzed.unit)
@@ -359,6 +508,8 @@ impl JavaPlugin {
         rel: &IndexRelation,
         parse_result: &GlobalParseResult,
         analyzed: &mut AnalyzeOutput,
+        path: &Path,
+        context: &ProjectContext,
     ) {
         let dummy_index = naviscope_plugin::EmptyCodeGraph;
         let type_provider = HeuristicAdapter;
@@ -367,7 +518,8 @@ impl JavaPlugin {
         let mut resolved_target = original_target.clone();
         let mut precise_bound = false;
 
-        if let (Some(tree), Some(source), Some(r)) = (&parse_result.tree, &parse_result.source, &rel.range)
+        if let (Some(tree), Some(source), Some(r)) =
+            (&parse_result.tree, &parse_result.source, &rel.range)
         {
             let point = tree_sitter::Point::new(r.start_line, r.start_col);
             if let Some(node) = tree
@@ -384,7 +536,9 @@ impl JavaPlugin {
                     &self.parser,
                 );
 
-                if let Some(SymbolResolution::Precise(fqn, _)) = self.resolve_symbol_internal(&context) {
+                if let Some(SymbolResolution::Precise(fqn, _)) =
+                    self.resolve_symbol_internal(&context)
+                {
                     resolved_target = fqn;
                     precise_bound = true;
                 } else if !resolved_target.contains('.') {
@@ -403,21 +557,28 @@ impl JavaPlugin {
             });
         }
 
-        let target_id = if resolved_target == original_target && matches!(rel.target_id, NodeId::Structured(_))
+        let target_id = if resolved_target == original_target
+            && matches!(rel.target_id, NodeId::Structured(_))
         {
             rel.target_id.clone()
         } else {
-            Self::build_target_node_id(
-                &resolved_target,
-                &rel.edge_type,
-                &analyzed.unit.nodes,
-            )
+            Self::build_target_node_id(&resolved_target, &rel.edge_type, &analyzed.unit.nodes)
+        };
+
+        // Module boundaries only matter once the project declares modules
+        let accessible = context.symbol_table.module_descriptors.is_empty()
+            || Self::project_package(&resolved_target, context)
+                .is_none_or(|package| context.can_access(path, package));
+        let edge = if accessible {
+            GraphEdge::new(rel.edge_type.clone())
+        } else {
+            GraphEdge::inaccessible(rel.edge_type.clone())
         };
 
         analyzed.bound_relations.push(BoundRelation {
             source_id: rel.source_id.clone(),
             target_id,
-            edge: GraphEdge::new(rel.edge_type.clone()),
+            edge,
         });
     }
 
@@ -460,6 +621,8 @@ impl JavaPlugin {
                         || *edge_type == EdgeType::InheritsFrom
                         || *edge_type == EdgeType::TypedAs
                         || *edge_type == EdgeType::DecoratedBy
+                        || *edge_type == EdgeType::Injects
+                        || *edge_type == EdgeType::BeanOf
                     {
                         found_kind = NodeKind::Class;
                     } else if part.chars().next().is_some_and(|c| c.is_uppercase()) {
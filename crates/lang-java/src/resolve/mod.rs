@@ -4,12 +4,38 @@ pub mod context;
 pub mod external;
 pub mod lang;
 pub mod semantic;
+mod signature;
 pub mod types;
 
 use crate::JavaPlugin;
 use crate::inference::adapters::CodeGraphTypeSystem;
 use context::ResolutionContext;
-use naviscope_api::models::{SymbolResolution, TypeRef};
+use naviscope_api::models::{ResolutionTrace, SymbolResolution, TypeRef};
+
+/// Records resolution stages when explaining; a no-op during normal resolution.
+pub(crate) struct Tracer<'t>(Option<&'t mut ResolutionTrace>);
+
+impl<'t> Tracer<'t> {
+    pub(crate) fn off() -> Self {
+        Self(None)
+    }
+
+    pub(crate) fn on(trace: &'t mut ResolutionTrace) -> Self {
+        Self(Some(trace))
+    }
+
+    fn step(&mut self, stage: &str, matched: bool, detail: impl FnOnce() -> String) {
+        if let Some(trace) = self.0.as_deref_mut() {
+            trace.step(stage, matched, detail());
+        }
+    }
+
+    fn scope(&mut self, scope: impl FnOnce() -> String) {
+        if let Some(trace) = self.0.as_deref_mut() {
+            trace.scopes.push(scope());
+        }
+    }
+}
 
 impl JavaPlugin {
     /// Helper to find enclosing class using ScopeManager
@@ -30,6 +56,15 @@ impl JavaPlugin {
 
     /// Resolve a symbol using the new inference-based approach.
     pub fn resolve_symbol_internal(&self, context: &ResolutionContext) -> Option<SymbolResolution> {
+        self.resolve_symbol_traced(context, &mut Tracer::off())
+    }
+
+    /// Resolve a symbol, reporting each stage to `tracer`.
+    pub(crate) fn resolve_symbol_traced(
+        &self,
+        context: &ResolutionContext,
+        tracer: &mut Tracer,
+    ) -> Option<SymbolResolution> {
         let ts = CodeGraphTypeSystem::new(context.index);
 
         // Extract package from tree
@@ -61,6 +96,22 @@ impl JavaPlugin {
                 .and_then(|sm| self.find_enclosing_class_via_scope(context.node, sm))
         };
 
+        let classes = if context.enclosing_classes.is_empty() {
+            enclosing_fqn.as_slice()
+        } else {
+            context.enclosing_classes.as_slice()
+        };
+        for class in classes {
+            tracer.scope(|| format!("class {class}"));
+        }
+        tracer.scope(|| match &package {
+            Some(pkg) => format!("package {pkg}"),
+            None => "default package".to_string(),
+        });
+        if !imports.is_empty() {
+            tracer.scope(|| format!("imports {}", imports.join(", ")));
+        }
+
         if let Some(fqn) = enclosing_fqn {
             infer_ctx = infer_ctx.with_enclosing_class(fqn);
         }
@@ -83,6 +134,7 @@ impl JavaPlugin {
                                 crate::naming::build_java_method_name(&context.name, &param_types);
                             let method_fqn =
                                 crate::naming::build_member_fqn(enclosing, &signed_name);
+                            tracer.step("declaration", true, || method_fqn.clone());
                             return Some(SymbolResolution::Precise(
                                 method_fqn,
                                 naviscope_api::models::SymbolIntent::Method,
@@ -93,6 +145,7 @@ impl JavaPlugin {
                         // Build class FQN
                         let res_ctx = infer_ctx.to_resolution_context();
                         if let Some(fqn) = infer_ctx.ts.resolve_type_name(&context.name, &res_ctx) {
+                            tracer.step("declaration", true, || fqn.clone());
                             return Some(SymbolResolution::Precise(
                                 fqn,
                                 naviscope_api::models::SymbolIntent::Type,
@@ -108,6 +161,7 @@ impl JavaPlugin {
                                 if let Some(ref enclosing) = infer_ctx.enclosing_class {
                                     let field_fqn =
                                         crate::naming::build_member_fqn(enclosing, &context.name);
+                                    tracer.step("declaration", true, || field_fqn.clone());
                                     return Some(SymbolResolution::Precise(
                                         field_fqn,
                                         naviscope_api::models::SymbolIntent::Field,
@@ -119,17 +173,22 @@ impl JavaPlugin {
                     }
                     _ => {}
                 }
+                tracer.step("declaration", false, || {
+                    format!("name of a {} without a declaration FQN", parent.kind())
+                });
             }
         }
 
         // 2. Handle 'this' specifically
         if context.node.kind() == "this" {
             if let Some(enclosing) = &infer_ctx.enclosing_class {
+                tracer.step("this", true, || enclosing.clone());
                 return Some(SymbolResolution::Precise(
                     enclosing.clone(),
                     naviscope_api::models::SymbolIntent::Type,
                 ));
             }
+            tracer.step("this", false, || "no enclosing class".to_string());
         }
 
         // 2.5. Check for local variable references (returns Local resolution)
@@ -159,8 +218,18 @@ impl JavaPlugin {
                         {
                             // Render the full local type for hover/highlight consumers.
                             let type_name = Some(crate::model::fmt_type(&info.type_ref));
+                            tracer.step("local", true, || {
+                                format!("declared at {}:{}", decl_line, decl_col)
+                            });
                             return Some(SymbolResolution::Local(info.range.clone(), type_name));
                         }
+                        tracer.step("local", false, || {
+                            format!("declared at {}:{}, after this usage", decl_line, decl_col)
+                        });
+                    } else {
+                        tracer.step("local", false, || {
+                            format!("no local or parameter named {}", context.name)
+                        });
                     }
                 }
             }
@@ -175,8 +244,12 @@ impl JavaPlugin {
                 if let Some(type_ref) =
                     crate::inference::strategy::MethodCallInfer.infer_member(&parent, &infer_ctx)
                 {
+                    tracer.step("method call", true, || type_ref.clone());
                     return Some(SymbolResolution::Precise(type_ref, context.intent));
                 }
+                tracer.step("method call", false, || {
+                    "receiver type or matching overload not found".to_string()
+                });
             }
             if parent.kind() == "field_access"
                 && parent.child_by_field_name("field") == Some(context.node)
@@ -184,18 +257,28 @@ impl JavaPlugin {
                 if let Some(type_ref) =
                     crate::inference::strategy::FieldAccessInfer.infer_member(&parent, &infer_ctx)
                 {
+                    tracer.step("field access", true, || type_ref.clone());
                     return Some(SymbolResolution::Precise(type_ref, context.intent));
                 }
+                tracer.step("field access", false, || {
+                    "receiver type or field not found".to_string()
+                });
             }
         }
 
         // 4. Main inference path for everything else
-        if let Some(type_ref) =
-            crate::inference::strategy::infer_expression(&context.node, &infer_ctx)
-        {
-            if let TypeRef::Id(fqn) = &type_ref {
-                return Some(SymbolResolution::Precise(fqn.clone(), context.intent));
+        match crate::inference::strategy::infer_expression(&context.node, &infer_ctx) {
+            Some(TypeRef::Id(fqn)) => {
+                tracer.step("inference", true, || fqn.clone());
+                return Some(SymbolResolution::Precise(fqn, context.intent));
             }
+            Some(other) => tracer.step("inference", false, || {
+                format!(
+                    "inferred {}, which is not a resolved type",
+                    crate::model::fmt_type(&other)
+                )
+            }),
+            None => tracer.step("inference", false, || "no type inferred".to_string()),
         }
 
         None
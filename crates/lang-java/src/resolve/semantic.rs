@@ -4,7 +4,9 @@ use crate::inference::{InheritanceProvider, MemberProvider, TypeProvider, TypeRe
 use crate::resolve::context::ResolutionContext;
 use naviscope_api::models::graph::EdgeType;
 use naviscope_api::models::symbol::{FqnId, matches_intent};
-use naviscope_api::models::{SymbolIntent, SymbolResolution, TypeRef};
+use naviscope_api::models::{
+    ResolutionTrace, SignatureHelp, SymbolIntent, SymbolResolution, TypeRef,
+};
 use naviscope_plugin::{CodeGraph, NamingConvention, SymbolQueryService, SymbolResolveService};
 use tree_sitter::Tree;
 
@@ -33,6 +35,61 @@ impl SymbolResolveService for JavaPlugin {
 
         self.resolve_symbol_internal(&context)
     }
+
+    fn explain_at(
+        &self,
+        tree: &Tree,
+        source: &str,
+        line: usize,
+        byte_col: usize,
+        index: &dyn CodeGraph,
+    ) -> (Option<SymbolResolution>, ResolutionTrace) {
+        let mut trace = ResolutionTrace::default();
+        let point = tree_sitter::Point::new(line, byte_col);
+        let Some(node) = tree
+            .root_node()
+            .named_descendant_for_point_range(point, point)
+        else {
+            trace.step("position", false, "no syntax node at position");
+            return (None, trace);
+        };
+        trace.symbol = node
+            .utf8_text(source.as_bytes())
+            .unwrap_or_default()
+            .to_string();
+        trace.syntax_kind = node.kind().to_string();
+        if !matches!(
+            node.kind(),
+            "identifier" | "type_identifier" | "scoped_identifier" | "this"
+        ) {
+            trace.step("position", false, "not an identifier");
+            return (None, trace);
+        }
+
+        let context = ResolutionContext::new(
+            node,
+            trace.symbol.clone(),
+            index,
+            source,
+            tree,
+            &self.parser,
+        );
+        let resolution =
+            self.resolve_symbol_traced(&context, &mut crate::resolve::Tracer::on(&mut trace));
+        trace.resolution = resolution.as_ref().map(|r| r.to_string());
+        (resolution, trace)
+    }
+
+    fn signature_help_at(
+        &self,
+        tree: &Tree,
+        source: &str,
+        line: usize,
+        byte_col: usize,
+        index: &dyn CodeGraph,
+    ) -> Option<SignatureHelp> {
+        self.signature_help_internal(tree, source, line, byte_col, index)
+    }
 }
 
 impl SymbolQueryService for JavaPlugin {
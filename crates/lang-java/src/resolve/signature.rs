@@ -0,0 +1,111 @@
+//! Signature help for method invocations.
+
+use crate::JavaPlugin;
+use crate::inference::MemberInfo;
+use crate::inference::adapters::CodeGraphTypeSystem;
+use crate::resolve::context::ResolutionContext;
+use naviscope_api::models::{SignatureHelp, SignatureInfo, TypeRef};
+use naviscope_plugin::CodeGraph;
+use tree_sitter::{Node, Point, Tree};
+
+impl JavaPlugin {
+    pub(crate) fn signature_help_internal(
+        &self,
+        tree: &Tree,
+        source: &str,
+        line: usize,
+        byte_col: usize,
+        index: &dyn CodeGraph,
+    ) -> Option<SignatureHelp> {
+        let point = Point::new(line, byte_col);
+        let (call, args) = enclosing_call(tree, point)?;
+        let active_parameter = active_parameter(&args, point);
+
+        let name_node = call.child_by_field_name("name")?;
+        let name = name_node.utf8_text(source.as_bytes()).ok()?.to_string();
+        let context = ResolutionContext::new(name_node, name, index, source, tree, &self.parser);
+
+        let ts = CodeGraphTypeSystem::new(index);
+        let mut scope_manager = crate::inference::scope::ScopeManager::new();
+        let mut infer_ctx = crate::inference::create_inference_context(
+            &tree.root_node(),
+            source,
+            &ts,
+            &mut scope_manager,
+            context.package.clone(),
+            context.imports.clone(),
+        );
+        let enclosing_fqn = context.enclosing_classes.first().cloned().or_else(|| {
+            infer_ctx
+                .scope_manager
+                .and_then(|sm| self.find_enclosing_class_via_scope(name_node, sm))
+        });
+        if let Some(fqn) = enclosing_fqn {
+            infer_ctx = infer_ctx.with_enclosing_class(fqn);
+        }
+
+        let (candidates, best) =
+            crate::inference::strategy::MethodCallInfer.overloads(&call, &infer_ctx)?;
+        let active_signature = best
+            .and_then(|best| candidates.iter().position(|c| c.fqn == best.fqn))
+            .unwrap_or(0);
+
+        Some(SignatureHelp {
+            signatures: candidates.iter().map(signature_info).collect(),
+            active_signature,
+            active_parameter,
+        })
+    }
+}
+
+/// Innermost `method_invocation` whose argument list contains `point`.
+fn enclosing_call(tree: &Tree, point: Point) -> Option<(Node<'_>, Node<'_>)> {
+    let mut node = tree.root_node().descendant_for_point_range(point, point)?;
+    loop {
+        if node.kind() == "argument_list"
+            && node.start_position() < point
+            && point < node.end_position()
+            && let Some(parent) = node.parent()
+            && parent.kind() == "method_invocation"
+        {
+            return Some((parent, node));
+        }
+        node = node.parent()?;
+    }
+}
+
+/// Index of the argument under `point`: the number of commas before it.
+fn active_parameter(args: &Node, point: Point) -> usize {
+    let mut cursor = args.walk();
+    args.children(&mut cursor)
+        .filter(|child| child.kind() == "," && child.end_position() <= point)
+        .count()
+}
+
+fn signature_info(member: &MemberInfo) -> SignatureInfo {
+    let parameters: Vec<String> = member
+        .parameters
+        .iter()
+        .flatten()
+        .map(|p| {
+            let param_type = match &p.type_ref {
+                TypeRef::Array { element, .. } if p.is_varargs => {
+                    format!("{}...", crate::model::fmt_type(element))
+                }
+                other => crate::model::fmt_type(other),
+            };
+            format!("{}: {}", p.name, param_type)
+        })
+        .collect();
+
+    SignatureInfo {
+        label: format!(
+            "{}({}) -> {}",
+            crate::naming::extract_simple_name(&member.name),
+            parameters.join(", "),
+            crate::model::fmt_type(&member.type_ref)
+        ),
+        parameters,
+        documentation: Some(format!("*Defined in `{}`*", member.declaring_type)),
+    }
+}
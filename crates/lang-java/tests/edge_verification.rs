@@ -195,6 +195,42 @@ fn test_edge_typed_as() {
     assert_edge(&index, "User#address", "Address", EdgeType::TypedAs);
 }
 
+#[test]
+fn test_edge_typed_as_nested_positions() {
+    let files = vec![
+        (
+            "src/Loader.java",
+            r#"
+            public class Loader<T extends Source> {
+                public void load(Config config) throws LoadError {
+                    Cache cache = null;
+                    var copy = cache;
+                }
+            }
+        "#,
+        ),
+        ("src/Source.java", "public interface Source {}"),
+        ("src/Config.java", "public class Config {}"),
+        (
+            "src/LoadError.java",
+            "public class LoadError extends Exception {}",
+        ),
+        ("src/Cache.java", "public class Cache {}"),
+    ];
+    let (index, _) = setup_java_test_graph(files);
+
+    assert_edge(&index, "Loader", "Source", EdgeType::TypedAs);
+    assert_edge(&index, "Loader#load(Config)", "Config", EdgeType::TypedAs);
+    assert_edge(
+        &index,
+        "Loader#load(Config)",
+        "LoadError",
+        EdgeType::TypedAs,
+    );
+    assert_edge(&index, "Loader#load(Config)", "Cache", EdgeType::TypedAs);
+    assert!(index.find_node("var").is_none());
+}
+
 #[test]
 fn test_edge_decorated_by() {
     let files = vec![
@@ -520,3 +520,38 @@ fn test_field_method_call_resolution() {
         panic!("Expected precise resolution to B.doB, got {:?}", res);
     }
 }
+
+#[test]
+fn test_file_without_package_stays_in_default_package() {
+    use naviscope_api::models::graph::NodeKind;
+    use naviscope_java::parser::JavaParser;
+    use naviscope_plugin::{
+        GraphOp, ParsedContent, ParsedFile, ProjectContext, SourceFile, SourceIndexCap,
+    };
+    use std::path::PathBuf;
+
+    let plugin = JavaPlugin::new().unwrap();
+    let parser = JavaParser::new().unwrap();
+    let context = ProjectContext::new();
+
+    let path = PathBuf::from("/p/sources/com/x/A.java");
+    let content = "public class A {}";
+    let file = ParsedFile {
+        file: SourceFile::new(path.clone(), 0, 0),
+        content: ParsedContent::Language(parser.parse_file(content, Some(&path)).unwrap()),
+    };
+    let collected = plugin.collect_source(&file, &context).unwrap();
+    let analyzed = plugin.analyze_source(collected, &context).unwrap();
+    let unit = plugin.lower_source(analyzed, &context).unwrap();
+
+    let nodes: Vec<_> = unit
+        .ops
+        .iter()
+        .filter_map(|op| match op {
+            GraphOp::AddNode { data: Some(node) } => Some(node),
+            _ => None,
+        })
+        .collect();
+    assert!(nodes.iter().all(|node| node.kind != NodeKind::Package));
+    assert!(nodes.iter().any(|node| node.id.to_string() == "A"));
+}
@@ -0,0 +1,150 @@
+use naviscope_java::JavaPlugin;
+use naviscope_java::parser::JavaParser;
+use naviscope_plugin::{
+    GraphOp, ParsedContent, ParsedFile, ProjectContext, SourceFile, SourceIndexCap,
+};
+use std::path::{Path, PathBuf};
+
+const LIB_MODULE: &str = r#"
+module lib {
+    requires transitive java.sql;
+    exports lib.api;
+    exports lib.spi to app, tools;
+}
+"#;
+
+/// Collect every file into one project context, then analyze and lower each
+/// of them against it, the way the source executor does.
+fn index(files: &[(&str, &str)]) -> Vec<GraphOp> {
+    let plugin = JavaPlugin::new().unwrap();
+    let parser = JavaParser::new().unwrap();
+    let mut context = ProjectContext::new();
+    let mut collected = Vec::new();
+    for (path, content) in files {
+        let path = PathBuf::from(path);
+        let file = ParsedFile {
+            file: SourceFile::new(path.clone(), 0, 0),
+            content: ParsedContent::Language(parser.parse_file(content, Some(&path)).unwrap()),
+        };
+        let artifact = plugin.collect_source(&file, &context).unwrap();
+        if let (Some(package), Some(dir)) = (artifact.declared_package(), path.parent()) {
+            context
+                .symbol_table
+                .package_dirs
+                .entry(package.to_string())
+                .or_default()
+                .insert(dir.to_path_buf());
+        }
+        if let Some(module) = artifact.module_descriptor() {
+            context
+                .symbol_table
+                .module_descriptors
+                .insert(module.name.clone(), module.clone());
+        }
+        collected.push(artifact);
+    }
+
+    let mut ops = Vec::new();
+    for artifact in collected {
+        let analyzed = plugin.analyze_source(artifact, &context).unwrap();
+        ops.extend(plugin.lower_source(analyzed, &context).unwrap().ops);
+    }
+    ops
+}
+
+fn superclass_edge_inaccessible(ops: &[GraphOp], target: &str) -> bool {
+    ops.iter()
+        .find_map(|op| match op {
+            GraphOp::AddEdge { to_id, edge, .. }
+                if edge.edge_type == naviscope_api::models::graph::EdgeType::InheritsFrom
+                    && to_id.to_string().contains(target) =>
+            {
+                Some(edge.inaccessible)
+            }
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("no InheritsFrom edge to {}", target))
+}
+
+#[test]
+fn module_info_is_parsed_into_descriptor() {
+    let parser = JavaParser::new().unwrap();
+    let result = parser
+        .parse_file(LIB_MODULE, Some(Path::new("/p/lib/module-info.java")))
+        .unwrap();
+    let (descriptor, _) = parser
+        .extract_module_descriptor(
+            result.tree.as_ref().unwrap(),
+            result.source.as_deref().unwrap(),
+            Path::new("/p/lib"),
+        )
+        .unwrap();
+
+    assert_eq!(descriptor.name, "lib");
+    assert_eq!(descriptor.root, PathBuf::from("/p/lib"));
+    assert_eq!(descriptor.requires.len(), 1);
+    assert_eq!(descriptor.requires[0].module, "java.sql");
+    assert!(descriptor.requires[0].transitive);
+    assert!(descriptor.exports_to("lib.api", "anyone"));
+    assert!(descriptor.exports_to("lib.spi", "app"));
+    assert!(!descriptor.exports_to("lib.spi", "other"));
+    assert!(!descriptor.exports_to("lib.internal", "app"));
+}
+
+#[test]
+fn references_to_unexported_packages_are_flagged() {
+    let ops = index(&[
+        ("/p/lib/module-info.java", LIB_MODULE),
+        (
+            "/p/lib/lib/api/Base.java",
+            "package lib.api; public class Base {}",
+        ),
+        (
+            "/p/lib/lib/internal/Hidden.java",
+            "package lib.internal; public class Hidden {}",
+        ),
+        ("/p/app/module-info.java", "module app { requires lib; }"),
+        (
+            "/p/app/app/Open.java",
+            "package app; import lib.api.Base; public class Open extends Base {}",
+        ),
+        (
+            "/p/app/app/Sneaky.java",
+            "package app; import lib.internal.Hidden; public class Sneaky extends Hidden {}",
+        ),
+    ]);
+
+    assert!(!superclass_edge_inaccessible(&ops, "Base"));
+    assert!(superclass_edge_inaccessible(&ops, "Hidden"));
+
+    let module_edges: Vec<_> = ops
+        .iter()
+        .filter_map(|op| match op {
+            GraphOp::AddEdge {
+                from_id,
+                to_id,
+                edge,
+            } => match edge.edge_type {
+                naviscope_api::models::graph::EdgeType::Requires
+                | naviscope_api::models::graph::EdgeType::Exports => {
+                    Some((from_id.to_string(), to_id.to_string()))
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    // java.sql is not part of the project, so only lib is required
+    assert_eq!(
+        module_edges
+            .iter()
+            .filter(|(from, _)| from.contains("app"))
+            .count(),
+        1
+    );
+    assert!(
+        module_edges
+            .iter()
+            .any(|(from, to)| from.contains("lib") && to.contains("api"))
+    );
+}
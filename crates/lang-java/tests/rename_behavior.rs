@@ -0,0 +1,50 @@
+mod common;
+
+use common::{offset_to_point, setup_java_test_graph};
+use naviscope_java::JavaPlugin;
+use naviscope_plugin::{LspSyntaxService, SymbolResolveService};
+
+/// Resolve the local used at `usage` and check renaming it to `new_name`.
+fn check_rename(source: &str, usage: &str, new_name: &str) -> Option<String> {
+    let (index, trees) = setup_java_test_graph(vec![("Test.java", source)]);
+    let plugin = JavaPlugin::new().expect("Failed to create JavaPlugin");
+    let (_, content, tree) = &trees[0];
+
+    let pos = content.find(usage).expect("find usage");
+    let (line, col) = offset_to_point(content, pos);
+    let resolution = plugin
+        .resolve_at(tree, content, line, col, &index)
+        .expect("resolve local");
+    assert!(matches!(
+        resolution,
+        naviscope_api::models::SymbolResolution::Local(..)
+    ));
+
+    plugin.check_local_rename(content, tree, &resolution, new_name)
+}
+
+const SOURCE: &str =
+    "public class Test { int total = 0; void run(int a) { int x = a; x = x + 1; total = x; } }";
+
+#[test]
+fn given_fresh_name_when_rename_local_then_accepted() {
+    assert_eq!(check_rename(SOURCE, "x = x + 1", "y"), None);
+}
+
+#[test]
+fn given_parameter_name_when_rename_local_then_conflict_reported() {
+    let reason = check_rename(SOURCE, "x = x + 1", "a").expect("conflict");
+    assert!(reason.contains("already defined"), "{}", reason);
+}
+
+#[test]
+fn given_field_name_when_rename_local_then_capture_refused() {
+    let reason = check_rename(SOURCE, "x = x + 1", "total").expect("capture");
+    assert!(reason.contains("capture"), "{}", reason);
+}
+
+#[test]
+fn given_keyword_when_rename_local_then_refused() {
+    let reason = check_rename(SOURCE, "x = x + 1", "class").expect("keyword");
+    assert!(reason.contains("keyword"), "{}", reason);
+}
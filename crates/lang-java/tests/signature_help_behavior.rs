@@ -0,0 +1,59 @@
+mod common;
+
+use common::{offset_to_point, setup_java_test_graph};
+use naviscope_java::JavaPlugin;
+use naviscope_plugin::SymbolResolveService;
+
+#[test]
+fn given_cursor_in_second_argument_when_signature_help_then_highlights_it() {
+    let files = vec![
+        (
+            "Service.java",
+            "public class Service { int add(int a, int b) { return a + b; } int add(int a) { return a; } }",
+        ),
+        (
+            "Client.java",
+            "public class Client { void run(Service s) { s.add(1, 2); } }",
+        ),
+    ];
+
+    let (index, trees) = setup_java_test_graph(files);
+    let resolver = JavaPlugin::new().expect("Failed to create JavaPlugin");
+
+    let content = &trees[1].1;
+    let tree = &trees[1].2;
+    let pos = content.find("2);").expect("find second argument");
+    let (line, col) = offset_to_point(content, pos);
+
+    let help = resolver
+        .signature_help_at(tree, content, line, col, &index)
+        .expect("signature help");
+
+    assert_eq!(help.signatures.len(), 2);
+    assert_eq!(help.active_parameter, 1);
+    let active = &help.signatures[help.active_signature];
+    assert_eq!(active.label, "add(a: int, b: int) -> int");
+    assert_eq!(active.parameters, vec!["a: int", "b: int"]);
+}
+
+#[test]
+fn given_cursor_outside_call_when_signature_help_then_returns_none() {
+    let files = vec![(
+        "Client.java",
+        "public class Client { void run() { int x = 1; } }",
+    )];
+
+    let (index, trees) = setup_java_test_graph(files);
+    let resolver = JavaPlugin::new().expect("Failed to create JavaPlugin");
+
+    let content = &trees[0].1;
+    let tree = &trees[0].2;
+    let pos = content.find("x = 1").expect("find variable");
+    let (line, col) = offset_to_point(content, pos);
+
+    assert!(
+        resolver
+            .signature_help_at(tree, content, line, col, &index)
+            .is_none()
+    );
+}
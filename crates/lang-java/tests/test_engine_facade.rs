@@ -1,7 +1,9 @@
 mod common;
 
 use common::{offset_to_point, setup_java_engine};
-use naviscope_api::models::{PositionContext, ReferenceQuery, SymbolQuery, SymbolResolution};
+use naviscope_api::models::{
+    PositionContext, ReferenceKind, ReferenceQuery, SymbolQuery, SymbolResolution, TypeUsageFilter,
+};
 use naviscope_api::semantic::{
     CallHierarchyAnalyzer, ReferenceAnalyzer, SymbolInfoProvider, SymbolNavigator,
 };
@@ -99,6 +101,7 @@ public class App {
         language: naviscope_api::models::Language::JAVA,
         resolution: resolution.clone(),
         include_declaration: false,
+        type_usages: Default::default(),
     };
     let refs = handle.find_references(&query_refs).await.unwrap();
     assert_eq!(
@@ -169,6 +172,7 @@ public class Use {
             language: naviscope_api::models::Language::JAVA,
             resolution,
             include_declaration: false,
+            type_usages: Default::default(),
         })
         .await
         .unwrap();
@@ -237,6 +241,7 @@ public class Use {
             language: naviscope_api::models::Language::JAVA,
             resolution: resolution.clone(),
             include_declaration: false,
+            type_usages: Default::default(),
         })
         .await
         .unwrap();
@@ -247,12 +252,66 @@ public class Use {
             language: naviscope_api::models::Language::JAVA,
             resolution,
             include_declaration: true,
+            type_usages: Default::default(),
         })
         .await
         .unwrap();
     assert_eq!(refs_with_decl.len(), 2);
 }
 
+#[tokio::test]
+async fn test_find_reference_sites_tags_type_usages() {
+    let temp_dir = std::env::temp_dir().join("naviscope_java_ref_type_usage_test");
+    if temp_dir.exists() {
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+    std::fs::create_dir_all(&temp_dir).unwrap();
+
+    let files = vec![
+        (
+            "com/example/A.java",
+            "package com.example; public class A {}",
+        ),
+        (
+            "com/example/Use.java",
+            r#"
+package com.example;
+public class Use {
+    private A field;
+    A make(A input) { return new A(); }
+}
+"#,
+        ),
+    ];
+
+    let handle = setup_java_engine(&temp_dir, files).await;
+
+    let sites = |type_usages| {
+        let handle = handle.clone();
+        async move {
+            handle
+                .find_reference_sites(&ReferenceQuery {
+                    language: naviscope_api::models::Language::JAVA,
+                    resolution: SymbolResolution::Global("com.example.A".to_string()),
+                    include_declaration: false,
+                    type_usages,
+                })
+                .await
+                .unwrap()
+        }
+    };
+
+    let only = sites(TypeUsageFilter::Only).await;
+    assert_eq!(only.len(), 3, "field, return and parameter types");
+    assert!(only.iter().all(|s| s.kind == ReferenceKind::TypeUsage));
+
+    let excluded = sites(TypeUsageFilter::Exclude).await;
+    assert!(excluded.iter().all(|s| s.kind == ReferenceKind::Usage));
+
+    let all = sites(TypeUsageFilter::Include).await;
+    assert_eq!(all.len(), only.len() + excluded.len());
+}
+
 #[tokio::test]
 async fn test_find_references_static_member_hiding() {
     let temp_dir = std::env::temp_dir().join("naviscope_java_ref_static_hiding_test");
@@ -309,6 +368,7 @@ public class Use {
             language: naviscope_api::models::Language::JAVA,
             resolution,
             include_declaration: false,
+            type_usages: Default::default(),
         })
         .await
         .unwrap();
@@ -370,6 +430,7 @@ async fn test_find_references_same_class_overloads_different_arity_via_engine_fa
             language: naviscope_api::models::Language::JAVA,
             resolution,
             include_declaration: true,
+            type_usages: Default::default(),
         })
         .await
         .unwrap();
@@ -447,3 +508,69 @@ async fn test_resolve_method_declaration_name_and_symbol_info_for_overload_hover
         "signature should be suitable for hover at declaration name"
     );
 }
+
+#[tokio::test]
+async fn test_resolve_batch_follows_position_order() {
+    let temp_dir = std::env::temp_dir().join("naviscope_java_resolve_batch_test");
+    if temp_dir.exists() {
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+    std::fs::create_dir_all(&temp_dir).unwrap();
+
+    let files = vec![
+        (
+            "com/example/Base.java",
+            "package com.example; public interface Base { void run(); }",
+        ),
+        (
+            "com/example/App.java",
+            r#"
+package com.example;
+public class App {
+    void start(Base b) {
+        int count = 0;
+        b.run();
+        b.run();
+        count++;
+    }
+}
+"#,
+        ),
+    ];
+
+    let handle = setup_java_engine(&temp_dir, files).await;
+
+    let app_path = temp_dir.join("com/example/App.java");
+    let content = std::fs::read_to_string(&app_path).unwrap();
+    let point = |offset: usize| {
+        let (line, col) = offset_to_point(&content, offset);
+        (line as u32, col as u32)
+    };
+    let first_run = content.find("b.run()").unwrap() + 2;
+    let second_run = content.rfind("b.run()").unwrap() + 2;
+    let positions = [
+        point(first_run),
+        point(content.rfind("count").unwrap()),
+        point(0),
+        point(second_run),
+    ];
+
+    let resolved = handle.resolve_batch(&app_path, &positions).await.unwrap();
+    assert_eq!(resolved.len(), 4);
+
+    let run = resolved[0].as_ref().expect("b.run() should resolve");
+    assert_eq!(run.fqn.as_deref(), Some("com.example.Base#run()"));
+    assert!(run.node.is_some());
+    let count = resolved[1].as_ref().expect("count should resolve");
+    assert!(count.fqn.is_none() && count.local_declaration.is_some());
+    assert!(resolved[2].is_none());
+    assert_eq!(resolved[3].as_ref().unwrap().fqn, run.fqn);
+
+    // Same answers as resolving one position at a time
+    let single = handle
+        .resolve_at(&app_path, positions[0].0, positions[0].1)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(single.fqn, run.fqn);
+}
@@ -0,0 +1,5 @@
+use crate::SqlPlugin;
+use naviscope_plugin::AssetCap;
+
+/// Migrations are project sources; there are no SQL libraries to index
+impl AssetCap for SqlPlugin {}
@@ -0,0 +1,121 @@
+use crate::SqlPlugin;
+use naviscope_api::models::graph::GraphEdge;
+use naviscope_plugin::{
+    BoxError, GlobalParseResult, GraphOp, ParsedContent, ParsedFile, ProjectContext, ResolvedUnit,
+    SourceAnalyzeArtifact, SourceCollectArtifact, SourceIndexCap,
+};
+use std::sync::Arc;
+
+struct SqlCollectArtifact {
+    parse_result: GlobalParseResult,
+    unit: ResolvedUnit,
+}
+
+struct SqlAnalyzeArtifact {
+    unit: ResolvedUnit,
+}
+
+/// Tables are not types of a program: nothing is collected for the symbol
+/// table, and migrations depend on nothing
+impl SourceCollectArtifact for SqlCollectArtifact {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn std::any::Any + Send + Sync> {
+        self
+    }
+
+    fn collected_type_symbols(&self) -> &[String] {
+        &[]
+    }
+
+    fn collected_method_symbols(&self) -> &[String] {
+        &[]
+    }
+
+    fn provided_dependency_symbols(&self) -> &[String] {
+        &[]
+    }
+
+    fn required_dependency_symbols(&self) -> &[String] {
+        &[]
+    }
+}
+
+impl SourceAnalyzeArtifact for SqlAnalyzeArtifact {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn std::any::Any + Send + Sync> {
+        self
+    }
+}
+
+impl SourceIndexCap for SqlPlugin {
+    fn collect_source(
+        &self,
+        file: &ParsedFile,
+        _context: &ProjectContext,
+    ) -> Result<Box<dyn SourceCollectArtifact>, BoxError> {
+        let parse_result = match &file.content {
+            ParsedContent::Language(res) => res.clone(),
+            ParsedContent::Unparsed(src) => crate::parser::parse_file(src, Some(file.path()))?,
+            ParsedContent::Lazy => {
+                let src = std::fs::read_to_string(file.path())
+                    .map_err(|e| format!("Failed to read file {}: {}", file.path().display(), e))?;
+                crate::parser::parse_file(&src, Some(file.path()))?
+            }
+            _ => return Err("Unsupported parsed content in SQL collect_source".into()),
+        };
+
+        let mut unit = ResolvedUnit::new();
+        unit.identifiers = parse_result.output.identifiers.clone();
+        unit.ops.push(GraphOp::UpdateIdentifiers {
+            path: Arc::from(file.path()),
+            identifiers: unit.identifiers.clone(),
+        });
+        Ok(Box::new(SqlCollectArtifact { parse_result, unit }))
+    }
+
+    fn analyze_source(
+        &self,
+        collected: Box<dyn SourceCollectArtifact>,
+        _context: &ProjectContext,
+    ) -> Result<Box<dyn SourceAnalyzeArtifact>, BoxError> {
+        let collected = collected
+            .into_any()
+            .downcast::<SqlCollectArtifact>()
+            .map_err(|_| "SQL analyze_source received incompatible collect artifact")?;
+        let SqlCollectArtifact {
+            parse_result,
+            mut unit,
+        } = *collected;
+
+        for node in &parse_result.output.nodes {
+            unit.add_node(node.clone());
+        }
+        for rel in &parse_result.output.relations {
+            unit.add_edge(
+                rel.source_id.clone(),
+                rel.target_id.clone(),
+                GraphEdge::new(rel.edge_type.clone()),
+            );
+        }
+
+        Ok(Box::new(SqlAnalyzeArtifact { unit }))
+    }
+
+    fn lower_source(
+        &self,
+        analyzed: Box<dyn SourceAnalyzeArtifact>,
+        _context: &ProjectContext,
+    ) -> Result<ResolvedUnit, BoxError> {
+        let analyzed = analyzed
+            .into_any()
+            .downcast::<SqlAnalyzeArtifact>()
+            .map_err(|_| "SQL lower_source received incompatible analyze artifact")?;
+        Ok(analyzed.unit)
+    }
+}
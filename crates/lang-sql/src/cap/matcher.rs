@@ -0,0 +1,12 @@
+use crate::SqlPlugin;
+use naviscope_plugin::FileMatcherCap;
+use std::path::Path;
+
+impl FileMatcherCap for SqlPlugin {
+    fn supports_path(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .map(|ext| ext.eq_ignore_ascii_case("sql"))
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,4 @@
+use crate::SqlPlugin;
+use naviscope_plugin::MetadataCodecCap;
+
+impl MetadataCodecCap for SqlPlugin {}
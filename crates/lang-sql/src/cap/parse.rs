@@ -0,0 +1,13 @@
+use crate::SqlPlugin;
+use naviscope_plugin::LanguageParseCap;
+use std::path::Path;
+
+impl LanguageParseCap for SqlPlugin {
+    fn parse_language_file(
+        &self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<naviscope_plugin::GlobalParseResult, naviscope_plugin::BoxError> {
+        crate::parser::parse_file(source, Some(path))
+    }
+}
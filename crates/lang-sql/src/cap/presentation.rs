@@ -0,0 +1,49 @@
+use crate::SqlPlugin;
+use crate::parser::{KIND_LABEL_COLUMN, KIND_LABEL_TABLE};
+use naviscope_api::models::graph::{DisplayGraphNode, GraphNode, NodeKind};
+use naviscope_api::models::symbol::FqnReader;
+use naviscope_plugin::{
+    NamingConvention, NodePresenter, PresentationCap, StandardNamingConvention,
+};
+use std::sync::Arc;
+
+impl NodePresenter for SqlPlugin {
+    fn render_display_node(&self, node: &GraphNode, fqns: &dyn FqnReader) -> DisplayGraphNode {
+        let id = StandardNamingConvention.render_fqn(node.id, fqns);
+        let table = id.split_once('.').map(|(table, _column)| table.to_string());
+        DisplayGraphNode {
+            symbol_id: StandardNamingConvention.symbol_id("sql", &id),
+            generated: false,
+            name: fqns.resolve_atom(node.name).to_string(),
+            kind: node.kind.clone(),
+            lang: "sql".to_string(),
+            source: node.source.clone(),
+            status: node.status,
+            location: node.location.as_ref().map(|l| l.to_display(fqns)),
+            detail: table.map(|t| format!("*Column of `{}`*", t)),
+            signature: None,
+            modifiers: vec![],
+            children: None,
+            id,
+        }
+    }
+}
+
+impl PresentationCap for SqlPlugin {
+    fn naming_convention(&self) -> Option<Arc<dyn NamingConvention>> {
+        Some(Arc::new(StandardNamingConvention))
+    }
+
+    fn node_presenter(&self) -> Option<Arc<dyn NodePresenter>> {
+        Some(Arc::new(self.clone()))
+    }
+
+    fn symbol_kind(&self, kind: &NodeKind) -> lsp_types::SymbolKind {
+        use lsp_types::SymbolKind;
+        match kind {
+            NodeKind::Custom(label) if label == KIND_LABEL_TABLE => SymbolKind::STRUCT,
+            NodeKind::Custom(label) if label == KIND_LABEL_COLUMN => SymbolKind::FIELD,
+            _ => SymbolKind::VARIABLE,
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use crate::SqlPlugin;
+use naviscope_api::models::Language;
+use naviscope_plugin::{LanguageCaps, SemanticCap};
+use std::sync::Arc;
+
+pub fn sql_caps() -> LanguageCaps {
+    let plugin = Arc::new(SqlPlugin::new());
+    LanguageCaps {
+        language: Language::SQL,
+        matcher: plugin.clone(),
+        parser: plugin.clone(),
+        semantic: plugin.clone() as Arc<dyn SemanticCap>,
+        indexing: plugin.clone(),
+        asset: plugin.clone(),
+        presentation: plugin.clone(),
+        metadata_codec: plugin,
+    }
+}
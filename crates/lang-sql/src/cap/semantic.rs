@@ -0,0 +1,74 @@
+use crate::SqlPlugin;
+use naviscope_api::models::SymbolResolution;
+use naviscope_api::models::graph::DisplayGraphNode;
+use naviscope_api::models::symbol::{FqnId, Range};
+use naviscope_plugin::{
+    CodeGraph, LspSyntaxService, ReferenceCheckService, SymbolQueryService, SymbolResolveService,
+};
+use tree_sitter::Tree;
+
+/// Migrations are indexed without a syntax tree, so there is none to
+/// resolve positions in; tables and columns are reached through the graph.
+impl SymbolResolveService for SqlPlugin {
+    fn resolve_at(
+        &self,
+        _tree: &Tree,
+        _source: &str,
+        _line: usize,
+        _byte_col: usize,
+        _index: &dyn CodeGraph,
+    ) -> Option<SymbolResolution> {
+        None
+    }
+}
+
+impl SymbolQueryService for SqlPlugin {
+    fn find_matches(&self, index: &dyn CodeGraph, res: &SymbolResolution) -> Vec<FqnId> {
+        res.fqn()
+            .map(|fqn| index.resolve_fqn(fqn))
+            .unwrap_or_default()
+    }
+
+    fn resolve_type_of(
+        &self,
+        _index: &dyn CodeGraph,
+        _res: &SymbolResolution,
+    ) -> Vec<SymbolResolution> {
+        Vec::new()
+    }
+
+    fn find_implementations(&self, _index: &dyn CodeGraph, _res: &SymbolResolution) -> Vec<FqnId> {
+        Vec::new()
+    }
+}
+
+impl LspSyntaxService for SqlPlugin {
+    fn parse(&self, _source: &str, _old_tree: Option<&Tree>) -> Option<Tree> {
+        None
+    }
+
+    fn extract_symbols(&self, _tree: &Tree, _source: &str) -> Vec<DisplayGraphNode> {
+        Vec::new()
+    }
+
+    fn find_occurrences(
+        &self,
+        _source: &str,
+        _tree: &Tree,
+        _target: &SymbolResolution,
+        _index: Option<&dyn CodeGraph>,
+    ) -> Vec<Range> {
+        Vec::new()
+    }
+}
+
+impl ReferenceCheckService for SqlPlugin {
+    fn is_reference_to(
+        &self,
+        _graph: &dyn CodeGraph,
+        candidate: &SymbolResolution,
+        target: &SymbolResolution,
+    ) -> bool {
+        candidate == target
+    }
+}
@@ -0,0 +1,14 @@
+pub mod cap;
+pub mod parser;
+
+pub use cap::sql_caps;
+
+/// SQL schema migrations (Flyway scripts, Liquibase formatted SQL changelogs)
+#[derive(Clone, Default)]
+pub struct SqlPlugin;
+
+impl SqlPlugin {
+    pub fn new() -> Self {
+        Self
+    }
+}
@@ -0,0 +1,567 @@
+//! Tables and columns declared by SQL migrations
+//!
+//! Migrations are written in the dialect of their database, often with
+//! procedural code in between, so they are not parsed in full. The scanner
+//! splits a script into statements and reads the `CREATE TABLE` and
+//! `ALTER TABLE ... ADD COLUMN`/`RENAME COLUMN` ones; everything else is
+//! skipped. Names are the unqualified table and column names in lower case,
+//! the same the Java plugin gives the tables of JPA entities. A migration
+//! declares what it adds: columns dropped by a later migration stay in the
+//! graph until the migration adding them is removed.
+
+use naviscope_api::models::graph::{
+    DisplaySymbolLocation, EdgeType, EmptyMetadata, NodeKind, NodeSource, ResolutionStatus,
+};
+use naviscope_api::models::symbol::{NodeId, Range};
+use naviscope_plugin::{BoxError, GlobalParseResult, IndexNode, IndexRelation, ParseOutput};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Arc;
+
+pub const KIND_LABEL_TABLE: &str = "table";
+pub const KIND_LABEL_COLUMN: &str = "column";
+
+/// Words starting a table element or an `ADD` action that is not a column
+const CONSTRAINT_KEYWORDS: &[&str] = &[
+    "CONSTRAINT",
+    "PRIMARY",
+    "FOREIGN",
+    "UNIQUE",
+    "CHECK",
+    "INDEX",
+    "KEY",
+    "EXCLUDE",
+    "LIKE",
+    "FULLTEXT",
+    "SPATIAL",
+    "PERIOD",
+];
+/// Words between `CREATE` and `TABLE`
+const TABLE_OPTIONS: &[&str] = &[
+    "OR",
+    "REPLACE",
+    "GLOBAL",
+    "LOCAL",
+    "TEMPORARY",
+    "TEMP",
+    "UNLOGGED",
+];
+
+/// A table a statement creates or alters, with the columns it adds
+#[derive(Debug, Clone)]
+pub struct TableStatement {
+    pub name: String,
+    pub name_range: Range,
+    /// The whole statement
+    pub range: Range,
+    pub columns: Vec<ColumnDefinition>,
+}
+
+/// A column a statement adds to a table
+#[derive(Debug, Clone)]
+pub struct ColumnDefinition {
+    pub name: String,
+    pub name_range: Range,
+    /// The column definition, or the `ALTER TABLE` action
+    pub range: Range,
+}
+
+/// Parses a migration into table and column nodes, the tables containing
+/// their columns. There is no syntax tree.
+pub fn parse_file(source: &str, file_path: Option<&Path>) -> Result<GlobalParseResult, BoxError> {
+    let location = |range: Range, name_range: Range| {
+        file_path.map(|p| DisplaySymbolLocation {
+            path: p.to_string_lossy().to_string(),
+            range,
+            selection_range: Some(name_range),
+        })
+    };
+    let node = |id: NodeId, name: &str, kind: &str, location| IndexNode {
+        id,
+        name: name.to_string(),
+        kind: NodeKind::Custom(kind.to_string()),
+        lang: "sql".to_string(),
+        source: NodeSource::Project,
+        status: ResolutionStatus::Resolved,
+        location,
+        metadata: Arc::new(EmptyMetadata),
+    };
+
+    let mut nodes: Vec<IndexNode> = Vec::new();
+    let mut relations = Vec::new();
+    let mut identifiers = BTreeSet::new();
+    for table in scan(source) {
+        let id = table_id(&table.name);
+        if !nodes.iter().any(|n| n.id == id) {
+            nodes.push(node(
+                id.clone(),
+                &table.name,
+                KIND_LABEL_TABLE,
+                location(table.range, table.name_range),
+            ));
+        }
+        identifiers.insert(table.name.clone());
+        for column in table.columns {
+            let column_id = column_id(&table.name, &column.name);
+            if nodes.iter().any(|n| n.id == column_id) {
+                continue;
+            }
+            nodes.push(node(
+                column_id.clone(),
+                &column.name,
+                KIND_LABEL_COLUMN,
+                location(column.range, column.name_range),
+            ));
+            relations.push(IndexRelation {
+                source_id: id.clone(),
+                target_id: column_id,
+                edge_type: EdgeType::Contains,
+                range: None,
+            });
+            identifiers.insert(column.name);
+        }
+    }
+
+    Ok(GlobalParseResult {
+        package_name: None,
+        imports: Vec::new(),
+        output: ParseOutput {
+            nodes,
+            relations,
+            identifiers: identifiers.into_iter().collect(),
+        },
+        source: Some(source.to_string()),
+        tree: None,
+    })
+}
+
+pub fn table_id(table: &str) -> NodeId {
+    NodeId::Structured(vec![(
+        NodeKind::Custom(KIND_LABEL_TABLE.to_string()),
+        table.to_string(),
+    )])
+}
+
+pub fn column_id(table: &str, column: &str) -> NodeId {
+    NodeId::Structured(vec![
+        (
+            NodeKind::Custom(KIND_LABEL_TABLE.to_string()),
+            table.to_string(),
+        ),
+        (
+            NodeKind::Custom(KIND_LABEL_COLUMN.to_string()),
+            column.to_string(),
+        ),
+    ])
+}
+
+/// The `CREATE TABLE` and `ALTER TABLE` statements of a script
+pub fn scan(source: &str) -> Vec<TableStatement> {
+    let positions = Positions::new(source);
+    let tokens = tokenize(source);
+    tokens
+        .split(|t| t.kind == TokenKind::Punct(';'))
+        .filter(|statement| !statement.is_empty())
+        .filter_map(|statement| table_statement(statement, &positions))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Word,
+    /// `"name"`, `` `name` `` or `[name]`
+    Quoted,
+    /// A string, or a dollar-quoted body
+    Literal,
+    Punct(char),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'s> {
+    kind: TokenKind,
+    text: &'s str,
+    start: usize,
+    end: usize,
+}
+
+impl Token<'_> {
+    fn is(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    fn is_any(&self, keywords: &[&str]) -> bool {
+        keywords.iter().any(|k| self.is(k))
+    }
+
+    fn is_name(&self) -> bool {
+        match self.kind {
+            TokenKind::Word => true,
+            TokenKind::Quoted => self.text.len() > 2,
+            _ => false,
+        }
+    }
+
+    /// The identifier without its quotes, in lower case
+    fn name(&self) -> String {
+        let name = match self.kind {
+            TokenKind::Quoted => self.text.get(1..self.text.len() - 1).unwrap_or_default(),
+            _ => self.text,
+        };
+        name.to_lowercase()
+    }
+}
+
+/// Line and column of byte offsets
+struct Positions {
+    line_starts: Vec<usize>,
+}
+
+impl Positions {
+    fn new(source: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { line_starts }
+    }
+
+    fn range(&self, start: usize, end: usize) -> Range {
+        let (start_line, start_col) = self.position(start);
+        let (end_line, end_col) = self.position(end);
+        Range {
+            start_line,
+            start_col,
+            end_line,
+            end_col,
+        }
+    }
+
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|&s| s <= offset) - 1;
+        (line, offset - self.line_starts[line])
+    }
+}
+
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let bytes = source.as_bytes();
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || b >= 0x80;
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let kind = match bytes[i] {
+            b if b.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = find(bytes, i, b"\n").unwrap_or(bytes.len());
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = find(bytes, i + 2, b"*/").map_or(bytes.len(), |end| end + 2);
+                continue;
+            }
+            b'\'' => {
+                // `''` escapes a quote and continues the string
+                i += 1;
+                while i < bytes.len() {
+                    if bytes[i] == b'\'' {
+                        if bytes.get(i + 1) == Some(&b'\'') {
+                            i += 2;
+                            continue;
+                        }
+                        break;
+                    }
+                    i += 1;
+                }
+                i = (i + 1).min(bytes.len());
+                TokenKind::Literal
+            }
+            quote @ (b'"' | b'`' | b'[') => {
+                let close = if quote == b'[' { b']' } else { quote };
+                i = find(bytes, i + 1, &[close]).map_or(bytes.len(), |end| end + 1);
+                TokenKind::Quoted
+            }
+            b'$' => {
+                // `$body$ ... $body$`, `$$ ... $$`
+                let tag_end = bytes[i + 1..]
+                    .iter()
+                    .position(|&b| !(b.is_ascii_alphanumeric() || b == b'_'))
+                    .map(|p| i + 1 + p);
+                match tag_end {
+                    Some(end) if bytes[end] == b'$' => {
+                        let tag = &bytes[i..=end];
+                        i = find(bytes, end + 1, tag)
+                            .map_or(bytes.len(), |close| close + tag.len());
+                        TokenKind::Literal
+                    }
+                    _ => {
+                        i += 1;
+                        TokenKind::Punct('$')
+                    }
+                }
+            }
+            b if is_word(b) => {
+                while i < bytes.len() && is_word(bytes[i]) {
+                    i += 1;
+                }
+                TokenKind::Word
+            }
+            b => {
+                i += 1;
+                TokenKind::Punct(b as char)
+            }
+        };
+        tokens.push(Token {
+            kind,
+            text: &source[start..i],
+            start,
+            end: i,
+        });
+    }
+    tokens
+}
+
+fn find(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|p| from + p)
+}
+
+fn table_statement(statement: &[Token], positions: &Positions) -> Option<TableStatement> {
+    let mut rest = statement;
+    let create = if rest.first()?.is("CREATE") {
+        rest = &rest[1..];
+        while rest.first()?.is_any(TABLE_OPTIONS) {
+            rest = &rest[1..];
+        }
+        true
+    } else if rest.first()?.is("ALTER") {
+        rest = &rest[1..];
+        false
+    } else {
+        return None;
+    };
+    if !rest.first()?.is("TABLE") {
+        return None;
+    }
+    rest = skip_words(&rest[1..], &["IF", "NOT", "EXISTS", "ONLY"]);
+    let (name, after_name) = qualified_name(rest)?;
+    rest = after_name;
+
+    let columns = if create {
+        match rest.first() {
+            Some(t) if t.kind == TokenKind::Punct('(') => split_list(&rest[1..])
+                .into_iter()
+                .filter_map(|element| column_definition(element, positions))
+                .collect(),
+            // `CREATE TABLE ... AS SELECT`
+            _ => Vec::new(),
+        }
+    } else {
+        split_list(rest)
+            .into_iter()
+            .filter_map(|action| added_column(action, positions))
+            .collect()
+    };
+
+    let last = statement.last()?;
+    Some(TableStatement {
+        name: name.name(),
+        name_range: positions.range(name.start, name.end),
+        range: positions.range(statement[0].start, last.end),
+        columns,
+    })
+}
+
+/// The last part of `schema.table`, and the tokens after it
+fn qualified_name<'t, 's>(tokens: &'t [Token<'s>]) -> Option<(Token<'s>, &'t [Token<'s>])> {
+    let mut name = *tokens.first().filter(|t| t.is_name())?;
+    let mut rest = &tokens[1..];
+    while let [dot, part, after @ ..] = rest {
+        if dot.kind != TokenKind::Punct('.') || !part.is_name() {
+            break;
+        }
+        name = *part;
+        rest = after;
+    }
+    Some((name, rest))
+}
+
+fn skip_words<'t, 's>(mut tokens: &'t [Token<'s>], words: &[&str]) -> &'t [Token<'s>] {
+    while tokens.first().is_some_and(|t| t.is_any(words)) {
+        tokens = &tokens[1..];
+    }
+    tokens
+}
+
+/// Tokens separated by the commas outside parentheses, up to the
+/// parenthesis closing the list if there is one
+fn split_list<'t, 's>(tokens: &'t [Token<'s>]) -> Vec<&'t [Token<'s>]> {
+    let mut parts = Vec::new();
+    let mut level = 0;
+    let mut start = 0;
+    let mut end = tokens.len();
+    for (i, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::Punct('(') => level += 1,
+            TokenKind::Punct(')') if level == 0 => {
+                end = i;
+                break;
+            }
+            TokenKind::Punct(')') => level -= 1,
+            TokenKind::Punct(',') if level == 0 => {
+                parts.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&tokens[start..end]);
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+/// The column an element of `CREATE TABLE (...)` defines
+fn column_definition(element: &[Token], positions: &Positions) -> Option<ColumnDefinition> {
+    let name = element.first().filter(|t| t.is_name())?;
+    if name.kind == TokenKind::Word && name.is_any(CONSTRAINT_KEYWORDS) {
+        return None;
+    }
+    let last = element.last()?;
+    Some(ColumnDefinition {
+        name: name.name(),
+        name_range: positions.range(name.start, name.end),
+        range: positions.range(name.start, last.end),
+    })
+}
+
+/// The column an `ALTER TABLE` action adds: `ADD [COLUMN] name ...`, or the
+/// new name of `RENAME COLUMN old TO name`
+fn added_column(action: &[Token], positions: &Positions) -> Option<ColumnDefinition> {
+    let first = action.first()?;
+    let name = if first.is("ADD") {
+        let rest = skip_words(&action[1..], &["COLUMN", "IF", "NOT", "EXISTS"]);
+        let name = rest.first().filter(|t| t.is_name())?;
+        if name.kind == TokenKind::Word && name.is_any(CONSTRAINT_KEYWORDS) {
+            return None;
+        }
+        name
+    } else if first.is("RENAME") {
+        let to = action.iter().position(|t| t.is("TO"))?;
+        if !action[1..to].iter().any(|t| t.is("COLUMN")) && to != 2 {
+            return None;
+        }
+        action.get(to + 1).filter(|t| t.is_name())?
+    } else {
+        return None;
+    };
+    let last = action.last()?;
+    Some(ColumnDefinition {
+        name: name.name(),
+        name_range: positions.range(name.start, name.end),
+        range: positions.range(first.start, last.end),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tables(source: &str) -> Vec<(String, Vec<String>)> {
+        scan(source)
+            .into_iter()
+            .map(|t| (t.name, t.columns.into_iter().map(|c| c.name).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_create_table() {
+        let source = r#"
+-- V1__create_orders.sql
+CREATE TABLE IF NOT EXISTS public."Orders" (
+    id BIGSERIAL PRIMARY KEY,
+    total NUMERIC(10, 2) NOT NULL DEFAULT 0,
+    /* who placed it */
+    customer_id BIGINT REFERENCES customers (id),
+    note TEXT DEFAULT 'a; b',
+    CONSTRAINT total_positive CHECK (total >= 0),
+    PRIMARY KEY (id)
+);
+CREATE INDEX orders_customer ON orders (customer_id);
+"#;
+        assert_eq!(
+            tables(source),
+            [(
+                "orders".to_string(),
+                vec![
+                    "id".to_string(),
+                    "total".to_string(),
+                    "customer_id".to_string(),
+                    "note".to_string()
+                ]
+            )]
+        );
+
+        let table = &scan(source)[0];
+        assert_eq!(table.name_range.start_line, 2);
+        assert_eq!(table.columns[1].name_range.start_line, 4);
+        assert_eq!(table.columns[1].name_range.start_col, 4);
+    }
+
+    #[test]
+    fn test_alter_table() {
+        let source = r#"
+ALTER TABLE orders ADD COLUMN shipped_at TIMESTAMP, ADD CONSTRAINT fk FOREIGN KEY (x) REFERENCES y (id);
+ALTER TABLE ONLY `orders` ADD status VARCHAR(16);
+ALTER TABLE orders RENAME COLUMN note TO remark;
+ALTER TABLE orders RENAME TO purchases;
+"#;
+        assert_eq!(
+            tables(source),
+            [
+                ("orders".to_string(), vec!["shipped_at".to_string()]),
+                ("orders".to_string(), vec!["status".to_string()]),
+                ("orders".to_string(), vec!["remark".to_string()]),
+                ("orders".to_string(), Vec::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_procedural_code_is_skipped() {
+        let source = r#"
+CREATE FUNCTION touch() RETURNS trigger AS $$
+BEGIN
+    CREATE TABLE not_a_table (id INT);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+CREATE TABLE audit AS SELECT * FROM orders;
+"#;
+        assert_eq!(tables(source), [("audit".to_string(), Vec::new())]);
+    }
+
+    #[test]
+    fn test_parse_file_nodes() {
+        let source = "CREATE TABLE orders (id INT, total INT);\nALTER TABLE orders ADD total INT;";
+        let result = parse_file(source, Some(Path::new("V1__orders.sql"))).unwrap();
+        let names: Vec<String> = result
+            .output
+            .nodes
+            .iter()
+            .map(|n| n.id.to_string())
+            .collect();
+        assert_eq!(names, ["orders", "orders.id", "orders.total"]);
+        assert_eq!(result.output.relations.len(), 2);
+        assert!(
+            result
+                .output
+                .relations
+                .iter()
+                .all(|r| r.edge_type == EdgeType::Contains && r.source_id == table_id("orders"))
+        );
+    }
+}
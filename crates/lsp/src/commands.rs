@@ -0,0 +1,62 @@
+use crate::LspServer;
+use naviscope_api::models::PositionContext;
+use serde_json::Value;
+use tower_lsp::jsonrpc::{Error, Result};
+use tower_lsp::lsp_types::*;
+
+pub const EXPLAIN_RESOLUTION: &str = "naviscope.explainResolution";
+
+/// Commands advertised through `executeCommandProvider`.
+pub fn all() -> Vec<String> {
+    vec![EXPLAIN_RESOLUTION.to_string()]
+}
+
+pub async fn execute(server: &LspServer, params: ExecuteCommandParams) -> Result<Option<Value>> {
+    match params.command.as_str() {
+        EXPLAIN_RESOLUTION => explain_resolution(server, params.arguments).await,
+        other => Err(Error::invalid_params(format!("Unknown command: {}", other))),
+    }
+}
+
+/// Arguments: a single `TextDocumentPositionParams` object.
+async fn explain_resolution(server: &LspServer, arguments: Vec<Value>) -> Result<Option<Value>> {
+    let params: TextDocumentPositionParams = arguments
+        .into_iter()
+        .next()
+        .and_then(|arg| serde_json::from_value(arg).ok())
+        .ok_or_else(|| {
+            Error::invalid_params(format!(
+                "{} expects {{ textDocument, position }}",
+                EXPLAIN_RESOLUTION
+            ))
+        })?;
+    let uri = params.text_document.uri;
+    let position = params.position;
+
+    let engine = match server.engine.current().await {
+        Some(e) => e,
+        None => return Ok(None),
+    };
+
+    let content = server.documents.get(&uri).map(|d| d.content.clone());
+
+    let ctx = PositionContext {
+        uri: uri.to_string(),
+        line: position.line,
+        char: position.character,
+        content,
+    };
+
+    let trace = match engine.explain_resolution(&ctx).await {
+        Ok(trace) => trace,
+        Err(e) => {
+            tracing::warn!("explain_resolution failed for {}: {}", uri, e);
+            return Ok(None);
+        }
+    };
+
+    trace
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|_| Error::internal_error())
+}
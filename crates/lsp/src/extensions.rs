@@ -0,0 +1,267 @@
+//! Custom requests for the editor extension
+//!
+//! The companion VS Code extension renders a graph panel next to the editor.
+//! It asks for the node under the cursor with `naviscope/nodeAt`, then walks
+//! the graph around it with `naviscope/graphNeighbors`. Both are advertised
+//! under `experimental.naviscope` in the server capabilities.
+
+use crate::LspServer;
+use naviscope_api::models::graph::{DisplayGraphNode, GraphQuery, QueryResult, QueryResultEdge};
+use naviscope_api::models::{EdgeType, PositionContext};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tower_lsp::jsonrpc::{Error, Result};
+use tower_lsp::lsp_types::{Position, TextDocumentIdentifier};
+
+pub const NODE_AT: &str = "naviscope/nodeAt";
+pub const GRAPH_NEIGHBORS: &str = "naviscope/graphNeighbors";
+
+/// Neighbors returned by `graphNeighbors` unless the request sets a limit
+pub const DEFAULT_NEIGHBOR_LIMIT: usize = 200;
+
+/// Requests advertised through `experimental.naviscope.requests`.
+pub fn all() -> Vec<&'static str> {
+    vec![NODE_AT, GRAPH_NEIGHBORS]
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeAtParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: Position,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeAtResult {
+    /// Symbol the position resolves to
+    pub fqn: String,
+    /// Its graph node; `None` if it is not indexed (yet)
+    pub node: Option<DisplayGraphNode>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NeighborDirection {
+    /// What the node depends on
+    Outgoing,
+    /// What depends on the node
+    Incoming,
+    #[default]
+    Both,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphNeighborsParams {
+    pub fqn: String,
+    #[serde(default)]
+    pub direction: NeighborDirection,
+    /// Relationship types to follow; all if empty
+    #[serde(default)]
+    pub edge_types: Vec<EdgeType>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphNeighborsResult {
+    /// The requested node; `None` if it is not indexed
+    pub center: Option<DisplayGraphNode>,
+    pub nodes: Vec<DisplayGraphNode>,
+    pub edges: Vec<QueryResultEdge>,
+    /// Whether neighbors were dropped to stay within the limit
+    pub truncated: bool,
+}
+
+pub async fn node_at(server: &LspServer, params: NodeAtParams) -> Result<Option<NodeAtResult>> {
+    let uri = params.text_document.uri;
+    let engine = match server.engine.current().await {
+        Some(e) => e,
+        None => return Ok(None),
+    };
+
+    let content = server.documents.get(&uri).map(|d| d.content.clone());
+    let ctx = PositionContext {
+        uri: uri.to_string(),
+        line: params.position.line,
+        char: params.position.character,
+        content,
+    };
+
+    let resolution = match engine.resolve_symbol_at(&ctx).await {
+        Ok(Some(res)) => res,
+        Ok(None) => return Ok(None),
+        Err(e) => {
+            tracing::warn!("nodeAt resolve_symbol_at failed for {}: {}", uri, e);
+            return Ok(None);
+        }
+    };
+    let Some(fqn) = resolution.fqn().map(str::to_string) else {
+        return Ok(None);
+    };
+    let node = engine.get_symbol_info(&fqn).await.ok().flatten();
+    Ok(Some(NodeAtResult { fqn, node }))
+}
+
+pub async fn graph_neighbors(
+    server: &LspServer,
+    params: GraphNeighborsParams,
+) -> Result<GraphNeighborsResult> {
+    let engine = match server.engine.current().await {
+        Some(e) => e,
+        None => return Ok(GraphNeighborsResult::default()),
+    };
+
+    let center = engine
+        .get_symbol_info(&params.fqn)
+        .await
+        .map_err(|e| Error::invalid_params(e.to_string()))?;
+    if center.is_none() {
+        return Ok(GraphNeighborsResult::default());
+    }
+
+    let revs: &[bool] = match params.direction {
+        NeighborDirection::Outgoing => &[false],
+        NeighborDirection::Incoming => &[true],
+        NeighborDirection::Both => &[false, true],
+    };
+    let mut results = Vec::with_capacity(revs.len());
+    for &rev in revs {
+        let query = GraphQuery::Deps {
+            fqn: params.fqn.clone(),
+            rev,
+            edge_types: params.edge_types.clone(),
+            sources: vec![],
+            statuses: vec![],
+            group_by: None,
+            count: false,
+        };
+        let result = engine.query(&query).await.map_err(|e| {
+            tracing::warn!("graphNeighbors failed for {}: {}", params.fqn, e);
+            Error::internal_error()
+        })?;
+        results.push(result);
+    }
+
+    let limit = params.limit.unwrap_or(DEFAULT_NEIGHBOR_LIMIT);
+    Ok(merge_neighbors(center, results, limit))
+}
+
+/// Combine the results of both directions, keeping each neighbor once and at
+/// most `limit` of them, with only the edges between kept nodes.
+fn merge_neighbors(
+    center: Option<DisplayGraphNode>,
+    results: Vec<QueryResult>,
+    limit: usize,
+) -> GraphNeighborsResult {
+    let center_id = center.as_ref().map(|c| c.id.clone());
+    let mut kept: HashSet<String> = center_id.iter().cloned().collect();
+    let mut merged = GraphNeighborsResult {
+        center,
+        ..Default::default()
+    };
+    let mut edges = Vec::new();
+
+    for result in results {
+        for node in result.nodes {
+            if kept.contains(&node.id) {
+                continue;
+            }
+            if merged.nodes.len() == limit {
+                merged.truncated = true;
+                continue;
+            }
+            kept.insert(node.id.clone());
+            merged.nodes.push(node);
+        }
+        edges.extend(result.edges);
+    }
+
+    let mut seen = HashSet::new();
+    merged.edges = edges
+        .into_iter()
+        .filter(|e| kept.contains(&*e.from) && kept.contains(&*e.to))
+        .filter(|e| seen.insert((e.from.clone(), e.to.clone(), e.data.clone())))
+        .collect();
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use naviscope_api::models::graph::{GraphEdge, NodeKind, NodeSource, ResolutionStatus};
+
+    fn node(id: &str) -> DisplayGraphNode {
+        DisplayGraphNode {
+            id: id.to_string(),
+            symbol_id: String::new(),
+            generated: false,
+            name: id.to_string(),
+            kind: NodeKind::Class,
+            lang: "java".to_string(),
+            source: NodeSource::Project,
+            status: ResolutionStatus::Resolved,
+            location: None,
+            detail: None,
+            signature: None,
+            modifiers: vec![],
+            children: None,
+        }
+    }
+
+    fn edge(from: &str, to: &str) -> QueryResultEdge {
+        QueryResultEdge {
+            from: from.into(),
+            to: to.into(),
+            data: GraphEdge::new(EdgeType::TypedAs),
+        }
+    }
+
+    fn result(nodes: &[&str], edges: &[(&str, &str)]) -> QueryResult {
+        QueryResult {
+            nodes: nodes.iter().map(|id| node(id)).collect(),
+            edges: edges.iter().map(|(f, t)| edge(f, t)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn params_default_to_both_directions() {
+        let params: GraphNeighborsParams =
+            serde_json::from_value(serde_json::json!({ "fqn": "a.A" })).unwrap();
+        assert_eq!(params.direction, NeighborDirection::Both);
+        assert!(params.edge_types.is_empty() && params.limit.is_none());
+
+        let params: GraphNeighborsParams = serde_json::from_value(
+            serde_json::json!({ "fqn": "a.A", "direction": "incoming", "edgeTypes": ["InheritsFrom"] }),
+        )
+        .unwrap();
+        assert_eq!(params.direction, NeighborDirection::Incoming);
+        assert_eq!(params.edge_types, [EdgeType::InheritsFrom]);
+    }
+
+    #[test]
+    fn merge_keeps_each_neighbor_once_within_the_limit() {
+        let outgoing = result(&["a.B", "a.C"], &[("a.A", "a.B"), ("a.A", "a.C")]);
+        let incoming = result(&["a.B", "a.D"], &[("a.B", "a.A"), ("a.D", "a.A")]);
+
+        let merged = merge_neighbors(
+            Some(node("a.A")),
+            vec![outgoing.clone(), incoming.clone()],
+            10,
+        );
+        let ids: Vec<_> = merged.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["a.B", "a.C", "a.D"]);
+        assert_eq!(merged.edges.len(), 4);
+        assert!(!merged.truncated);
+
+        // Edges to dropped neighbors are dropped with them
+        let merged = merge_neighbors(Some(node("a.A")), vec![outgoing, incoming], 2);
+        assert!(merged.truncated);
+        assert_eq!(merged.nodes.len(), 2);
+        assert!(merged.edges.iter().all(|e| &*e.from != "a.D"));
+        assert_eq!(merged.edges.len(), 3);
+    }
+}
@@ -0,0 +1,43 @@
+use crate::LspServer;
+use naviscope_api::models::FoldingKind;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::*;
+
+pub async fn folding_range(
+    server: &LspServer,
+    params: FoldingRangeParams,
+) -> Result<Option<Vec<FoldingRange>>> {
+    let uri = params.text_document.uri;
+    let content = server.documents.get(&uri).map(|d| d.content.clone());
+
+    let engine = match server.engine.current().await {
+        Some(e) => e,
+        None => return Ok(None),
+    };
+
+    let ranges = match engine.get_folding_ranges(uri.as_str(), content).await {
+        Ok(r) => r,
+        Err(e) => {
+            tracing::warn!("get_folding_ranges failed for {}: {}", uri, e);
+            return Ok(None);
+        }
+    };
+
+    Ok(Some(
+        ranges
+            .into_iter()
+            .map(|r| FoldingRange {
+                start_line: r.start_line as u32,
+                start_character: None,
+                end_line: r.end_line as u32,
+                end_character: None,
+                kind: Some(match r.kind {
+                    FoldingKind::Comment => FoldingRangeKind::Comment,
+                    FoldingKind::Imports => FoldingRangeKind::Imports,
+                    FoldingKind::Region => FoldingRangeKind::Region,
+                }),
+                collapsed_text: None,
+            })
+            .collect(),
+    ))
+}
@@ -40,8 +40,7 @@ pub async fn definition(
         content,
     };
 
-    let engine_lock = server.engine.read().await;
-    let engine = match engine_lock.as_ref() {
+    let engine = match server.engine.current().await {
         Some(e) => e,
         None => return Ok(None),
     };
@@ -117,8 +116,7 @@ pub async fn type_definition(
         content,
     };
 
-    let engine_lock = server.engine.read().await;
-    let engine = match engine_lock.as_ref() {
+    let engine = match server.engine.current().await {
         Some(e) => e,
         None => return Ok(None),
     };
@@ -174,8 +172,7 @@ pub async fn references(
         content: content.clone(), // Clone for ctx
     };
 
-    let engine_lock = server.engine.read().await;
-    let engine = match engine_lock.as_ref() {
+    let engine = match server.engine.current().await {
         Some(e) => e,
         None => return Ok(None),
     };
@@ -212,6 +209,7 @@ pub async fn references(
         resolution,
         language,
         include_declaration: params.context.include_declaration,
+        type_usages: Default::default(),
     };
 
     let locations = match engine.find_references(&query).await {
@@ -252,8 +250,7 @@ pub async fn implementation(
         content,
     };
 
-    let engine_lock = server.engine.read().await;
-    let engine = match &*engine_lock {
+    let engine = match server.engine.current().await {
         Some(n) => n,
         None => return Ok(None),
     };
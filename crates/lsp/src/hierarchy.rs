@@ -56,9 +56,8 @@ pub async fn prepare_call_hierarchy(
     let uri = params.text_document_position_params.text_document.uri;
     let position = params.text_document_position_params.position;
 
-    let engine_lock = server.engine.read().await;
-    let engine = match engine_lock.as_ref() {
-        Some(e) => e.clone(),
+    let engine = match server.engine.current().await {
+        Some(e) => e,
         None => return Ok(None),
     };
 
@@ -107,9 +106,8 @@ pub async fn incoming_calls(
         return Ok(None);
     }
 
-    let engine_lock = server.engine.read().await;
-    let engine = match engine_lock.as_ref() {
-        Some(e) => e.clone(),
+    let engine = match server.engine.current().await {
+        Some(e) => e,
         None => return Ok(None),
     };
 
@@ -178,9 +176,8 @@ pub async fn outgoing_calls(
         return Ok(None);
     }
 
-    let engine_lock = server.engine.read().await;
-    let engine = match engine_lock.as_ref() {
-        Some(e) => e.clone(),
+    let engine = match server.engine.current().await {
+        Some(e) => e,
         None => return Ok(None),
     };
 
@@ -261,6 +258,8 @@ mod tests {
     fn build_call_hierarchy_item_rejects_missing_location() {
         let info = DisplayGraphNode {
             id: "com.example.A#m()".to_string(),
+            symbol_id: String::new(),
+            generated: false,
             name: "m".to_string(),
             kind: NodeKind::Method,
             lang: "java".to_string(),
@@ -279,6 +278,8 @@ mod tests {
     fn build_call_hierarchy_item_accepts_location() {
         let info = DisplayGraphNode {
             id: "com.example.A#m()".to_string(),
+            symbol_id: String::new(),
+            generated: false,
             name: "m".to_string(),
             kind: NodeKind::Method,
             lang: "java".to_string(),
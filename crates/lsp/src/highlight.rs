@@ -10,9 +10,8 @@ pub async fn highlight(
     let uri = params.text_document_position_params.text_document.uri;
     let position = params.text_document_position_params.position;
 
-    let engine_lock = server.engine.read().await;
-    let engine = match engine_lock.as_ref() {
-        Some(e) => e.clone(),
+    let engine = match server.engine.current().await {
+        Some(e) => e,
         None => return Ok(None),
     };
 
@@ -1,5 +1,8 @@
 use crate::LspServer;
-use naviscope_api::models::{DisplayGraphNode, PositionContext, SymbolResolution};
+use naviscope_api::models::{
+    DetailLimits, DisplayGraphNode, GraphQuery, MemberSummary, NodeKind, NodeNote, PositionContext,
+    SymbolResolution,
+};
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 
@@ -33,9 +36,8 @@ pub async fn hover(server: &LspServer, params: HoverParams) -> Result<Option<Hov
     let position = params.text_document_position_params.position;
     let content = server.documents.get(&uri).map(|d| d.content.clone());
 
-    let engine_lock = server.engine.read().await;
-    let engine = match engine_lock.as_ref() {
-        Some(e) => e.clone(),
+    let engine = match server.engine.current().await {
+        Some(e) => e,
         None => return Ok(None),
     };
 
@@ -62,7 +64,24 @@ pub async fn hover(server: &LspServer, params: HoverParams) -> Result<Option<Hov
         }
         SymbolResolution::Local(_, _) => None,
     };
-    let hover_text = build_hover_text(&resolution, info.as_ref());
+    let limits = match engine.host_settings().await {
+        Ok(settings) => settings.lsp_detail.unwrap_or(DetailLimits::LSP),
+        Err(_) => DetailLimits::LSP,
+    };
+    let mut hover_text = build_hover_text(&resolution, info.as_ref(), &limits);
+    if let Some(info) = &info {
+        if is_type_kind(&info.kind) {
+            let query = GraphQuery::Cat {
+                fqn: info.id.clone(),
+                limits: Some(limits),
+            };
+            if let Some(members) = engine.query(&query).await.ok().and_then(|r| r.members) {
+                append_members(&mut hover_text, &members);
+            }
+        }
+        let notes = engine.notes(&info.id).await.unwrap_or_default();
+        append_notes(&mut hover_text, &notes);
+    }
 
     if !hover_text.is_empty() {
         return Ok(Some(Hover {
@@ -74,7 +93,11 @@ pub async fn hover(server: &LspServer, params: HoverParams) -> Result<Option<Hov
     Ok(None)
 }
 
-fn build_hover_text(resolution: &SymbolResolution, info: Option<&DisplayGraphNode>) -> String {
+fn build_hover_text(
+    resolution: &SymbolResolution,
+    info: Option<&DisplayGraphNode>,
+    limits: &DetailLimits,
+) -> String {
     match resolution {
         SymbolResolution::Local(range, type_name) => {
             let mut hover_text = String::new();
@@ -92,8 +115,10 @@ fn build_hover_text(resolution: &SymbolResolution, info: Option<&DisplayGraphNod
             hover_text.push_str("*Scope: local*");
             hover_text
         }
-        SymbolResolution::Precise(fqn, intent) => build_symbol_hover(fqn, Some(*intent), info),
-        SymbolResolution::Global(fqn) => build_symbol_hover(fqn, None, info),
+        SymbolResolution::Precise(fqn, intent) => {
+            build_symbol_hover(fqn, Some(*intent), info, limits)
+        }
+        SymbolResolution::Global(fqn) => build_symbol_hover(fqn, None, info, limits),
     }
 }
 
@@ -101,6 +126,7 @@ fn build_symbol_hover(
     fqn: &str,
     intent: Option<naviscope_api::models::SymbolIntent>,
     info: Option<&DisplayGraphNode>,
+    limits: &DetailLimits,
 ) -> String {
     let Some(info) = info else {
         return format_fallback_hover(fqn, intent);
@@ -114,7 +140,13 @@ fn build_symbol_hover(
 
     if let Some(sig) = &info.signature {
         let lang_tag = &info.lang;
-        hover_text.push_str(&format!("```{}\n{}\n```\n", lang_tag, sig));
+        match limits.abridge(sig) {
+            Some(short) => hover_text.push_str(&format!(
+                "```{}\n{}\n```\n*Signature shortened, see the declaration for the full text*\n\n",
+                lang_tag, short
+            )),
+            None => hover_text.push_str(&format!("```{}\n{}\n```\n", lang_tag, sig)),
+        }
     } else {
         hover_text.push_str(&format!("**{}** *{}*\n\n", info.name, info.kind.to_string()));
     }
@@ -131,6 +163,9 @@ fn build_symbol_hover(
         naviscope_api::models::NodeSource::Builtin => {
             hover_text.push_str("*Source: builtin*\n\n");
         }
+        naviscope_api::models::NodeSource::Project if info.generated => {
+            hover_text.push_str("*Source: generated*\n\n");
+        }
         naviscope_api::models::NodeSource::Project => {}
     }
 
@@ -138,6 +173,49 @@ fn build_symbol_hover(
     hover_text
 }
 
+fn is_type_kind(kind: &NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Class | NodeKind::Interface | NodeKind::Enum | NodeKind::Annotation
+    )
+}
+
+/// Members of a type, as many as the limits allow, then their count by kind
+fn append_members(hover_text: &mut String, members: &MemberSummary) {
+    if members.total == 0 {
+        return;
+    }
+    let kinds: Vec<String> = members
+        .kinds
+        .iter()
+        .map(|k| format!("{}: {}", k.key, k.count))
+        .collect();
+    hover_text.push_str(&format!("\n\n---\n\n**Members** ({})\n", kinds.join(", ")));
+    for member in &members.listed {
+        let text = member.signature.as_deref().unwrap_or(&member.name);
+        hover_text.push_str(&format!("\n- `{}`", text));
+    }
+    let hidden = members.total - members.listed.len();
+    if hidden > 0 {
+        hover_text.push_str(&format!("\n\n*{} more, see the document outline*", hidden));
+    }
+}
+
+/// Notes left on the node with `annotate`, one bullet each
+fn append_notes(hover_text: &mut String, notes: &[NodeNote]) {
+    if notes.is_empty() {
+        return;
+    }
+    hover_text.push_str("\n\n---\n\n**Notes**\n");
+    for note in notes {
+        hover_text.push_str("\n- ");
+        for tag in &note.tags {
+            hover_text.push_str(&format!("`#{}` ", tag));
+        }
+        hover_text.push_str(&note.text);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -157,6 +235,7 @@ mod tests {
                 Some("List<String>".into()),
             ),
             None,
+            &DetailLimits::LSP,
         );
         assert!(text.contains("Local variable"));
         assert!(text.contains("List<String>"));
@@ -167,6 +246,8 @@ mod tests {
     fn hover_member_uses_signature_and_owner() {
         let info = DisplayGraphNode {
             id: "com.example.Service#getContext".into(),
+            symbol_id: String::new(),
+            generated: false,
             name: "getContext".into(),
             kind: NodeKind::Method,
             lang: "java".into(),
@@ -185,6 +266,7 @@ mod tests {
                 naviscope_api::models::SymbolIntent::Method,
             ),
             Some(&info),
+            &DetailLimits::LSP,
         );
         assert!(text.contains("SessionContext getContext()"));
         assert!(text.contains("Declared in `com.example.Service`"));
@@ -195,6 +277,7 @@ mod tests {
         let text = build_hover_text(
             &SymbolResolution::Global("com.example.Missing#call".into()),
             None,
+            &DetailLimits::LSP,
         );
         assert!(text.contains("Metadata unavailable"));
         assert!(text.contains("com.example.Missing"));
@@ -204,6 +287,8 @@ mod tests {
     fn hover_external_marks_source() {
         let info = DisplayGraphNode {
             id: "java.util.List#size".into(),
+            symbol_id: String::new(),
+            generated: false,
             name: "size".into(),
             kind: NodeKind::Method,
             lang: "java".into(),
@@ -219,7 +304,73 @@ mod tests {
         let text = build_hover_text(
             &SymbolResolution::Global("java.util.List#size".into()),
             Some(&info),
+            &DetailLimits::LSP,
         );
         assert!(text.contains("Source: external"));
     }
+
+    #[test]
+    fn hover_shortens_long_signatures_and_members() {
+        let mut info = DisplayGraphNode {
+            id: "com.example.Cache#load".into(),
+            symbol_id: String::new(),
+            generated: true,
+            name: "load".into(),
+            kind: NodeKind::Method,
+            lang: "java".into(),
+            source: NodeSource::Project,
+            status: ResolutionStatus::Resolved,
+            location: None,
+            detail: None,
+            signature: Some(
+                "load(keys: Map<String, List<Entry<Key, Value>>>) -> Set<Value>".into(),
+            ),
+            modifiers: vec![],
+            children: None,
+        };
+        let limits = DetailLimits {
+            signature_chars: 40,
+            members: 1,
+        };
+        let text = build_hover_text(
+            &SymbolResolution::Global(info.id.clone()),
+            Some(&info),
+            &limits,
+        );
+        assert!(text.contains("load(keys: Map<…>) -> Set<…>"));
+        assert!(text.contains("Signature shortened"));
+
+        info.signature = Some("load(keys: Map<…>) -> Set<…>".into());
+        let mut text = String::new();
+        append_members(
+            &mut text,
+            &MemberSummary {
+                total: 30,
+                kinds: vec![naviscope_api::models::QueryGroup {
+                    key: "method".into(),
+                    count: 30,
+                }],
+                listed: vec![info],
+            },
+        );
+        assert!(text.contains("**Members** (method: 30)"));
+        assert!(text.contains("- `load(keys: Map<…>) -> Set<…>`"));
+        assert!(text.ends_with("*29 more, see the document outline*"));
+    }
+
+    #[test]
+    fn hover_lists_notes() {
+        let mut text = "**Service** *class*".to_string();
+        append_notes(&mut text, &[]);
+        assert_eq!(text, "**Service** *class*");
+
+        let note = NodeNote {
+            id: 1,
+            text: "Not thread-safe".into(),
+            tags: vec!["concurrency".into()],
+            created_at: 0,
+        };
+        append_notes(&mut text, &[note]);
+        assert!(text.ends_with("**Notes**\n\n- `#concurrency` Not thread-safe"));
+    }
 }
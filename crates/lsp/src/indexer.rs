@@ -1,47 +1,267 @@
+use naviscope_api::ApiError;
 use naviscope_api::NaviscopeEngine;
-use std::path::PathBuf;
+use naviscope_api::lifecycle::{EngineWatchHandle, IndexControl, IndexPhase, IndexProgress};
+use naviscope_mcp::StatusTracker;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use tower_lsp::Client;
-use tower_lsp::lsp_types::MessageType;
+use tower_lsp::lsp_types::notification::Progress;
+use tower_lsp::lsp_types::request::WorkDoneProgressCreate;
+use tower_lsp::lsp_types::{
+    MessageType, NumberOrString, ProgressParams, ProgressParamsValue, WorkDoneProgress,
+    WorkDoneProgressBegin, WorkDoneProgressCreateParams, WorkDoneProgressEnd,
+    WorkDoneProgressReport,
+};
 
+static NEXT_PROGRESS_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Background indexing task bound to a single engine instance.
+pub struct IndexerHandle {
+    pub root: PathBuf,
+    progress_token: NumberOrString,
+    token: CancellationToken,
+    task: JoinHandle<Option<Arc<dyn EngineWatchHandle>>>,
+}
+
+impl IndexerHandle {
+    /// Cancel indexing, wait for the task to drain and stop its file watcher.
+    pub async fn shutdown(self) {
+        self.token.cancel();
+        match self.task.await {
+            Ok(Some(watch)) => watch.stop(),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Indexer task for {:?} ended abnormally: {}", self.root, e),
+        }
+    }
+
+    /// Handle `window/workDoneProgress/cancel`; returns whether `token` was ours.
+    pub fn cancel_progress(&self, token: &NumberOrString) -> bool {
+        if *token != self.progress_token {
+            return false;
+        }
+        self.token.cancel();
+        true
+    }
+}
+
+/// Sends `$/progress` notifications for one indexing run.
+struct ProgressReporter {
+    client: Client,
+    token: NumberOrString,
+}
+
+impl ProgressReporter {
+    /// Ask the client to create the progress token; `None` if it refuses.
+    async fn begin(client: &Client, token: NumberOrString, root: &Path) -> Option<Self> {
+        let created = client
+            .send_request::<WorkDoneProgressCreate>(WorkDoneProgressCreateParams {
+                token: token.clone(),
+            })
+            .await;
+        if let Err(e) = created {
+            tracing::debug!("Client declined work done progress: {}", e);
+            return None;
+        }
+
+        let reporter = Self {
+            client: client.clone(),
+            token,
+        };
+        reporter
+            .send(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                title: "Naviscope indexing".to_string(),
+                cancellable: Some(true),
+                message: Some(root.display().to_string()),
+                percentage: Some(0),
+            }))
+            .await;
+        Some(reporter)
+    }
+
+    async fn report(&self, progress: &IndexProgress, percentage: u32) {
+        let message = if progress.total > 0 {
+            format!(
+                "{} {}/{}",
+                progress.phase.label(),
+                progress.done,
+                progress.total
+            )
+        } else {
+            progress.phase.label().to_string()
+        };
+        self.send(WorkDoneProgress::Report(WorkDoneProgressReport {
+            cancellable: Some(progress.phase != IndexPhase::Committing),
+            message: Some(message),
+            percentage: Some(percentage),
+        }))
+        .await;
+    }
+
+    async fn end(&self, message: String) {
+        self.send(WorkDoneProgress::End(WorkDoneProgressEnd {
+            message: Some(message),
+        }))
+        .await;
+    }
+
+    async fn send(&self, value: WorkDoneProgress) {
+        self.client
+            .send_notification::<Progress>(ProgressParams {
+                token: self.token.clone(),
+                value: ProgressParamsValue::WorkDone(value),
+            })
+            .await;
+    }
+}
+
+/// Start indexing `path` in the background.
+///
+/// When `work_done_progress` is set, phase progress is reported through
+/// `window/workDoneProgress` once `initialized` turns true, and cancelling it
+/// from the client cancels the rebuild. `status` follows the run for MCP
+/// readiness checks either way.
 pub fn spawn_indexer(
     path: PathBuf,
     client: Client,
-    engine_lock: Arc<RwLock<Option<Arc<dyn NaviscopeEngine>>>>,
-) {
-    tokio::spawn(async move {
+    engine: Arc<dyn NaviscopeEngine>,
+    work_done_progress: bool,
+    mut initialized: watch::Receiver<bool>,
+    status: StatusTracker,
+) -> IndexerHandle {
+    let token = CancellationToken::new();
+    let task_token = token.clone();
+    let root = path.clone();
+    let progress_token = NumberOrString::String(format!(
+        "naviscope/indexing/{}",
+        NEXT_PROGRESS_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+    let task_progress_token = progress_token.clone();
+
+    let task = tokio::spawn(async move {
         let start = std::time::Instant::now();
+        status.start();
         client
             .log_message(
                 MessageType::INFO,
                 format!("Naviscope indexing started for {:?}", path),
             )
             .await;
+        if let Ok(Some(resume)) = engine.pending_resume().await {
+            client
+                .log_message(
+                    MessageType::INFO,
+                    format!(
+                        "Resuming interrupted indexing: {} files already indexed (epoch {})",
+                        resume.committed_files, resume.epoch
+                    ),
+                )
+                .await;
+        }
 
-        // Retrieve existing handle
-        let engine = {
-            let lock = engine_lock.read().await;
-            match lock.as_ref() {
-                Some(h) => h.clone(),
-                None => {
-                    client
-                        .log_message(MessageType::ERROR, "Engine handle not initialized")
-                        .await;
-                    return;
-                }
+        // Progress can only be requested after the client acknowledged initialization
+        let ready = work_done_progress
+            && tokio::select! {
+                ready = initialized.wait_for(|ready| *ready) => ready.is_ok(),
+                _ = task_token.cancelled() => false,
+            };
+        let reporter = if ready {
+            ProgressReporter::begin(&client, task_progress_token, &path).await
+        } else {
+            None
+        };
+        let reporter = reporter.map(Arc::new);
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<(IndexProgress, u32)>();
+        // The engine's own progress counts committed batches, so it does not
+        // start over with each batch the way the phases of the run do
+        let (control, follower) = match engine.subscribe_progress().await {
+            Ok(mut updates) => {
+                let follower = tokio::spawn(async move {
+                    while let Ok(progress) = updates.next().await {
+                        let _ = tx.send((progress.current, progress.percent));
+                    }
+                });
+                (IndexControl::new(), Some(follower))
+            }
+            Err(_) => {
+                let control = IndexControl::new().with_progress(move |progress| {
+                    let _ = tx.send((progress, progress.overall_percentage()));
+                });
+                (control, None)
             }
         };
+        let forwarder = {
+            let reporter = reporter.clone();
+            let status = status.clone();
+            tokio::spawn(async move {
+                let mut last = None;
+                while let Some((progress, percentage)) = rx.recv().await {
+                    // Only forward visible changes; parsing reports once per file
+                    let key = (progress.phase, percentage);
+                    if last != Some(key) {
+                        last = Some(key);
+                        status.progress(key.0, key.1);
+                        if let Some(reporter) = &reporter {
+                            reporter.report(&progress, percentage).await;
+                        }
+                    }
+                }
+            })
+        };
 
         // 1. Initial full index rebuild
-        if let Err(e) = engine.rebuild().await {
-            client
-                .log_message(
-                    MessageType::ERROR,
-                    format!("Initial indexing failed: {}", e),
-                )
-                .await;
-            return;
+        let rebuild = {
+            let rebuild_fut = engine.rebuild_with_control(&control);
+            tokio::pin!(rebuild_fut);
+            tokio::select! {
+                res = &mut rebuild_fut => res,
+                _ = task_token.cancelled() => {
+                    // The engine checks the control between files
+                    control.cancel();
+                    rebuild_fut.await
+                }
+            }
+        };
+        drop(control);
+        if let Some(follower) = follower {
+            // The run is over; its last change no longer needs showing
+            follower.abort();
+            let _ = follower.await;
+        }
+        let _ = forwarder.await;
+
+        match rebuild {
+            Ok(()) => status.ready(),
+            Err(ApiError::Cancelled) => {
+                status.cancelled();
+                if let Some(reporter) = &reporter {
+                    reporter.end("Indexing cancelled".to_string()).await;
+                }
+                client
+                    .log_message(
+                        MessageType::INFO,
+                        format!("Naviscope indexing cancelled for {:?}", path),
+                    )
+                    .await;
+                return None;
+            }
+            Err(e) => {
+                status.failed(e.to_string());
+                if let Some(reporter) = &reporter {
+                    reporter.end(format!("Indexing failed: {}", e)).await;
+                }
+                client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("Initial indexing failed: {}", e),
+                    )
+                    .await;
+                return None;
+            }
         }
 
         let duration = start.elapsed();
@@ -55,20 +275,54 @@ pub fn spawn_indexer(
                 duration, e
             ),
         };
+        if let Some(reporter) = &reporter {
+            reporter.end(stats_msg.clone()).await;
+        }
         client.log_message(MessageType::INFO, stats_msg).await;
 
+        if task_token.is_cancelled() {
+            return None;
+        }
+
         // 2. Setup file watcher
-        if let Err(e) = engine.start_watch().await {
-            client
-                .log_message(
-                    MessageType::ERROR,
-                    format!("Failed to start file watcher: {}", e),
-                )
-                .await;
-        } else {
-            client
-                .log_message(MessageType::INFO, "File watcher started successfully.")
-                .await;
+        match engine.start_watch().await {
+            Ok(watch) => {
+                client
+                    .log_message(MessageType::INFO, "File watcher started successfully.")
+                    .await;
+                Some(watch)
+            }
+            Err(e) => {
+                client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("Failed to start file watcher: {}", e),
+                    )
+                    .await;
+                None
+            }
         }
     });
+
+    IndexerHandle {
+        root,
+        progress_token,
+        token,
+        task,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overall_percentage_advances_across_phases() {
+        let at = |phase, done, total| IndexProgress { phase, done, total }.overall_percentage();
+
+        assert_eq!(at(IndexPhase::Scanning, 0, 0), 0);
+        assert_eq!(at(IndexPhase::Parsing, 50, 100), 30);
+        assert_eq!(at(IndexPhase::Resolving, 0, 10), 50);
+        assert_eq!(at(IndexPhase::Committing, 1, 1), 100);
+    }
 }
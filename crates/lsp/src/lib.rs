@@ -1,19 +1,33 @@
 pub mod capabilities;
+pub mod commands;
+pub mod extensions;
+pub mod folding;
 pub mod goto;
 pub mod hierarchy;
 pub mod highlight;
 pub mod hover;
 pub mod indexer;
+pub mod rename;
+pub mod selection;
+pub mod signature;
 pub mod symbols;
+pub mod timing;
 pub mod util;
+pub mod watched_files;
+pub mod workspace;
 
 use crate::util::Document;
 use dashmap::DashMap;
 use naviscope_api::NaviscopeEngine;
+use naviscope_api::lifecycle::{DocumentEvent, SHUTDOWN_DEADLINE};
 use naviscope_api::models::Language;
+use naviscope_mcp::provider::EngineBuilder;
+use naviscope_mcp::{EngineProvider, McpServer};
+use serde_json::Value;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::{Mutex, RwLock, watch};
 use tokio_util::sync::CancellationToken;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
@@ -21,31 +35,68 @@ use tower_lsp::{Client, LanguageServer};
 
 pub struct LspServer {
     client: Client,
-    pub engine: Arc<RwLock<Option<Arc<dyn NaviscopeEngine>>>>,
-    pub engine_builder: Arc<dyn Fn(PathBuf) -> Arc<dyn NaviscopeEngine> + Send + Sync>,
+    /// Engine for the current root, shared with the MCP server
+    pub engine: EngineProvider,
     pub documents: DashMap<Url, Arc<Document>>,
+    mcp: McpServer,
+    indexer: Mutex<Option<indexer::IndexerHandle>>,
     session_path: Arc<RwLock<Option<PathBuf>>>,
     cancel_token: CancellationToken,
+    /// Client accepts server-initiated `window/workDoneProgress`
+    work_done_progress: AtomicBool,
+    /// Client can register `workspace/didChangeWatchedFiles` dynamically
+    watched_files_registration: AtomicBool,
+    initialized: watch::Sender<bool>,
 }
 
 impl LspServer {
-    pub fn new(
-        client: Client,
-        engine_builder: Arc<dyn Fn(PathBuf) -> Arc<dyn NaviscopeEngine> + Send + Sync>,
-    ) -> Self {
+    pub fn new(client: Client, engine_builder: Arc<EngineBuilder>) -> Self {
+        let engine = EngineProvider::new(engine_builder);
         Self {
             client,
-            engine: Arc::new(RwLock::new(None)),
-            engine_builder,
+            engine: engine.clone(),
             documents: DashMap::new(),
+            mcp: McpServer::new(engine),
+            indexer: Mutex::new(None),
             session_path: Arc::new(RwLock::new(None)),
             cancel_token: CancellationToken::new(),
+            work_done_progress: AtomicBool::new(false),
+            watched_files_registration: AtomicBool::new(false),
+            initialized: watch::channel(false).0,
         }
     }
 
+    /// Handle `window/workDoneProgress/cancel`, which tower-lsp does not route itself.
+    pub async fn work_done_progress_cancel(&self, params: WorkDoneProgressCancelParams) {
+        let indexer = self.indexer.lock().await;
+        if indexer
+            .as_ref()
+            .is_some_and(|i| i.cancel_progress(&params.token))
+        {
+            self.client
+                .log_message(MessageType::INFO, "Indexing cancelled by the client.")
+                .await;
+        }
+    }
+
+    /// Handle `naviscope/nodeAt`: the graph node under a position.
+    pub async fn node_at(
+        &self,
+        params: extensions::NodeAtParams,
+    ) -> Result<Option<extensions::NodeAtResult>> {
+        extensions::node_at(self, params).await
+    }
+
+    /// Handle `naviscope/graphNeighbors`: the nodes and edges around a node.
+    pub async fn graph_neighbors(
+        &self,
+        params: extensions::GraphNeighborsParams,
+    ) -> Result<extensions::GraphNeighborsResult> {
+        extensions::graph_neighbors(self, params).await
+    }
+
     pub async fn get_language_for_uri(&self, uri: &Url) -> Option<Language> {
-        let engine_lock = self.engine.read().await;
-        let engine = engine_lock.as_ref()?;
+        let engine = self.engine.current().await?;
         engine
             .get_language_for_document(uri.as_str())
             .await
@@ -53,6 +104,28 @@ impl LspServer {
             .flatten()
     }
 
+    /// Report an editor event for `uri` to the engine. Applying held-back
+    /// updates may re-index the file, so it runs in the background.
+    async fn document_event(&self, uri: &Url, event: DocumentEvent) {
+        let Ok(path) = uri.to_file_path() else {
+            return;
+        };
+        let Some(engine) = self.engine.current().await else {
+            return;
+        };
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            if let Err(e) = engine.document_event(&path, event).await {
+                client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("Failed to update {}: {}", path.display(), e),
+                    )
+                    .await;
+            }
+        });
+    }
+
     fn offset_at(&self, text: &str, position: Position) -> usize {
         let mut line = 0;
         let mut offset = 0;
@@ -95,57 +168,50 @@ impl LanguageServer for LspServer {
     async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
         let root_path = params
             .root_uri
+            .or_else(|| {
+                params
+                    .workspace_folders
+                    .as_ref()
+                    .and_then(|folders| folders.first())
+                    .map(|f| f.uri.clone())
+            })
             .and_then(|uri| uri.to_file_path().ok())
             .map(|p| p.canonicalize().unwrap_or(p));
 
-        if let Some(path) = root_path {
-            {
-                let handle = (self.engine_builder)(path.clone());
-                let mut guard = self.engine.write().await;
-                *guard = Some(handle);
-            }
+        let work_done_progress = params
+            .capabilities
+            .window
+            .as_ref()
+            .and_then(|w| w.work_done_progress)
+            .unwrap_or(false);
+        self.work_done_progress
+            .store(work_done_progress, Ordering::Relaxed);
+
+        let watched_files_registration = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|w| w.did_change_watched_files.as_ref())
+            .and_then(|c| c.dynamic_registration)
+            .unwrap_or(false);
+        self.watched_files_registration
+            .store(watched_files_registration, Ordering::Relaxed);
 
-            indexer::spawn_indexer(path.clone(), self.client.clone(), self.engine.clone());
+        if let Some(path) = root_path {
+            workspace::switch_root(self, path.clone()).await;
+            let settings = match self.engine.current().await {
+                Some(engine) => engine.host_settings().await.unwrap_or_default(),
+                None => Default::default(),
+            };
 
-            // Start MCP HTTP Server via encapsulated helper
-            // Start MCP HTTP Server via encapsulated helper
+            // Start MCP HTTP Server sharing this server's engine slot
             naviscope_mcp::http::spawn_http_server(
                 self.client.clone(),
-                // This cast is problematic. Let's fix it by wrapping or adapting.
-                // Since we can't cheaply convert the lock type, we will fix this by
-                // Creating a simplified shared state adapter in a follow up.
-                // FOR NOW: We will use a temporary mismatched type cast (unsafe) to prove the point? NO.
-                // We must provide what strictly matches.
-
-                // Let's create a derived lock that proxies? No.
-                //
-                // The correct fix is:
-                // 1. Initialize MCP with a `McpEngineGlue`.
-                // 2. Or, change LspServer's engine field to be generic?
-
-                // Let's go with:
-                // Change `McpServer` to accept a trait `EngineProvider`.
-                // But `McpServer` is in `naviscope-mcp` which we just freed from `naviscope-core`.
-
-                // OK, easier path:
-                // McpServer doesn't strictly need the SAME `RwLock` object if we just want it to work.
-                // But it needs to see "Some(handle)" when the LSP initializes it.
-
-                // Let's do this:
-                // We can't pass `self.engine` directly.
-                {
-                    // Capture the handle we just created
-                    let handle_for_mcp = (self.engine_builder)(path.clone());
-                    let mcp_engine: std::sync::Arc<
-                        tokio::sync::RwLock<
-                            Option<std::sync::Arc<dyn naviscope_api::graph::GraphService>>,
-                        >,
-                    > = std::sync::Arc::new(tokio::sync::RwLock::new(Some(handle_for_mcp.clone())));
-                    mcp_engine
-                },
+                self.mcp.clone(),
                 path,
                 self.session_path.clone(),
                 params.client_info.map(|i| i.name),
+                naviscope_mcp::http::HttpAccess::from_settings(&settings),
                 self.cancel_token.clone(),
             );
         }
@@ -159,8 +225,33 @@ impl LanguageServer for LspServer {
         })
     }
 
+    async fn initialized(&self, _: InitializedParams) {
+        self.initialized.send_replace(true);
+
+        if self.watched_files_registration.load(Ordering::Relaxed) {
+            let registration = watched_files::registration();
+            if let Err(e) = self.client.register_capability(vec![registration]).await {
+                self.client
+                    .log_message(
+                        MessageType::WARNING,
+                        format!("Client refused file watcher registration: {}", e),
+                    )
+                    .await;
+            }
+        }
+    }
+
     async fn shutdown(&self) -> Result<()> {
         self.cancel_token.cancel();
+        if let Some(indexer) = self.indexer.lock().await.take() {
+            indexer.shutdown().await;
+        }
+        // Let a running update commit and write the index before exiting
+        if let Some(engine) = self.engine.current().await
+            && let Err(e) = engine.shutdown(SHUTDOWN_DEADLINE).await
+        {
+            tracing::warn!("Engine shutdown incomplete: {}", e);
+        }
         let mut lock = self.session_path.write().await;
         if let Some(path) = lock.take() {
             let _ = std::fs::remove_file(path);
@@ -168,6 +259,40 @@ impl LanguageServer for LspServer {
         Ok(())
     }
 
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        self.client
+            .log_message(
+                MessageType::LOG,
+                format!(
+                    "LSP Event: didChangeWorkspaceFolders added={} removed={}",
+                    params.event.added.len(),
+                    params.event.removed.len()
+                ),
+            )
+            .await;
+        workspace::did_change_workspace_folders(self, params).await;
+    }
+
+    async fn did_change_configuration(&self, _: DidChangeConfigurationParams) {
+        self.client
+            .log_message(MessageType::LOG, "LSP Event: didChangeConfiguration")
+            .await;
+        workspace::reload_config(self).await;
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        self.client
+            .log_message(
+                MessageType::LOG,
+                format!(
+                    "LSP Event: didChangeWatchedFiles changes={}",
+                    params.changes.len()
+                ),
+            )
+            .await;
+        watched_files::did_change_watched_files(self, params).await;
+    }
+
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
         let uri = params.text_document.uri;
         let content = params.text_document.text;
@@ -177,6 +302,8 @@ impl LanguageServer for LspServer {
             .get_language_for_uri(&uri)
             .await
             .unwrap_or(Language::UNKNOWN);
+        self.document_event(&uri, DocumentEvent::Opened { version })
+            .await;
         self.documents
             .insert(uri, Arc::new(Document::new(content, lang, version)));
     }
@@ -184,6 +311,8 @@ impl LanguageServer for LspServer {
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         let uri = params.text_document.uri;
         let version = params.text_document.version;
+        self.document_event(&uri, DocumentEvent::Changed { version })
+            .await;
 
         if let Some(mut doc_ref) = self.documents.get_mut(&uri) {
             let doc = doc_ref.value_mut();
@@ -217,6 +346,11 @@ impl LanguageServer for LspServer {
             }
         }
     }
+    async fn did_save(&self, params: DidSaveTextDocumentParams) {
+        self.document_event(&params.text_document.uri, DocumentEvent::Saved)
+            .await;
+    }
+
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         self.client
             .log_message(
@@ -224,6 +358,8 @@ impl LanguageServer for LspServer {
                 format!("LSP Event: did_close uri={}", params.text_document.uri),
             )
             .await;
+        self.document_event(&params.text_document.uri, DocumentEvent::Closed)
+            .await;
         self.documents.remove(&params.text_document.uri);
     }
 
@@ -260,6 +396,55 @@ impl LanguageServer for LspServer {
         result
     }
 
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let pos = params.text_document_position_params.position;
+        self.client
+            .log_message(
+                MessageType::LOG,
+                format!(
+                    "LSP Request: textDocument/signatureHelp uri={} pos={}:{}",
+                    uri, pos.line, pos.character
+                ),
+            )
+            .await;
+        let result = signature::signature_help(self, params).await;
+        match &result {
+            Ok(Some(help)) => {
+                self.client
+                    .log_message(
+                        MessageType::LOG,
+                        format!("LSP Response: {} signatures", help.signatures.len()),
+                    )
+                    .await
+            }
+            Ok(None) => {
+                self.client
+                    .log_message(MessageType::LOG, "LSP Response: no signature help")
+                    .await
+            }
+            Err(e) => {
+                self.client
+                    .log_message(MessageType::ERROR, format!("LSP Error: {}", e))
+                    .await
+            }
+        }
+        result
+    }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<Value>> {
+        self.client
+            .log_message(
+                MessageType::LOG,
+                format!(
+                    "LSP Request: workspace/executeCommand command={}",
+                    params.command
+                ),
+            )
+            .await;
+        commands::execute(self, params).await
+    }
+
     async fn document_highlight(
         &self,
         params: DocumentHighlightParams,
@@ -287,6 +472,28 @@ impl LanguageServer for LspServer {
         result
     }
 
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        rename::prepare_rename(self, params).await
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let pos = params.text_document_position.position;
+        self.client
+            .log_message(
+                MessageType::LOG,
+                format!(
+                    "LSP Request: textDocument/rename uri={} pos={}:{} new_name={}",
+                    uri, pos.line, pos.character, params.new_name
+                ),
+            )
+            .await;
+        rename::rename(self, params).await
+    }
+
     async fn goto_definition(
         &self,
         params: GotoDefinitionParams,
@@ -384,6 +591,45 @@ impl LanguageServer for LspServer {
         result
     }
 
+    async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+        self.client
+            .log_message(
+                MessageType::LOG,
+                format!(
+                    "LSP Request: textDocument/foldingRange uri={}",
+                    params.text_document.uri
+                ),
+            )
+            .await;
+        let result = folding::folding_range(self, params).await;
+        if let Ok(Some(ranges)) = &result {
+            self.client
+                .log_message(
+                    MessageType::LOG,
+                    format!("LSP Response: found {} folding ranges", ranges.len()),
+                )
+                .await;
+        }
+        result
+    }
+
+    async fn selection_range(
+        &self,
+        params: SelectionRangeParams,
+    ) -> Result<Option<Vec<SelectionRange>>> {
+        self.client
+            .log_message(
+                MessageType::LOG,
+                format!(
+                    "LSP Request: textDocument/selectionRange uri={} positions={}",
+                    params.text_document.uri,
+                    params.positions.len()
+                ),
+            )
+            .await;
+        selection::selection_range(self, params).await
+    }
+
     async fn symbol(
         &self,
         params: WorkspaceSymbolParams,
@@ -542,9 +788,16 @@ where
     let builder = std::sync::Arc::new(engine_builder);
 
     let (service, socket) =
-        tower_lsp::LspService::new(move |client| LspServer::new(client, builder.clone()));
+        tower_lsp::LspService::build(move |client| LspServer::new(client, builder.clone()))
+            .custom_method(
+                "window/workDoneProgress/cancel",
+                LspServer::work_done_progress_cancel,
+            )
+            .custom_method(extensions::NODE_AT, LspServer::node_at)
+            .custom_method(extensions::GRAPH_NEIGHBORS, LspServer::graph_neighbors)
+            .finish();
     tower_lsp::Server::new(stdin, stdout, socket)
-        .serve(service)
+        .serve(timing::Timed::new(service))
         .await;
 
     Ok(())
@@ -0,0 +1,81 @@
+use crate::LspServer;
+use naviscope_api::ApiError;
+use naviscope_api::models::PositionContext;
+use std::collections::HashMap;
+use tower_lsp::jsonrpc::{Error, Result};
+use tower_lsp::lsp_types::*;
+
+fn to_lsp_range(range: naviscope_api::models::Range) -> Range {
+    Range {
+        start: Position::new(range.start_line as u32, range.start_col as u32),
+        end: Position::new(range.end_line as u32, range.end_col as u32),
+    }
+}
+
+/// Only locals and parameters can be renamed; other symbols report no range.
+pub async fn prepare_rename(
+    server: &LspServer,
+    params: TextDocumentPositionParams,
+) -> Result<Option<PrepareRenameResponse>> {
+    let uri = params.text_document.uri;
+    let position = params.position;
+
+    let engine = match server.engine.current().await {
+        Some(e) => e,
+        None => return Ok(None),
+    };
+
+    let ctx = PositionContext {
+        uri: uri.to_string(),
+        line: position.line,
+        char: position.character,
+        content: server.documents.get(&uri).map(|d| d.content.clone()),
+    };
+
+    match engine.prepare_rename(&ctx).await {
+        Ok(range) => Ok(range.map(|r| PrepareRenameResponse::Range(to_lsp_range(r)))),
+        Err(e) => {
+            tracing::warn!("prepare_rename failed for {}: {}", uri, e);
+            Ok(None)
+        }
+    }
+}
+
+pub async fn rename(server: &LspServer, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+    let uri = params.text_document_position.text_document.uri;
+    let position = params.text_document_position.position;
+
+    let engine = match server.engine.current().await {
+        Some(e) => e,
+        None => return Ok(None),
+    };
+
+    let ctx = PositionContext {
+        uri: uri.to_string(),
+        line: position.line,
+        char: position.character,
+        content: server.documents.get(&uri).map(|d| d.content.clone()),
+    };
+
+    let ranges = match engine.rename_local(&ctx, &params.new_name).await {
+        Ok(ranges) => ranges,
+        // Shown to the user, e.g. a name clash
+        Err(ApiError::InvalidArgument(reason)) => return Err(Error::invalid_params(reason)),
+        Err(e) => {
+            tracing::warn!("rename failed for {}: {}", uri, e);
+            return Ok(None);
+        }
+    };
+
+    let edits = ranges
+        .into_iter()
+        .map(|range| TextEdit {
+            range: to_lsp_range(range),
+            new_text: params.new_name.clone(),
+        })
+        .collect();
+    Ok(Some(WorkspaceEdit {
+        changes: Some(HashMap::from([(uri, edits)])),
+        ..Default::default()
+    }))
+}
@@ -0,0 +1,79 @@
+use crate::LspServer;
+use naviscope_api::models::PositionContext;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::*;
+
+pub async fn selection_range(
+    server: &LspServer,
+    params: SelectionRangeParams,
+) -> Result<Option<Vec<SelectionRange>>> {
+    let uri = params.text_document.uri;
+    let content = server.documents.get(&uri).map(|d| d.content.clone());
+
+    let engine = match server.engine.current().await {
+        Some(e) => e,
+        None => return Ok(None),
+    };
+
+    // The response holds one entry per requested position
+    let mut result = Vec::with_capacity(params.positions.len());
+    for position in params.positions {
+        let ctx = PositionContext {
+            uri: uri.to_string(),
+            line: position.line,
+            char: position.character,
+            content: content.clone(),
+        };
+        let ranges = match engine.get_selection_ranges(&ctx).await {
+            Ok(r) => r,
+            Err(e) => {
+                tracing::warn!("get_selection_ranges failed for {}: {}", uri, e);
+                return Ok(None);
+            }
+        };
+        result.push(nest(ranges).unwrap_or(SelectionRange {
+            range: Range::new(position, position),
+            parent: None,
+        }));
+    }
+
+    Ok(Some(result))
+}
+
+/// Chain innermost-first ranges into nested selection ranges.
+fn nest(ranges: Vec<naviscope_api::models::Range>) -> Option<SelectionRange> {
+    ranges.into_iter().rev().fold(None, |parent, range| {
+        Some(SelectionRange {
+            range: Range {
+                start: Position::new(range.start_line as u32, range.start_col as u32),
+                end: Position::new(range.end_line as u32, range.end_col as u32),
+            },
+            parent: parent.map(Box::new),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start_line: usize, end_line: usize) -> naviscope_api::models::Range {
+        naviscope_api::models::Range {
+            start_line,
+            start_col: 0,
+            end_line,
+            end_col: 1,
+        }
+    }
+
+    #[test]
+    fn nest_puts_innermost_range_first() {
+        let nested = nest(vec![range(2, 2), range(1, 3), range(0, 5)]).unwrap();
+        assert_eq!(nested.range.start.line, 2);
+        let parent = nested.parent.unwrap();
+        assert_eq!(parent.range.start.line, 1);
+        let root = parent.parent.unwrap();
+        assert_eq!(root.range.end.line, 5);
+        assert!(root.parent.is_none());
+    }
+}
@@ -0,0 +1,91 @@
+use crate::LspServer;
+use naviscope_api::models::PositionContext;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::*;
+
+pub async fn signature_help(
+    server: &LspServer,
+    params: SignatureHelpParams,
+) -> Result<Option<SignatureHelp>> {
+    let uri = params.text_document_position_params.text_document.uri;
+    let position = params.text_document_position_params.position;
+    let content = server.documents.get(&uri).map(|d| d.content.clone());
+
+    let engine = match server.engine.current().await {
+        Some(e) => e,
+        None => return Ok(None),
+    };
+
+    let ctx = PositionContext {
+        uri: uri.to_string(),
+        line: position.line,
+        char: position.character,
+        content,
+    };
+
+    match engine.get_signature_help(&ctx).await {
+        Ok(Some(help)) if !help.signatures.is_empty() => Ok(Some(to_lsp(help))),
+        Ok(_) => Ok(None),
+        Err(e) => {
+            tracing::warn!("signature help failed for {}: {}", uri, e);
+            Ok(None)
+        }
+    }
+}
+
+fn to_lsp(help: naviscope_api::models::SignatureHelp) -> SignatureHelp {
+    let signatures = help
+        .signatures
+        .into_iter()
+        .map(|sig| SignatureInformation {
+            label: sig.label,
+            documentation: sig.documentation.map(|value| {
+                Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value,
+                })
+            }),
+            parameters: Some(
+                sig.parameters
+                    .into_iter()
+                    .map(|label| ParameterInformation {
+                        label: ParameterLabel::Simple(label),
+                        documentation: None,
+                    })
+                    .collect(),
+            ),
+            active_parameter: None,
+        })
+        .collect();
+
+    SignatureHelp {
+        signatures,
+        active_signature: Some(help.active_signature as u32),
+        active_parameter: Some(help.active_parameter as u32),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use naviscope_api::models::SignatureInfo;
+
+    #[test]
+    fn converts_signatures_and_active_indices() {
+        let help = naviscope_api::models::SignatureHelp {
+            signatures: vec![SignatureInfo {
+                label: "add(a: int, b: int) -> int".into(),
+                parameters: vec!["a: int".into(), "b: int".into()],
+                documentation: Some("*Defined in `Service`*".into()),
+            }],
+            active_signature: 0,
+            active_parameter: 1,
+        };
+
+        let lsp = to_lsp(help);
+        assert_eq!(lsp.active_parameter, Some(1));
+        let params = lsp.signatures[0].parameters.as_ref().unwrap();
+        assert_eq!(params.len(), 2);
+        assert!(matches!(&params[1].label, ParameterLabel::Simple(l) if l == "b: int"));
+    }
+}
@@ -9,9 +9,8 @@ pub async fn document_symbol(
 ) -> Result<Option<DocumentSymbolResponse>> {
     let uri = params.text_document.uri;
 
-    let engine_lock = server.engine.read().await;
-    let engine = match engine_lock.as_ref() {
-        Some(e) => e.clone(),
+    let engine = match server.engine.current().await {
+        Some(e) => e,
         None => return Ok(None),
     };
 
@@ -74,6 +73,7 @@ fn node_kind_to_symbol_kind(kind: &NodeKind) -> SymbolKind {
         NodeKind::Dependency => SymbolKind::MODULE,
         NodeKind::Task => SymbolKind::FUNCTION,
         NodeKind::Plugin => SymbolKind::MODULE,
+        NodeKind::File => SymbolKind::FILE,
         NodeKind::Custom(s) => match s.as_str() {
             "function" => SymbolKind::FUNCTION,
             "property" => SymbolKind::PROPERTY,
@@ -86,18 +86,18 @@ pub async fn workspace_symbol(
     server: &LspServer,
     params: WorkspaceSymbolParams,
 ) -> Result<Option<Vec<SymbolInformation>>> {
-    let engine_lock = server.engine.read().await;
-    let engine = match engine_lock.as_ref() {
-        Some(e) => e.clone(),
+    let engine = match server.engine.current().await {
+        Some(e) => e,
         None => return Ok(None),
     };
 
-    // Use engine's graph query for workspace symbols
+    // Ranked fuzzy match, so `UsrCtl` finds `UserController`
     use naviscope_api::graph::GraphQuery;
-    let query = GraphQuery::Find {
+    let query = GraphQuery::Fuzzy {
         pattern: params.query,
         kind: vec![],
         sources: vec![],
+        statuses: vec![],
         limit: 100,
     };
 
@@ -147,6 +147,8 @@ mod tests {
     fn convert_api_symbols_skips_entries_without_location() {
         let symbols = vec![DisplayGraphNode {
             id: "com.example.Missing".to_string(),
+            symbol_id: String::new(),
+            generated: false,
             name: "Missing".to_string(),
             kind: NodeKind::Class,
             lang: "java".to_string(),
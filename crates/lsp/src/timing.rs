@@ -0,0 +1,72 @@
+//! Spans and latencies of LSP requests
+//!
+//! Every message from the client is handled in an `lsp_request` span; the
+//! latency of each request (not notification) is recorded in the
+//! `naviscope.lsp.request.duration` histogram. Both reach the OTLP exporter
+//! when the process exports telemetry, and cost next to nothing otherwise.
+
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::Histogram;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::LazyLock;
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tower_lsp::jsonrpc::Request;
+use tower_service::Service;
+use tracing::Instrument;
+
+static REQUEST_DURATION: LazyLock<Histogram<f64>> = LazyLock::new(|| {
+    opentelemetry::global::meter("naviscope")
+        .f64_histogram("naviscope.lsp.request.duration")
+        .with_unit("ms")
+        .with_description("Time to answer an LSP request")
+        .build()
+});
+
+/// Service handling each message of `inner` in a span, timing the requests
+pub struct Timed<S> {
+    inner: S,
+}
+
+impl<S> Timed<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+}
+
+impl<S> Service<Request> for Timed<S>
+where
+    S: Service<Request>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let method = request.method().to_string();
+        let is_request = request.id().is_some();
+        let span = tracing::info_span!("lsp_request", method = %method);
+        let response = self.inner.call(request);
+
+        Box::pin(
+            async move {
+                let started = Instant::now();
+                let response = response.await;
+                if is_request {
+                    REQUEST_DURATION.record(
+                        started.elapsed().as_secs_f64() * 1000.0,
+                        &[KeyValue::new("method", method)],
+                    );
+                }
+                response
+            }
+            .instrument(span),
+        )
+    }
+}
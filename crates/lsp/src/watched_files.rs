@@ -0,0 +1,115 @@
+use crate::LspServer;
+use std::path::{Path, PathBuf};
+use tower_lsp::lsp_types::*;
+
+const REGISTRATION_ID: &str = "naviscope/watched-files";
+
+/// Project configuration file, reloaded when it changes
+const CONFIG_FILE: &str = "naviscope.toml";
+
+/// Files the engine indexes: Java sources and Gradle build scripts, plus the
+/// project configuration.
+const WATCHED_GLOBS: &[&str] = &[
+    "**/*.java",
+    "**/*.gradle",
+    "**/*.gradle.kts",
+    "**/naviscope.toml",
+];
+
+/// Dynamic registration asking the client to report changes to indexed files.
+pub fn registration() -> Registration {
+    let options = DidChangeWatchedFilesRegistrationOptions {
+        watchers: WATCHED_GLOBS
+            .iter()
+            .map(|glob| FileSystemWatcher {
+                glob_pattern: GlobPattern::String(glob.to_string()),
+                kind: None,
+            })
+            .collect(),
+    };
+    Registration {
+        id: REGISTRATION_ID.to_string(),
+        method: "workspace/didChangeWatchedFiles".to_string(),
+        register_options: serde_json::to_value(options).ok(),
+    }
+}
+
+/// Re-index the files reported by the client.
+///
+/// The update runs in the background so the notification queue is not held
+/// up. Events that the internal watcher also sees are cheap: the engine skips
+/// files whose fingerprint did not change.
+pub async fn did_change_watched_files(server: &LspServer, params: DidChangeWatchedFilesParams) {
+    let root = server.indexer.lock().await.as_ref().map(|i| i.root.clone());
+    let mut paths = changed_paths(root.as_deref(), &params.changes);
+    let config_path = root.map(|root| root.join(CONFIG_FILE));
+    if let Some(pos) = paths.iter().position(|p| Some(p) == config_path.as_ref()) {
+        paths.remove(pos);
+        crate::workspace::reload_config(server).await;
+    }
+    if paths.is_empty() {
+        return;
+    }
+
+    let Some(engine) = server.engine.current().await else {
+        return;
+    };
+    let client = server.client.clone();
+    tokio::spawn(async move {
+        let count = paths.len();
+        if let Err(e) = engine.update_files(paths).await {
+            client
+                .log_message(
+                    MessageType::ERROR,
+                    format!("Failed to update {} watched files: {}", count, e),
+                )
+                .await;
+        }
+    });
+}
+
+/// Local paths under `root` named by `changes`, sorted and de-duplicated.
+fn changed_paths(root: Option<&Path>, changes: &[FileEvent]) -> Vec<PathBuf> {
+    let Some(root) = root else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = changes
+        .iter()
+        .filter_map(|change| change.uri.to_file_path().ok())
+        .filter(|path| path.starts_with(root))
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(path: &str, typ: FileChangeType) -> FileEvent {
+        FileEvent {
+            uri: Url::from_file_path(path).unwrap(),
+            typ,
+        }
+    }
+
+    #[test]
+    fn changed_paths_keeps_files_under_root_once() {
+        let changes = vec![
+            event("/ws/src/B.java", FileChangeType::CHANGED),
+            event("/ws/src/A.java", FileChangeType::CREATED),
+            event("/ws/src/B.java", FileChangeType::DELETED),
+            event("/elsewhere/C.java", FileChangeType::CHANGED),
+        ];
+
+        assert_eq!(
+            changed_paths(Some(Path::new("/ws")), &changes),
+            vec![
+                PathBuf::from("/ws/src/A.java"),
+                PathBuf::from("/ws/src/B.java")
+            ]
+        );
+        assert!(changed_paths(None, &changes).is_empty());
+    }
+}
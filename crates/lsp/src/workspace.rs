@@ -0,0 +1,219 @@
+use crate::LspServer;
+use crate::indexer;
+use crate::util::Document;
+use naviscope_api::NaviscopeEngine;
+use naviscope_api::models::Language;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use tower_lsp::lsp_types::*;
+
+/// Point the server at `root`, replacing the current engine if it serves another root.
+///
+/// The previous indexer is drained and its watcher stopped before the new engine is
+/// installed. Requests already running finish on the old engine; later ones get the
+/// new one. Open documents are re-bound to the new engine and connected MCP clients
+/// are told about the change.
+pub async fn switch_root(server: &LspServer, root: PathBuf) {
+    let mut current = server.indexer.lock().await;
+    if current.as_ref().is_some_and(|i| i.root == root) {
+        return;
+    }
+
+    let previous_root = match current.take() {
+        Some(old) => {
+            let old_root = old.root.clone();
+            old.shutdown().await;
+            Some(old_root)
+        }
+        None => None,
+    };
+
+    // The MCP server shares the provider and sees the new engine too
+    let Some(engine) = server.engine.switch_to(root.clone()).await else {
+        return;
+    };
+
+    migrate_documents(server, engine.as_ref()).await;
+
+    *current = Some(indexer::spawn_indexer(
+        root.clone(),
+        server.client.clone(),
+        engine,
+        server.work_done_progress.load(Ordering::Relaxed),
+        server.initialized.subscribe(),
+        server.mcp.indexing_status().clone(),
+    ));
+    drop(current);
+
+    if let Some(old_root) = previous_root {
+        server
+            .client
+            .log_message(
+                MessageType::INFO,
+                format!(
+                    "Naviscope workspace root changed: {:?} -> {:?}",
+                    old_root, root
+                ),
+            )
+            .await;
+
+        {
+            let mut session = server.session_path.write().await;
+            if let Some(old_session) = session.take() {
+                *session = naviscope_mcp::relocate_session(&old_session, &root);
+            }
+        }
+        server.mcp.notify_root_changed(&root).await;
+    }
+}
+
+/// Re-read `naviscope.toml` for the current engine.
+pub async fn reload_config(server: &LspServer) {
+    let Some(engine) = server.engine.current().await else {
+        return;
+    };
+    let (kind, message) = match engine.reload_config().await {
+        Ok(()) => (
+            MessageType::INFO,
+            "Reloaded naviscope.toml; changes apply from the next indexing run".to_string(),
+        ),
+        Err(e) => (
+            MessageType::ERROR,
+            format!("Failed to reload naviscope.toml: {}", e),
+        ),
+    };
+    server.client.log_message(kind, message).await;
+}
+
+pub async fn did_change_workspace_folders(
+    server: &LspServer,
+    params: DidChangeWorkspaceFoldersParams,
+) {
+    let current = server.indexer.lock().await.as_ref().map(|i| i.root.clone());
+
+    if let Some(root) = next_root(current.as_deref(), &params.event) {
+        switch_root(server, root).await;
+    }
+}
+
+/// Re-detect the language of every open document against the new engine.
+async fn migrate_documents(server: &LspServer, engine: &dyn NaviscopeEngine) {
+    let uris: Vec<Url> = server.documents.iter().map(|e| e.key().clone()).collect();
+
+    for uri in uris {
+        let lang = engine
+            .get_language_for_document(uri.as_str())
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or(Language::UNKNOWN);
+
+        if let Some(mut doc_ref) = server.documents.get_mut(&uri) {
+            let doc = doc_ref.value_mut();
+            *doc = Arc::new(Document::new(doc.content.clone(), lang, doc.version));
+        }
+    }
+}
+
+/// Decide which root to serve after a workspace folder change.
+///
+/// The root only moves when the current one is removed (or none is set) and a new
+/// folder is available.
+fn next_root(current: Option<&Path>, event: &WorkspaceFoldersChangeEvent) -> Option<PathBuf> {
+    let current_removed = match current {
+        Some(root) => event
+            .removed
+            .iter()
+            .filter_map(folder_path)
+            .any(|p| p == root),
+        None => true,
+    };
+
+    if !current_removed {
+        return None;
+    }
+
+    event.added.iter().filter_map(folder_path).next()
+}
+
+/// Canonical path of `folder`, comparable with the current root
+fn folder_path(folder: &WorkspaceFolder) -> Option<PathBuf> {
+    let path = folder.uri.to_file_path().ok()?;
+    Some(path.canonicalize().unwrap_or(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::next_root;
+    use std::path::PathBuf;
+    use tower_lsp::lsp_types::{Url, WorkspaceFolder, WorkspaceFoldersChangeEvent};
+
+    fn folder(path: &str) -> WorkspaceFolder {
+        WorkspaceFolder {
+            uri: Url::from_file_path(path).unwrap(),
+            name: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn next_root_moves_when_current_removed() {
+        let event = WorkspaceFoldersChangeEvent {
+            added: vec![folder("/naviscope_new_root")],
+            removed: vec![folder("/naviscope_old_root")],
+        };
+
+        let current = PathBuf::from("/naviscope_old_root");
+        assert_eq!(
+            next_root(Some(&current), &event),
+            Some(PathBuf::from("/naviscope_new_root"))
+        );
+    }
+
+    #[test]
+    fn next_root_keeps_current_when_still_present() {
+        let event = WorkspaceFoldersChangeEvent {
+            added: vec![folder("/naviscope_other_root")],
+            removed: vec![],
+        };
+
+        let current = PathBuf::from("/naviscope_old_root");
+        assert_eq!(next_root(Some(&current), &event), None);
+    }
+
+    #[test]
+    fn next_root_adopts_first_folder_without_current() {
+        let event = WorkspaceFoldersChangeEvent {
+            added: vec![folder("/naviscope_new_root")],
+            removed: vec![],
+        };
+
+        assert_eq!(
+            next_root(None, &event),
+            Some(PathBuf::from("/naviscope_new_root"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn next_root_matches_a_removed_folder_opened_through_a_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("project");
+        let other = dir.path().join("other");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::create_dir(&other).unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&root, &link).unwrap();
+
+        let event = WorkspaceFoldersChangeEvent {
+            added: vec![folder(other.to_str().unwrap())],
+            removed: vec![folder(link.to_str().unwrap())],
+        };
+
+        let current = root.canonicalize().unwrap();
+        assert_eq!(
+            next_root(Some(&current), &event),
+            Some(other.canonicalize().unwrap())
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! Audit log of tool calls
+//!
+//! Every tool call that reaches the engine is recorded in the project's audit
+//! log once it completes: the client that made it, the tool and its
+//! arguments, when, how long it took and whether it failed. Teams letting
+//! agents loose on a repository read it back with `naviscope audit-log`.
+
+use crate::McpServer;
+use naviscope_api::lifecycle::AuditEntry;
+use rmcp::ErrorData as McpError;
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{CallToolResult, RawContent, Tool};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Tools that only report the state of the server; agents poll `status`
+const UNAUDITED_TOOLS: &[&str] = &["status", "get_guide", "runtime_metrics"];
+
+/// The tool router of a server, recording the calls it dispatches
+pub(crate) struct AuditedRouter<'a> {
+    server: &'a McpServer,
+}
+
+impl McpServer {
+    pub(crate) fn audited_router(&self) -> AuditedRouter<'_> {
+        AuditedRouter { server: self }
+    }
+}
+
+impl<'a> AuditedRouter<'a> {
+    pub(crate) async fn call(
+        &self,
+        context: ToolCallContext<'_, McpServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let tool = context.name.to_string();
+        if UNAUDITED_TOOLS.contains(&tool.as_str()) {
+            return self.server.tool_router.call(context).await;
+        }
+        let client = context
+            .request_context
+            .peer
+            .peer_info()
+            .map(|info| info.client_info.name.clone());
+        let arguments = context
+            .arguments
+            .clone()
+            .map_or(serde_json::Value::Null, serde_json::Value::Object);
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let started = Instant::now();
+
+        let result = self.server.tool_router.call(context).await;
+
+        let entry = AuditEntry {
+            at,
+            client,
+            tool,
+            arguments,
+            error: failure(&result),
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+        // Calls made before an engine exists failed without touching a project
+        if let Some(engine) = self.server.engine.current().await
+            && let Err(e) = engine.record_audit(entry).await
+        {
+            tracing::warn!("Failed to record tool call in the audit log: {}", e);
+        }
+        result
+    }
+
+    pub(crate) fn list_all(&self) -> Vec<Tool> {
+        self.server.tool_router.list_all()
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&'a Tool> {
+        self.server.tool_router.get(name)
+    }
+}
+
+/// Error of a failed call, as the client saw it
+fn failure(result: &Result<CallToolResult, McpError>) -> Option<String> {
+    match result {
+        Err(e) => Some(e.message.to_string()),
+        Ok(result) if result.is_error == Some(true) => Some(
+            result
+                .content
+                .iter()
+                .find_map(|content| match &content.raw {
+                    RawContent::Text(text) => Some(text.text.clone()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| "tool error".to_string()),
+        ),
+        Ok(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::Content;
+
+    #[test]
+    fn failures_keep_the_message_the_client_saw() {
+        assert_eq!(
+            failure(&Err(McpError::invalid_params(
+                "pass either fqn or path",
+                None
+            )))
+            .as_deref(),
+            Some("pass either fqn or path")
+        );
+        assert_eq!(
+            failure(&Ok(CallToolResult::error(vec![Content::text("No node A")]))).as_deref(),
+            Some("No node A")
+        );
+        assert_eq!(
+            failure(&Ok(CallToolResult::success(vec![Content::text("{}")]))),
+            None
+        );
+    }
+}
@@ -0,0 +1,68 @@
+//! In-flight tool calls
+//!
+//! Every tool call is registered under its JSON-RPC request id together with
+//! the cancellation token rmcp cancels on `notifications/cancelled`. The
+//! registry lets the server abort all running calls at once, e.g. when the
+//! engine they query is replaced.
+
+use rmcp::model::RequestId;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Default)]
+pub(crate) struct CallRegistry {
+    calls: Mutex<HashMap<RequestId, CancellationToken>>,
+}
+
+impl CallRegistry {
+    /// Track a call until the returned guard is dropped.
+    pub(crate) fn register(self: &Arc<Self>, id: RequestId, token: CancellationToken) -> CallGuard {
+        self.calls.lock().unwrap().insert(id.clone(), token);
+        CallGuard {
+            registry: self.clone(),
+            id,
+        }
+    }
+
+    pub(crate) fn cancel_all(&self) {
+        for token in self.calls.lock().unwrap().values() {
+            token.cancel();
+        }
+    }
+}
+
+pub(crate) struct CallGuard {
+    registry: Arc<CallRegistry>,
+    id: RequestId,
+}
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        self.registry.calls.lock().unwrap().remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::NumberOrString;
+
+    #[test]
+    fn guard_unregisters_and_cancel_all_reaches_live_calls() {
+        let registry = Arc::new(CallRegistry::default());
+        let first = CancellationToken::new();
+        let second = CancellationToken::new();
+
+        let guard = registry.register(NumberOrString::Number(1), first.clone());
+        drop(registry.register(NumberOrString::Number(2), second.clone()));
+        assert_eq!(registry.calls.lock().unwrap().len(), 1);
+
+        registry.cancel_all();
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+
+        drop(guard);
+        assert_eq!(registry.calls.lock().unwrap().len(), 0);
+    }
+}
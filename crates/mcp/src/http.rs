@@ -1,71 +1,150 @@
-use crate::McpServer;
+use crate::{IndexingStatus, McpServer};
 use axum::{
-    Router,
-    extract::State,
+    Json, Router,
     extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    extract::{Path as UrlPath, State},
+    http::{HeaderMap, StatusCode, header},
+    response::IntoResponse,
     routing::get,
 };
 use futures::{sink::SinkExt, stream::StreamExt};
-use naviscope_api::graph::GraphService;
+use naviscope_api::NaviscopeEngine;
+use naviscope_api::lifecycle::{HostSettings, SHUTDOWN_DEADLINE};
 use rmcp::ServiceExt;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpSocket};
 use tokio::sync::RwLock;
 use tokio_util::sync::CancellationToken;
 use tower_lsp::Client;
 use tower_lsp::lsp_types::MessageType;
-use tracing::info;
+use tracing::{info, warn};
+
+/// Where the HTTP server listens and the token its clients must present
+#[derive(Debug, Clone)]
+pub struct HttpAccess {
+    pub host: IpAddr,
+    /// Fixed port; a free one is picked when unset
+    pub port: Option<u16>,
+    pub token: String,
+}
+
+impl HttpAccess {
+    /// Access configured in `settings`: loopback unless a host is set, and a
+    /// fresh token unless one is set.
+    pub fn from_settings(settings: &HostSettings) -> Self {
+        Self {
+            host: settings.mcp_host.unwrap_or(Ipv4Addr::LOCALHOST.into()),
+            port: settings.mcp_port,
+            token: settings.mcp_token.clone().unwrap_or_else(generate_token),
+        }
+    }
+}
+
+/// Random 128-bit token from the operating system's CSPRNG, hex-encoded
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).expect("operating system random source unavailable");
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
 
 pub fn spawn_http_server(
     client: Client,
-    engine: Arc<RwLock<Option<Arc<dyn GraphService>>>>,
+    mcp: McpServer,
     root_path: PathBuf,
     session_path_lock: Arc<RwLock<Option<PathBuf>>>,
     client_name: Option<String>,
+    access: HttpAccess,
     cancel_token: CancellationToken,
 ) {
     tokio::spawn(async move {
-        let port = {
-            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.ok();
-            listener.map(|l| l.local_addr().unwrap().port())
-        };
-
-        if let Some(port) = port {
-            // 1. Session recording
-            let session_path = super::get_session_path(&root_path);
-            let info = super::SessionInfo {
-                port,
-                pid: std::process::id(),
-                root_path: root_path.clone(),
-            };
-            if let Ok(json) = serde_json::to_string(&info) {
-                let _ = std::fs::write(&session_path, json);
-                let mut lock = session_path_lock.write().await;
-                *lock = Some(session_path);
-            }
-
-            // 2. Auto-config for Cursor
-            if let Some(name) = &client_name {
-                if name.to_lowercase().contains("cursor") {
-                    write_cursor_config(&root_path);
-                }
-            }
+        let removed = super::remove_dead_sessions().await;
+        if removed > 0 {
+            info!("Removed {} stale MCP session file(s)", removed);
+        }
 
-            // 3. Run server
-            let mcp_err = match run_http_server(engine, Some(root_path), port, cancel_token).await {
-                Ok(_) => None,
-                Err(e) => Some(e.to_string()),
-            };
-            if let Some(e) = mcp_err {
+        let listener = match bind_listener(access.host, access.port).await {
+            Ok(listener) => listener,
+            Err(e) => {
                 let _ = client
                     .log_message(MessageType::ERROR, format!("MCP HTTP Server failed: {}", e))
                     .await;
+                return;
             }
+        };
+        let Ok(addr) = listener.local_addr() else {
+            return;
+        };
+
+        // 1. Session recording, with the port actually bound
+        let session_path = super::get_session_path(&root_path);
+        let info = super::SessionInfo {
+            port: addr.port(),
+            pid: std::process::id(),
+            root_path: root_path.clone(),
+            host: Some(access.host),
+            token: Some(access.token.clone()),
+            route: None,
+        };
+        if info.write(&session_path).is_ok() {
+            let mut lock = session_path_lock.write().await;
+            *lock = Some(session_path);
+        }
+
+        // 2. Auto-config for Cursor
+        if let Some(name) = &client_name
+            && name.to_lowercase().contains("cursor")
+        {
+            write_cursor_config(&root_path);
+        }
+
+        // 3. Run server
+        let routes = ProjectRoutes::default();
+        let mcp_err = match serve_listener(mcp, routes, listener, access.token, cancel_token).await
+        {
+            Ok(_) => None,
+            Err(e) => Some(e.to_string()),
+        };
+        if let Some(e) = mcp_err {
+            let _ = client
+                .log_message(MessageType::ERROR, format!("MCP HTTP Server failed: {}", e))
+                .await;
         }
     });
 }
 
+/// Bind the server's listener. `SO_REUSEADDR` lets a restarted server take
+/// over its fixed port while connections of the previous one linger in
+/// `TIME_WAIT`; if the port is still taken, a free one is used instead.
+pub async fn bind_listener(host: IpAddr, port: Option<u16>) -> std::io::Result<TcpListener> {
+    let bind = |port: u16| -> std::io::Result<TcpListener> {
+        let addr = SocketAddr::new(host, port);
+        let socket = match addr {
+            SocketAddr::V4(_) => TcpSocket::new_v4()?,
+            SocketAddr::V6(_) => TcpSocket::new_v6()?,
+        };
+        // On Windows the option allows stealing a bound port, so it stays off
+        #[cfg(unix)]
+        socket.set_reuseaddr(true)?;
+        socket.bind(addr)?;
+        socket.listen(1024)
+    };
+
+    match port {
+        Some(port) => bind(port).or_else(|e| {
+            warn!(
+                "MCP port {} is unavailable ({}), using a free port instead",
+                port, e
+            );
+            bind(0)
+        }),
+        None => bind(0),
+    }
+}
+
 fn write_cursor_config(root_path: &Path) {
     let config = serde_json::json!({
         "mcpServers": {
@@ -84,19 +163,237 @@ fn write_cursor_config(root_path: &Path) {
 }
 
 pub async fn run_http_server(
-    engine: Arc<RwLock<Option<Arc<dyn GraphService>>>>,
+    engine: Arc<RwLock<Option<Arc<dyn NaviscopeEngine>>>>,
     _root_path: Option<PathBuf>, // Kept for API compatibility, but not used in McpServer
-    port: u16,
+    access: HttpAccess,
+    cancel_token: CancellationToken,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let server = McpServer::new(engine);
+    server.mark_ready_if_loaded().await;
+    serve_http(server, ProjectRoutes::default(), access, cancel_token).await
+}
+
+/// Serve the MCP servers of several projects from one HTTP server, each
+/// indexing and watching its own project.
+///
+/// Every project is served at `/projects/{name}/mcp`, the first one also at
+/// `/mcp`. Each gets a session record pointing at its route, so
+/// `naviscope mcp --path <project>` proxies reach the right engine.
+pub async fn run_projects_server(
+    engines: Vec<(PathBuf, Arc<dyn NaviscopeEngine>)>,
+    access: HttpAccess,
+    cancel_token: CancellationToken,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let removed = super::remove_dead_sessions().await;
+    if removed > 0 {
+        info!("Removed {} stale MCP session file(s)", removed);
+    }
+
+    let listener = bind_listener(access.host, access.port).await?;
+    let port = listener.local_addr()?.port();
+    let routes = ProjectRoutes::default();
+    let mut default = None;
+    let mut indexers = Vec::new();
+    let mut sessions = Vec::new();
+    let mut hosted = Vec::new();
+    for (root_path, engine) in engines {
+        hosted.push(engine.clone());
+        let server = McpServer::new(Arc::new(RwLock::new(Some(engine.clone()))));
+        indexers.push(tokio::spawn(crate::stdio::index_and_watch(
+            engine,
+            server.indexing_status().clone(),
+            cancel_token.clone(),
+        )));
+
+        let route = project_route(&routes.register(&root_path, server.clone()));
+        info!("Serving {} at {}", root_path.display(), route);
+        let session_path = super::get_session_path(&root_path);
+        let info = super::SessionInfo {
+            port,
+            pid: std::process::id(),
+            root_path,
+            host: Some(access.host),
+            token: Some(access.token.clone()),
+            route: Some(route),
+        };
+        match info.write(&session_path) {
+            Ok(()) => sessions.push(session_path),
+            Err(e) => warn!(
+                "Failed to write MCP session {}: {}",
+                session_path.display(),
+                e
+            ),
+        }
+
+        // The server at `/mcp` follows commits in `serve_listener`
+        if default.is_none() {
+            default = Some(server);
+        } else {
+            let cancel_token = cancel_token.clone();
+            tokio::spawn(async move { server.follow_commits(cancel_token).await });
+        }
+    }
+    let default = default.ok_or("No project to serve")?;
+
+    let result = serve_listener(
+        default,
+        routes,
+        listener,
+        access.token,
+        cancel_token.clone(),
+    )
+    .await;
+    cancel_token.cancel();
+    for indexer in indexers {
+        if let Ok(Some(watch)) = indexer.await {
+            watch.stop();
+        }
+    }
+    for engine in hosted {
+        if let Err(e) = engine.shutdown(SHUTDOWN_DEADLINE).await {
+            warn!("Engine shutdown incomplete: {}", e);
+        }
+    }
+    for session_path in sessions {
+        let _ = std::fs::remove_file(session_path);
+    }
+    result
+}
+
+/// Path of the MCP endpoint of the project registered as `name`
+pub fn project_route(name: &str) -> String {
+    format!("/projects/{}/mcp", name)
+}
+
+/// MCP servers of the projects hosted next to the default one, by name
+#[derive(Clone, Default)]
+pub struct ProjectRoutes {
+    projects: Arc<std::sync::Mutex<BTreeMap<String, (PathBuf, McpServer)>>>,
+}
+
+impl ProjectRoutes {
+    /// Serve `mcp` for the project at `root_path`. The project is named after
+    /// its directory, with a numeric suffix when another project has that
+    /// name; returns the name.
+    pub fn register(&self, root_path: &Path, mcp: McpServer) -> String {
+        let base: String = root_path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        let base = if base.is_empty() || base == "." || base == ".." {
+            "project".to_string()
+        } else {
+            base
+        };
+
+        let mut projects = self.projects.lock().unwrap();
+        let name = std::iter::once(base.clone())
+            .chain((2..).map(|n| format!("{}-{}", base, n)))
+            .find(|name| !projects.contains_key(name))
+            .unwrap();
+        projects.insert(name.clone(), (root_path.to_path_buf(), mcp));
+        name
+    }
+
+    /// Stop serving the project registered as `name`.
+    pub fn unregister(&self, name: &str) {
+        self.projects.lock().unwrap().remove(name);
+    }
+
+    fn get(&self, name: &str) -> Option<McpServer> {
+        let projects = self.projects.lock().unwrap();
+        projects.get(name).map(|(_, mcp)| mcp.clone())
+    }
+
+    fn entries(&self) -> Vec<ProjectEntry> {
+        let projects = self.projects.lock().unwrap();
+        projects
+            .iter()
+            .map(|(name, (root_path, mcp))| ProjectEntry {
+                name: name.clone(),
+                root_path: root_path.clone(),
+                endpoint: project_route(name),
+                status: mcp.indexing_status().snapshot(),
+            })
+            .collect()
+    }
+}
+
+#[derive(serde::Serialize)]
+struct ProjectEntry {
+    name: String,
+    root_path: PathBuf,
+    endpoint: String,
+    status: IndexingStatus,
+}
+
+#[derive(Clone)]
+struct HttpState {
+    mcp: McpServer,
+    projects: ProjectRoutes,
+    token: Arc<str>,
+}
+
+/// Serve an existing [`McpServer`] over WebSocket as described by `access`.
+///
+/// The `/mcp` endpoint requires `Authorization: Bearer <token>`; the health
+/// endpoints stay open for probes. The projects in `routes` are served at
+/// their own endpoints, see [`project_route`].
+pub async fn serve_http(
+    mcp: McpServer,
+    routes: ProjectRoutes,
+    access: HttpAccess,
     cancel_token: CancellationToken,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mcp = McpServer::new(engine);
+    let listener = bind_listener(access.host, access.port).await?;
+    serve_listener(mcp, routes, listener, access.token, cancel_token).await
+}
+
+async fn serve_listener(
+    mcp: McpServer,
+    projects: ProjectRoutes,
+    listener: TcpListener,
+    token: String,
+    cancel_token: CancellationToken,
+) -> Result<(), Box<dyn std::error::Error>> {
+    tokio::spawn({
+        let mcp = mcp.clone();
+        let cancel_token = cancel_token.clone();
+        async move { mcp.follow_commits(cancel_token).await }
+    });
 
     let app = Router::new()
         .route("/mcp", get(mcp_ws_handler))
-        .with_state(mcp);
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route("/metrics", get(metrics))
+        .route("/projects", get(list_projects))
+        .route("/projects/{name}/mcp", get(project_ws_handler))
+        .route("/projects/{name}/readyz", get(project_readyz))
+        .with_state(HttpState {
+            mcp,
+            projects,
+            token: token.into(),
+        });
 
-    let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{}", port)).await?;
-    info!("MCP WebSocket server listening on 127.0.0.1:{}", port);
+    let addr = listener.local_addr()?;
+    if addr.ip().is_loopback() {
+        info!("MCP WebSocket server listening on {}", addr);
+    } else {
+        warn!(
+            "MCP WebSocket server listening on {}, reachable from other hosts",
+            addr
+        );
+    }
 
     axum::serve(listener, app)
         .with_graceful_shutdown(async move {
@@ -106,11 +403,112 @@ pub async fn run_http_server(
     Ok(())
 }
 
+/// Whether `headers` carry `Authorization: Bearer <token>`; also guards the
+/// remote index server
+pub fn is_authorized(headers: &HeaderMap, token: &str) -> bool {
+    let Some(presented) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    // Compare every byte of the token whatever was presented, so the time
+    // taken does not tell how much of it matched
+    let presented = presented.as_bytes();
+    let diff = token
+        .bytes()
+        .enumerate()
+        .fold(presented.len() ^ token.len(), |diff, (i, b)| {
+            diff | usize::from(presented.get(i).copied().unwrap_or(0) ^ b)
+        });
+    std::hint::black_box(diff) == 0
+}
+
+/// Liveness: the server is up and accepting connections
+async fn healthz() -> &'static str {
+    "ok"
+}
+
+/// Readiness: 200 once the index is built, 503 before; both carry the status
+async fn readyz(State(state): State<HttpState>) -> impl axum::response::IntoResponse {
+    readiness(&state.mcp)
+}
+
+async fn project_readyz(
+    UrlPath(name): UrlPath<String>,
+    State(state): State<HttpState>,
+) -> axum::response::Response {
+    match state.projects.get(&name) {
+        Some(mcp) => readiness(&mcp).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+fn readiness(mcp: &McpServer) -> (StatusCode, Json<IndexingStatus>) {
+    let status = mcp.indexing_status().snapshot();
+    let code = if status.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (code, Json(status))
+}
+
+/// Runtime metrics of the default project's engine, for Prometheus to
+/// scrape; 503 until it is built
+async fn metrics(headers: HeaderMap, State(state): State<HttpState>) -> axum::response::Response {
+    if !is_authorized(&headers, &state.token) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    let Ok(engine) = state.mcp.get_or_build_index().await else {
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    };
+    match engine.runtime_metrics().await {
+        Ok(metrics) => (
+            [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+            metrics.to_prometheus(),
+        )
+            .into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// The hosted projects with their endpoints and indexing status
+async fn list_projects(
+    headers: HeaderMap,
+    State(state): State<HttpState>,
+) -> axum::response::Response {
+    if !is_authorized(&headers, &state.token) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    Json(state.projects.entries()).into_response()
+}
+
 async fn mcp_ws_handler(
     ws: WebSocketUpgrade,
-    State(mcp): State<McpServer>,
-) -> impl axum::response::IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, mcp))
+    headers: HeaderMap,
+    State(state): State<HttpState>,
+) -> axum::response::Response {
+    if !is_authorized(&headers, &state.token) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    ws.on_upgrade(move |socket| handle_socket(socket, state.mcp.for_connection()))
+}
+
+async fn project_ws_handler(
+    ws: WebSocketUpgrade,
+    UrlPath(name): UrlPath<String>,
+    headers: HeaderMap,
+    State(state): State<HttpState>,
+) -> axum::response::Response {
+    if !is_authorized(&headers, &state.token) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    let Some(mcp) = state.projects.get(&name) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    ws.on_upgrade(move |socket| handle_socket(socket, mcp.for_connection()))
 }
 
 async fn handle_socket(socket: WebSocket, mcp: McpServer) {
@@ -174,3 +572,100 @@ async fn handle_socket(socket: WebSocket, mcp: McpServer) {
         _ = (&mut mcp_to_ws) => { ws_to_mcp.abort(); },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn taken_port_falls_back_to_a_free_one() {
+        let host: IpAddr = Ipv4Addr::LOCALHOST.into();
+        let first = bind_listener(host, None).await.unwrap();
+        let port = first.local_addr().unwrap().port();
+
+        let second = bind_listener(host, Some(port)).await.unwrap();
+        assert_ne!(second.local_addr().unwrap().port(), port);
+
+        // Once the port is released, it is bound again right away
+        drop(first);
+        let third = bind_listener(host, Some(port)).await.unwrap();
+        assert_eq!(third.local_addr().unwrap().port(), port);
+    }
+
+    /// Status line of a plain HTTP GET of `path`
+    async fn get_status(addr: SocketAddr, path: &str, token: Option<&str>) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let auth = token
+            .map(|t| format!("Authorization: Bearer {}\r\n", t))
+            .unwrap_or_default();
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: localhost\r\n{}Connection: close\r\n\r\n",
+            path, auth
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response.lines().next().unwrap_or_default().to_string()
+    }
+
+    #[tokio::test]
+    async fn projects_are_routed_by_name() {
+        let server = || McpServer::new(Arc::new(RwLock::new(None)));
+        let routes = ProjectRoutes::default();
+        assert_eq!(routes.register(Path::new("/ws/app"), server()), "app");
+        assert_eq!(routes.register(Path::new("/other/app"), server()), "app-2");
+        assert_eq!(routes.register(Path::new("/ws/my lib"), server()), "my-lib");
+        routes.get("app").unwrap().indexing_status().ready();
+
+        let listener = bind_listener(Ipv4Addr::LOCALHOST.into(), None)
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let cancel_token = CancellationToken::new();
+        let serve = serve_listener(
+            server(),
+            routes,
+            listener,
+            "abc".to_string(),
+            cancel_token.clone(),
+        );
+        tokio::spawn(async move { serve.await.is_ok() });
+
+        let ok = "HTTP/1.1 200 OK";
+        assert_eq!(get_status(addr, "/projects/app/readyz", None).await, ok);
+        assert!(
+            get_status(addr, "/projects/app-2/readyz", None)
+                .await
+                .contains("503")
+        );
+        assert!(
+            get_status(addr, "/projects/gone/readyz", None)
+                .await
+                .contains("404")
+        );
+        assert!(get_status(addr, "/projects", None).await.contains("401"));
+        assert_eq!(get_status(addr, "/projects", Some("abc")).await, ok);
+        cancel_token.cancel();
+    }
+
+    #[test]
+    fn bearer_token_is_required() {
+        let mut headers = HeaderMap::new();
+        assert!(!is_authorized(&headers, "abc"));
+        headers.insert(header::AUTHORIZATION, "Bearer abd".parse().unwrap());
+        assert!(!is_authorized(&headers, "abc"));
+        headers.insert(header::AUTHORIZATION, "abc".parse().unwrap());
+        assert!(!is_authorized(&headers, "abc"));
+        // Neither a prefix nor an extension of the token passes
+        headers.insert(header::AUTHORIZATION, "Bearer ab".parse().unwrap());
+        assert!(!is_authorized(&headers, "abc"));
+        headers.insert(header::AUTHORIZATION, "Bearer abcd".parse().unwrap());
+        assert!(!is_authorized(&headers, "abc"));
+        headers.insert(header::AUTHORIZATION, "Bearer abc".parse().unwrap());
+        assert!(is_authorized(&headers, "abc"));
+
+        let token = generate_token();
+        assert_eq!(token.len(), 32);
+        assert_ne!(token, generate_token());
+    }
+}
@@ -1,75 +1,142 @@
-use naviscope_api::graph::GraphService;
-use naviscope_api::models::{EdgeType, GraphQuery, NodeKind};
+use naviscope_api::graph::QueryControl;
+use naviscope_api::lifecycle::{ToolDefaults, WarningSeverity};
+use naviscope_api::models::graph::{NodeSource, ResolutionStatus};
+use naviscope_api::models::{
+    DetailLimits, EdgeType, GraphQuery, GroupBy, NodeKind, TypeUsageFilter,
+};
+use naviscope_api::{ApiError, NaviscopeEngine};
 use rmcp::{
     ErrorData as McpError,
     handler::server::{tool::ToolRouter, wrapper::Parameters},
-    model::{CallToolResult, Content, Implementation, InitializeResult, ServerCapabilities},
+    model::{
+        CallToolResult, Content, Implementation, InitializeResult, ListResourceTemplatesResult,
+        ListResourcesResult, LoggingLevel, LoggingMessageNotificationParam, PaginatedRequestParams,
+        ProgressNotificationParam, ReadResourceRequestParams, ReadResourceResult, ResourceContents,
+        ResourceUpdatedNotificationParam, ServerCapabilities, SubscribeRequestParams,
+        UnsubscribeRequestParams,
+    },
+    service::{NotificationContext, Peer, RequestContext, RoleServer},
     tool, tool_handler, tool_router,
 };
 use schemars::JsonSchema;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 use xxhash_rust::xxh3::xxh3_64;
 
+mod audit;
+mod calls;
 pub mod http;
+pub mod provider;
 pub mod proxy;
+pub mod resources;
+pub mod session;
+pub mod source;
+pub mod status;
 pub mod stdio;
 
-#[derive(serde::Serialize, serde::Deserialize)]
-pub struct SessionInfo {
-    pub port: u16,
-    pub pid: u32,
-    pub root_path: PathBuf,
-}
+pub use provider::EngineProvider;
+use resources::Connection;
+pub use resources::ResourceUri;
+pub use session::{SessionInfo, get_session_path, relocate_session, remove_dead_sessions};
+pub use status::{IndexingState, IndexingStatus, StatusTracker};
 
-pub fn get_session_path(root_path: &Path) -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    let session_dir = Path::new(&home).join(".naviscope/sessions");
-    let _ = std::fs::create_dir_all(&session_dir);
+/// How often `follow_commits` checks for a new or replaced engine
+const COMMIT_RECHECK: Duration = Duration::from_secs(5);
 
-    let abs_path = root_path
-        .canonicalize()
-        .unwrap_or_else(|_| root_path.to_path_buf());
-    let hash = xxh3_64(abs_path.to_string_lossy().as_bytes());
-    session_dir.join(format!("{:016x}.json", hash))
-}
+/// Positions `resolve_batch` accepts per call
+const MAX_BATCH_POSITIONS: usize = 1000;
+
+/// Follows results shortened to the project's `[detail.mcp]` limits
+const ABRIDGED_HINT: &str = "Some signatures were shortened (`…`) or members left out. \
+Call `cat` with `full: true` for the complete details, or `ls` to list every member.";
+
+const TRUNCATED_HINT: &str = "The query was cut short by its time or result budget, so the \
+result is partial; `total` counts every match when known. Narrow it with `kind`, `edge_types` \
+or a more specific pattern, or use `count`/`group_by` for an overview.";
 
 #[derive(Clone)]
 pub struct McpServer {
     pub(crate) tool_router: Arc<ToolRouter<Self>>,
-    pub(crate) engine: Arc<RwLock<Option<Arc<dyn GraphService>>>>,
+    pub(crate) engine: EngineProvider,
+    pub(crate) peers: Arc<RwLock<Vec<Connection>>>,
+    pub(crate) calls: Arc<calls::CallRegistry>,
+    pub(crate) status: StatusTracker,
+    /// Resources the client of this connection subscribed to
+    pub(crate) subscriptions: resources::Subscriptions,
+}
+
+/// Map an engine error to a tool error; cancellation uses the LSP `RequestCancelled` code
+fn api_error(e: ApiError) -> McpError {
+    match e {
+        ApiError::Cancelled => McpError::new(
+            rmcp::model::ErrorCode(-32800),
+            "Request cancelled".to_string(),
+            None,
+        ),
+        other => McpError::new(rmcp::model::ErrorCode(-32000), other.to_string(), None),
+    }
 }
 
 #[derive(Deserialize, JsonSchema)]
 pub struct FindArgs {
     /// Search pattern (simple string or regex) for code element names
     pub pattern: String,
-    /// Optional: Filter by element type.
+    /// Optional: Filter by element type (default: project setting, else all).
     pub kind: Option<Vec<NodeKind>>,
-    /// Optional: Filter by node source (project, external, builtin).
-    pub sources: Option<Vec<naviscope_api::models::graph::NodeSource>>,
-    /// Maximum number of results to return (default: 20)
+    /// Optional: Filter by node source (default: project setting, else all).
+    pub sources: Option<Vec<NodeSource>>,
+    /// Optional: Filter by resolution status, e.g. ["resolved"] to skip library stubs
+    /// or ["stubbed"] to keep only them (default: project setting, else all).
+    pub statuses: Option<Vec<ResolutionStatus>>,
+    /// Optional: Keep elements with all these modifiers, e.g. ["vulnerable"] with kind
+    /// ["dependency"] for libraries with a known advisory, or ["license:MIT"].
+    pub modifiers: Option<Vec<String>>,
+    /// Maximum number of results to return (default: project setting, else 20)
     pub limit: Option<usize>,
+    /// Optional: Return per-group counts (kind, package or module) instead of nodes.
+    pub group_by: Option<GroupBy>,
+    /// If true, return only the number of matches.
+    #[serde(default)]
+    pub count: bool,
+    /// If true, match the pattern as an abbreviation of the name (e.g. `UsrCtl` for
+    /// `UserController`) and rank results best first. Ignores group_by and count.
+    #[serde(default)]
+    pub fuzzy: bool,
 }
 
 #[derive(Deserialize, JsonSchema)]
 pub struct LsArgs {
-    /// Target node FQN to list children for. If null, lists top-level modules.
+    /// Target node FQN to list children for, or `file:<path>` for the symbols a
+    /// file declares (relative paths work). If null, lists top-level modules.
     pub fqn: Option<String>,
-    /// Optional: Filter results by element type.
+    /// Optional: Filter results by element type (default: project setting, else all).
     pub kind: Option<Vec<NodeKind>>,
-    /// Optional: Filter by node source (project, external, builtin).
-    pub sources: Option<Vec<naviscope_api::models::graph::NodeSource>>,
+    /// Optional: Filter by node source (default: project setting, else all).
+    pub sources: Option<Vec<NodeSource>>,
+    /// Optional: Filter by resolution status, e.g. ["resolved"] to skip library stubs
+    /// or ["stubbed"] to keep only them (default: project setting, else all).
+    pub statuses: Option<Vec<ResolutionStatus>>,
     /// Optional: Filter results by modifiers (e.g. ["public", "static"])
     pub modifiers: Option<Vec<String>>,
+    /// Optional: Return per-group counts (kind, package or module) instead of nodes.
+    pub group_by: Option<GroupBy>,
+    /// If true, return only the number of children.
+    #[serde(default)]
+    pub count: bool,
 }
 
 #[derive(Deserialize, JsonSchema)]
 pub struct CatArgs {
     /// The Fully Qualified Name (FQN) of the code element to inspect
     pub fqn: String,
+    /// Return complete signatures and every member. By default long generic
+    /// signatures are shortened and large member lists summarized.
+    pub full: Option<bool>,
 }
 
 #[derive(Deserialize, JsonSchema)]
@@ -80,51 +147,517 @@ pub struct DepsArgs {
     /// If false (default), find outgoing dependencies (who do I depend on).
     #[serde(default)]
     pub rev: bool,
-    /// Optional: Filter by relationship types.
+    /// Optional: Filter by relationship types (default: project setting, else all).
+    pub edge_type: Option<Vec<EdgeType>>,
+    /// Optional: Keep related nodes from these sources (project, external, builtin).
+    pub sources: Option<Vec<NodeSource>>,
+    /// Optional: Keep related nodes with these resolution statuses.
+    pub statuses: Option<Vec<ResolutionStatus>>,
+    /// Optional: Return per-group counts (kind, package or module) instead of nodes.
+    pub group_by: Option<GroupBy>,
+    /// If true, return only the number of related nodes.
+    #[serde(default)]
+    pub count: bool,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ImpactArgs {
+    /// The Fully Qualified Name (FQN) of the code element about to change
+    pub fqn: String,
+    /// Maximum number of dependency hops to follow (default: 5)
+    pub max_depth: Option<usize>,
+    /// Optional: Relationship types to follow (default: project setting, else all but Contains and Declares).
     pub edge_type: Option<Vec<EdgeType>>,
 }
 
+#[derive(Deserialize, JsonSchema)]
+pub struct CyclesArgs {
+    /// Optional: Only look for cycles inside this package, module or class (FQN)
+    pub scope_fqn: Option<String>,
+    /// Optional: Relationship types to follow (default: project setting, else all but Contains).
+    pub edge_type: Option<Vec<EdgeType>>,
+    /// If true, report cycles between packages instead of classes.
+    #[serde(default)]
+    pub packages: bool,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DuplicatesArgs {
+    /// Ignore methods with fewer normalized tokens (default: 50)
+    pub min_tokens: Option<usize>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DeadArgs {
+    /// Optional: Only report symbols inside this package, module or class (FQN)
+    pub scope_fqn: Option<String>,
+    /// Optional: Entry points besides tests, `main`, framework callbacks and the
+    /// project's `[query] entry_points`: "@Scheduled" for an annotation, "run" for a method
+    pub entry_points: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct TestsForArgs {
+    /// The FQN of a class, of one of its members, or of a package whose classes to cover
+    pub fqn: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct SearchTextArgs {
+    /// Text to search for literally (e.g. a SQL fragment or log message)
+    pub pattern: String,
+    /// If true, match ASCII letters case-insensitively.
+    #[serde(default)]
+    pub ignore_case: bool,
+    /// Maximum number of hits (default: project setting, else 20)
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct SafeDeleteArgs {
+    /// The Fully Qualified Name (FQN) of the code element to remove
+    pub fqn: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ExternalApiUsageArgs {
+    /// Keep only libraries whose name contains this, e.g. "guava" or "jackson-databind"
+    pub artifact: Option<String>,
+    /// Maximum symbols listed per package (default: 10)
+    pub top: Option<usize>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct IndexWarningsArgs {
+    /// Keep only warnings about files whose path contains this
+    pub path: Option<String>,
+    /// Minimum severity: "info", "warning" (default) or "error"
+    pub severity: Option<WarningSeverity>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct FindReferencesArgs {
+    /// The Fully Qualified Name (FQN) of the code element to look up
+    pub fqn: String,
+    /// Type-only usages (field, parameter, return types...): "include" (default), "exclude" or "only"
+    #[serde(default)]
+    pub type_usages: TypeUsageFilter,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ResolveAtArgs {
+    /// Absolute path of the source file
+    pub path: String,
+    /// 1-based line number
+    pub line: u32,
+    /// 1-based column (character) number
+    pub column: u32,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct SourcePosition {
+    /// 1-based line number
+    pub line: u32,
+    /// 1-based column (character) number
+    pub column: u32,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ResolveBatchArgs {
+    /// Absolute path of the source file
+    pub path: String,
+    /// Positions to resolve, at most 1000
+    pub positions: Vec<SourcePosition>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ReadSourceArgs {
+    /// The Fully Qualified Name (FQN) of the code element to read
+    pub fqn: Option<String>,
+    /// Absolute path of an indexed source file to read instead of an element
+    pub path: Option<String>,
+    /// Optional: First line, 1-based (default: the element's first line, or the start of the file)
+    pub start_line: Option<usize>,
+    /// Optional: Last line, 1-based and inclusive (default: the element's last line, or the end of the file)
+    pub end_line: Option<usize>,
+    /// Optional: Lines of context before and after the range (default: 2)
+    pub context: Option<usize>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct AnnotateArgs {
+    /// The Fully Qualified Name (FQN) of the code element to annotate
+    pub fqn: String,
+    /// Optional: Free-form note to attach
+    pub note: Option<String>,
+    /// Optional: Tags to attach with the note, e.g. ["hot-path", "legacy"]
+    pub tags: Option<Vec<String>>,
+    /// Optional: Id of a note to remove from the element instead
+    pub remove: Option<u64>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ValidateQueryArgs {
+    /// The graph query to check, e.g. {"command": "find", "pattern": "Order", "kind": ["class"]}
+    pub query: serde_json::Value,
+}
+
 #[derive(Deserialize, JsonSchema)]
 pub struct GetGuideArgs {}
 
+#[derive(Deserialize, JsonSchema)]
+pub struct StatusArgs {}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct RuntimeMetricsArgs {}
+
 #[tool_router]
 impl McpServer {
-    pub fn new(engine: Arc<RwLock<Option<Arc<dyn GraphService>>>>) -> Self {
+    pub fn new(engine: impl Into<EngineProvider>) -> Self {
         Self {
             tool_router: Arc::new(Self::tool_router()),
-            engine,
+            engine: engine.into(),
+            peers: Arc::new(RwLock::new(Vec::new())),
+            calls: Arc::new(calls::CallRegistry::default()),
+            status: StatusTracker::new(),
+            subscriptions: Default::default(),
+        }
+    }
+
+    /// Handle for serving one more connection: shares the engine and
+    /// status, with subscriptions of its own.
+    pub fn for_connection(&self) -> Self {
+        Self {
+            subscriptions: Default::default(),
+            ..self.clone()
+        }
+    }
+
+    /// Indexing status served by `/readyz` and the `status` tool; the
+    /// indexer feeding this server's engine updates it.
+    pub fn indexing_status(&self) -> &StatusTracker {
+        &self.status
+    }
+
+    /// Servers handed an engine up front have no indexer to report readiness.
+    pub(crate) async fn mark_ready_if_loaded(&self) {
+        if self.engine.current().await.is_some() {
+            self.status.ready();
+        }
+    }
+
+    /// Notify every connected client that the server now serves a different project root.
+    ///
+    /// Clients whose transport has closed are dropped from the peer list. Calls
+    /// still running against the previous root are cancelled.
+    pub async fn notify_root_changed(&self, root_path: &Path) {
+        self.calls.cancel_all();
+
+        for peer in self.live_peers().await {
+            let param = LoggingMessageNotificationParam {
+                level: LoggingLevel::Notice,
+                logger: Some("naviscope".to_string()),
+                data: serde_json::json!({
+                    "event": "root_changed",
+                    "root_path": root_path,
+                }),
+            };
+            if let Err(e) = peer.notify_logging_message(param).await {
+                tracing::warn!("Failed to notify MCP client of root change: {}", e);
+            }
+        }
+    }
+
+    /// Connected clients, dropping those whose transport has closed
+    async fn live_connections(&self) -> Vec<Connection> {
+        let mut peers = self.peers.write().await;
+        peers.retain(|connection| !connection.peer.is_transport_closed());
+        peers.clone()
+    }
+
+    async fn live_peers(&self) -> Vec<Peer<RoleServer>> {
+        self.live_connections()
+            .await
+            .into_iter()
+            .map(|connection| connection.peer)
+            .collect()
+    }
+
+    /// Tell every client that the index changed: `resources/list_changed` to
+    /// all, and `resources/updated` for subscribed resources whose content
+    /// differs from what the client last saw.
+    pub async fn notify_graph_changed(&self) {
+        let Some(engine) = self.engine.current().await else {
+            return;
+        };
+        // Read each subscribed resource once, however many clients follow it
+        let mut hashes: HashMap<String, Option<u64>> = HashMap::new();
+        for connection in self.live_connections().await {
+            if let Err(e) = connection.peer.notify_resource_list_changed().await {
+                tracing::warn!("Failed to notify MCP client of index change: {}", e);
+                continue;
+            }
+            let subscribed: Vec<(String, Option<u64>)> = connection
+                .subscriptions
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .map(|(uri, hash)| (uri.clone(), *hash))
+                .collect();
+            for (uri, seen) in subscribed {
+                let hash = match hashes.get(&uri) {
+                    Some(hash) => *hash,
+                    None => {
+                        let hash = Self::resource_hash(&engine, &uri).await;
+                        hashes.insert(uri.clone(), hash);
+                        hash
+                    }
+                };
+                if hash == seen {
+                    continue;
+                }
+                if let Some(entry) = connection
+                    .subscriptions
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .get_mut(&uri)
+                {
+                    *entry = hash;
+                }
+                let param = ResourceUpdatedNotificationParam { uri };
+                if let Err(e) = connection.peer.notify_resource_updated(param).await {
+                    tracing::warn!("Failed to notify MCP client of resource update: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Send change notifications after every index commit until `cancel_token`
+    /// is cancelled. Follows the engine the host currently serves.
+    pub async fn follow_commits(&self, cancel_token: CancellationToken) {
+        let mut epoch = None;
+        loop {
+            let engine = self.engine.current().await;
+            let since = match (&engine, epoch) {
+                (Some(_), Some(epoch)) => Some(epoch),
+                (Some(engine), None) => engine.graph_epoch().await.ok(),
+                (None, _) => None,
+            };
+            let (Some(engine), Some(since)) = (engine, since) else {
+                // No index yet
+                if tokio::time::timeout(COMMIT_RECHECK, cancel_token.cancelled())
+                    .await
+                    .is_ok()
+                {
+                    break;
+                }
+                continue;
+            };
+            epoch = Some(since);
+
+            tokio::select! {
+                _ = cancel_token.cancelled() => break,
+                // Time out now and then in case the host replaced the engine
+                committed = tokio::time::timeout(COMMIT_RECHECK, engine.wait_for_commit(since)) => {
+                    match committed {
+                        Ok(Ok(Some(next))) => {
+                            epoch = Some(next);
+                            self.notify_graph_changed().await;
+                        }
+                        // The engine does not report commits
+                        Ok(Ok(None)) => tokio::time::sleep(COMMIT_RECHECK).await,
+                        Ok(Err(e)) => {
+                            tracing::warn!("Failed to follow index commits: {}", e);
+                            tokio::time::sleep(COMMIT_RECHECK).await;
+                        }
+                        Err(_) => {}
+                    }
+                }
+            }
         }
     }
 
-    pub(crate) async fn get_or_build_index(&self) -> Result<Arc<dyn GraphService>, McpError> {
-        let lock = self.engine.read().await;
+    /// JSON content of the resource at `uri`
+    async fn resource_text(
+        engine: &Arc<dyn NaviscopeEngine>,
+        uri: &str,
+    ) -> Result<String, McpError> {
+        let json = match ResourceUri::parse(uri) {
+            Some(ResourceUri::Node(fqn)) => {
+                let node = engine
+                    .get_node_display(&fqn)
+                    .await
+                    .map_err(api_error)?
+                    .ok_or_else(|| {
+                        McpError::resource_not_found(format!("No node {}", fqn), None)
+                    })?;
+                serde_json::to_string_pretty(
+                    &resources::NodeResource::load(engine.as_ref(), node).await,
+                )
+            }
+            Some(ResourceUri::File(path)) => {
+                let symbols = engine
+                    .get_document_symbols(&path.to_string_lossy())
+                    .await
+                    .map_err(api_error)?;
+                serde_json::to_string_pretty(&resources::FileResource { path, symbols })
+            }
+            None => {
+                return Err(McpError::invalid_params(
+                    format!("Unknown resource URI: {}", uri),
+                    None,
+                ));
+            }
+        };
+        json.map_err(|e| McpError::internal_error(e.to_string(), None))
+    }
+
+    /// Hash of a resource's content, `None` if it cannot be read (e.g. its node was removed)
+    async fn resource_hash(engine: &Arc<dyn NaviscopeEngine>, uri: &str) -> Option<u64> {
+        Self::resource_text(engine, uri)
+            .await
+            .ok()
+            .map(|text| xxh3_64(text.as_bytes()))
+    }
 
-        match &*lock {
-            Some(handle) => Ok(handle.clone()),
+    pub(crate) async fn get_or_build_index(&self) -> Result<Arc<dyn NaviscopeEngine>, McpError> {
+        match self.engine.current().await {
+            Some(handle) => Ok(handle),
             None => {
                 // Index not yet built by LSP, return error
                 Err(McpError::new(
                     rmcp::model::ErrorCode(-32000),
-                    "Index not yet available. The LSP server is still building the index. Please wait a moment and try again.".to_string(),
-                    None
+                    format!(
+                        "Index not yet available ({}). Poll the `status` tool until it reports `ready`.",
+                        self.status.snapshot().describe()
+                    ),
+                    None,
                 ))
             }
         }
     }
 
+    /// Argument defaults for `tool` from the project's `[mcp.tools.<tool>]`
+    pub(crate) async fn tool_defaults(&self, tool: &str) -> ToolDefaults {
+        let Some(engine) = self.engine.current().await else {
+            return ToolDefaults::default();
+        };
+        match engine.host_settings().await {
+            Ok(mut settings) => settings.mcp_tools.remove(tool).unwrap_or_default(),
+            Err(e) => {
+                tracing::warn!("Failed to read MCP tool defaults: {}", e);
+                ToolDefaults::default()
+            }
+        }
+    }
+
+    /// Size limits of node details from the project's `[detail.mcp]`
+    async fn detail_limits(&self) -> DetailLimits {
+        let Some(engine) = self.engine.current().await else {
+            return DetailLimits::MCP;
+        };
+        match engine.host_settings().await {
+            Ok(settings) => settings.mcp_detail.unwrap_or(DetailLimits::MCP),
+            Err(e) => {
+                tracing::warn!("Failed to read MCP detail limits: {}", e);
+                DetailLimits::MCP
+            }
+        }
+    }
+
     pub(crate) async fn execute_query(
         &self,
         query: GraphQuery,
+        ctx: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
         let engine = self.get_or_build_index().await?;
+        let _call = self.calls.register(ctx.id.clone(), ctx.ct.clone());
 
-        // EngineHandle now handles async execution and error mapping internally
-        let result = engine
-            .query(&query)
-            .await
-            .map_err(|e| McpError::new(rmcp::model::ErrorCode(-32000), e.to_string(), None))?;
+        let mut control = QueryControl::new();
+        if let Some(progress_token) = ctx.meta.get_progress_token() {
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            control = control.with_progress(move |done, total| {
+                let _ = tx.send((done, total));
+            });
+            let peer = ctx.peer.clone();
+            // Ends once the query finishes and drops the sender
+            tokio::spawn(async move {
+                while let Some((done, total)) = rx.recv().await {
+                    let param = ProgressNotificationParam {
+                        progress_token: progress_token.clone(),
+                        progress: done as f64,
+                        total: total.map(|t| t as f64),
+                        message: None,
+                    };
+                    if peer.notify_progress(param).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        let result = {
+            let query_fut = engine.query_with_control(&query, &control);
+            tokio::pin!(query_fut);
+            tokio::select! {
+                result = &mut query_fut => result,
+                _ = ctx.ct.cancelled() => {
+                    // The engine checks the control between traversal steps
+                    control.cancel();
+                    query_fut.await
+                }
+            }
+        };
+
+        let result = result.map_err(api_error)?;
 
         match serde_json::to_string_pretty(&result) {
+            Ok(json_str) => {
+                let mut content = vec![Content::text(json_str)];
+                if result.abridged {
+                    content.push(Content::text(ABRIDGED_HINT));
+                }
+                if result.truncated {
+                    content.push(Content::text(TRUNCATED_HINT));
+                }
+                Ok(CallToolResult::success(content))
+            }
+            Err(e) => Err(McpError::new(
+                rmcp::model::ErrorCode(-32000),
+                e.to_string(),
+                None,
+            )),
+        }
+    }
+
+    #[tool(
+        description = "Report whether the index is ready: `state` (starting, indexing, ready, failed, cancelled), the current indexing `phase` and overall `percent` complete. Poll this after connecting instead of retrying failed queries."
+    )]
+    pub async fn status(
+        &self,
+        _params: Parameters<StatusArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        match serde_json::to_string_pretty(&self.status.snapshot()) {
+            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+            Err(e) => Err(McpError::new(
+                rmcp::model::ErrorCode(-32000),
+                e.to_string(),
+                None,
+            )),
+        }
+    }
+
+    #[tool(
+        description = "Report the engine's index update counters since it started: files handed to updates (`runnable`), file updates held back by unsaved edits (`deferred`), committed snapshots (`committed`) and a histogram of update latencies (`execute_latency`). Use this to diagnose a slow or stalled index."
+    )]
+    pub async fn runtime_metrics(
+        &self,
+        _params: Parameters<RuntimeMetricsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let engine = self.get_or_build_index().await?;
+        let metrics = engine.runtime_metrics().await.map_err(api_error)?;
+        match serde_json::to_string_pretty(&metrics) {
             Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
             Err(e) => Err(McpError::new(
                 rmcp::model::ErrorCode(-32000),
@@ -154,15 +687,44 @@ Naviscope is a graph-based code understanding engine. Unlike text search, it und
 
 2. **Find Entry Points**: Use `find` to locate specific symbols (classes, methods) by name.
    - `find(pattern="UserController", kind=["class"])`
+   - `find(pattern="UsrCtl", fuzzy=true)` -> Ranked abbreviation match when you only half-remember a name
+   - `search_text(pattern="order not found")` -> Source lines containing arbitrary text, with the enclosing node of each hit
 
 3. **Deep Analysis**: Once you have a Fully Qualified Name (FQN), use `cat` and `deps`.
    - `cat(fqn="...")` -> View source code and metadata
    - `deps(fqn="...")` -> View outgoing dependencies (What does this code use?)
    - `deps(fqn="...", rev=true)` -> View incoming dependencies (Who uses this code?)
+   - `find_references(fqn="...", type_usages="exclude")` -> Exact reference locations, without declared-type mentions
+   - `impact(fqn="...")` -> Classes and methods that may break if this changes, grouped by module
+   - `cycles(scope_fqn="com.example")` -> Dependency cycles between classes (`packages=true` for packages)
+   - `duplicates(min_tokens=50)` -> Groups of near-duplicate methods, e.g. pasted code with renamed variables
+
+4. **From a Location**: Use `resolve_at` to turn a file position (stack trace, grep hit) into an FQN.
+   - `resolve_at(path="/abs/path/Foo.java", line=42, column=17)` -> FQN and node of the symbol there
+   - `resolve_batch(path="/abs/path/Foo.java", positions=[{line: 42, column: 17}, ...])` -> The same for many positions of one file in one call
+   - `read_source(fqn="...")` -> The element's source lines with a little context
+   - `read_source(path="/abs/path/Foo.java", start_line=40, end_line=60)` -> Lines of an indexed file
+
+5. **Clean Up**: Before removing a symbol, use `safe_delete` to check it is unused.
+   - `dead(scope_fqn="com.example")` -> Public types and methods nothing is known to use, candidates for removal
+   - `tests_for(fqn="...")` -> Test methods exercising the class, to run or extend before changing it
+   - `safe_delete(fqn="...")` -> Verdict (`safe`, `review`, `unsafe`) and the usages blocking removal
+   - `external_api_usage(artifact="guava")` -> Which parts of a library the project uses, before upgrading or replacing it
+   - `index_warnings(path="Order.java")` -> What the indexer skipped in a file, when a symbol you expect is missing
+
+6. **Remember**: Leave notes on code elements for later sessions; `cat` shows them.
+   - `annotate(fqn="...", note="Retries are handled by the caller", tags=["invariant"])`
+   - `annotate(fqn="...", remove=2)` -> Drop a note that no longer holds
 
 ## 💡 Tips
+- **Readiness**: Right after startup the index may still be building. `status()` reports the indexing phase and percent complete; wait for `state: ready`. If updates seem slow later, `runtime_metrics()` shows how many ran and how long they took.
 - **FQNs**: Naviscope relies on Fully Qualified Names (e.g., `com.example.MyClass`, `src/main.rs`). Always use the FQN returned by `ls` or `find` for subsequent `cat`/`deps` calls.
+- **Symbol IDs**: Nodes also carry a `symbol_id` tagged with their language (e.g., `java:com.example.MyClass#run(int)`). Tools accept it wherever they take an FQN; prefer it when passing symbols to other tools.
 - **Filters**: Use the `kind` (e.g., "class", "method") and `edge_type` (e.g., "TypedAs", "InheritsFrom") filters to narrow down noisy results.
+  - `sources=["Project"]` keeps first-party code; `statuses=["stubbed"]` keeps only library stubs built from bytecode.
+- **Unsure of a filter value?** `validate_query(query={"command": "deps", "fqn": "...", "edge_types": ["Implements"]})` checks a query and suggests fixes without running it.
+- **Surveys**: For "how many" questions, pass `count=true` or `group_by` ("kind", "package", "module") to get compact counts instead of node lists.
+  - `find(pattern="^com\.app\.", kind=["class"], group_by="package")` -> Classes per package under `com.app`
 "#;
         Ok(CallToolResult::success(vec![Content::text(guide)]))
     }
@@ -170,54 +732,599 @@ Naviscope is a graph-based code understanding engine. Unlike text search, it und
     #[tool(
         description = "Search for code elements (classes, methods, fields, etc.) across the project using a name pattern or regex. Use this to find definitions when you only know a name or part of it."
     )]
-    pub async fn find(&self, params: Parameters<FindArgs>) -> Result<CallToolResult, McpError> {
+    pub async fn find(
+        &self,
+        params: Parameters<FindArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
         let args = params.0;
-        self.execute_query(GraphQuery::Find {
-            pattern: args.pattern,
-            kind: args.kind.unwrap_or_default(),
-            sources: args.sources.unwrap_or_default(),
-            limit: args.limit.unwrap_or(20),
-        })
-        .await
+        let defaults = self.tool_defaults("find").await;
+        let kind = args.kind.or(defaults.kind).unwrap_or_default();
+        let sources = args.sources.or(defaults.sources).unwrap_or_default();
+        let statuses = args.statuses.or(defaults.statuses).unwrap_or_default();
+        let limit = args.limit.or(defaults.limit).unwrap_or(20);
+        let query = if args.fuzzy {
+            GraphQuery::Fuzzy {
+                pattern: args.pattern,
+                kind,
+                sources,
+                statuses,
+                limit,
+            }
+        } else {
+            GraphQuery::Find {
+                pattern: args.pattern,
+                kind,
+                sources,
+                statuses,
+                modifiers: args.modifiers.unwrap_or_default(),
+                limit,
+                group_by: args.group_by,
+                count: args.count,
+            }
+        };
+        self.execute_query(query, ctx).await
     }
 
     #[tool(
-        description = "List sub-elements of a given node (FQN) or list top-level project modules if FQN is omitted. Use this to explore package structures or class members."
+        description = "List sub-elements of a given node (FQN) or list top-level project modules if FQN is omitted. Use this to explore package structures or class members; `file:src/main/java/App.java` lists the symbols declared in a file."
     )]
-    pub async fn ls(&self, params: Parameters<LsArgs>) -> Result<CallToolResult, McpError> {
+    pub async fn ls(
+        &self,
+        params: Parameters<LsArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
         let args = params.0;
-        self.execute_query(GraphQuery::Ls {
-            fqn: args.fqn,
-            kind: args.kind.unwrap_or_default(),
-            sources: args.sources.unwrap_or_default(),
-            modifiers: args.modifiers.unwrap_or_default(),
-        })
+        let defaults = self.tool_defaults("ls").await;
+        self.execute_query(
+            GraphQuery::Ls {
+                fqn: args.fqn,
+                kind: args.kind.or(defaults.kind).unwrap_or_default(),
+                sources: args.sources.or(defaults.sources).unwrap_or_default(),
+                statuses: args.statuses.or(defaults.statuses).unwrap_or_default(),
+                modifiers: args.modifiers.unwrap_or_default(),
+                group_by: args.group_by,
+                count: args.count,
+            },
+            ctx,
+        )
         .await
     }
 
     #[tool(
-        description = "Retrieve detailed information about a specific code element by its Fully Qualified Name (FQN), including its source code snippet, location, and metadata."
+        description = "Retrieve detailed information about a specific code element by its Fully Qualified Name (FQN), including its source code snippet, location, and metadata. Long generic signatures are shortened and large member lists summarized; pass `full: true` for everything. With git history enabled, `history` gives the last commit, author and age of the element's lines and how many commits changed them."
     )]
-    pub async fn cat(&self, params: Parameters<CatArgs>) -> Result<CallToolResult, McpError> {
+    pub async fn cat(
+        &self,
+        params: Parameters<CatArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
         let args = params.0;
-        self.execute_query(GraphQuery::Cat { fqn: args.fqn }).await
+        let limits = if args.full.unwrap_or(false) {
+            DetailLimits::UNLIMITED
+        } else {
+            self.detail_limits().await
+        };
+        self.execute_query(
+            GraphQuery::Cat {
+                fqn: args.fqn,
+                limits: Some(limits),
+            },
+            ctx,
+        )
+        .await
     }
 
     #[tool(
         description = "Analyze dependencies for a given FQN. By default, shows outgoing dependencies (who I depend on). Use rev=true for incoming dependencies (who depends on me/impact analysis)."
     )]
-    pub async fn deps(&self, params: Parameters<DepsArgs>) -> Result<CallToolResult, McpError> {
+    pub async fn deps(
+        &self,
+        params: Parameters<DepsArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
         let args = params.0;
-        self.execute_query(GraphQuery::Deps {
-            fqn: args.fqn,
-            rev: args.rev,
-            edge_types: args.edge_type.unwrap_or_default(),
-        })
+        let defaults = self.tool_defaults("deps").await;
+        self.execute_query(
+            GraphQuery::Deps {
+                fqn: args.fqn,
+                rev: args.rev,
+                edge_types: args.edge_type.or(defaults.edge_type).unwrap_or_default(),
+                sources: args.sources.or(defaults.sources).unwrap_or_default(),
+                statuses: args.statuses.or(defaults.statuses).unwrap_or_default(),
+                group_by: args.group_by,
+                count: args.count,
+            },
+            ctx,
+        )
+        .await
+    }
+
+    #[tool(
+        description = "Impact analysis for a code element (FQN): follows incoming dependencies transitively and returns the classes and methods that may break if it changes, ordered by module, with per-module counts in `groups`. Use this before changing a signature or contract."
+    )]
+    pub async fn impact(
+        &self,
+        params: Parameters<ImpactArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = params.0;
+        let defaults = self.tool_defaults("impact").await;
+        self.execute_query(
+            GraphQuery::Impact {
+                fqn: args.fqn,
+                max_depth: args.max_depth.unwrap_or(5),
+                edge_types: args.edge_type.or(defaults.edge_type).unwrap_or_default(),
+            },
+            ctx,
+        )
+        .await
+    }
+
+    #[tool(
+        description = "Find dependency cycles between classes (or packages with packages=true), optionally within a scope FQN. Nodes are ordered by cycle, largest first; `groups` gives each cycle's size and `edges` the dependencies closing it."
+    )]
+    pub async fn cycles(
+        &self,
+        params: Parameters<CyclesArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = params.0;
+        let defaults = self.tool_defaults("cycles").await;
+        self.execute_query(
+            GraphQuery::Cycles {
+                scope_fqn: args.scope_fqn,
+                edge_types: args.edge_type.or(defaults.edge_type).unwrap_or_default(),
+                packages: args.packages,
+            },
+            ctx,
+        )
+        .await
+    }
+
+    #[tool(
+        description = "Find near-duplicate methods: copies whose token streams match once identifiers and literals are ignored, e.g. pasted code with renamed variables. Nodes are ordered by clone group, largest methods first, with their locations; `groups` gives each group's size, keyed by its first member. Use `deps`/`impact` on the members to decide which copy to keep."
+    )]
+    pub async fn duplicates(
+        &self,
+        params: Parameters<DuplicatesArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        self.execute_query(
+            GraphQuery::Duplicates {
+                min_tokens: params.0.min_tokens.unwrap_or(50),
+            },
+            ctx,
+        )
         .await
     }
+
+    #[tool(
+        description = "Find unused code: public project types and methods that no edge reaches from outside, that no other file mentions by name and that override nothing. Tests, `main`, framework callbacks (e.g. @Bean, @Scheduled) and `entry_points` are kept, as are test sources; members of an unused type are reported through the type. Check candidates with `safe_delete` before removing them."
+    )]
+    pub async fn dead(
+        &self,
+        params: Parameters<DeadArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = params.0;
+        self.execute_query(
+            GraphQuery::Dead {
+                scope_fqn: args.scope_fqn,
+                entry_points: args.entry_points.unwrap_or_default(),
+            },
+            ctx,
+        )
+        .await
+    }
+
+    #[tool(
+        description = "Find the tests covering a class: JUnit/TestNG test methods linked to it by `Tests` edges. For a member, the tests of its class; for a package, those of all its classes. A test is linked to the class it is named after (`OrderServiceTest` for `OrderService`), to the types of its @InjectMocks/@Autowired fields and to the project types its body uses; `edges` tell which class each test covers."
+    )]
+    pub async fn tests_for(
+        &self,
+        params: Parameters<TestsForArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        self.execute_query(GraphQuery::TestsFor { fqn: params.0.fqn }, ctx)
+            .await
+    }
+
+    #[tool(
+        description = "Full-text search over indexed source files for arbitrary strings such as SQL fragments or log messages. Returns matching lines in `hits` (path, zero-based line and column, text, enclosing node FQN) and the enclosing nodes in `nodes`."
+    )]
+    pub async fn search_text(
+        &self,
+        params: Parameters<SearchTextArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = params.0;
+        let defaults = self.tool_defaults("search_text").await;
+        self.execute_query(
+            GraphQuery::Text {
+                pattern: args.pattern,
+                ignore_case: args.ignore_case,
+                limit: args.limit.or(defaults.limit).unwrap_or(20),
+            },
+            ctx,
+        )
+        .await
+    }
+
+    #[tool(
+        description = "Find the source locations that reference a code element (FQN). Each location is tagged `usage` (calls, instantiations, member access) or `type_usage` (field, parameter, return and other declared types); use `type_usages` to include, exclude or keep only the latter."
+    )]
+    pub async fn find_references(
+        &self,
+        params: Parameters<FindReferencesArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let engine = self.get_or_build_index().await?;
+        let _call = self.calls.register(ctx.id.clone(), ctx.ct.clone());
+
+        let sites = tokio::select! {
+            sites = engine.references(&params.0.fqn, params.0.type_usages) => sites,
+            _ = ctx.ct.cancelled() => Err(ApiError::Cancelled),
+        };
+        let sites = sites.map_err(api_error)?;
+
+        match serde_json::to_string_pretty(&sites) {
+            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+            Err(e) => Err(McpError::new(
+                rmcp::model::ErrorCode(-32000),
+                e.to_string(),
+                None,
+            )),
+        }
+    }
+
+    #[tool(
+        description = "Resolve the symbol at a source position (file, 1-based line and column) to its FQN and indexed node. Use this to turn a location from a stack trace, diff or grep hit into an FQN for `cat`, `deps` or `find_references`."
+    )]
+    pub async fn resolve_at(
+        &self,
+        params: Parameters<ResolveAtArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let engine = self.get_or_build_index().await?;
+        let _call = self.calls.register(ctx.id.clone(), ctx.ct.clone());
+
+        let args = params.0;
+        let path = PathBuf::from(&args.path);
+        if !path.is_absolute() {
+            return Err(api_error(ApiError::InvalidArgument(format!(
+                "path must be absolute: {}",
+                args.path
+            ))));
+        }
+        if args.line == 0 || args.column == 0 {
+            return Err(api_error(ApiError::InvalidArgument(
+                "line and column are 1-based".to_string(),
+            )));
+        }
+        let resolved = tokio::select! {
+            resolved = engine.resolve_at(&path, args.line - 1, args.column - 1) => resolved,
+            _ = ctx.ct.cancelled() => Err(ApiError::Cancelled),
+        };
+        let resolved = resolved.map_err(api_error)?;
+
+        match serde_json::to_string_pretty(&resolved) {
+            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+            Err(e) => Err(McpError::new(
+                rmcp::model::ErrorCode(-32000),
+                e.to_string(),
+                None,
+            )),
+        }
+    }
+
+    #[tool(
+        description = "Resolve many positions of one source file at once (1-based lines and columns), e.g. every identifier of interest in a file. The file is parsed and the graph read once. Returns one entry per position, in order, with its `line`, `column` and `symbol` (as `resolve_at` returns it, or null)."
+    )]
+    pub async fn resolve_batch(
+        &self,
+        params: Parameters<ResolveBatchArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let engine = self.get_or_build_index().await?;
+        let _call = self.calls.register(ctx.id.clone(), ctx.ct.clone());
+
+        let args = params.0;
+        let path = PathBuf::from(&args.path);
+        if !path.is_absolute() {
+            return Err(api_error(ApiError::InvalidArgument(format!(
+                "path must be absolute: {}",
+                args.path
+            ))));
+        }
+        if args.positions.len() > MAX_BATCH_POSITIONS {
+            return Err(api_error(ApiError::InvalidArgument(format!(
+                "at most {} positions per call",
+                MAX_BATCH_POSITIONS
+            ))));
+        }
+        if args.positions.iter().any(|p| p.line == 0 || p.column == 0) {
+            return Err(api_error(ApiError::InvalidArgument(
+                "lines and columns are 1-based".to_string(),
+            )));
+        }
+        let positions: Vec<(u32, u32)> = args
+            .positions
+            .iter()
+            .map(|p| (p.line - 1, p.column - 1))
+            .collect();
+        let resolved = tokio::select! {
+            resolved = engine.resolve_batch(&path, &positions) => resolved,
+            _ = ctx.ct.cancelled() => Err(ApiError::Cancelled),
+        };
+        let resolved = resolved.map_err(api_error)?;
+
+        let entries: Vec<_> = args
+            .positions
+            .iter()
+            .zip(resolved)
+            .map(|(position, symbol)| {
+                serde_json::json!({
+                    "line": position.line,
+                    "column": position.column,
+                    "symbol": symbol,
+                })
+            })
+            .collect();
+        match serde_json::to_string_pretty(&entries) {
+            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+            Err(e) => Err(McpError::new(
+                rmcp::model::ErrorCode(-32000),
+                e.to_string(),
+                None,
+            )),
+        }
+    }
+
+    #[tool(
+        description = "Read source text from the index: the lines a code element (FQN) spans, or a line range of an indexed file (absolute path), with a few lines of context. Returns `text` with its 1-based `start_line` and `end_line`, and the requested lines as `focus_start` and `focus_end`."
+    )]
+    pub async fn read_source(
+        &self,
+        params: Parameters<ReadSourceArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let engine = self.get_or_build_index().await?;
+        let _call = self.calls.register(ctx.id.clone(), ctx.ct.clone());
+
+        let args = params.0;
+        let (path, fqn, focus_start, focus_end) = match (args.fqn, args.path) {
+            (Some(fqn), None) => {
+                let node = engine
+                    .get_node_display(&fqn)
+                    .await
+                    .map_err(api_error)?
+                    .ok_or_else(|| api_error(ApiError::NotFound(format!("No node {}", fqn))))?;
+                let Some(location) = node.location else {
+                    return Err(api_error(ApiError::NotFound(format!(
+                        "{} has no source location",
+                        fqn
+                    ))));
+                };
+                let range = location.range;
+                (
+                    PathBuf::from(location.path),
+                    Some(fqn),
+                    range.start_line + 1,
+                    Some(range.end_line + 1),
+                )
+            }
+            (None, Some(path)) => {
+                let path = PathBuf::from(path);
+                if !path.is_absolute() {
+                    return Err(api_error(ApiError::InvalidArgument(format!(
+                        "path must be absolute: {}",
+                        path.display()
+                    ))));
+                }
+                (path, None, 1, None)
+            }
+            _ => {
+                return Err(api_error(ApiError::InvalidArgument(
+                    "pass either fqn or path".to_string(),
+                )));
+            }
+        };
+
+        // Only files the index knows are served by path
+        let content = if fqn.is_some() {
+            engine.read_source(&path).await
+        } else {
+            source::read_indexed(engine.as_ref(), &path).await
+        }
+        .map_err(api_error)?;
+        let start_line = args.start_line.unwrap_or(focus_start);
+        let end_line = args.end_line.or(focus_end);
+        let context = args.context.unwrap_or(source::DEFAULT_CONTEXT);
+        let mut snippet =
+            source::SourceSnippet::slice(path, &content, start_line, end_line, context)
+                .ok_or_else(|| {
+                    api_error(ApiError::InvalidArgument(format!(
+                        "line {} is past the end of the file or after end_line",
+                        start_line
+                    )))
+                })?;
+        snippet.fqn = fqn;
+
+        match serde_json::to_string_pretty(&snippet) {
+            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+            Err(e) => Err(McpError::new(
+                rmcp::model::ErrorCode(-32000),
+                e.to_string(),
+                None,
+            )),
+        }
+    }
+
+    #[tool(
+        description = "Attach a note and/or tags to a code element (FQN), or remove one by id. Notes are stored with the project index, survive re-indexing and later sessions, and appear in `cat` output and editor hovers. Returns all notes of the element."
+    )]
+    pub async fn annotate(
+        &self,
+        params: Parameters<AnnotateArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let engine = self.get_or_build_index().await?;
+        let _call = self.calls.register(ctx.id.clone(), ctx.ct.clone());
+
+        let args = params.0;
+        // Notes are stored under the FQN `cat` renders for the node; notes of
+        // a node that is gone can still be removed by the FQN they carry
+        let key = engine
+            .get_node_display(&args.fqn)
+            .await
+            .map_err(api_error)?
+            .map_or(args.fqn, |node| node.id);
+        match args.remove {
+            Some(id) => {
+                if !engine.remove_note(&key, id).await.map_err(api_error)? {
+                    return Err(api_error(ApiError::NotFound(format!(
+                        "{} has no note {}",
+                        key, id
+                    ))));
+                }
+            }
+            None => {
+                let text = args.note.unwrap_or_default();
+                let tags = args.tags.unwrap_or_default();
+                engine
+                    .annotate(&key, &text, &tags)
+                    .await
+                    .map_err(api_error)?;
+            }
+        }
+        let notes = engine.notes(&key).await.map_err(api_error)?;
+
+        let result = serde_json::json!({ "fqn": key, "notes": notes });
+        match serde_json::to_string_pretty(&result) {
+            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+            Err(e) => Err(McpError::new(
+                rmcp::model::ErrorCode(-32000),
+                e.to_string(),
+                None,
+            )),
+        }
+    }
+
+    #[tool(
+        description = "Check a graph query without running it. `query` is a JSON object with a `command` (ls, find, fuzzy, cat, deps, impact, cycles, stats, text, duplicates, dead, tests_for) and that command's fields. Returns `valid`, and `issues` naming each bad field with a `suggestion` (e.g. `class` for kind `Class`, or the closest existing FQN for an unknown one)."
+    )]
+    pub async fn validate_query(
+        &self,
+        params: Parameters<ValidateQueryArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let engine = self.get_or_build_index().await?;
+        let validation = engine
+            .validate_query(&params.0.query)
+            .await
+            .map_err(api_error)?;
+
+        match serde_json::to_string_pretty(&validation) {
+            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+            Err(e) => Err(McpError::new(
+                rmcp::model::ErrorCode(-32000),
+                e.to_string(),
+                None,
+            )),
+        }
+    }
+
+    #[tool(
+        description = "Check whether a code element (FQN) can be deleted safely. Reports remaining references outside tests, overriding methods and reflective-use hints, with a verdict of safe, review or unsafe."
+    )]
+    pub async fn safe_delete(
+        &self,
+        params: Parameters<SafeDeleteArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let engine = self.get_or_build_index().await?;
+        let _call = self.calls.register(ctx.id.clone(), ctx.ct.clone());
+
+        let report = tokio::select! {
+            report = engine.safe_delete(&params.0.fqn) => report,
+            _ = ctx.ct.cancelled() => Err(ApiError::Cancelled),
+        };
+        let report = report.map_err(api_error)?;
+
+        match serde_json::to_string_pretty(&report) {
+            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+            Err(e) => Err(McpError::new(
+                rmcp::model::ErrorCode(-32000),
+                e.to_string(),
+                None,
+            )),
+        }
+    }
+
+    #[tool(
+        description = "Report the external API the project uses: references from project code into library symbols, grouped by artifact (group:artifact:version, JDK or jar) and package, with counts and the most used symbols. Shows how coupled the project is to a library before an upgrade or replacement."
+    )]
+    pub async fn external_api_usage(
+        &self,
+        params: Parameters<ExternalApiUsageArgs>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let engine = self.get_or_build_index().await?;
+        let _call = self.calls.register(ctx.id.clone(), ctx.ct.clone());
+        let args = params.0;
+
+        let report = tokio::select! {
+            report = engine.external_api_usage(args.artifact.as_deref(), args.top.unwrap_or(10)) => report,
+            _ = ctx.ct.cancelled() => Err(ApiError::Cancelled),
+        };
+        let report = report.map_err(api_error)?;
+
+        match serde_json::to_string_pretty(&report) {
+            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+            Err(e) => Err(McpError::new(
+                rmcp::model::ErrorCode(-32000),
+                e.to_string(),
+                None,
+            )),
+        }
+    }
+
+    #[tool(
+        description = "List the warnings recorded while indexing: constructs the parsers and resolvers skipped, such as declarations inside syntax errors or build scripts that did not parse. Each warning names the file, phase, severity and what was lost. Check this when a symbol you expect is missing from the index."
+    )]
+    pub async fn index_warnings(
+        &self,
+        params: Parameters<IndexWarningsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let engine = self.get_or_build_index().await?;
+        let args = params.0;
+        let severity = args.severity.unwrap_or(WarningSeverity::Warning);
+
+        let warnings: Vec<_> = engine
+            .index_warnings()
+            .await
+            .map_err(api_error)?
+            .into_iter()
+            .filter(|w| w.severity >= severity)
+            .filter(|w| {
+                args.path
+                    .as_ref()
+                    .is_none_or(|p| w.path.contains(p.as_str()))
+            })
+            .collect();
+
+        match serde_json::to_string_pretty(&warnings) {
+            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
+            Err(e) => Err(McpError::new(
+                rmcp::model::ErrorCode(-32000),
+                e.to_string(),
+                None,
+            )),
+        }
+    }
 }
 
-#[tool_handler]
+#[tool_handler(router = self.audited_router())]
 impl rmcp::ServerHandler for McpServer {
     fn get_info(&self) -> InitializeResult {
         InitializeResult {
@@ -227,8 +1334,92 @@ impl rmcp::ServerHandler for McpServer {
                 version: env!("CARGO_PKG_VERSION").into(),
                 ..Default::default()
             },
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_logging()
+                .enable_resources()
+                .enable_resources_subscribe()
+                .enable_resources_list_changed()
+                .build(),
             ..Default::default()
         }
     }
+
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        self.peers.write().await.push(Connection {
+            peer: context.peer,
+            subscriptions: self.subscriptions.clone(),
+        });
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        // Nodes and files are only addressed through the templates
+        Ok(ListResourcesResult::default())
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, McpError> {
+        Ok(ListResourceTemplatesResult::with_all_items(
+            resources::templates(),
+        ))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        let engine = self.get_or_build_index().await?;
+        let json = Self::resource_text(&engine, &request.uri).await?;
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri: request.uri,
+                mime_type: Some("application/json".to_string()),
+                text: json,
+                meta: None,
+            }],
+        })
+    }
+
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        if ResourceUri::parse(&request.uri).is_none() {
+            return Err(McpError::invalid_params(
+                format!("Unknown resource URI: {}", request.uri),
+                None,
+            ));
+        }
+        // Updates are reported relative to the content as of subscribing
+        let hash = match self.engine.current().await {
+            Some(engine) => Self::resource_hash(&engine, &request.uri).await,
+            None => None,
+        };
+        self.subscriptions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(request.uri, hash);
+        Ok(())
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        self.subscriptions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&request.uri);
+        Ok(())
+    }
 }
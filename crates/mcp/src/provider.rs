@@ -0,0 +1,106 @@
+//! The engine a process serves, shared by its servers
+//!
+//! `naviscope lsp` hosts an MCP server next to the LSP server. Both hold
+//! clones of one provider, so they always observe the same engine: replacing
+//! it when the workspace root changes is seen by both at once, and the
+//! project is indexed only once.
+
+use naviscope_api::NaviscopeEngine;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Creates the engine for a project root
+pub type EngineBuilder = dyn Fn(PathBuf) -> Arc<dyn NaviscopeEngine> + Send + Sync;
+
+/// Current engine, if any; clones share it.
+///
+/// Requests take the engine current when they start and run to the end on
+/// it, even if it is replaced in the meantime.
+#[derive(Clone)]
+pub struct EngineProvider {
+    engine: Arc<RwLock<Option<Arc<dyn NaviscopeEngine>>>>,
+    builder: Option<Arc<EngineBuilder>>,
+}
+
+impl EngineProvider {
+    /// Provider that builds an engine with `builder` for every root it is pointed at
+    pub fn new(builder: Arc<EngineBuilder>) -> Self {
+        Self {
+            engine: Arc::new(RwLock::new(None)),
+            builder: Some(builder),
+        }
+    }
+
+    /// Provider serving a given engine slot, which it cannot rebuild
+    pub fn fixed(engine: Arc<RwLock<Option<Arc<dyn NaviscopeEngine>>>>) -> Self {
+        Self {
+            engine,
+            builder: None,
+        }
+    }
+
+    /// Engine serving requests now, if one is installed
+    pub async fn current(&self) -> Option<Arc<dyn NaviscopeEngine>> {
+        self.engine.read().await.clone()
+    }
+
+    /// Build the engine for `root` and install it in place of the current
+    /// one. Returns `None` for a fixed provider.
+    pub async fn switch_to(&self, root: PathBuf) -> Option<Arc<dyn NaviscopeEngine>> {
+        let builder = self.builder.as_ref()?;
+        let engine = builder(root);
+        *self.engine.write().await = Some(engine.clone());
+        Some(engine)
+    }
+}
+
+impl From<Arc<RwLock<Option<Arc<dyn NaviscopeEngine>>>>> for EngineProvider {
+    fn from(engine: Arc<RwLock<Option<Arc<dyn NaviscopeEngine>>>>) -> Self {
+        Self::fixed(engine)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use naviscope_core::facade::EngineHandle;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn clones_observe_the_same_engine() {
+        let dir = tempfile::tempdir().unwrap();
+        let builds = Arc::new(AtomicUsize::new(0));
+        let builder: Arc<EngineBuilder> = Arc::new({
+            let builds = builds.clone();
+            move |root| {
+                builds.fetch_add(1, Ordering::SeqCst);
+                Arc::new(EngineHandle::new(root))
+            }
+        });
+        let lsp = EngineProvider::new(builder);
+        let mcp = lsp.clone();
+        assert!(mcp.current().await.is_none());
+
+        let first = lsp.switch_to(dir.path().to_path_buf()).await.unwrap();
+        assert_eq!(builds.load(Ordering::SeqCst), 1);
+        assert!(Arc::ptr_eq(&mcp.current().await.unwrap(), &first));
+
+        // Switching from either clone replaces the engine for both
+        let second = mcp.switch_to(dir.path().to_path_buf()).await.unwrap();
+        assert_eq!(builds.load(Ordering::SeqCst), 2);
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert!(Arc::ptr_eq(&lsp.current().await.unwrap(), &second));
+    }
+
+    #[tokio::test]
+    async fn fixed_providers_do_not_switch() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine: Arc<dyn NaviscopeEngine> =
+            Arc::new(EngineHandle::new(dir.path().to_path_buf()));
+        let provider = EngineProvider::fixed(Arc::new(RwLock::new(Some(engine.clone()))));
+
+        assert!(provider.switch_to(dir.path().to_path_buf()).await.is_none());
+        assert!(Arc::ptr_eq(&provider.current().await.unwrap(), &engine));
+    }
+}
@@ -8,86 +8,61 @@ pub async fn run_mcp_proxy(path: &Path) -> Result<(), Box<dyn std::error::Error>
     // 1. Find session file
     let session_path = get_session_path(path);
 
-    // Wait for LSP to start (if session file doesn't exist yet)
-    if !session_path.exists() {
-        info!("LSP session not found, waiting for LSP server to start...");
-
-        // Wait up to 60 seconds for LSP to start
-        let wait_result = timeout(Duration::from_secs(60), async {
-            loop {
-                if session_path.exists() {
-                    break;
+    // 2. Wait for a live server; a record left by a crashed one is removed
+    // and waited past like a missing one
+    let mut waiting = false;
+    let wait_result = timeout(Duration::from_secs(60), async {
+        loop {
+            match SessionInfo::read(&session_path) {
+                Some(session) if session.is_live().await => return session,
+                Some(session) => {
+                    warn!(
+                        "LSP process (PID: {}) is not serving port {}, removing stale session file",
+                        session.pid, session.port
+                    );
+                    let _ = std::fs::remove_file(&session_path);
                 }
-                sleep(Duration::from_millis(500)).await;
+                None => {}
+            }
+            if !waiting {
+                info!("LSP session not found, waiting for LSP server to start...");
+                waiting = true;
             }
-        })
-        .await;
+            sleep(Duration::from_millis(500)).await;
+        }
+    })
+    .await;
 
-        if wait_result.is_err() {
+    let session = match wait_result {
+        Ok(session) => session,
+        Err(_) => {
             return Err(format!(
                 "LSP server did not start within 60 seconds. Please ensure the LSP server is running for project: {}",
                 path.display()
             ).into());
         }
-
-        info!("LSP session file detected, connecting...");
-    }
-
-    // 2. Read session and check PID
-    let session: SessionInfo = match std::fs::read_to_string(&session_path)
-        .ok()
-        .and_then(|s| serde_json::from_str(&s).ok())
-    {
-        Some(s) => s,
-        None => {
-            return Err(
-                format!("Failed to parse session file at {}", session_path.display()).into(),
-            );
-        }
     };
 
-    // Check if PID is alive
-    if !is_pid_alive(session.pid) {
-        warn!(
-            "LSP process (PID: {}) is not alive, removing stale session file",
-            session.pid
-        );
-        let _ = std::fs::remove_file(session_path);
-        return Err(format!("LSP process (PID: {}) is not running", session.pid).into());
-    }
-
     // 3. Start proxy
     info!("Connecting to LSP MCP server at port {}", session.port);
-    start_ws_proxy(session.port).await?;
+    start_ws_proxy(&session).await?;
 
     Ok(())
 }
 
-fn is_pid_alive(pid: u32) -> bool {
-    #[cfg(unix)]
-    {
-        std::process::Command::new("kill")
-            .arg("-0")
-            .arg(pid.to_string())
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .status()
-            .map(|s| s.success())
-            .unwrap_or(false)
-    }
-    #[cfg(not(unix))]
-    {
-        // Simple fallback for non-unix, might need better implementation
-        true
-    }
-}
-
-async fn start_ws_proxy(port: u16) -> Result<(), Box<dyn std::error::Error>> {
+async fn start_ws_proxy(session: &SessionInfo) -> Result<(), Box<dyn std::error::Error>> {
     use tokio::io::{AsyncReadExt, AsyncWriteExt, stdin, stdout};
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
     use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
 
-    let url = format!("ws://127.0.0.1:{}/mcp", port);
-    let (ws_stream, _) = connect_async(&url).await?;
+    let url = format!("ws://{}{}", session.connect_addr(), session.endpoint());
+    let mut request = url.into_client_request()?;
+    if let Some(token) = &session.token {
+        request
+            .headers_mut()
+            .insert("Authorization", format!("Bearer {}", token).parse()?);
+    }
+    let (ws_stream, _) = connect_async(request).await?;
 
     let (mut ws_sink, mut ws_stream) = ws_stream.split();
     let mut stdin = stdin();
@@ -0,0 +1,199 @@
+//! MCP resources for graph nodes and source files
+//!
+//! `naviscope://node/{fqn}` reads as the node's display JSON with the source
+//! lines it spans, `naviscope://file/{path}` as the symbols declared in an
+//! absolute source path. Agents can read and subscribe to them instead of
+//! calling `cat` repeatedly.
+//!
+//! After every index commit each connection is sent `resources/list_changed`
+//! and, for each subscribed resource whose content changed, `resources/updated`.
+
+use naviscope_api::NaviscopeEngine;
+use naviscope_api::models::DisplayGraphNode;
+use rmcp::model::{AnnotateAble, RawResourceTemplate, ResourceTemplate};
+use rmcp::service::{Peer, RoleServer};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+pub const NODE_PREFIX: &str = "naviscope://node/";
+pub const FILE_PREFIX: &str = "naviscope://file/";
+
+/// Resource URIs one connection subscribed to, with a hash of the content
+/// the client last saw (`None` if it could not be read)
+pub(crate) type Subscriptions = Arc<Mutex<HashMap<String, Option<u64>>>>;
+
+/// A connected client: where to send notifications and what it subscribed to
+#[derive(Clone)]
+pub(crate) struct Connection {
+    pub(crate) peer: Peer<RoleServer>,
+    pub(crate) subscriptions: Subscriptions,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceUri {
+    Node(String),
+    File(PathBuf),
+}
+
+impl ResourceUri {
+    /// Parse a resource URI; FQNs and paths may be percent-encoded.
+    pub fn parse(uri: &str) -> Option<Self> {
+        if let Some(fqn) = uri.strip_prefix(NODE_PREFIX) {
+            let fqn = percent_decode(fqn)?;
+            return (!fqn.is_empty()).then_some(Self::Node(fqn));
+        }
+        let path = percent_decode(uri.strip_prefix(FILE_PREFIX)?)?;
+        if path.is_empty() {
+            return None;
+        }
+        // `naviscope://file/home/a/A.java` and `naviscope://file//home/a/A.java`
+        // both name `/home/a/A.java`
+        let path = PathBuf::from(&path);
+        if path.is_absolute() {
+            Some(Self::File(path))
+        } else {
+            Some(Self::File(PathBuf::from("/").join(path)))
+        }
+    }
+
+    pub fn node(fqn: &str) -> String {
+        format!("{}{}", NODE_PREFIX, percent_encode(fqn))
+    }
+
+    pub fn file(path: &std::path::Path) -> String {
+        format!(
+            "{}{}",
+            FILE_PREFIX,
+            percent_encode(path.to_string_lossy().trim_start_matches('/'))
+        )
+    }
+}
+
+pub fn templates() -> Vec<ResourceTemplate> {
+    let template = |uri_template: &str, name: &str, description: &str| {
+        RawResourceTemplate {
+            uri_template: uri_template.to_string(),
+            name: name.to_string(),
+            title: None,
+            description: Some(description.to_string()),
+            mime_type: Some("application/json".to_string()),
+            icons: None,
+        }
+        .no_annotation()
+    };
+    vec![
+        template(
+            "naviscope://node/{fqn}",
+            "node",
+            "A code element by FQN: its display node (kind, location, signature, modifiers) and the source lines it spans",
+        ),
+        template(
+            "naviscope://file/{path}",
+            "file",
+            "Symbols declared in a source file, by absolute path",
+        ),
+    ]
+}
+
+/// Content of a node resource
+#[derive(Debug, Serialize)]
+pub struct NodeResource {
+    pub node: DisplayGraphNode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+impl NodeResource {
+    /// Attach the lines of the node's location, if its file is readable
+    pub async fn load(engine: &dyn NaviscopeEngine, node: DisplayGraphNode) -> Self {
+        let source = match &node.location {
+            Some(location) => engine
+                .read_source(Path::new(&location.path))
+                .await
+                .ok()
+                .map(|content| {
+                    content
+                        .lines()
+                        .skip(location.range.start_line)
+                        .take(
+                            location
+                                .range
+                                .end_line
+                                .saturating_sub(location.range.start_line)
+                                + 1,
+                        )
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                }),
+            None => None,
+        };
+        Self { node, source }
+    }
+}
+
+/// Content of a file resource
+#[derive(Debug, Serialize)]
+pub struct FileResource {
+    pub path: PathBuf,
+    pub symbols: Vec<DisplayGraphNode>,
+}
+
+/// Escape the characters that would end the path part of a URI
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '%' | '#' | '?' | ' ' => out.push_str(&format!("%{:02X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_node_and_file_uris() {
+        assert_eq!(
+            ResourceUri::parse("naviscope://node/com.app.Repo%23load()"),
+            Some(ResourceUri::Node("com.app.Repo#load()".to_string()))
+        );
+        let file = Some(ResourceUri::File(PathBuf::from("/ws/src/A.java")));
+        assert_eq!(ResourceUri::parse("naviscope://file/ws/src/A.java"), file);
+        assert_eq!(ResourceUri::parse("naviscope://file//ws/src/A.java"), file);
+        assert_eq!(
+            ResourceUri::parse(&ResourceUri::file(std::path::Path::new("/ws/src/A.java"))),
+            file
+        );
+        let method = ResourceUri::node("com.app.Repo#load()");
+        assert_eq!(method, "naviscope://node/com.app.Repo%23load()");
+        assert_eq!(
+            ResourceUri::parse(&method),
+            Some(ResourceUri::Node("com.app.Repo#load()".to_string()))
+        );
+        assert_eq!(ResourceUri::parse("naviscope://node/"), None);
+        assert_eq!(ResourceUri::parse("naviscope://node/bad%2"), None);
+        assert_eq!(ResourceUri::parse("file:///ws/src/A.java"), None);
+    }
+}
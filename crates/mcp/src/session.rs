@@ -0,0 +1,228 @@
+//! Session records of running MCP HTTP servers
+//!
+//! An LSP server writes `~/.naviscope/sessions/<hash of root>.json` with the
+//! port of its MCP server, and `naviscope mcp` proxies read it to connect.
+//! A crashed server leaves its record behind, so proxies used to connect to a
+//! dead port. Records are now checked before use (the process must be alive
+//! and the port must accept connections) and dead ones are removed when a
+//! server starts.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// How long a port probe waits for the connection to be accepted
+const PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct SessionInfo {
+    pub port: u16,
+    pub pid: u32,
+    pub root_path: PathBuf,
+    /// Address the server listens on; loopback when absent
+    #[serde(default)]
+    pub host: Option<IpAddr>,
+    /// Bearer token the server requires
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Path of the project's MCP endpoint, when the server hosts several
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+}
+
+impl SessionInfo {
+    /// Read the record at `path`; `None` if it is missing or unreadable.
+    pub fn read(path: &Path) -> Option<Self> {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+    }
+
+    /// Write the record, readable only by the current user since it holds
+    /// the token.
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string(self).map_err(std::io::Error::other)?;
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(path)?;
+        // The mode only applies to new files; an existing record keeps its own
+        #[cfg(unix)]
+        file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+        std::io::Write::write_all(&mut file, json.as_bytes())
+    }
+
+    /// Address clients connect to; a server bound to all interfaces is
+    /// reached over loopback.
+    pub fn connect_addr(&self) -> SocketAddr {
+        let host = match self.host {
+            Some(host) if !host.is_unspecified() => host,
+            _ => Ipv4Addr::LOCALHOST.into(),
+        };
+        SocketAddr::new(host, self.port)
+    }
+
+    /// Path of the WebSocket endpoint serving the project
+    pub fn endpoint(&self) -> &str {
+        self.route.as_deref().unwrap_or("/mcp")
+    }
+
+    /// Whether the recorded server still runs: its process is alive and its
+    /// port accepts connections.
+    pub async fn is_live(&self) -> bool {
+        if !is_pid_alive(self.pid) {
+            return false;
+        }
+        let connect = tokio::net::TcpStream::connect(self.connect_addr());
+        matches!(
+            tokio::time::timeout(PROBE_TIMEOUT, connect).await,
+            Ok(Ok(_))
+        )
+    }
+}
+
+fn session_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    Path::new(&home).join(".naviscope/sessions")
+}
+
+pub fn get_session_path(root_path: &Path) -> PathBuf {
+    let session_dir = session_dir();
+    let _ = std::fs::create_dir_all(&session_dir);
+
+    let abs_path = root_path
+        .canonicalize()
+        .unwrap_or_else(|_| root_path.to_path_buf());
+    let hash = xxh3_64(abs_path.to_string_lossy().as_bytes());
+    session_dir.join(format!("{:016x}.json", hash))
+}
+
+/// Move an existing session record to the session path of `new_root`.
+///
+/// The port and pid are preserved so proxies started for the new root can reach
+/// the already-running server. Returns the new session path on success.
+pub fn relocate_session(old_session: &Path, new_root: &Path) -> Option<PathBuf> {
+    let mut info = SessionInfo::read(old_session)?;
+    let _ = std::fs::remove_file(old_session);
+
+    info.root_path = new_root.to_path_buf();
+    let new_session = get_session_path(new_root);
+    info.write(&new_session).ok()?;
+    Some(new_session)
+}
+
+/// Remove the records of servers that are no longer running, and records
+/// that cannot be read. Returns how many were removed.
+pub async fn remove_dead_sessions() -> usize {
+    remove_dead_sessions_in(&session_dir()).await
+}
+
+async fn remove_dead_sessions_in(dir: &Path) -> usize {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let live = match SessionInfo::read(&path) {
+            Some(info) => info.is_live().await,
+            None => false,
+        };
+        if !live && std::fs::remove_file(&path).is_ok() {
+            tracing::info!("Removed stale MCP session {}", path.display());
+            removed += 1;
+        }
+    }
+    removed
+}
+
+pub(crate) fn is_pid_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        std::process::Command::new("kill")
+            .arg("-0")
+            .arg(pid.to_string())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        // Simple fallback for non-unix, might need better implementation
+        let _ = pid;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(port: u16) -> SessionInfo {
+        SessionInfo {
+            port,
+            pid: std::process::id(),
+            root_path: PathBuf::from("/ws"),
+            host: None,
+            token: None,
+            route: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn liveness_needs_a_listening_port() {
+        let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(session(port).is_live().await);
+
+        drop(listener);
+        assert!(!session(port).is_live().await);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn dead_and_unreadable_sessions_are_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .unwrap();
+        let port = listener.local_addr().unwrap().port();
+        session(port).write(&dir.path().join("live.json")).unwrap();
+        // The port is taken, but the recorded process has exited
+        let mut exited = std::process::Command::new("true").spawn().unwrap();
+        exited.wait().unwrap();
+        let dead = SessionInfo {
+            pid: exited.id(),
+            ..session(port)
+        };
+        dead.write(&dir.path().join("dead.json")).unwrap();
+        std::fs::write(dir.path().join("broken.json"), "{").unwrap();
+
+        assert_eq!(remove_dead_sessions_in(dir.path()).await, 2);
+        assert!(dir.path().join("live.json").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn records_are_private_even_when_they_existed() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        std::fs::write(&path, "{}").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        session(1).write(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(SessionInfo::read(&path).unwrap().port, 1);
+    }
+}
@@ -0,0 +1,149 @@
+//! Source text for the `read_source` tool
+//!
+//! Agents asked for a code element's source used to shell out to `cat` and
+//! guess the path. `read_source` reads it from the indexed location instead,
+//! or from an indexed file by path, with a few lines of context around the
+//! requested range.
+
+use naviscope_api::{ApiError, ApiResult, NaviscopeEngine};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Lines of context around the requested range by default
+pub const DEFAULT_CONTEXT: usize = 2;
+
+/// Most lines returned by one call
+pub const MAX_LINES: usize = 400;
+
+/// Prefix of the FQNs of file nodes, as in `naviscope_core::model::graph`
+const FILE_ID_PREFIX: &str = "file:";
+
+/// The content of `path`, if it is a file of the index. Other files on the
+/// machine are not served, even ones a plugin could parse.
+pub async fn read_indexed(engine: &dyn NaviscopeEngine, path: &Path) -> ApiResult<Arc<str>> {
+    let file = format!("{}{}", FILE_ID_PREFIX, path.display());
+    if engine.get_node_display(&file).await?.is_none() {
+        return Err(ApiError::NotFound(format!(
+            "{} is not indexed",
+            path.display()
+        )));
+    }
+    engine.read_source(path).await
+}
+
+/// Lines read from a source file; line numbers are 1-based and inclusive
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct SourceSnippet {
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fqn: Option<String>,
+    /// Lines the request asked for, without context
+    pub focus_start: usize,
+    pub focus_end: usize,
+    /// Lines in `text`, including context
+    pub start_line: usize,
+    pub end_line: usize,
+    pub text: String,
+    /// Whether lines were cut to stay within `MAX_LINES`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+impl SourceSnippet {
+    /// Lines `focus_start..=focus_end` of `content`, 1-based and clamped to
+    /// the file, with `context` lines on either side. `None` if the range
+    /// starts past the end of the file or is reversed.
+    pub fn slice(
+        path: PathBuf,
+        content: &str,
+        focus_start: usize,
+        focus_end: Option<usize>,
+        context: usize,
+    ) -> Option<Self> {
+        let lines: Vec<&str> = content.lines().collect();
+        let focus_start = focus_start.max(1);
+        let focus_end = focus_end.unwrap_or(lines.len()).min(lines.len());
+        if focus_start > focus_end {
+            return None;
+        }
+        let start_line = focus_start.saturating_sub(context).max(1);
+        let mut end_line = (focus_end + context).min(lines.len());
+        let truncated = end_line + 1 - start_line > MAX_LINES;
+        if truncated {
+            end_line = start_line + MAX_LINES - 1;
+        }
+        Some(Self {
+            path,
+            fqn: None,
+            focus_start,
+            focus_end,
+            start_line,
+            end_line,
+            text: lines[start_line - 1..end_line].join("\n"),
+            truncated,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slice_adds_context_and_clamps_to_the_file() {
+        let content = (1..=10).map(|n| format!("line {}", n)).collect::<Vec<_>>();
+        let content = content.join("\n");
+        let path = PathBuf::from("/ws/A.java");
+
+        let snippet = SourceSnippet::slice(path.clone(), &content, 4, Some(5), 2).unwrap();
+        assert_eq!((snippet.start_line, snippet.end_line), (2, 7));
+        assert_eq!((snippet.focus_start, snippet.focus_end), (4, 5));
+        assert!(snippet.text.starts_with("line 2\n") && snippet.text.ends_with("line 7"));
+
+        // Context stops at the edges; an open range runs to the end of the file
+        let snippet = SourceSnippet::slice(path.clone(), &content, 1, None, 3).unwrap();
+        assert_eq!((snippet.start_line, snippet.end_line), (1, 10));
+        assert!(!snippet.truncated);
+
+        assert!(SourceSnippet::slice(path.clone(), &content, 11, None, 0).is_none());
+        assert!(SourceSnippet::slice(path, &content, 5, Some(4), 0).is_none());
+    }
+
+    #[test]
+    fn slice_is_capped() {
+        let content = "x\n".repeat(MAX_LINES * 2);
+        let snippet =
+            SourceSnippet::slice(PathBuf::from("/ws/A.java"), &content, 1, None, 0).unwrap();
+        assert!(snippet.truncated);
+        assert_eq!(snippet.end_line, MAX_LINES);
+        assert_eq!(snippet.text.lines().count(), MAX_LINES);
+    }
+
+    #[tokio::test]
+    async fn only_indexed_files_are_read() {
+        let project = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let indexed = project.path().join("A.java");
+        let other = outside.path().join("B.java");
+        std::fs::write(&indexed, "class A {}").unwrap();
+        std::fs::write(&other, "class B {}").unwrap();
+        let engine = naviscope_core::runtime::NaviscopeEngine::builder(project.path().into())
+            .with_language_caps(naviscope_java::java_caps().unwrap())
+            .build();
+        let engine: Arc<dyn NaviscopeEngine> = Arc::new(
+            naviscope_core::facade::EngineHandle::from_engine(Arc::new(engine)),
+        );
+        engine.update_files(vec![indexed.clone()]).await.unwrap();
+
+        assert_eq!(
+            &*read_indexed(engine.as_ref(), &indexed).await.unwrap(),
+            "class A {}"
+        );
+        // Parseable, but outside the project and not in the index
+        assert!(matches!(
+            read_indexed(engine.as_ref(), &other).await,
+            Err(ApiError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,149 @@
+//! Indexing readiness, reported by `/readyz` and the `status` tool
+//!
+//! Clients may connect while the initial index is still being built. The
+//! indexer records its progress here so they can poll for readiness instead
+//! of failing on their first query.
+
+use naviscope_api::lifecycle::IndexPhase;
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexingState {
+    /// No indexing run has started yet
+    Starting,
+    Indexing,
+    Ready,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IndexingStatus {
+    pub state: IndexingState,
+    /// Current phase while indexing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phase: Option<IndexPhase>,
+    /// Overall completion, 0-100
+    pub percent: u32,
+    /// Failure reason
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl IndexingStatus {
+    pub fn is_ready(&self) -> bool {
+        self.state == IndexingState::Ready
+    }
+
+    /// One-line description, e.g. for error messages
+    pub fn describe(&self) -> String {
+        match (self.state, self.phase, &self.message) {
+            (IndexingState::Indexing, Some(phase), _) => {
+                format!("indexing ({}, {}%)", phase.label(), self.percent)
+            }
+            (IndexingState::Failed, _, Some(message)) => format!("indexing failed: {}", message),
+            (state, _, _) => format!("{:?}", state).to_lowercase(),
+        }
+    }
+}
+
+/// Shared indexing status; clones observe the same state.
+#[derive(Debug, Clone)]
+pub struct StatusTracker {
+    inner: Arc<RwLock<IndexingStatus>>,
+}
+
+impl Default for StatusTracker {
+    fn default() -> Self {
+        Self::with_state(IndexingState::Starting)
+    }
+}
+
+impl StatusTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_state(state: IndexingState) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(IndexingStatus {
+                state,
+                phase: None,
+                percent: 0,
+                message: None,
+            })),
+        }
+    }
+
+    pub fn snapshot(&self) -> IndexingStatus {
+        self.inner
+            .read()
+            .map(|s| s.clone())
+            .unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    fn set(
+        &self,
+        state: IndexingState,
+        phase: Option<IndexPhase>,
+        percent: u32,
+        message: Option<String>,
+    ) {
+        let mut guard = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        *guard = IndexingStatus {
+            state,
+            phase,
+            percent,
+            message,
+        };
+    }
+
+    /// A new indexing run began
+    pub fn start(&self) {
+        self.set(IndexingState::Indexing, None, 0, None);
+    }
+
+    /// `percent` is the overall completion of the run
+    pub fn progress(&self, phase: IndexPhase, percent: u32) {
+        self.set(IndexingState::Indexing, Some(phase), percent.min(100), None);
+    }
+
+    pub fn ready(&self) {
+        self.set(IndexingState::Ready, None, 100, None);
+    }
+
+    pub fn failed(&self, message: impl Into<String>) {
+        self.set(IndexingState::Failed, None, 0, Some(message.into()));
+    }
+
+    pub fn cancelled(&self) {
+        self.set(IndexingState::Cancelled, None, 0, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracker_reports_run_transitions() {
+        let tracker = StatusTracker::new();
+        let observer = tracker.clone();
+        assert_eq!(observer.snapshot().state, IndexingState::Starting);
+
+        tracker.start();
+        tracker.progress(IndexPhase::Parsing, 30);
+        let status = observer.snapshot();
+        assert!(!status.is_ready());
+        assert_eq!(status.describe(), "indexing (Parsing, 30%)");
+
+        tracker.ready();
+        assert!(observer.snapshot().is_ready());
+        assert_eq!(observer.snapshot().percent, 100);
+
+        tracker.failed("disk full");
+        assert_eq!(observer.snapshot().describe(), "indexing failed: disk full");
+    }
+}
@@ -1,15 +1,139 @@
-use crate::McpServer;
-use naviscope_api::graph::GraphService;
+use crate::{McpServer, StatusTracker};
+use naviscope_api::lifecycle::{EngineWatchHandle, IndexControl};
+use naviscope_api::{ApiError, NaviscopeEngine};
 use rmcp::{ServiceExt, transport::stdio};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 
 pub async fn run_stdio_server(
-    engine: Arc<RwLock<Option<Arc<dyn GraphService>>>>,
+    engine: Arc<RwLock<Option<Arc<dyn NaviscopeEngine>>>>,
     _root_path: Option<PathBuf>, // Not used anymore, kept for API compatibility
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let service = McpServer::new(engine).serve(stdio()).await?;
+    let server = McpServer::new(engine);
+    server.mark_ready_if_loaded().await;
+    serve_stdio(server).await
+}
+
+/// Serve MCP over stdio from an engine of its own, for agents without an
+/// editor attached: index the project in the background, then keep the index
+/// up to date until the client disconnects.
+pub async fn run_standalone_server(
+    engine: Arc<dyn NaviscopeEngine>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let server = McpServer::new(Arc::new(RwLock::new(Some(engine.clone()))));
+    let cancel_token = CancellationToken::new();
+    let indexer = tokio::spawn(index_and_watch(
+        engine,
+        server.indexing_status().clone(),
+        cancel_token.clone(),
+    ));
+
+    let result = serve_stdio(server).await;
+    cancel_token.cancel();
+    if let Ok(Some(watch)) = indexer.await {
+        watch.stop();
+    }
+    result
+}
+
+async fn serve_stdio(server: McpServer) -> Result<(), Box<dyn std::error::Error>> {
+    let cancel_token = CancellationToken::new();
+    tokio::spawn({
+        let server = server.clone();
+        let cancel_token = cancel_token.clone();
+        async move { server.follow_commits(cancel_token).await }
+    });
+    let _stop_following = cancel_token.drop_guard();
+    let service = server.serve(stdio()).await?;
     service.waiting().await?;
     Ok(())
 }
+
+/// Build the index, reporting progress to `status`, then start the file
+/// watcher. Returns the watcher unless indexing failed or was cancelled.
+pub(crate) async fn index_and_watch(
+    engine: Arc<dyn NaviscopeEngine>,
+    status: StatusTracker,
+    cancel_token: CancellationToken,
+) -> Option<Arc<dyn EngineWatchHandle>> {
+    status.start();
+    if let Ok(Some(resume)) = engine.pending_resume().await {
+        tracing::info!(
+            "Resuming interrupted indexing: {} files already indexed (epoch {})",
+            resume.committed_files,
+            resume.epoch
+        );
+    }
+
+    let progress = status.clone();
+    let control = IndexControl::new()
+        .with_progress(move |p| progress.progress(p.phase, p.overall_percentage()));
+    let rebuild = {
+        let rebuild_fut = engine.rebuild_with_control(&control);
+        tokio::pin!(rebuild_fut);
+        tokio::select! {
+            res = &mut rebuild_fut => res,
+            _ = cancel_token.cancelled() => {
+                control.cancel();
+                rebuild_fut.await
+            }
+        }
+    };
+
+    match rebuild {
+        Ok(()) => status.ready(),
+        Err(ApiError::Cancelled) => {
+            status.cancelled();
+            return None;
+        }
+        Err(e) => {
+            tracing::error!("Indexing failed: {}", e);
+            status.failed(e.to_string());
+            return None;
+        }
+    }
+    if cancel_token.is_cancelled() {
+        return None;
+    }
+
+    match engine.start_watch().await {
+        Ok(watch) => Some(watch),
+        Err(e) => {
+            tracing::error!("Failed to start file watcher: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use naviscope_core::facade::EngineHandle;
+
+    #[tokio::test]
+    async fn project_is_indexed_then_watched() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("A.java"), "class A {}").unwrap();
+        let engine: Arc<dyn NaviscopeEngine> =
+            Arc::new(EngineHandle::new(dir.path().to_path_buf()));
+        let status = StatusTracker::new();
+
+        let watch = index_and_watch(engine, status.clone(), CancellationToken::new()).await;
+        assert!(status.snapshot().is_ready());
+        watch.unwrap().stop();
+    }
+
+    #[tokio::test]
+    async fn cancelled_indexing_starts_no_watcher() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine: Arc<dyn NaviscopeEngine> =
+            Arc::new(EngineHandle::new(dir.path().to_path_buf()));
+        let cancel_token = CancellationToken::new();
+        cancel_token.cancel();
+
+        let watch = index_and_watch(engine, StatusTracker::new(), cancel_token).await;
+        assert!(watch.is_none());
+    }
+}
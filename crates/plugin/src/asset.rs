@@ -7,6 +7,7 @@
 //! - Route registry (mapping FQNs to asset paths)
 
 use crate::model::IndexNode;
+use naviscope_api::models::Range;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -77,6 +78,33 @@ impl AssetEntry {
             source: AssetSource::Unknown,
         }
     }
+
+    /// Readable name of the asset: `group:artifact:version`, the JDK and its
+    /// version, or the file name
+    pub fn display_name(&self) -> String {
+        match &self.source {
+            AssetSource::Gradle {
+                group,
+                artifact,
+                version,
+            }
+            | AssetSource::Maven {
+                group,
+                artifact,
+                version,
+            } => format!("{}:{}:{}", group, artifact, version),
+            AssetSource::Jdk {
+                version: Some(version),
+                ..
+            } => format!("jdk {}", version),
+            AssetSource::Jdk { version: None, .. } => "jdk".to_string(),
+            AssetSource::Local { .. } | AssetSource::Unknown => self
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| self.path.display().to_string()),
+        }
+    }
 }
 
 // ==================== Asset Layer ====================
@@ -92,6 +120,13 @@ pub trait AssetDiscoverer: Send + Sync {
 
     /// Default source type for this discoverer
     fn source_type(&self) -> &str;
+
+    /// Directories whose changes signal newly fetched assets (e.g. a build
+    /// tool's cache root). They are watched non-recursively; an event there
+    /// triggers an incremental re-discovery. Empty for static locations.
+    fn watch_roots(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
 }
 
 /// Asset indexer - knows how to read asset internal structure
@@ -149,6 +184,41 @@ pub trait StubGenerator: Send + Sync {
     /// Implementations should include the primary requested node when possible,
     /// and may include related nodes (e.g. class members) for richer resolution.
     fn generate_stubs(&self, fqn: &str, entry: &AssetEntry) -> Result<Vec<IndexNode>, BoxError>;
+
+    /// Render a readable source document for the type declaring `fqn`.
+    ///
+    /// Used as a navigation target when the asset ships without sources.
+    /// Generators that cannot reconstruct declarations return `Ok(None)`.
+    fn render_source(
+        &self,
+        _fqn: &str,
+        _entry: &AssetEntry,
+    ) -> Result<Option<SyntheticSource>, BoxError> {
+        Ok(None)
+    }
+
+    /// Read the source document of the type declaring `fqn` from the source
+    /// artifact `sources` paired with the asset.
+    ///
+    /// Preferred over `render_source` when the asset has sources, locally or
+    /// downloaded. Returns `Ok(None)` when the artifact lacks the type.
+    fn extract_source(
+        &self,
+        _fqn: &str,
+        _sources: &Path,
+    ) -> Result<Option<SyntheticSource>, BoxError> {
+        Ok(None)
+    }
+}
+
+/// Source document reconstructed from a binary asset
+#[derive(Debug, Clone)]
+pub struct SyntheticSource {
+    /// Path relative to the synthetic source directory (e.g. `java/util/List.java`)
+    pub relative_path: PathBuf,
+    pub content: String,
+    /// Declaration ranges of the rendered symbols, keyed by FQN
+    pub symbols: HashMap<String, Range>,
 }
 
 /// Stub request (with source info)
@@ -0,0 +1,42 @@
+/// What is known about a library version: advisories against it and the
+/// licenses it is distributed under
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DependencyAdvice {
+    /// Advisory ids, e.g. `CVE-2021-44228` or `GHSA-jfh8-c2jp-5v3q`
+    pub vulnerabilities: Vec<String>,
+    /// SPDX license identifiers or expressions, e.g. `Apache-2.0`
+    pub licenses: Vec<String>,
+}
+
+impl DependencyAdvice {
+    pub fn is_empty(&self) -> bool {
+        self.vulnerabilities.is_empty() && self.licenses.is_empty()
+    }
+
+    /// Add what `other` knows, keeping the first occurrence of each entry
+    pub fn merge(&mut self, other: DependencyAdvice) {
+        for id in other.vulnerabilities {
+            if !self.vulnerabilities.contains(&id) {
+                self.vulnerabilities.push(id);
+            }
+        }
+        for license in other.licenses {
+            if !self.licenses.contains(&license) {
+                self.licenses.push(license);
+            }
+        }
+    }
+}
+
+/// Looks up vulnerabilities and licenses of dependencies, e.g. from an
+/// offline vulnerability database or a license scanner. The engine asks every
+/// registered advisor about each dependency node it renders, and shows the
+/// merged advice in the node's detail and modifiers.
+pub trait DependencyAdvisor: Send + Sync {
+    /// Short name of the advisor, e.g. `osv`
+    fn name(&self) -> &str;
+
+    /// Advice on the library with Maven-style `coordinates`
+    /// (`group:artifact:version`, with missing parts left out)
+    fn advise(&self, coordinates: &str) -> Option<DependencyAdvice>;
+}
@@ -7,6 +7,24 @@ pub trait AssetCap: Send + Sync {
         None
     }
 
+    /// Discoverer for a toolchain installed at `home` (e.g. the `[jdk] home`
+    /// setting), used instead of `global_asset_discoverer` when it returns one.
+    fn toolchain_asset_discoverer(&self, _home: &Path) -> Option<Box<dyn AssetDiscoverer>> {
+        None
+    }
+
+    /// Discoverer for the installed toolchain matching the version the project
+    /// at `project_root` targets, or `version` when set (e.g. the `[jdk]
+    /// version` setting). Used instead of `global_asset_discoverer` when it
+    /// returns one.
+    fn project_toolchain_discoverer(
+        &self,
+        _project_root: &Path,
+        _version: Option<&str>,
+    ) -> Option<Box<dyn AssetDiscoverer>> {
+        None
+    }
+
     fn project_asset_discoverer(&self, _project_root: &Path) -> Option<Box<dyn AssetDiscoverer>> {
         None
     }
@@ -11,6 +11,16 @@ pub trait SourceCollectArtifact: Send + Sync {
     fn collected_method_symbols(&self) -> &[String];
     fn provided_dependency_symbols(&self) -> &[String];
     fn required_dependency_symbols(&self) -> &[String];
+
+    /// Package declared by the file, if the language has packages
+    fn declared_package(&self) -> Option<&str> {
+        None
+    }
+
+    /// Module declared by the file (e.g. `module-info.java`)
+    fn module_descriptor(&self) -> Option<&crate::ModuleDescriptor> {
+        None
+    }
 }
 
 pub trait SourceAnalyzeArtifact: Send + Sync {
@@ -0,0 +1,47 @@
+use std::path::Path;
+
+/// Which side of a cross-language link an endpoint is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkRole {
+    /// Serves something under a key: the handler of `GET /users`, the Rust
+    /// function exported as `Java_com_app_Native_hash`
+    Provider,
+    /// Reaches something by key: `fetch('/api/users')`, a `native` method
+    Consumer,
+}
+
+/// A place in a source file where a symbol provides or consumes something
+/// another language refers to by key. The engine links the innermost indexed
+/// symbol enclosing the position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkEndpoint {
+    pub role: LinkRole,
+    pub key: String,
+    /// Zero-based line of the endpoint
+    pub line: usize,
+    /// Zero-based byte column of the endpoint
+    pub col: usize,
+}
+
+/// Connects symbols that no single language's resolution can, such as an
+/// HTTP client call in one language and the handler serving its route in
+/// another. The engine reads the endpoints of every indexed file the resolver
+/// supports and adds a `CrossLanguage` edge from each consumer to the
+/// providers whose key it matches.
+pub trait CrossLanguageResolver: Send + Sync {
+    /// Short name of the resolver, e.g. `http` or `jni`
+    fn name(&self) -> &str;
+
+    /// Whether the resolver reads endpoints from the file at `path`
+    fn supports_path(&self, path: &Path) -> bool;
+
+    /// Endpoints of the file at `path` with content `source`
+    fn endpoints(&self, path: &Path, source: &str) -> Vec<LinkEndpoint>;
+
+    /// Whether the consumer key `consumer` reaches the provider key
+    /// `provider`; resolvers normalizing keys differently on each side, e.g.
+    /// stripping an `/api` prefix added by a proxy, override it.
+    fn matches(&self, consumer: &str, provider: &str) -> bool {
+        consumer == provider
+    }
+}
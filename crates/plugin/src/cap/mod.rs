@@ -1,13 +1,17 @@
+pub mod advisory;
 pub mod asset;
 pub mod indexing;
+pub mod linker;
 pub mod matcher;
 pub mod metadata_codec;
 pub mod parse;
 pub mod presentation;
 pub mod runtime;
 
+pub use advisory::*;
 pub use asset::*;
 pub use indexing::*;
+pub use linker::*;
 pub use matcher::*;
 pub use metadata_codec::*;
 pub use parse::*;
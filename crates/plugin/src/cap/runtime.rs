@@ -1,7 +1,7 @@
 use crate::graph::CodeGraph;
-use naviscope_api::models::SymbolResolution;
 use naviscope_api::models::graph::DisplayGraphNode;
-use naviscope_api::models::symbol::{FqnId, Range};
+use naviscope_api::models::symbol::{FoldingRange, FqnId, Range};
+use naviscope_api::models::{ReferenceKind, ResolutionTrace, SignatureHelp, SymbolResolution};
 use tree_sitter::Tree;
 
 pub trait SymbolResolveService: Send + Sync {
@@ -13,6 +13,49 @@ pub trait SymbolResolveService: Send + Sync {
         byte_col: usize,
         index: &dyn CodeGraph,
     ) -> Option<SymbolResolution>;
+
+    /// Like `resolve_at`, but also records how the resolution was reached.
+    /// Languages without instrumented resolution report a single stage.
+    fn explain_at(
+        &self,
+        tree: &Tree,
+        source: &str,
+        line: usize,
+        byte_col: usize,
+        index: &dyn CodeGraph,
+    ) -> (Option<SymbolResolution>, ResolutionTrace) {
+        let mut trace = ResolutionTrace::default();
+        let point = tree_sitter::Point::new(line, byte_col);
+        if let Some(node) = tree
+            .root_node()
+            .named_descendant_for_point_range(point, point)
+        {
+            trace.symbol = node
+                .utf8_text(source.as_bytes())
+                .unwrap_or_default()
+                .to_string();
+            trace.syntax_kind = node.kind().to_string();
+        }
+        let resolution = self.resolve_at(tree, source, line, byte_col, index);
+        match &resolution {
+            Some(res) => trace.step("resolve", true, res.to_string()),
+            None => trace.step("resolve", false, "no resolution"),
+        }
+        trace.resolution = resolution.as_ref().map(|r| r.to_string());
+        (resolution, trace)
+    }
+
+    /// Signatures of the call whose argument list contains the position.
+    fn signature_help_at(
+        &self,
+        _tree: &Tree,
+        _source: &str,
+        _line: usize,
+        _byte_col: usize,
+        _index: &dyn CodeGraph,
+    ) -> Option<SignatureHelp> {
+        None
+    }
 }
 
 pub trait SymbolQueryService: Send + Sync {
@@ -35,6 +78,34 @@ pub trait LspSyntaxService: Send + Sync {
         target: &SymbolResolution,
         index: Option<&dyn CodeGraph>,
     ) -> Vec<Range>;
+
+    /// How the occurrence at `range` uses its target. Languages that cannot
+    /// tell type positions apart report every occurrence as a usage.
+    fn classify_occurrence(&self, _tree: &Tree, _source: &str, _range: &Range) -> ReferenceKind {
+        ReferenceKind::Usage
+    }
+
+    /// Why renaming the local variable or parameter `target` to `new_name`
+    /// would change the meaning of the file, if it would: a clash with
+    /// another declaration, or an existing reference the new name would
+    /// capture. Languages without scope analysis accept every name.
+    fn check_local_rename(
+        &self,
+        _source: &str,
+        _tree: &Tree,
+        _target: &SymbolResolution,
+        _new_name: &str,
+    ) -> Option<String> {
+        None
+    }
+
+    fn folding_ranges(&self, tree: &Tree, _source: &str) -> Vec<FoldingRange> {
+        crate::utils::folding_ranges(tree)
+    }
+
+    fn selection_ranges(&self, tree: &Tree, line: usize, byte_col: usize) -> Vec<Range> {
+        crate::utils::selection_ranges(tree, line, byte_col)
+    }
 }
 
 pub trait ReferenceCheckService: Send + Sync {
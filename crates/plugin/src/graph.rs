@@ -1,5 +1,6 @@
-use crate::model::{IndexNode, SourceFile};
+use crate::model::{IndexNode, PluginWarning, SourceFile};
 use crate::naming::NamingConvention;
+use naviscope_api::lifecycle::WarningSeverity;
 use naviscope_api::models::graph::{EdgeType, GraphEdge, GraphNode};
 use naviscope_api::models::symbol::{FqnId, FqnReader, NodeId, Symbol};
 use std::collections::HashMap;
@@ -25,6 +26,26 @@ pub enum GraphOp {
     },
     /// Update file metadata (hash, mtime)
     UpdateFile { metadata: SourceFile },
+    /// Update what is derived from the content of a file: its trigrams, used
+    /// by full-text search, and the fingerprints of its methods, used to find
+    /// duplicated code
+    UpdateContent {
+        path: Arc<Path>,
+        trigrams: Vec<u32>,
+        fingerprints: Vec<CodeFingerprint>,
+    },
+}
+
+/// Winnowed hashes of the normalized tokens of a method, identified by where
+/// its range starts in the file
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CodeFingerprint {
+    pub line: usize,
+    pub col: usize,
+    /// Number of tokens of the method
+    pub tokens: usize,
+    /// Sorted and distinct
+    pub hashes: Vec<u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -45,6 +66,8 @@ pub struct ResolvedUnit {
     pub naming_convention: Option<Arc<dyn NamingConvention>>,
     /// Deferred unresolved targets produced during analyze pass.
     pub deferred_symbols: Vec<DeferredSymbol>,
+    /// Constructs of the unit that were skipped or only partly indexed
+    pub warnings: Vec<PluginWarning>,
 }
 
 impl ResolvedUnit {
@@ -55,9 +78,18 @@ impl ResolvedUnit {
             identifiers: Vec::new(),
             naming_convention: None,
             deferred_symbols: Vec::new(),
+            warnings: Vec::new(),
         }
     }
 
+    pub fn warn(&mut self, severity: WarningSeverity, message: impl Into<String>) {
+        self.warnings.push(PluginWarning {
+            severity,
+            message: message.into(),
+            path: None,
+        });
+    }
+
     pub fn add_node(&mut self, data: IndexNode) {
         self.nodes.insert(data.id.clone(), data.clone());
         self.ops.push(GraphOp::AddNode { data: Some(data) });
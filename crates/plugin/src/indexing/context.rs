@@ -1,10 +1,15 @@
-use std::collections::HashMap;
+use super::modules::{ModuleDescriptor, module_reads};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Default)]
 pub struct ProjectSymbolTable {
-    pub type_symbols: std::collections::HashSet<String>,
-    pub method_symbols: std::collections::HashSet<String>,
+    pub type_symbols: HashSet<String>,
+    pub method_symbols: HashSet<String>,
+    /// Module descriptors declared in the project, keyed by module name
+    pub module_descriptors: HashMap<String, ModuleDescriptor>,
+    /// Package -> directories of the source files declaring it
+    pub package_dirs: HashMap<String, HashSet<PathBuf>>,
 }
 
 /// Project context generated during build indexing.
@@ -12,6 +17,8 @@ pub struct ProjectSymbolTable {
 pub struct ProjectContext {
     /// Mapping from path prefixes to module IDs (e.g., "/project/app" -> "module::app")
     pub path_to_module: HashMap<PathBuf, String>,
+    /// Detected or configured source roots holding annotation processor output
+    pub generated_roots: Vec<PathBuf>,
     /// Project-level collected symbol snapshot used by analyze/bind stage.
     pub symbol_table: ProjectSymbolTable,
 }
@@ -20,6 +27,7 @@ impl ProjectContext {
     pub fn new() -> Self {
         Self {
             path_to_module: HashMap::new(),
+            generated_roots: Vec::new(),
             symbol_table: ProjectSymbolTable::default(),
         }
     }
@@ -35,4 +43,117 @@ impl ProjectContext {
         }
         None
     }
+
+    /// Whether the file at `path` lies in a generated source root.
+    pub fn is_generated(&self, path: &Path) -> bool {
+        self.generated_roots
+            .iter()
+            .any(|root| path.starts_with(root))
+    }
+
+    /// Innermost declared module containing the given file path.
+    pub fn find_module_descriptor_for_path(&self, path: &Path) -> Option<&ModuleDescriptor> {
+        self.symbol_table
+            .module_descriptors
+            .values()
+            .filter(|m| path.starts_with(&m.root))
+            .max_by_key(|m| m.root.components().count())
+    }
+
+    /// Declared module whose sources declare `package`.
+    pub fn find_package_owner(&self, package: &str) -> Option<&ModuleDescriptor> {
+        self.symbol_table
+            .package_dirs
+            .get(package)?
+            .iter()
+            .find_map(|dir| self.find_module_descriptor_for_path(dir))
+    }
+
+    /// Whether code in the file `from` may use types of `package`.
+    ///
+    /// Only references between declared modules of the project are checked;
+    /// classpath code and packages outside the project are always accessible.
+    pub fn can_access(&self, from: &Path, package: &str) -> bool {
+        let (Some(reader), Some(owner)) = (
+            self.find_module_descriptor_for_path(from),
+            self.find_package_owner(package),
+        ) else {
+            return true;
+        };
+        if reader.name == owner.name {
+            return true;
+        }
+        let descriptors = &self.symbol_table.module_descriptors;
+        module_reads(reader, &owner.name, |name| descriptors.get(name))
+            && owner.exports_to(package, &reader.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexing::modules::{ModuleExport, ModuleRequire};
+
+    fn descriptor(
+        name: &str,
+        requires: &[(&str, bool)],
+        exports: &[(&str, &[&str])],
+    ) -> ModuleDescriptor {
+        ModuleDescriptor {
+            name: name.to_string(),
+            root: PathBuf::from(format!("/p/{}", name)),
+            requires: requires
+                .iter()
+                .map(|(module, transitive)| ModuleRequire {
+                    module: module.to_string(),
+                    transitive: *transitive,
+                })
+                .collect(),
+            exports: exports
+                .iter()
+                .map(|(package, to)| ModuleExport {
+                    package: package.to_string(),
+                    to: to.iter().map(|m| m.to_string()).collect(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn access_follows_requires_and_exports() {
+        let mut ctx = ProjectContext::new();
+        for module in [
+            descriptor("app", &[("api", false)], &[]),
+            descriptor("api", &[("core", true)], &[("api.pub", &[])]),
+            descriptor("core", &[], &[("core.pub", &[]), ("core.friend", &["api"])]),
+        ] {
+            ctx.symbol_table
+                .module_descriptors
+                .insert(module.name.clone(), module);
+        }
+        for (package, dir) in [
+            ("api.pub", "/p/api/api/pub"),
+            ("api.internal", "/p/api/api/internal"),
+            ("core.pub", "/p/core/core/pub"),
+            ("core.friend", "/p/core/core/friend"),
+        ] {
+            ctx.symbol_table
+                .package_dirs
+                .entry(package.to_string())
+                .or_default()
+                .insert(PathBuf::from(dir));
+        }
+
+        let app = Path::new("/p/app/app/Main.java");
+        assert!(ctx.can_access(app, "api.pub"));
+        assert!(!ctx.can_access(app, "api.internal"));
+        // Read through `requires transitive`
+        assert!(ctx.can_access(app, "core.pub"));
+        // Qualified export to another module
+        assert!(!ctx.can_access(app, "core.friend"));
+        assert!(ctx.can_access(Path::new("/p/api/api/pub/A.java"), "core.friend"));
+        // Packages outside the project and classpath code are not checked
+        assert!(ctx.can_access(app, "java.util"));
+        assert!(ctx.can_access(Path::new("/elsewhere/B.java"), "api.internal"));
+    }
 }
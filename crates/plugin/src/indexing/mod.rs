@@ -1,3 +1,5 @@
 mod context;
+mod modules;
 
 pub use context::*;
+pub use modules::*;
@@ -0,0 +1,72 @@
+//! Module descriptors (Java `module-info.java`) and readability between them
+//!
+//! Code on the classpath can see every package. Code inside a declared
+//! module only sees its own packages and the packages exported by modules it
+//! reads: the ones it `requires`, plus those they `requires transitive`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleRequire {
+    pub module: String,
+    /// Readers of the requiring module also read this one
+    pub transitive: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleExport {
+    pub package: String,
+    /// Modules the export is restricted to; empty for everyone
+    pub to: Vec<String>,
+}
+
+/// A declared module and the directory its descriptor lives in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleDescriptor {
+    pub name: String,
+    /// Directory of the descriptor; files below it belong to the module
+    pub root: PathBuf,
+    pub requires: Vec<ModuleRequire>,
+    pub exports: Vec<ModuleExport>,
+}
+
+impl ModuleDescriptor {
+    /// Whether `package` is exported to the module named `reader`
+    pub fn exports_to(&self, package: &str, reader: &str) -> bool {
+        self.exports
+            .iter()
+            .any(|e| e.package == package && (e.to.is_empty() || e.to.iter().any(|m| m == reader)))
+    }
+}
+
+/// Whether `reader` reads `target` through its requires, following
+/// `requires transitive` of the modules it reads. `lookup` returns the
+/// descriptors of declared modules.
+pub fn module_reads<'a>(
+    reader: &'a ModuleDescriptor,
+    target: &str,
+    lookup: impl Fn(&str) -> Option<&'a ModuleDescriptor>,
+) -> bool {
+    let mut visited = HashSet::new();
+    let mut pending: Vec<&str> = reader.requires.iter().map(|r| r.module.as_str()).collect();
+    while let Some(name) = pending.pop() {
+        if name == target {
+            return true;
+        }
+        if !visited.insert(name) {
+            continue;
+        }
+        if let Some(required) = lookup(name) {
+            pending.extend(
+                required
+                    .requires
+                    .iter()
+                    .filter(|r| r.transitive)
+                    .map(|r| r.module.as_str()),
+            );
+        }
+    }
+    false
+}
@@ -1,4 +1,5 @@
 use crate::core::SymbolInterner;
+use naviscope_api::lifecycle::WarningSeverity;
 use naviscope_api::models::graph::{
     DisplaySymbolLocation, EdgeType, EmptyMetadata, NodeKind, NodeMetadata, NodeSource,
     ResolutionStatus,
@@ -141,10 +142,22 @@ pub struct GlobalParseResult {
     pub tree: Option<Tree>,
 }
 
+/// A construct a plugin skipped or could only partly index. The engine adds
+/// the file and phase and keeps it with the index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginWarning {
+    pub severity: WarningSeverity,
+    pub message: String,
+    /// File the warning concerns, when the unit spans several
+    pub path: Option<PathBuf>,
+}
+
 /// Result of parsing a build file
 pub struct BuildParseResult {
     // For now, mirroring what we have. Can be expanded.
     pub content: BuildContent,
+    /// Parts of the file that could not be parsed
+    pub warnings: Vec<PluginWarning>,
 }
 
 #[derive(Debug, Clone)]
@@ -243,6 +256,9 @@ pub struct SourceFile {
     pub path: PathBuf,
     pub content_hash: u64,
     pub last_modified: u64, // UNIX timestamp
+    /// Output of an annotation processor rather than hand-written code
+    #[serde(default)]
+    pub generated: bool,
 }
 
 impl SourceFile {
@@ -251,6 +267,7 @@ impl SourceFile {
             path,
             content_hash,
             last_modified,
+            generated: false,
         }
     }
 }
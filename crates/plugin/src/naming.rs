@@ -224,6 +224,11 @@ pub trait NamingConvention: Send + Sync + Debug {
     fn is_member_fqn(&self, fqn: &str) -> bool {
         is_member_fqn(fqn)
     }
+
+    /// Stable, language-tagged ID for a symbol of `lang` named `fqn`.
+    fn symbol_id(&self, lang: &str, fqn: &str) -> String {
+        naviscope_api::models::symbol_id(lang, fqn)
+    }
 }
 
 /// A standard naming convention suitable for most polyglot scenarios.
@@ -418,6 +423,21 @@ mod tests {
         assert_eq!(parts[2], (NodeKind::Method, "foo(T1)".to_string()));
     }
 
+    #[test]
+    fn symbol_ids_round_trip() {
+        use naviscope_api::models::parse_symbol_id;
+
+        let id = StandardNamingConvention.symbol_id("java", "com.app.Foo#bar(int)");
+        assert_eq!(id, "java:com.app.Foo#bar(int)");
+        assert_eq!(parse_symbol_id(&id), Some(("java", "com.app.Foo#bar(int)")));
+        assert_eq!(
+            parse_symbol_id("gradle:project:app"),
+            Some(("gradle", "project:app"))
+        );
+        assert_eq!(parse_symbol_id("com.app.Foo"), None);
+        assert_eq!(parse_symbol_id("Foo:bar"), None);
+    }
+
     #[test]
     fn two_overloads_produce_distinct_fqn_paths() {
         let conv = StandardNamingConvention;
@@ -1,5 +1,5 @@
 use naviscope_api::models::graph::{DisplayGraphNode, DisplaySymbolLocation, NodeKind, NodeSource};
-use naviscope_api::models::symbol::Range;
+use naviscope_api::models::symbol::{FoldingKind, FoldingRange, Range};
 use tree_sitter::{Language, Node, Query};
 
 /// Converts a tree-sitter range to our internal Range model.
@@ -12,6 +12,87 @@ pub fn range_from_ts(range: tree_sitter::Range) -> Range {
     }
 }
 
+/// Collapsible regions of a syntax tree.
+///
+/// Multi-line comments, runs of import declarations and brace-delimited nodes
+/// (bodies, blocks, initializers) fold. Blocks stop before the line holding
+/// the closing brace so it stays visible.
+pub fn folding_ranges(tree: &tree_sitter::Tree) -> Vec<FoldingRange> {
+    let mut ranges = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+
+        let mut imports: Option<(usize, usize)> = None;
+        for child in &children {
+            if child.kind().contains("import") {
+                let end = child.end_position().row;
+                imports = Some(match imports {
+                    Some((start, _)) => (start, end),
+                    None => (child.start_position().row, end),
+                });
+                continue;
+            }
+            if let Some((start_line, end_line)) = imports.take() {
+                push_fold(&mut ranges, start_line, end_line, FoldingKind::Imports);
+            }
+            stack.push(*child);
+        }
+        if let Some((start_line, end_line)) = imports {
+            push_fold(&mut ranges, start_line, end_line, FoldingKind::Imports);
+        }
+
+        let start_line = node.start_position().row;
+        let end_line = node.end_position().row;
+        if node.kind().contains("comment") {
+            push_fold(&mut ranges, start_line, end_line, FoldingKind::Comment);
+        } else if children.first().is_some_and(|c| c.kind() == "{")
+            && children.last().is_some_and(|c| c.kind() == "}")
+        {
+            push_fold(
+                &mut ranges,
+                start_line,
+                end_line.saturating_sub(1),
+                FoldingKind::Region,
+            );
+        }
+    }
+    ranges.sort_by_key(|r| (r.start_line, r.end_line));
+    ranges.dedup();
+    ranges
+}
+
+fn push_fold(
+    ranges: &mut Vec<FoldingRange>,
+    start_line: usize,
+    end_line: usize,
+    kind: FoldingKind,
+) {
+    if end_line > start_line {
+        ranges.push(FoldingRange {
+            start_line,
+            end_line,
+            kind,
+        });
+    }
+}
+
+/// Ranges of the syntax nodes enclosing a position, innermost first.
+pub fn selection_ranges(tree: &tree_sitter::Tree, line: usize, byte_col: usize) -> Vec<Range> {
+    let point = tree_sitter::Point::new(line, byte_col);
+    let mut ranges: Vec<Range> = Vec::new();
+    let mut current = tree.root_node().descendant_for_point_range(point, point);
+    while let Some(node) = current {
+        let range = range_from_ts(node.range());
+        if ranges.last() != Some(&range) {
+            ranges.push(range);
+        }
+        current = node.parent();
+    }
+    ranges
+}
+
 /// Loads a Tree-sitter query from an SCM string.
 pub fn load_query(
     language: &Language,
@@ -50,6 +131,8 @@ pub fn build_symbol_hierarchy(raw_symbols: Vec<RawSymbol>) -> Vec<DisplayGraphNo
     for (i, raw) in raw_symbols.iter().enumerate() {
         flat_symbols.push(DisplayGraphNode {
             id: raw.name.clone(),
+            symbol_id: String::new(),
+            generated: false,
             name: raw.name.clone(),
             kind: raw.kind.clone(),
             lang: String::new(),
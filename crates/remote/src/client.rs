@@ -0,0 +1,702 @@
+use crate::protocol::{Call, RPC_PATH, Request, Response};
+use async_trait::async_trait;
+use futures::{sink::SinkExt, stream::StreamExt};
+use naviscope_api::graph::{
+    ExportFormat, ExportSummary, ExternalApiReport, GraphDelta, GraphQuery, GraphStats, NodeNote,
+    QueryResult, QueryValidation, SafeDeleteReport,
+};
+use naviscope_api::lifecycle::{
+    AuditEntry, BuildResume, EpochProgress, HostSettings, IndexWarning, RuntimeMetrics, WatchFlow,
+};
+use naviscope_api::models::{
+    CallHierarchyIncomingCall, CallHierarchyOutgoingCall, DisplayGraphNode, FoldingRange, Language,
+    PositionContext, Range, ReferenceQuery, ReferenceSite, ResolutionTrace, ResolvedSymbol,
+    SignatureHelp, SymbolLocation, SymbolQuery, SymbolResolution, TypeUsageFilter,
+};
+use naviscope_api::navigation::ResolveResult;
+use naviscope_api::{
+    ApiError, ApiResult, CallHierarchyAnalyzer, EngineLifecycle, EngineWatchHandle, GraphService,
+    NavigationService, NaviscopeEngine, ReferenceAnalyzer, StubCacheManager, SymbolInfoProvider,
+    SymbolNavigator,
+};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info};
+
+/// An engine served by `naviscope serve-index` on another machine.
+///
+/// Queries and navigation are answered by the server, which also owns the
+/// project tree: it indexes and watches it itself, so loading, refreshing,
+/// watching, rebuilding, saving and file updates are no-ops here, and
+/// exporting or clearing the index fails. Paths in requests and results are
+/// those of the server, which only serves the sources of indexed files. The
+/// connection is opened on first use and reopened after it drops.
+pub struct RemoteEngine {
+    url: String,
+    token: Option<String>,
+    stub_cache: Arc<dyn StubCacheManager>,
+    connection: tokio::sync::Mutex<Option<Arc<Connection>>>,
+    next_id: AtomicU64,
+}
+
+struct Connection {
+    outgoing: mpsc::UnboundedSender<String>,
+    /// Request id -> caller waiting for its response
+    pending: Arc<Mutex<HashMap<u64, oneshot::Sender<Response>>>>,
+    closed: CancellationToken,
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.closed.cancel();
+    }
+}
+
+impl RemoteEngine {
+    /// Client of the server at `addr`, either `host:port` or a `ws://` URL.
+    /// `stub_cache` is the local cache, which the server's does not replace.
+    pub fn new(addr: &str, token: Option<String>, stub_cache: Arc<dyn StubCacheManager>) -> Self {
+        let url = if addr.starts_with("ws://") || addr.starts_with("wss://") {
+            addr.to_string()
+        } else {
+            format!("ws://{}{}", addr, RPC_PATH)
+        };
+        Self {
+            url,
+            token,
+            stub_cache,
+            connection: tokio::sync::Mutex::new(None),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// URL of the server's endpoint
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn unreachable(&self, reason: impl std::fmt::Display) -> ApiError {
+        ApiError::Internal(format!(
+            "Cannot reach the index server at {}: {}",
+            self.url, reason
+        ))
+    }
+
+    async fn connection(&self) -> ApiResult<Arc<Connection>> {
+        let mut connection = self.connection.lock().await;
+        if let Some(open) = connection.as_ref().filter(|c| !c.closed.is_cancelled()) {
+            return Ok(open.clone());
+        }
+        let open = Arc::new(self.connect().await?);
+        *connection = Some(open.clone());
+        Ok(open)
+    }
+
+    async fn connect(&self) -> ApiResult<Connection> {
+        let mut request = self
+            .url
+            .as_str()
+            .into_client_request()
+            .map_err(|e| self.unreachable(e))?;
+        if let Some(token) = &self.token {
+            let value = format!("Bearer {}", token)
+                .parse()
+                .map_err(|_| ApiError::InvalidArgument("malformed token".to_string()))?;
+            request.headers_mut().insert("Authorization", value);
+        }
+        let (ws_stream, _) = connect_async(request)
+            .await
+            .map_err(|e| self.unreachable(e))?;
+        info!("Connected to the index server at {}", self.url);
+
+        let (mut ws_sink, mut ws_stream) = ws_stream.split();
+        let (outgoing, mut requests) = mpsc::unbounded_channel::<String>();
+        let pending: Arc<Mutex<HashMap<u64, oneshot::Sender<Response>>>> = Arc::default();
+        let closed = CancellationToken::new();
+
+        tokio::spawn({
+            let closed = closed.clone();
+            async move {
+                loop {
+                    let text = tokio::select! {
+                        text = requests.recv() => text,
+                        _ = closed.cancelled() => None,
+                    };
+                    let Some(text) = text else {
+                        break;
+                    };
+                    if ws_sink.send(Message::Text(text.into())).await.is_err() {
+                        break;
+                    }
+                }
+                closed.cancel();
+                let _ = ws_sink.close().await;
+            }
+        });
+
+        tokio::spawn({
+            let pending = pending.clone();
+            let closed = closed.clone();
+            let url = self.url.clone();
+            async move {
+                loop {
+                    let msg = tokio::select! {
+                        msg = ws_stream.next() => msg,
+                        _ = closed.cancelled() => None,
+                    };
+                    let text = match msg {
+                        Some(Ok(Message::Text(text))) => text.to_string(),
+                        Some(Ok(Message::Binary(data))) => {
+                            String::from_utf8_lossy(&data).into_owned()
+                        }
+                        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                        Some(Ok(_)) => continue,
+                    };
+                    let Ok(response) = serde_json::from_str::<Response>(&text) else {
+                        debug!("Ignoring unreadable message from {}", url);
+                        continue;
+                    };
+                    let waiting = response
+                        .id
+                        .and_then(|id| pending.lock().unwrap().remove(&id));
+                    if let Some(waiting) = waiting {
+                        let _ = waiting.send(response);
+                    }
+                }
+                // Callers still waiting learn that the connection is gone
+                closed.cancel();
+                pending.lock().unwrap().clear();
+                debug!("Disconnected from the index server at {}", url);
+            }
+        });
+
+        Ok(Connection {
+            outgoing,
+            pending,
+            closed,
+        })
+    }
+
+    async fn call<T: DeserializeOwned>(&self, call: Call) -> ApiResult<T> {
+        let connection = self.connection().await?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let text = serde_json::to_string(&Request::new(id, call))
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+        let (sender, receiver) = oneshot::channel();
+        connection.pending.lock().unwrap().insert(id, sender);
+        if connection.closed.is_cancelled() || connection.outgoing.send(text).is_err() {
+            connection.pending.lock().unwrap().remove(&id);
+            return Err(self.unreachable("connection closed"));
+        }
+        let response = receiver
+            .await
+            .map_err(|_| self.unreachable("connection closed"))?;
+        serde_json::from_value(response.outcome()?).map_err(|e| {
+            ApiError::Internal(format!("Unexpected reply from the index server: {}", e))
+        })
+    }
+}
+
+/// The server watches its own tree
+struct RemoteWatch;
+
+impl EngineWatchHandle for RemoteWatch {
+    fn stop(&self) {}
+}
+
+#[async_trait]
+impl GraphService for RemoteEngine {
+    async fn query(&self, query: &GraphQuery) -> ApiResult<QueryResult> {
+        self.call(Call::Query {
+            query: query.clone(),
+        })
+        .await
+    }
+
+    async fn get_stats(&self) -> ApiResult<GraphStats> {
+        self.call(Call::GetStats).await
+    }
+
+    async fn validate_query(&self, query: &serde_json::Value) -> ApiResult<QueryValidation> {
+        self.call(Call::ValidateQuery {
+            query: query.clone(),
+        })
+        .await
+    }
+
+    async fn graph_epoch(&self) -> ApiResult<u64> {
+        self.call(Call::GraphEpoch).await
+    }
+
+    async fn wait_for_commit(&self, epoch: u64) -> ApiResult<Option<u64>> {
+        self.call(Call::WaitForCommit { epoch }).await
+    }
+
+    async fn get_node_display(&self, fqn: &str) -> ApiResult<Option<DisplayGraphNode>> {
+        self.call(Call::GetNodeDisplay {
+            fqn: fqn.to_string(),
+        })
+        .await
+    }
+
+    async fn safe_delete(&self, fqn: &str) -> ApiResult<SafeDeleteReport> {
+        self.call(Call::SafeDelete {
+            fqn: fqn.to_string(),
+        })
+        .await
+    }
+
+    async fn references(
+        &self,
+        fqn: &str,
+        type_usages: TypeUsageFilter,
+    ) -> ApiResult<Vec<ReferenceSite>> {
+        self.call(Call::References {
+            fqn: fqn.to_string(),
+            type_usages,
+        })
+        .await
+    }
+
+    async fn notes(&self, fqn: &str) -> ApiResult<Vec<NodeNote>> {
+        self.call(Call::Notes {
+            fqn: fqn.to_string(),
+        })
+        .await
+    }
+
+    async fn annotate(&self, fqn: &str, text: &str, tags: &[String]) -> ApiResult<NodeNote> {
+        self.call(Call::Annotate {
+            fqn: fqn.to_string(),
+            text: text.to_string(),
+            tags: tags.to_vec(),
+        })
+        .await
+    }
+
+    async fn remove_note(&self, fqn: &str, id: u64) -> ApiResult<bool> {
+        self.call(Call::RemoveNote {
+            fqn: fqn.to_string(),
+            id,
+        })
+        .await
+    }
+
+    async fn save_snapshot(&self, name: &str) -> ApiResult<PathBuf> {
+        self.call(Call::SaveSnapshot {
+            name: name.to_string(),
+        })
+        .await
+    }
+
+    async fn diff(&self, reference: &str) -> ApiResult<GraphDelta> {
+        self.call(Call::Diff {
+            reference: reference.to_string(),
+        })
+        .await
+    }
+
+    async fn index_revision(&self, rev: &str) -> ApiResult<PathBuf> {
+        self.call(Call::IndexRevision {
+            rev: rev.to_string(),
+        })
+        .await
+    }
+
+    async fn diff_between(&self, old: &str, new: &str) -> ApiResult<GraphDelta> {
+        self.call(Call::DiffBetween {
+            old: old.to_string(),
+            new: new.to_string(),
+        })
+        .await
+    }
+
+    async fn export_graph(
+        &self,
+        _format: ExportFormat,
+        _out_dir: &Path,
+    ) -> ApiResult<ExportSummary> {
+        Err(server_side_only("exported"))
+    }
+
+    async fn export_bundle(&self, _path: &Path) -> ApiResult<ExportSummary> {
+        Err(server_side_only("exported"))
+    }
+
+    async fn external_api_usage(
+        &self,
+        artifact: Option<&str>,
+        top: usize,
+    ) -> ApiResult<ExternalApiReport> {
+        self.call(Call::ExternalApiUsage {
+            artifact: artifact.map(str::to_string),
+            top,
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl NavigationService for RemoteEngine {
+    async fn resolve_path(
+        &self,
+        target: &str,
+        current_context: Option<&str>,
+    ) -> ApiResult<ResolveResult> {
+        self.call(Call::ResolvePath {
+            target: target.to_string(),
+            current_context: current_context.map(str::to_string),
+        })
+        .await
+    }
+
+    async fn nearest_existing(&self, fqn: &str) -> ApiResult<Option<String>> {
+        self.call(Call::NearestExisting {
+            fqn: fqn.to_string(),
+        })
+        .await
+    }
+
+    async fn get_completion_candidates(
+        &self,
+        prefix: &str,
+        limit: usize,
+    ) -> ApiResult<Vec<String>> {
+        self.call(Call::GetCompletionCandidates {
+            prefix: prefix.to_string(),
+            limit,
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl SymbolNavigator for RemoteEngine {
+    async fn resolve_symbol_at(
+        &self,
+        ctx: &PositionContext,
+    ) -> ApiResult<Option<SymbolResolution>> {
+        self.call(Call::ResolveSymbolAt { ctx: ctx.clone() }).await
+    }
+
+    async fn resolve_at(
+        &self,
+        path: &Path,
+        line: u32,
+        col: u32,
+    ) -> ApiResult<Option<ResolvedSymbol>> {
+        self.call(Call::ResolveAt {
+            path: path.to_path_buf(),
+            line,
+            col,
+        })
+        .await
+    }
+
+    async fn resolve_batch(
+        &self,
+        path: &Path,
+        positions: &[(u32, u32)],
+    ) -> ApiResult<Vec<Option<ResolvedSymbol>>> {
+        self.call(Call::ResolveBatch {
+            path: path.to_path_buf(),
+            positions: positions.to_vec(),
+        })
+        .await
+    }
+
+    async fn explain_resolution(
+        &self,
+        ctx: &PositionContext,
+    ) -> ApiResult<Option<ResolutionTrace>> {
+        self.call(Call::ExplainResolution { ctx: ctx.clone() })
+            .await
+    }
+
+    async fn find_definitions(&self, query: &SymbolQuery) -> ApiResult<Vec<SymbolLocation>> {
+        self.call(Call::FindDefinitions {
+            query: query.clone(),
+        })
+        .await
+    }
+
+    async fn find_type_definitions(&self, query: &SymbolQuery) -> ApiResult<Vec<SymbolLocation>> {
+        self.call(Call::FindTypeDefinitions {
+            query: query.clone(),
+        })
+        .await
+    }
+
+    async fn find_implementations(&self, query: &SymbolQuery) -> ApiResult<Vec<SymbolLocation>> {
+        self.call(Call::FindImplementations {
+            query: query.clone(),
+        })
+        .await
+    }
+
+    async fn find_highlights(&self, ctx: &PositionContext) -> ApiResult<Vec<Range>> {
+        self.call(Call::FindHighlights { ctx: ctx.clone() }).await
+    }
+
+    async fn prepare_rename(&self, ctx: &PositionContext) -> ApiResult<Option<Range>> {
+        self.call(Call::PrepareRename { ctx: ctx.clone() }).await
+    }
+
+    async fn rename_local(&self, ctx: &PositionContext, new_name: &str) -> ApiResult<Vec<Range>> {
+        self.call(Call::RenameLocal {
+            ctx: ctx.clone(),
+            new_name: new_name.to_string(),
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl ReferenceAnalyzer for RemoteEngine {
+    async fn find_reference_sites(&self, query: &ReferenceQuery) -> ApiResult<Vec<ReferenceSite>> {
+        self.call(Call::FindReferenceSites {
+            query: query.clone(),
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl CallHierarchyAnalyzer for RemoteEngine {
+    async fn find_incoming_calls(&self, fqn: &str) -> ApiResult<Vec<CallHierarchyIncomingCall>> {
+        self.call(Call::FindIncomingCalls {
+            fqn: fqn.to_string(),
+        })
+        .await
+    }
+
+    async fn find_outgoing_calls(&self, fqn: &str) -> ApiResult<Vec<CallHierarchyOutgoingCall>> {
+        self.call(Call::FindOutgoingCalls {
+            fqn: fqn.to_string(),
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl SymbolInfoProvider for RemoteEngine {
+    async fn get_symbol_info(&self, fqn: &str) -> ApiResult<Option<DisplayGraphNode>> {
+        self.call(Call::GetSymbolInfo {
+            fqn: fqn.to_string(),
+        })
+        .await
+    }
+
+    async fn get_signature_help(&self, ctx: &PositionContext) -> ApiResult<Option<SignatureHelp>> {
+        self.call(Call::GetSignatureHelp { ctx: ctx.clone() }).await
+    }
+
+    async fn get_document_symbols(&self, uri: &str) -> ApiResult<Vec<DisplayGraphNode>> {
+        self.call(Call::GetDocumentSymbols {
+            uri: uri.to_string(),
+        })
+        .await
+    }
+
+    async fn get_folding_ranges(
+        &self,
+        uri: &str,
+        content: Option<String>,
+    ) -> ApiResult<Vec<FoldingRange>> {
+        self.call(Call::GetFoldingRanges {
+            uri: uri.to_string(),
+            content,
+        })
+        .await
+    }
+
+    async fn get_selection_ranges(&self, ctx: &PositionContext) -> ApiResult<Vec<Range>> {
+        self.call(Call::GetSelectionRanges { ctx: ctx.clone() })
+            .await
+    }
+
+    async fn get_language_for_document(&self, uri: &str) -> ApiResult<Option<Language>> {
+        self.call(Call::GetLanguageForDocument {
+            uri: uri.to_string(),
+        })
+        .await
+    }
+
+    async fn read_source(&self, path: &Path) -> ApiResult<Arc<str>> {
+        let source: String = self
+            .call(Call::ReadSource {
+                path: path.to_path_buf(),
+            })
+            .await?;
+        Ok(source.into())
+    }
+}
+
+#[async_trait]
+impl EngineLifecycle for RemoteEngine {
+    async fn rebuild(&self) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn update_files(&self, _files: Vec<PathBuf>) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn pending_resume(&self) -> ApiResult<Option<BuildResume>> {
+        self.call(Call::PendingResume).await
+    }
+
+    async fn reload_config(&self) -> ApiResult<()> {
+        self.call(Call::ReloadConfig).await
+    }
+
+    async fn host_settings(&self) -> ApiResult<HostSettings> {
+        self.call(Call::HostSettings).await
+    }
+
+    async fn watch_flow(&self) -> ApiResult<WatchFlow> {
+        self.call(Call::WatchFlow).await
+    }
+
+    async fn runtime_metrics(&self) -> ApiResult<RuntimeMetrics> {
+        self.call(Call::RuntimeMetrics).await
+    }
+
+    async fn epoch_progress(&self, epoch: u64) -> ApiResult<Option<EpochProgress>> {
+        self.call(Call::EpochProgress { epoch }).await
+    }
+
+    async fn index_warnings(&self) -> ApiResult<Vec<IndexWarning>> {
+        self.call(Call::IndexWarnings).await
+    }
+
+    async fn record_audit(&self, entry: AuditEntry) -> ApiResult<()> {
+        self.call(Call::RecordAudit { entry }).await
+    }
+
+    async fn audit_log(&self) -> ApiResult<Vec<AuditEntry>> {
+        self.call(Call::AuditLog).await
+    }
+
+    /// Checks that the server answers; its index is already loaded
+    async fn load(&self) -> ApiResult<bool> {
+        self.graph_epoch().await?;
+        Ok(true)
+    }
+
+    async fn save(&self) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn refresh(&self) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn start_watch(&self) -> ApiResult<Arc<dyn EngineWatchHandle>> {
+        Ok(Arc::new(RemoteWatch))
+    }
+
+    async fn clear_index(&self) -> ApiResult<()> {
+        Err(server_side_only("cleared"))
+    }
+}
+
+/// Error for what only the server may do to its index
+fn server_side_only(action: &str) -> ApiError {
+    ApiError::InvalidArgument(format!(
+        "the index of a remote server cannot be {} from a client",
+        action
+    ))
+}
+
+impl NaviscopeEngine for RemoteEngine {
+    fn get_stub_cache_manager(&self) -> Arc<dyn StubCacheManager> {
+        self.stub_cache.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use naviscope_core::facade::EngineHandle;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn requests_are_answered_by_the_served_engine() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("A.java"), "class A {}").unwrap();
+        let engine = naviscope_core::runtime::NaviscopeEngine::builder(dir.path().to_path_buf())
+            .with_language_caps(naviscope_java::java_caps().unwrap())
+            .build();
+        let engine: Arc<dyn NaviscopeEngine> =
+            Arc::new(EngineHandle::from_engine(Arc::new(engine)));
+        engine
+            .update_files(vec![dir.path().join("A.java")])
+            .await
+            .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let cancel_token = CancellationToken::new();
+        tokio::spawn(crate::serve(
+            engine.clone(),
+            listener,
+            "secret".to_string(),
+            cancel_token.clone(),
+        ));
+
+        let remote = RemoteEngine::new(
+            &addr,
+            Some("secret".to_string()),
+            engine.get_stub_cache_manager(),
+        );
+        std::fs::write(dir.path().join("notes.txt"), "secret").unwrap();
+        assert!(remote.load().await.unwrap());
+        let (epoch, stats) = tokio::join!(remote.graph_epoch(), remote.get_stats());
+        assert_eq!(epoch.unwrap(), engine.graph_epoch().await.unwrap());
+        assert!(stats.unwrap().node_count > 0);
+        assert!(remote.get_node_display("a.B").await.unwrap().is_none());
+        let source = remote.read_source(&dir.path().join("A.java")).await;
+        assert_eq!(&*source.unwrap(), "class A {}");
+        // Engine errors keep their kind
+        let missing = remote.read_source(&dir.path().join("B.java")).await;
+        assert!(matches!(missing, Err(ApiError::NotFound(_))));
+        // Files outside the index are not served
+        let unindexed = remote.read_source(&dir.path().join("notes.txt")).await;
+        assert!(matches!(unindexed, Err(ApiError::NotFound(_))));
+        // Nor can a client write to or clear the server's index
+        assert!(remote.export_bundle(&dir.path().join("out")).await.is_err());
+        assert!(remote.clear_index().await.is_err());
+        // Snapshots are named, never placed at a path of the client's choosing
+        let outside = dir.path().join("outside.bin");
+        let saved = remote.save_snapshot(&outside.to_string_lossy()).await;
+        assert!(matches!(saved, Err(ApiError::InvalidArgument(_))));
+        assert!(!outside.exists());
+        let read = remote
+            .diff(&dir.path().join("A.java").to_string_lossy())
+            .await;
+        assert!(matches!(read, Err(ApiError::InvalidArgument(_))));
+        let between = remote.diff_between("base", "../base").await;
+        assert!(matches!(between, Err(ApiError::InvalidArgument(_))));
+        assert!(engine.get_stats().await.unwrap().node_count > 0);
+
+        let denied = RemoteEngine::new(
+            &addr,
+            Some("guess".to_string()),
+            engine.get_stub_cache_manager(),
+        );
+        assert!(denied.load().await.is_err());
+
+        // Calls fail once the server is gone
+        cancel_token.cancel();
+        drop(remote.connection.lock().await.take());
+        assert!(remote.graph_epoch().await.is_err());
+    }
+}
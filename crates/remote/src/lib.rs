@@ -0,0 +1,14 @@
+//! Serving an index to other machines
+//!
+//! A huge project can be indexed once, on a machine that can afford it, and
+//! queried from others: `serve` hosts an engine behind a WebSocket
+//! endpoint speaking JSON-RPC, and `RemoteEngine` implements the engine API
+//! by forwarding every query, navigation and semantic request to it. The
+//! server keeps indexing and watching its tree; clients do neither.
+
+mod client;
+mod protocol;
+mod server;
+
+pub use client::RemoteEngine;
+pub use server::serve;
@@ -0,0 +1,321 @@
+//! Messages exchanged with a remote index server
+//!
+//! JSON-RPC 2.0 over WebSocket text frames: one request or response per
+//! frame. Methods are named after the engine methods they call and take
+//! their arguments by name, e.g.
+//! `{"jsonrpc": "2.0", "id": 1, "method": "get_node_display", "params": {"fqn": "a.B"}}`.
+//! Responses may arrive out of order; a request waiting for a commit does
+//! not hold back the others.
+
+use naviscope_api::ApiError;
+use naviscope_api::graph::GraphQuery;
+use naviscope_api::lifecycle::AuditEntry;
+use naviscope_api::models::{PositionContext, ReferenceQuery, SymbolQuery, TypeUsageFilter};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Path of the WebSocket endpoint
+pub(crate) const RPC_PATH: &str = "/rpc";
+
+const JSONRPC_VERSION: &str = "2.0";
+
+/// Error codes of JSON-RPC, and of the engine errors carried over it
+pub(crate) mod code {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+    pub const NOT_FOUND: i64 = -32001;
+    pub const UNSUPPORTED: i64 = -32002;
+    /// As in LSP
+    pub const CANCELLED: i64 = -32800;
+}
+
+/// An engine call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub(crate) enum Call {
+    // GraphService
+    Query {
+        query: GraphQuery,
+    },
+    GetStats,
+    ValidateQuery {
+        query: serde_json::Value,
+    },
+    GraphEpoch,
+    WaitForCommit {
+        epoch: u64,
+    },
+    GetNodeDisplay {
+        fqn: String,
+    },
+    SafeDelete {
+        fqn: String,
+    },
+    References {
+        fqn: String,
+        type_usages: TypeUsageFilter,
+    },
+    Notes {
+        fqn: String,
+    },
+    Annotate {
+        fqn: String,
+        text: String,
+        tags: Vec<String>,
+    },
+    RemoveNote {
+        fqn: String,
+        id: u64,
+    },
+    SaveSnapshot {
+        name: String,
+    },
+    Diff {
+        reference: String,
+    },
+    IndexRevision {
+        rev: String,
+    },
+    DiffBetween {
+        old: String,
+        new: String,
+    },
+    ExternalApiUsage {
+        artifact: Option<String>,
+        top: usize,
+    },
+
+    // NavigationService
+    ResolvePath {
+        target: String,
+        current_context: Option<String>,
+    },
+    NearestExisting {
+        fqn: String,
+    },
+    GetCompletionCandidates {
+        prefix: String,
+        limit: usize,
+    },
+
+    // EngineLifecycle
+    PendingResume,
+    ReloadConfig,
+    HostSettings,
+    WatchFlow,
+    RuntimeMetrics,
+    EpochProgress {
+        epoch: u64,
+    },
+    IndexWarnings,
+    RecordAudit {
+        entry: AuditEntry,
+    },
+    AuditLog,
+
+    // Semantic services
+    ResolveSymbolAt {
+        ctx: PositionContext,
+    },
+    ResolveAt {
+        path: PathBuf,
+        line: u32,
+        col: u32,
+    },
+    ResolveBatch {
+        path: PathBuf,
+        positions: Vec<(u32, u32)>,
+    },
+    ExplainResolution {
+        ctx: PositionContext,
+    },
+    FindDefinitions {
+        query: SymbolQuery,
+    },
+    FindTypeDefinitions {
+        query: SymbolQuery,
+    },
+    FindImplementations {
+        query: SymbolQuery,
+    },
+    FindHighlights {
+        ctx: PositionContext,
+    },
+    PrepareRename {
+        ctx: PositionContext,
+    },
+    RenameLocal {
+        ctx: PositionContext,
+        new_name: String,
+    },
+    FindReferenceSites {
+        query: ReferenceQuery,
+    },
+    FindIncomingCalls {
+        fqn: String,
+    },
+    FindOutgoingCalls {
+        fqn: String,
+    },
+    GetSymbolInfo {
+        fqn: String,
+    },
+    GetSignatureHelp {
+        ctx: PositionContext,
+    },
+    GetDocumentSymbols {
+        uri: String,
+    },
+    GetFoldingRanges {
+        uri: String,
+        content: Option<String>,
+    },
+    GetSelectionRanges {
+        ctx: PositionContext,
+    },
+    GetLanguageForDocument {
+        uri: String,
+    },
+    ReadSource {
+        path: PathBuf,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Request {
+    pub(crate) jsonrpc: String,
+    pub(crate) id: u64,
+    #[serde(flatten)]
+    pub(crate) call: Call,
+}
+
+impl Request {
+    pub(crate) fn new(id: u64, call: Call) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            call,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Response {
+    pub(crate) jsonrpc: String,
+    /// `None` when the request could not be read
+    pub(crate) id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) result: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<ErrorObject>,
+}
+
+impl Response {
+    pub(crate) fn new(id: Option<u64>, outcome: Result<serde_json::Value, ErrorObject>) -> Self {
+        let (result, error) = match outcome {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            result,
+            error,
+        }
+    }
+
+    /// The outcome of the call, as the engine returned it
+    pub(crate) fn outcome(self) -> Result<serde_json::Value, ApiError> {
+        match self.error {
+            Some(error) => Err(error.into()),
+            None => Ok(self.result.unwrap_or(serde_json::Value::Null)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ErrorObject {
+    pub(crate) code: i64,
+    pub(crate) message: String,
+}
+
+impl ErrorObject {
+    pub(crate) fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<ApiError> for ErrorObject {
+    fn from(error: ApiError) -> Self {
+        match error {
+            ApiError::NotFound(message) => Self::new(code::NOT_FOUND, message),
+            ApiError::InvalidArgument(message) => Self::new(code::INVALID_PARAMS, message),
+            ApiError::Internal(message) => Self::new(code::INTERNAL_ERROR, message),
+            ApiError::Cancelled => Self::new(code::CANCELLED, "cancelled"),
+            unsupported @ ApiError::UnsupportedCapability { .. } => {
+                Self::new(code::UNSUPPORTED, unsupported.to_string())
+            }
+        }
+    }
+}
+
+impl From<ErrorObject> for ApiError {
+    fn from(error: ErrorObject) -> Self {
+        match error.code {
+            code::NOT_FOUND => ApiError::NotFound(error.message),
+            code::INVALID_PARAMS => ApiError::InvalidArgument(error.message),
+            code::CANCELLED => ApiError::Cancelled,
+            _ => ApiError::Internal(error.message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls_are_json_rpc_requests() {
+        let request = Request::new(
+            7,
+            Call::GetNodeDisplay {
+                fqn: "a.B".to_string(),
+            },
+        );
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 7,
+                "method": "get_node_display",
+                "params": {"fqn": "a.B"}
+            })
+        );
+
+        let read: Request =
+            serde_json::from_str(r#"{"jsonrpc": "2.0", "id": 8, "method": "get_stats"}"#).unwrap();
+        assert_eq!(read.id, 8);
+        assert!(matches!(read.call, Call::GetStats));
+    }
+
+    #[test]
+    fn engine_errors_survive_the_round_trip() {
+        for error in [
+            ApiError::NotFound("a.B".to_string()),
+            ApiError::InvalidArgument("limit".to_string()),
+            ApiError::Internal("boom".to_string()),
+            ApiError::Cancelled,
+        ] {
+            let expected = error.to_string();
+            let response = Response::new(Some(1), Err(error.into()));
+            let text = serde_json::to_string(&response).unwrap();
+            let read: Response = serde_json::from_str(&text).unwrap();
+            assert_eq!(read.outcome().unwrap_err().to_string(), expected);
+        }
+    }
+}
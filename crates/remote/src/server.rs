@@ -0,0 +1,247 @@
+use crate::protocol::{Call, ErrorObject, RPC_PATH, Request, Response, code};
+use axum::{
+    Router,
+    extract::State,
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    http::{HeaderMap, StatusCode, header},
+    response::IntoResponse,
+    routing::get,
+};
+use futures::{sink::SinkExt, stream::StreamExt};
+use naviscope_api::{ApiError, ApiResult, NaviscopeEngine};
+use naviscope_mcp::http::is_authorized;
+use naviscope_mcp::source::read_indexed;
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+#[derive(Clone)]
+struct ServerState {
+    engine: Arc<dyn NaviscopeEngine>,
+    token: Arc<str>,
+}
+
+/// Serve `engine` on `listener` until `cancel_token` is cancelled. Clients
+/// must present `Authorization: Bearer <token>`.
+pub async fn serve(
+    engine: Arc<dyn NaviscopeEngine>,
+    listener: TcpListener,
+    token: String,
+    cancel_token: CancellationToken,
+) -> std::io::Result<()> {
+    let app = Router::new()
+        .route(RPC_PATH, get(rpc_ws_handler))
+        .route("/healthz", get(healthz))
+        .route("/metrics", get(metrics))
+        .with_state(ServerState {
+            engine,
+            token: token.into(),
+        });
+
+    let addr = listener.local_addr()?;
+    if addr.ip().is_loopback() {
+        info!("Index server listening on {}", addr);
+    } else {
+        warn!(
+            "Index server listening on {}, reachable from other hosts",
+            addr
+        );
+    }
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            cancel_token.cancelled().await;
+        })
+        .await
+}
+
+async fn healthz() -> &'static str {
+    "ok"
+}
+
+/// Runtime metrics of the engine, for Prometheus to scrape
+async fn metrics(headers: HeaderMap, State(state): State<ServerState>) -> axum::response::Response {
+    if !is_authorized(&headers, &state.token) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    match state.engine.runtime_metrics().await {
+        Ok(metrics) => (
+            [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+            metrics.to_prometheus(),
+        )
+            .into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+async fn rpc_ws_handler(
+    ws: WebSocketUpgrade,
+    headers: HeaderMap,
+    State(state): State<ServerState>,
+) -> axum::response::Response {
+    if !is_authorized(&headers, &state.token) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    ws.on_upgrade(move |socket| handle_socket(socket, state.engine))
+}
+
+async fn handle_socket(socket: WebSocket, engine: Arc<dyn NaviscopeEngine>) {
+    let (mut ws_sink, mut ws_stream) = socket.split();
+    let (responses, mut outgoing) = mpsc::unbounded_channel::<Response>();
+
+    let mut writer = tokio::spawn(async move {
+        while let Some(response) = outgoing.recv().await {
+            let Ok(text) = serde_json::to_string(&response) else {
+                continue;
+            };
+            if ws_sink.send(Message::Text(text.into())).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    // Every request runs on its own, so that one waiting for a commit does
+    // not hold back the others
+    let mut reader = tokio::spawn(async move {
+        while let Some(Ok(msg)) = ws_stream.next().await {
+            let text = match msg {
+                Message::Text(text) => text.to_string(),
+                Message::Binary(data) => String::from_utf8_lossy(&data).into_owned(),
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            let engine = engine.clone();
+            let responses = responses.clone();
+            tokio::spawn(async move {
+                let _ = responses.send(handle_request(engine.as_ref(), &text).await);
+            });
+        }
+    });
+
+    tokio::select! {
+        _ = (&mut reader) => { writer.abort(); },
+        _ = (&mut writer) => { reader.abort(); },
+    }
+}
+
+async fn handle_request(engine: &dyn NaviscopeEngine, text: &str) -> Response {
+    let value: serde_json::Value = match serde_json::from_str(text) {
+        Ok(value) => value,
+        Err(e) => {
+            let error = ErrorObject::new(code::PARSE_ERROR, e.to_string());
+            return Response::new(None, Err(error));
+        }
+    };
+    let id = value.get("id").and_then(|id| id.as_u64());
+    let request: Request = match serde_json::from_value(value) {
+        Ok(request) => request,
+        Err(e) => {
+            let error = ErrorObject::new(code::INVALID_PARAMS, e.to_string());
+            return Response::new(id, Err(error));
+        }
+    };
+    debug!("Remote call {}: {:?}", request.id, request.call);
+    Response::new(Some(request.id), dispatch(engine, request.call).await)
+}
+
+fn reply<T: Serialize>(result: ApiResult<T>) -> Result<serde_json::Value, ErrorObject> {
+    let value = result.map_err(ErrorObject::from)?;
+    serde_json::to_value(value).map_err(|e| ErrorObject::new(code::INTERNAL_ERROR, e.to_string()))
+}
+
+async fn dispatch(
+    engine: &dyn NaviscopeEngine,
+    call: Call,
+) -> Result<serde_json::Value, ErrorObject> {
+    match call {
+        Call::Query { query } => reply(engine.query(&query).await),
+        Call::GetStats => reply(engine.get_stats().await),
+        Call::ValidateQuery { query } => reply(engine.validate_query(&query).await),
+        Call::GraphEpoch => reply(engine.graph_epoch().await),
+        Call::WaitForCommit { epoch } => reply(engine.wait_for_commit(epoch).await),
+        Call::GetNodeDisplay { fqn } => reply(engine.get_node_display(&fqn).await),
+        Call::SafeDelete { fqn } => reply(engine.safe_delete(&fqn).await),
+        Call::References { fqn, type_usages } => reply(engine.references(&fqn, type_usages).await),
+        Call::Notes { fqn } => reply(engine.notes(&fqn).await),
+        Call::Annotate { fqn, text, tags } => reply(engine.annotate(&fqn, &text, &tags).await),
+        Call::RemoveNote { fqn, id } => reply(engine.remove_note(&fqn, id).await),
+        Call::SaveSnapshot { name } => reply(engine.save_snapshot(bare_reference(&name)?).await),
+        Call::Diff { reference } => reply(engine.diff(bare_reference(&reference)?).await),
+        Call::IndexRevision { rev } => reply(engine.index_revision(&rev).await),
+        Call::DiffBetween { old, new } => {
+            let (old, new) = (bare_reference(&old)?, bare_reference(&new)?);
+            reply(engine.diff_between(old, new).await)
+        }
+        Call::ExternalApiUsage { artifact, top } => {
+            reply(engine.external_api_usage(artifact.as_deref(), top).await)
+        }
+
+        Call::ResolvePath {
+            target,
+            current_context,
+        } => reply(
+            engine
+                .resolve_path(&target, current_context.as_deref())
+                .await,
+        ),
+        Call::NearestExisting { fqn } => reply(engine.nearest_existing(&fqn).await),
+        Call::GetCompletionCandidates { prefix, limit } => {
+            reply(engine.get_completion_candidates(&prefix, limit).await)
+        }
+
+        Call::PendingResume => reply(engine.pending_resume().await),
+        Call::ReloadConfig => reply(engine.reload_config().await),
+        Call::HostSettings => reply(engine.host_settings().await),
+        Call::WatchFlow => reply(engine.watch_flow().await),
+        Call::RuntimeMetrics => reply(engine.runtime_metrics().await),
+        Call::EpochProgress { epoch } => reply(engine.epoch_progress(epoch).await),
+        Call::IndexWarnings => reply(engine.index_warnings().await),
+        Call::RecordAudit { entry } => reply(engine.record_audit(entry).await),
+        Call::AuditLog => reply(engine.audit_log().await),
+
+        Call::ResolveSymbolAt { ctx } => reply(engine.resolve_symbol_at(&ctx).await),
+        Call::ResolveAt { path, line, col } => reply(engine.resolve_at(&path, line, col).await),
+        Call::ResolveBatch { path, positions } => {
+            reply(engine.resolve_batch(&path, &positions).await)
+        }
+        Call::ExplainResolution { ctx } => reply(engine.explain_resolution(&ctx).await),
+        Call::FindDefinitions { query } => reply(engine.find_definitions(&query).await),
+        Call::FindTypeDefinitions { query } => reply(engine.find_type_definitions(&query).await),
+        Call::FindImplementations { query } => reply(engine.find_implementations(&query).await),
+        Call::FindHighlights { ctx } => reply(engine.find_highlights(&ctx).await),
+        Call::PrepareRename { ctx } => reply(engine.prepare_rename(&ctx).await),
+        Call::RenameLocal { ctx, new_name } => reply(engine.rename_local(&ctx, &new_name).await),
+        Call::FindReferenceSites { query } => reply(engine.find_reference_sites(&query).await),
+        Call::FindIncomingCalls { fqn } => reply(engine.find_incoming_calls(&fqn).await),
+        Call::FindOutgoingCalls { fqn } => reply(engine.find_outgoing_calls(&fqn).await),
+        Call::GetSymbolInfo { fqn } => reply(engine.get_symbol_info(&fqn).await),
+        Call::GetSignatureHelp { ctx } => reply(engine.get_signature_help(&ctx).await),
+        Call::GetDocumentSymbols { uri } => reply(engine.get_document_symbols(&uri).await),
+        Call::GetFoldingRanges { uri, content } => {
+            reply(engine.get_folding_ranges(&uri, content).await)
+        }
+        Call::GetSelectionRanges { ctx } => reply(engine.get_selection_ranges(&ctx).await),
+        Call::GetLanguageForDocument { uri } => reply(engine.get_language_for_document(&uri).await),
+        Call::ReadSource { path } => reply(
+            read_indexed(engine, &path)
+                .await
+                .map(|content| content.to_string()),
+        ),
+    }
+}
+
+/// `reference` if it names a snapshot or git revision rather than a file.
+/// The engine takes a reference with a separator or a `.bin` extension as a
+/// snapshot file path, which a client must not pick on the server.
+fn bare_reference(reference: &str) -> ApiResult<&str> {
+    if reference.contains(['/', '\\']) || reference.ends_with(".bin") {
+        return Err(ApiError::InvalidArgument(format!(
+            "snapshot paths are not accepted from clients: {}",
+            reference
+        )));
+    }
+    Ok(reference)
+}
@@ -3,36 +3,99 @@ use naviscope_api::{ApiError, ApiResult};
 use std::path::PathBuf;
 use std::sync::Arc;
 
+pub use naviscope_core::config::Profile;
+use naviscope_core::config::ProjectConfig;
+use naviscope_core::runtime::NaviscopeEngineBuilder;
+
+/// Comparison of the Java index with compiled classes, for `audit-java`
+pub use naviscope_java::audit as java_audit;
+
 /// Bootstraps a full-featured Naviscope engine with all available plugins.
 ///
 /// This function acts as the central factory for the Naviscope runtime,
-/// assembling the core engine with language-specific plugins like Java and Gradle.
+/// assembling the core engine with language-specific plugins like Java, Groovy, Gradle and SQL.
 pub fn build_default_engine(path: PathBuf) -> Arc<dyn NaviscopeEngine> {
-    let mut builder = naviscope_core::runtime::NaviscopeEngine::builder(path);
+    build_engine(path, Profile::Cli)
+}
+
+/// Like `build_default_engine`, tuned for the component that hosts the engine.
+pub fn build_engine(path: PathBuf, profile: Profile) -> Arc<dyn NaviscopeEngine> {
+    let engine = engine_builder(path, profile).build();
+
+    // Wrap in the standard EngineHandle which implements all API traits
+    Arc::new(naviscope_core::facade::EngineHandle::from_engine(Arc::new(
+        engine,
+    )))
+}
+
+/// Opens the prebuilt index at `index_path` read-only, with all available
+/// plugins. Nothing is rebuilt, watched or written, so parallel CI jobs can
+/// query the same index artifact; `path` is the project it was built from.
+pub async fn open_readonly(
+    path: PathBuf,
+    index_path: PathBuf,
+) -> ApiResult<Arc<dyn NaviscopeEngine>> {
+    let handle = naviscope_core::facade::EngineHandle::open_readonly_with(
+        engine_builder(path, Profile::Cli),
+        index_path,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Arc::new(handle))
+}
+
+/// Variable holding the token clients present to an index server
+pub const REMOTE_TOKEN_ENV: &str = "NAVISCOPE_REMOTE_TOKEN";
+
+/// Connects to the index server at `addr`, `host:port` or a `ws://` URL,
+/// started by `naviscope serve-index`. The token is taken from
+/// `NAVISCOPE_REMOTE_TOKEN`; the connection is opened on first use.
+pub fn connect_remote(addr: &str) -> Arc<dyn NaviscopeEngine> {
+    let token = std::env::var(REMOTE_TOKEN_ENV)
+        .ok()
+        .filter(|token| !token.is_empty());
+    Arc::new(naviscope_remote::RemoteEngine::new(
+        addr,
+        token,
+        get_cache_manager(),
+    ))
+}
+
+fn engine_builder(path: PathBuf, profile: Profile) -> NaviscopeEngineBuilder {
+    let config = ProjectConfig::load(&path).unwrap_or_else(|e| {
+        tracing::warn!("Failed to load project config: {}", e);
+        ProjectConfig::default()
+    });
+    // Spring and JPA are modeled by the Java plugin and listed with the plugins
+    let spring = config.plugins.is_enabled("spring");
+    let jpa = config.plugins.is_enabled("jpa");
+    let mut builder = naviscope_core::runtime::NaviscopeEngine::builder(path)
+        .with_profile(profile)
+        .with_config(config);
 
     // Register Build Tool Caps
     builder = builder.with_build_caps(naviscope_gradle::gradle_caps());
 
     // Register Language Caps
-    builder = match naviscope_java::java_caps() {
-        Ok(caps) => builder.with_language_caps(caps),
+    builder = match naviscope_java::JavaPlugin::new() {
+        Ok(plugin) => builder.with_language_caps(naviscope_java::java_caps_with(
+            plugin.with_spring(spring).with_jpa(jpa),
+        )),
         Err(e) => {
             tracing::error!("Failed to load Java plugin: {}", e);
             builder
         }
     };
-
-    let engine = builder.build();
-
-    // Wrap in the standard EngineHandle which implements all API traits
-    Arc::new(naviscope_core::facade::EngineHandle::from_engine(Arc::new(
-        engine,
-    )))
+    builder = builder.with_language_caps(naviscope_groovy::groovy_caps());
+    builder.with_language_caps(naviscope_sql::sql_caps())
 }
 
 /// Initializes the logging system for a specific component.
 /// This delegates to the core logging module.
-pub fn init_logging(component: &str, to_stderr: bool) -> Option<impl Drop> {
+pub fn init_logging(
+    component: &str,
+    to_stderr: bool,
+) -> Option<naviscope_core::logging::LoggingGuard> {
     Some(naviscope_core::logging::init_logging(component, to_stderr))
 }
 